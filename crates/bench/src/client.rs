@@ -0,0 +1,305 @@
+//! Connections sending requests and reading responses.
+use std::collections::BTreeMap;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::{Buf, Bytes, BytesMut};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const MAX_HEADERS: usize = 64;
+
+/// Shared settings of the connections of a load test.
+pub(crate) struct Plan {
+    pub(crate) addr: SocketAddr,
+    pub(crate) request: Bytes,
+    pub(crate) pipeline: usize,
+    pub(crate) record_from: Instant,
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) budget: Option<AtomicUsize>,
+}
+
+impl Plan {
+    /// Returns how many requests should be sent in the next batch, `0` means the test is done.
+    fn next_batch(&self, now: Instant) -> usize {
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            return 0;
+        }
+        let Some(budget) = &self.budget else {
+            return self.pipeline;
+        };
+        // Requests sent in warmup are not counted.
+        if now < self.record_from {
+            return self.pipeline;
+        }
+        let mut taken = 0;
+        budget
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                taken = remaining.min(self.pipeline);
+                Some(remaining - taken)
+            })
+            .ok();
+        taken
+    }
+}
+
+/// Results recorded by a connection.
+#[derive(Default)]
+pub(crate) struct Stats {
+    pub(crate) latencies: Vec<Duration>,
+    pub(crate) statuses: BTreeMap<u16, u64>,
+    pub(crate) errors: u64,
+    pub(crate) bytes: u64,
+}
+
+/// Sends requests on one connection until the plan is done, the connection is reopened after errors.
+pub(crate) async fn drive(plan: Arc<Plan>) -> Stats {
+    let mut stats = Stats::default();
+    let mut conn: Option<Connection> = None;
+    loop {
+        let started = Instant::now();
+        let batch = plan.next_batch(started);
+        if batch == 0 {
+            break;
+        }
+        let record = started >= plan.record_from;
+        if conn.is_none() {
+            match Connection::connect(plan.addr).await {
+                Ok(new_conn) => conn = Some(new_conn),
+                Err(e) => {
+                    tracing::debug!(error = ?e, "connect failed");
+                    if record {
+                        stats.errors += batch as u64;
+                    }
+                    continue;
+                }
+            }
+        }
+        let current = conn.as_mut().expect("connection should be opened");
+        if let Err(e) = current.send(&plan.request, batch).await {
+            tracing::debug!(error = ?e, "send requests failed");
+            if record {
+                stats.errors += batch as u64;
+            }
+            conn = None;
+            continue;
+        }
+        let mut keep_alive = true;
+        for index in 0..batch {
+            match current.read_response().await {
+                Ok(res) => {
+                    keep_alive &= res.keep_alive;
+                    if record {
+                        stats.latencies.push(started.elapsed());
+                        *stats.statuses.entry(res.status).or_default() += 1;
+                        stats.bytes += res.body_size;
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!(error = ?e, "read response failed");
+                    if record {
+                        stats.errors += (batch - index) as u64;
+                    }
+                    keep_alive = false;
+                    break;
+                }
+            }
+        }
+        if !keep_alive {
+            conn = None;
+        }
+    }
+    stats
+}
+
+/// Status and body size of a response.
+struct ResponseInfo {
+    status: u16,
+    body_size: u64,
+    keep_alive: bool,
+}
+
+enum BodyKind {
+    Empty,
+    Length(usize),
+    Chunked,
+    UntilClose,
+}
+
+struct Head {
+    len: usize,
+    status: u16,
+    body: BodyKind,
+    keep_alive: bool,
+}
+
+struct Connection {
+    stream: TcpStream,
+    buf: BytesMut,
+}
+
+impl Connection {
+    async fn connect(addr: SocketAddr) -> IoResult<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            buf: BytesMut::with_capacity(8 * 1024),
+        })
+    }
+
+    async fn send(&mut self, request: &[u8], count: usize) -> IoResult<()> {
+        if count == 1 {
+            self.stream.write_all(request).await
+        } else {
+            self.stream.write_all(&request.repeat(count)).await
+        }
+    }
+
+    async fn read_response(&mut self) -> IoResult<ResponseInfo> {
+        let head = loop {
+            if let Some(head) = parse_head(&self.buf)? {
+                break head;
+            }
+            self.fill().await?;
+        };
+        self.buf.advance(head.len);
+        let mut keep_alive = head.keep_alive;
+        let body_size = match head.body {
+            BodyKind::Empty => 0,
+            BodyKind::Length(len) => {
+                while self.buf.len() < len {
+                    self.fill().await?;
+                }
+                self.buf.advance(len);
+                len as u64
+            }
+            BodyKind::Chunked => self.read_chunked().await?,
+            BodyKind::UntilClose => {
+                while self.stream.read_buf(&mut self.buf).await? > 0 {}
+                keep_alive = false;
+                let len = self.buf.len();
+                self.buf.clear();
+                len as u64
+            }
+        };
+        Ok(ResponseInfo {
+            status: head.status,
+            body_size,
+            keep_alive,
+        })
+    }
+
+    async fn read_chunked(&mut self) -> IoResult<u64> {
+        let mut total = 0;
+        loop {
+            let size = self
+                .read_line(|line| {
+                    std::str::from_utf8(line).ok().and_then(|line| {
+                        usize::from_str_radix(line.split(';').next().unwrap_or_default().trim(), 16).ok()
+                    })
+                })
+                .await?
+                .ok_or_else(|| IoError::new(ErrorKind::InvalidData, "invalid chunk size"))?;
+            if size == 0 {
+                // Skip trailers.
+                while !self.read_line(|line| line.is_empty()).await? {}
+                return Ok(total);
+            }
+            while self.buf.len() < size + 2 {
+                self.fill().await?;
+            }
+            self.buf.advance(size + 2);
+            total += size as u64;
+        }
+    }
+
+    /// Reads a line ending with CRLF and parses it in place.
+    async fn read_line<T>(&mut self, parse: impl Fn(&[u8]) -> T) -> IoResult<T> {
+        loop {
+            if let Some(pos) = self.buf.windows(2).position(|window| window == b"\r\n") {
+                let value = parse(&self.buf[..pos]);
+                self.buf.advance(pos + 2);
+                return Ok(value);
+            }
+            self.fill().await?;
+        }
+    }
+
+    async fn fill(&mut self) -> IoResult<()> {
+        if self.stream.read_buf(&mut self.buf).await? == 0 {
+            return Err(IoError::new(ErrorKind::UnexpectedEof, "connection closed"));
+        }
+        Ok(())
+    }
+}
+
+fn parse_head(buf: &[u8]) -> IoResult<Option<Head>> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut res = httparse::Response::new(&mut headers);
+    let len = match res.parse(buf).map_err(|e| IoError::new(ErrorKind::InvalidData, e))? {
+        httparse::Status::Complete(len) => len,
+        httparse::Status::Partial => return Ok(None),
+    };
+    let status = res.code.unwrap_or_default();
+    let mut keep_alive = res.version == Some(1);
+    let mut length = None;
+    let mut chunked = false;
+    for header in res.headers.iter() {
+        let value = std::str::from_utf8(header.value).unwrap_or_default().trim();
+        if header.name.eq_ignore_ascii_case("content-length") {
+            length = value.parse::<usize>().ok();
+        } else if header.name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.to_ascii_lowercase().contains("chunked");
+        } else if header.name.eq_ignore_ascii_case("connection") {
+            if value.eq_ignore_ascii_case("close") {
+                keep_alive = false;
+            } else if value.eq_ignore_ascii_case("keep-alive") {
+                keep_alive = true;
+            }
+        }
+    }
+    let body = if (100..200).contains(&status) || status == 204 || status == 304 {
+        BodyKind::Empty
+    } else if chunked {
+        BodyKind::Chunked
+    } else if let Some(length) = length {
+        BodyKind::Length(length)
+    } else {
+        BodyKind::UntilClose
+    };
+    Ok(Some(Head {
+        len,
+        status,
+        body,
+        keep_alive,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_head() {
+        assert!(parse_head(b"HTTP/1.1 200 OK\r\ncontent-le").unwrap().is_none());
+
+        let head = parse_head(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nhello")
+            .unwrap()
+            .unwrap();
+        assert_eq!(head.len, 38);
+        assert!(matches!(head.body, BodyKind::Length(5)) && head.keep_alive);
+
+        let head = parse_head(b"HTTP/1.1 404 Not Found\r\ntransfer-encoding: chunked\r\nconnection: close\r\n\r\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(head.status, 404);
+        assert!(matches!(head.body, BodyKind::Chunked) && !head.keep_alive);
+
+        let head = parse_head(b"HTTP/1.0 204 No Content\r\n\r\n").unwrap().unwrap();
+        assert!(matches!(head.body, BodyKind::Empty) && !head.keep_alive);
+    }
+}
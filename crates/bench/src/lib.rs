@@ -0,0 +1,303 @@
+//! Load generator for benchmarking Salvo web framework.
+//!
+//! [`LoadTest`] serves a router on a local listener, then opens a number of connections to it and sends requests
+//! with HTTP/1.1, optionally pipelined. When the test is done, the returned [`Report`] gives the throughput, the
+//! status codes and the latency percentiles, so performance regressions of the framework and of middlewares can be
+//! tracked in tests or benches.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_bench::LoadTest;
+//! use salvo_core::prelude::*;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::with_path("hello").get(hello);
+//!     let report = LoadTest::new("/hello")
+//!         .connections(32)
+//!         .pipeline(4)
+//!         .duration(Duration::from_secs(10))
+//!         .run(router)
+//!         .await
+//!         .unwrap();
+//!     println!("{report}");
+//!     assert!(report.percentile(99.0) < Duration::from_millis(10));
+//! }
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use std::io::{Error as IoError, Result as IoResult};
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::future::join_all;
+use salvo_core::conn::{Listener, TcpListener};
+use salvo_core::http::header::{HeaderMap, HeaderName, HeaderValue};
+use salvo_core::http::Method;
+use salvo_core::{Server, Service};
+
+mod client;
+mod report;
+
+use client::Plan;
+pub use report::Report;
+
+/// A load test driving requests to a service.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug)]
+pub struct LoadTest {
+    method: Method,
+    path: String,
+    headers: HeaderMap,
+    body: Bytes,
+    connections: usize,
+    pipeline: usize,
+    duration: Duration,
+    requests: Option<usize>,
+    warmup: Duration,
+}
+
+impl LoadTest {
+    /// Create a new `LoadTest` sending `GET` requests to the path.
+    ///
+    /// By default, 8 connections send requests one by one for 5 seconds.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            method: Method::GET,
+            path: path.into(),
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+            connections: 8,
+            pipeline: 1,
+            duration: Duration::from_secs(5),
+            requests: None,
+            warmup: Duration::ZERO,
+        }
+    }
+
+    /// Set the method of requests.
+    #[inline]
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Add a header to requests.
+    #[inline]
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Set the body of requests.
+    #[inline]
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Set the body of requests to a payload of `size` bytes.
+    #[inline]
+    pub fn payload_size(self, size: usize) -> Self {
+        self.body(vec![b'x'; size])
+    }
+
+    /// Set the number of concurrent connections.
+    #[inline]
+    pub fn connections(mut self, connections: usize) -> Self {
+        self.connections = connections.max(1);
+        self
+    }
+
+    /// Set the number of requests written to a connection before reading the responses.
+    ///
+    /// The default is 1, which disables pipelining.
+    #[inline]
+    pub fn pipeline(mut self, pipeline: usize) -> Self {
+        self.pipeline = pipeline.max(1);
+        self
+    }
+
+    /// Set how long requests are sent after the warmup.
+    #[inline]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Stop after `requests` requests are sent after the warmup, instead of after the duration.
+    #[inline]
+    pub fn requests(mut self, requests: usize) -> Self {
+        self.requests = Some(requests);
+        self
+    }
+
+    /// Set how long requests are sent before they are recorded, so lazily initialized state is not measured.
+    #[inline]
+    pub fn warmup(mut self, warmup: Duration) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Serve the service on a local listener and run the test against it.
+    pub async fn run<S>(self, service: S) -> IoResult<Report>
+    where
+        S: Into<Service> + Send + 'static,
+    {
+        let acceptor = TcpListener::new("127.0.0.1:0")
+            .try_bind()
+            .await
+            .map_err(IoError::other)?;
+        let addr = acceptor.local_addr()?;
+        let server = tokio::spawn(Server::new(acceptor).serve(service));
+        let report = self.run_against(addr).await;
+        server.abort();
+        report
+    }
+
+    /// Run the test against a server which is already listening on the address.
+    pub async fn run_against(self, addr: SocketAddr) -> IoResult<Report> {
+        let start = Instant::now();
+        let record_from = start + self.warmup;
+        let plan = Arc::new(Plan {
+            addr,
+            request: self.encode(addr),
+            pipeline: self.pipeline,
+            record_from,
+            deadline: self.requests.is_none().then(|| record_from + self.duration),
+            budget: self.requests.map(AtomicUsize::new),
+        });
+        let tasks = (0..self.connections).map(|_| tokio::spawn(client::drive(plan.clone())));
+        let mut report = Report::default();
+        for stats in join_all(tasks).await {
+            report.merge(stats.map_err(IoError::other)?);
+        }
+        report.finish(Instant::now().saturating_duration_since(record_from));
+        Ok(report)
+    }
+
+    fn encode(&self, addr: SocketAddr) -> Bytes {
+        let mut request = BytesMut::new();
+        request.put_slice(format!("{} {} HTTP/1.1\r\nhost: {addr}\r\n", self.method, self.path).as_bytes());
+        for (name, value) in &self.headers {
+            request.put_slice(name.as_str().as_bytes());
+            request.put_slice(b": ");
+            request.put_slice(value.as_bytes());
+            request.put_slice(b"\r\n");
+        }
+        if !self.body.is_empty() || !matches!(self.method, Method::GET | Method::HEAD) {
+            request.put_slice(format!("content-length: {}\r\n", self.body.len()).as_bytes());
+        }
+        request.put_slice(b"\r\n");
+        request.put_slice(&self.body);
+        request.freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use salvo_core::http::header::CONTENT_TYPE;
+    use salvo_core::http::ResBody;
+    use salvo_core::prelude::*;
+
+    use super::*;
+
+    #[handler]
+    async fn hello() -> &'static str {
+        "Hello World"
+    }
+    #[handler]
+    async fn echo(req: &mut Request) -> String {
+        req.payload()
+            .await
+            .map(|payload| payload.len())
+            .unwrap_or_default()
+            .to_string()
+    }
+    #[handler]
+    async fn chunked(res: &mut Response) {
+        let chunks = vec![Ok::<_, std::io::Error>("Hello"), Ok(" "), Ok("World")];
+        res.body(ResBody::stream(stream::iter(chunks)));
+    }
+
+    fn router() -> Router {
+        Router::new()
+            .push(Router::with_path("hello").get(hello))
+            .push(Router::with_path("echo").post(echo))
+            .push(Router::with_path("chunked").get(chunked))
+    }
+
+    #[tokio::test]
+    async fn test_load_test() {
+        let report = LoadTest::new("/hello")
+            .connections(4)
+            .requests(200)
+            .run(router())
+            .await
+            .unwrap();
+        assert_eq!(report.requests, 200);
+        assert_eq!(report.errors, 0);
+        assert_eq!(report.statuses.get(&200), Some(&200));
+        assert_eq!(report.bytes, 200 * "Hello World".len() as u64);
+        assert!(report.percentile(50.0) <= report.percentile(99.0));
+        assert!(report.percentile(99.0) <= report.max());
+        assert!(report.throughput() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_load_test_pipeline() {
+        let report = LoadTest::new("/chunked")
+            .connections(2)
+            .pipeline(8)
+            .requests(100)
+            .run(router())
+            .await
+            .unwrap();
+        assert_eq!(report.requests, 100);
+        assert_eq!(report.errors, 0);
+        assert_eq!(report.bytes, 100 * "Hello World".len() as u64);
+
+        let report = LoadTest::new("/echo")
+            .method(Method::POST)
+            .header(CONTENT_TYPE, HeaderValue::from_static("text/plain"))
+            .payload_size(4096)
+            .pipeline(4)
+            .requests(40)
+            .run(router())
+            .await
+            .unwrap();
+        assert_eq!(report.statuses.get(&200), Some(&40));
+        assert_eq!(report.bytes, 40 * "4096".len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_load_test_duration() {
+        let report = LoadTest::new("/missing")
+            .connections(2)
+            .warmup(Duration::from_millis(50))
+            .duration(Duration::from_millis(200))
+            .run(router())
+            .await
+            .unwrap();
+        assert!(report.requests > 0);
+        assert_eq!(report.statuses.get(&404), Some(&report.requests));
+        assert!(report.elapsed >= Duration::from_millis(200));
+    }
+}
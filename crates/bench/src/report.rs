@@ -0,0 +1,145 @@
+//! Results of a load test.
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+use crate::client::Stats;
+
+/// Results of a [`LoadTest`](crate::LoadTest).
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Report {
+    /// Number of responses received.
+    pub requests: u64,
+    /// Number of requests failed without a response, such as the connection is closed.
+    pub errors: u64,
+    /// Total size of the response bodies.
+    pub bytes: u64,
+    /// Time taken by the test, excluding the warmup.
+    pub elapsed: Duration,
+    /// Number of responses of each status code.
+    pub statuses: BTreeMap<u16, u64>,
+    latencies: Vec<Duration>,
+}
+
+impl Report {
+    pub(crate) fn merge(&mut self, stats: Stats) {
+        self.requests += stats.latencies.len() as u64;
+        self.errors += stats.errors;
+        self.bytes += stats.bytes;
+        for (status, count) in stats.statuses {
+            *self.statuses.entry(status).or_default() += count;
+        }
+        self.latencies.extend(stats.latencies);
+    }
+
+    pub(crate) fn finish(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+        self.latencies.sort_unstable();
+    }
+
+    /// Latencies of all requests, sorted from the fastest.
+    #[inline]
+    pub fn latencies(&self) -> &[Duration] {
+        &self.latencies
+    }
+
+    /// Responses received per second.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.requests as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the latency at the percentile, such as `99.0` for p99.
+    ///
+    /// Returns zero if no response is received.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let len = self.latencies.len();
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * len as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, len) - 1]
+    }
+
+    /// Mean latency.
+    pub fn mean(&self) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32
+    }
+
+    /// Min latency.
+    #[inline]
+    pub fn min(&self) -> Duration {
+        self.latencies.first().copied().unwrap_or_default()
+    }
+
+    /// Max latency.
+    #[inline]
+    pub fn max(&self) -> Duration {
+        self.latencies.last().copied().unwrap_or_default()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} requests in {:.2?}, {:.1} req/s, {} errors, {} bytes",
+            self.requests,
+            self.elapsed,
+            self.throughput(),
+            self.errors,
+            self.bytes
+        )?;
+        writeln!(
+            f,
+            "latency: min {:.2?}, mean {:.2?}, p50 {:.2?}, p90 {:.2?}, p99 {:.2?}, p99.9 {:.2?}, max {:.2?}",
+            self.min(),
+            self.mean(),
+            self.percentile(50.0),
+            self.percentile(90.0),
+            self.percentile(99.0),
+            self.percentile(99.9),
+            self.max()
+        )?;
+        let statuses = self
+            .statuses
+            .iter()
+            .map(|(status, count)| format!("{status}: {count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "status: {statuses}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_percentile() {
+        let mut report = Report::default();
+        report.merge(Stats {
+            latencies: (1..=100).rev().map(Duration::from_millis).collect(),
+            statuses: [(200, 100)].into_iter().collect(),
+            ..Default::default()
+        });
+        report.finish(Duration::from_secs(2));
+        assert_eq!(report.requests, 100);
+        assert_eq!(report.throughput(), 50.0);
+        assert_eq!(report.min(), Duration::from_millis(1));
+        assert_eq!(report.percentile(50.0), Duration::from_millis(50));
+        assert_eq!(report.percentile(99.0), Duration::from_millis(99));
+        assert_eq!(report.percentile(100.0), Duration::from_millis(100));
+        assert_eq!(report.percentile(0.0), Duration::from_millis(1));
+        assert_eq!(report.mean(), Duration::from_micros(50500));
+        assert!(report.to_string().contains("status: 200: 100"));
+    }
+}
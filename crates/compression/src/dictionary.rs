@@ -0,0 +1,227 @@
+//! Compression dictionary transport.
+//!
+//! A response sent with a `Use-As-Dictionary` header is stored by browsers, which then advertise its SHA-256 hash in
+//! the `Available-Dictionary` header of later requests matching the pattern. If the dictionary is registered in the
+//! [`DictionaryStore`] of the [`Compression`](crate::Compression) middleware, the response is compressed against it
+//! with `dcb` (brotli) or `dcz` (zstd) encoding, so only the delta to the previous version is transferred.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_compression::{Compression, DictionaryStore};
+//! use salvo_core::prelude::*;
+//!
+//! struct Bundle {
+//!     store: DictionaryStore,
+//! }
+//! #[handler]
+//! impl Bundle {
+//!     async fn handle(&self, res: &mut Response) {
+//!         let content = std::fs::read("dist/app.js").unwrap();
+//!         // Later versions of the bundle are compressed against this one.
+//!         self.store.insert(content.clone()).use_as(res, "/js/app.*.js");
+//!         res.headers_mut().insert("content-type", "application/javascript".parse().unwrap());
+//!         res.write_body(content).ok();
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let store = DictionaryStore::new();
+//!     let router = Router::with_hoop(Compression::new().dictionaries(store.clone()))
+//!         .push(Router::with_path("js/<name>").get(Bundle { store }));
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, RwLock};
+
+use base64::engine::{general_purpose, Engine};
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+
+use salvo_core::http::header::{HeaderName, HeaderValue};
+use salvo_core::Response;
+
+use super::CompressionAlgo;
+
+/// The `Available-Dictionary` request header.
+pub const AVAILABLE_DICTIONARY: HeaderName = HeaderName::from_static("available-dictionary");
+/// The `Use-As-Dictionary` response header.
+pub const USE_AS_DICTIONARY: HeaderName = HeaderName::from_static("use-as-dictionary");
+
+/// Content encoding using a shared dictionary.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DictionaryEncoding {
+    /// Dictionary-compressed brotli, `dcb`.
+    #[cfg(feature = "brotli")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "brotli")))]
+    Brotli,
+    /// Dictionary-compressed zstd, `dcz`.
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    Zstd,
+}
+
+impl DictionaryEncoding {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            #[cfg(feature = "brotli")]
+            "dcb" => Some(Self::Brotli),
+            #[cfg(feature = "zstd")]
+            "dcz" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The algorithm whose level is used by this encoding.
+    pub(crate) fn algo(self) -> CompressionAlgo {
+        match self {
+            #[cfg(feature = "brotli")]
+            Self::Brotli => CompressionAlgo::Brotli,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => CompressionAlgo::Zstd,
+        }
+    }
+}
+
+impl From<DictionaryEncoding> for HeaderValue {
+    #[inline]
+    fn from(encoding: DictionaryEncoding) -> Self {
+        match encoding {
+            #[cfg(feature = "brotli")]
+            DictionaryEncoding::Brotli => HeaderValue::from_static("dcb"),
+            #[cfg(feature = "zstd")]
+            DictionaryEncoding::Zstd => HeaderValue::from_static("dcz"),
+        }
+    }
+}
+
+/// A dictionary which responses are compressed against.
+#[derive(Clone)]
+pub struct Dictionary {
+    data: Bytes,
+    hash: [u8; 32],
+}
+
+impl Debug for Dictionary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dictionary")
+            .field("len", &self.data.len())
+            .field("hash", &self.structured_hash())
+            .finish()
+    }
+}
+
+impl Dictionary {
+    /// Create a new `Dictionary` with its content.
+    pub fn new(data: impl Into<Bytes>) -> Self {
+        let data = data.into();
+        let hash = Sha256::digest(&data).into();
+        Self { data, hash }
+    }
+
+    /// Get the content of the dictionary.
+    #[inline]
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Get the SHA-256 hash of the content.
+    #[inline]
+    pub fn hash(&self) -> &[u8; 32] {
+        &self.hash
+    }
+
+    /// Get the hash as a structured field byte sequence, the format used in the `Available-Dictionary` header.
+    pub fn structured_hash(&self) -> String {
+        format!(":{}:", general_purpose::STANDARD.encode(self.hash))
+    }
+
+    /// Set the `Use-As-Dictionary` header of the response which serves this dictionary, so clients use it for
+    /// later requests whose path matches `match_pattern`, such as `/js/app.*.js`.
+    pub fn use_as(&self, res: &mut Response, match_pattern: &str) {
+        let value = format!("match=\"{}\"", match_pattern.replace('\\', "\\\\").replace('"', "\\\""));
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            res.headers_mut().insert(USE_AS_DICTIONARY, value);
+        } else {
+            tracing::error!(match_pattern, "invalid dictionary match pattern");
+        }
+    }
+}
+
+/// Registered dictionaries, keyed by their hashes.
+///
+/// It is cheap to clone, clones share the same dictionaries, so handlers can register dictionaries while serving.
+#[derive(Clone, Default, Debug)]
+pub struct DictionaryStore {
+    inner: Arc<RwLock<HashMap<[u8; 32], Dictionary>>>,
+}
+
+impl DictionaryStore {
+    /// Create a new empty `DictionaryStore`.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Register a dictionary with its content and returns it.
+    pub fn insert(&self, data: impl Into<Bytes>) -> Dictionary {
+        let dictionary = Dictionary::new(data);
+        self.inner
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(dictionary.hash, dictionary.clone());
+        dictionary
+    }
+
+    /// Remove the dictionary with the hash.
+    pub fn remove(&self, hash: &[u8; 32]) -> Option<Dictionary> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner()).remove(hash)
+    }
+
+    /// Get the dictionary with the hash.
+    pub fn get(&self, hash: &[u8; 32]) -> Option<Dictionary> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).get(hash).cloned()
+    }
+
+    /// Returns `true` if no dictionary is registered.
+    pub fn is_empty(&self) -> bool {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).is_empty()
+    }
+
+    /// Get the dictionary advertised by the value of an `Available-Dictionary` header.
+    pub(crate) fn find(&self, available: &str) -> Option<Dictionary> {
+        let encoded = available.trim().strip_prefix(':')?.strip_suffix(':')?;
+        let hash: [u8; 32] = general_purpose::STANDARD.decode(encoded).ok()?.try_into().ok()?;
+        self.get(&hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dictionary_store() {
+        let store = DictionaryStore::new();
+        let dictionary = store.insert("hello world");
+        assert_eq!(
+            dictionary.structured_hash(),
+            ":uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=:"
+        );
+        assert!(store.find(&dictionary.structured_hash()).is_some());
+        assert!(store.find("uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=").is_none());
+        assert!(store.find(":aGVsbG8=:").is_none());
+
+        let mut res = Response::new();
+        dictionary.use_as(&mut res, "/js/app.*.js");
+        assert_eq!(res.headers().get(USE_AS_DICTIONARY).unwrap(), "match=\"/js/app.*.js\"");
+
+        store.remove(dictionary.hash());
+        assert!(store.is_empty());
+    }
+}
@@ -1,6 +1,14 @@
 //! Compress the body of a response.
+#[cfg(all(feature = "brotli", feature = "dictionary"))]
+use std::io::{Error as IoError, ErrorKind};
 use std::io::{Result as IoResult, Write};
 
+#[cfg(all(feature = "brotli", feature = "dictionary"))]
+use brotli::enc::encode::{
+    BrotliEncoderDestroyInstance, BrotliEncoderOperation, BrotliEncoderParameter, BrotliEncoderStateStruct,
+};
+#[cfg(all(feature = "brotli", feature = "dictionary"))]
+use brotli::enc::StandardAlloc;
 #[cfg(feature = "brotli")]
 use brotli::CompressorWriter as BrotliEncoder;
 use bytes::{Bytes, BytesMut};
@@ -12,6 +20,15 @@ use flate2::write::ZlibEncoder;
 use zstd::stream::write::Encoder as ZstdEncoder;
 
 use super::{CompressionAlgo, CompressionLevel};
+#[cfg(feature = "dictionary")]
+use super::{Dictionary, DictionaryEncoding};
+
+/// Magic number of `dcb` encoding, followed by the hash of the dictionary.
+#[cfg(all(feature = "brotli", feature = "dictionary"))]
+const DCB_MAGIC: [u8; 4] = [0xff, 0x44, 0x43, 0x42];
+/// Magic number of `dcz` encoding, a zstd skippable frame with the hash of the dictionary as content.
+#[cfg(all(feature = "zstd", feature = "dictionary"))]
+const DCZ_MAGIC: [u8; 8] = [0x5e, 0x2a, 0x4d, 0x18, 0x20, 0x00, 0x00, 0x00];
 
 pub(super) struct Writer {
     buf: BytesMut,
@@ -91,6 +108,97 @@ impl CompressionLevel {
         };
         ZstdEncoder::new(Writer::new(), quality).expect("`ZstdEncoder::new` returned an error")
     }
+
+    #[cfg(all(feature = "zstd", feature = "dictionary"))]
+    fn into_zstd_with_dictionary(self, writer: Writer, dictionary: &[u8]) -> IoResult<ZstdEncoder<'static, Writer>> {
+        let quality = match self {
+            Self::Fastest => 1,
+            Self::Minsize => 21,
+            Self::Precise(quality) => quality.min(21) as i32,
+            Self::Default => 3,
+        };
+        ZstdEncoder::with_dictionary(writer, quality, dictionary)
+    }
+
+    #[cfg(all(feature = "brotli", feature = "dictionary"))]
+    fn into_brotli_with_dictionary(self, writer: Writer, dictionary: &[u8]) -> DictBrotliEncoder {
+        // Custom dictionaries are ignored by brotli with quality lower than 2.
+        let quality = match self {
+            Self::Fastest => 2,
+            Self::Minsize => 11,
+            Self::Precise(quality) => quality.clamp(2, 11),
+            Self::Default => 5,
+        };
+        DictBrotliEncoder::new(writer, quality, dictionary)
+    }
+}
+
+/// Brotli encoder with a custom dictionary, which is not supported by [`BrotliEncoder`].
+#[cfg(all(feature = "brotli", feature = "dictionary"))]
+pub(super) struct DictBrotliEncoder {
+    state: BrotliEncoderStateStruct<StandardAlloc>,
+    writer: Writer,
+    buf: Vec<u8>,
+}
+
+#[cfg(all(feature = "brotli", feature = "dictionary"))]
+impl DictBrotliEncoder {
+    fn new(writer: Writer, quality: u32, dictionary: &[u8]) -> Self {
+        // The window must be large enough to reference the whole dictionary.
+        let lgwin = (22..24).find(|lgwin| dictionary.len() + 16 <= 1 << lgwin).unwrap_or(24);
+        let mut state = BrotliEncoderStateStruct::new(StandardAlloc::default());
+        state.set_parameter(BrotliEncoderParameter::BROTLI_PARAM_QUALITY, quality);
+        state.set_parameter(BrotliEncoderParameter::BROTLI_PARAM_LGWIN, lgwin);
+        state.set_custom_dictionary(dictionary.len(), dictionary);
+        Self {
+            state,
+            writer,
+            buf: vec![0; 32 * 1024],
+        }
+    }
+
+    fn compress(&mut self, op: BrotliEncoderOperation, mut input: &[u8]) -> IoResult<()> {
+        let mut nop_callback = |_: &mut brotli::interface::PredictionModeContextMap<brotli::InputReferenceMut>,
+                                _: &mut [brotli::interface::StaticCommand],
+                                _: brotli::InputPair,
+                                _: &mut StandardAlloc| ();
+        loop {
+            let mut available_in = input.len();
+            let mut input_offset = 0;
+            let mut available_out = self.buf.len();
+            let mut output_offset = 0;
+            let mut total_out = None;
+            if !self.state.compress_stream(
+                op,
+                &mut available_in,
+                input,
+                &mut input_offset,
+                &mut available_out,
+                &mut self.buf,
+                &mut output_offset,
+                &mut total_out,
+                &mut nop_callback,
+            ) {
+                return Err(IoError::new(ErrorKind::InvalidData, "brotli compression failed"));
+            }
+            self.writer.write_all(&self.buf[..output_offset])?;
+            input = &input[input_offset..];
+            let done = match op {
+                BrotliEncoderOperation::BROTLI_OPERATION_FINISH => self.state.is_finished(),
+                _ => input.is_empty() && !self.state.has_more_output(),
+            };
+            if done {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "brotli", feature = "dictionary"))]
+impl Drop for DictBrotliEncoder {
+    fn drop(&mut self) {
+        BrotliEncoderDestroyInstance(&mut self.state);
+    }
 }
 
 pub(super) enum Encoder {
@@ -102,6 +210,8 @@ pub(super) enum Encoder {
     Gzip(GzEncoder<Writer>),
     #[cfg(feature = "zstd")]
     Zstd(ZstdEncoder<'static, Writer>),
+    #[cfg(all(feature = "brotli", feature = "dictionary"))]
+    DictBrotli(Box<DictBrotliEncoder>),
 }
 
 impl Encoder {
@@ -118,6 +228,33 @@ impl Encoder {
             CompressionAlgo::Zstd => Self::Zstd(level.into_zstd()),
         }
     }
+
+    /// Create an encoder compressing against the dictionary, the output starts with the header of the encoding.
+    #[cfg(feature = "dictionary")]
+    #[allow(unused_variables)]
+    pub(super) fn with_dictionary(
+        encoding: DictionaryEncoding,
+        level: CompressionLevel,
+        dictionary: &Dictionary,
+    ) -> IoResult<Self> {
+        let mut writer = Writer::new();
+        match encoding {
+            #[cfg(feature = "brotli")]
+            DictionaryEncoding::Brotli => {
+                writer.write_all(&DCB_MAGIC)?;
+                writer.write_all(dictionary.hash())?;
+                Ok(Self::DictBrotli(Box::new(
+                    level.into_brotli_with_dictionary(writer, dictionary.data()),
+                )))
+            }
+            #[cfg(feature = "zstd")]
+            DictionaryEncoding::Zstd => {
+                writer.write_all(&DCZ_MAGIC)?;
+                writer.write_all(dictionary.hash())?;
+                Ok(Self::Zstd(level.into_zstd_with_dictionary(writer, dictionary.data())?))
+            }
+        }
+    }
     pub(super) fn take(&mut self) -> IoResult<Bytes> {
         match *self {
             #[cfg(feature = "brotli")]
@@ -140,6 +277,11 @@ impl Encoder {
                 encoder.flush()?;
                 Ok(encoder.get_mut().take())
             }
+            #[cfg(all(feature = "brotli", feature = "dictionary"))]
+            Self::DictBrotli(ref mut encoder) => {
+                encoder.compress(BrotliEncoderOperation::BROTLI_OPERATION_FLUSH, &[])?;
+                Ok(encoder.writer.take())
+            }
         }
     }
 
@@ -165,6 +307,11 @@ impl Encoder {
                 Ok(writer) => Ok(writer.buf.freeze()),
                 Err(err) => Err(err),
             },
+            #[cfg(all(feature = "brotli", feature = "dictionary"))]
+            Self::DictBrotli(mut encoder) => {
+                encoder.compress(BrotliEncoderOperation::BROTLI_OPERATION_FINISH, &[])?;
+                Ok(encoder.writer.take())
+            }
         }
     }
 
@@ -179,6 +326,10 @@ impl Encoder {
             Self::Gzip(ref mut encoder) => encoder.write_all(data),
             #[cfg(feature = "zstd")]
             Self::Zstd(ref mut encoder) => encoder.write_all(data),
+            #[cfg(all(feature = "brotli", feature = "dictionary"))]
+            Self::DictBrotli(ref mut encoder) => {
+                encoder.compress(BrotliEncoderOperation::BROTLI_OPERATION_PROCESS, data)
+            }
         }
     }
 }
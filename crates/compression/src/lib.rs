@@ -10,12 +10,21 @@ use std::str::FromStr;
 use indexmap::IndexMap;
 
 use salvo_core::http::body::ResBody;
+#[cfg(feature = "dictionary")]
+use salvo_core::http::header::VARY;
 use salvo_core::http::header::{HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE};
 use salvo_core::http::{self, mime, Mime, StatusCode};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
 
+#[cfg(feature = "dictionary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dictionary")))]
+pub mod dictionary;
 mod encoder;
 mod stream;
+#[cfg(feature = "dictionary")]
+use dictionary::AVAILABLE_DICTIONARY;
+#[cfg(feature = "dictionary")]
+pub use dictionary::{Dictionary, DictionaryEncoding, DictionaryStore};
 use encoder::Encoder;
 use stream::EncodeStream;
 
@@ -131,6 +140,10 @@ pub struct Compression {
     pub min_length: usize,
     /// Ignore request algorithms order in `Accept-Encoding` header and always server's config.
     pub force_priority: bool,
+    /// Dictionaries which responses can be compressed against.
+    #[cfg(feature = "dictionary")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dictionary")))]
+    pub dictionaries: DictionaryStore,
 }
 
 impl Default for Compression {
@@ -158,6 +171,8 @@ impl Default for Compression {
             ],
             min_length: 0,
             force_priority: false,
+            #[cfg(feature = "dictionary")]
+            dictionaries: DictionaryStore::new(),
         }
     }
 }
@@ -264,7 +279,28 @@ impl Compression {
         self
     }
 
-    fn negotiate(&self, req: &Request, res: &Response) -> Option<(CompressionAlgo, CompressionLevel)> {
+    /// Sets the store of dictionaries which responses can be compressed against.
+    ///
+    /// If a request advertises a registered dictionary in the `Available-Dictionary` header and accepts `dcb` or
+    /// `dcz` encoding, the response is compressed against the dictionary with the level of brotli or zstd.
+    #[cfg(feature = "dictionary")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dictionary")))]
+    #[inline]
+    pub fn dictionaries(mut self, dictionaries: DictionaryStore) -> Self {
+        self.dictionaries = dictionaries;
+        self
+    }
+
+    /// Register a dictionary which responses can be compressed against.
+    #[cfg(feature = "dictionary")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dictionary")))]
+    #[inline]
+    pub fn dictionary(self, data: impl Into<bytes::Bytes>) -> Self {
+        self.dictionaries.insert(data);
+        self
+    }
+
+    fn negotiate(&self, req: &Request, res: &Response) -> Option<(Encoder, HeaderValue)> {
         if req.headers().contains_key(&CONTENT_ENCODING) {
             return None;
         }
@@ -290,8 +326,13 @@ impl Compression {
             }
         }
         let header = req.headers().get(ACCEPT_ENCODING).and_then(|v| v.to_str().ok())?;
+        let accept_encodings = http::parse_accept_encoding(header);
+        #[cfg(feature = "dictionary")]
+        if let Some(negotiated) = self.negotiate_dictionary(req, &accept_encodings) {
+            return Some(negotiated);
+        }
 
-        let accept_algos = http::parse_accept_encoding(header)
+        let accept_algos = accept_encodings
             .into_iter()
             .filter_map(|(algo, level)| {
                 if let Ok(algo) = algo.parse::<CompressionAlgo>() {
@@ -301,7 +342,7 @@ impl Compression {
                 }
             })
             .collect::<Vec<_>>();
-        if self.force_priority {
+        let (algo, level) = if self.force_priority {
             let accept_algos = accept_algos.into_iter().map(|(algo, _)| algo).collect::<Vec<_>>();
             self.algos
                 .iter()
@@ -311,10 +352,43 @@ impl Compression {
             accept_algos
                 .into_iter()
                 .find_map(|(algo, _)| self.algos.get(&algo).map(|level| (algo, *level)))
+        }?;
+        Some((Encoder::new(algo, level), algo.into()))
+    }
+
+    #[cfg(feature = "dictionary")]
+    fn negotiate_dictionary(&self, req: &Request, accept_encodings: &[(String, u8)]) -> Option<(Encoder, HeaderValue)> {
+        if self.dictionaries.is_empty() {
+            return None;
         }
+        let available = req.headers().get(AVAILABLE_DICTIONARY).and_then(|v| v.to_str().ok())?;
+        let dictionary = self.dictionaries.find(available)?;
+        accept_encodings
+            .iter()
+            .filter_map(|(encoding, _)| DictionaryEncoding::parse(encoding))
+            .find_map(|encoding| {
+                let level = *self.algos.get(&encoding.algo())?;
+                match Encoder::with_dictionary(encoding, level, &dictionary) {
+                    Ok(encoder) => Some((encoder, encoding.into())),
+                    Err(e) => {
+                        tracing::error!(error = ?e, "create dictionary encoder failed");
+                        None
+                    }
+                }
+            })
     }
 }
 
+/// Sets the `Content-Encoding` header, responses compressed against a dictionary also vary with the dictionary.
+fn set_encoding(res: &mut Response, encoding: HeaderValue) {
+    #[cfg(feature = "dictionary")]
+    if encoding == "dcb" || encoding == "dcz" {
+        res.headers_mut()
+            .append(VARY, HeaderValue::from_static("accept-encoding, available-dictionary"));
+    }
+    res.headers_mut().append(CONTENT_ENCODING, encoding);
+}
+
 #[async_trait]
 impl Handler for Compression {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
@@ -339,9 +413,9 @@ impl Handler for Compression {
                     return;
                 }
                 match self.negotiate(req, res) {
-                    Some((algo, level)) => {
-                        res.stream(EncodeStream::new(algo, level, Some(bytes)));
-                        res.headers_mut().append(CONTENT_ENCODING, algo.into());
+                    Some((encoder, encoding)) => {
+                        res.stream(EncodeStream::new(encoder, Some(bytes)));
+                        set_encoding(res, encoding);
                     }
                     None => {
                         res.body(ResBody::Once(bytes));
@@ -358,9 +432,9 @@ impl Handler for Compression {
                     }
                 }
                 match self.negotiate(req, res) {
-                    Some((algo, level)) => {
-                        res.stream(EncodeStream::new(algo, level, chunks));
-                        res.headers_mut().append(CONTENT_ENCODING, algo.into());
+                    Some((encoder, encoding)) => {
+                        res.stream(EncodeStream::new(encoder, chunks));
+                        set_encoding(res, encoding);
                     }
                     None => {
                         res.body(ResBody::Chunks(chunks));
@@ -369,9 +443,9 @@ impl Handler for Compression {
                 }
             }
             ResBody::Hyper(body) => match self.negotiate(req, res) {
-                Some((algo, level)) => {
-                    res.stream(EncodeStream::new(algo, level, body));
-                    res.headers_mut().append(CONTENT_ENCODING, algo.into());
+                Some((encoder, encoding)) => {
+                    res.stream(EncodeStream::new(encoder, body));
+                    set_encoding(res, encoding);
                 }
                 None => {
                     res.body(ResBody::Hyper(body));
@@ -381,9 +455,9 @@ impl Handler for Compression {
             ResBody::Stream(body) => {
                 let body = body.into_inner();
                 match self.negotiate(req, res) {
-                    Some((algo, level)) => {
-                        res.stream(EncodeStream::new(algo, level, body));
-                        res.headers_mut().append(CONTENT_ENCODING, algo.into());
+                    Some((encoder, encoding)) => {
+                        res.stream(EncodeStream::new(encoder, body));
+                        set_encoding(res, encoding);
                     }
                     None => {
                         res.body(ResBody::stream(body));
@@ -450,4 +524,51 @@ mod tests {
         let content = res.take_string().await.unwrap();
         assert_eq!(content, "hello");
     }
+
+    #[tokio::test]
+    async fn test_dictionary() {
+        use std::io::Read;
+
+        let dictionary = "hello world, hello salvo";
+        let dict = Dictionary::new(dictionary);
+        let router = Router::with_hoop(Compression::new().min_length(1).dictionary(dictionary))
+            .push(Router::with_path("hello").get(hello));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header(ACCEPT_ENCODING, "dcz, gzip", true)
+            .add_header(AVAILABLE_DICTIONARY, dict.structured_hash(), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "dcz");
+        assert_eq!(
+            res.headers().get(VARY).unwrap(),
+            "accept-encoding, available-dictionary"
+        );
+        let body = res.take_bytes(None).await.unwrap();
+        assert_eq!(&body[8..40], dict.hash());
+        let mut content = String::new();
+        zstd::stream::read::Decoder::with_dictionary(&body[40..], dictionary.as_bytes())
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "hello");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header(ACCEPT_ENCODING, "dcb", true)
+            .add_header(AVAILABLE_DICTIONARY, dict.structured_hash(), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "dcb");
+        let body = res.take_bytes(None).await.unwrap();
+        assert_eq!(&body[..4], &[0xff, 0x44, 0x43, 0x42]);
+        assert_eq!(&body[4..36], dict.hash());
+
+        let res = TestClient::get("http://127.0.0.1:5801/hello")
+            .add_header(ACCEPT_ENCODING, "dcz, gzip", true)
+            .add_header(AVAILABLE_DICTIONARY, Dictionary::new("unknown").structured_hash(), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+    }
 }
@@ -12,7 +12,7 @@ use tokio::task::{spawn_blocking, JoinHandle};
 use salvo_core::http::body::{Body, BytesFrame, HyperBody};
 use salvo_core::BoxedError;
 
-use super::{CompressionAlgo, CompressionLevel, Encoder};
+use super::Encoder;
 
 const MAX_CHUNK_SIZE_ENCODE_IN_PLACE: usize = 1024;
 
@@ -24,13 +24,12 @@ pub(super) struct EncodeStream<B> {
 }
 
 impl<B> EncodeStream<B> {
-    #[allow(unused_variables)]
-    pub(super) fn new(algo: CompressionAlgo, level: CompressionLevel, body: B) -> Self {
+    pub(super) fn new(encoder: Encoder, body: B) -> Self {
         Self {
             body,
             eof: false,
             encoding: None,
-            encoder: Some(Encoder::new(algo, level)),
+            encoder: Some(encoder),
         }
     }
 }
@@ -0,0 +1,77 @@
+//! Measures payload copies and time per request for different response body kinds.
+//!
+//! A body is copied when the bytes taken from the response do not point to the payload anymore, which is checked
+//! by address since counting allocations would need a global allocator.
+//!
+//! Run with `cargo bench -p salvo_core --features test --bench res_body`.
+use std::time::Instant;
+
+use bytes::Bytes;
+use salvo_core::prelude::*;
+use salvo_core::test::{ResponseExt, TestClient};
+
+const ITERATIONS: usize = 10_000;
+static PAYLOAD: &[u8] = &[b'x'; 64 * 1024];
+
+/// Injects a shared `Bytes` into the depot, the handler clones it without copying the data.
+struct InjectBytes(Bytes);
+
+#[async_trait]
+impl Handler for InjectBytes {
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        depot.inject(self.0.clone());
+    }
+}
+
+#[handler]
+async fn copied(res: &mut Response) {
+    res.write_body(PAYLOAD.to_vec()).ok();
+}
+
+#[handler]
+async fn static_slice() -> &'static [u8] {
+    PAYLOAD
+}
+
+#[handler]
+async fn shared_bytes(depot: &mut Depot) -> Bytes {
+    depot.obtain::<Bytes>().cloned().unwrap_or_default()
+}
+
+async fn measure(name: &str, service: &Service, url: &str) {
+    // Warm up, so lazily initialized state is not counted.
+    TestClient::get(url).send(service).await.take_bytes(None).await.unwrap();
+
+    let mut copies = 0;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut res = TestClient::get(url).send(service).await;
+        let body = res.take_bytes(None).await.unwrap();
+        assert_eq!(body.len(), PAYLOAD.len());
+        if body.as_ptr() != PAYLOAD.as_ptr() {
+            copies += 1;
+        }
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{name:<16} {:>6.2} copies/req {:>12.1} copied bytes/req {:>10.2?}/req",
+        copies as f64 / ITERATIONS as f64,
+        (copies * PAYLOAD.len()) as f64 / ITERATIONS as f64,
+        elapsed / ITERATIONS as u32,
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let router = Router::new()
+        .hoop(InjectBytes(Bytes::from_static(PAYLOAD)))
+        .push(Router::with_path("copied").get(copied))
+        .push(Router::with_path("static").get(static_slice))
+        .push(Router::with_path("bytes").get(shared_bytes));
+    let service = Service::new(router);
+
+    println!("response body of {} bytes, {ITERATIONS} requests", PAYLOAD.len());
+    measure("Vec<u8> copy", &service, "http://127.0.0.1:5800/copied").await;
+    measure("&'static [u8]", &service, "http://127.0.0.1:5800/static").await;
+    measure("Bytes", &service, "http://127.0.0.1:5800/bytes").await;
+}
@@ -0,0 +1,362 @@
+//! Admission control with priorities.
+//!
+//! An [`AdmissionQueue`] set on the [`Service`](crate::Service) limits the number of requests processed
+//! concurrently. Requests which can not be processed immediately wait in a bounded queue, higher priority requests
+//! are admitted first. When the queue is full, a request with higher priority takes the place of a waiting request
+//! with lower priority, so under saturation low priority requests are shed first and health checks and critical
+//! endpoints stay responsive. Shed requests get `503 Service Unavailable` responses.
+//!
+//! The priority of a request is the one set on the innermost matched [`Router`](crate::Router) with
+//! [`Router::priority`](crate::Router::priority), [`Priority::Normal`] is used if none is set.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_core::admission::{AdmissionQueue, Priority};
+//! use salvo_core::prelude::*;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::new()
+//!         .push(Router::with_path("healthz").priority(Priority::High).get(hello))
+//!         .push(Router::with_path("reports").priority(Priority::Low).get(hello))
+//!         .push(Router::with_path("hello").get(hello));
+//!     let service = Service::new(router).admission(
+//!         AdmissionQueue::new(256)
+//!             .queue_capacity(1024)
+//!             .queue_timeout(Duration::from_secs(5)),
+//!     );
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(service).await;
+//! }
+//! ```
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+/// Priority of requests in an [`AdmissionQueue`].
+#[derive(Clone, Copy, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Priority {
+    /// Requests which are shed first, such as reports and batch jobs.
+    Low,
+    /// Requests without priority set.
+    #[default]
+    Normal,
+    /// Requests which are shed last, such as health checks and critical endpoints.
+    High,
+}
+
+impl Priority {
+    const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The reason why a request is not admitted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Shed {
+    /// The queue is full of requests with the same or higher priority.
+    QueueFull,
+    /// The request was replaced in the queue by a request with higher priority.
+    Evicted,
+    /// The request waited longer than the queue timeout.
+    Timeout,
+}
+
+impl fmt::Display for Shed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull => f.write_str("admission queue is full"),
+            Self::Evicted => f.write_str("evicted from admission queue"),
+            Self::Timeout => f.write_str("admission queue timeout"),
+        }
+    }
+}
+
+impl std::error::Error for Shed {}
+
+struct Waiter {
+    id: u64,
+    tx: oneshot::Sender<AdmissionPermit>,
+}
+
+#[derive(Default)]
+struct State {
+    running: usize,
+    next_id: u64,
+    /// Waiting requests, indexed by [`Priority::index`].
+    waiters: [VecDeque<Waiter>; 3],
+}
+
+impl State {
+    fn queued(&self) -> usize {
+        self.waiters.iter().map(VecDeque::len).sum()
+    }
+}
+
+struct Inner {
+    max_concurrency: usize,
+    state: Mutex<State>,
+}
+
+impl Inner {
+    /// Hands the slot of a finished request to the next waiter, or frees it.
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock();
+        for priority in Priority::ALL {
+            while let Some(waiter) = state.waiters[priority.index()].pop_front() {
+                match waiter.tx.send(AdmissionPermit {
+                    inner: Some(self.clone()),
+                }) {
+                    Ok(()) => return,
+                    // The waiter is gone, the permit must not release the slot again.
+                    Err(mut permit) => permit.inner = None,
+                }
+            }
+        }
+        state.running -= 1;
+    }
+}
+
+/// A queue which limits the number of requests processed concurrently and admits waiting requests by priority.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone)]
+pub struct AdmissionQueue {
+    inner: Arc<Inner>,
+    queue_capacity: usize,
+    queue_timeout: Option<Duration>,
+}
+
+impl Debug for AdmissionQueue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.inner.state.lock();
+        f.debug_struct("AdmissionQueue")
+            .field("max_concurrency", &self.inner.max_concurrency)
+            .field("queue_capacity", &self.queue_capacity)
+            .field("queue_timeout", &self.queue_timeout)
+            .field("running", &state.running)
+            .field("queued", &state.queued())
+            .finish()
+    }
+}
+
+impl AdmissionQueue {
+    /// Create a new `AdmissionQueue` processing at most `max_concurrency` requests at the same time.
+    ///
+    /// By default, at most `max_concurrency` requests wait in the queue without timeout.
+    #[inline]
+    pub fn new(max_concurrency: usize) -> Self {
+        let max_concurrency = max_concurrency.max(1);
+        Self {
+            inner: Arc::new(Inner {
+                max_concurrency,
+                state: Mutex::new(State::default()),
+            }),
+            queue_capacity: max_concurrency,
+            queue_timeout: None,
+        }
+    }
+
+    /// Sets the max number of waiting requests, `0` sheds all requests which can not be processed immediately.
+    #[inline]
+    pub fn queue_capacity(mut self, queue_capacity: usize) -> Self {
+        self.queue_capacity = queue_capacity;
+        self
+    }
+
+    /// Sets how long a request can wait in the queue before it is shed.
+    #[inline]
+    pub fn queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = Some(queue_timeout);
+        self
+    }
+
+    /// Get the number of requests being processed.
+    pub fn running(&self) -> usize {
+        self.inner.state.lock().running
+    }
+
+    /// Get the number of waiting requests.
+    pub fn queued(&self) -> usize {
+        self.inner.state.lock().queued()
+    }
+
+    /// Wait until a request with the priority can be processed, the returned permit must be held while processing.
+    pub async fn acquire(&self, priority: Priority) -> Result<AdmissionPermit, Shed> {
+        let (id, rx) = {
+            let mut state = self.inner.state.lock();
+            if state.running < self.inner.max_concurrency && state.queued() == 0 {
+                state.running += 1;
+                return Ok(AdmissionPermit {
+                    inner: Some(self.inner.clone()),
+                });
+            }
+            if state.queued() >= self.queue_capacity {
+                // Waiters whose requests are dropped do not take places.
+                for waiters in &mut state.waiters {
+                    waiters.retain(|waiter| !waiter.tx.is_closed());
+                }
+            }
+            if state.queued() >= self.queue_capacity {
+                // The newest waiter with the lowest priority is evicted, its sender is dropped.
+                let evicted = Priority::ALL
+                    .into_iter()
+                    .rev()
+                    .take_while(|lower| *lower < priority)
+                    .find_map(|lower| state.waiters[lower.index()].pop_back());
+                if evicted.is_none() {
+                    return Err(Shed::QueueFull);
+                }
+            }
+            let id = state.next_id;
+            state.next_id += 1;
+            let (tx, rx) = oneshot::channel();
+            state.waiters[priority.index()].push_back(Waiter { id, tx });
+            (id, rx)
+        };
+        let Some(timeout) = self.queue_timeout else {
+            return rx.await.map_err(|_| Shed::Evicted);
+        };
+        let mut rx = rx;
+        match tokio::time::timeout(timeout, &mut rx).await {
+            Ok(permit) => permit.map_err(|_| Shed::Evicted),
+            Err(_) => {
+                let mut state = self.inner.state.lock();
+                state.waiters[priority.index()].retain(|waiter| waiter.id != id);
+                drop(state);
+                // The permit may be sent just after the timeout.
+                rx.try_recv().map_err(|_| Shed::Timeout)
+            }
+        }
+    }
+}
+
+/// A permit of an admitted request, the slot is handed to the next waiting request when it is dropped.
+pub struct AdmissionPermit {
+    inner: Option<Arc<Inner>>,
+}
+
+impl Debug for AdmissionPermit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdmissionPermit").finish()
+    }
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.take() {
+            inner.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::prelude::*;
+    use crate::test::TestClient;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_admission_priority() {
+        let queue = AdmissionQueue::new(1).queue_capacity(2);
+        let running = queue.acquire(Priority::Normal).await.unwrap();
+
+        let low = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire(Priority::Low).await.map(|_| ()) }
+        });
+        let normal = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire(Priority::Normal).await.map(|_| ()) }
+        });
+        while queue.queued() < 2 {
+            tokio::task::yield_now().await;
+        }
+        // The queue is full, the low priority request is evicted.
+        let high = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire(Priority::High).await.map(|_| ()) }
+        });
+        assert_eq!(low.await.unwrap(), Err(Shed::Evicted));
+        assert_eq!(queue.acquire(Priority::Low).await.unwrap_err(), Shed::QueueFull);
+
+        drop(running);
+        assert_eq!(high.await.unwrap(), Ok(()));
+        assert_eq!(normal.await.unwrap(), Ok(()));
+        assert_eq!(queue.running(), 0);
+        assert_eq!(queue.queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_admission_timeout() {
+        let queue = AdmissionQueue::new(1).queue_timeout(Duration::from_millis(20));
+        let running = queue.acquire(Priority::High).await.unwrap();
+        assert_eq!(queue.acquire(Priority::High).await.unwrap_err(), Shed::Timeout);
+        assert_eq!(queue.queued(), 0);
+        drop(running);
+        assert!(queue.acquire(Priority::Low).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_admission_zero_concurrency() {
+        let queue = AdmissionQueue::new(0);
+        let running = queue.acquire(Priority::Normal).await.unwrap();
+        let waiting = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire(Priority::Normal).await.map(|_| ()) }
+        });
+        while queue.queued() < 1 {
+            tokio::task::yield_now().await;
+        }
+        drop(running);
+        assert_eq!(waiting.await.unwrap(), Ok(()));
+    }
+
+    #[handler]
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        "slow"
+    }
+
+    #[tokio::test]
+    async fn test_admission_service() {
+        let router = Router::new()
+            .push(Router::with_path("slow").get(slow))
+            .push(Router::with_path("low").priority(Priority::Low).get(slow));
+        let service = Service::new(router).admission(AdmissionQueue::new(1).queue_capacity(0));
+        let service = std::sync::Arc::new(service);
+        let first = tokio::spawn({
+            let service = service.clone();
+            async move {
+                TestClient::get("http://127.0.0.1:5801/slow")
+                    .send(&*service)
+                    .await
+                    .status_code
+            }
+        });
+        while service.admission.as_ref().unwrap().running() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let res = TestClient::get("http://127.0.0.1:5801/low").send(&*service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(first.await.unwrap(), Some(StatusCode::OK));
+    }
+}
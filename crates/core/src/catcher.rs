@@ -32,9 +32,36 @@
 //! The error handler is still [`Handler`].
 //!
 //! You can add multiple custom error catching handlers to [`Catcher`] through [`Catcher::hoop`]. The custom error
-//! handler can call [`FlowCtrl::skip_rest()`] method to skip next error handlers and return early.
+//! handler can call [`FlowCtrl::skip_rest()`] method to skip next error handlers and return early. Handlers added by
+//! [`Catcher::hoop_status`] only run for responses with the status code.
+//!
+//! Before the handlers run, a copy of the [`StatusError`] written to the response, which contains the original error
+//! as its cause, is put into the [`Depot`], the error stays in the response body. Handlers get it by
+//! [`CatcherDepotExt`]:
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_core::catcher::{Catcher, CatcherDepotExt};
+//!
+//! #[handler]
+//! async fn handle_io_error(depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+//!     if let Some(e) = depot.caught_cause::<std::io::Error>() {
+//!         res.render(format!("io error: {}", e.kind()));
+//!         ctrl.skip_rest();
+//!     }
+//! }
+//!
+//! let catcher = Catcher::default().hoop_status(StatusCode::INTERNAL_SERVER_ERROR, handle_io_error);
+//! ```
+//!
+//! [`DefaultGoal`] negotiates the format of the error page with the `Accept` header of the request, it renders an
+//! HTML page, JSON, [problem details](https://www.rfc-editor.org/rfc/rfc9457) (`application/problem+json`), XML or
+//! plain text. Templates of each status and format can be set by [`DefaultGoal::template`].
 
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -44,12 +71,84 @@ use once_cell::sync::Lazy;
 use serde::Serialize;
 
 use crate::handler::{Handler, WhenHoop};
-use crate::http::{guess_accept_mime, header, Request, ResBody, Response, StatusCode, StatusError};
+use crate::http::{header, Request, ResBody, Response, StatusCode, StatusError};
 use crate::{Depot, FlowCtrl};
 
 static SUPPORTED_FORMATS: Lazy<Vec<mime::Name>> = Lazy::new(|| vec![mime::JSON, mime::HTML, mime::XML, mime::PLAIN]);
 const EMPTY_CAUSE_MSG: &str = "There is no more detailed explanation.";
 const SALVO_LINK: &str = r#"<a href="https://salvo.rs" target="_blank">salvo</a>"#;
+static PROBLEM_JSON: Lazy<Mime> = Lazy::new(|| "application/problem+json".parse().expect("mime should be valid"));
+
+/// The error caught by [`Catcher`], a copy of the error in the response body which shares its cause.
+struct CaughtError {
+    error: StatusError,
+    cause: Option<Arc<dyn StdError + Send + Sync>>,
+}
+
+/// The cause shared by the error in the response body and the [`CaughtError`].
+struct SharedCause(Arc<dyn StdError + Send + Sync>);
+impl fmt::Debug for SharedCause {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+impl fmt::Display for SharedCause {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+impl StdError for SharedCause {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Copy the error in the response body, the cause is moved into an [`Arc`] shared by both errors.
+fn share_error(err: &mut StatusError) -> CaughtError {
+    let cause: Option<Arc<dyn StdError + Send + Sync>> = err.cause.take().map(Arc::from);
+    let shared = || {
+        cause
+            .clone()
+            .map(|cause| Box::new(SharedCause(cause)) as Box<dyn StdError + Send + Sync>)
+    };
+    err.cause = shared();
+    let error = StatusError {
+        code: err.code,
+        name: err.name.clone(),
+        brief: err.brief.clone(),
+        detail: err.detail.clone(),
+        cause: shared(),
+    };
+    CaughtError { error, cause }
+}
+
+/// Extension for [`Depot`] to get the error caught by [`Catcher`].
+pub trait CatcherDepotExt {
+    /// Get the [`StatusError`] written to the response.
+    ///
+    /// Returns `None` if the response has an error status code without error body, such as `404` returned by the
+    /// router.
+    fn caught_error(&self) -> Option<&StatusError>;
+
+    /// Get the original error, which is the cause of the caught [`StatusError`], if it is of type `E`.
+    fn caught_cause<E: StdError + 'static>(&self) -> Option<&E>;
+}
+
+impl CatcherDepotExt for Depot {
+    #[inline]
+    fn caught_error(&self) -> Option<&StatusError> {
+        self.obtain::<CaughtError>().ok().map(|caught| &caught.error)
+    }
+
+    #[inline]
+    fn caught_cause<E: StdError + 'static>(&self) -> Option<&E> {
+        let cause: &(dyn StdError + 'static) = self.obtain::<CaughtError>().ok()?.cause.as_deref()?;
+        cause.downcast_ref::<E>()
+    }
+}
 
 /// `Catcher` is used to catch errors.
 ///
@@ -107,13 +206,40 @@ impl Catcher {
         self
     }
 
+    /// Add a handler as middleware, it only runs for responses with the status code.
+    #[inline]
+    pub fn hoop_status<H: Handler>(mut self, status: StatusCode, hoop: H) -> Self {
+        self.hoops.push(Arc::new(StatusHoop { inner: hoop, status }));
+        self
+    }
+
     /// Catch error and send error page.
+    ///
+    /// A copy of the [`StatusError`] of the response body is put into the depot, see [`CatcherDepotExt`].
     pub async fn catch(&self, req: &mut Request, depot: &mut Depot, res: &mut Response) {
+        if let ResBody::Error(e) = &mut res.body {
+            depot.inject(share_error(e));
+        }
         let mut ctrl = FlowCtrl::new(self.hoops.iter().chain([&self.goal]).cloned().collect());
         ctrl.call_next(req, depot, res).await;
     }
 }
 
+struct StatusHoop<H> {
+    inner: H,
+    status: StatusCode,
+}
+#[async_trait]
+impl<H: Handler> Handler for StatusHoop<H> {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if res.status_code == Some(self.status) {
+            self.inner.handle(req, depot, res, ctrl).await;
+        } else {
+            ctrl.call_next(req, depot, res).await;
+        }
+    }
+}
+
 impl<H> From<H> for Catcher
 where
     H: Into<Arc<dyn Handler>>,
@@ -128,15 +254,22 @@ where
 /// If http status is error, and all custom handlers is not catch it and write body,
 /// `DefaultGoal` will used to catch them.
 ///
-/// `DefaultGoal` supports sending error pages in `XML`, `JSON`, `HTML`, `Text` formats.
+/// `DefaultGoal` supports sending error pages in `XML`, `JSON`, problem details, `HTML`, `Text` formats, the format
+/// is negotiated with the `Accept` header of the request.
 #[derive(Default)]
 pub struct DefaultGoal {
     footer: Option<Cow<'static, str>>,
+    templates: HashMap<(StatusCode, String), Cow<'static, str>>,
+    problem_details: bool,
 }
 impl DefaultGoal {
     /// Create new `DefaultGoal`.
     pub fn new() -> Self {
-        DefaultGoal { footer: None }
+        DefaultGoal {
+            footer: None,
+            templates: HashMap::new(),
+            problem_details: false,
+        }
     }
     /// Create new `DefaultGoal` with custom footer.
     #[inline]
@@ -152,15 +285,109 @@ impl DefaultGoal {
         self.footer = Some(footer.into());
         self
     }
+
+    /// Set the template of the error page of the status in the format, such as `mime::TEXT_HTML`.
+    ///
+    /// `{code}`, `{name}`, `{brief}` and `{cause}` in the template are replaced by the fields of the error, they are
+    /// escaped in HTML templates.
+    pub fn template(mut self, status: StatusCode, format: Mime, template: impl Into<Cow<'static, str>>) -> Self {
+        self.templates
+            .insert((status, format.essence_str().to_owned()), template.into());
+        self
+    }
+
+    /// Send errors as problem details when the request accepts `application/json`.
+    ///
+    /// Requests accepting `application/problem+json` always receive problem details.
+    pub fn problem_details(mut self, problem_details: bool) -> Self {
+        self.problem_details = problem_details;
+        self
+    }
+}
+impl From<DefaultGoal> for Arc<dyn Handler> {
+    /// Use a customized [`DefaultGoal`] as the goal of [`Catcher::new`].
+    #[inline]
+    fn from(goal: DefaultGoal) -> Self {
+        Arc::new(goal)
+    }
 }
 #[async_trait]
 impl Handler for DefaultGoal {
-    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
         let status = res.status_code.unwrap_or(StatusCode::NOT_FOUND);
-        if (status.is_server_error() || status.is_client_error()) && (res.body.is_none() || res.body.is_error()) {
-            write_error_default(req, res, self.footer.as_deref());
+        if !(status.is_server_error() || status.is_client_error()) || !(res.body.is_none() || res.body.is_error()) {
+            return;
         }
+        let mut format = negotiate_format(req);
+        if self.problem_details && format.subtype() == mime::JSON && format.suffix().is_none() {
+            format = PROBLEM_JSON.clone();
+        }
+        let default;
+        let err = if let ResBody::Error(e) = &res.body {
+            e
+        } else if let Some(e) = depot.caught_error() {
+            e
+        } else {
+            default = StatusError::from_code(status).unwrap_or_else(StatusError::internal_server_error);
+            &default
+        };
+        let (format, data) = match self.templates.get(&(err.code, format.essence_str().to_owned())) {
+            Some(template) => (format.clone(), Bytes::from(render_template(template, err, &format))),
+            None => status_error_bytes(err, &format, self.footer.as_deref()),
+        };
+        write_error_body(res, format, data);
+    }
+}
+
+/// Choose the format of the error page from the `Accept` header, the one with the highest quality is chosen.
+fn negotiate_format(req: &Request) -> Mime {
+    let mut chosen: Option<(f32, Mime)> = None;
+    for mime in req.accept_list() {
+        let quality = mime
+            .get_param("q")
+            .and_then(|q| q.as_str().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if quality <= 0.0 || chosen.as_ref().is_some_and(|(q, _)| *q >= quality) {
+            continue;
+        }
+        let format = if mime.subtype() == "problem" && mime.suffix() == Some(mime::JSON) {
+            PROBLEM_JSON.clone()
+        } else if mime.subtype() == mime::STAR {
+            mime::TEXT_HTML
+        } else if SUPPORTED_FORMATS.contains(&mime.subtype()) {
+            mime.clone()
+        } else {
+            continue;
+        };
+        chosen = Some((quality, format));
     }
+    chosen.map(|(_, format)| format).unwrap_or(mime::TEXT_HTML)
+}
+
+fn render_template(template: &str, err: &StatusError, format: &Mime) -> String {
+    #[cfg(debug_assertions)]
+    let cause = err.cause.as_ref().map(|e| format!("{:#?}", e.as_ref()));
+    #[cfg(not(debug_assertions))]
+    let cause: Option<String> = None;
+    let code = err.code.as_u16().to_string();
+    let cause = cause.as_deref().unwrap_or(EMPTY_CAUSE_MSG);
+    let escape = |value: &str| -> String {
+        if format.subtype() == mime::HTML {
+            value
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+                .replace('\'', "&#x27;")
+        } else {
+            value.to_owned()
+        }
+    };
+    template
+        .replace("{code}", &code)
+        .replace("{name}", &escape(&err.name))
+        .replace("{brief}", &escape(&err.brief))
+        .replace("{cause}", &escape(cause))
 }
 
 fn status_error_html(code: StatusCode, name: &str, brief: &str, cause: Option<&str>, footer: Option<&str>) -> String {
@@ -231,6 +458,27 @@ fn status_error_json(code: StatusCode, name: &str, brief: &str, cause: Option<&s
     serde_json::to_string(&data).unwrap_or_default()
 }
 
+fn status_error_problem(code: StatusCode, name: &str, brief: &str, cause: Option<&str>) -> String {
+    #[derive(Serialize)]
+    struct Problem<'a> {
+        #[serde(rename = "type")]
+        kind: &'a str,
+        title: &'a str,
+        status: u16,
+        detail: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cause: Option<&'a str>,
+    }
+    let problem = Problem {
+        kind: "about:blank",
+        title: name,
+        status: code.as_u16(),
+        detail: brief,
+        cause,
+    };
+    serde_json::to_string(&problem).unwrap_or_default()
+}
+
 fn status_error_plain(code: StatusCode, name: &str, brief: &str, cause: Option<&str>) -> String {
     format!(
         "code: {}\n\nname: {}\n\nbrief: {}\n\ncause: {}",
@@ -263,7 +511,9 @@ fn status_error_xml(code: StatusCode, name: &str, brief: &str, cause: Option<&st
 #[doc(hidden)]
 #[inline]
 pub fn status_error_bytes(err: &StatusError, prefer_format: &Mime, footer: Option<&str>) -> (Mime, Bytes) {
-    let format = if !SUPPORTED_FORMATS.contains(&prefer_format.subtype()) {
+    let format = if prefer_format.subtype() == "problem" && prefer_format.suffix() == Some(mime::JSON) {
+        PROBLEM_JSON.clone()
+    } else if !SUPPORTED_FORMATS.contains(&prefer_format.subtype()) {
         mime::TEXT_HTML
     } else {
         prefer_format.clone()
@@ -273,6 +523,7 @@ pub fn status_error_bytes(err: &StatusError, prefer_format: &Mime, footer: Optio
     #[cfg(not(debug_assertions))]
     let cause: Option<String> = None;
     let content = match format.subtype().as_ref() {
+        "problem" => status_error_problem(err.code, &err.name, &err.brief, cause.as_deref()),
         "plain" => status_error_plain(err.code, &err.name, &err.brief, cause.as_deref()),
         "json" => status_error_json(err.code, &err.name, &err.brief, cause.as_deref()),
         "xml" => status_error_xml(err.code, &err.name, &err.brief, cause.as_deref()),
@@ -283,7 +534,7 @@ pub fn status_error_bytes(err: &StatusError, prefer_format: &Mime, footer: Optio
 
 #[doc(hidden)]
 pub fn write_error_default(req: &Request, res: &mut Response, footer: Option<&str>) {
-    let format = guess_accept_mime(req, None);
+    let format = negotiate_format(req);
     let (format, data) = if let ResBody::Error(body) = &res.body {
         status_error_bytes(body, &format, footer)
     } else {
@@ -294,6 +545,10 @@ pub fn write_error_default(req: &Request, res: &mut Response, footer: Option<&st
             footer,
         )
     };
+    write_error_body(res, format, data);
+}
+
+fn write_error_body(res: &mut Response, format: Mime, data: Bytes) {
     res.headers_mut().insert(
         header::CONTENT_TYPE,
         format.to_string().parse().expect("invalid `Content-Type`"),
@@ -305,6 +560,7 @@ pub fn write_error_default(req: &Request, res: &mut Response, footer: Option<&st
 mod tests {
     use crate::prelude::*;
     use crate::test::{ResponseExt, TestClient};
+    use crate::Error;
 
     use super::*;
 
@@ -366,4 +622,84 @@ mod tests {
 
         assert_eq!(access(&service, "notfound").await, "Custom 404 Error Page");
     }
+
+    #[tokio::test]
+    async fn test_catcher_negotiation() {
+        let service = Service::new(Router::new()).catcher(Catcher::new(
+            DefaultGoal::new()
+                .template(StatusCode::NOT_FOUND, mime::TEXT_PLAIN, "{code} {name}")
+                .template(StatusCode::NOT_FOUND, mime::TEXT_HTML, "<p>{brief}</p>"),
+        ));
+        async fn access(service: &Service, accept: &str) -> (String, String) {
+            let mut res = TestClient::get("http://127.0.0.1:5800/notfound")
+                .add_header(header::ACCEPT, accept, true)
+                .send(service)
+                .await;
+            let content_type = res
+                .content_type()
+                .map(|m| m.essence_str().to_owned())
+                .unwrap_or_default();
+            (content_type, res.take_string().await.unwrap())
+        }
+
+        let (content_type, body) = access(&service, "text/plain").await;
+        assert_eq!((content_type.as_str(), body.as_str()), ("text/plain", "404 Not Found"));
+        let (content_type, body) = access(&service, "image/webp, application/problem+json;q=0.9").await;
+        assert_eq!(content_type, "application/problem+json");
+        assert!(body.contains(r#""status":404"#) && body.contains(r#""title":"Not Found""#));
+        let (content_type, body) = access(&service, "application/json;q=0.5, text/html").await;
+        assert_eq!(content_type, "text/html");
+        assert_eq!(body, "<p>The requested resource could not be found.</p>");
+        let (content_type, _) = access(&service, "application/json").await;
+        assert_eq!(content_type, "application/json");
+
+        let service = Service::new(Router::new()).catcher(Catcher::new(DefaultGoal::new().problem_details(true)));
+        let (content_type, _) = access(&service, "application/json").await;
+        assert_eq!(content_type, "application/problem+json");
+    }
+
+    #[test]
+    fn test_render_template_escape() {
+        let err = StatusError::bad_request().brief(r#"<a href='x'>"&"</a>"#);
+        assert_eq!(
+            render_template("{brief}", &err, &mime::TEXT_HTML),
+            "&lt;a href=&#x27;x&#x27;&gt;&quot;&amp;&quot;&lt;/a&gt;"
+        );
+        assert_eq!(render_template("{brief}", &err, &mime::TEXT_PLAIN), err.brief);
+    }
+
+    #[tokio::test]
+    async fn test_catcher_status_hoop() {
+        #[handler]
+        async fn fail() -> Result<(), Error> {
+            Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied").into())
+        }
+        #[handler]
+        async fn handle_io_error(depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+            let kind = depot.caught_cause::<Error>().map(|e| matches!(e, Error::Io(_)));
+            let in_body = matches!(res.take_body(), ResBody::Error(e) if e.cause.is_some());
+            res.render(format!("io: {kind:?} {in_body}"));
+            ctrl.skip_rest();
+        }
+        let router = Router::new().push(Router::with_path("fail").get(fail));
+        let service = Service::new(router).catcher(
+            Catcher::default()
+                .hoop_status(StatusCode::INTERNAL_SERVER_ERROR, handle_io_error)
+                .hoop(handle404),
+        );
+        let content = TestClient::get("http://127.0.0.1:5800/fail")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "io: Some(true) true");
+        let content = TestClient::get("http://127.0.0.1:5800/notfound")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "Custom 404 Error Page");
+    }
 }
@@ -1,5 +1,5 @@
 //! JoinListener and it's implements.
-use std::io::Result as IoResult;
+use std::io::{IoSlice, Result as IoResult};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -65,6 +65,22 @@ where
             JoinedStream::B(b) => Pin::new(b).poll_shutdown(cx),
         }
     }
+
+    #[inline]
+    fn poll_write_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[IoSlice<'_>]) -> Poll<IoResult<usize>> {
+        match &mut self.get_mut() {
+            JoinedStream::A(a) => Pin::new(a).poll_write_vectored(cx, bufs),
+            JoinedStream::B(b) => Pin::new(b).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            JoinedStream::A(a) => a.is_write_vectored(),
+            JoinedStream::B(b) => b.is_write_vectored(),
+        }
+    }
 }
 
 /// `JoinedListener` is a listener that can join two listeners.
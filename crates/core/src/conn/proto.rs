@@ -33,7 +33,10 @@ use crate::conn::quinn;
 
 const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
 
-#[doc(hidden)]
+/// Builder of the HTTP protocols used to serve connections.
+///
+/// Use it with [`Server::with_http_builder`](crate::Server::with_http_builder) to tune the buffer sizes and the
+/// flow control of connections, the defaults of hyper are used if nothing is set.
 pub struct HttpBuilder {
     #[cfg(feature = "http1")]
     pub(crate) http1: http1::Builder,
@@ -49,6 +52,7 @@ impl Default for HttpBuilder {
 }
 
 impl HttpBuilder {
+    /// Create a new `HttpBuilder` with the default settings.
     pub fn new() -> Self {
         Self {
             #[cfg(feature = "http1")]
@@ -60,6 +64,76 @@ impl HttpBuilder {
         }
     }
 
+    cfg_feature! {
+        #![feature = "http1"]
+        /// Set the maximum buffer size of a HTTP/1 connection, the default is about 400kb.
+        ///
+        /// A request head larger than this size is rejected, the size must be at least 8kb.
+        #[inline]
+        pub fn http1_max_buf_size(mut self, max: usize) -> Self {
+            self.http1.max_buf_size(max);
+            self
+        }
+        /// Set whether HTTP/1 connections should try to use vectored writes, or always flatten into a single buffer.
+        ///
+        /// By default it is detected from the I/O stream.
+        #[inline]
+        pub fn http1_writev(mut self, enabled: bool) -> Self {
+            self.http1.writev(enabled);
+            self
+        }
+        /// Get mutable reference to the HTTP/1 builder for other settings.
+        #[inline]
+        pub fn http1_mut(&mut self) -> &mut http1::Builder {
+            &mut self.http1
+        }
+    }
+
+    cfg_feature! {
+        #![feature = "http2"]
+        /// Set the initial window size of HTTP/2 stream-level flow control, the default is 1MB.
+        #[inline]
+        pub fn http2_initial_stream_window_size(mut self, size: u32) -> Self {
+            self.http2.initial_stream_window_size(size);
+            self
+        }
+        /// Set the initial window size of HTTP/2 connection-level flow control, the default is 1MB.
+        #[inline]
+        pub fn http2_initial_connection_window_size(mut self, size: u32) -> Self {
+            self.http2.initial_connection_window_size(size);
+            self
+        }
+        /// Set whether to use an adaptive flow control, it overrides the initial window sizes if enabled.
+        #[inline]
+        pub fn http2_adaptive_window(mut self, enabled: bool) -> Self {
+            self.http2.adaptive_window(enabled);
+            self
+        }
+        /// Set the maximum frame size to use for HTTP/2, the default is 16kb.
+        #[inline]
+        pub fn http2_max_frame_size(mut self, size: u32) -> Self {
+            self.http2.max_frame_size(size);
+            self
+        }
+        /// Set the maximum number of concurrent streams of a HTTP/2 connection, the default is 200.
+        #[inline]
+        pub fn http2_max_concurrent_streams(mut self, max: u32) -> Self {
+            self.http2.max_concurrent_streams(max);
+            self
+        }
+        /// Set the maximum write buffer size of each HTTP/2 stream, the default is 400kb.
+        #[inline]
+        pub fn http2_max_send_buf_size(mut self, max: usize) -> Self {
+            self.http2.max_send_buf_size(max);
+            self
+        }
+        /// Get mutable reference to the HTTP/2 builder for other settings.
+        #[inline]
+        pub fn http2_mut(&mut self) -> &mut http2::Builder<TokioExecutor> {
+            &mut self.http2
+        }
+    }
+
     /// Serve a connection with the given service.
     #[allow(unused_variables)]
     pub async fn serve_connection<I, S, B>(
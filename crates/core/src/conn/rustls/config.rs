@@ -5,16 +5,19 @@ use std::future::{ready, Ready};
 use std::io::{Error as IoError, ErrorKind, Read, Result as IoResult};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use futures_util::stream::{once, Once, Stream};
+use futures_util::stream::{once, Once, Stream, StreamExt};
 use tokio_rustls::rustls::crypto::ring::sign::any_supported_type;
 use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
 use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
 use tokio_rustls::rustls::sign::CertifiedKey;
+use x509_parser::prelude::{FromDer, X509Certificate};
 
 pub use tokio_rustls::rustls::server::ServerConfig;
 
 use crate::conn::IntoConfigStream;
+use crate::secret::{SecretProvider, SecretWatcher};
 
 use super::read_trust_anchor;
 
@@ -82,6 +85,16 @@ impl Keycert {
         &self.ocsp_resp
     }
 
+    /// Get the expiry time of the first certificate of the chain, returns `None` if it can not be parsed.
+    ///
+    /// It can be added to the [`StartupReport`](crate::startup::StartupReport) of the server.
+    pub fn not_after(&self) -> Option<SystemTime> {
+        let cert = rustls_pemfile::certs(&mut self.cert.as_ref()).next()?.ok()?;
+        let (_, cert) = X509Certificate::from_der(cert.as_ref()).ok()?;
+        let secs = u64::try_from(cert.validity().not_after.timestamp()).ok()?;
+        Some(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
     fn build_certified_key(&mut self) -> IoResult<CertifiedKey> {
         let cert = rustls_pemfile::certs(&mut self.cert.as_ref())
             .flat_map(|certs| certs.into_iter().collect::<Vec<CertificateDer<'static>>>())
@@ -159,6 +172,25 @@ impl RustlsConfig {
         }
     }
 
+    /// Create a config stream from the certificate and private key stored in a secret provider.
+    ///
+    /// A new config is yielded every time the watcher notices that the certificate or the key rotates, so the
+    /// listener reloads them without restarting. Use [`StreamExt::map`](futures_util::StreamExt::map) to customize
+    /// each config, such as setting client authentication.
+    pub fn from_secrets<P>(
+        watcher: &SecretWatcher<P>,
+        cert: &str,
+        key: &str,
+    ) -> impl Stream<Item = RustlsConfig> + Send + 'static
+    where
+        P: SecretProvider + ?Sized,
+    {
+        watcher.watch([cert.to_owned(), key.to_owned()]).map(|secrets| {
+            let keycert = Keycert::new().cert(secrets[0].expose()).key(secrets[1].expose());
+            RustlsConfig::new(keycert)
+        })
+    }
+
     /// Sets the trust anchor for optional Tls client authentication via file path.
     ///
     /// Anonymous and authenticated clients will be accepted. If no trust anchor is provided by any
@@ -1,5 +1,5 @@
 use std::future::Future;
-use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::io::{Error as IoError, ErrorKind, IoSlice, Result as IoResult};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -133,6 +133,7 @@ where
                     }
                     Poll::Pending => return Poll::Pending,
                 },
+                // Written bytes are reported by the underlying stream, handshake records included.
                 State::Ready(stream) => return Pin::new(stream).poll_write(cx, buf),
                 State::Error => return Poll::Ready(Err(invalid_data_error("poll write invalid data"))),
             }
@@ -176,6 +177,38 @@ where
             }
         }
     }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<IoResult<usize>> {
+        let this = &mut *self;
+
+        loop {
+            match &mut this.state {
+                State::Handshaking(fut) => match fut.poll_unpin(cx) {
+                    Poll::Ready(Ok(s)) => this.set_state_ready(s),
+                    Poll::Ready(Err(err)) => {
+                        this.state = State::Error;
+                        return Poll::Ready(Err(err));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                // Written bytes are reported by the underlying stream, handshake records included.
+                State::Ready(stream) => return Pin::new(stream).poll_write_vectored(cx, bufs),
+                State::Error => return Poll::Ready(Err(invalid_data_error("poll write invalid data"))),
+            }
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        // The stream is unknown before handshake finished, `poll_write_vectored` still works in that case.
+        match &self.state {
+            State::Ready(stream) => stream.is_write_vectored(),
+            _ => false,
+        }
+    }
 }
 
 fn invalid_data_error(msg: &'static str) -> IoError {
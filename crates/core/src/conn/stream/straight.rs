@@ -121,10 +121,21 @@ where
 
     fn poll_write_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[IoSlice<'_>]) -> Poll<IoResult<usize>> {
         let this = self.project();
-        if let Some(fusewire) = &this.fusewire {
-            fusewire.event(FuseEvent::Alive);
+        match this.inner.poll_write_vectored(cx, bufs) {
+            Poll::Ready(Ok(len)) => {
+                if let Some(fusewire) = &this.fusewire {
+                    fusewire.event(FuseEvent::WriteData(len));
+                }
+                Poll::Ready(Ok(len))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => {
+                if let Some(fusewire) = &this.fusewire {
+                    fusewire.event(FuseEvent::Alive);
+                }
+                Poll::Pending
+            }
         }
-        this.inner.poll_write_vectored(cx, bufs)
     }
 
     fn is_write_vectored(&self) -> bool {
@@ -1,9 +1,13 @@
 //! TcpListener and it's implements.
-use std::io::{Error as IoError, Result as IoResult};
-use std::net::SocketAddr;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
 use std::vec;
 
 use tokio::net::{TcpListener as TokioTcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
 use crate::conn::{Holding, StraightStream};
 use crate::fuse::{ArcFuseFactory, FuseInfo, TransProto};
@@ -31,8 +35,11 @@ use crate::conn::acme::AcmeListener;
 pub struct TcpListener<T> {
     local_addr: T,
     ttl: Option<u32>,
+    dual_stack: bool,
     #[cfg(feature = "socket2")]
     backlog: Option<u32>,
+    #[cfg(feature = "socket2")]
+    shards: usize,
 }
 impl<T: ToSocketAddrs + Send> TcpListener<T> {
     /// Bind to socket address.
@@ -40,7 +47,11 @@ impl<T: ToSocketAddrs + Send> TcpListener<T> {
     #[inline]
     pub fn new(local_addr: T) -> Self {
         #[cfg(not(feature = "socket2"))]
-        TcpListener { local_addr, ttl: None }
+        TcpListener {
+            local_addr,
+            ttl: None,
+            dual_stack: false,
+        }
     }
     /// Bind to socket address.
     #[cfg(feature = "socket2")]
@@ -49,7 +60,9 @@ impl<T: ToSocketAddrs + Send> TcpListener<T> {
         TcpListener {
             local_addr,
             ttl: None,
+            dual_stack: false,
             backlog: None,
+            shards: 1,
         }
     }
 
@@ -125,6 +138,34 @@ impl<T: ToSocketAddrs + Send> TcpListener<T> {
             self.backlog = Some(backlog);
             self
         }
+
+        /// Set the number of sockets bound to the same address with `SO_REUSEPORT`.
+        ///
+        /// The kernel distributes incoming connections across the sockets, and each socket is accepted by its own
+        /// task, so the `accept` calls run on several worker threads. The accepted connections are still handed to
+        /// the server's single accept loop through one queue, which spawns a task for each of them. This helps when
+        /// accepting connections is the bottleneck, it is usually set to the number of CPU cores.
+        /// On platforms which do not support `SO_REUSEPORT`, only one socket is bound.
+        #[inline]
+        pub fn shards(mut self, shards: usize) -> Self {
+            self.shards = shards.max(1);
+            self
+        }
+    }
+}
+impl TcpListener<SocketAddr> {
+    /// Bind to the port on both IPv6 and IPv4 unspecified addresses, `[::]` and `0.0.0.0`.
+    ///
+    /// Whether an IPv6 socket also accepts IPv4 connections (`IPV6_V6ONLY`) differs between platforms. With the
+    /// `socket2` feature, two sockets are bound with `IPV6_V6ONLY` set, otherwise the IPv4 socket is skipped if the
+    /// IPv6 socket already accepts IPv4 connections. Remote IPv4 addresses are never reported as IPv4-mapped IPv6
+    /// addresses. If IPv6 is not available, only IPv4 is bound. Both addresses are reported in
+    /// [`holdings`](Acceptor::holdings).
+    #[inline]
+    pub fn dual_stack(port: u16) -> Self {
+        let mut listener = Self::new(SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)));
+        listener.dual_stack = true;
+        listener
     }
 }
 impl<T> Listener for TcpListener<T>
@@ -134,6 +175,25 @@ where
     type Acceptor = TcpAcceptor;
 
     async fn try_bind(self) -> crate::Result<Self::Acceptor> {
+        if self.dual_stack {
+            let port = tokio::net::lookup_host(self.local_addr)
+                .await?
+                .next()
+                .map(|addr| addr.port())
+                .unwrap_or_default();
+            #[cfg(feature = "socket2")]
+            return bind_dual_stack(port, self.backlog, self.ttl).await;
+            #[cfg(not(feature = "socket2"))]
+            return bind_dual_stack(port, None, self.ttl).await;
+        }
+        #[cfg(feature = "socket2")]
+        if self.shards > 1 {
+            #[cfg(unix)]
+            return bind_shards(self.local_addr, self.shards, self.backlog, self.ttl).await;
+            #[cfg(not(unix))]
+            tracing::warn!("`SO_REUSEPORT` is not supported on this platform, only one socket is bound");
+        }
+
         let inner = TokioTcpListener::bind(self.local_addr).await?;
 
         #[cfg(feature = "socket2")]
@@ -148,14 +208,192 @@ where
         Ok(inner.try_into()?)
     }
 }
+
+#[cfg(all(feature = "socket2", unix))]
+async fn bind_shards<T: ToSocketAddrs>(
+    local_addr: T,
+    shards: usize,
+    backlog: Option<u32>,
+    ttl: Option<u32>,
+) -> crate::Result<TcpAcceptor> {
+    let addr = tokio::net::lookup_host(local_addr)
+        .await?
+        .next()
+        .ok_or_else(|| IoError::new(std::io::ErrorKind::AddrNotAvailable, "no socket address resolved"))?;
+    let first = bind_reuse_port(addr, backlog)?;
+    // Binding to port 0 picks a random port, the other shards must use the same one.
+    let addr = first.local_addr()?;
+    let mut listeners = vec![first];
+    for _ in 1..shards {
+        listeners.push(bind_reuse_port(addr, backlog)?);
+    }
+    if let Some(ttl) = ttl {
+        for listener in &listeners {
+            listener.set_ttl(ttl)?;
+        }
+    }
+
+    let listeners: Vec<_> = listeners.into_iter().map(Arc::new).collect();
+    let mut acceptor = TcpAcceptor::try_from(listeners[0].clone())?;
+    acceptor.shards = Some(Shards::spawn(
+        listeners.into_iter().map(|listener| (0, listener)).collect(),
+    ));
+    Ok(acceptor)
+}
+
+async fn bind_dual_stack(port: u16, backlog: Option<u32>, ttl: Option<u32>) -> crate::Result<TcpAcceptor> {
+    let v6 = match bind_listener(SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)), backlog) {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            tracing::warn!(error = ?e, "IPv6 is not available, only IPv4 is bound");
+            None
+        }
+    };
+    // Binding to port 0 picks a random port, the IPv4 socket must use the same one.
+    let port = match &v6 {
+        Some(v6) => v6.local_addr()?.port(),
+        None => port,
+    };
+    let v4_addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+    let v4 = match bind_listener(v4_addr, backlog) {
+        Ok(listener) => Some(listener),
+        // The IPv6 socket is not `IPV6_V6ONLY` and accepts IPv4 connections already.
+        Err(e) if e.kind() == ErrorKind::AddrInUse && v6.is_some() && cfg!(not(feature = "socket2")) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let mut holdings = Vec::with_capacity(2);
+    let mut listeners = Vec::with_capacity(2);
+    if let Some(v6) = v6 {
+        holdings.push(Holding::tcp(v6.local_addr()?));
+        if v4.is_none() {
+            holdings.push(Holding::tcp(v4_addr));
+        }
+        listeners.push((0, Arc::new(v6)));
+    }
+    if let Some(v4) = v4 {
+        holdings.push(Holding::tcp(v4.local_addr()?));
+        listeners.push((holdings.len() - 1, Arc::new(v4)));
+    }
+    if let Some(ttl) = ttl {
+        for (_, listener) in &listeners {
+            listener.set_ttl(ttl)?;
+        }
+    }
+    Ok(TcpAcceptor {
+        inner: listeners[0].1.clone(),
+        shards: Some(Shards::spawn(listeners)),
+        holdings,
+    })
+}
+
+#[cfg(feature = "socket2")]
+fn bind_listener(addr: SocketAddr, backlog: Option<u32>) -> IoResult<TokioTcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(backlog.unwrap_or(1024) as _)?;
+    TokioTcpListener::from_std(socket.into())
+}
+#[cfg(not(feature = "socket2"))]
+fn bind_listener(addr: SocketAddr, _backlog: Option<u32>) -> IoResult<TokioTcpListener> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    TokioTcpListener::from_std(listener)
+}
+
+#[cfg(all(feature = "socket2", unix))]
+fn bind_reuse_port(addr: SocketAddr, backlog: Option<u32>) -> IoResult<TokioTcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_reuse_port(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(backlog.unwrap_or(1024) as _)?;
+    TokioTcpListener::from_std(socket.into())
+}
+
+/// Accept tasks of the sockets bound with `SO_REUSEPORT` or for dual-stack.
+struct Shards {
+    /// Accepted connections with the index of the holding of their sockets.
+    rx: mpsc::Receiver<(usize, IoResult<(TcpStream, SocketAddr)>)>,
+    listeners: Vec<Arc<TokioTcpListener>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+impl Shards {
+    fn spawn(listeners: Vec<(usize, Arc<TokioTcpListener>)>) -> Self {
+        let (tx, rx) = mpsc::channel(listeners.len() * 64);
+        let tasks = listeners
+            .iter()
+            .map(|(holding, listener)| {
+                let (holding, listener, tx) = (*holding, listener.clone(), tx.clone());
+                tokio::spawn(async move {
+                    // Errors such as running out of file descriptors usually persist for a while, accepting is
+                    // retried with a growing delay instead of spinning.
+                    let mut backoff = Duration::ZERO;
+                    loop {
+                        let accepted = listener.accept().await;
+                        backoff = match &accepted {
+                            Ok(_) => Duration::ZERO,
+                            Err(_) => (backoff * 2).clamp(Duration::from_millis(5), Duration::from_secs(1)),
+                        };
+                        if tx.send((holding, accepted)).await.is_err() {
+                            break;
+                        }
+                        if !backoff.is_zero() {
+                            tokio::time::sleep(backoff).await;
+                        }
+                    }
+                })
+            })
+            .collect();
+        Self {
+            rx,
+            listeners: listeners.into_iter().map(|(_, listener)| listener).collect(),
+            tasks,
+        }
+    }
+}
+impl Drop for Shards {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl Holding {
+    fn tcp(local_addr: SocketAddr) -> Self {
+        Holding {
+            local_addr: local_addr.into(),
+            #[cfg(not(feature = "http2-cleartext"))]
+            http_versions: vec![Version::HTTP_11],
+            #[cfg(feature = "http2-cleartext")]
+            http_versions: vec![Version::HTTP_11, Version::HTTP_2],
+            http_scheme: Scheme::HTTP,
+        }
+    }
+}
+
 /// `TcpAcceptor` is used to accept a TCP connection.
 pub struct TcpAcceptor {
-    inner: TokioTcpListener,
+    inner: Arc<TokioTcpListener>,
     holdings: Vec<Holding>,
+    shards: Option<Shards>,
 }
 
 impl TcpAcceptor {
     /// Get the inner `TokioTcpListener`.
+    ///
+    /// If the listener is sharded, this is the first of the bound sockets.
     pub fn inner(&self) -> &TokioTcpListener {
         &self.inner
     }
@@ -176,25 +414,31 @@ impl TcpAcceptor {
     /// Sets the value for the `IP_TTL` option on this socket.
     ///
     /// This value sets the time-to-live field that is used in every packet sent
-    /// from this socket.
+    /// from this socket. If the listener is sharded, all sockets are changed.
     pub fn set_ttl(&self, ttl: u32) -> IoResult<()> {
-        self.inner.set_ttl(ttl)
+        match &self.shards {
+            Some(shards) => shards.listeners.iter().try_for_each(|listener| listener.set_ttl(ttl)),
+            None => self.inner.set_ttl(ttl),
+        }
     }
 }
 
 impl TryFrom<TokioTcpListener> for TcpAcceptor {
     type Error = IoError;
     fn try_from(inner: TokioTcpListener) -> Result<Self, Self::Error> {
-        let holdings = vec![Holding {
-            local_addr: inner.local_addr()?.into(),
-            #[cfg(not(feature = "http2-cleartext"))]
-            http_versions: vec![Version::HTTP_11],
-            #[cfg(feature = "http2-cleartext")]
-            http_versions: vec![Version::HTTP_11, Version::HTTP_2],
-            http_scheme: Scheme::HTTP,
-        }];
+        Self::try_from(Arc::new(inner))
+    }
+}
+impl TryFrom<Arc<TokioTcpListener>> for TcpAcceptor {
+    type Error = IoError;
+    fn try_from(inner: Arc<TokioTcpListener>) -> Result<Self, Self::Error> {
+        let holdings = vec![Holding::tcp(inner.local_addr()?)];
 
-        Ok(TcpAcceptor { inner, holdings })
+        Ok(TcpAcceptor {
+            inner,
+            holdings,
+            shards: None,
+        })
     }
 }
 
@@ -208,8 +452,21 @@ impl Acceptor for TcpAcceptor {
 
     #[inline]
     async fn accept(&mut self, fuse_factory: Option<ArcFuseFactory>) -> IoResult<Accepted<Self::Conn>> {
-        self.inner.accept().await.map(move |(conn, remote_addr)| {
-            let local_addr = self.holdings[0].local_addr.clone();
+        let (holding, accepted) = match &mut self.shards {
+            Some(shards) => shards
+                .rx
+                .recv()
+                .await
+                .unwrap_or_else(|| (0, Err(IoError::other("tcp shards are stopped")))),
+            None => (0, self.inner.accept().await),
+        };
+        // Only dual-stack acceptors have more than one holding.
+        let dual_stack = self.holdings.len() > 1;
+        accepted.map(move |(conn, mut remote_addr)| {
+            if dual_stack {
+                remote_addr.set_ip(remote_addr.ip().to_canonical());
+            }
+            let local_addr = self.holdings[holding].local_addr.clone();
             Accepted {
                 conn: StraightStream::new(
                     conn,
@@ -248,4 +505,54 @@ mod tests {
         let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
         assert_eq!(conn.read_i32().await.unwrap(), 150);
     }
+
+    #[tokio::test]
+    async fn test_tcp_listener_dual_stack() {
+        let mut acceptor = TcpListener::dual_stack(0).bind().await;
+        let holdings = acceptor.holdings();
+        let port = holdings[0].local_addr.clone().into_std().unwrap().port();
+        assert_ne!(port, 0);
+        if holdings.len() == 2 {
+            assert!(holdings[0].local_addr.is_ipv6());
+            assert!(holdings[1].local_addr.is_ipv4());
+            assert_eq!(holdings[1].local_addr.clone().into_std().unwrap().port(), port);
+        }
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await.unwrap();
+            stream.write_i32(150).await.unwrap();
+        });
+
+        let Accepted {
+            mut conn, remote_addr, ..
+        } = acceptor.accept(None).await.unwrap();
+        assert!(remote_addr.is_ipv4());
+        assert_eq!(conn.read_i32().await.unwrap(), 150);
+    }
+
+    #[cfg(all(feature = "socket2", unix))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_tcp_listener_shards() {
+        let mut acceptor = TcpListener::new("127.0.0.1:0").shards(4).bind().await;
+        let addr = acceptor.local_addr().unwrap();
+        assert_ne!(addr.port(), 0);
+        tokio::spawn(async move {
+            for i in 0..16 {
+                let mut stream = TcpStream::connect(addr).await.unwrap();
+                stream.write_i32(i).await.unwrap();
+            }
+        });
+
+        let mut received = Vec::new();
+        for _ in 0..16 {
+            let Accepted { mut conn, .. } = acceptor.accept(None).await.unwrap();
+            received.push(conn.read_i32().await.unwrap());
+        }
+        received.sort();
+        assert_eq!(received, (0..16).collect::<Vec<_>>());
+
+        acceptor.set_ttl(32).unwrap();
+        for listener in &acceptor.shards.as_ref().unwrap().listeners {
+            assert_eq!(listener.ttl().unwrap(), 32);
+        }
+    }
 }
@@ -0,0 +1,249 @@
+//! Dependency injection container.
+//!
+//! A [`Container`] holds the constructors of services, keyed by their type. Use it as a hoop, then handlers request
+//! the services from the depot with [`Depot::resolve`] and [`Depot::resolve_mut`]:
+//!
+//! - A **singleton** is built on first use and shared by all requests, such as a database pool.
+//! - A **per-request** service is built on first use in a request and injected into the [`Depot`], so it is dropped
+//!   with the depot after the request is handled, such as a database session.
+//!
+//! Values injected into the depot with [`Depot::inject`] take precedence over the registered constructors, which
+//! makes it easy to replace services in tests.
+//!
+//! # Example
+//!
+//! ```
+//! use std::convert::Infallible;
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//!
+//! use salvo_core::container::Container;
+//! use salvo_core::prelude::*;
+//!
+//! struct Pool {
+//!     sessions: AtomicU64,
+//! }
+//! struct Session {
+//!     id: u64,
+//! }
+//!
+//! #[handler]
+//! async fn hello(depot: &mut Depot) -> Result<String, salvo_core::Error> {
+//!     let session = depot.resolve::<Session>().await?;
+//!     Ok(format!("session {}", session.id))
+//! }
+//!
+//! let container = Container::new()
+//!     .instance(Pool {
+//!         sessions: AtomicU64::new(0),
+//!     })
+//!     .per_request(|depot: &Depot| {
+//!         let pool = depot.obtain::<Container>().ok().and_then(|c| c.get::<Pool>());
+//!         let id = pool.map(|pool| pool.sessions.fetch_add(1, Ordering::Relaxed)).unwrap_or_default();
+//!         async move { Ok::<_, Infallible>(Session { id }) }
+//!     });
+//! let router = Router::new().hoop(container).get(hello);
+//! ```
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+
+use futures_util::future::{BoxFuture, FutureExt};
+use tokio::sync::OnceCell;
+
+use crate::depot::BoxedValue;
+use crate::http::{Request, Response};
+use crate::{async_trait, BoxedError, Depot, Error, FlowCtrl, Handler};
+
+type Constructor = dyn Fn(&Depot) -> BoxFuture<'static, Result<BoxedValue, Error>> + Send + Sync;
+
+enum Provider {
+    Singleton {
+        constructor: Option<Box<Constructor>>,
+        value: OnceCell<BoxedValue>,
+    },
+    PerRequest(Box<Constructor>),
+}
+
+/// Registry of service constructors, keyed by the type of the service.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Default)]
+pub struct Container {
+    providers: HashMap<TypeId, Arc<Provider>>,
+}
+impl Debug for Container {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Container")
+            .field("providers", &self.providers.len())
+            .finish()
+    }
+}
+impl Container {
+    /// Create a new empty `Container`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a singleton which is already built.
+    #[inline]
+    pub fn instance<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        let provider = Provider::Singleton {
+            constructor: None,
+            value: OnceCell::new_with(Some(Box::new(value) as BoxedValue)),
+        };
+        self.providers.insert(TypeId::of::<T>(), Arc::new(provider));
+        self
+    }
+
+    /// Register the constructor of a singleton, it is called once on first use.
+    ///
+    /// If it fails, the error is returned to the caller and the next use calls it again.
+    pub fn singleton<T, F, Fut, E>(mut self, constructor: F) -> Self
+    where
+        T: Any + Send + Sync,
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        E: Into<BoxedError>,
+    {
+        let provider = Provider::Singleton {
+            constructor: Some(erase(move |_: &Depot| constructor())),
+            value: OnceCell::new(),
+        };
+        self.providers.insert(TypeId::of::<T>(), Arc::new(provider));
+        self
+    }
+
+    /// Register the constructor of a per-request service, it is called once in each request which uses the service.
+    ///
+    /// The constructor gets the depot, so it can read the values set by previous hoops and other services, and
+    /// returns a future which does not borrow it.
+    pub fn per_request<T, F, Fut, E>(mut self, constructor: F) -> Self
+    where
+        T: Any + Send + Sync,
+        F: Fn(&Depot) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        E: Into<BoxedError>,
+    {
+        let provider = Provider::PerRequest(erase(constructor));
+        self.providers.insert(TypeId::of::<T>(), Arc::new(provider));
+        self
+    }
+
+    /// Get a singleton if it is built.
+    #[inline]
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        match &**self.providers.get(&TypeId::of::<T>())? {
+            Provider::Singleton { value, .. } => value.get()?.downcast_ref(),
+            Provider::PerRequest(_) => None,
+        }
+    }
+
+    /// Build the service with type `id`, returns the value of a per-request service and `None` of a singleton,
+    /// which is kept in the container.
+    pub(crate) async fn construct(&self, id: TypeId, depot: &Depot) -> Result<Option<BoxedValue>, Error> {
+        match self.providers.get(&id).map(|provider| &**provider) {
+            Some(Provider::Singleton { constructor, value }) => {
+                value
+                    .get_or_try_init(|| match constructor {
+                        Some(constructor) => constructor(depot),
+                        None => async { Err(Error::other("singleton is not built")) }.boxed(),
+                    })
+                    .await?;
+                Ok(None)
+            }
+            Some(Provider::PerRequest(constructor)) => constructor(depot).await.map(Some),
+            None => Err(Error::other("service is not registered in the container")),
+        }
+    }
+}
+
+fn erase<T, F, Fut, E>(constructor: F) -> Box<Constructor>
+where
+    T: Any + Send + Sync,
+    F: Fn(&Depot) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+    E: Into<BoxedError>,
+{
+    Box::new(move |depot: &Depot| {
+        constructor(depot)
+            .map(|result| match result {
+                Ok(value) => Ok(Box::new(value) as BoxedValue),
+                Err(e) => Err(Error::other(e)),
+            })
+            .boxed()
+    })
+}
+
+#[async_trait]
+impl Handler for Container {
+    #[inline]
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        depot.inject(self.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    static BUILT: AtomicUsize = AtomicUsize::new(0);
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Config(&'static str);
+    struct Session(usize);
+    impl Drop for Session {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_container() {
+        #[handler]
+        async fn hello(depot: &mut Depot) -> Result<String, Error> {
+            depot.resolve_mut::<Session>().await?.0 += 10;
+            let session = depot.resolve::<Session>().await?.0;
+            let config = depot.resolve::<Config>().await?.0;
+            assert!(depot.resolve_mut::<Config>().await.is_err());
+            assert!(depot.resolve::<String>().await.is_err());
+            Ok(format!("{config} {session}"))
+        }
+        let container = Container::new()
+            .singleton(|| async {
+                BUILT.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, Infallible>(Config("hello"))
+            })
+            .per_request(|_: &Depot| async {
+                let id = BUILT.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, Infallible>(Session(id))
+            });
+        let service = Service::new(Router::new().hoop(container).get(hello));
+
+        let content = TestClient::get("http://127.0.0.1:5800")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "hello 10");
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+        let content = TestClient::get("http://127.0.0.1:5800")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "hello 12");
+        assert_eq!(BUILT.load(Ordering::SeqCst), 3);
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+    }
+}
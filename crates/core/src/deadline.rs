@@ -0,0 +1,135 @@
+//! Request deadline propagated to outbound calls.
+//!
+//! A [`Deadline`] stored in the [`Depot`] is the instant by which the current request must be answered. It is set
+//! by the timeout middleware, which also honors the deadline of the caller sent in the [`X-Request-Timeout`]
+//! header, and outbound calls such as the proxy and the HTTP client of `salvo-proxy` give up when it expires. They
+//! send the remaining time in the `X-Request-Timeout` header, so the budget decreases across hops in a service mesh.
+//!
+//! [`X-Request-Timeout`]: REQUEST_TIMEOUT
+use std::time::{Duration, Instant};
+
+use crate::http::header::{HeaderMap, HeaderName, HeaderValue};
+use crate::Depot;
+
+/// The header carrying the remaining time of the caller's deadline, in milliseconds.
+///
+/// Values with a `ms` or `s` suffix are also accepted, such as `1500ms` or `2s`.
+pub const REQUEST_TIMEOUT: HeaderName = HeaderName::from_static("x-request-timeout");
+
+/// The instant by which a request must be answered.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// Create a new `Deadline` expiring at `instant`.
+    #[inline]
+    pub fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    /// Create a new `Deadline` expiring after `timeout` from now.
+    #[inline]
+    pub fn after(timeout: Duration) -> Self {
+        Self(now() + timeout)
+    }
+
+    /// Parse the deadline sent in the [`REQUEST_TIMEOUT`] header, if any.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = headers.get(REQUEST_TIMEOUT)?.to_str().ok()?.trim();
+        let timeout = if let Some(millis) = value.strip_suffix("ms") {
+            Duration::from_millis(millis.trim().parse().ok()?)
+        } else if let Some(secs) = value.strip_suffix('s') {
+            Duration::from_secs(secs.trim().parse().ok()?)
+        } else {
+            Duration::from_millis(value.parse().ok()?)
+        };
+        Some(Self::after(timeout))
+    }
+
+    /// Get the instant when the deadline expires.
+    #[inline]
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Get the remaining time, it is zero if the deadline has expired.
+    #[inline]
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(now())
+    }
+
+    /// Returns `true` if the deadline has expired.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Set the remaining time in the [`REQUEST_TIMEOUT`] header of an outbound request.
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        headers.insert(REQUEST_TIMEOUT, HeaderValue::from(self.remaining().as_millis() as u64));
+    }
+}
+
+/// Read the time from tokio's clock, so deadlines follow the paused time of tests.
+#[inline]
+fn now() -> Instant {
+    tokio::time::Instant::now().into_std()
+}
+
+/// Extension trait for the [`Deadline`] of the current request stored in the [`Depot`].
+pub trait DeadlineDepotExt {
+    /// Get the deadline of the current request.
+    fn deadline(&self) -> Option<Deadline>;
+
+    /// Set the deadline of the current request, it is ignored if an earlier deadline is already set.
+    ///
+    /// Returns the effective deadline.
+    fn set_deadline(&mut self, deadline: Deadline) -> Deadline;
+}
+
+impl DeadlineDepotExt for Depot {
+    #[inline]
+    fn deadline(&self) -> Option<Deadline> {
+        self.obtain::<Deadline>().ok().copied()
+    }
+
+    fn set_deadline(&mut self, deadline: Deadline) -> Deadline {
+        let deadline = match self.deadline() {
+            Some(current) => current.min(deadline),
+            None => deadline,
+        };
+        self.inject(deadline);
+        deadline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline() {
+        let mut headers = HeaderMap::new();
+        assert!(Deadline::from_headers(&headers).is_none());
+        for value in ["1500", "1500ms", "2s"] {
+            headers.insert(REQUEST_TIMEOUT, HeaderValue::from_static(value));
+            let remaining = Deadline::from_headers(&headers).unwrap().remaining();
+            assert!(remaining > Duration::from_millis(1000) && remaining <= Duration::from_secs(2));
+        }
+        headers.insert(REQUEST_TIMEOUT, HeaderValue::from_static("soon"));
+        assert!(Deadline::from_headers(&headers).is_none());
+
+        let mut depot = Depot::new();
+        let late = Deadline::after(Duration::from_secs(10));
+        let early = Deadline::after(Duration::from_secs(1));
+        assert_eq!(depot.set_deadline(late), late);
+        assert_eq!(depot.set_deadline(early), early);
+        assert_eq!(depot.set_deadline(late), early);
+        assert_eq!(depot.deadline(), Some(early));
+
+        early.apply(&mut headers);
+        let millis: u64 = headers.get(REQUEST_TIMEOUT).unwrap().to_str().unwrap().parse().unwrap();
+        assert!(millis <= 1000 && millis > 500);
+        assert!(Deadline::at(Instant::now()).is_expired());
+    }
+}
@@ -1,6 +1,13 @@
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::fmt::{self, Formatter};
+use std::future::Future;
+
+use futures_util::future::{BoxFuture, FutureExt};
+
+use crate::container::Container;
+use crate::tasks::Deferred;
+use crate::Error;
 
 /// Store temp data for current request.
 ///
@@ -30,15 +37,99 @@ use std::fmt::{self, Formatter};
 ///     Server::new(acceptor).serve(router).await;
 /// }
 /// ```
-
+///
+/// Values can also be keyed by their type with [`Depot::inject`] and [`Depot::obtain`]. Type-keyed values are
+/// stored apart from the string-keyed ones, the first few are kept inline and looked up without hashing. They are
+/// still found by the string-keyed methods, such as [`Depot::get`], with the `Debug` output of their `TypeId` as
+/// the key, like in the previous versions. Services
+/// registered in a [`Container`] are requested by their type with [`Depot::resolve`].
+///
+/// Resources such as transactions, temp dirs and locks are created on first use with
+/// [`Depot::get_or_try_init_async`], and finalized by the hooks registered with [`Depot::on_cleanup`].
 #[derive(Default)]
 pub struct Depot {
     map: HashMap<String, Box<dyn Any + Send + Sync>>,
+    types: TypeMap,
+    cleanups: Vec<(TypeId, CleanupHook)>,
 }
 
-#[inline]
-fn type_key<T: 'static>() -> String {
-    format!("{:?}", TypeId::of::<T>())
+type CleanupHook = Box<dyn FnOnce(BoxedValue) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Number of type-keyed values stored inline, most requests only inject a few values.
+const INLINE_TYPES: usize = 4;
+
+pub(crate) type BoxedValue = Box<dyn Any + Send + Sync>;
+
+/// Map of type-keyed values, the lookup compares `TypeId` one by one, which is faster than hashing for a few entries.
+#[derive(Default)]
+struct TypeMap {
+    inline: [Option<(TypeId, BoxedValue)>; INLINE_TYPES],
+    overflow: Vec<(TypeId, BoxedValue)>,
+}
+
+impl TypeMap {
+    #[inline]
+    fn entries(&self) -> impl Iterator<Item = &(TypeId, BoxedValue)> {
+        self.inline.iter().flatten().chain(self.overflow.iter())
+    }
+
+    #[inline]
+    fn get(&self, id: TypeId) -> Option<&BoxedValue> {
+        self.entries().find(|(key, _)| *key == id).map(|(_, value)| value)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, id: TypeId) -> Option<&mut BoxedValue> {
+        self.inline
+            .iter_mut()
+            .flatten()
+            .chain(self.overflow.iter_mut())
+            .find(|(key, _)| *key == id)
+            .map(|(_, value)| value)
+    }
+
+    fn insert(&mut self, id: TypeId, value: BoxedValue) {
+        if let Some(slot) = self.get_mut(id) {
+            *slot = value;
+        } else if let Some(slot) = self.inline.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some((id, value));
+        } else {
+            self.overflow.push((id, value));
+        }
+    }
+
+    fn remove(&mut self, id: TypeId) -> Option<BoxedValue> {
+        if let Some(slot) = self
+            .inline
+            .iter_mut()
+            .find(|slot| matches!(slot, Some((key, _)) if *key == id))
+        {
+            return slot.take().map(|(_, value)| value);
+        }
+        let index = self.overflow.iter().position(|(key, _)| *key == id)?;
+        Some(self.overflow.swap_remove(index).1)
+    }
+
+    #[inline]
+    fn contains(&self, id: TypeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.entries().count()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.overflow.capacity()
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inline = Default::default();
+        self.overflow.clear();
+    }
 }
 
 impl Depot {
@@ -47,10 +138,17 @@ impl Depot {
     /// The depot is initially created with a capacity of 0, so it will not allocate until it is first inserted into.
     #[inline]
     pub fn new() -> Depot {
-        Depot { map: HashMap::new() }
+        Depot {
+            map: HashMap::new(),
+            types: TypeMap::default(),
+            cleanups: Vec::new(),
+        }
     }
 
-    /// Get reference to depot inner map.
+    /// Get reference to depot inner map of the values inserted with string keys.
+    ///
+    /// Values injected by type are kept in an inline storage, they are looked up with [`Depot::obtain`], or with
+    /// [`Depot::get`] and the `Debug` output of their `TypeId`.
     #[inline]
     pub fn inner(&self) -> &HashMap<String, Box<dyn Any + Send + Sync>> {
         &self.map
@@ -63,18 +161,40 @@ impl Depot {
     pub fn with_capacity(capacity: usize) -> Self {
         Depot {
             map: HashMap::with_capacity(capacity),
+            types: TypeMap::default(),
+            cleanups: Vec::new(),
         }
     }
     /// Returns the number of elements the depot can hold without reallocating.
     #[inline]
     pub fn capacity(&self) -> usize {
-        self.map.capacity()
+        self.map.capacity() + self.types.capacity()
+    }
+
+    /// Removes all values, keeping the allocated memory for reuse.
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.cleanups.clear();
+        self.map.clear();
+        self.types.clear();
+    }
+
+    /// Get the type of the injected value whose key is `key`, the key of an injected value is the `Debug` output of
+    /// its `TypeId`.
+    fn injected_type(&self, key: &str) -> Option<TypeId> {
+        if !key.starts_with("TypeId") {
+            return None;
+        }
+        self.types
+            .entries()
+            .map(|(id, _)| *id)
+            .find(|id| format!("{id:?}") == key)
     }
 
     /// Inject a value into the depot.
     #[inline]
     pub fn inject<V: Any + Send + Sync>(&mut self, value: V) -> &mut Self {
-        self.map.insert(type_key::<V>(), Box::new(value));
+        self.types.insert(TypeId::of::<V>(), Box::new(value));
         self
     }
 
@@ -84,7 +204,11 @@ impl Depot {
     /// Returns `Err(Some(Box<dyn Any + Send + Sync>))` if value is present in depot but downcast failed.
     #[inline]
     pub fn obtain<T: Any + Send + Sync>(&self) -> Result<&T, Option<&Box<dyn Any + Send + Sync>>> {
-        self.get(&type_key::<T>())
+        if let Some(value) = self.types.get(TypeId::of::<T>()) {
+            value.downcast_ref::<T>().ok_or(Some(value))
+        } else {
+            Err(None)
+        }
     }
 
     /// Obtain a mutable reference to a value previous inject to the depot.
@@ -93,7 +217,132 @@ impl Depot {
     /// Returns `Err(Some(Box<dyn Any + Send + Sync>))` if value is present in depot but downcast failed.
     #[inline]
     pub fn obtain_mut<T: Any + Send + Sync>(&mut self) -> Result<&mut T, Option<&mut Box<dyn Any + Send + Sync>>> {
-        self.get_mut(&type_key::<T>())
+        if let Some(value) = self.types.get_mut(TypeId::of::<T>()) {
+            if value.downcast_mut::<T>().is_some() {
+                Ok(value.downcast_mut::<T>().expect("downcast_mut should not be failed"))
+            } else {
+                Err(Some(value))
+            }
+        } else {
+            Err(None)
+        }
+    }
+
+    /// Resolve a service registered in the [`Container`] of the request, or a value injected into the depot.
+    ///
+    /// A per-request service is built on first use and injected into the depot, a singleton is built on first use
+    /// and kept in the container. Returns an error if no container is set, the service is not registered or its
+    /// constructor fails.
+    pub async fn resolve<T: Any + Send + Sync>(&mut self) -> Result<&T, Error> {
+        let id = TypeId::of::<T>();
+        if !self.types.contains(id) {
+            let container = self
+                .obtain::<Container>()
+                .map_err(|_| Error::other("container is not set"))?;
+            match container.construct(id, self).await? {
+                Some(value) => self.types.insert(id, value),
+                None => {
+                    return self
+                        .obtain::<Container>()
+                        .ok()
+                        .and_then(Container::get::<T>)
+                        .ok_or_else(|| Error::other("singleton is not built"));
+                }
+            }
+        }
+        self.obtain::<T>()
+            .map_err(|_| Error::other("service has a different type"))
+    }
+
+    /// Resolve a per-request service registered in the [`Container`] of the request, or a value injected into the
+    /// depot, mutably.
+    ///
+    /// Returns an error for singletons, which are shared by all requests.
+    pub async fn resolve_mut<T: Any + Send + Sync>(&mut self) -> Result<&mut T, Error> {
+        let id = TypeId::of::<T>();
+        if !self.types.contains(id) {
+            let container = self
+                .obtain::<Container>()
+                .map_err(|_| Error::other("container is not set"))?;
+            match container.construct(id, self).await? {
+                Some(value) => self.types.insert(id, value),
+                None => return Err(Error::other("singleton can not be borrowed mutably")),
+            }
+        }
+        self.obtain_mut::<T>()
+            .map_err(|_| Error::other("service has a different type"))
+    }
+
+    /// Get a mutable reference to the value of type `T`, it is created by `factory` and injected into the depot if
+    /// it is not present.
+    ///
+    /// If `factory` fails, the error is returned and nothing is injected, so the next call tries again.
+    pub async fn get_or_try_init_async<T, F, Fut, E>(&mut self, factory: F) -> Result<&mut T, E>
+    where
+        T: Any + Send + Sync,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if !self.contains::<T>() {
+            let value = factory().await?;
+            self.inject(value);
+        }
+        Ok(self.obtain_mut::<T>().expect("value should be injected by the factory"))
+    }
+
+    /// Register a hook finalizing the value of type `T` after the request is handled, it replaces the previous hook
+    /// of the type.
+    ///
+    /// The hook takes the value if it is still in the depot after the response is sent, even if a handler panicked,
+    /// or when the depot is dropped because the request was cancelled. It runs with the jobs deferred by
+    /// [`Response::on_sent`](crate::Response::on_sent), which [`Server`](crate::Server) waits for when it is stopped.
+    /// A value taken out of the depot, such as a committed transaction taken with [`Depot::scrape`], is not passed
+    /// to the hook.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// struct Transaction;
+    /// impl Transaction {
+    ///     async fn rollback(self) {}
+    /// }
+    ///
+    /// #[handler]
+    /// async fn begin(depot: &mut Depot) -> Result<(), StatusError> {
+    ///     depot.get_or_try_init_async(|| async { Ok::<_, StatusError>(Transaction) }).await?;
+    ///     depot.on_cleanup(|tx: Transaction| tx.rollback());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn on_cleanup<T, F, Fut>(&mut self, hook: F) -> &mut Self
+    where
+        T: Any + Send + Sync,
+        F: FnOnce(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let id = TypeId::of::<T>();
+        let hook: CleanupHook = Box::new(move |value: BoxedValue| match value.downcast::<T>() {
+            Ok(value) => hook(*value).boxed(),
+            Err(_) => async {}.boxed(),
+        });
+        match self.cleanups.iter_mut().find(|(key, _)| *key == id) {
+            Some(slot) => slot.1 = hook,
+            None => self.cleanups.push((id, hook)),
+        }
+        self
+    }
+
+    /// Take the cleanup hooks with the values they finalize, they are spawned when the returned jobs are dropped.
+    pub(crate) fn take_cleanups(&mut self) -> Deferred {
+        let mut deferred = Deferred::default();
+        for (id, hook) in std::mem::take(&mut self.cleanups) {
+            if let Some(value) = self.types.remove(id) {
+                deferred.push(hook(value));
+            }
+        }
+        deferred
     }
 
     /// Inserts a key-value pair into the depot.
@@ -110,14 +359,14 @@ impl Depot {
     /// Check is there a value stored in depot with this key.
     #[inline]
     pub fn contains_key(&self, key: &str) -> bool {
-        self.map.contains_key(key)
+        self.map.contains_key(key) || self.injected_type(key).is_some()
     }
     /// Check is there a value is injected to the depot.
     ///
     /// **Note: This is only check injected value.**
     #[inline]
     pub fn contains<T: Any + Send + Sync>(&self) -> bool {
-        self.map.contains_key(&type_key::<T>())
+        self.types.contains(TypeId::of::<T>())
     }
 
     /// Immutably borrows value from depot.
@@ -126,7 +375,11 @@ impl Depot {
     /// Returns `Err(Some(Box<dyn Any + Send + Sync>))` if value is present in depot but downcast failed.
     #[inline]
     pub fn get<V: Any + Send + Sync>(&self, key: &str) -> Result<&V, Option<&Box<dyn Any + Send + Sync>>> {
-        if let Some(value) = self.map.get(key) {
+        let value = match self.map.get(key) {
+            Some(value) => Some(value),
+            None => self.injected_type(key).and_then(|id| self.types.get(id)),
+        };
+        if let Some(value) = value {
             value.downcast_ref::<V>().ok_or(Some(value))
         } else {
             Err(None)
@@ -141,9 +394,13 @@ impl Depot {
         &mut self,
         key: &str,
     ) -> Result<&mut V, Option<&mut Box<dyn Any + Send + Sync>>> {
-        if let Some(value) = self.map.get_mut(key) {
+        let value = match self.injected_type(key).filter(|_| !self.map.contains_key(key)) {
+            Some(id) => self.types.get_mut(id),
+            None => self.map.get_mut(key),
+        };
+        if let Some(value) = value {
             if value.downcast_mut::<V>().is_some() {
-                Ok(value.downcast_mut::<V>().expect("downcast_mut shuold not be failed"))
+                Ok(value.downcast_mut::<V>().expect("downcast_mut should not be failed"))
             } else {
                 Err(Some(value))
             }
@@ -155,7 +412,11 @@ impl Depot {
     /// Remove value from depot and returning the value at the key if the key was previously in the depot.
     #[inline]
     pub fn remove<V: Any + Send + Sync>(&mut self, key: &str) -> Result<V, Option<Box<dyn Any + Send + Sync>>> {
-        if let Some(value) = self.map.remove(key) {
+        let value = match self.map.remove(key) {
+            Some(value) => Some(value),
+            None => self.injected_type(key).and_then(|id| self.types.remove(id)),
+        };
+        if let Some(value) = value {
             value.downcast::<V>().map(|b| *b).map_err(Some)
         } else {
             Err(None)
@@ -165,24 +426,39 @@ impl Depot {
     /// Delete the key from depot, if the key is not present, return `false`.
     #[inline]
     pub fn delete(&mut self, key: &str) -> bool {
-        self.map.remove(key).is_some()
+        self.map.remove(key).is_some() || self.injected_type(key).and_then(|id| self.types.remove(id)).is_some()
     }
 
     /// Remove value from depot and returning the value if the type was previously in the depot.
     #[inline]
     pub fn scrape<T: Any + Send + Sync>(&mut self) -> Result<T, Option<Box<dyn Any + Send + Sync>>> {
-        self.remove(&type_key::<T>())
+        if let Some(value) = self.types.remove(TypeId::of::<T>()) {
+            value.downcast::<T>().map(|b| *b).map_err(Some)
+        } else {
+            Err(None)
+        }
+    }
+}
+
+impl Drop for Depot {
+    fn drop(&mut self) {
+        drop(self.take_cleanups());
     }
 }
 
 impl fmt::Debug for Depot {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Depot").field("keys", &self.map.keys()).finish()
+        f.debug_struct("Depot")
+            .field("keys", &self.map.keys())
+            .field("types", &self.types.len())
+            .finish()
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use crate::prelude::*;
     use crate::test::{ResponseExt, TestClient};
 
@@ -200,6 +476,95 @@ mod test {
         assert_eq!(depot.get_mut::<String>("one").unwrap(), &mut "ONE".to_owned());
     }
 
+    #[test]
+    fn test_depot_type_keyed() {
+        let mut depot = Depot::new();
+        depot
+            .inject(1u8)
+            .inject(2u16)
+            .inject(3u32)
+            .inject(4u64)
+            .inject(5u128)
+            .inject("six".to_owned());
+        assert_eq!(*depot.obtain::<u8>().unwrap(), 1);
+        assert_eq!(*depot.obtain::<u128>().unwrap(), 5);
+        assert_eq!(depot.obtain::<String>().unwrap(), "six");
+        assert!(depot.obtain::<i8>().is_err());
+        assert!(depot.inner().is_empty());
+
+        // Injected values are found by the string-keyed methods with the key of their type.
+        let key = format!("{:?}", TypeId::of::<u32>());
+        assert!(depot.contains_key(&key));
+        assert_eq!(*depot.get::<u32>(&key).unwrap(), 3);
+        *depot.get_mut::<u32>(&key).unwrap() += 1;
+        assert_eq!(depot.remove::<u32>(&key).unwrap(), 4);
+        assert!(!depot.contains::<u32>());
+        assert!(depot.delete(&format!("{:?}", TypeId::of::<u64>())));
+        assert!(!depot.contains_key("TypeId"));
+        depot.inject(3u32).inject(4u64);
+
+        depot.inject(10u8);
+        *depot.obtain_mut::<u128>().unwrap() += 1;
+        assert_eq!(*depot.obtain::<u8>().unwrap(), 10);
+        assert_eq!(*depot.obtain::<u128>().unwrap(), 6);
+
+        assert_eq!(depot.scrape::<u16>().unwrap(), 2);
+        assert!(!depot.contains::<u16>());
+        depot.inject(7i32);
+        assert_eq!(*depot.obtain::<i32>().unwrap(), 7);
+        assert_eq!(depot.scrape::<String>().unwrap(), "six");
+        assert!(depot.contains::<u64>());
+
+        depot.clear();
+        assert!(!depot.contains::<u8>());
+        assert!(!depot.contains::<i32>());
+    }
+
+    #[tokio::test]
+    async fn test_depot_cleanup() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let mut depot = Depot::new();
+        let value = depot
+            .get_or_try_init_async(|| async { Ok::<_, std::io::Error>(1u32) })
+            .await
+            .unwrap();
+        *value += 1;
+        let value = depot
+            .get_or_try_init_async(|| async { Err::<u32, _>(std::io::Error::other("called again")) })
+            .await
+            .unwrap();
+        assert_eq!(*value, 2);
+        depot.on_cleanup(move |value: u32| async move {
+            tx.send(value).unwrap();
+        });
+        depot.on_cleanup(|_: String| async { unreachable!() });
+        depot.inject("taken".to_owned());
+        assert_eq!(depot.scrape::<String>().unwrap(), "taken");
+        drop(depot);
+        assert_eq!(rx.await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_depot_cleanup_after_sent() {
+        static CLEANED: AtomicUsize = AtomicUsize::new(0);
+        #[handler]
+        async fn begin(depot: &mut Depot) -> &'static str {
+            depot.inject(1u32);
+            depot.on_cleanup(|_: u32| async {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                CLEANED.fetch_add(1, Ordering::SeqCst);
+            });
+            "begun"
+        }
+        let service = Service::new(Router::new().get(begin));
+
+        let mut res = TestClient::get("http://127.0.0.1:5800").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "begun");
+        drop(res);
+        crate::tasks::drain_deferred().await;
+        assert_eq!(CLEANED.load(Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn test_middleware_use_depot() {
         #[handler]
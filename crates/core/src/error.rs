@@ -1,9 +1,10 @@
+use std::borrow::Cow;
 use std::convert::Infallible;
 use std::error::Error as StdError;
 use std::fmt::{self, Display, Formatter};
 use std::io::Error as IoError;
 
-use crate::http::{ParseError, StatusError};
+use crate::http::{ParseError, StatusCode, StatusError};
 use crate::{Response, Scribe};
 
 /// `BoxedError` is a boxed error type that can be used as a trait object.
@@ -37,6 +38,28 @@ pub enum Error {
     #[cfg(feature = "eyre")]
     #[cfg_attr(docsrs, doc(cfg(feature = "eyre")))]
     Eyre(eyre::Report),
+    /// Sqlx error.
+    #[cfg(feature = "sqlx")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sqlx")))]
+    Sqlx(sqlx::Error),
+    /// Reqwest error.
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+    Reqwest(reqwest::Error),
+    /// Error with a status code attached by [`Error::with_status`].
+    WithStatus {
+        /// Status code of the response.
+        status: StatusCode,
+        /// The original error.
+        source: Box<Error>,
+    },
+    /// Error with a context message attached by [`Error::context`].
+    Context {
+        /// Context message.
+        context: Cow<'static, str>,
+        /// The original error.
+        source: Box<Error>,
+    },
     /// Custom error that does not fall under any other error kind.
     Other(BoxedError),
 }
@@ -47,6 +70,59 @@ impl Error {
     pub fn other(error: impl Into<BoxedError>) -> Self {
         Self::Other(error.into())
     }
+
+    /// Attach a status code, which is used as the status of the response when the error is rendered.
+    ///
+    /// Invalid status codes are ignored.
+    pub fn with_status(self, status: impl IntoStatusCode) -> Self {
+        match status.into_status_code() {
+            Some(status) => Self::WithStatus {
+                status,
+                source: Box::new(self),
+            },
+            None => {
+                tracing::warn!("invalid status code attached to error");
+                self
+            }
+        }
+    }
+
+    /// Attach a context message, such as what the handler was doing when the error happened.
+    #[inline]
+    pub fn context(self, context: impl Into<Cow<'static, str>>) -> Self {
+        Self::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Get the status code of the response when the error is rendered.
+    ///
+    /// The status attached by [`Error::with_status`] is used if there is one, otherwise it is inferred from the
+    /// kind of the error, such as `400 Bad Request` for [`ParseError`] and `404 Not Found` for `sqlx::Error::RowNotFound`.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::WithStatus { status, .. } => *status,
+            Self::Context { source, .. } => source.status(),
+            Self::HttpStatus(e) => e.code,
+            Self::HttpParse(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "sqlx")]
+            Self::Sqlx(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND,
+            #[cfg(feature = "reqwest")]
+            Self::Reqwest(e) if e.is_timeout() => StatusCode::GATEWAY_TIMEOUT,
+            #[cfg(feature = "reqwest")]
+            Self::Reqwest(_) => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Get the error without the status codes and context messages attached to it.
+    pub fn root(&self) -> &Error {
+        match self {
+            Self::WithStatus { source, .. } | Self::Context { source, .. } => source.root(),
+            _ => self,
+        }
+    }
 }
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -63,12 +139,25 @@ impl Display for Error {
             Self::Anyhow(e) => Display::fmt(e, f),
             #[cfg(feature = "eyre")]
             Self::Eyre(e) => Display::fmt(e, f),
+            #[cfg(feature = "sqlx")]
+            Self::Sqlx(e) => Display::fmt(e, f),
+            #[cfg(feature = "reqwest")]
+            Self::Reqwest(e) => Display::fmt(e, f),
+            Self::WithStatus { source, .. } => Display::fmt(source, f),
+            Self::Context { context, source } => write!(f, "{context}: {source}"),
             Self::Other(e) => Display::fmt(e, f),
         }
     }
 }
 
-impl StdError for Error {}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::WithStatus { source, .. } | Self::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl From<Infallible> for Error {
     #[inline]
@@ -130,6 +219,24 @@ cfg_feature! {
         }
     }
 }
+cfg_feature! {
+    #![feature = "sqlx"]
+    impl From<sqlx::Error> for Error {
+        #[inline]
+        fn from(e: sqlx::Error) -> Error {
+            Error::Sqlx(e)
+        }
+    }
+}
+cfg_feature! {
+    #![feature = "reqwest"]
+    impl From<reqwest::Error> for Error {
+        #[inline]
+        fn from(e: reqwest::Error) -> Error {
+            Error::Reqwest(e)
+        }
+    }
+}
 cfg_feature! {
     #![feature = "eyre"]
     impl From<eyre::Report> for Error {
@@ -147,11 +254,122 @@ impl From<BoxedError> for Error {
     }
 }
 
+/// A type that can be converted into a [`StatusCode`], such as `404` or `StatusCode::NOT_FOUND`.
+pub trait IntoStatusCode {
+    /// Convert into a [`StatusCode`], returns `None` if it is invalid.
+    fn into_status_code(self) -> Option<StatusCode>;
+}
+impl IntoStatusCode for StatusCode {
+    #[inline]
+    fn into_status_code(self) -> Option<StatusCode> {
+        Some(self)
+    }
+}
+impl IntoStatusCode for u16 {
+    #[inline]
+    fn into_status_code(self) -> Option<StatusCode> {
+        StatusCode::from_u16(self).ok()
+    }
+}
+
+/// Extension for `Result` and `Option` to attach status codes and context messages, so handlers can use `?` and
+/// still get a sensible response.
+///
+/// `None` is converted to a `404 Not Found` error.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::ResultExt;
+///
+/// #[handler]
+/// async fn show_user(req: &mut Request) -> AppResult<String> {
+///     let id: u64 = req.param("id").with_status(StatusCode::BAD_REQUEST)?;
+///     let name = ["alice", "bob"].get(id as usize).context("user not found")?;
+///     Ok(name.to_string())
+/// }
+/// ```
+pub trait ResultExt<T> {
+    /// Attach a status code to the error.
+    fn with_status(self, status: impl IntoStatusCode) -> Result<T, Error>;
+
+    /// Attach a context message to the error.
+    fn context<C>(self, context: C) -> Result<T, Error>
+    where
+        C: Into<Cow<'static, str>>;
+
+    /// Attach a context message to the error, the message is only created if there is an error.
+    fn with_context<C, F>(self, context: F) -> Result<T, Error>
+    where
+        C: Into<Cow<'static, str>>,
+        F: FnOnce() -> C;
+}
+
+impl<T, E> ResultExt<T> for Result<T, E>
+where
+    E: Into<Error>,
+{
+    #[inline]
+    fn with_status(self, status: impl IntoStatusCode) -> Result<T, Error> {
+        self.map_err(|e| e.into().with_status(status))
+    }
+
+    #[inline]
+    fn context<C>(self, context: C) -> Result<T, Error>
+    where
+        C: Into<Cow<'static, str>>,
+    {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    #[inline]
+    fn with_context<C, F>(self, context: F) -> Result<T, Error>
+    where
+        C: Into<Cow<'static, str>>,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|e| e.into().context(context()))
+    }
+}
+
+impl<T> ResultExt<T> for Option<T> {
+    #[inline]
+    fn with_status(self, status: impl IntoStatusCode) -> Result<T, Error> {
+        self.ok_or_else(|| Error::HttpStatus(StatusError::not_found()).with_status(status))
+    }
+
+    #[inline]
+    fn context<C>(self, context: C) -> Result<T, Error>
+    where
+        C: Into<Cow<'static, str>>,
+    {
+        self.ok_or_else(|| Error::HttpStatus(StatusError::not_found()).context(context))
+    }
+
+    #[inline]
+    fn with_context<C, F>(self, context: F) -> Result<T, Error>
+    where
+        C: Into<Cow<'static, str>>,
+        F: FnOnce() -> C,
+    {
+        self.ok_or_else(|| Error::HttpStatus(StatusError::not_found()).context(context()))
+    }
+}
+
 impl Scribe for Error {
     fn render(self, res: &mut Response) {
+        let status = self.status();
         let status_error = match self {
             Error::HttpStatus(e) => e,
-            _ => StatusError::internal_server_error().cause(self),
+            _ => {
+                if status.is_server_error() {
+                    tracing::error!(error = %self, "error occurred");
+                }
+                StatusError::from_code(status)
+                    .unwrap_or_else(StatusError::internal_server_error)
+                    .cause(self)
+            }
         };
         res.render(status_error);
     }
@@ -216,4 +434,34 @@ mod tests {
         e.write(&mut req, &mut depot, &mut res).await;
         assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
     }
+
+    #[tokio::test]
+    async fn test_error_status_and_context() {
+        let e = Error::from(IoError::other("disk full"))
+            .context("save avatar")
+            .with_status(StatusCode::SERVICE_UNAVAILABLE)
+            .context("update user");
+        assert_eq!(e.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(e.to_string(), "update user: save avatar: disk full");
+        assert!(matches!(e.root(), Error::Io(_)));
+        assert!(e.source().is_some());
+
+        let mut res = Response::default();
+        e.write(&mut Request::default(), &mut Depot::new(), &mut res).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        assert_eq!(
+            Error::other("x").with_status(1000).status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(Error::from(ParseError::EmptyBody).status(), StatusCode::BAD_REQUEST);
+
+        let found: Option<u8> = None;
+        assert_eq!(found.context("user").unwrap_err().status(), StatusCode::NOT_FOUND);
+        assert_eq!(found.with_status(403).unwrap_err().status(), StatusCode::FORBIDDEN);
+        let parsed: Result<u8, ParseError> = Err(ParseError::EmptyBody);
+        let e = parsed.with_context(|| format!("parse {}", "body")).unwrap_err();
+        assert_eq!(e.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(e.to_string(), format!("parse body: {}", ParseError::EmptyBody));
+    }
 }
@@ -155,6 +155,10 @@ pub struct Field {
     pub serde_rename: Option<&'static str>,
     /// Field metadata, this is used for nested extractible types.
     pub metadata: Option<&'static Metadata>,
+    /// Whether each value of an array query is a separate parameter, such as `ids=1&ids=2`, the default is `true`.
+    ///
+    /// When it is `false`, the values are separated by commas, such as `ids=1,2`.
+    pub explode: bool,
 }
 impl Field {
     /// Create a new field with the given name and kind.
@@ -172,6 +176,7 @@ impl Field {
             rename: None,
             serde_rename: None,
             metadata: None,
+            explode: true,
         }
     }
 
@@ -217,6 +222,12 @@ impl Field {
         self
     }
 
+    /// Sets the explode to the given value.
+    pub fn explode(mut self, explode: bool) -> Self {
+        self.explode = explode;
+        self
+    }
+
     /// Check is this field has body required.
     pub(crate) fn has_body_required(&self) -> bool {
         self.sources.iter().any(|s| s.from == SourceFrom::Body)
@@ -61,6 +61,37 @@
 //! ```
 //!
 //! View [full source code](https://github.com/salvo-rs/salvo/blob/main/examples/extract-nested/src/main.rs)
+//!
+//! Query fields support the serialization styles of OpenAPI parameters:
+//!
+//! - An object field is read from the queries in `deepObject` style, such as `filter[created_at][gte]=2024-01-01`.
+//! - An array field is read from repeated queries, such as `ids=1&ids=2`, or from comma separated values, such as
+//!   `ids=1,2`, when the field is marked with `#[salvo(extract(explode = false))]`.
+//! - An array path parameter is read from comma separated values, such as `/users/1,2`.
+//!
+//! ```
+//! # use salvo_core::prelude::*;
+//! # use serde::Deserialize;
+//! #[derive(Deserialize, Debug)]
+//! struct Range {
+//!     gte: Option<String>,
+//!     lte: Option<String>,
+//! }
+//! #[derive(Deserialize, Debug)]
+//! struct Filter {
+//!     created_at: Range,
+//!     tags: Vec<String>,
+//! }
+//!
+//! // Matches `?filter[created_at][gte]=2024-01-01&filter[tags][]=a&filter[tags][]=b&ids=1,2`.
+//! #[derive(Deserialize, Extractible, Debug)]
+//! #[salvo(extract(default_source(from = "query")))]
+//! struct Search {
+//!     filter: Filter,
+//!     #[salvo(extract(explode = false))]
+//!     ids: Vec<i64>,
+//! }
+//! ```
 
 /// Metadata types.
 pub mod metadata;
@@ -82,7 +82,7 @@ impl futures_util::AsyncWrite for BodySender {
     fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<IoResult<usize>> {
         match self.data_tx.poll_ready(cx) {
             Poll::Ready(Ok(())) => {
-                let data: Bytes = Bytes::from(buf.to_vec());
+                let data = Bytes::copy_from_slice(buf);
                 let len = buf.len();
                 Poll::Ready(
                     self.data_tx
@@ -116,7 +116,7 @@ impl tokio::io::AsyncWrite for BodySender {
     fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<IoResult<usize>> {
         match self.data_tx.poll_ready(cx) {
             Poll::Ready(Ok(())) => {
-                let data: Bytes = Bytes::from(buf.to_vec());
+                let data = Bytes::copy_from_slice(buf);
                 let len = buf.len();
                 Poll::Ready(
                     self.data_tx
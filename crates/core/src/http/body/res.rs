@@ -10,12 +10,18 @@ use futures_util::stream::{BoxStream, FusedStream, Stream, TryStreamExt};
 use hyper::body::{Body, Frame, Incoming, SizeHint};
 use sync_wrapper::SyncWrapper;
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 
 use crate::error::BoxedError;
 use crate::http::body::{BodyReceiver, BodySender, BytesFrame};
 use crate::prelude::StatusError;
 
+/// Consecutive small chunks smaller than this size in total are merged into one frame, so they are written with
+/// less syscalls.
+const COALESCE_SIZE: usize = 8 * 1024;
+/// Chunks smaller than this size are small, larger chunks are never copied to be merged.
+const SMALL_CHUNK_SIZE: usize = 1024;
+
 /// Body for HTTP response.
 #[allow(clippy::type_complexity)]
 #[non_exhaustive]
@@ -147,7 +153,7 @@ impl Body for ResBody {
                     Poll::Ready(Some(Ok(Frame::data(bytes))))
                 }
             }
-            Self::Chunks(chunks) => Poll::Ready(chunks.pop_front().map(|bytes| Ok(Frame::data(bytes)))),
+            Self::Chunks(chunks) => Poll::Ready(coalesce_chunks(chunks).map(|bytes| Ok(Frame::data(bytes)))),
             Self::Hyper(body) => match Body::poll_frame(Pin::new(body), cx) {
                 Poll::Ready(Some(Ok(frame))) => Poll::Ready(Some(Ok(frame))),
                 Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(IoError::new(ErrorKind::Other, e)))),
@@ -213,6 +219,31 @@ impl Body for ResBody {
     }
 }
 
+fn coalesce_chunks(chunks: &mut VecDeque<Bytes>) -> Option<Bytes> {
+    let first = chunks.pop_front()?;
+    let mut len = first.len();
+    let mut count = 0;
+    if len >= SMALL_CHUNK_SIZE {
+        return Some(first);
+    }
+    for bytes in chunks.iter() {
+        if bytes.len() >= SMALL_CHUNK_SIZE || len + bytes.len() > COALESCE_SIZE {
+            break;
+        }
+        len += bytes.len();
+        count += 1;
+    }
+    if count == 0 {
+        return Some(first);
+    }
+    let mut merged = BytesMut::with_capacity(len);
+    merged.extend_from_slice(&first);
+    for bytes in chunks.drain(..count) {
+        merged.extend_from_slice(&bytes);
+    }
+    Some(merged.freeze())
+}
+
 impl Stream for ResBody {
     type Item = IoResult<Frame<Bytes>>;
 
@@ -290,3 +321,38 @@ impl Debug for ResBody {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use http_body_util::BodyExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chunks_coalesced() {
+        let large = Bytes::from(vec![b'x'; COALESCE_SIZE]);
+        let medium = Bytes::from(vec![b'y'; SMALL_CHUNK_SIZE]);
+        let chunks = VecDeque::from(vec![
+            Bytes::from_static(b"hello"),
+            Bytes::from_static(b" "),
+            Bytes::from_static(b"world"),
+            large.clone(),
+            medium.clone(),
+            Bytes::from_static(b"!"),
+        ]);
+        let mut body = ResBody::Chunks(chunks);
+        let mut frames = Vec::new();
+        while let Some(frame) = body.frame().await {
+            frames.push(frame.unwrap().into_data().unwrap());
+        }
+        assert_eq!(
+            frames,
+            vec![
+                Bytes::from_static(b"hello world"),
+                large,
+                medium,
+                Bytes::from_static(b"!")
+            ]
+        );
+    }
+}
@@ -85,10 +85,18 @@ pub enum ParseError {
     #[error("UTF-8 processing error: {0}")]
     Utf8(#[from] Utf8Error),
 
+    /// A limit of the [`ParseConfig`](crate::http::ParseConfig) is exceeded.
+    #[error("The {0} limit is exceeded.")]
+    LimitExceeded(&'static str),
+
     /// Serde json error.
     #[error("Serde json error: {0}")]
     SerdeJson(#[from] serde_json::error::Error),
 
+    /// Serde xml error.
+    #[error("Serde xml error: {0}")]
+    SerdeXml(#[from] serde_xml_rs::Error),
+
     /// Custom error that does not fall under any other error kind.
     #[error("Other error: {0}")]
     Other(BoxedError),
@@ -1,15 +1,28 @@
 //! Form parse module.
+//!
+//! Uploaded files are stored by the global [`FileStorage`], set it with [`set_file_storage`]. The default
+//! [`TempFileStorage`] writes every file to its own temporary directory, which is removed when the [`FilePart`] is
+//! dropped at the end of the request.
+//!
+//! An [`UploadPipeline`] transforms the content of uploaded files on the fly before it is stored, such as to encrypt
+//! or compress it, and reports the upload progress for each received chunk.
 use std::ffi::OsStr;
-use std::io::{Cursor, Write};
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Cursor, Result as IoResult, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
+use async_trait::async_trait;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::engine::Engine;
+use bytes::{Bytes, BytesMut};
 use futures_util::StreamExt;
-use http_body_util::BodyExt;
+use http_body_util::{BodyExt, Limited};
 use mime::Mime;
-use multer::{Field, Multipart};
+use multer::{Constraints, Field, Multipart, SizeLimit};
 use multimap::MultiMap;
+use parking_lot::RwLock;
 use rand::rngs::OsRng;
 use rand::RngCore;
 use tempfile::Builder;
@@ -18,7 +31,353 @@ use tokio::io::AsyncWriteExt;
 
 use crate::http::body::ReqBody;
 use crate::http::header::{HeaderMap, CONTENT_TYPE};
-use crate::http::ParseError;
+use crate::http::{ParseConfig, ParseError};
+
+/// Prefix of the temporary directories created by [`TempFileStorage`].
+pub const TEMP_DIR_PREFIX: &str = "salvo_http_multipart";
+
+static FILE_STORAGE: RwLock<Option<Arc<dyn FileStorage>>> = RwLock::new(None);
+
+/// Get the global [`FileStorage`], [`TempFileStorage`] is used if not set.
+pub fn file_storage() -> Arc<dyn FileStorage> {
+    if let Some(storage) = &*FILE_STORAGE.read() {
+        return storage.clone();
+    }
+    FILE_STORAGE
+        .write()
+        .get_or_insert_with(|| Arc::new(TempFileStorage::new()))
+        .clone()
+}
+
+/// Set the [`FileStorage`] of uploaded files globally.
+pub fn set_file_storage(storage: impl FileStorage) {
+    *FILE_STORAGE.write() = Some(Arc::new(storage));
+}
+
+/// Storage backend of uploaded files.
+///
+/// Implement it to stream uploads somewhere else, such as an object store, and return the part with
+/// [`FilePart::remote`].
+#[async_trait]
+pub trait FileStorage: Send + Sync + 'static {
+    /// Store the content of `field` and return the part describing it.
+    async fn store(&self, field: &mut Field<'_>) -> Result<FilePart, ParseError>;
+}
+
+/// Streaming transformation of the content of an uploaded file, such as encryption or compression.
+pub trait ChunkTransform: Send {
+    /// Transform a chunk received from the client, the returned bytes are stored in place of it.
+    fn transform(&mut self, chunk: Bytes) -> Result<Bytes, ParseError>;
+    /// Returns the bytes to store after the last chunk, such as buffered compressed data or an authentication tag.
+    fn finish(&mut self) -> Result<Bytes, ParseError> {
+        Ok(Bytes::new())
+    }
+}
+
+/// Creates a [`ChunkTransform`] for each uploaded file.
+pub trait BodyTransform: Send + Sync + 'static {
+    /// Create the transform of the file uploaded in the field named `field_name`, such as with a fresh nonce.
+    fn create(&self, field_name: Option<&str>, file_name: Option<&str>) -> Box<dyn ChunkTransform>;
+}
+impl<F> BodyTransform for F
+where
+    F: Fn(Option<&str>, Option<&str>) -> Box<dyn ChunkTransform> + Send + Sync + 'static,
+{
+    fn create(&self, field_name: Option<&str>, file_name: Option<&str>) -> Box<dyn ChunkTransform> {
+        self(field_name, file_name)
+    }
+}
+
+/// Progress of an uploaded file, reported by [`UploadPipeline::on_progress`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct UploadProgress<'a> {
+    /// Name of the form field.
+    pub field_name: Option<&'a str>,
+    /// Name of the uploaded file.
+    pub file_name: Option<&'a str>,
+    /// Number of bytes received from the client so far, before they are transformed.
+    pub received: u64,
+    /// Whether the whole file is received.
+    pub completed: bool,
+}
+
+type ProgressFn = dyn Fn(&UploadProgress<'_>) + Send + Sync;
+
+/// Transform and progress hooks applied to uploaded files while they are read.
+///
+/// [`TempFileStorage`] applies the pipeline set with [`TempFileStorage::pipeline`], custom [`FileStorage`]s read
+/// fields with [`UploadPipeline::reader`] to apply it.
+///
+/// # Example
+///
+/// ```
+/// use bytes::Bytes;
+/// use salvo_core::http::form::{BodyTransform, ChunkTransform, TempFileStorage, UploadPipeline};
+/// use salvo_core::http::ParseError;
+///
+/// struct Xor(u8);
+/// impl ChunkTransform for Xor {
+///     fn transform(&mut self, chunk: Bytes) -> Result<Bytes, ParseError> {
+///         Ok(chunk.iter().map(|b| b ^ self.0).collect())
+///     }
+/// }
+/// struct XorTransform;
+/// impl BodyTransform for XorTransform {
+///     fn create(&self, _field_name: Option<&str>, _file_name: Option<&str>) -> Box<dyn ChunkTransform> {
+///         Box::new(Xor(0x5a))
+///     }
+/// }
+///
+/// let pipeline = UploadPipeline::new()
+///     .transform(XorTransform)
+///     .on_progress(|progress| println!("{:?}: {} bytes", progress.file_name, progress.received));
+/// salvo_core::http::form::set_file_storage(TempFileStorage::new().pipeline(pipeline));
+/// ```
+#[derive(Clone, Default)]
+pub struct UploadPipeline {
+    transform: Option<Arc<dyn BodyTransform>>,
+    progress: Option<Arc<ProgressFn>>,
+}
+impl Debug for UploadPipeline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UploadPipeline")
+            .field("transform", &self.transform.is_some())
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+impl UploadPipeline {
+    /// Create a new `UploadPipeline` storing files as received.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the transform applied to the content of each uploaded file before it is stored.
+    #[inline]
+    pub fn transform(mut self, transform: impl BodyTransform) -> Self {
+        self.transform = Some(Arc::new(transform));
+        self
+    }
+
+    /// Sets the callback called after each chunk received from the client, and once the file is completed.
+    #[inline]
+    pub fn on_progress(mut self, progress: impl Fn(&UploadProgress<'_>) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Create a reader of `field` applying this pipeline.
+    pub fn reader<'a, 'f>(&self, field: &'a mut Field<'f>) -> UploadReader<'a, 'f> {
+        UploadReader {
+            transform: self
+                .transform
+                .as_ref()
+                .map(|transform| transform.create(field.name(), field.file_name())),
+            progress: self.progress.clone(),
+            field,
+            received: 0,
+            finished: false,
+        }
+    }
+}
+
+/// Reader of an uploaded file applying an [`UploadPipeline`].
+pub struct UploadReader<'a, 'f> {
+    field: &'a mut Field<'f>,
+    transform: Option<Box<dyn ChunkTransform>>,
+    progress: Option<Arc<ProgressFn>>,
+    received: u64,
+    finished: bool,
+}
+impl UploadReader<'_, '_> {
+    /// Get the next transformed chunk to store, `None` once the file is completed.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, ParseError> {
+        if self.finished {
+            return Ok(None);
+        }
+        match self.field.chunk().await? {
+            Some(chunk) => {
+                self.received += chunk.len() as u64;
+                self.report();
+                match &mut self.transform {
+                    Some(transform) => transform.transform(chunk).map(Some),
+                    None => Ok(Some(chunk)),
+                }
+            }
+            None => {
+                self.finished = true;
+                self.report();
+                match &mut self.transform {
+                    Some(transform) => transform.finish().map(|tail| (!tail.is_empty()).then_some(tail)),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Number of bytes received from the client so far, before they are transformed.
+    #[inline]
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    fn report(&self) {
+        if let Some(progress) = &self.progress {
+            progress(&UploadProgress {
+                field_name: self.field.name(),
+                file_name: self.field.file_name(),
+                received: self.received,
+                completed: self.finished,
+            });
+        }
+    }
+}
+
+/// [`FileStorage`] writing uploaded files to temporary directories.
+///
+/// Each file is written to its own directory named with [`TEMP_DIR_PREFIX`], so directories left behind by a crashed
+/// process can be removed with [`sweep`](Self::sweep).
+#[derive(Clone, Debug, Default)]
+pub struct TempFileStorage {
+    dir: Option<PathBuf>,
+    memory_threshold: usize,
+    pipeline: UploadPipeline,
+}
+impl TempFileStorage {
+    /// Create a new `TempFileStorage` using the system temporary directory.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the directory temporary files are created in.
+    #[inline]
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Keep files no larger than `threshold` bytes in memory instead of writing them to disk.
+    ///
+    /// In-memory parts have an empty [`path`](FilePart::path), read them with [`FilePart::bytes`]. The default is 0,
+    /// which writes every file to disk.
+    #[inline]
+    pub fn memory_threshold(mut self, threshold: usize) -> Self {
+        self.memory_threshold = threshold;
+        self
+    }
+
+    /// Sets the pipeline transforming uploaded files before they are written and reporting their progress.
+    ///
+    /// The memory threshold and the [`size`](FilePart::size) of parts apply to the transformed content.
+    #[inline]
+    pub fn pipeline(mut self, pipeline: UploadPipeline) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
+    /// Remove temporary directories created by any `TempFileStorage` in the same directory which are older than
+    /// `max_age`, returns the number of removed directories.
+    ///
+    /// Call it at startup, or periodically, to clean up files left behind by crashed processes.
+    pub async fn sweep(&self, max_age: Duration) -> IoResult<usize> {
+        let dir = self.dir.clone().unwrap_or_else(std::env::temp_dir);
+        tokio::task::spawn_blocking(move || {
+            let now = SystemTime::now();
+            let mut removed = 0;
+            for entry in std::fs::read_dir(dir)? {
+                let entry = entry?;
+                if !entry.file_name().to_string_lossy().starts_with(TEMP_DIR_PREFIX) {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                let age = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| now.duration_since(modified).ok())
+                    .unwrap_or_default();
+                if metadata.is_dir() && age >= max_age && std::fs::remove_dir_all(entry.path()).is_ok() {
+                    removed += 1;
+                }
+            }
+            Ok(removed)
+        })
+        .await
+        .expect("Runtime spawn blocking poll error")
+    }
+
+    /// Create a file in a new temporary directory, the returned part removes both when dropped.
+    async fn create_file(&self, name: Option<String>, headers: HeaderMap) -> IoResult<(FilePart, File)> {
+        let dir = self.dir.clone();
+        let temp_dir = tokio::task::spawn_blocking(move || {
+            let mut builder = Builder::new();
+            builder.prefix(TEMP_DIR_PREFIX);
+            match dir {
+                Some(dir) => builder.tempdir_in(dir),
+                None => builder.tempdir(),
+            }
+        })
+        .await
+        .expect("Runtime spawn blocking poll error")?
+        .keep();
+        let mut path = temp_dir.clone();
+        path.push(format!(
+            "{}.{}",
+            text_nonce(),
+            name.as_deref()
+                .and_then(|name| { Path::new(name).extension().and_then(OsStr::to_str) })
+                .unwrap_or("unknown")
+        ));
+        let part = FilePart {
+            name,
+            headers,
+            path,
+            size: 0,
+            temp_dir: Some(temp_dir),
+            data: None,
+            location: None,
+        };
+        let file = File::create(&part.path).await?;
+        Ok((part, file))
+    }
+}
+#[async_trait]
+impl FileStorage for TempFileStorage {
+    async fn store(&self, field: &mut Field<'_>) -> Result<FilePart, ParseError> {
+        let name = field.file_name().map(|s| s.to_owned());
+        let headers = field.headers().to_owned();
+        let mut buffer = BytesMut::new();
+        let mut size = 0;
+        let mut file: Option<(FilePart, File)> = None;
+        let mut reader = self.pipeline.reader(field);
+        while let Some(chunk) = reader.chunk().await? {
+            size += chunk.len() as u64;
+            match &mut file {
+                Some((_, handle)) => handle.write_all(&chunk).await?,
+                None if buffer.len() + chunk.len() <= self.memory_threshold => buffer.extend_from_slice(&chunk),
+                None => {
+                    let (part, mut handle) = self.create_file(name.clone(), headers.clone()).await?;
+                    handle.write_all(&buffer).await?;
+                    handle.write_all(&chunk).await?;
+                    buffer.clear();
+                    file = Some((part, handle));
+                }
+            }
+        }
+        let (mut part, handle) = match file {
+            Some(file) => file,
+            None if self.memory_threshold > 0 => return Ok(FilePart::memory(name, headers, buffer.freeze())),
+            None => self.create_file(name, headers).await?,
+        };
+        handle.sync_all().await?;
+        part.size = size;
+        Ok(part)
+    }
+}
 
 /// The extracted text fields and uploaded files from a `multipart/form-data` request.
 #[derive(Debug)]
@@ -42,17 +401,31 @@ impl FormData {
         }
     }
 
-    /// Parse MIME `multipart/*` information from a stream as a `FormData`.
-    pub(crate) async fn read(headers: &HeaderMap, body: ReqBody) -> Result<FormData, ParseError> {
+    /// Parse MIME `multipart/*` information from a stream as a `FormData`, within the limits of `config`.
+    pub(crate) async fn read(headers: &HeaderMap, body: ReqBody, config: &ParseConfig) -> Result<FormData, ParseError> {
         let ctype: Option<Mime> = headers
             .get(CONTENT_TYPE)
             .and_then(|h| h.to_str().ok())
             .and_then(|v| v.parse().ok());
         match ctype {
             Some(ctype) if ctype.subtype() == mime::WWW_FORM_URLENCODED => {
-                let data = BodyExt::collect(body).await.map_err(ParseError::other)?.to_bytes();
+                let data = match config.max_body_size {
+                    Some(max_size) => Limited::new(body, max_size)
+                        .collect()
+                        .await
+                        .map_err(ParseError::other)?
+                        .to_bytes(),
+                    None => BodyExt::collect(body).await.map_err(ParseError::other)?.to_bytes(),
+                };
                 let mut form_data = FormData::new();
-                form_data.fields = form_urlencoded::parse(&data).into_owned().collect();
+                for (count, (name, value)) in form_urlencoded::parse(&data).enumerate() {
+                    if config.max_form_fields.is_some_and(|max| count >= max) {
+                        return Err(ParseError::LimitExceeded("form fields"));
+                    }
+                    config.check_string(&name)?;
+                    config.check_string(&value)?;
+                    form_data.fields.insert(name.into_owned(), value.into_owned());
+                }
                 Ok(form_data)
             }
             Some(ctype) if ctype.type_() == mime::MULTIPART => {
@@ -63,13 +436,27 @@ impl FormData {
                     .and_then(|ct| multer::parse_boundary(ct).ok())
                 {
                     let body = body.map(|f| f.map(|f| f.into_data().unwrap_or_default()));
-                    let mut multipart = Multipart::new(body, boundary);
+                    let mut multipart = match config.max_body_size {
+                        Some(max_size) => Multipart::with_constraints(
+                            body,
+                            boundary,
+                            Constraints::new().size_limit(SizeLimit::new().whole_stream(max_size as u64)),
+                        ),
+                        None => Multipart::new(body, boundary),
+                    };
+                    let storage = file_storage();
+                    let mut count = 0;
                     while let Some(mut field) = multipart.next_field().await? {
+                        if config.max_form_fields.is_some_and(|max| count >= max) {
+                            return Err(ParseError::LimitExceeded("form fields"));
+                        }
+                        count += 1;
                         if let Some(name) = field.name().map(|s| s.to_owned()) {
+                            config.check_string(&name)?;
                             if field.headers().get(CONTENT_TYPE).is_some() {
-                                form_data.files.insert(name, FilePart::create(&mut field).await?);
+                                form_data.files.insert(name, storage.store(&mut field).await?);
                             } else {
-                                form_data.fields.insert(name, field.text().await?);
+                                form_data.fields.insert(name, read_text(&mut field, config).await?);
                             }
                         }
                     }
@@ -80,6 +467,18 @@ impl FormData {
         }
     }
 }
+
+/// Read a text field, without buffering more than the max string length of `config`.
+async fn read_text(field: &mut Field<'_>, config: &ParseConfig) -> Result<String, ParseError> {
+    let mut data = BytesMut::new();
+    while let Some(chunk) = field.chunk().await? {
+        data.extend_from_slice(&chunk);
+        if config.max_string_length.is_some_and(|max| data.len() > max) {
+            return Err(ParseError::LimitExceeded("string length"));
+        }
+    }
+    Ok(std::str::from_utf8(&data)?.to_owned())
+}
 impl Default for FormData {
     #[inline]
     fn default() -> Self {
@@ -100,8 +499,38 @@ pub struct FilePart {
     size: u64,
     // The temporary directory the upload was put into, saved for the Drop trait
     temp_dir: Option<PathBuf>,
+    // The content of the file if it is kept in memory.
+    data: Option<Bytes>,
+    // The location of the file if it is stored by a remote storage.
+    location: Option<String>,
 }
 impl FilePart {
+    /// Create a `FilePart` whose content is kept in memory.
+    #[inline]
+    pub fn memory(name: Option<String>, headers: HeaderMap, data: Bytes) -> FilePart {
+        FilePart {
+            name,
+            headers,
+            path: PathBuf::new(),
+            size: data.len() as u64,
+            temp_dir: None,
+            data: Some(data),
+            location: None,
+        }
+    }
+    /// Create a `FilePart` whose content is stored by a remote storage at `location`, such as an object key.
+    #[inline]
+    pub fn remote(name: Option<String>, headers: HeaderMap, size: u64, location: impl Into<String>) -> FilePart {
+        FilePart {
+            name,
+            headers,
+            path: PathBuf::new(),
+            size,
+            temp_dir: None,
+            data: None,
+            location: Some(location.into()),
+        }
+    }
     /// Get file name.
     #[inline]
     pub fn name(&self) -> Option<&str> {
@@ -129,11 +558,21 @@ impl FilePart {
             .and_then(|h| h.to_str().ok())
             .and_then(|v| v.parse().ok())
     }
-    /// Get file path.
+    /// Get file path, it is empty if the file is not stored on disk.
     #[inline]
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
+    /// Get file content if it is kept in memory.
+    #[inline]
+    pub fn bytes(&self) -> Option<&Bytes> {
+        self.data.as_ref()
+    }
+    /// Get file location if it is stored by a remote storage.
+    #[inline]
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
     /// Get file size.
     #[inline]
     pub fn size(&self) -> u64 {
@@ -148,46 +587,26 @@ impl FilePart {
 
     /// Create a new temporary FilePart (when created this way, the file will be
     /// deleted once the FilePart object goes out of scope).
+    #[inline]
     pub async fn create(field: &mut Field<'_>) -> Result<FilePart, ParseError> {
-        // Setup a file to capture the contents.
-        let mut path = tokio::task::spawn_blocking(|| Builder::new().prefix("salvo_http_multipart").tempdir())
-            .await
-            .expect("Runtime spawn blocking poll error")?
-            .into_path();
-        let temp_dir = Some(path.clone());
-        let name = field.file_name().map(|s| s.to_owned());
-        path.push(format!(
-            "{}.{}",
-            text_nonce(),
-            name.as_deref()
-                .and_then(|name| { Path::new(name).extension().and_then(OsStr::to_str) })
-                .unwrap_or("unknown")
-        ));
-        let mut file = File::create(&path).await?;
-        let mut size = 0;
-        while let Some(chunk) = field.chunk().await? {
-            size += chunk.len() as u64;
-            file.write_all(&chunk).await?;
-        }
-        file.sync_all().await?;
-        Ok(FilePart {
-            name,
-            headers: field.headers().to_owned(),
-            path,
-            size,
-            temp_dir,
-        })
+        TempFileStorage::new().store(field).await
     }
 }
 impl Drop for FilePart {
     fn drop(&mut self) {
-        if let Some(temp_dir) = &self.temp_dir {
-            let path = self.path.clone();
-            let temp_dir = temp_dir.to_owned();
-            tokio::task::spawn_blocking(move || {
+        if let Some(temp_dir) = self.temp_dir.take() {
+            let path = std::mem::take(&mut self.path);
+            let remove = move || {
                 std::fs::remove_file(&path).ok();
                 std::fs::remove_dir(temp_dir).ok();
-            });
+            };
+            // Remove inline if the runtime is gone, so files are not left behind on shutdown.
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle) => {
+                    handle.spawn_blocking(remove);
+                }
+                Err(_) => remove(),
+            }
         }
     }
 }
@@ -215,3 +634,90 @@ fn text_nonce() -> String {
     // base64 encode
     URL_SAFE_NO_PAD.encode(&raw)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    const BOUNDARY: &str = "X-BOUNDARY";
+
+    async fn store(storage: &TempFileStorage, content: &'static str) -> FilePart {
+        let body = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+            Content-Type: text/plain\r\n\r\n{content}\r\n--{BOUNDARY}--\r\n"
+        );
+        let stream = futures_util::stream::once(async move { Ok::<_, Infallible>(Bytes::from(body)) });
+        let mut multipart = Multipart::new(stream, BOUNDARY);
+        let mut field = multipart.next_field().await.unwrap().unwrap();
+        storage.store(&mut field).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_temp_file_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = TempFileStorage::new().dir(dir.path()).memory_threshold(8);
+
+        let part = store(&storage, "small").await;
+        assert_eq!(part.bytes().unwrap(), "small");
+        assert_eq!(part.size(), 5);
+        assert!(part.path().as_os_str().is_empty());
+
+        let part = store(&storage, "larger than threshold").await;
+        assert!(part.bytes().is_none());
+        assert!(part.path().starts_with(dir.path()));
+        assert_eq!(std::fs::read_to_string(part.path()).unwrap(), "larger than threshold");
+        assert_eq!(part.size(), 21);
+        let temp_dir = part.path().parent().unwrap().to_owned();
+        drop(part);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!temp_dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_upload_pipeline() {
+        struct Upper;
+        impl ChunkTransform for Upper {
+            fn transform(&mut self, chunk: Bytes) -> Result<Bytes, ParseError> {
+                Ok(chunk.to_ascii_uppercase().into())
+            }
+            fn finish(&mut self) -> Result<Bytes, ParseError> {
+                Ok(Bytes::from_static(b"!"))
+            }
+        }
+        let reports = Arc::new(RwLock::new(Vec::new()));
+        let pipeline = UploadPipeline::new()
+            .transform(|_: Option<&str>, _: Option<&str>| Box::new(Upper) as Box<dyn ChunkTransform>)
+            .on_progress({
+                let reports = reports.clone();
+                move |progress| {
+                    assert_eq!(progress.file_name, Some("a.txt"));
+                    reports.write().push((progress.received, progress.completed));
+                }
+            });
+        let dir = tempfile::tempdir().unwrap();
+        let storage = TempFileStorage::new().dir(dir.path()).pipeline(pipeline);
+
+        let part = store(&storage, "secret").await;
+        assert_eq!(std::fs::read_to_string(part.path()).unwrap(), "SECRET!");
+        assert_eq!(part.size(), 7);
+        let reports = reports.read();
+        assert_eq!(reports.last(), Some(&(6, true)));
+        assert!(reports[..reports.len() - 1].iter().all(|(_, completed)| !completed));
+    }
+
+    #[tokio::test]
+    async fn test_sweep() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = TempFileStorage::new().dir(dir.path());
+        let mut part = store(&storage, "orphan").await;
+        part.do_not_delete_on_drop();
+        drop(part);
+        std::fs::create_dir(dir.path().join("other")).unwrap();
+
+        assert_eq!(storage.sweep(Duration::from_secs(3600)).await.unwrap(), 0);
+        assert_eq!(storage.sweep(Duration::ZERO).await.unwrap(), 1);
+        assert!(dir.path().join("other").exists());
+    }
+}
@@ -0,0 +1,79 @@
+use crate::http::Method;
+
+/// Whether a request can be safely repeated, so that retrying, caching or deduplicating it has no side effects
+/// beyond the ones of a single request.
+///
+/// It is declared on a router with [`Router::idempotent`](crate::Router::idempotent) and applies to the requests
+/// handled by the router and its descendants, unless a descendant declares its own. Middlewares which retry, cache
+/// or deduplicate requests should check [`Request::is_idempotent`](crate::http::Request::is_idempotent) instead of
+/// guessing from the method alone.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::http::Idempotent;
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn search() -> &'static str {
+///     "[]"
+/// }
+/// #[handler]
+/// async fn delete_all() {}
+///
+/// let router = Router::new()
+///     // Searching with a body only reads data, it can be retried.
+///     .push(Router::with_path("search").idempotent(Idempotent::Yes).post(search))
+///     // Deleting everything can not be repeated once new data is created.
+///     .push(Router::with_path("all").idempotent(Idempotent::No).delete(delete_all));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Idempotent {
+    /// Idempotent if the method is, which are `GET`, `HEAD`, `OPTIONS`, `TRACE`, `PUT` and `DELETE`.
+    #[default]
+    ByMethod,
+    /// Idempotent whatever the method is.
+    Yes,
+    /// Not idempotent whatever the method is.
+    No,
+}
+
+impl Idempotent {
+    /// Returns `true` if a request with `method` is idempotent.
+    #[inline]
+    pub fn is_idempotent(self, method: &Method) -> bool {
+        match self {
+            Self::ByMethod => method.is_safe() || *method == Method::PUT || *method == Method::DELETE,
+            Self::Yes => true,
+            Self::No => false,
+        }
+    }
+}
+
+impl From<bool> for Idempotent {
+    #[inline]
+    fn from(idempotent: bool) -> Self {
+        if idempotent {
+            Self::Yes
+        } else {
+            Self::No
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idempotent() {
+        assert!(Idempotent::ByMethod.is_idempotent(&Method::GET));
+        assert!(Idempotent::ByMethod.is_idempotent(&Method::PUT));
+        assert!(Idempotent::ByMethod.is_idempotent(&Method::DELETE));
+        assert!(!Idempotent::ByMethod.is_idempotent(&Method::POST));
+        assert!(!Idempotent::ByMethod.is_idempotent(&Method::PATCH));
+        assert!(Idempotent::Yes.is_idempotent(&Method::POST));
+        assert!(!Idempotent::No.is_idempotent(&Method::GET));
+        assert_eq!(Idempotent::from(true), Idempotent::Yes);
+    }
+}
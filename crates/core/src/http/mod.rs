@@ -2,6 +2,8 @@
 
 pub mod errors;
 pub mod form;
+mod idempotent;
+mod parse_config;
 mod range;
 pub mod request;
 pub mod response;
@@ -13,7 +15,9 @@ pub use errors::{ParseError, StatusError};
 pub use headers;
 pub use http::method::Method;
 pub use http::{header, method, uri, HeaderMap, HeaderName, HeaderValue, StatusCode};
+pub use idempotent::Idempotent;
 pub use mime::{self, Mime};
+pub use parse_config::ParseConfig;
 pub use range::HttpRange;
 pub use request::Request;
 pub mod body;
@@ -86,8 +90,7 @@ pub fn parse_accept_encoding(header: &str) -> Vec<(String, u8)> {
 #[inline]
 pub fn guess_accept_mime(req: &Request, default_type: Option<Mime>) -> Mime {
     let dmime: Mime = default_type.unwrap_or(mime::TEXT_HTML);
-    let accept = req.accept();
-    accept.first().unwrap_or(&dmime).to_string().parse().unwrap_or(dmime)
+    req.accept_list().first().cloned().unwrap_or(dmime)
 }
 
 #[cfg(test)]
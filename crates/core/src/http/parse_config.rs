@@ -0,0 +1,185 @@
+use crate::http::request::secure_max_size;
+use crate::http::ParseError;
+
+/// Limits of the parsers used by the extractors, to prevent algorithmic complexity attacks on specific endpoints.
+///
+/// It is set on a router with [`Router::parse_config`](crate::Router::parse_config) and applies to the requests
+/// handled by the router and its descendants, unless a descendant sets its own. Limits which are not set are not
+/// enforced, except the body size which defaults to the global [`secure_max_size`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::http::ParseConfig;
+/// use salvo_core::prelude::*;
+///
+/// #[handler]
+/// async fn search(req: &mut Request) -> String {
+///     req.query::<String>("q").unwrap_or_default()
+/// }
+///
+/// let router = Router::with_path("search")
+///     .parse_config(ParseConfig::new().max_query_pairs(8).max_string_length(256))
+///     .get(search);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ParseConfig {
+    /// Max size of bodies read into memory, such as JSON bodies.
+    pub max_body_size: Option<usize>,
+    /// Max nesting depth of JSON bodies.
+    ///
+    /// JSON bodies nested deeper than 128 levels are always rejected by the JSON parser.
+    pub max_json_depth: Option<usize>,
+    /// Max number of fields and files of form bodies.
+    pub max_form_fields: Option<usize>,
+    /// Max number of query pairs.
+    pub max_query_pairs: Option<usize>,
+    /// Max length in bytes of query keys and values, form field names and values and JSON strings.
+    pub max_string_length: Option<usize>,
+}
+
+impl ParseConfig {
+    /// Create a new `ParseConfig` without limits.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            max_body_size: None,
+            max_json_depth: None,
+            max_form_fields: None,
+            max_query_pairs: None,
+            max_string_length: None,
+        }
+    }
+
+    /// Sets the max size of bodies read into memory.
+    #[inline]
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = Some(size);
+        self
+    }
+
+    /// Sets the max nesting depth of JSON bodies.
+    #[inline]
+    pub fn max_json_depth(mut self, depth: usize) -> Self {
+        self.max_json_depth = Some(depth);
+        self
+    }
+
+    /// Sets the max number of fields and files of form bodies.
+    #[inline]
+    pub fn max_form_fields(mut self, count: usize) -> Self {
+        self.max_form_fields = Some(count);
+        self
+    }
+
+    /// Sets the max number of query pairs.
+    #[inline]
+    pub fn max_query_pairs(mut self, count: usize) -> Self {
+        self.max_query_pairs = Some(count);
+        self
+    }
+
+    /// Sets the max length in bytes of query keys and values, form field names and values and JSON strings.
+    #[inline]
+    pub fn max_string_length(mut self, length: usize) -> Self {
+        self.max_string_length = Some(length);
+        self
+    }
+
+    /// Get the max size of bodies read into memory, the global [`secure_max_size`] if it is not set.
+    #[inline]
+    pub fn body_size_limit(&self) -> usize {
+        self.max_body_size.unwrap_or_else(secure_max_size)
+    }
+
+    /// Check the number of query pairs and the length of their keys and values.
+    pub fn check_queries(&self, query: &str) -> Result<(), ParseError> {
+        if self.max_query_pairs.is_none() && self.max_string_length.is_none() {
+            return Ok(());
+        }
+        for (count, (key, value)) in form_urlencoded::parse(query.as_bytes()).enumerate() {
+            if self.max_query_pairs.is_some_and(|max| count >= max) {
+                return Err(ParseError::LimitExceeded("query pairs"));
+            }
+            self.check_string(&key)?;
+            self.check_string(&value)?;
+        }
+        Ok(())
+    }
+
+    /// Check the length of a string.
+    #[inline]
+    pub fn check_string(&self, value: &str) -> Result<(), ParseError> {
+        if self.max_string_length.is_some_and(|max| value.len() > max) {
+            Err(ParseError::LimitExceeded("string length"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the nesting depth and the length of strings of a JSON document, without parsing it.
+    pub fn check_json(&self, data: &[u8]) -> Result<(), ParseError> {
+        if self.max_json_depth.is_none() && self.max_string_length.is_none() {
+            return Ok(());
+        }
+        let mut depth = 0usize;
+        let mut string_length = None;
+        let mut escaped = false;
+        for byte in data {
+            if let Some(length) = &mut string_length {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => {
+                        escaped = true;
+                        continue;
+                    }
+                    b'"' => {
+                        string_length = None;
+                        continue;
+                    }
+                    _ => {}
+                }
+                *length += 1;
+                if self.max_string_length.is_some_and(|max| *length > max) {
+                    return Err(ParseError::LimitExceeded("string length"));
+                }
+                continue;
+            }
+            match byte {
+                b'"' => string_length = Some(0usize),
+                b'[' | b'{' => {
+                    depth += 1;
+                    if self.max_json_depth.is_some_and(|max| depth > max) {
+                        return Err(ParseError::LimitExceeded("JSON depth"));
+                    }
+                }
+                b']' | b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_limits() {
+        let config = ParseConfig::new()
+            .max_json_depth(2)
+            .max_query_pairs(2)
+            .max_string_length(4);
+        assert!(config.check_json(br#"{"a": [1, "abcd"]}"#).is_ok());
+        assert!(config.check_json(br#"{"a": [[1]]}"#).is_err());
+        assert!(config.check_json(br#"{"a": "[[\"x"}"#).is_ok());
+        assert!(config.check_json(br#"{"a": "abcde"}"#).is_err());
+        assert!(config.check_queries("a=1&b=2").is_ok());
+        assert!(config.check_queries("a=1&b=2&c=3").is_err());
+        assert!(config.check_queries("a=%20%20%20%20").is_ok());
+        assert!(config.check_queries("a=abcde").is_err());
+        assert!(ParseConfig::new().check_json(b"[[[[[[").is_ok());
+    }
+}
@@ -1,6 +1,7 @@
 //! HTTP request.
 use std::error::Error as StdError;
 use std::fmt::{self, Formatter};
+use std::future::Future;
 #[cfg(feature = "quinn")]
 use std::sync::Arc;
 
@@ -18,14 +19,15 @@ use multimap::MultiMap;
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
 use serde::de::Deserialize;
+use tokio_util::sync::CancellationToken;
 
 use crate::conn::SocketAddr;
 use crate::extract::{Extractible, Metadata};
 use crate::fuse::TransProto;
 use crate::http::body::ReqBody;
 use crate::http::form::{FilePart, FormData};
-use crate::http::{Mime, ParseError, Version};
-use crate::serde::{from_request, from_str_map, from_str_multi_map, from_str_multi_val, from_str_val};
+use crate::http::{Idempotent, Mime, ParseConfig, ParseError, Version};
+use crate::serde::{from_request, from_str_map, from_str_multi_map, from_str_multi_val, from_str_val, NestedValue};
 use crate::Error;
 
 static SECURE_MAX_SIZE: RwLock<usize> = RwLock::new(64 * 1024);
@@ -58,21 +60,30 @@ pub struct Request {
     // The request method.
     method: Method,
 
+    // Parsed from the `Cookie` headers on first access, reset when headers are changed.
     #[cfg(feature = "cookie")]
-    pub(crate) cookies: CookieJar,
+    pub(crate) cookies: OnceCell<CookieJar>,
 
     pub(crate) params: IndexMap<String, String>,
 
-    // accept: Option<Vec<Mime>>,
+    // Parsed from the `Accept` header on first access, reset when headers are changed.
+    accept: OnceCell<Vec<Mime>>,
     pub(crate) queries: OnceCell<MultiMap<String, String>>,
     pub(crate) form_data: tokio::sync::OnceCell<FormData>,
     pub(crate) payload: tokio::sync::OnceCell<Bytes>,
+    // Set by the service from the matched router.
+    pub(crate) parse_config: Option<ParseConfig>,
+    // Set by the service from the matched router.
+    pub(crate) idempotent: Idempotent,
 
     /// The version of the HTTP protocol used.
     pub(crate) version: Version,
     pub(crate) scheme: Scheme,
     pub(crate) local_addr: SocketAddr,
     pub(crate) remote_addr: SocketAddr,
+
+    // Set by the service, cancelled when the client disconnects.
+    pub(crate) cancellation: Option<CancellationToken>,
 }
 
 impl fmt::Debug for Request {
@@ -109,15 +120,19 @@ impl Request {
             extensions: Extensions::default(),
             method: Method::default(),
             #[cfg(feature = "cookie")]
-            cookies: CookieJar::default(),
+            cookies: OnceCell::new(),
             params: IndexMap::new(),
+            accept: OnceCell::new(),
             queries: OnceCell::new(),
             form_data: tokio::sync::OnceCell::new(),
             payload: tokio::sync::OnceCell::new(),
+            parse_config: None,
+            idempotent: Idempotent::ByMethod,
             version: Version::default(),
             scheme: Scheme::HTTP,
             local_addr: SocketAddr::Unknown,
             remote_addr: SocketAddr::Unknown,
+            cancellation: None,
         }
     }
     #[doc(hidden)]
@@ -145,22 +160,6 @@ impl Request {
             body,
         ) = req.into_parts();
 
-        // Set the request cookies, if they exist.
-        #[cfg(feature = "cookie")]
-        let cookies = {
-            let mut cookie_jar = CookieJar::new();
-            for header in headers.get_all(http::header::COOKIE) {
-                if let Ok(header) = header.to_str() {
-                    for cookie_str in header.split(';').map(|s| s.trim()) {
-                        if let Ok(cookie) = Cookie::parse_encoded(cookie_str).map(|c| c.into_owned()) {
-                            cookie_jar.add_original(cookie);
-                        }
-                    }
-                }
-            }
-            cookie_jar
-        };
-
         Request {
             queries: OnceCell::new(),
             uri,
@@ -169,16 +168,19 @@ impl Request {
             extensions,
             method,
             #[cfg(feature = "cookie")]
-            cookies,
-            // accept: None,
+            cookies: OnceCell::new(),
+            accept: OnceCell::new(),
             params: IndexMap::new(),
             form_data: tokio::sync::OnceCell::new(),
             payload: tokio::sync::OnceCell::new(),
+            parse_config: None,
+            idempotent: Idempotent::ByMethod,
             // multipart: OnceCell::new(),
             local_addr: SocketAddr::Unknown,
             remote_addr: SocketAddr::Unknown,
             version,
             scheme,
+            cancellation: None,
         }
     }
 
@@ -225,6 +227,7 @@ impl Request {
         self.uri = uri;
         self.version = version;
         self.headers = headers;
+        self.reset_parsed_headers();
         self.extensions = extensions;
         self.body = body;
     }
@@ -358,6 +361,9 @@ impl Request {
 
     /// Returns a mutable reference to the associated header field map.
     ///
+    /// The cached accept list and cookies are parsed again from the headers on next access, so changes made
+    /// through [`cookies_mut`](Self::cookies_mut) before are discarded.
+    ///
     /// # Examples
     ///
     /// ```
@@ -369,22 +375,26 @@ impl Request {
     /// ```
     #[inline]
     pub fn headers_mut(&mut self) -> &mut HeaderMap<HeaderValue> {
+        self.reset_parsed_headers();
         &mut self.headers
     }
 
+    #[inline]
+    fn reset_parsed_headers(&mut self) {
+        self.accept = OnceCell::new();
+        #[cfg(feature = "cookie")]
+        {
+            self.cookies = OnceCell::new();
+        }
+    }
+
     /// Get header with supplied name and try to parse to a 'T', returns None if failed or not found.
     #[inline]
     pub fn header<'de, T>(&'de self, key: impl AsHeaderName) -> Option<T>
     where
         T: Deserialize<'de>,
     {
-        let values = self
-            .headers
-            .get_all(key)
-            .iter()
-            .filter_map(|v| v.to_str().ok())
-            .collect::<Vec<_>>();
-        from_str_multi_val(values).ok()
+        from_str_multi_val(self.headers.get_all(key).iter().filter_map(|v| v.to_str().ok())).ok()
     }
 
     /// Modify a header for this request.
@@ -400,9 +410,9 @@ impl Request {
             .try_into()
             .map_err(|_| Error::Other("invalid header value".into()))?;
         if overwrite {
-            self.headers.insert(name, value);
+            self.headers_mut().insert(name, value);
         } else {
-            self.headers.append(name, value);
+            self.headers_mut().append(name, value);
         }
         Ok(self)
     }
@@ -444,6 +454,56 @@ impl Request {
         &self.extensions
     }
 
+    /// Returns `true` if the request is cancelled because the client disconnected.
+    ///
+    /// Requests which are not handled by a [`Service`](crate::Service) are never cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Waits until the request is cancelled because the client disconnected.
+    ///
+    /// Requests which are not handled by a [`Service`](crate::Service) are never cancelled.
+    pub async fn cancelled(&self) {
+        match &self.cancellation {
+            Some(token) => token.cancelled().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Returns a future which completes when the client disconnects.
+    ///
+    /// Unlike [`cancelled`](Self::cancelled), the future does not borrow the request, so it can be moved into
+    /// spawned tasks, such as report generation or streaming, which then stop promptly when the peer goes away.
+    /// The client is also considered disconnected when a streamed response body is dropped before its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn report(req: &mut Request) {
+    ///     let disconnected = req.on_disconnect();
+    ///     tokio::spawn(async move {
+    ///         tokio::select! {
+    ///             _ = disconnected => {}
+    ///             _ = tokio::time::sleep(std::time::Duration::from_secs(60)) => {}
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn on_disconnect(&self) -> impl Future<Output = ()> + Send + 'static {
+        let token = self.cancellation.clone();
+        async move {
+            match token {
+                Some(token) => token.cancelled_owned().await,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
     cfg_feature! {
         #![feature = "quinn"]
 
@@ -511,28 +571,30 @@ impl Request {
     }
 
     /// Get accept.
+    #[inline]
     pub fn accept(&self) -> Vec<Mime> {
-        let mut list: Vec<Mime> = vec![];
-        if let Some(accept) = self.headers.get("accept").and_then(|h| h.to_str().ok()) {
-            let parts: Vec<&str> = accept.split(',').collect();
-            for part in parts {
-                if let Ok(mt) = part.parse() {
-                    list.push(mt);
-                }
-            }
-        }
-        list
+        self.accept_list().to_vec()
     }
 
     /// Get first accept.
     #[inline]
     pub fn first_accept(&self) -> Option<Mime> {
-        let mut accept = self.accept();
-        if !accept.is_empty() {
-            Some(accept.remove(0))
-        } else {
-            None
-        }
+        self.accept_list().first().cloned()
+    }
+
+    /// Get the mime types parsed from the `Accept` header, they are parsed once and cached.
+    pub(crate) fn accept_list(&self) -> &[Mime] {
+        self.accept.get_or_init(|| {
+            let mut list: Vec<Mime> = vec![];
+            if let Some(accept) = self.headers.get("accept").and_then(|h| h.to_str().ok()) {
+                for part in accept.split(',') {
+                    if let Ok(mt) = part.trim().parse() {
+                        list.push(mt);
+                    }
+                }
+            }
+            list
+        })
     }
 
     /// Get content type.
@@ -547,14 +609,17 @@ impl Request {
     cfg_feature! {
         #![feature = "cookie"]
         /// Get `CookieJar` reference.
+        ///
+        /// Cookies are parsed from the `Cookie` headers on first access.
         #[inline]
         pub fn cookies(&self) -> &CookieJar {
-            &self.cookies
+            self.cookies.get_or_init(|| parse_cookies(&self.headers))
         }
         /// Get `CookieJar` mutable reference.
         #[inline]
         pub fn cookies_mut(&mut self) -> &mut CookieJar {
-            &mut self.cookies
+            let _ = self.cookies();
+            self.cookies.get_mut().expect("cookies should be initialized")
         }
         /// Get `Cookie` from cookies.
         #[inline]
@@ -562,7 +627,7 @@ impl Request {
         where
             T: AsRef<str>,
         {
-            self.cookies.get(name.as_ref())
+            self.cookies().get(name.as_ref())
         }
     }
     /// Get params reference.
@@ -585,11 +650,50 @@ impl Request {
         self.params.get(key).and_then(|v| from_str_val(v).ok())
     }
 
+    /// Get the limits of the parsers of this request.
+    ///
+    /// It is set from the [`ParseConfig`] of the matched router, the default has no limits.
+    #[inline]
+    pub fn parse_config(&self) -> ParseConfig {
+        self.parse_config.unwrap_or_default()
+    }
+    /// Set the limits of the parsers of this request.
+    #[inline]
+    pub fn set_parse_config(&mut self, parse_config: ParseConfig) {
+        self.parse_config = Some(parse_config);
+    }
+
+    /// Get the idempotency declared for this request.
+    ///
+    /// It is set from the [`Idempotent`] of the matched router, the default is [`Idempotent::ByMethod`].
+    #[inline]
+    pub fn idempotent(&self) -> Idempotent {
+        self.idempotent
+    }
+    /// Set the idempotency declared for this request.
+    #[inline]
+    pub fn set_idempotent(&mut self, idempotent: Idempotent) {
+        self.idempotent = idempotent;
+    }
+    /// Returns `true` if this request can be safely retried, cached or deduplicated.
+    ///
+    /// It uses the [`Idempotent`] declared by the matched router, falling back to the request method.
+    #[inline]
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent.is_idempotent(&self.method)
+    }
+
     /// Get queries reference.
+    ///
+    /// Pairs beyond the max number of query pairs of the [`ParseConfig`] and pairs with a too long key or value are
+    /// ignored, [`parse_queries`](Self::parse_queries) and the extractors reject them instead.
     pub fn queries(&self) -> &MultiMap<String, String> {
         self.queries.get_or_init(|| {
+            let config = self.parse_config();
             form_urlencoded::parse(self.uri.query().unwrap_or_default().as_bytes())
-                .into_owned()
+                .take(config.max_query_pairs.unwrap_or(usize::MAX))
+                .filter(|(key, value)| config.check_string(key).is_ok() && config.check_string(value).is_ok())
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
                 .collect()
         })
     }
@@ -600,12 +704,18 @@ impl Request {
     }
 
     /// Get query value from queries.
+    ///
+    /// If there is no query named `key`, the queries in `deepObject` style such as `key[field][op]=value` are
+    /// deserialized as an object.
     #[inline]
     pub fn query<'de, T>(&'de self, key: &str) -> Option<T>
     where
         T: Deserialize<'de>,
     {
-        self.queries().get_vec(key).and_then(|vs| from_str_multi_val(vs).ok())
+        match self.queries().get_vec(key) {
+            Some(vs) => from_str_multi_val(vs).ok(),
+            None => NestedValue::from_deep_object(self.queries().iter_all(), key).and_then(|v| T::deserialize(v).ok()),
+        }
     }
 
     /// Get field data from form.
@@ -680,13 +790,13 @@ impl Request {
             .unwrap_or_default()
     }
 
-    /// Get request payload with default max size limit(64KB).
+    /// Get request payload with the max body size of the [`ParseConfig`], or the default max size limit(64KB).
     ///
     /// <https://github.com/hyperium/hyper/issues/3111>
     /// *Notice: This method takes body.
     #[inline]
     pub async fn payload(&mut self) -> Result<&Bytes, ParseError> {
-        self.payload_with_max_size(secure_max_size()).await
+        self.payload_with_max_size(self.parse_config().body_size_limit()).await
     }
 
     /// Get request payload with max size limit.
@@ -709,15 +819,17 @@ impl Request {
 
     /// Get `FormData` reference from request.
     ///
-    /// *Notice: This method takes body and body's size is not limited.
+    /// *Notice: This method takes body and body's size is not limited, unless the [`ParseConfig`] sets a max body
+    /// size.
     #[inline]
     pub async fn form_data(&mut self) -> Result<&FormData, ParseError> {
         if let Some(ctype) = self.content_type() {
             if ctype.subtype() == mime::WWW_FORM_URLENCODED || ctype.type_() == mime::MULTIPART {
                 let body = self.take_body();
+                let config = self.parse_config();
                 let headers = self.headers();
                 self.form_data
-                    .get_or_try_init(|| async { FormData::read(headers, body).await })
+                    .get_or_try_init(|| async { FormData::read(headers, body, &config).await })
                     .await
             } else {
                 Err(ParseError::NotFormData)
@@ -761,6 +873,8 @@ impl Request {
     where
         T: Deserialize<'de>,
     {
+        self.parse_config()
+            .check_queries(self.uri.query().unwrap_or_default())?;
         let queries = self.queries().iter_all();
         from_str_multi_map(queries).map_err(ParseError::Deserialize)
     }
@@ -794,13 +908,15 @@ impl Request {
         }
     }
 
-    /// Parse json body as type `T` from request with default max size limit.
+    /// Parse json body as type `T` from request with the max body size of the [`ParseConfig`], or the default max
+    /// size limit.
     #[inline]
     pub async fn parse_json<'de, T>(&'de mut self) -> Result<T, ParseError>
     where
         T: Deserialize<'de>,
     {
-        self.parse_json_with_max_size(secure_max_size()).await
+        self.parse_json_with_max_size(self.parse_config().body_size_limit())
+            .await
     }
     /// Parse json body as type `T` from request with max size limit.
     #[inline]
@@ -809,9 +925,11 @@ impl Request {
         T: Deserialize<'de>,
     {
         let ctype = self.content_type();
+        let config = self.parse_config();
         if let Some(ctype) = ctype {
             if ctype.subtype() == mime::JSON {
                 return self.payload_with_max_size(max_size).await.and_then(|payload| {
+                    config.check_json(payload)?;
                     // fix issue https://github.com/salvo-rs/salvo/issues/545
                     let payload = if payload.is_empty() {
                         "null".as_bytes()
@@ -825,6 +943,35 @@ impl Request {
         Err(ParseError::InvalidContentType)
     }
 
+    /// Parse xml body as type `T` from request with the max body size of the [`ParseConfig`], or the default max
+    /// size limit.
+    #[inline]
+    pub async fn parse_xml<'de, T>(&'de mut self) -> Result<T, ParseError>
+    where
+        T: Deserialize<'de>,
+    {
+        self.parse_xml_with_max_size(self.parse_config().body_size_limit())
+            .await
+    }
+    /// Parse xml body as type `T` from request with max size limit.
+    ///
+    /// Both `application/xml`, `text/xml` and structured syntax suffixes such as `application/soap+xml` are accepted.
+    #[inline]
+    pub async fn parse_xml_with_max_size<'de, T>(&'de mut self, max_size: usize) -> Result<T, ParseError>
+    where
+        T: Deserialize<'de>,
+    {
+        if let Some(ctype) = self.content_type() {
+            if is_xml(&ctype) {
+                return self
+                    .payload_with_max_size(max_size)
+                    .await
+                    .and_then(|payload| serde_xml_rs::from_reader(payload.as_ref()).map_err(ParseError::SerdeXml));
+            }
+        }
+        Err(ParseError::InvalidContentType)
+    }
+
     /// Parse form body as type `T` from request.
     #[inline]
     pub async fn parse_form<'de, T>(&'de mut self) -> Result<T, ParseError>
@@ -839,16 +986,18 @@ impl Request {
         Err(ParseError::InvalidContentType)
     }
 
-    /// Parse json body or form body as type `T` from request with default max size.
+    /// Parse json body or form body as type `T` from request with the max body size of the [`ParseConfig`], or the
+    /// default max size.
     #[inline]
     pub async fn parse_body<'de, T>(&'de mut self) -> Result<T, ParseError>
     where
         T: Deserialize<'de>,
     {
-        self.parse_body_with_max_size(secure_max_size()).await
+        self.parse_body_with_max_size(self.parse_config().body_size_limit())
+            .await
     }
 
-    /// Parse json body or form body as type `T` from request with max size.
+    /// Parse json body, xml body or form body as type `T` from request with max size.
     pub async fn parse_body_with_max_size<'de, T>(&'de mut self, max_size: usize) -> Result<T, ParseError>
     where
         T: Deserialize<'de>,
@@ -857,22 +1006,47 @@ impl Request {
             if ctype.subtype() == mime::WWW_FORM_URLENCODED || ctype.subtype() == mime::FORM_DATA {
                 return from_str_multi_map(self.form_data().await?.fields.iter_all()).map_err(ParseError::Deserialize);
             } else if ctype.subtype() == mime::JSON {
+                let config = self.parse_config();
+                return self.payload_with_max_size(max_size).await.and_then(|body| {
+                    config.check_json(body)?;
+                    serde_json::from_slice::<T>(body).map_err(ParseError::SerdeJson)
+                });
+            } else if is_xml(&ctype) {
                 return self
                     .payload_with_max_size(max_size)
                     .await
-                    .and_then(|body| serde_json::from_slice::<T>(body).map_err(ParseError::SerdeJson));
+                    .and_then(|body| serde_xml_rs::from_reader(body.as_ref()).map_err(ParseError::SerdeXml));
             }
         }
         Err(ParseError::InvalidContentType)
     }
 }
 
+fn is_xml(ctype: &Mime) -> bool {
+    ctype.subtype() == mime::XML || ctype.suffix() == Some(mime::XML)
+}
+
+#[cfg(feature = "cookie")]
+fn parse_cookies(headers: &HeaderMap) -> CookieJar {
+    let mut cookie_jar = CookieJar::new();
+    for header in headers.get_all(http::header::COOKIE) {
+        if let Ok(header) = header.to_str() {
+            for cookie_str in header.split(';').map(|s| s.trim()) {
+                if let Ok(cookie) = Cookie::parse_encoded(cookie_str).map(|c| c.into_owned()) {
+                    cookie_jar.add_original(cookie);
+                }
+            }
+        }
+    }
+    cookie_jar
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{Deserialize, Serialize};
 
     use super::*;
-    use crate::test::TestClient;
+    use crate::test::{ResponseExt, TestClient};
 
     #[tokio::test]
     async fn test_parse_queries() {
@@ -963,4 +1137,95 @@ file content\r\n\
         let files = req.files("file1").await.unwrap();
         assert_eq!(files[0].name().unwrap(), "err.txt");
     }
+
+    #[tokio::test]
+    async fn test_router_parse_config() {
+        use crate::prelude::*;
+
+        #[handler]
+        async fn hello(req: &mut Request) -> String {
+            match req.parse_json::<Vec<Vec<u8>>>().await {
+                Ok(data) => format!("{}", data.len()),
+                Err(e) => e.to_string(),
+            }
+        }
+        let router = Router::new()
+            .push(
+                Router::with_path("strict")
+                    .parse_config(ParseConfig::new().max_json_depth(1))
+                    .post(hello),
+            )
+            .push(Router::with_path("lenient").post(hello));
+        let service = Service::new(router);
+        let content = TestClient::post("http://127.0.0.1:5800/strict")
+            .json(&vec![vec![1u8]])
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "The JSON depth limit is exceeded.");
+        let content = TestClient::post("http://127.0.0.1:5800/lenient")
+            .json(&vec![vec![1u8]])
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "1");
+    }
+
+    #[tokio::test]
+    async fn test_router_idempotent() {
+        use crate::prelude::*;
+
+        #[handler]
+        async fn hello(req: &mut Request) -> String {
+            req.is_idempotent().to_string()
+        }
+        let router = Router::new()
+            .idempotent(Idempotent::Yes)
+            .push(Router::with_path("search").post(hello))
+            .push(
+                Router::with_path("orders")
+                    .idempotent(Idempotent::ByMethod)
+                    .post(hello)
+                    .put(hello),
+            );
+        let service = Service::new(router);
+        for (client, expected) in [
+            (TestClient::post("http://127.0.0.1:5800/search"), "true"),
+            (TestClient::post("http://127.0.0.1:5800/orders"), "false"),
+            (TestClient::put("http://127.0.0.1:5800/orders"), "true"),
+        ] {
+            let content = client.send(&service).await.take_string().await.unwrap();
+            assert_eq!(content, expected);
+        }
+    }
+
+    #[test]
+    fn test_accept_cached() {
+        let mut req = Request::new();
+        req.add_header("accept", "application/json,text/html", true).unwrap();
+        assert_eq!(req.first_accept(), Some(mime::APPLICATION_JSON));
+        assert_eq!(req.accept(), vec![mime::APPLICATION_JSON, mime::TEXT_HTML]);
+        req.headers_mut()
+            .insert("accept", HeaderValue::from_static("text/plain"));
+        assert_eq!(req.accept(), vec![mime::TEXT_PLAIN]);
+    }
+    #[cfg(feature = "cookie")]
+    #[test]
+    fn test_cookies_parsed_lazily() {
+        let mut req = TestClient::get("http://127.0.0.1:5800/hello")
+            .add_header("cookie", "name=jobs; lang=rust", true)
+            .build();
+        assert!(req.cookies.get().is_none());
+        assert_eq!(req.cookie("name").unwrap().value(), "jobs");
+        req.cookies_mut().add(Cookie::new("age", "25"));
+        assert_eq!(req.cookies().iter().count(), 3);
+        req.headers_mut()
+            .insert("cookie", HeaderValue::from_static("name=gates"));
+        assert_eq!(req.cookie("name").unwrap().value(), "gates");
+        assert_eq!(req.cookies().iter().count(), 1);
+    }
 }
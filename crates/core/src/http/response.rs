@@ -1,7 +1,10 @@
 //! HTTP response.
 use std::collections::VecDeque;
 use std::fmt::{self, Display, Formatter};
+use std::future::Future;
 use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 #[cfg(feature = "cookie")]
 use cookie::{Cookie, CookieJar};
@@ -9,11 +12,14 @@ use futures_util::stream::Stream;
 use http::header::{HeaderMap, HeaderValue, IntoHeaderName};
 pub use http::response::Parts;
 use http::{version::Version, Extensions};
+use hyper::body::{Body, Frame, SizeHint};
 use mime::Mime;
+use tokio_util::sync::CancellationToken;
 
 use crate::fs::NamedFile;
 use crate::fuse::TransProto;
 use crate::http::{StatusCode, StatusError};
+use crate::tasks::Deferred;
 use crate::{BoxedError, Error, Scribe};
 use bytes::Bytes;
 
@@ -35,6 +41,8 @@ pub struct Response {
     pub body: ResBody,
     /// Used to store extra data derived from the underlying protocol.
     pub extensions: Extensions,
+    pub(crate) cancellation: Option<CancellationToken>,
+    pub(crate) deferred: Deferred,
 }
 impl Default for Response {
     #[inline]
@@ -82,6 +90,8 @@ where
             #[cfg(feature = "cookie")]
             cookies,
             extensions: Extensions::new(),
+            cancellation: None,
+            deferred: Deferred::default(),
         }
     }
 }
@@ -98,6 +108,8 @@ impl Response {
             #[cfg(feature = "cookie")]
             cookies: CookieJar::default(),
             extensions: Extensions::new(),
+            cancellation: None,
+            deferred: Deferred::default(),
         }
     }
 
@@ -112,9 +124,49 @@ impl Response {
             headers: HeaderMap::new(),
             cookies,
             extensions: Extensions::new(),
+            cancellation: None,
+            deferred: Deferred::default(),
         }
     }
 
+    /// Get the token which is cancelled when the client disconnects.
+    ///
+    /// Long-running work producing the response, such as a task sending chunks through a [`BodySender`], can stop
+    /// promptly when the peer goes away instead of discovering it only at write time. Responses which are not
+    /// produced by a [`Service`](crate::Service) get a token which is never cancelled.
+    #[inline]
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone().unwrap_or_default()
+    }
+
+    /// Defer `job` until the response is sent.
+    ///
+    /// Non-critical work such as audit writes, cache population and notifications does not delay the response. The
+    /// job is spawned when the body is sent, or the client disconnects, and runs on a pool which
+    /// [`Server`](crate::Server) drains when it is stopped, see [`drain_deferred`](crate::tasks::drain_deferred).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use salvo_core::prelude::*;
+    ///
+    /// #[handler]
+    /// async fn order(res: &mut Response) {
+    ///     res.render("ordered");
+    ///     res.on_sent(async move {
+    ///         println!("send confirmation email");
+    ///     });
+    /// }
+    /// ```
+    #[inline]
+    pub fn on_sent<F>(&mut self, job: F) -> &mut Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.deferred.push(Box::pin(job));
+        self
+    }
+
     /// Get headers reference.
     #[inline]
     pub fn headers(&self) -> &HeaderMap {
@@ -219,6 +271,7 @@ impl Response {
             headers,
             body,
             extensions,
+            deferred,
             ..
         } = self;
 
@@ -234,6 +287,14 @@ impl Response {
             ResBody::Error(e) => e.code,
             _ => StatusCode::OK,
         });
+        let body = if deferred.is_empty() {
+            body
+        } else {
+            ResBody::Boxed(Box::pin(DeferredBody {
+                body,
+                _deferred: deferred,
+            }))
+        };
         let mut res = hyper::Response::new(body);
         *res.extensions_mut() = extensions;
         *res.headers_mut() = headers;
@@ -479,6 +540,33 @@ impl Response {
     }
 }
 
+/// Response body which spawns the deferred jobs of the response when it is dropped, after it is sent.
+struct DeferredBody {
+    body: ResBody,
+    _deferred: Deferred,
+}
+impl Body for DeferredBody {
+    type Data = Bytes;
+    type Error = BoxedError;
+
+    #[inline]
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, BoxedError>>> {
+        Pin::new(&mut self.body)
+            .poll_frame(cx)
+            .map(|frame| frame.map(|frame| frame.map_err(Into::into)))
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        Body::size_hint(&self.body)
+    }
+}
+
 impl fmt::Debug for Response {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         f.debug_struct("Response")
@@ -526,6 +614,25 @@ mod test {
         assert_eq!("hello", &result)
     }
 
+    #[tokio::test]
+    async fn test_on_sent() {
+        let (tx, mut rx) = tokio::sync::oneshot::channel();
+        let mut res = Response::new();
+        res.render("hello");
+        res.on_sent(async move {
+            tx.send(()).unwrap();
+        });
+        let mut body = res.into_hyper().into_body();
+        assert_eq!(Body::size_hint(&body).exact(), Some(5));
+        while body.next().await.is_some() {}
+        tokio::task::yield_now().await;
+        assert!(rx.try_recv().is_err());
+
+        drop(body);
+        crate::tasks::drain_deferred().await;
+        assert!(rx.try_recv().is_ok());
+    }
+
     #[tokio::test]
     async fn test_body_stream2() {
         let mut body = ResBody::stream(iter(vec![
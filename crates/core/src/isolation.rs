@@ -0,0 +1,353 @@
+//! Supervised execution of handlers.
+//!
+//! When an [`Isolation`] is set on the [`Service`](crate::Service), the hoops and the handler of each request run in
+//! their own task, supervised by the connection task:
+//!
+//! - A panic in a handler is contained in its task and turned into a `500 Internal Server Error` response, the
+//!   connection and the other requests on it are not affected. Nothing is unwound across the connection task, so
+//!   the service stays consistent with `panic = "abort"` too, where the panic aborts the process as usual.
+//! - The time spent polling the handler is accounted, a request which exceeds its poll time budget is cancelled with
+//!   a `503 Service Unavailable` response at its next await point which yields. The budget is only checked between
+//!   polls, so a handler which blocks or loops without yielding can not be stopped, it keeps its worker thread busy
+//!   until it yields or returns. Run CPU heavy work with `spawn_blocking` instead.
+//! - When the client disconnects, the request is cancelled and the handler is dropped at its next await point.
+//!   Handlers observe the cancellation with [`Request::cancelled`] and [`Request::is_cancelled`], and
+//!   [`FlowCtrl`] does not call the rest handlers of a cancelled request.
+//!
+//! The accounting of the current request is injected into the [`Depot`] as a [`Supervision`], handlers can read the
+//! memory hint to size buffers and caches, and the poll time spent so far.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_core::isolation::Isolation;
+//! use salvo_core::prelude::*;
+//!
+//! #[handler]
+//! async fn report(req: &mut Request) -> &'static str {
+//!     tokio::select! {
+//!         _ = req.cancelled() => "cancelled",
+//!         _ = tokio::time::sleep(Duration::from_secs(10)) => "done",
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::with_path("report").get(report);
+//!     let service = Service::new(router).isolation(
+//!         Isolation::new()
+//!             .poll_budget(Duration::from_millis(200))
+//!             .memory_hint(64 * 1024 * 1024),
+//!     );
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(service).await;
+//! }
+//! ```
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use pin_project::pin_project;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+
+use crate::http::{Request, Response, StatusError};
+use crate::routing::FlowCtrl;
+use crate::Depot;
+
+/// Settings of the supervised execution of handlers.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug)]
+pub struct Isolation {
+    poll_budget: Option<Duration>,
+    memory_hint: Option<usize>,
+    cancel_on_disconnect: bool,
+}
+
+impl Default for Isolation {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Isolation {
+    /// Create a new `Isolation` without poll time budget and memory hint, requests are cancelled when clients
+    /// disconnect.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            poll_budget: None,
+            memory_hint: None,
+            cancel_on_disconnect: true,
+        }
+    }
+
+    /// Sets the total time which can be spent polling the handlers of a request.
+    ///
+    /// Time spent waiting on I/O or timers is not counted, only the time the request keeps a worker thread busy. The
+    /// budget is checked each time the handlers yield, a request which exceeds it is cancelled at that point, but a
+    /// handler which never yields is not interrupted.
+    #[inline]
+    pub fn poll_budget(mut self, poll_budget: Duration) -> Self {
+        self.poll_budget = Some(poll_budget);
+        self
+    }
+
+    /// Sets the max memory in bytes a request is expected to use.
+    ///
+    /// It is a hint which is not enforced, handlers read it from the [`Supervision`] in the [`Depot`].
+    #[inline]
+    pub fn memory_hint(mut self, memory_hint: usize) -> Self {
+        self.memory_hint = Some(memory_hint);
+        self
+    }
+
+    /// Sets whether the handlers are dropped when the client disconnects, the default is `true`.
+    ///
+    /// If it is `false`, handlers run to completion, they can still observe the disconnection with
+    /// [`Request::cancelled`].
+    #[inline]
+    pub fn cancel_on_disconnect(mut self, cancel_on_disconnect: bool) -> Self {
+        self.cancel_on_disconnect = cancel_on_disconnect;
+        self
+    }
+
+    /// Calls the handlers of `ctrl` in a supervised task.
+    pub(crate) async fn supervise(&self, mut ctrl: FlowCtrl, req: &mut Request, depot: &mut Depot, res: &mut Response) {
+        let token = req.cancellation.get_or_insert_with(CancellationToken::new).clone();
+        let supervision = Supervision {
+            stats: Default::default(),
+            poll_budget: self.poll_budget,
+            memory_hint: self.memory_hint,
+        };
+        let stats = supervision.stats.clone();
+        depot.inject(supervision);
+        // The request is lost if the handler panics, the catcher gets a copy of its head.
+        let (method, uri, headers) = (req.method().clone(), req.uri().clone(), req.headers().clone());
+
+        let mut task_req = std::mem::take(req);
+        let mut task_depot = std::mem::take(depot);
+        let mut task_res = std::mem::take(res);
+        let poll_budget = self.poll_budget;
+        let cancel_on_disconnect = self.cancel_on_disconnect;
+        let task_token = token.clone();
+        let handle = tokio::spawn(
+            async move {
+                let fut = Accounted {
+                    inner: ctrl.call_next(&mut task_req, &mut task_depot, &mut task_res),
+                    stats,
+                    poll_budget,
+                };
+                let outcome = if cancel_on_disconnect {
+                    tokio::select! {
+                        biased;
+                        _ = task_token.cancelled() => Outcome::Cancelled,
+                        outcome = fut => outcome,
+                    }
+                } else {
+                    fut.await
+                };
+                (outcome, task_req, task_depot, task_res)
+            }
+            .in_current_span(),
+        );
+        match handle.await {
+            Ok((outcome, task_req, task_depot, task_res)) => {
+                *req = task_req;
+                *depot = task_depot;
+                *res = task_res;
+                match outcome {
+                    Outcome::Finished => {}
+                    Outcome::Cancelled => tracing::debug!("request is cancelled"),
+                    Outcome::BudgetExceeded => {
+                        tracing::warn!(uri = ?req.uri(), "request exceeded its poll time budget");
+                        res.render(StatusError::service_unavailable().brief("Request exceeded its poll time budget."));
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = %e, uri = ?uri, "handler panicked");
+                *req.method_mut() = method;
+                *req.uri_mut() = uri;
+                *req.headers_mut() = headers;
+                req.cancellation = Some(token.clone());
+                res.cancellation = Some(token);
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct Stats {
+    polls: AtomicU64,
+    poll_nanos: AtomicU64,
+}
+
+/// Resource accounting of the current request, injected into the [`Depot`] when an [`Isolation`] is set.
+#[derive(Clone, Debug)]
+pub struct Supervision {
+    stats: Arc<Stats>,
+    poll_budget: Option<Duration>,
+    memory_hint: Option<usize>,
+}
+
+impl Supervision {
+    /// Get the number of times the handlers have been polled.
+    #[inline]
+    pub fn polls(&self) -> u64 {
+        self.stats.polls.load(Ordering::Relaxed)
+    }
+
+    /// Get the total time spent polling the handlers.
+    #[inline]
+    pub fn poll_time(&self) -> Duration {
+        Duration::from_nanos(self.stats.poll_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Get the poll time budget of the request.
+    #[inline]
+    pub fn poll_budget(&self) -> Option<Duration> {
+        self.poll_budget
+    }
+
+    /// Get the max memory in bytes the request is expected to use.
+    #[inline]
+    pub fn memory_hint(&self) -> Option<usize> {
+        self.memory_hint
+    }
+}
+
+/// Extension trait for getting the [`Supervision`] from the [`Depot`].
+pub trait SupervisionDepotExt {
+    /// Get the resource accounting of the current request.
+    ///
+    /// Returns `None` if no [`Isolation`] is set in the [`Service`](crate::Service).
+    fn supervision(&self) -> Option<&Supervision>;
+}
+
+impl SupervisionDepotExt for Depot {
+    #[inline]
+    fn supervision(&self) -> Option<&Supervision> {
+        self.obtain::<Supervision>().ok()
+    }
+}
+
+enum Outcome {
+    Finished,
+    Cancelled,
+    BudgetExceeded,
+}
+
+/// Accounts the time spent polling the inner future.
+///
+/// The budget is checked after a poll returns `Pending`, a poll which never returns can not be cancelled.
+#[pin_project]
+struct Accounted<F> {
+    #[pin]
+    inner: F,
+    stats: Arc<Stats>,
+    poll_budget: Option<Duration>,
+}
+
+impl<F: Future> Future for Accounted<F> {
+    type Output = Outcome;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let started = Instant::now();
+        let poll = this.inner.poll(cx);
+        let elapsed = started.elapsed().as_nanos() as u64;
+        this.stats.polls.fetch_add(1, Ordering::Relaxed);
+        let total = this.stats.poll_nanos.fetch_add(elapsed, Ordering::Relaxed) + elapsed;
+        match poll {
+            Poll::Ready(_) => Poll::Ready(Outcome::Finished),
+            Poll::Pending if this.poll_budget.is_some_and(|budget| total > budget.as_nanos() as u64) => {
+                Poll::Ready(Outcome::BudgetExceeded)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn boom() {
+        panic!("boom");
+    }
+
+    #[handler]
+    async fn busy(depot: &mut Depot) -> String {
+        loop {
+            std::thread::sleep(Duration::from_millis(5));
+            tokio::task::yield_now().await;
+            if depot.supervision().map(|s| s.poll_time()).unwrap_or_default() > Duration::from_secs(1) {
+                return "finished".into();
+            }
+        }
+    }
+
+    #[handler]
+    async fn hello(depot: &mut Depot) -> String {
+        format!("{:?}", depot.supervision().and_then(|s| s.memory_hint()))
+    }
+
+    #[tokio::test]
+    async fn test_isolation_panic_and_budget() {
+        let router = Router::new()
+            .push(Router::with_path("panic").get(boom))
+            .push(Router::with_path("busy").get(busy))
+            .push(Router::with_path("hello").get(hello));
+        let service = Service::new(router).isolation(
+            Isolation::new()
+                .poll_budget(Duration::from_millis(20))
+                .memory_hint(1024),
+        );
+
+        let res = TestClient::get("http://127.0.0.1:5801/panic").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+        let res = TestClient::get("http://127.0.0.1:5801/busy").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        let content = TestClient::get("http://127.0.0.1:5801/hello")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "Some(1024)");
+    }
+
+    static OBSERVED: AtomicBool = AtomicBool::new(false);
+
+    #[handler]
+    async fn wait(req: &mut Request) {
+        req.cancelled().await;
+        OBSERVED.store(true, Ordering::SeqCst);
+    }
+
+    #[tokio::test]
+    async fn test_isolation_cancel() {
+        let service = Service::new(Router::new().get(wait)).isolation(Isolation::new().cancel_on_disconnect(false));
+        // Dropping the response future is what happens when the client disconnects.
+        let result = tokio::time::timeout(Duration::from_millis(20), service.handle(Request::new())).await;
+        assert!(result.is_err());
+        while !OBSERVED.load(Ordering::SeqCst) {
+            tokio::task::yield_now().await;
+        }
+    }
+}
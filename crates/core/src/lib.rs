@@ -22,6 +22,9 @@
 //! | `tower-compat` | Adapters for `tower::Layer` and `tower::Service` | ❌ |
 //! | `anyhow` | Integrate with the [`anyhow`](https://crates.io/crates/anyhow) crate | ❌ |
 //! | `eyre` | Integrate with the [`eyre`](https://crates.io/crates/eyre) crate | ❌ |
+//! | `sqlx` | Convert [`sqlx`](https://crates.io/crates/sqlx) errors into [`Error`] | ❌ |
+//! | `reqwest` | Convert [`reqwest`](https://crates.io/crates/reqwest) errors into [`Error`] | ❌ |
+//! | `vault` | Load secrets from [HashiCorp Vault](https://www.vaultproject.io) | ❌ |
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -38,8 +41,11 @@ extern crate self as salvo_core;
 #[macro_use]
 mod cfg;
 
+pub mod admission;
 pub mod catcher;
 pub mod conn;
+pub mod container;
+pub mod deadline;
 mod depot;
 mod error;
 pub mod extract;
@@ -47,9 +53,13 @@ pub mod fs;
 pub mod fuse;
 pub mod handler;
 pub mod http;
+pub mod isolation;
+pub mod limits;
+mod pool;
 pub mod proto;
 pub mod routing;
 pub mod rt;
+pub mod secret;
 #[doc(hidden)]
 pub mod serde;
 cfg_feature! {
@@ -58,6 +68,10 @@ cfg_feature! {
     pub use self::server::Server;
 }
 mod service;
+pub mod span;
+pub mod startup;
+pub mod strict;
+pub mod tasks;
 pub mod writing;
 cfg_feature! {
     #![feature ="test"]
@@ -70,12 +84,12 @@ cfg_feature! {
 cfg_feature! {
     #![feature ="tower-compat"]
     pub mod tower_compat;
-    pub use tower_compat::{TowerServiceCompat, TowerLayerCompat};
+    pub use tower_compat::{TowerServiceCompat, TowerLayerCompat, TowerServiceAdapter};
 }
 
 pub use self::conn::Listener;
 pub use self::depot::Depot;
-pub use self::error::{BoxedError, Error};
+pub use self::error::{BoxedError, Error, IntoStatusCode, ResultExt};
 pub use self::extract::Extractible;
 pub use self::handler::Handler;
 pub use self::http::{Request, Response};
@@ -84,6 +98,8 @@ pub use self::service::Service;
 pub use self::writing::{Scribe, Writer};
 /// Result type which has `salvo::Error` as it's error type.
 pub type Result<T> = std::result::Result<T, Error>;
+/// Result type returned by handlers, errors are rendered as responses with the status of [`Error::status`].
+pub type AppResult<T, E = Error> = std::result::Result<T, E>;
 
 /// A list of things that automatically imports into application use salvo_core.
 pub mod prelude {
@@ -92,6 +108,7 @@ pub mod prelude {
 
     pub use crate::depot::Depot;
     pub use crate::http::{Request, Response, StatusCode, StatusError};
+    pub use crate::AppResult;
     cfg_feature! {
         #![feature = "acme"]
         pub use crate::conn::AcmeListener;
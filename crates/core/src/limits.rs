@@ -0,0 +1,183 @@
+//! Limits of the request line and headers.
+//!
+//! When a request exceeds a limit of the HTTP/1 parser, such as its read buffer, hyper answers it or closes the
+//! connection by itself: the request never reaches the service, so it is not logged and the response can not be
+//! customized. When [`HeadLimits`] is set on the [`Service`](crate::Service) with limits below the ones of the
+//! parser, such requests are rejected by the service instead, before routing:
+//!
+//! - A request target longer than [`HeadLimits::max_uri_length`] is rejected with `414 URI Too Long`.
+//! - More headers than [`HeadLimits::max_headers`], or headers larger than [`HeadLimits::max_header_size`] in total,
+//!   are rejected with `431 Request Header Fields Too Large`.
+//!
+//! The rejection is logged, and rendered as a [`StatusError`] which the [`Catcher`](crate::catcher::Catcher) of the
+//! service customizes like any other error.
+//!
+//! The parser limits are set on the server, see
+//! [`HttpBuilder::http1_max_buf_size`](crate::conn::HttpBuilder::http1_max_buf_size), raise them above the limits
+//! of the service, or requests exceeding both are still rejected by the parser.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::limits::HeadLimits;
+//! use salvo_core::prelude::*;
+//!
+//! let service = Service::new(Router::new()).head_limits(
+//!     HeadLimits::new()
+//!         .max_uri_length(4 * 1024)
+//!         .max_headers(64)
+//!         .max_header_size(16 * 1024),
+//! );
+//! ```
+use crate::http::{Request, StatusError};
+
+/// Limits of the request line and headers.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug)]
+pub struct HeadLimits {
+    max_uri_length: Option<usize>,
+    max_headers: Option<usize>,
+    max_header_size: Option<usize>,
+    log_rejections: bool,
+}
+
+impl Default for HeadLimits {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeadLimits {
+    /// Create a new `HeadLimits` without limits, rejections are logged.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            max_uri_length: None,
+            max_headers: None,
+            max_header_size: None,
+            log_rejections: true,
+        }
+    }
+
+    /// Sets the max length in bytes of the request target.
+    #[inline]
+    pub fn max_uri_length(mut self, length: usize) -> Self {
+        self.max_uri_length = Some(length);
+        self
+    }
+
+    /// Sets the max number of headers.
+    #[inline]
+    pub fn max_headers(mut self, count: usize) -> Self {
+        self.max_headers = Some(count);
+        self
+    }
+
+    /// Sets the max size in bytes of all headers, counting the names, the values and the separators of each line.
+    #[inline]
+    pub fn max_header_size(mut self, size: usize) -> Self {
+        self.max_header_size = Some(size);
+        self
+    }
+
+    /// Sets whether rejections are logged, the default is `true`.
+    #[inline]
+    pub fn log_rejections(mut self, log_rejections: bool) -> Self {
+        self.log_rejections = log_rejections;
+        self
+    }
+
+    /// Check `req`, returns the error which the request is rejected with.
+    pub fn check(&self, req: &Request) -> Result<(), StatusError> {
+        let result = self.scan(req);
+        if self.log_rejections {
+            if let Err(e) = &result {
+                tracing::warn!(
+                    status = %e.code,
+                    reason = %e.brief,
+                    remote_addr = %req.remote_addr(),
+                    method = req.method().as_str(),
+                    "request head exceeds limits"
+                );
+            }
+        }
+        result
+    }
+
+    fn scan(&self, req: &Request) -> Result<(), StatusError> {
+        if let Some(max) = self.max_uri_length {
+            let length = req
+                .uri()
+                .path_and_query()
+                .map(|path_and_query| path_and_query.as_str().len())
+                .unwrap_or_default();
+            if length > max {
+                return Err(StatusError::uri_too_long());
+            }
+        }
+        let headers = req.headers();
+        if self.max_headers.is_some_and(|max| headers.len() > max) {
+            return Err(StatusError::request_header_fields_toolarge().brief("Too many header fields."));
+        }
+        if let Some(max) = self.max_header_size {
+            let size = headers
+                .iter()
+                .map(|(name, value)| name.as_str().len() + value.len() + 4)
+                .sum::<usize>();
+            if size > max {
+                return Err(StatusError::request_header_fields_toolarge());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catcher::Catcher;
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_head_limits() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        #[handler]
+        async fn custom(res: &mut Response, ctrl: &mut FlowCtrl) {
+            if let Some(code) = res.status_code {
+                res.render(format!("rejected {}", code.as_u16()));
+                ctrl.skip_rest();
+            }
+        }
+
+        let limits = HeadLimits::new()
+            .max_uri_length(32)
+            .max_headers(4)
+            .max_header_size(64)
+            .log_rejections(false);
+        let service = Service::new(Router::new().get(hello))
+            .head_limits(limits)
+            .catcher(Catcher::default().hoop(custom));
+
+        let res = TestClient::get("http://127.0.0.1:5801/?q=short").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+
+        let mut res = TestClient::get(format!("http://127.0.0.1:5801/?q={}", "a".repeat(32)))
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::URI_TOO_LONG));
+        assert_eq!(res.take_string().await.unwrap(), "rejected 414");
+
+        let res = TestClient::get("http://127.0.0.1:5801")
+            .add_header("x-large", "a".repeat(64), true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE));
+    }
+}
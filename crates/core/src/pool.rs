@@ -0,0 +1,93 @@
+//! Pool of per-request allocations.
+use parking_lot::Mutex;
+
+use crate::routing::PathParams;
+use crate::Depot;
+
+/// Max number of each kind of objects kept in a pool, a connection rarely handles more requests concurrently.
+const MAX_POOLED: usize = 16;
+/// Objects which have grown larger than this capacity are dropped instead of pooled.
+const MAX_CAPACITY: usize = 64;
+
+/// Pool of the allocations used by a request, such as the [`Depot`] and the path params.
+///
+/// A pool is created for each connection, so the allocations are reused across keep-alive requests. All pooled
+/// objects are cleared before they are reused.
+#[derive(Default)]
+pub(crate) struct RequestPool {
+    depots: Mutex<Vec<Depot>>,
+    params: Mutex<Vec<PathParams>>,
+    parts: Mutex<Vec<Vec<String>>>,
+}
+
+impl RequestPool {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Default::default()
+    }
+
+    /// Take a cleared depot, a new depot is created if the pool is empty.
+    pub(crate) fn depot(&self) -> Depot {
+        self.depots.lock().pop().unwrap_or_default()
+    }
+
+    /// Take the buffers used by [`PathState`](crate::routing::PathState).
+    pub(crate) fn path_buffers(&self) -> (Vec<String>, PathParams) {
+        let parts = self.parts.lock().pop().unwrap_or_default();
+        let params = self.params.lock().pop().unwrap_or_default();
+        (parts, params)
+    }
+
+    /// Return a depot to the pool.
+    pub(crate) fn recycle_depot(&self, mut depot: Depot) {
+        if depot.capacity() > MAX_CAPACITY {
+            return;
+        }
+        depot.clear();
+        push_bounded(&mut self.depots.lock(), depot);
+    }
+
+    /// Return the buffers used by [`PathState`](crate::routing::PathState) to the pool.
+    pub(crate) fn recycle_path_buffers(&self, mut parts: Vec<String>, mut params: PathParams) {
+        if parts.capacity() <= MAX_CAPACITY {
+            parts.clear();
+            push_bounded(&mut self.parts.lock(), parts);
+        }
+        if params.capacity() <= MAX_CAPACITY {
+            params.clear();
+            push_bounded(&mut self.params.lock(), params);
+        }
+    }
+}
+
+#[inline]
+fn push_bounded<T>(pool: &mut Vec<T>, value: T) {
+    if pool.len() < MAX_POOLED {
+        pool.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RequestPool;
+
+    #[test]
+    fn test_request_pool() {
+        let pool = RequestPool::new();
+        let mut depot = pool.depot();
+        depot.insert("user", "jobs");
+        let capacity = depot.capacity();
+        pool.recycle_depot(depot);
+        let depot = pool.depot();
+        assert!(!depot.contains_key("user"));
+        assert_eq!(depot.capacity(), capacity);
+
+        let (mut parts, mut params) = pool.path_buffers();
+        parts.push("users".into());
+        params.insert("id".into(), "1".into());
+        pool.recycle_path_buffers(parts, params);
+        let (parts, params) = pool.path_buffers();
+        assert!(parts.is_empty() && parts.capacity() > 0);
+        assert!(params.is_empty() && params.capacity() > 0);
+    }
+}
@@ -17,6 +17,20 @@ use crate::routing::PathState;
 pub use others::*;
 pub use path::*;
 
+/// Describes which path segment a filter requires, it is used to index routers when a [`Service`] is created.
+///
+/// [`Service`]: crate::Service
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PathMatch {
+    /// The filter does not consume the path, and it has no side effect unless the path is ended.
+    Ignored,
+    /// The filter only matches when the remaining part of the current path segment equals the value.
+    Segment(String),
+    /// The filter may match any path.
+    Unknown,
+}
+
 /// Trait for filter request.
 ///
 /// View [module level documentation](../index.html) for more details.
@@ -30,6 +44,10 @@ pub trait Filter: fmt::Debug + Send + Sync + 'static {
     fn type_name(&self) -> &'static str {
         std::any::type_name::<Self>()
     }
+    #[doc(hidden)]
+    fn path_template(&self) -> Option<&str> {
+        None
+    }
     /// Create a new filter use `And` filter.
     #[inline]
     fn and<F>(self, other: F) -> And<Self, F>
@@ -84,6 +102,15 @@ pub trait Filter: fmt::Debug + Send + Sync + 'static {
 
     /// Filter `Request` and returns false or true.
     fn filter(&self, req: &mut Request, path: &mut PathState) -> bool;
+
+    /// Describes which path segment this filter requires.
+    ///
+    /// Filters which read or consume the path should keep the default value [`PathMatch::Unknown`], a wrong
+    /// value causes routers to be skipped when matching requests.
+    #[inline]
+    fn path_match(&self) -> PathMatch {
+        PathMatch::Unknown
+    }
 }
 
 /// `FnFilter` accepts a function as it's param, use this function to filter request.
@@ -1,7 +1,7 @@
 use std::fmt::{self, Formatter};
 
 use crate::http::Request;
-use crate::routing::{Filter, PathState};
+use crate::routing::{Filter, PathMatch, PathState};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Or<T, U> {
@@ -22,6 +22,15 @@ where
             self.second.filter(req, state)
         }
     }
+    #[inline]
+    fn path_match(&self) -> PathMatch {
+        let first = self.first.path_match();
+        if first == self.second.path_match() {
+            first
+        } else {
+            PathMatch::Unknown
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -70,6 +79,13 @@ where
             self.second.filter(req, state)
         }
     }
+    #[inline]
+    fn path_match(&self) -> PathMatch {
+        match self.first.path_match() {
+            PathMatch::Ignored => self.second.path_match(),
+            first => first,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
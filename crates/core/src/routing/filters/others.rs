@@ -2,7 +2,7 @@ use std::fmt::{self, Formatter};
 
 use crate::http::uri::Scheme;
 use crate::http::{Method, Request};
-use crate::routing::{Filter, PathState};
+use crate::routing::{Filter, PathMatch, PathState};
 
 /// Filter by request method
 #[derive(Clone, PartialEq, Eq)]
@@ -21,6 +21,10 @@ impl Filter for MethodFilter {
         }
         req.method() == self.0
     }
+    #[inline]
+    fn path_match(&self) -> PathMatch {
+        PathMatch::Ignored
+    }
 }
 impl fmt::Debug for MethodFilter {
     #[inline]
@@ -54,6 +58,10 @@ impl Filter for SchemeFilter {
     fn filter(&self, req: &mut Request, _state: &mut PathState) -> bool {
         req.uri().scheme().map(|s| s == &self.scheme).unwrap_or(self.lack)
     }
+    #[inline]
+    fn path_match(&self) -> PathMatch {
+        PathMatch::Ignored
+    }
 }
 impl fmt::Debug for SchemeFilter {
     #[inline]
@@ -109,6 +117,10 @@ impl Filter for HostFilter {
         .map(|h| h == self.host)
         .unwrap_or(self.lack)
     }
+    #[inline]
+    fn path_match(&self) -> PathMatch {
+        PathMatch::Ignored
+    }
 }
 impl fmt::Debug for HostFilter {
     #[inline]
@@ -164,6 +176,10 @@ impl Filter for PortFilter {
         .map(|p| p == self.port)
         .unwrap_or(self.lack)
     }
+    #[inline]
+    fn path_match(&self) -> PathMatch {
+        PathMatch::Ignored
+    }
 }
 impl fmt::Debug for PortFilter {
     #[inline]
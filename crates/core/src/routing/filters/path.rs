@@ -10,7 +10,7 @@ use parking_lot::RwLock;
 use regex::Regex;
 
 use crate::http::Request;
-use crate::routing::{Filter, PathState};
+use crate::routing::{Filter, PathMatch, PathState};
 
 /// PathWisp
 pub trait PathWisp: Send + Sync + fmt::Debug + 'static {
@@ -862,6 +862,18 @@ impl Filter for PathFilter {
     fn filter(&self, _req: &mut Request, state: &mut PathState) -> bool {
         self.detect(state)
     }
+    #[inline]
+    fn path_match(&self) -> PathMatch {
+        match self.path_wisps.first() {
+            None => PathMatch::Ignored,
+            Some(WispKind::Const(wisp)) => PathMatch::Segment(wisp.0.clone()),
+            Some(_) => PathMatch::Unknown,
+        }
+    }
+    #[inline]
+    fn path_template(&self) -> Option<&str> {
+        Some(&self.raw_value)
+    }
 }
 impl PathFilter {
     /// Create new `PathFilter`.
@@ -0,0 +1,302 @@
+use std::sync::Arc;
+
+use super::{PathMatch, PathState, Router};
+
+/// Index of a router tree, it is built when a [`Service`](crate::Service) is created.
+///
+/// Each node keeps the children of a router in a radix trie keyed by the path segment they require, so the
+/// children which can match the current segment are found in time linear to the segment length, and only they
+/// are detected, in their original order. Children whose filters do not describe a segment are always detected.
+///
+/// The route of each router, joined from the path templates of its ancestors, is built here once so it is not
+/// joined again on every match.
+#[derive(Debug, Default)]
+pub(crate) struct RouterIndex {
+    router_id: usize,
+    route: Arc<str>,
+    children: Vec<RouterIndex>,
+    segments: SegmentTrie,
+    others: Vec<usize>,
+}
+
+impl RouterIndex {
+    pub(crate) fn new(router: &Router) -> Self {
+        Self::with_prefix(router, "")
+    }
+
+    fn with_prefix(router: &Router, prefix: &str) -> Self {
+        let route: Arc<str> = format!("{prefix}{}", router.path_template()).into();
+        let mut segments = SegmentTrie::default();
+        let mut others = Vec::new();
+        for (i, child) in router.routers.iter().enumerate() {
+            match leading_segment(child) {
+                Some(segment) => segments.insert(segment.as_bytes(), i),
+                None => others.push(i),
+            }
+        }
+        Self {
+            router_id: router.id,
+            children: router
+                .routers
+                .iter()
+                .map(|child| RouterIndex::with_prefix(child, &route))
+                .collect(),
+            route,
+            segments,
+            others,
+        }
+    }
+
+    /// Check whether this index is built from the router, the routers may be replaced after the index is built.
+    #[inline]
+    pub(crate) fn is_for(&self, router: &Router) -> bool {
+        self.router_id == router.id && self.children.len() == router.routers.len()
+    }
+
+    /// The path templates of the router and its ancestors joined, such as `/users/<id>`.
+    #[inline]
+    pub(crate) fn route(&self) -> &Arc<str> {
+        &self.route
+    }
+
+    #[inline]
+    pub(crate) fn child(&self, index: usize) -> Option<&RouterIndex> {
+        self.children.get(index)
+    }
+
+    /// Returns the indexes of children which may match the current path segment, ordered as the children.
+    ///
+    /// Returns `None` if all children should be detected.
+    pub(crate) fn candidates<'a>(&'a self, state: &PathState) -> Option<Candidates<'a>> {
+        // Filters may have side effects when the path is ended, such as `MethodFilter`.
+        if self.segments.is_empty() || state.is_ended() {
+            return None;
+        }
+        let keyed = state
+            .pick()
+            .and_then(|segment| self.segments.get(segment.as_bytes()))
+            .unwrap_or_default();
+        Some(Candidates {
+            keyed,
+            others: &self.others,
+        })
+    }
+}
+
+/// Radix trie from path segments to the ordered indexes of the children which require them.
+///
+/// Edges are labeled with byte strings, nodes with a single child and no value are merged into their parent.
+#[derive(Debug, Default)]
+struct SegmentTrie {
+    label: Vec<u8>,
+    values: Vec<usize>,
+    // Sorted by the first byte of their labels, which are never empty and never share the first byte.
+    nodes: Vec<SegmentTrie>,
+}
+
+impl SegmentTrie {
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.nodes.is_empty()
+    }
+
+    fn insert(&mut self, key: &[u8], value: usize) {
+        let Some(&first) = key.first() else {
+            self.values.push(value);
+            return;
+        };
+        match self.nodes.binary_search_by_key(&first, |node| node.label[0]) {
+            Ok(pos) => {
+                let node = &mut self.nodes[pos];
+                let common = node.label.iter().zip(key).take_while(|(a, b)| a == b).count();
+                if common < node.label.len() {
+                    // Split the edge at the end of the common prefix.
+                    let suffix = node.label.split_off(common);
+                    let split = SegmentTrie {
+                        label: suffix,
+                        values: std::mem::take(&mut node.values),
+                        nodes: std::mem::take(&mut node.nodes),
+                    };
+                    node.nodes.push(split);
+                }
+                node.insert(&key[common..], value);
+            }
+            Err(pos) => self.nodes.insert(
+                pos,
+                SegmentTrie {
+                    label: key.to_vec(),
+                    values: vec![value],
+                    nodes: Vec::new(),
+                },
+            ),
+        }
+    }
+
+    fn get(&self, mut key: &[u8]) -> Option<&[usize]> {
+        let mut node = self;
+        while let Some(&first) = key.first() {
+            let pos = node.nodes.binary_search_by_key(&first, |node| node.label[0]).ok()?;
+            node = &node.nodes[pos];
+            key = key.strip_prefix(&node.label[..])?;
+        }
+        Some(&node.values)
+    }
+}
+
+/// Merges two ordered lists of child indexes.
+pub(crate) struct Candidates<'a> {
+    keyed: &'a [usize],
+    others: &'a [usize],
+}
+
+impl Iterator for Candidates<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let list = match (self.keyed.first(), self.others.first()) {
+            (Some(keyed), Some(other)) if keyed < other => &mut self.keyed,
+            (Some(_), None) => &mut self.keyed,
+            (_, Some(_)) => &mut self.others,
+            (None, None) => return None,
+        };
+        let (first, rest) = list.split_first()?;
+        *list = rest;
+        Some(*first)
+    }
+}
+
+/// Returns the path segment which the router requires before any of its filters consume the path.
+fn leading_segment(router: &Router) -> Option<String> {
+    for filter in &router.filters {
+        match filter.path_match() {
+            PathMatch::Ignored => continue,
+            PathMatch::Segment(segment) => return Some(segment),
+            _ => return None,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RouterIndex, SegmentTrie};
+    use crate::prelude::*;
+    use crate::routing::PathState;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[test]
+    fn test_router_index_candidates() {
+        let router = Router::new()
+            .push(Router::with_path("users"))
+            .push(Router::with_path("<id>"))
+            .push(Router::with_path("articles"))
+            .push(Router::with_filter(crate::routing::get()).path("users/<id>"))
+            .push(Router::with_path("users<id>"));
+        let index = RouterIndex::new(&router);
+        assert!(index.is_for(&router));
+
+        let state = PathState::new("/users/1");
+        assert_eq!(index.candidates(&state).unwrap().collect::<Vec<_>>(), vec![0, 1, 3, 4]);
+        let state = PathState::new("/articles");
+        assert_eq!(index.candidates(&state).unwrap().collect::<Vec<_>>(), vec![1, 2, 4]);
+        let state = PathState::new("/");
+        assert!(index.candidates(&state).is_none());
+        assert!(!index.is_for(&Router::new()));
+    }
+
+    #[test]
+    fn test_router_index_route() {
+        #[handler]
+        async fn show() {}
+
+        let router = Router::with_path("api").push(Router::with_path("users/<id>").get(show));
+        let index = RouterIndex::new(&router);
+        assert_eq!(&**index.child(0).unwrap().route(), "/api/users/<id>");
+
+        let mut req = Request::default();
+        *req.uri_mut() = "http://127.0.0.1:5800/api/users/1".parse().unwrap();
+        let mut state = PathState::new("/api/users/1");
+        let matched = router.detect_indexed(Some(&index), &mut req, &mut state).unwrap();
+        assert_eq!(matched.route(), "/api/users/<id>");
+
+        // The children are replaced after the index is built, the route is joined again.
+        let router = router.push(Router::with_path("articles/<id>").get(show));
+        let mut req = Request::default();
+        *req.uri_mut() = "http://127.0.0.1:5800/api/articles/1".parse().unwrap();
+        let mut state = PathState::new("/api/articles/1");
+        let matched = router.detect_indexed(Some(&index), &mut req, &mut state).unwrap();
+        assert_eq!(matched.route(), "/api/articles/<id>");
+        let mut state = PathState::new("/api/articles/1");
+        let matched = router.detect(&mut req, &mut state).unwrap();
+        assert_eq!(matched.route(), "/api/articles/<id>");
+    }
+
+    #[test]
+    fn test_segment_trie() {
+        let mut trie = SegmentTrie::default();
+        assert!(trie.is_empty());
+        for (i, key) in ["users", "user", "users2", "articles", "users", "a", "用户"]
+            .into_iter()
+            .enumerate()
+        {
+            trie.insert(key.as_bytes(), i);
+        }
+        assert!(!trie.is_empty());
+        assert_eq!(trie.get(b"users"), Some(&[0, 4][..]));
+        assert_eq!(trie.get(b"user"), Some(&[1][..]));
+        assert_eq!(trie.get(b"users2"), Some(&[2][..]));
+        assert_eq!(trie.get(b"articles"), Some(&[3][..]));
+        assert_eq!(trie.get(b"a"), Some(&[5][..]));
+        assert_eq!(trie.get("用户".as_bytes()), Some(&[6][..]));
+        assert_eq!(trie.get(b"us"), None);
+        assert_eq!(trie.get(b"users3"), None);
+        assert_eq!(trie.get(b"art"), None);
+        assert_eq!(trie.get(b"b"), None);
+    }
+
+    #[tokio::test]
+    async fn test_router_index_detect() {
+        #[handler]
+        async fn show(req: &mut Request) -> String {
+            format!(
+                "{}:{}",
+                req.param::<String>("kind").unwrap_or_default(),
+                req.param::<String>("id").unwrap()
+            )
+        }
+        #[handler]
+        async fn create() -> &'static str {
+            "created"
+        }
+
+        let mut router = Router::new();
+        for i in 0..2000 {
+            router = router.push(Router::with_path(format!("resource{i}/<id>")).get(show).post(create));
+        }
+        let router = router
+            .push(Router::with_path("<kind>/<id>").get(show))
+            .push(Router::with_path("resource7/<id>/extra").get(create))
+            .push(Router::with_path("other/<id>").get(create));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/resource1999/42")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), ":42");
+        let mut res = TestClient::post("http://127.0.0.1:5800/resource7/42")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "created");
+        let mut res = TestClient::get("http://127.0.0.1:5800/resource7/42/extra")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "created");
+        // Routers are detected in order, the wildcard router matches before the later keyed router.
+        let mut res = TestClient::get("http://127.0.0.1:5800/other/42").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "other:42");
+        let res = TestClient::delete("http://127.0.0.1:5800/resource3/42")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::METHOD_NOT_ALLOWED));
+    }
+}
@@ -371,6 +371,8 @@
 
 pub mod filters;
 pub use filters::*;
+mod index;
+pub(crate) use index::RouterIndex;
 mod router;
 pub use router::Router;
 
@@ -379,13 +381,50 @@ use std::sync::Arc;
 
 use indexmap::IndexMap;
 
-use crate::http::{Request, Response};
+use crate::admission::Priority;
+use crate::http::{Idempotent, ParseConfig, Request, Response};
 use crate::{Depot, Handler};
 
 #[doc(hidden)]
 pub struct DetectMatched {
-    pub hoops: Vec<Arc<dyn Handler>>,
-    pub goal: Arc<dyn Handler>,
+    pub(crate) hoops: Vec<Arc<dyn Handler>>,
+    pub(crate) goal: Arc<dyn Handler>,
+    pub(crate) route: Arc<str>,
+    pub(crate) priority: Option<Priority>,
+    pub(crate) parse_config: Option<ParseConfig>,
+    pub(crate) idempotent: Option<Idempotent>,
+}
+impl DetectMatched {
+    /// The middlewares of the matched routers, from the outermost to the innermost.
+    #[inline]
+    pub fn hoops(&self) -> &[Arc<dyn Handler>] {
+        &self.hoops
+    }
+    /// The final handler of the matched router.
+    #[inline]
+    pub fn goal(&self) -> &Arc<dyn Handler> {
+        &self.goal
+    }
+    /// The path templates of the matched routers joined, such as `/users/<id>`.
+    #[inline]
+    pub fn route(&self) -> &str {
+        &self.route
+    }
+    /// The admission priority of the innermost matched router which has one.
+    #[inline]
+    pub fn priority(&self) -> Option<Priority> {
+        self.priority
+    }
+    /// The parser limits of the innermost matched router which has them.
+    #[inline]
+    pub fn parse_config(&self) -> Option<ParseConfig> {
+        self.parse_config
+    }
+    /// The idempotency of the innermost matched router which declares one.
+    #[inline]
+    pub fn idempotent(&self) -> Option<Idempotent> {
+        self.idempotent
+    }
 }
 
 #[doc(hidden)]
@@ -404,23 +443,31 @@ impl PathState {
     /// Create new `PathState`.
     #[inline]
     pub fn new(url_path: &str) -> Self {
+        Self::with_buffers(url_path, Vec::new(), PathParams::new())
+    }
+
+    /// Create new `PathState` which reuses the memory of `parts` and `params`, they are cleared first.
+    pub(crate) fn with_buffers(url_path: &str, mut parts: Vec<String>, mut params: PathParams) -> Self {
+        parts.clear();
+        params.clear();
         let end_slash = url_path.ends_with('/');
-        let parts = url_path
-            .trim_start_matches('/')
-            .trim_end_matches('/')
-            .split('/')
-            .filter_map(|p| {
-                if !p.is_empty() {
-                    Some(decode_url_path_safely(p))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+        parts.extend(
+            url_path
+                .trim_start_matches('/')
+                .trim_end_matches('/')
+                .split('/')
+                .filter_map(|p| {
+                    if !p.is_empty() {
+                        Some(decode_url_path_safely(p))
+                    } else {
+                        None
+                    }
+                }),
+        );
         PathState {
             parts,
             cursor: (0, 0),
-            params: PathParams::new(),
+            params,
             end_slash,
             has_any_goal: false,
         }
@@ -537,13 +584,14 @@ impl FlowCtrl {
 
     /// Call next handler. If get next handler and executed, returns `true``, otherwise returns `false`.
     ///
-    /// **NOTE**: If response status code is error or is redirection, all reset handlers will be skipped.
+    /// **NOTE**: If response status code is error or is redirection, all reset handlers will be skipped. If the
+    /// request is [cancelled](Request::is_cancelled), all reset handlers will be skipped too.
     #[inline]
     pub async fn call_next(&mut self, req: &mut Request, depot: &mut Depot, res: &mut Response) -> bool {
         if self.catching.is_none() {
             self.catching = Some(res.is_stamped());
         }
-        if !self.catching.unwrap_or_default() && res.is_stamped() {
+        if (!self.catching.unwrap_or_default() && res.is_stamped()) || req.is_cancelled() {
             self.skip_rest();
             return false;
         }
@@ -3,9 +3,15 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use super::filters::{self, FnFilter, PathFilter};
-use super::{DetectMatched, Filter, PathState};
+use super::{DetectMatched, Filter, PathState, RouterIndex};
+use crate::admission::Priority;
 use crate::handler::{Handler, WhenHoop};
 use crate::http::uri::Scheme;
+#[cfg(feature = "tower-compat")]
+use crate::http::ReqBody;
+use crate::http::{Idempotent, ParseConfig};
+#[cfg(feature = "tower-compat")]
+use crate::tower_compat::{FlowCtrlService, TowerLayerCompat, TowerLayerHandler};
 use crate::{Depot, Request};
 
 /// Route request to different handlers.
@@ -23,6 +29,12 @@ pub struct Router {
     pub hoops: Vec<Arc<dyn Handler>>,
     /// The final handler to handle request of current router.
     pub goal: Option<Arc<dyn Handler>>,
+    /// The admission priority of requests handled by current router and its children.
+    pub priority: Option<Priority>,
+    /// The parser limits of requests handled by current router and its children.
+    pub parse_config: Option<ParseConfig>,
+    /// The idempotency of requests handled by current router and its children.
+    pub idempotent: Option<Idempotent>,
 }
 
 impl Default for Router {
@@ -43,6 +55,9 @@ impl Router {
             filters: Vec::new(),
             hoops: Vec::new(),
             goal: None,
+            priority: None,
+            parse_config: None,
+            idempotent: None,
         }
     }
 
@@ -80,22 +95,63 @@ impl Router {
     }
 
     /// Detect current router is matched for current request.
+    #[inline]
     pub fn detect(&self, req: &mut Request, path_state: &mut PathState) -> Option<DetectMatched> {
+        self.detect_indexed(None, req, path_state)
+    }
+
+    /// Detect current router is matched for current request, children which can not match the current path
+    /// segment are skipped with the help of `index`.
+    pub(crate) fn detect_indexed(
+        &self,
+        index: Option<&RouterIndex>,
+        req: &mut Request,
+        path_state: &mut PathState,
+    ) -> Option<DetectMatched> {
         for filter in &self.filters {
             if !filter.filter(req, path_state) {
                 return None;
             }
         }
+        // The route is taken from the index when it is built from this router, otherwise it is joined level by level.
+        let index = index.filter(|index| index.is_for(self));
         if !self.routers.is_empty() {
             let original_cursor = path_state.cursor;
-            for child in &self.routers {
-                if let Some(dm) = child.detect(req, path_state) {
-                    return Some(DetectMatched {
+            let detect_child = |i: usize, req: &mut Request, path_state: &mut PathState| {
+                let child = &self.routers[i];
+                let child_index = index.and_then(|index| index.child(i));
+                if let Some(dm) = child.detect_indexed(child_index, req, path_state) {
+                    let route = if child_index.is_some_and(|child_index| child_index.is_for(child)) {
+                        dm.route
+                    } else if let Some(index) = index {
+                        format!("{}{}", index.route(), dm.route).into()
+                    } else {
+                        format!("{}{}", self.path_template(), dm.route).into()
+                    };
+                    Some(DetectMatched {
                         hoops: [&self.hoops[..], &dm.hoops[..]].concat(),
                         goal: dm.goal.clone(),
-                    });
+                        route,
+                        priority: dm.priority.or(self.priority),
+                        parse_config: dm.parse_config.or(self.parse_config),
+                        idempotent: dm.idempotent.or(self.idempotent),
+                    })
                 } else {
                     path_state.cursor = original_cursor;
+                    None
+                }
+            };
+            if let Some(candidates) = index.and_then(|index| index.candidates(path_state)) {
+                for i in candidates {
+                    if let Some(dm) = detect_child(i, req, path_state) {
+                        return Some(dm);
+                    }
+                }
+            } else {
+                for i in 0..self.routers.len() {
+                    if let Some(dm) = detect_child(i, req, path_state) {
+                        return Some(dm);
+                    }
                 }
             }
         }
@@ -105,12 +161,32 @@ impl Router {
                 return Some(DetectMatched {
                     hoops: self.hoops.clone(),
                     goal: goal.clone(),
+                    route: match index {
+                        Some(index) => index.route().clone(),
+                        None => self.path_template().into(),
+                    },
+                    priority: self.priority,
+                    parse_config: self.parse_config,
+                    idempotent: self.idempotent,
                 });
             }
         }
         None
     }
 
+    /// Joins the path templates of the filters of current router, such as `/users/<id>`.
+    pub(crate) fn path_template(&self) -> String {
+        let mut route = String::new();
+        for template in self.filters.iter().filter_map(|filter| filter.path_template()) {
+            let template = template.trim_matches('/');
+            if !template.is_empty() {
+                route.push('/');
+                route.push_str(template);
+            }
+        }
+        route
+    }
+
     /// Insert a router at the begining of current router, shifting all routers after it to the right.
     #[inline]
     pub fn unshift(mut self, router: Router) -> Self {
@@ -175,6 +251,22 @@ impl Router {
         self
     }
 
+    /// Add a [`tower::Layer`] as middleware, such as the layers of `tower-http`.
+    ///
+    /// It is a shortcut of `hoop(layer.compat())`.
+    #[cfg(feature = "tower-compat")]
+    #[inline]
+    pub fn hoop_layer<L>(self, layer: L) -> Self
+    where
+        L: tower::Layer<FlowCtrlService> + Send + Sync + 'static,
+        L::Service: tower::Service<hyper::Request<ReqBody>> + Sync + Send + 'static,
+        <L::Service as tower::Service<hyper::Request<ReqBody>>>::Future: Send,
+        <L::Service as tower::Service<hyper::Request<ReqBody>>>::Error: std::error::Error + Send + Sync,
+        TowerLayerHandler<L::Service, ReqBody>: Handler,
+    {
+        self.hoop(layer.compat::<ReqBody>())
+    }
+
     /// Create a new router and set path filter.
     ///
     /// # Panics
@@ -224,6 +316,39 @@ impl Router {
         self.filter(FnFilter(func))
     }
 
+    /// Sets the admission priority of requests handled by current router and its descendants, unless a
+    /// descendant sets its own.
+    ///
+    /// It only takes effect when the [`Service`](crate::Service) has an
+    /// [`AdmissionQueue`](crate::admission::AdmissionQueue).
+    #[inline]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Sets the parser limits of requests handled by current router and its descendants, unless a descendant sets
+    /// its own.
+    ///
+    /// They are enforced by the extractors and the parse methods of [`Request`]. View [`ParseConfig`] for more
+    /// details.
+    #[inline]
+    pub fn parse_config(mut self, config: ParseConfig) -> Self {
+        self.parse_config = Some(config);
+        self
+    }
+
+    /// Declares whether requests handled by current router and its descendants can be safely repeated, unless a
+    /// descendant declares its own.
+    ///
+    /// Middlewares which retry, cache or deduplicate requests read it with [`Request::is_idempotent`]. View
+    /// [`Idempotent`] for more details.
+    #[inline]
+    pub fn idempotent(mut self, idempotent: impl Into<Idempotent>) -> Self {
+        self.idempotent = Some(idempotent.into());
+        self
+    }
+
     /// Sets current router's handler.
     #[inline]
     pub fn goal<H: Handler>(mut self, goal: H) -> Self {
@@ -10,3 +10,175 @@ pub use hyper::rt::*;
 pub mod tokio {
     pub use hyper_util::rt::{TokioExecutor, TokioIo};
 }
+
+/// Builder of a multi-threaded tokio runtime whose worker threads are pinned to CPU cores.
+///
+/// Each worker thread is pinned to one of the cores, so tasks are not moved across cores by the OS scheduler,
+/// this is useful for latency sensitive deployments. Combined with a sharded
+/// [`TcpListener`](crate::conn::TcpListener), each worker runs its own acceptor (thread-per-core style).
+///
+/// Pinning is supported on Linux, Android and FreeBSD, worker threads are not pinned on other platforms.
+///
+/// # Example
+///
+/// Sharding the listener requires the `socket2` feature.
+///
+/// ```ignore
+/// use salvo_core::prelude::*;
+/// use salvo_core::rt::Topology;
+///
+/// #[handler]
+/// async fn hello() -> &'static str {
+///     "Hello World"
+/// }
+///
+/// fn main() {
+///     let topology = Topology::new();
+///     let shards = topology.workers();
+///     topology.build().unwrap().block_on(async move {
+///         let acceptor = TcpListener::new("0.0.0.0:5800").shards(shards).bind().await;
+///         Server::new(acceptor).serve(Router::new().get(hello)).await;
+///     });
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Topology {
+    cores: Vec<usize>,
+    pinned: bool,
+    thread_name: String,
+}
+
+impl Default for Topology {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Topology {
+    /// Create a new `Topology` with one worker thread for each available core.
+    pub fn new() -> Self {
+        let count = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        Self {
+            cores: (0..count).collect(),
+            pinned: true,
+            thread_name: "salvo-worker".into(),
+        }
+    }
+
+    /// Set the cores used by the worker threads, one worker thread is started for each core.
+    #[inline]
+    pub fn cores(mut self, cores: impl IntoIterator<Item = usize>) -> Self {
+        self.cores = cores.into_iter().collect();
+        self
+    }
+
+    /// Set whether to pin the worker threads to the cores, the default is `true`.
+    #[inline]
+    pub fn pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Set the name of worker threads.
+    #[inline]
+    pub fn thread_name(mut self, name: impl Into<String>) -> Self {
+        self.thread_name = name.into();
+        self
+    }
+
+    /// Get the number of worker threads.
+    #[inline]
+    pub fn workers(&self) -> usize {
+        self.cores.len().max(1)
+    }
+
+    /// Build the runtime.
+    ///
+    /// The worker threads are the first threads started by the runtime, so only the first threads are pinned, the
+    /// threads of the blocking pool are not pinned.
+    pub fn build(&self) -> std::io::Result<::tokio::runtime::Runtime> {
+        let mut builder = ::tokio::runtime::Builder::new_multi_thread();
+        builder
+            .worker_threads(self.workers())
+            .thread_name(self.thread_name.clone())
+            .enable_all();
+        if self.pinned && !self.cores.is_empty() {
+            let cores = std::sync::Arc::new(self.cores.clone());
+            let started = std::sync::atomic::AtomicUsize::new(0);
+            builder.on_thread_start(move || {
+                let index = started.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                if let Some(core) = cores.get(index) {
+                    pin_current_thread(*core);
+                }
+            });
+        }
+        builder.build()
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+fn pin_current_thread(core: usize) {
+    use nix::sched::{sched_setaffinity, CpuSet};
+    use nix::unistd::Pid;
+
+    let mut cpu_set = CpuSet::new();
+    if let Err(e) = cpu_set
+        .set(core)
+        .and_then(|_| sched_setaffinity(Pid::from_raw(0), &cpu_set))
+    {
+        tracing::warn!(error = ?e, core, "pin worker thread to core failed");
+    }
+}
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+)))]
+fn pin_current_thread(core: usize) {
+    tracing::debug!(core, "pinning worker threads is not supported on this platform");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Topology;
+
+    #[test]
+    fn test_topology() {
+        let topology = Topology::new();
+        assert!(topology.workers() >= 1);
+        let runtime = topology.build().unwrap();
+        assert_eq!(
+            runtime.block_on(async { ::tokio::spawn(async { 42 }).await.unwrap() }),
+            42
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_topology_pinned() {
+        use nix::sched::{sched_getaffinity, CpuSet};
+        use nix::unistd::Pid;
+
+        let allowed = sched_getaffinity(Pid::from_raw(0)).unwrap();
+        let core = (0..CpuSet::count())
+            .find(|i| allowed.is_set(*i).unwrap_or(false))
+            .unwrap();
+        let runtime = Topology::new().cores([core]).build().unwrap();
+        let affinity = runtime.block_on(async {
+            ::tokio::spawn(async { sched_getaffinity(Pid::from_raw(0)).unwrap() })
+                .await
+                .unwrap()
+        });
+        let pinned = (0..CpuSet::count())
+            .filter(|i| affinity.is_set(*i).unwrap_or(false))
+            .collect::<Vec<_>>();
+        assert_eq!(pinned, vec![core]);
+    }
+}
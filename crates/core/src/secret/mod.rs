@@ -0,0 +1,462 @@
+//! Secrets such as TLS keys, signing keys and tokens loaded from external providers.
+//!
+//! A [`SecretProvider`] loads secrets by name, from environment variables with [`EnvSecrets`], from mounted files
+//! with [`FileSecrets`], or from a secret store such as Vault with `VaultSecrets` behind the `vault` feature.
+//! Secrets encrypted by a KMS can be decrypted when they are loaded with [`EnvelopeSecrets`].
+//!
+//! [`SecretWatcher`] polls a provider and yields the secrets again when they rotate. The stream can be used directly
+//! as a TLS config stream, such as `RustlsConfig::from_secrets`, so certificates are reloaded without restarting the
+//! server.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_core::secret::{FileSecrets, SecretWatcher};
+//!
+//! # async fn load() -> std::io::Result<()> {
+//! let watcher = SecretWatcher::new(FileSecrets::new("/run/secrets")).interval(Duration::from_secs(60));
+//! let secrets = watcher.load(["session-key"]).await?;
+//! # let _ = secrets;
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::stream::{self, Stream};
+
+cfg_feature! {
+    #![feature = "vault"]
+    mod vault;
+    pub use vault::VaultSecrets;
+}
+
+/// A secret value, its content is never printed by `Debug`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(Bytes);
+
+impl Secret {
+    /// Create a new `Secret`.
+    #[inline]
+    pub fn new(value: impl Into<Bytes>) -> Self {
+        Self(value.into())
+    }
+
+    /// Get the content of the secret.
+    #[inline]
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Get the content of the secret as a UTF-8 string.
+    #[inline]
+    pub fn expose_str(&self) -> IoResult<&str> {
+        std::str::from_utf8(&self.0).map_err(|e| IoError::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Returns the length of the secret.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the secret is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    cfg_feature! {
+        #![feature = "cookie"]
+        /// Use the secret as a key of signed and private cookies, the secret must be at least 64 bytes.
+        pub fn cookie_key(&self) -> IoResult<cookie::Key> {
+            cookie::Key::try_from(self.expose()).map_err(|e| IoError::new(ErrorKind::InvalidData, e))
+        }
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
+
+impl From<&'static str> for Secret {
+    #[inline]
+    fn from(value: &'static str) -> Self {
+        Self::new(value)
+    }
+}
+impl From<String> for Secret {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+impl From<Vec<u8>> for Secret {
+    #[inline]
+    fn from(value: Vec<u8>) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Loads secrets by name.
+#[async_trait]
+pub trait SecretProvider: Send + Sync + 'static {
+    /// Load the current value of the secret, returns an error of kind [`ErrorKind::NotFound`] if it does not exist.
+    async fn load(&self, name: &str) -> IoResult<Secret>;
+}
+
+#[async_trait]
+impl<P> SecretProvider for Arc<P>
+where
+    P: SecretProvider + ?Sized,
+{
+    #[inline]
+    async fn load(&self, name: &str) -> IoResult<Secret> {
+        (**self).load(name).await
+    }
+}
+
+/// Loads secrets from environment variables.
+///
+/// The variable of a secret is its name in upper case with `-`, `.` and `/` replaced by `_`, after the prefix.
+#[derive(Clone, Default, Debug)]
+pub struct EnvSecrets {
+    prefix: String,
+    vars: Option<HashMap<String, Secret>>,
+}
+
+impl EnvSecrets {
+    /// Create a new `EnvSecrets` without prefix.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a new `EnvSecrets` reading the given variables instead of the environment of the process, such as
+    /// variables loaded from a `.env` file or set in tests.
+    pub fn with_vars<I, K, V>(vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Secret>,
+    {
+        Self {
+            prefix: String::new(),
+            vars: Some(vars.into_iter().map(|(k, v)| (k.into(), v.into())).collect()),
+        }
+    }
+
+    /// Set the prefix of variables, such as `APP_`.
+    #[inline]
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn var_name(&self, name: &str) -> String {
+        let name = name
+            .chars()
+            .map(|c| match c {
+                '-' | '.' | '/' => '_',
+                c => c.to_ascii_uppercase(),
+            })
+            .collect::<String>();
+        format!("{}{name}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl SecretProvider for EnvSecrets {
+    async fn load(&self, name: &str) -> IoResult<Secret> {
+        let var = self.var_name(name);
+        let value = match &self.vars {
+            Some(vars) => vars.get(&var).cloned(),
+            None => std::env::var_os(&var).map(|value| Secret::new(value.into_encoded_bytes())),
+        };
+        match value {
+            Some(value) => Ok(value),
+            None => Err(IoError::new(
+                ErrorKind::NotFound,
+                format!("environment variable `{var}` not found"),
+            )),
+        }
+    }
+}
+
+/// Loads secrets from files in a directory, such as secrets mounted by Kubernetes or Docker.
+#[derive(Clone, Debug)]
+pub struct FileSecrets {
+    dir: PathBuf,
+    trim_end: bool,
+}
+
+impl FileSecrets {
+    /// Create a new `FileSecrets` reading the file named after the secret in the directory.
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            trim_end: false,
+        }
+    }
+
+    /// Remove trailing whitespaces of the files, such as the newline added by editors.
+    ///
+    /// The default is `false`, so binary secrets are kept as is.
+    #[inline]
+    pub fn trim_end(mut self, trim_end: bool) -> Self {
+        self.trim_end = trim_end;
+        self
+    }
+}
+
+#[async_trait]
+impl SecretProvider for FileSecrets {
+    async fn load(&self, name: &str) -> IoResult<Secret> {
+        let relative = Path::new(name);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                format!("invalid secret name `{name}`"),
+            ));
+        }
+        let mut content = tokio::fs::read(self.dir.join(relative)).await?;
+        if self.trim_end {
+            while content.last().is_some_and(u8::is_ascii_whitespace) {
+                content.pop();
+            }
+        }
+        Ok(Secret::new(content))
+    }
+}
+
+/// Decrypts secrets loaded by [`EnvelopeSecrets`].
+///
+/// Implement it with the client of a KMS, such as AWS KMS or Google Cloud KMS, to store encrypted secrets in
+/// environment variables or files.
+#[async_trait]
+pub trait SecretDecryptor: Send + Sync + 'static {
+    /// Decrypt the ciphertext of the secret.
+    async fn decrypt(&self, name: &str, ciphertext: Secret) -> IoResult<Secret>;
+}
+
+/// Loads encrypted secrets from a provider and decrypts them.
+#[derive(Clone, Debug)]
+pub struct EnvelopeSecrets<P, D> {
+    provider: P,
+    decryptor: D,
+}
+
+impl<P, D> EnvelopeSecrets<P, D>
+where
+    P: SecretProvider,
+    D: SecretDecryptor,
+{
+    /// Create a new `EnvelopeSecrets`.
+    #[inline]
+    pub fn new(provider: P, decryptor: D) -> Self {
+        Self { provider, decryptor }
+    }
+}
+
+#[async_trait]
+impl<P, D> SecretProvider for EnvelopeSecrets<P, D>
+where
+    P: SecretProvider,
+    D: SecretDecryptor,
+{
+    async fn load(&self, name: &str) -> IoResult<Secret> {
+        let ciphertext = self.provider.load(name).await?;
+        self.decryptor.decrypt(name, ciphertext).await
+    }
+}
+
+/// Polls a [`SecretProvider`] to notify rotations of secrets.
+///
+/// View [module level documentation](index.html) for more details.
+pub struct SecretWatcher<P: ?Sized> {
+    provider: Arc<P>,
+    interval: Duration,
+}
+
+impl<P: ?Sized> Clone for SecretWatcher<P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            interval: self.interval,
+        }
+    }
+}
+
+impl<P: ?Sized> Debug for SecretWatcher<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretWatcher")
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl<P> SecretWatcher<P>
+where
+    P: SecretProvider,
+{
+    /// Create a new `SecretWatcher`, the provider is polled every 5 minutes by default.
+    #[inline]
+    pub fn new(provider: P) -> Self {
+        Self::with_arc(Arc::new(provider))
+    }
+}
+
+impl<P> SecretWatcher<P>
+where
+    P: SecretProvider + ?Sized,
+{
+    /// Create a new `SecretWatcher` with a shared provider.
+    #[inline]
+    pub fn with_arc(provider: Arc<P>) -> Self {
+        Self {
+            provider,
+            interval: Duration::from_secs(300),
+        }
+    }
+
+    /// Set how often the provider is polled.
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Get the provider.
+    #[inline]
+    pub fn provider(&self) -> &Arc<P> {
+        &self.provider
+    }
+
+    /// Load the secrets once, in the order of the names.
+    pub async fn load<I>(&self, names: I) -> IoResult<Vec<Secret>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut secrets = Vec::new();
+        for name in names {
+            secrets.push(self.provider.load(name.as_ref()).await?);
+        }
+        Ok(secrets)
+    }
+
+    /// Returns a stream which yields the secrets, in the order of the names, when they are first loaded and then
+    /// every time any of them changes.
+    ///
+    /// Errors are logged and the provider is polled again after the interval, the stream never ends.
+    pub fn watch<I>(&self, names: I) -> impl Stream<Item = Vec<Secret>> + Send + 'static
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let names = names
+            .into_iter()
+            .map(|name| name.as_ref().to_owned())
+            .collect::<Vec<_>>();
+        let watcher = self.clone();
+        stream::unfold(
+            (watcher, names, None::<Vec<Secret>>, false),
+            |(watcher, names, last, polled)| async move {
+                let mut polled = polled;
+                let mut last = last;
+                loop {
+                    if polled {
+                        tokio::time::sleep(watcher.interval).await;
+                    }
+                    polled = true;
+                    match watcher.load(&names).await {
+                        Ok(secrets) => {
+                            if last.as_ref() != Some(&secrets) {
+                                last = Some(secrets.clone());
+                                return Some((secrets, (watcher, names, last, polled)));
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!(error = ?e, names = ?names, "load secrets failed");
+                        }
+                    }
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("jwt-key"), "secret\n").unwrap();
+        let provider = FileSecrets::new(dir.path());
+        assert_eq!(provider.load("jwt-key").await.unwrap().expose(), b"secret\n");
+        let provider = provider.trim_end(true);
+        assert_eq!(provider.load("jwt-key").await.unwrap().expose_str().unwrap(), "secret");
+        assert_eq!(provider.load("missing").await.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(
+            provider.load("../jwt-key").await.unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(format!("{:?}", Secret::from("secret")), "Secret(..)");
+    }
+
+    #[tokio::test]
+    async fn test_env_secrets() {
+        let provider = EnvSecrets::with_vars([("SALVO_TEST_SESSION_KEY", "value")]).prefix("SALVO_TEST_");
+        assert_eq!(provider.load("session-key").await.unwrap().expose(), b"value");
+        assert!(provider.load("missing").await.is_err());
+    }
+
+    struct Reversed;
+    #[async_trait]
+    impl SecretDecryptor for Reversed {
+        async fn decrypt(&self, _name: &str, ciphertext: Secret) -> IoResult<Secret> {
+            Ok(Secret::new(
+                ciphertext.expose().iter().rev().copied().collect::<Vec<_>>(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_envelope_secrets() {
+        let secrets = EnvSecrets::with_vars([("SALVO_ENVELOPE_TOKEN", "terces")]).prefix("SALVO_ENVELOPE_");
+        let provider = EnvelopeSecrets::new(secrets, Reversed);
+        assert_eq!(provider.load("token").await.unwrap().expose(), b"secret");
+    }
+
+    #[tokio::test]
+    async fn test_secret_watcher() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key");
+        std::fs::write(&path, "first").unwrap();
+        let watcher = SecretWatcher::new(FileSecrets::new(dir.path())).interval(Duration::from_millis(10));
+        let mut stream = Box::pin(watcher.watch(["key"]));
+        assert_eq!(stream.next().await.unwrap(), vec![Secret::from("first")]);
+        std::fs::write(&path, "second").unwrap();
+        let rotated = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rotated, vec![Secret::from("second")]);
+    }
+}
@@ -0,0 +1,173 @@
+//! Secrets stored in the KV version 2 engine of HashiCorp Vault.
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioExecutor;
+use once_cell::sync::OnceCell;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::Deserialize;
+
+use super::{Secret, SecretProvider};
+use crate::http::header::HeaderValue;
+use crate::http::StatusCode;
+
+type HyperClient = Client<HttpsConnector<HttpConnector>, Full<Bytes>>;
+
+/// Characters encoded in the segments of secret names, all but the unreserved ones.
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Loads secrets from the KV version 2 engine of [HashiCorp Vault](https://www.vaultproject.io).
+///
+/// The secret named `tls/cert` is read from the `value` field of `{addr}/v1/secret/data/tls/cert` by default. The
+/// segments of names are percent-encoded, and names containing `..` or empty segments are rejected.
+pub struct VaultSecrets {
+    addr: String,
+    token: Secret,
+    mount: String,
+    field: String,
+    namespace: Option<String>,
+    client: OnceCell<HyperClient>,
+}
+
+impl Debug for VaultSecrets {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VaultSecrets")
+            .field("addr", &self.addr)
+            .field("mount", &self.mount)
+            .field("field", &self.field)
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
+
+impl VaultSecrets {
+    /// Create a new `VaultSecrets` with the address of the server, such as `https://vault.example.com:8200`, and the
+    /// token used to authenticate.
+    #[inline]
+    pub fn new(addr: impl Into<String>, token: impl Into<Secret>) -> Self {
+        Self {
+            addr: addr.into(),
+            token: token.into(),
+            mount: "secret".into(),
+            field: "value".into(),
+            namespace: None,
+            client: OnceCell::new(),
+        }
+    }
+
+    /// Set the path the KV engine is mounted at, the default is `secret`.
+    #[inline]
+    pub fn mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+
+    /// Set the field of the secret data which holds the value, the default is `value`.
+    #[inline]
+    pub fn field(mut self, field: impl Into<String>) -> Self {
+        self.field = field.into();
+        self
+    }
+
+    /// Set the namespace of Vault Enterprise.
+    #[inline]
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    fn client(&self) -> IoResult<&HyperClient> {
+        self.client.get_or_try_init(|| {
+            let https = HttpsConnectorBuilder::new()
+                .with_native_roots()?
+                .https_or_http()
+                .enable_http1()
+                .build();
+            Ok(Client::builder(TokioExecutor::new()).build(https))
+        })
+    }
+}
+
+/// Returns the percent-encoded path of the secret, names which may escape the mount are rejected.
+fn secret_path(name: &str) -> IoResult<String> {
+    let name = name.trim_matches('/');
+    if name.contains("..") || name.split('/').any(|segment| segment.is_empty() || segment == ".") {
+        return Err(IoError::new(
+            ErrorKind::InvalidInput,
+            format!("invalid secret name `{name}`"),
+        ));
+    }
+    Ok(name
+        .split('/')
+        .map(|segment| utf8_percent_encode(segment, SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+#[derive(Deserialize)]
+struct ReadResponse {
+    data: ReadData,
+}
+#[derive(Deserialize)]
+struct ReadData {
+    data: HashMap<String, serde_json::Value>,
+}
+
+#[async_trait]
+impl SecretProvider for VaultSecrets {
+    async fn load(&self, name: &str) -> IoResult<Secret> {
+        let uri = format!(
+            "{}/v1/{}/data/{}",
+            self.addr.trim_end_matches('/'),
+            self.mount.trim_matches('/'),
+            secret_path(name)?
+        );
+        let token =
+            HeaderValue::from_bytes(self.token.expose()).map_err(|e| IoError::new(ErrorKind::InvalidInput, e))?;
+        let mut req = hyper::Request::get(uri).header("x-vault-token", token);
+        if let Some(namespace) = &self.namespace {
+            req = req.header("x-vault-namespace", namespace.as_str());
+        }
+        let req = req.body(Full::new(Bytes::new())).map_err(IoError::other)?;
+        let res = self.client()?.request(req).await.map_err(IoError::other)?;
+        let status = res.status();
+        let body = res.into_body().collect().await.map_err(IoError::other)?.to_bytes();
+        if status == StatusCode::NOT_FOUND {
+            return Err(IoError::new(ErrorKind::NotFound, format!("secret `{name}` not found")));
+        } else if !status.is_success() {
+            return Err(IoError::other(format!(
+                "vault responded with status {status} for secret `{name}`"
+            )));
+        }
+        let mut res: ReadResponse =
+            serde_json::from_slice(&body).map_err(|e| IoError::new(ErrorKind::InvalidData, e))?;
+        match res.data.data.remove(&self.field) {
+            Some(serde_json::Value::String(value)) => Ok(Secret::new(value)),
+            Some(value) => Ok(Secret::new(value.to_string())),
+            None => Err(IoError::new(
+                ErrorKind::NotFound,
+                format!("field `{}` of secret `{name}` not found", self.field),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_path() {
+        assert_eq!(secret_path("/tls/cert").unwrap(), "tls/cert");
+        assert_eq!(secret_path("app/db key?v=1#x").unwrap(), "app/db%20key%3Fv%3D1%23x");
+        for name in ["../sys/policy", "tls/../../sys", "tls//cert", "tls/./cert", ""] {
+            assert_eq!(secret_path(name).unwrap_err().kind(), ErrorKind::InvalidInput, "{name}");
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::hash::Hash;
 
+use indexmap::IndexMap;
 pub use serde::de::value::{Error as ValError, MapDeserializer, SeqDeserializer};
 use serde::de::{
     Deserialize, DeserializeSeed, Deserializer, EnumAccess, Error as DeError, IntoDeserializer, VariantAccess, Visitor,
@@ -142,7 +143,7 @@ impl<'de> VariantAccess<'de> for UnitOnlyVariantAccess {
 }
 
 #[derive(Debug)]
-struct CowValue<'de>(Cow<'de, str>);
+pub(crate) struct CowValue<'de>(Cow<'de, str>);
 impl<'de> IntoDeserializer<'de> for CowValue<'de> {
     type Deserializer = Self;
 
@@ -194,6 +195,24 @@ impl<'de> Deserializer<'de> for CowValue<'de> {
         visitor.visit_newtype_struct(self)
     }
 
+    #[inline]
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    /// A single value is deserialized as a sequence by splitting it on commas, such as a path parameter `1,2,3` of
+    /// `simple` style.
+    #[inline]
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDeserializer::new(split_values(self.0).into_iter()))
+    }
+
     forward_to_deserialize_any! {
         char
         str
@@ -205,9 +224,7 @@ impl<'de> Deserializer<'de> for CowValue<'de> {
         tuple_struct
         struct
         identifier
-        tuple
         ignored_any
-        seq
         map
     }
 
@@ -341,6 +358,204 @@ where
     }
 }
 
+/// Split a value of a non exploded array, such as `1,2,3`.
+fn split_values(value: Cow<'_, str>) -> Vec<CowValue<'_>> {
+    match value {
+        Cow::Borrowed(value) => value.split(',').map(|v| CowValue(v.into())).collect(),
+        Cow::Owned(value) => value.split(',').map(|v| CowValue(v.to_owned().into())).collect(),
+    }
+}
+
+/// Value of a parameter serialized in `deepObject` style, such as `filter[createdAt][gte]=2024-01-01`.
+#[derive(Debug)]
+pub(crate) enum NestedValue<'de> {
+    Leaf(Vec<CowValue<'de>>),
+    Map(IndexMap<&'de str, NestedValue<'de>>),
+}
+impl<'de> NestedValue<'de> {
+    /// Collect the entries whose key is `name` followed by bracketed segments, returns `None` if there is none.
+    ///
+    /// Empty segments append to the values of their parent, so `name[tags][]=a&name[tags][]=b` gives two tags.
+    pub(crate) fn from_deep_object<I, C>(entries: I, name: &str) -> Option<Self>
+    where
+        I: IntoIterator<Item = (&'de String, C)>,
+        C: IntoIterator<Item = &'de String>,
+    {
+        let mut root = None;
+        for (key, values) in entries {
+            let Some(path) = deep_object_path(key, name) else {
+                continue;
+            };
+            root.get_or_insert_with(|| Self::Map(IndexMap::new()))
+                .insert(&path, values.into_iter().map(|v| CowValue(v.as_str().into())));
+        }
+        root
+    }
+
+    fn insert(&mut self, path: &[&'de str], values: impl Iterator<Item = CowValue<'de>>) {
+        match (self, path.split_first()) {
+            (Self::Leaf(leaf), _) => leaf.extend(values),
+            (Self::Map(map), Some((key, rest))) => map
+                .entry(*key)
+                .or_insert_with(|| {
+                    if rest.is_empty() {
+                        Self::Leaf(vec![])
+                    } else {
+                        Self::Map(IndexMap::new())
+                    }
+                })
+                .insert(rest, values),
+            // Both `name[a]=1` and `name[a][b]=2`, the value of `a` is kept.
+            (Self::Map(_), None) => {}
+        }
+    }
+}
+
+/// Split `name[a][b]` into `[a, b]`, returns `None` if `key` is not a nested key of `name`.
+fn deep_object_path<'de>(key: &'de str, name: &str) -> Option<Vec<&'de str>> {
+    let segments = key.strip_prefix(name)?.strip_prefix('[')?.strip_suffix(']')?;
+    let mut path = Vec::new();
+    for segment in segments.split("][") {
+        if segment.contains(['[', ']']) {
+            return None;
+        }
+        if !segment.is_empty() {
+            path.push(segment);
+        }
+    }
+    Some(path)
+}
+
+impl<'de> IntoDeserializer<'de> for NestedValue<'de> {
+    type Deserializer = Self;
+
+    #[inline]
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_nested_value {
+    ($($method:ident,)*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+                where V: Visitor<'de>
+            {
+                match self {
+                    Self::Leaf(values) => VecValue(values.into_iter()).$method(visitor),
+                    map => map.deserialize_any(visitor),
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> Deserializer<'de> for NestedValue<'de> {
+    type Error = ValError;
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Leaf(values) => VecValue(values.into_iter()).deserialize_any(visitor),
+            Self::Map(map) => visitor.visit_map(MapDeserializer::new(
+                map.into_iter().map(|(key, value)| (CowValue(key.into()), value)),
+            )),
+        }
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Leaf(values) => VecValue(values.into_iter()).deserialize_enum(name, variants, visitor),
+            Self::Map(_) => Err(DeError::custom("expected value, found nested object")),
+        }
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// A map is deserialized as a sequence of its values, such as `ids[0]=1&ids[1]=2`.
+    #[inline]
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Leaf(values) => VecValue(values.into_iter()).deserialize_seq(visitor),
+            Self::Map(map) => visitor.visit_seq(SeqDeserializer::new(map.into_values())),
+        }
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char
+        str
+        string
+        unit
+        bytes
+        byte_buf
+        unit_struct
+        struct
+        identifier
+        ignored_any
+        map
+    }
+
+    forward_nested_value! {
+        deserialize_bool,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_f32,
+        deserialize_f64,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -15,23 +15,29 @@ use crate::http::header::HeaderMap;
 use crate::http::ParseError;
 use crate::Request;
 
-use super::{CowValue, VecValue};
+use super::{CowValue, NestedValue, VecValue};
 
 pub async fn from_request<'de, T>(req: &'de mut Request, metadata: &'de Metadata) -> Result<T, ParseError>
 where
     T: Deserialize<'de>,
 {
+    let config = req.parse_config();
+    config.check_queries(req.uri().query().unwrap_or_default())?;
     // Ensure body is parsed correctly.
     if let Some(ctype) = req.content_type() {
         match ctype.subtype() {
             mime::WWW_FORM_URLENCODED | mime::FORM_DATA => {
                 if metadata.has_body_required() {
-                    req.form_data().await.ok();
+                    if let Err(e @ ParseError::LimitExceeded(_)) = req.form_data().await {
+                        return Err(e);
+                    }
                 }
             }
             mime::JSON => {
                 if metadata.has_body_required() {
-                    req.payload().await.ok();
+                    if let Ok(payload) = req.payload().await {
+                        config.check_json(payload)?;
+                    }
                 }
             }
             _ => {}
@@ -73,6 +79,7 @@ pub(crate) struct RequestDeserializer<'de> {
     field_source: Option<&'de Source>,
     field_str_value: Option<&'de str>,
     field_vec_value: Option<Vec<CowValue<'de>>>,
+    field_nested_value: Option<NestedValue<'de>>,
 }
 
 impl<'de> RequestDeserializer<'de> {
@@ -117,6 +124,7 @@ impl<'de> RequestDeserializer<'de> {
             field_source: None,
             field_str_value: None,
             field_vec_value: None,
+            field_nested_value: None,
         })
     }
 
@@ -164,6 +172,7 @@ impl<'de> RequestDeserializer<'de> {
                 field_source: None,
                 field_str_value: None,
                 field_vec_value: None,
+                field_nested_value: None,
             })
         } else {
             let source = self
@@ -185,6 +194,8 @@ impl<'de> RequestDeserializer<'de> {
                 seed.deserialize(CowValue(value.into()))
             } else if let Some(value) = self.field_vec_value.take() {
                 seed.deserialize(VecValue(value.into_iter()))
+            } else if let Some(value) = self.field_nested_value.take() {
+                seed.deserialize(value)
             } else {
                 Err(ValError::custom("parse value error"))
             }
@@ -247,7 +258,23 @@ impl<'de> RequestDeserializer<'de> {
                         }
                     }
                     if let Some(value) = value {
-                        self.field_vec_value = Some(value.iter().map(|v| CowValue(v.into())).collect());
+                        self.field_vec_value = Some(if field.explode {
+                            value.iter().map(|v| CowValue(v.into())).collect()
+                        } else {
+                            value
+                                .iter()
+                                .flat_map(|v| v.split(','))
+                                .map(|v| CowValue(v.into()))
+                                .collect()
+                        });
+                        self.field_source = Some(source);
+                        return true;
+                    }
+                    let nested = std::iter::once(field_name.as_ref())
+                        .chain(field.aliases.iter().copied())
+                        .find_map(|name| NestedValue::from_deep_object(self.queries.iter_all(), name));
+                    if let Some(nested) = nested {
+                        self.field_nested_value = Some(nested);
                         self.field_source = Some(source);
                         return true;
                     }
@@ -379,6 +406,7 @@ impl<'de> RequestDeserializer<'de> {
             self.field_flatten = field.flatten;
             self.field_str_value = None;
             self.field_vec_value = None;
+            self.field_nested_value = None;
 
             if self.fill_value(field) {
                 return field.serde_rename.map(Cow::from).or_else(|| {
@@ -589,6 +617,61 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_de_request_with_parameter_styles() {
+        #[derive(Deserialize, Eq, PartialEq, Debug)]
+        struct Range {
+            gte: Option<u32>,
+            lte: Option<u32>,
+        }
+        #[derive(Deserialize, Eq, PartialEq, Debug)]
+        struct Filter {
+            created_at: Range,
+            tags: Vec<String>,
+        }
+        #[derive(Deserialize, Extractible, Eq, PartialEq, Debug)]
+        #[salvo(extract(default_source(from = "query")))]
+        struct RequestData {
+            filter: Filter,
+            #[salvo(extract(explode = false))]
+            ids: Vec<i64>,
+            #[salvo(extract(source(from = "param")))]
+            pids: Vec<u8>,
+        }
+
+        let mut req = TestClient::get("http://127.0.0.1:5800/test")
+            .query("filter[created_at][gte]", "10")
+            .query("filter[tags][]", "a")
+            .query("filter[tags][]", "b")
+            .query("ids", "1,2")
+            .query("ids", "3")
+            .build();
+        req.params.insert("pids".into(), "4,5".into());
+        let data: RequestData = req.extract().await.unwrap();
+        assert_eq!(
+            data,
+            RequestData {
+                filter: Filter {
+                    created_at: Range {
+                        gte: Some(10),
+                        lte: None
+                    },
+                    tags: vec!["a".into(), "b".into()],
+                },
+                ids: vec![1, 2, 3],
+                pids: vec![4, 5],
+            }
+        );
+        let range: Range = req.query("filter[created_at]").unwrap();
+        assert_eq!(
+            range,
+            Range {
+                gte: Some(10),
+                lte: None
+            }
+        );
+    }
+
     #[tokio::test]
     async fn test_de_request_with_json_vec() {
         #[derive(Deserialize, Extractible, Eq, PartialEq, Debug)]
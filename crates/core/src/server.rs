@@ -27,6 +27,9 @@ use crate::conn::quinn;
 use crate::conn::{Accepted, Acceptor, Holding, HttpBuilder};
 use crate::fuse::{ArcFuseFactory, FuseFactory};
 use crate::http::{HeaderValue, HttpConnection, Version};
+#[cfg(feature = "server-handle")]
+use crate::tasks::Tasks;
+use crate::startup::StartupReport;
 use crate::Service;
 
 cfg_feature! {
@@ -101,6 +104,8 @@ pub struct Server<A> {
     tx_cmd: UnboundedSender<ServerCommand>,
     #[cfg(feature = "server-handle")]
     rx_cmd: UnboundedReceiver<ServerCommand>,
+    #[cfg(feature = "server-handle")]
+    tasks: Tasks,
 }
 
 impl<A: Acceptor + Send> Server<A> {
@@ -133,6 +138,8 @@ impl<A: Acceptor + Send> Server<A> {
             tx_cmd,
             #[cfg(feature = "server-handle")]
             rx_cmd,
+            #[cfg(feature = "server-handle")]
+            tasks: Tasks::new(),
         }
     }
 
@@ -169,6 +176,13 @@ impl<A: Acceptor + Send> Server<A> {
         pub fn stop_graceful(&self, timeout: impl Into<Option<Duration>>) {
             self.tx_cmd.send(ServerCommand::StopGraceful(timeout.into())).ok();
         }
+
+        /// Get the background [`Tasks`] of this server.
+        ///
+        /// Tasks are cancelled when the server is stopped.
+        pub fn tasks(&self) -> &Tasks {
+            &self.tasks
+        }
     }
     
     /// Get holding information of this server.
@@ -177,6 +191,15 @@ impl<A: Acceptor + Send> Server<A> {
         self.acceptor.holdings()
     }
 
+    /// Get the [`StartupReport`] of this server serving `service`.
+    ///
+    /// Certificates and configuration warnings known by the application can be added to the report before it is
+    /// printed.
+    #[inline]
+    pub fn startup_report(&self, service: &Service) -> StartupReport {
+        StartupReport::new(self.holdings(), service)
+    }
+
     cfg_feature! {
         #![feature = "http1"]
         /// Use this function to set http1 protocol.
@@ -239,6 +262,7 @@ impl<A: Acceptor + Send> Server<A> {
             builder,
             fuse_factory,
             mut rx_cmd,
+            tasks,
             ..
         } = self;
         let alive_connections = Arc::new(AtomicUsize::new(0));
@@ -332,10 +356,16 @@ impl<A: Acceptor + Send> Server<A> {
             }
         }
 
+        tasks.stop().await;
         if alive_connections.load(Ordering::Acquire) > 0 {
             tracing::info!("wait for all connections to close.");
             notify.notified().await;
         }
+        if crate::tasks::pending_deferred() > 0 {
+            tracing::info!("wait for all deferred jobs to finish.");
+            crate::tasks::drain_deferred().await;
+        }
+        tasks.run_shutdown_jobs().await;
 
         tracing::info!("server stopped");
         Ok(())
@@ -462,4 +492,33 @@ mod tests {
             .unwrap();
         assert!(result.contains("<code>404</code>"));
     }
+
+    #[cfg(all(feature = "http1", feature = "http2"))]
+    #[tokio::test]
+    async fn test_server_with_http_builder() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::conn::HttpBuilder;
+
+        #[handler]
+        async fn hello() -> &'static str {
+            "Hello World"
+        }
+        let acceptor = TcpListener::new("127.0.0.1:6881").bind().await;
+        let builder = HttpBuilder::new()
+            .http1_max_buf_size(16 * 1024)
+            .http2_initial_stream_window_size(4 * 1024 * 1024)
+            .http2_initial_connection_window_size(8 * 1024 * 1024)
+            .http2_max_concurrent_streams(1000);
+        tokio::spawn(Server::with_http_builder(acceptor, builder).serve(Router::new().get(hello)));
+
+        let mut stream = tokio::net::TcpStream::connect("127.0.0.1:6881").await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut content = String::new();
+        stream.read_to_string(&mut content).await.unwrap();
+        assert!(content.ends_with("Hello World"));
+    }
 }
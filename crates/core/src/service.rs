@@ -1,20 +1,34 @@
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{ready, Context, Poll};
 
+use bytes::Bytes;
 use headers::HeaderValue;
-use http::header::{ALT_SVC, CONTENT_TYPE};
+use http::header::{ALT_SVC, CONNECTION, CONTENT_TYPE};
 use http::uri::Scheme;
+use hyper::body::{Body, Frame, SizeHint};
 use hyper::service::Service as HyperService;
 use hyper::{Method, Request as HyperRequest, Response as HyperResponse};
+use tokio_util::sync::{CancellationToken, DropGuard};
+use tracing::{Instrument, Span};
 
+use crate::admission::AdmissionQueue;
 use crate::catcher::{write_error_default, Catcher};
 use crate::conn::SocketAddr;
 use crate::fuse::ArcFusewire;
 use crate::handler::{Handler, WhenHoop};
 use crate::http::body::{ReqBody, ResBody};
-use crate::http::{Mime, Request, Response, StatusCode};
-use crate::routing::{FlowCtrl, PathState, Router};
+use crate::http::{Mime, Request, Response, StatusCode, StatusError};
+use crate::isolation::Isolation;
+use crate::limits::HeadLimits;
+use crate::pool::RequestPool;
+use crate::routing::{FlowCtrl, PathState, Router, RouterIndex};
+use crate::span::RequestSpan;
+use crate::strict::StrictParsing;
+#[cfg(feature = "tower-compat")]
+use crate::tower_compat::{FlowCtrlService, TowerLayerCompat, TowerLayerHandler, TowerServiceAdapter};
+use crate::BoxedError;
 use crate::Depot;
 
 /// Service http request.
@@ -28,20 +42,45 @@ pub struct Service {
     pub hoops: Vec<Arc<dyn Handler>>,
     /// The allowed media types of this service.
     pub allowed_media_types: Arc<Vec<Mime>>,
+    /// Whether a [`RequestSpan`] is created for each request, the default is `true`.
+    pub request_span: bool,
+    /// The admission queue of this service.
+    pub admission: Option<AdmissionQueue>,
+    /// The settings of the supervised execution of handlers.
+    pub isolation: Option<Isolation>,
+    /// The strict parsing of requests against request smuggling.
+    pub strict_parsing: Option<StrictParsing>,
+    /// The limits of the request line and headers.
+    pub head_limits: Option<HeadLimits>,
+    router_index: Arc<RouterIndex>,
+    /// Pool of the requests handled by [`Service::call`], which has no connection to keep a pool for.
+    pool: Arc<RequestPool>,
 }
 
 impl Service {
     /// Create a new Service with a [`Router`].
+    ///
+    /// The router tree is indexed by the path segments which routers require, so a request is only detected
+    /// against the routers which can match its path. Routers which are replaced after the service is created are
+    /// detected without the index.
     #[inline]
     pub fn new<T>(router: T) -> Service
     where
         T: Into<Arc<Router>>,
     {
+        let router = router.into();
         Service {
-            router: router.into(),
+            router_index: Arc::new(RouterIndex::new(&router)),
+            router,
             catcher: None,
             hoops: vec![],
             allowed_media_types: Arc::new(vec![]),
+            request_span: true,
+            admission: None,
+            isolation: None,
+            strict_parsing: None,
+            head_limits: None,
+            pool: Arc::new(RequestPool::new()),
         }
     }
 
@@ -99,6 +138,22 @@ impl Service {
         self
     }
 
+    /// Add a [`tower::Layer`] as middleware, such as the layers of `tower-http`.
+    ///
+    /// It is a shortcut of `hoop(layer.compat())`.
+    #[cfg(feature = "tower-compat")]
+    #[inline]
+    pub fn hoop_layer<L>(self, layer: L) -> Self
+    where
+        L: tower::Layer<FlowCtrlService> + Send + Sync + 'static,
+        L::Service: tower::Service<hyper::Request<ReqBody>> + Sync + Send + 'static,
+        <L::Service as tower::Service<hyper::Request<ReqBody>>>::Future: Send,
+        <L::Service as tower::Service<hyper::Request<ReqBody>>>::Error: std::error::Error + Send + Sync,
+        TowerLayerHandler<L::Service, ReqBody>: Handler,
+    {
+        self.hoop(layer.compat::<ReqBody>())
+    }
+
     /// Sets allowed media types list and returns `Self` for write code chained.
     ///
     /// # Example
@@ -120,6 +175,63 @@ impl Service {
         self
     }
 
+    /// Sets whether a [`RequestSpan`] is created for each request, the default is `true`.
+    ///
+    /// The span is entered while hoops, the handler and the catcher run. View [`span`](crate::span) module
+    /// documentation for more details.
+    #[inline]
+    pub fn request_span(mut self, enabled: bool) -> Self {
+        self.request_span = enabled;
+        self
+    }
+
+    /// Sets the [`AdmissionQueue`] which limits the number of requests processed concurrently.
+    ///
+    /// Requests which are shed get `503 Service Unavailable` responses, the priorities of requests are set with
+    /// [`Router::priority`]. View [`admission`](crate::admission) module documentation for more details.
+    #[inline]
+    pub fn admission(mut self, admission: AdmissionQueue) -> Self {
+        self.admission = Some(admission);
+        self
+    }
+
+    /// Sets the [`Isolation`] which runs the hoops and the handler of each request in a supervised task.
+    ///
+    /// Panics are contained in the task, the poll time of requests is accounted and requests are cancelled when
+    /// clients disconnect. View [`isolation`](crate::isolation) module documentation for more details.
+    #[inline]
+    pub fn isolation(mut self, isolation: Isolation) -> Self {
+        self.isolation = Some(isolation);
+        self
+    }
+
+    /// Sets the [`StrictParsing`] which rejects requests which can be read differently by intermediaries.
+    ///
+    /// It is recommended for deployments directly exposed to the internet. View [`strict`](crate::strict) module
+    /// documentation for more details.
+    #[inline]
+    pub fn strict_parsing(mut self, strict_parsing: StrictParsing) -> Self {
+        self.strict_parsing = Some(strict_parsing);
+        self
+    }
+
+    /// Sets the [`HeadLimits`] which rejects requests with too long URIs or too large headers.
+    ///
+    /// Unlike the limits of the HTTP/1 parser, the rejections are logged and handled by the catcher. View
+    /// [`limits`](crate::limits) module documentation for more details.
+    #[inline]
+    pub fn head_limits(mut self, head_limits: HeadLimits) -> Self {
+        self.head_limits = Some(head_limits);
+        self
+    }
+
+    /// Convert this `Service` to a [`tower::Service`].
+    #[cfg(feature = "tower-compat")]
+    #[inline]
+    pub fn into_tower(self) -> TowerServiceAdapter {
+        TowerServiceAdapter::new(self)
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn hyper_handler(
@@ -129,19 +241,68 @@ impl Service {
         http_scheme: Scheme,
         fusewire: Option<ArcFusewire>,
         alt_svc_h3: Option<HeaderValue>,
+    ) -> HyperHandler {
+        self.handler_with_pool(
+            local_addr,
+            remote_addr,
+            http_scheme,
+            fusewire,
+            alt_svc_h3,
+            Arc::new(RequestPool::new()),
+        )
+    }
+    fn handler_with_pool(
+        &self,
+        local_addr: SocketAddr,
+        remote_addr: SocketAddr,
+        http_scheme: Scheme,
+        fusewire: Option<ArcFusewire>,
+        alt_svc_h3: Option<HeaderValue>,
+        pool: Arc<RequestPool>,
     ) -> HyperHandler {
         HyperHandler {
             local_addr,
             remote_addr,
             http_scheme,
             router: self.router.clone(),
+            router_index: self.router_index.clone(),
+            pool,
             catcher: self.catcher.clone(),
             hoops: self.hoops.clone(),
             allowed_media_types: self.allowed_media_types.clone(),
+            request_span: self.request_span,
+            admission: self.admission.clone(),
+            isolation: self.isolation.clone(),
+            strict_parsing: self.strict_parsing.clone(),
+            head_limits: self.head_limits.clone(),
             fusewire,
             alt_svc_h3,
         }
     }
+    /// Handle a [`hyper::Request`] through the hoops, router and catcher of this service and returns the
+    /// [`hyper::Response`], without any listener or server.
+    ///
+    /// It allows the service to be driven by runtimes which provide their own HTTP stack, such as edge or FaaS
+    /// platforms and FFI hosts. The scheme is taken from the request uri or defaults to `http`, local and remote
+    /// addresses are unknown. The pooled allocations of requests are shared by all calls.
+    pub async fn call<B>(&self, req: HyperRequest<B>) -> HyperResponse<ResBody>
+    where
+        B: Into<ReqBody>,
+    {
+        let scheme = req.uri().scheme().cloned().unwrap_or(Scheme::HTTP);
+        self.handler_with_pool(
+            SocketAddr::Unknown,
+            SocketAddr::Unknown,
+            scheme.clone(),
+            None,
+            None,
+            self.pool.clone(),
+        )
+        .handle(Request::from_hyper(req, scheme))
+        .await
+        .into_hyper()
+    }
+
     /// Handle new request, this function only used for test.
     #[cfg(feature = "test")]
     #[inline]
@@ -176,9 +337,16 @@ pub struct HyperHandler {
     pub(crate) remote_addr: SocketAddr,
     pub(crate) http_scheme: Scheme,
     pub(crate) router: Arc<Router>,
+    pub(crate) router_index: Arc<RouterIndex>,
+    pub(crate) pool: Arc<RequestPool>,
     pub(crate) catcher: Option<Arc<Catcher>>,
     pub(crate) hoops: Vec<Arc<dyn Handler>>,
     pub(crate) allowed_media_types: Arc<Vec<Mime>>,
+    pub(crate) request_span: bool,
+    pub(crate) admission: Option<AdmissionQueue>,
+    pub(crate) isolation: Option<Isolation>,
+    pub(crate) strict_parsing: Option<StrictParsing>,
+    pub(crate) head_limits: Option<HeadLimits>,
     pub(crate) fusewire: Option<ArcFusewire>,
     pub(crate) alt_svc_h3: Option<HeaderValue>,
 }
@@ -191,79 +359,136 @@ impl HyperHandler {
         req.remote_addr = self.remote_addr.clone();
         #[cfg(not(feature = "cookie"))]
         let mut res = Response::new();
+        // Cookies are only parsed if the request carries any.
         #[cfg(feature = "cookie")]
-        let mut res = Response::with_cookies(req.cookies.clone());
+        let mut res = if req.headers().contains_key(http::header::COOKIE) {
+            Response::with_cookies(req.cookies().clone())
+        } else {
+            Response::new()
+        };
         if let Some(alt_svc_h3) = &self.alt_svc_h3 {
             if !res.headers().contains_key(ALT_SVC) {
                 res.headers_mut().insert(ALT_SVC, alt_svc_h3.clone());
             }
         }
-        let mut depot = Depot::new();
-        let mut path_state = PathState::new(req.uri().path());
+        let cancellation = req.cancellation.get_or_insert_with(CancellationToken::new).clone();
+        res.cancellation = Some(cancellation.clone());
+        let mut depot = self.pool.depot();
+        let (parts, params) = self.pool.path_buffers();
+        let mut path_state = PathState::with_buffers(req.uri().path(), parts, params);
         let router = self.router.clone();
+        let router_index = self.router_index.clone();
+        let pool = self.pool.clone();
 
         let hoops = self.hoops.clone();
+        let request_span = self.request_span;
+        let admission = self.admission.clone();
+        let isolation = self.isolation.clone();
+        let violation = self
+            .strict_parsing
+            .as_ref()
+            .and_then(|strict_parsing| strict_parsing.check(&req).err());
+        let rejection = self
+            .head_limits
+            .as_ref()
+            .and_then(|head_limits| head_limits.check(&req).err());
         async move {
-            if let Some(dm) = router.detect(&mut req, &mut path_state) {
-                req.params = path_state.params;
-                let mut ctrl = FlowCtrl::new([&hoops[..], &dm.hoops[..], &[dm.goal]].concat());
-                ctrl.call_next(&mut req, &mut depot, &mut res).await;
-                if res.status_code.is_none() {
-                    res.status_code = Some(StatusCode::OK);
-                }
-            } else if !hoops.is_empty() {
-                req.params = path_state.params;
-                let mut ctrl = FlowCtrl::new(hoops);
-                ctrl.call_next(&mut req, &mut depot, &mut res).await;
-                if res.status_code.is_none() && path_state.has_any_goal {
+            // The connection task drops this future when the client disconnects, which cancels the request.
+            let disconnect_guard = cancellation.drop_guard();
+            let dm = router.detect_indexed(Some(&router_index), &mut req, &mut path_state);
+            let span = if request_span {
+                let span = RequestSpan::new(&req, dm.as_ref().map(|dm| &*dm.route));
+                depot.inject(span.clone());
+                span.span().clone()
+            } else {
+                Span::none()
+            };
+            async {
+                let admitted = match &admission {
+                    Some(admission) if violation.is_none() && rejection.is_none() => {
+                        let priority = dm.as_ref().and_then(|dm| dm.priority).unwrap_or_default();
+                        admission.acquire(priority).await.map(Some)
+                    }
+                    _ => Ok(None),
+                };
+                if let Some(violation) = violation {
+                    // The rest of the connection can not be trusted to be delimited as the client intended.
+                    res.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
+                    res.render(StatusError::bad_request().brief(violation.to_string()));
+                } else if let Some(rejection) = rejection {
+                    res.render(rejection);
+                } else if let Err(shed) = &admitted {
+                    tracing::debug!(reason = %shed, "request is shed");
+                    res.render(StatusError::service_unavailable().brief(shed.to_string()));
+                } else if let Some(dm) = dm {
+                    req.params = std::mem::take(&mut path_state.params);
+                    if dm.parse_config.is_some() {
+                        req.parse_config = dm.parse_config;
+                    }
+                    if let Some(idempotent) = dm.idempotent {
+                        req.idempotent = idempotent;
+                    }
+                    let ctrl = FlowCtrl::new([&hoops[..], &dm.hoops[..], &[dm.goal]].concat());
+                    call_flow(isolation.as_ref(), ctrl, &mut req, &mut depot, &mut res).await;
+                    if res.status_code.is_none() {
+                        res.status_code = Some(StatusCode::OK);
+                    }
+                } else if !hoops.is_empty() {
+                    req.params = std::mem::take(&mut path_state.params);
+                    let ctrl = FlowCtrl::new(hoops);
+                    call_flow(isolation.as_ref(), ctrl, &mut req, &mut depot, &mut res).await;
+                    if res.status_code.is_none() && path_state.has_any_goal {
+                        res.status_code = Some(StatusCode::METHOD_NOT_ALLOWED);
+                    }
+                } else if path_state.has_any_goal {
                     res.status_code = Some(StatusCode::METHOD_NOT_ALLOWED);
                 }
-            } else if path_state.has_any_goal {
-                res.status_code = Some(StatusCode::METHOD_NOT_ALLOWED);
-            }
 
-            let status = res.status_code.unwrap_or(StatusCode::NOT_FOUND);
-            if !allowed_media_types.is_empty() {
-                if let Some(ctype) = res
-                    .headers()
-                    .get(CONTENT_TYPE)
-                    .and_then(|c| c.to_str().ok())
-                    .and_then(|c| c.parse::<Mime>().ok())
-                {
-                    let mut is_allowed = false;
-                    for mime in &*allowed_media_types {
-                        if mime.type_() == ctype.type_() && mime.subtype() == ctype.subtype() {
-                            is_allowed = true;
-                            break;
+                let status = *res.status_code.get_or_insert(StatusCode::NOT_FOUND);
+                if !allowed_media_types.is_empty() {
+                    if let Some(ctype) = res
+                        .headers()
+                        .get(CONTENT_TYPE)
+                        .and_then(|c| c.to_str().ok())
+                        .and_then(|c| c.parse::<Mime>().ok())
+                    {
+                        let mut is_allowed = false;
+                        for mime in &*allowed_media_types {
+                            if mime.type_() == ctype.type_() && mime.subtype() == ctype.subtype() {
+                                is_allowed = true;
+                                break;
+                            }
+                        }
+                        if !is_allowed {
+                            res.status_code(StatusCode::UNSUPPORTED_MEDIA_TYPE);
                         }
-                    }
-                    if !is_allowed {
-                        res.status_code(StatusCode::UNSUPPORTED_MEDIA_TYPE);
                     }
                 }
-            }
-            let has_error = status.is_client_error() || status.is_server_error();
-            if res.body.is_none()
-                && !has_error
-                && !status.is_redirection()
-                && res.status_code != Some(StatusCode::NO_CONTENT)
-                && res.status_code != Some(StatusCode::SWITCHING_PROTOCOLS)
-                && [Method::GET, Method::POST, Method::PATCH, Method::PUT].contains(req.method())
-            {
-                // check for avoid warning when errors (404 etc.)
-                tracing::warn!(
-                    uri = ?req.uri(),
-                    method = req.method().as_str(),
-                    "http response content type header not set"
-                );
-            }
-            if Method::HEAD != *req.method() && (res.body.is_none() || res.body.is_error()) && has_error {
-                if let Some(catcher) = catcher {
-                    catcher.catch(&mut req, &mut depot, &mut res).await;
-                } else {
-                    write_error_default(&req, &mut res, None);
+                let has_error = status.is_client_error() || status.is_server_error();
+                if res.body.is_none()
+                    && !has_error
+                    && !status.is_redirection()
+                    && res.status_code != Some(StatusCode::NO_CONTENT)
+                    && res.status_code != Some(StatusCode::SWITCHING_PROTOCOLS)
+                    && [Method::GET, Method::POST, Method::PATCH, Method::PUT].contains(req.method())
+                {
+                    // check for avoid warning when errors (404 etc.)
+                    tracing::warn!(
+                        uri = ?req.uri(),
+                        method = req.method().as_str(),
+                        "http response content type header not set"
+                    );
+                }
+                if Method::HEAD != *req.method() && (res.body.is_none() || res.body.is_error()) && has_error {
+                    if let Some(catcher) = catcher {
+                        catcher.catch(&mut req, &mut depot, &mut res).await;
+                    } else {
+                        write_error_default(&req, &mut res, None);
+                    }
                 }
             }
+            .instrument(span)
+            .await;
             #[cfg(debug_assertions)]
             if Method::HEAD == *req.method() && !res.body.is_none() {
                 tracing::warn!("request with head method should not have body: https://developer.mozilla.org/en-US/docs/Web/HTTP/Methods/HEAD");
@@ -291,11 +516,31 @@ impl HyperHandler {
                     res.extensions.insert(Arc::new(stream));
                 }
             }
+            // Cleanup hooks of the depot run after the response is sent, like the jobs deferred by the handlers.
+            res.deferred.append(depot.take_cleanups());
+            pool.recycle_depot(depot);
+            pool.recycle_path_buffers(path_state.parts, std::mem::take(&mut req.params));
+            disconnect_guard.disarm();
             res
         }
     }
 }
 
+async fn call_flow(
+    isolation: Option<&Isolation>,
+    mut ctrl: FlowCtrl,
+    req: &mut Request,
+    depot: &mut Depot,
+    res: &mut Response,
+) {
+    match isolation {
+        Some(isolation) => isolation.supervise(ctrl, req, depot, res).await,
+        None => {
+            ctrl.call_next(req, depot, res).await;
+        }
+    }
+}
+
 impl<B> HyperService<HyperRequest<B>> for HyperHandler
 where
     B: Into<ReqBody>,
@@ -330,13 +575,59 @@ where
         }
         let mut request = Request::from_hyper(req, scheme);
         request.body.set_fusewire(self.fusewire.clone());
+        let cancellation = CancellationToken::new();
+        request.cancellation = Some(cancellation.clone());
         let response = self.handle(request);
-        Box::pin(async move { Ok(response.await.into_hyper()) })
+        Box::pin(async move {
+            let mut response = response.await;
+            if response.body.is_hyper()
+                || response.body.is_boxed()
+                || response.body.is_stream()
+                || response.body.is_channel()
+            {
+                response.body = ResBody::Boxed(Box::pin(DisconnectGuardBody {
+                    body: std::mem::take(&mut response.body),
+                    guard: Some(cancellation.drop_guard()),
+                }));
+            }
+            Ok(response.into_hyper())
+        })
+    }
+}
+
+/// Streamed response body which cancels the request when it is dropped before its end, which happens when the client
+/// disconnects while the body is sent.
+struct DisconnectGuardBody {
+    body: ResBody,
+    guard: Option<DropGuard>,
+}
+
+impl Body for DisconnectGuardBody {
+    type Data = Bytes;
+    type Error = BoxedError;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, BoxedError>>> {
+        let frame = ready!(Pin::new(&mut self.body).poll_frame(cx));
+        if frame.is_none() || self.body.is_end_stream() {
+            if let Some(guard) = self.guard.take() {
+                guard.disarm();
+            }
+        }
+        Poll::Ready(frame.map(|frame| frame.map_err(Into::into)))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::http::ReqBody;
     use crate::prelude::*;
     use crate::test::{ResponseExt, TestClient};
 
@@ -397,4 +688,111 @@ mod tests {
         let content = access(&service, "3").await;
         assert_eq!(content, "before1before2before3");
     }
+
+    #[tokio::test]
+    async fn test_service_call() {
+        #[handler]
+        async fn hello(req: &mut Request) -> String {
+            format!("hello {}", req.scheme())
+        }
+        let service = Service::new(Router::with_path("hello").get(hello));
+
+        let req = hyper::Request::builder()
+            .uri("https://example.com/hello")
+            .body(ReqBody::None)
+            .unwrap();
+        let mut res = Response::from(service.call(req).await);
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(res.take_string().await.unwrap(), "hello https");
+
+        let req = hyper::Request::builder().uri("/missing").body(ReqBody::None).unwrap();
+        assert_eq!(service.call(req).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_service_reuses_cleared_depot() {
+        #[handler]
+        async fn visit(req: &mut Request, depot: &mut Depot) -> String {
+            let seen = depot.contains_key("visited");
+            depot.insert("visited", true);
+            format!("{seen} {}", req.param::<String>("name").unwrap_or_default())
+        }
+        let service = Service::new(
+            Router::new()
+                .push(Router::with_path("hello/<name>").get(visit))
+                .push(Router::with_path("hello").get(visit)),
+        );
+        let handler = service.hyper_handler(
+            crate::conn::SocketAddr::Unknown,
+            crate::conn::SocketAddr::Unknown,
+            crate::http::uri::Scheme::HTTP,
+            None,
+            None,
+        );
+
+        let mut res = handler
+            .handle(TestClient::get("http://127.0.0.1:5800/hello/jobs").build())
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "false jobs");
+        let mut res = handler
+            .handle(TestClient::get("http://127.0.0.1:5800/hello").build())
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "false ");
+    }
+
+    #[tokio::test]
+    async fn test_service_disconnect() {
+        use std::time::Duration;
+
+        use hyper::service::Service as _;
+        use tokio::sync::mpsc;
+
+        struct Watch {
+            tx: mpsc::UnboundedSender<bool>,
+        }
+        #[handler]
+        impl Watch {
+            async fn handle(&self, req: &mut Request, res: &mut Response) {
+                let disconnected = req.on_disconnect();
+                let token = res.cancellation_token();
+                let tx = self.tx.clone();
+                tokio::spawn(async move {
+                    disconnected.await;
+                    tx.send(token.is_cancelled()).ok();
+                });
+                if req.query::<bool>("stream").unwrap_or_default() {
+                    let _sender = res.channel();
+                } else {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                }
+            }
+        }
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let service = Service::new(Router::new().get(Watch { tx }));
+        let handler = service.hyper_handler(
+            crate::conn::SocketAddr::Unknown,
+            crate::conn::SocketAddr::Unknown,
+            crate::http::uri::Scheme::HTTP,
+            None,
+            None,
+        );
+
+        // Dropping the future is what the connection task does when the client disconnects.
+        let result = tokio::time::timeout(
+            Duration::from_millis(20),
+            handler.handle(TestClient::get("http://127.0.0.1:5800/").build()),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(rx.recv().await, Some(true));
+
+        let req = hyper::Request::builder()
+            .uri("http://127.0.0.1:5800/?stream=true")
+            .body(ReqBody::None)
+            .unwrap();
+        let res = handler.call(req).await.unwrap();
+        assert!(rx.try_recv().is_err());
+        drop(res);
+        assert_eq!(rx.recv().await, Some(true));
+    }
 }
@@ -0,0 +1,151 @@
+//! Request-scoped tracing span.
+//!
+//! For each request, [`Service`](crate::Service) creates a span named `request` which carries the method, the
+//! matched route template, the client IP, the request id and the user id. The span is entered while hoops, the
+//! handler and the catcher run, so events emitted by `tracing::info!` and friends in handlers inherit these fields
+//! without `#[instrument]`.
+//!
+//! The span is also injected into the [`Depot`], middlewares can record the fields which they know about:
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_core::span::SpanDepotExt;
+//!
+//! #[handler]
+//! async fn auth(depot: &mut Depot) {
+//!     if let Some(span) = depot.request_span() {
+//!         span.record_user_id("alice");
+//!     }
+//! }
+//! ```
+use std::fmt::Display;
+
+use tracing::field::Empty;
+use tracing::Span;
+
+use crate::http::Request;
+use crate::Depot;
+
+/// The span of the current request, injected into the [`Depot`] by [`Service`](crate::Service).
+#[derive(Clone, Debug)]
+pub struct RequestSpan {
+    span: Span,
+    route: Option<String>,
+}
+
+impl RequestSpan {
+    pub(crate) fn new(req: &Request, route: Option<&str>) -> Self {
+        let route = route.map(|route| {
+            if route.is_empty() {
+                "/".to_owned()
+            } else {
+                route.to_owned()
+            }
+        });
+        let span = tracing::info_span!(
+            "request",
+            method = %req.method(),
+            route = route.as_deref().unwrap_or_default(),
+            client_ip = Empty,
+            request_id = Empty,
+            user_id = Empty,
+        );
+        if let Some(addr) = req.remote_addr().clone().into_std() {
+            span.record("client_ip", tracing::field::display(addr.ip()));
+        }
+        if let Some(id) = req.headers().get("x-request-id").and_then(|id| id.to_str().ok()) {
+            span.record("request_id", id);
+        }
+        Self { span, route }
+    }
+
+    /// Get the [`Span`].
+    #[inline]
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Get the path template of the matched route, such as `/users/<id>`.
+    ///
+    /// Returns `None` if no route is matched.
+    #[inline]
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    /// Record the request id.
+    #[inline]
+    pub fn record_request_id(&self, id: impl Display) {
+        self.span.record("request_id", tracing::field::display(id));
+    }
+
+    /// Record the id of the authenticated user.
+    #[inline]
+    pub fn record_user_id(&self, id: impl Display) {
+        self.span.record("user_id", tracing::field::display(id));
+    }
+}
+
+/// Extension trait for getting the [`RequestSpan`] from the [`Depot`].
+pub trait SpanDepotExt {
+    /// Get the span of the current request.
+    ///
+    /// Returns `None` if request spans are disabled in the [`Service`](crate::Service).
+    fn request_span(&self) -> Option<&RequestSpan>;
+}
+
+impl SpanDepotExt for Depot {
+    #[inline]
+    fn request_span(&self) -> Option<&RequestSpan> {
+        self.obtain::<RequestSpan>().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn show_route(depot: &mut Depot) -> String {
+        depot
+            .request_span()
+            .and_then(|span| span.route())
+            .unwrap_or("none")
+            .to_owned()
+    }
+
+    #[tokio::test]
+    async fn test_request_span_route() {
+        let router = Router::new()
+            .push(Router::with_path("users/<id>").push(Router::with_path("posts/<post_id>").get(show_route)));
+        let service = Service::new(router);
+        let content = TestClient::get("http://127.0.0.1:5801/users/1/posts/2")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "/users/<id>/posts/<post_id>");
+
+        let service = Service::new(Router::new().get(show_route));
+        let content = TestClient::get("http://127.0.0.1:5801/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "/");
+
+        let service = Service::new(Router::new().get(show_route)).request_span(false);
+        let content = TestClient::get("http://127.0.0.1:5801/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "none");
+    }
+}
@@ -0,0 +1,305 @@
+//! Startup report of a server.
+//!
+//! [`StartupReport`] collects the bound addresses with their schemes and ALPN protocols, the number of routes, the
+//! middlewares, the expiry dates of TLS certificates and configuration warnings. It is printed as a banner for humans
+//! with [`Display`], or serialized as JSON for orchestration tools with [`StartupReport::to_json`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     let server = Server::new(acceptor);
+//!     let service = Service::new(Router::new().get(hello));
+//!     server.startup_report(&service).print();
+//!     server.serve(service).await;
+//! }
+//! ```
+use std::fmt::{self, Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::conn::Holding;
+use crate::http::uri::Scheme;
+use crate::http::Version;
+use crate::routing::Router;
+use crate::Service;
+
+/// Certificates expiring in less days are reported as warnings.
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Report of a server at startup.
+#[derive(Serialize, Clone, Default, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StartupReport {
+    /// Bound listeners.
+    pub listeners: Vec<ListenerReport>,
+    /// Number of routes, a route is a router with a goal handler.
+    pub routes: usize,
+    /// Type names of the middlewares of the service and its routers, in the order they are found.
+    pub middlewares: Vec<String>,
+    /// TLS certificates.
+    pub certificates: Vec<CertificateReport>,
+    /// Configuration warnings.
+    pub warnings: Vec<String>,
+}
+
+/// Report of a bound listener.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ListenerReport {
+    /// Local address.
+    pub addr: String,
+    /// Http scheme.
+    pub scheme: String,
+    /// Http versions.
+    pub http_versions: Vec<String>,
+    /// ALPN protocols negotiated by TLS listeners, empty for plain listeners.
+    pub alpn: Vec<String>,
+}
+
+/// Report of a TLS certificate.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CertificateReport {
+    /// Name of the certificate, such as the domain it is served for.
+    pub name: String,
+    /// Expiry time as seconds since the Unix epoch.
+    pub not_after: u64,
+    /// Days left until the certificate expires, negative if it is expired.
+    pub days_left: i64,
+}
+
+impl StartupReport {
+    /// Create a new `StartupReport` of `service` served on the listeners of `holdings`.
+    pub fn new(holdings: &[Holding], service: &Service) -> Self {
+        let listeners = holdings
+            .iter()
+            .map(|holding| ListenerReport {
+                addr: holding
+                    .local_addr
+                    .to_string()
+                    .trim_start_matches("socket://")
+                    .to_owned(),
+                scheme: holding.http_scheme.to_string(),
+                http_versions: holding
+                    .http_versions
+                    .iter()
+                    .map(|version| version_name(*version))
+                    .collect(),
+                alpn: if holding.http_scheme == Scheme::HTTPS {
+                    holding
+                        .http_versions
+                        .iter()
+                        .rev()
+                        .filter_map(|version| alpn_id(*version))
+                        .map(Into::into)
+                        .collect()
+                } else {
+                    vec![]
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let mut middlewares = service
+            .hoops
+            .iter()
+            .map(|hoop| hoop.type_name().to_owned())
+            .collect::<Vec<_>>();
+        let mut routes = 0;
+        collect_router(&service.router, &mut routes, &mut middlewares);
+        let mut seen = std::collections::HashSet::new();
+        middlewares.retain(|name| seen.insert(name.clone()));
+
+        let mut warnings = vec![];
+        if listeners.is_empty() {
+            warnings.push("no listener is bound".to_owned());
+        }
+        if routes == 0 {
+            warnings.push("no route is registered".to_owned());
+        }
+        if cfg!(debug_assertions) {
+            warnings.push("running a debug build, build with `--release` for production".to_owned());
+        }
+        Self {
+            listeners,
+            routes,
+            middlewares,
+            certificates: vec![],
+            warnings,
+        }
+    }
+
+    /// Add a TLS certificate with its expiry time, a warning is added if it expires in less than 30 days.
+    pub fn add_certificate(mut self, name: impl Into<String>, not_after: SystemTime) -> Self {
+        let name = name.into();
+        let not_after = not_after
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let days_left = (not_after as i64 - now as i64).div_euclid(24 * 60 * 60);
+        if days_left < 0 {
+            self.warnings.push(format!("certificate `{name}` is expired"));
+        } else if days_left < EXPIRY_WARNING_DAYS {
+            self.warnings
+                .push(format!("certificate `{name}` expires in {days_left} days"));
+        }
+        self.certificates.push(CertificateReport {
+            name,
+            not_after,
+            days_left,
+        });
+        self
+    }
+
+    /// Add a configuration warning.
+    pub fn add_warning(mut self, warning: impl Into<String>) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    /// Serialize the report as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Print the report to stdout.
+    pub fn print(&self) {
+        println!("{self}");
+    }
+}
+
+impl Display for StartupReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Salvo server is ready")?;
+        writeln!(f, "  listeners:")?;
+        for listener in &self.listeners {
+            write!(
+                f,
+                "    {}://{} [{}]",
+                listener.scheme,
+                listener.addr,
+                listener.http_versions.join(", ")
+            )?;
+            if !listener.alpn.is_empty() {
+                write!(f, " alpn: {}", listener.alpn.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "  routes: {}", self.routes)?;
+        if !self.middlewares.is_empty() {
+            writeln!(f, "  middlewares:")?;
+            for middleware in &self.middlewares {
+                writeln!(f, "    {middleware}")?;
+            }
+        }
+        if !self.certificates.is_empty() {
+            writeln!(f, "  certificates:")?;
+            for certificate in &self.certificates {
+                writeln!(f, "    {} expires in {} days", certificate.name, certificate.days_left)?;
+            }
+        }
+        if !self.warnings.is_empty() {
+            writeln!(f, "  warnings:")?;
+            for warning in &self.warnings {
+                writeln!(f, "    - {warning}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `Debug` output of [`Version`] differs between versions of the `http` crate.
+fn version_name(version: Version) -> String {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9".into(),
+        Version::HTTP_10 => "HTTP/1.0".into(),
+        Version::HTTP_11 => "HTTP/1.1".into(),
+        Version::HTTP_2 => "HTTP/2".into(),
+        Version::HTTP_3 => "HTTP/3".into(),
+        _ => format!("{version:?}"),
+    }
+}
+
+fn alpn_id(version: Version) -> Option<&'static str> {
+    match version {
+        Version::HTTP_10 => Some("http/1.0"),
+        Version::HTTP_11 => Some("http/1.1"),
+        Version::HTTP_2 => Some("h2"),
+        Version::HTTP_3 => Some("h3"),
+        _ => None,
+    }
+}
+
+fn collect_router(router: &Router, routes: &mut usize, middlewares: &mut Vec<String>) {
+    if router.goal.is_some() {
+        *routes += 1;
+    }
+    middlewares.extend(router.hoops.iter().map(|hoop| hoop.type_name().to_owned()));
+    for child in &router.routers {
+        collect_router(child, routes, middlewares);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddrV4};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[handler]
+    async fn hello() -> &'static str {
+        "Hello World"
+    }
+    #[handler]
+    async fn auth() {}
+
+    #[test]
+    fn test_startup_report() {
+        let holdings = [Holding {
+            local_addr: SocketAddrV4::new(Ipv4Addr::LOCALHOST, 5800).into(),
+            http_versions: vec![Version::HTTP_11, Version::HTTP_2],
+            http_scheme: Scheme::HTTPS,
+        }];
+        let router = Router::new()
+            .hoop(auth)
+            .get(hello)
+            .push(Router::with_path("users").hoop(auth).get(hello).post(hello));
+        let week = Duration::from_secs(7 * 24 * 60 * 60);
+        let report = StartupReport::new(&holdings, &Service::new(router))
+            .add_certificate("example.com", SystemTime::now() + week * 10 + Duration::from_secs(60))
+            .add_certificate("old.example.com", SystemTime::now() + week + Duration::from_secs(60));
+
+        assert_eq!(report.routes, 3);
+        assert_eq!(report.middlewares.len(), 1);
+        assert!(report.middlewares[0].ends_with("auth"));
+        assert_eq!(report.listeners[0].addr, "127.0.0.1:5800");
+        assert_eq!(report.listeners[0].alpn, ["h2", "http/1.1"]);
+        assert_eq!(report.certificates[0].days_left, 70);
+        assert!(report
+            .warnings
+            .contains(&"certificate `old.example.com` expires in 7 days".to_owned()));
+
+        let text = report.to_string();
+        assert!(text.contains("    https://127.0.0.1:5800 [HTTP/1.1, HTTP/2] alpn: h2, http/1.1\n"));
+        assert!(text.contains("  routes: 3\n"));
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["listeners"][0]["scheme"], "https");
+        assert_eq!(json["certificates"][1]["name"], "old.example.com");
+    }
+}
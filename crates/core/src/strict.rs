@@ -0,0 +1,349 @@
+//! Strict parsing of requests against request smuggling.
+//!
+//! Request smuggling exploits the different ways front proxies and servers delimit requests on a shared
+//! connection. The HTTP/1 parser already rejects requests which can not be parsed unambiguously, but it tolerates
+//! a few constructs defined as valid or recoverable by the specifications, which are still read differently by some
+//! intermediaries. When [`StrictParsing`] is set on the [`Service`](crate::Service), such requests are inspected
+//! before routing:
+//!
+//! - `Content-Length` sent with `Transfer-Encoding`, invalid or repeated `Content-Length`.
+//! - `Transfer-Encoding` outside of HTTP/1.1, without `chunked` as final coding or with other codings.
+//! - Header values folded over several lines, or carrying a bare CR or LF, which can come from hosts building
+//!   requests themselves, see [`Service::call`](crate::Service::call).
+//! - Header values with bytes outside of ASCII.
+//! - Several `Host` headers, or a `Host` different from the authority of the request target.
+//!
+//! Each [`Violation`] is logged as a security event with the `salvo::security` target, and the request is rejected
+//! with a `400 Bad Request` response closing the connection unless the [`Leniency`] tolerates it.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_core::strict::{Leniency, StrictParsing};
+//!
+//! let service = Service::new(Router::new()).strict_parsing(StrictParsing::new().leniency(Leniency::Strict));
+//! ```
+use std::fmt::{self, Display, Formatter};
+
+use crate::http::header::{HeaderMap, CONTENT_LENGTH, HOST, TRANSFER_ENCODING};
+use crate::http::{Request, Version};
+
+/// Target of the security events logged for violations.
+pub const SECURITY_TARGET: &str = "salvo::security";
+
+/// Violations tolerated by [`StrictParsing`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Leniency {
+    /// Reject every violation.
+    Strict,
+    /// Reject violations which can desynchronize intermediaries, tolerate repeated identical `Content-Length`,
+    /// transfer codings other than `chunked` and bytes outside of ASCII.
+    #[default]
+    Standard,
+    /// Only log violations.
+    Lenient,
+}
+
+/// A construct of a request which can be read differently by intermediaries.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Violation {
+    /// Both `Content-Length` and `Transfer-Encoding` are present.
+    ContentLengthWithTransferEncoding,
+    /// `Content-Length` is not a valid length, or it has different values.
+    InvalidContentLength,
+    /// `Content-Length` is sent several times with the same value.
+    DuplicateContentLength,
+    /// `Transfer-Encoding` is sent outside of HTTP/1.1, or `chunked` is not its only final coding.
+    InvalidTransferEncoding,
+    /// `Transfer-Encoding` has codings other than `chunked`.
+    UnknownTransferCoding,
+    /// A header value is folded over several lines.
+    ObsFold,
+    /// A header value has a CR or LF which does not start a folded line.
+    BareCr,
+    /// A header value has bytes outside of ASCII.
+    ObsText,
+    /// `Host` is sent several times, or it differs from the authority of the request target.
+    ConflictingHost,
+}
+
+impl Violation {
+    /// Returns `true` if the violation is tolerated by `leniency`.
+    pub fn is_tolerated(&self, leniency: Leniency) -> bool {
+        match leniency {
+            Leniency::Strict => false,
+            Leniency::Standard => matches!(
+                self,
+                Self::DuplicateContentLength | Self::UnknownTransferCoding | Self::ObsText
+            ),
+            Leniency::Lenient => true,
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::ContentLengthWithTransferEncoding => "both content-length and transfer-encoding are present",
+            Self::InvalidContentLength => "invalid content-length",
+            Self::DuplicateContentLength => "duplicate content-length",
+            Self::InvalidTransferEncoding => "invalid transfer-encoding",
+            Self::UnknownTransferCoding => "unknown transfer coding",
+            Self::ObsFold => "folded header value",
+            Self::BareCr => "bare CR or LF in header value",
+            Self::ObsText => "non-ASCII header value",
+            Self::ConflictingHost => "conflicting host",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Strict parsing of requests.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug)]
+pub struct StrictParsing {
+    leniency: Leniency,
+    log_violations: bool,
+}
+
+impl Default for StrictParsing {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StrictParsing {
+    /// Create a new `StrictParsing` with the [`Leniency::Standard`] level, violations are logged.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            leniency: Leniency::Standard,
+            log_violations: true,
+        }
+    }
+
+    /// Sets the violations which are tolerated.
+    #[inline]
+    pub fn leniency(mut self, leniency: Leniency) -> Self {
+        self.leniency = leniency;
+        self
+    }
+
+    /// Sets whether violations are logged as security events, the default is `true`.
+    #[inline]
+    pub fn log_violations(mut self, log_violations: bool) -> Self {
+        self.log_violations = log_violations;
+        self
+    }
+
+    /// Check `req`, returns the first violation which is not tolerated.
+    pub fn check(&self, req: &Request) -> Result<(), Violation> {
+        let violations = scan(req);
+        if self.log_violations {
+            for violation in &violations {
+                tracing::warn!(
+                    target: SECURITY_TARGET,
+                    %violation,
+                    rejected = !violation.is_tolerated(self.leniency),
+                    remote_addr = %req.remote_addr(),
+                    method = req.method().as_str(),
+                    uri = ?req.uri(),
+                    "suspicious request"
+                );
+            }
+        }
+        match violations
+            .into_iter()
+            .find(|violation| !violation.is_tolerated(self.leniency))
+        {
+            Some(violation) => Err(violation),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Find the violations of `req`, each kind is reported once.
+fn scan(req: &Request) -> Vec<Violation> {
+    let headers = req.headers();
+    let mut violations = vec![];
+    let has_transfer_encoding = headers.contains_key(TRANSFER_ENCODING);
+    if headers.contains_key(CONTENT_LENGTH) {
+        if has_transfer_encoding {
+            violations.push(Violation::ContentLengthWithTransferEncoding);
+        }
+        match list(headers, CONTENT_LENGTH.as_str()) {
+            Some(lengths)
+                if !lengths.is_empty()
+                    && lengths
+                        .iter()
+                        .all(|length| !length.is_empty() && length.bytes().all(|b| b.is_ascii_digit())) =>
+            {
+                if lengths.windows(2).any(|pair| pair[0] != pair[1]) {
+                    violations.push(Violation::InvalidContentLength);
+                } else if lengths.len() > 1 {
+                    violations.push(Violation::DuplicateContentLength);
+                }
+            }
+            _ => violations.push(Violation::InvalidContentLength),
+        }
+    }
+    if has_transfer_encoding {
+        let codings = list(headers, TRANSFER_ENCODING.as_str()).unwrap_or_default();
+        let chunked = codings
+            .iter()
+            .filter(|coding| coding.eq_ignore_ascii_case("chunked"))
+            .count();
+        if req.version() != Version::HTTP_11
+            || chunked != 1
+            || !codings
+                .last()
+                .is_some_and(|coding| coding.eq_ignore_ascii_case("chunked"))
+        {
+            violations.push(Violation::InvalidTransferEncoding);
+        } else if codings.len() > 1 {
+            violations.push(Violation::UnknownTransferCoding);
+        }
+    }
+
+    let mut hosts = headers.get_all(HOST).iter();
+    if let Some(host) = hosts.next() {
+        let conflicting = hosts.next().is_some()
+            || req
+                .uri()
+                .authority()
+                .is_some_and(|authority| !authority.as_str().as_bytes().eq_ignore_ascii_case(host.as_bytes()));
+        if conflicting {
+            violations.push(Violation::ConflictingHost);
+        }
+    }
+
+    for violation in headers.values().filter_map(|value| scan_value(value.as_bytes())) {
+        if !violations.contains(&violation) {
+            violations.push(violation);
+        }
+    }
+    violations
+}
+
+/// Find the violation of a header value.
+///
+/// Values with CR or LF can not be parsed from the wire, they are only built by hosts bypassing the validation of
+/// header values.
+fn scan_value(bytes: &[u8]) -> Option<Violation> {
+    if let Some(index) = bytes.iter().position(|b| *b == b'\r' || *b == b'\n') {
+        let rest = bytes[index..].strip_prefix(b"\r").unwrap_or(&bytes[index..]);
+        if rest.starts_with(b"\n ") || rest.starts_with(b"\n\t") {
+            Some(Violation::ObsFold)
+        } else {
+            Some(Violation::BareCr)
+        }
+    } else if !bytes.is_ascii() {
+        Some(Violation::ObsText)
+    } else {
+        None
+    }
+}
+
+/// Values of a comma separated list header, `None` if a value is not visible ASCII.
+fn list<'a>(headers: &'a HeaderMap, name: &str) -> Option<Vec<&'a str>> {
+    let mut items = vec![];
+    for value in headers.get_all(name) {
+        items.extend(value.to_str().ok()?.split(',').map(str::trim));
+    }
+    Some(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::CONNECTION;
+    use http::HeaderValue;
+
+    use crate::prelude::*;
+    use crate::test::TestClient;
+
+    use super::*;
+
+    fn request(headers: &[(&'static str, &str)]) -> Request {
+        let mut req = Request::new();
+        for (name, value) in headers {
+            req.headers_mut().append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        req
+    }
+
+    #[test]
+    fn test_scan() {
+        assert!(scan(&request(&[("content-length", "5")])).is_empty());
+        assert_eq!(
+            scan(&request(&[("content-length", "5"), ("transfer-encoding", "chunked")])),
+            vec![Violation::ContentLengthWithTransferEncoding]
+        );
+        assert_eq!(
+            scan(&request(&[("content-length", "5"), ("content-length", "6")])),
+            vec![Violation::InvalidContentLength]
+        );
+        assert_eq!(
+            scan(&request(&[("content-length", "5, 5")])),
+            vec![Violation::DuplicateContentLength]
+        );
+        assert_eq!(
+            scan(&request(&[("content-length", "+5")])),
+            vec![Violation::InvalidContentLength]
+        );
+        assert_eq!(
+            scan(&request(&[("transfer-encoding", "chunked, gzip")])),
+            vec![Violation::InvalidTransferEncoding]
+        );
+        assert_eq!(
+            scan(&request(&[("transfer-encoding", "gzip, chunked")])),
+            vec![Violation::UnknownTransferCoding]
+        );
+        assert_eq!(
+            scan(&request(&[("host", "a.com"), ("host", "b.com")])),
+            vec![Violation::ConflictingHost]
+        );
+        assert_eq!(scan(&request(&[("x-name", "café")])), vec![Violation::ObsText]);
+
+        assert_eq!(scan_value(b"a\r\n b"), Some(Violation::ObsFold));
+        assert_eq!(scan_value(b"a\n\tb"), Some(Violation::ObsFold));
+        assert_eq!(scan_value(b"a\rb"), Some(Violation::BareCr));
+        assert_eq!(scan_value(b"a\r\nb"), Some(Violation::BareCr));
+        assert_eq!(scan_value(b"a b"), None);
+    }
+
+    #[tokio::test]
+    async fn test_strict_parsing() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+
+        let service = Service::new(Router::new().post(hello)).strict_parsing(StrictParsing::new());
+        let res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("content-length", "5", true)
+            .add_header("transfer-encoding", "chunked", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+        assert_eq!(res.headers().get(CONNECTION).unwrap(), "close");
+
+        let res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("transfer-encoding", "gzip, chunked", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+
+        let service = Service::new(Router::new().post(hello))
+            .strict_parsing(StrictParsing::new().leniency(Leniency::Strict).log_violations(false));
+        let res = TestClient::post("http://127.0.0.1:5801")
+            .add_header("transfer-encoding", "gzip, chunked", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,675 @@
+//! Background tasks tied to the server lifecycle.
+//!
+//! [`Tasks`] spawns named jobs which run on the tokio runtime beside the server:
+//!
+//! - [`Tasks::spawn`] runs a job once, until it returns.
+//! - [`Tasks::spawn_periodic`] runs a job at a fixed interval.
+//! - [`Tasks::spawn_on_demand`] runs a job each time it is [triggered](Tasks::trigger).
+//! - `Tasks::spawn_cron` runs a job on a cron schedule, it requires the `cron` feature.
+//!
+//! A job which panics is restarted after a backoff delay, which doubles on every consecutive panic. All jobs are
+//! cancelled when [`Tasks::shutdown`] is called, which [`Server`](crate::Server) does when it is stopped. Jobs
+//! registered with [`Tasks::on_shutdown`] run once at that time, such as to save the state changed by the last
+//! requests.
+//!
+//! Work deferred by handlers with [`Response::on_sent`] runs on a separate pool after the response is sent, the
+//! server waits for it with [`drain_deferred`] when it is stopped, instead of cancelling it.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_core::prelude::*;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     let server = Server::new(acceptor);
+//!     let tasks = server.tasks().clone();
+//!     tasks.spawn_periodic("cleanup", Duration::from_secs(60), || async {
+//!         println!("cleanup");
+//!     });
+//!     let router = Router::with_path("tasks").get(tasks.inspector());
+//!     server.serve(router).await;
+//! }
+//! ```
+use std::any::Any;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::future::BoxFuture;
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use serde::Serialize;
+use sync_wrapper::SyncWrapper;
+use tokio::sync::Notify;
+use tokio::task::{JoinError, JoinHandle};
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+use crate::writing::Json;
+use crate::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+
+cfg_feature! {
+    #![feature = "cron"]
+    mod cron;
+    pub use self::cron::{CronSchedule, Overlap};
+    pub use chrono_tz::Tz;
+}
+
+fn deferred_tracker() -> &'static TaskTracker {
+    static TRACKER: OnceLock<TaskTracker> = OnceLock::new();
+    TRACKER.get_or_init(TaskTracker::new)
+}
+
+/// Number of jobs deferred with [`Response::on_sent`] which are running.
+#[inline]
+pub fn pending_deferred() -> usize {
+    deferred_tracker().len()
+}
+
+/// Wait for the jobs deferred with [`Response::on_sent`] to finish.
+///
+/// [`Server`](crate::Server) calls it when it is stopped, after all connections are closed.
+pub async fn drain_deferred() {
+    let tracker = deferred_tracker();
+    tracker.close();
+    tracker.wait().await;
+    tracker.reopen();
+}
+
+/// Jobs deferred until a response is sent, they are spawned when it is dropped.
+#[derive(Default)]
+pub(crate) struct Deferred(Vec<SyncWrapper<BoxFuture<'static, ()>>>);
+impl Deferred {
+    #[inline]
+    pub(crate) fn push(&mut self, job: BoxFuture<'static, ()>) {
+        self.0.push(SyncWrapper::new(job));
+    }
+
+    #[inline]
+    pub(crate) fn append(&mut self, mut other: Deferred) {
+        self.0.append(&mut other.0);
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+impl Drop for Deferred {
+    fn drop(&mut self) {
+        if self.0.is_empty() {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!(
+                count = self.0.len(),
+                "no runtime to run deferred jobs, they are dropped"
+            );
+            return;
+        };
+        for job in self.0.drain(..) {
+            deferred_tracker().spawn_on(job.into_inner(), &handle);
+        }
+    }
+}
+
+/// Kind of a background task.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TaskKind {
+    /// Runs once, until the job returns.
+    Job,
+    /// Runs at a fixed interval.
+    Periodic,
+    /// Runs when it is triggered.
+    OnDemand,
+    /// Runs on a cron schedule.
+    Cron,
+}
+
+/// State of a background task.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    /// Waiting for the next run.
+    Idle,
+    /// The job is running.
+    Running,
+    /// The job panicked and will be restarted after a backoff delay.
+    Restarting,
+    /// The job has returned and will not run again.
+    Finished,
+    /// The task is cancelled.
+    Stopped,
+}
+
+/// Status of a background task.
+#[derive(Serialize, Clone, Debug)]
+pub struct TaskStatus {
+    /// Name of the task.
+    pub name: String,
+    /// Kind of the task.
+    pub kind: TaskKind,
+    /// Current state of the task.
+    pub state: TaskState,
+    /// How many times the job has been started.
+    pub runs: u64,
+    /// How many times the job has panicked.
+    pub panics: u64,
+    /// When the job was last started, in milliseconds since unix epoch.
+    pub last_started_at: Option<u64>,
+    /// When the job last returned or panicked, in milliseconds since unix epoch.
+    pub last_finished_at: Option<u64>,
+    /// Message of the last panic.
+    pub last_panic: Option<String>,
+    /// How many scheduled runs were skipped because the previous run was still running.
+    pub skipped: u64,
+    /// When the job is scheduled to run next, in milliseconds since unix epoch.
+    pub next_run_at: Option<u64>,
+}
+impl TaskStatus {
+    fn new(name: String, kind: TaskKind) -> Self {
+        Self {
+            name,
+            kind,
+            state: TaskState::Idle,
+            runs: 0,
+            panics: 0,
+            last_started_at: None,
+            last_finished_at: None,
+            last_panic: None,
+            skipped: 0,
+            next_run_at: None,
+        }
+    }
+
+    fn started(&mut self) {
+        self.state = TaskState::Running;
+        self.runs += 1;
+        self.last_started_at = Some(now_millis());
+    }
+
+    fn panicked(&mut self, message: String) {
+        self.panics += 1;
+        self.last_finished_at = Some(now_millis());
+        self.last_panic = Some(message);
+    }
+}
+
+enum Schedule {
+    Once,
+    Every(Duration),
+    OnDemand(Arc<Notify>),
+    #[cfg(feature = "cron")]
+    Cron(CronSchedule),
+}
+impl Schedule {
+    fn kind(&self) -> TaskKind {
+        match self {
+            Self::Once => TaskKind::Job,
+            Self::Every(_) => TaskKind::Periodic,
+            Self::OnDemand(_) => TaskKind::OnDemand,
+            #[cfg(feature = "cron")]
+            Self::Cron(_) => TaskKind::Cron,
+        }
+    }
+}
+
+struct Entry {
+    status: Arc<Mutex<TaskStatus>>,
+    token: CancellationToken,
+    trigger: Option<Arc<Notify>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+type ShutdownJob = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+struct Inner {
+    token: CancellationToken,
+    backoff: Mutex<(Duration, Duration)>,
+    entries: Mutex<IndexMap<String, Entry>>,
+    shutdown_jobs: Mutex<Vec<ShutdownJob>>,
+}
+
+/// Registry of named background tasks.
+///
+/// `Tasks` is cheap to clone, all clones share the same tasks.
+#[derive(Clone)]
+pub struct Tasks {
+    inner: Arc<Inner>,
+}
+impl Default for Tasks {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Debug for Tasks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tasks").field("tasks", &self.statuses()).finish()
+    }
+}
+impl Tasks {
+    /// Create new `Tasks`.
+    ///
+    /// Panicked jobs are restarted after 1 second by default, and the delay doubles up to 1 minute.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                token: CancellationToken::new(),
+                backoff: Mutex::new((Duration::from_secs(1), Duration::from_secs(60))),
+                entries: Mutex::new(IndexMap::new()),
+                shutdown_jobs: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Set the initial and the max backoff delay to restart panicked jobs.
+    ///
+    /// It only applies to tasks spawned afterwards.
+    #[inline]
+    pub fn restart_backoff(self, initial: Duration, max: Duration) -> Self {
+        *self.inner.backoff.lock() = (initial, max.max(initial));
+        self
+    }
+
+    /// Spawn a job which runs once, until it returns.
+    ///
+    /// A task with the same name is cancelled and replaced.
+    pub fn spawn<F, Fut>(&self, name: impl Into<String>, job: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_with(name.into(), Schedule::Once, job);
+    }
+
+    /// Spawn a job which runs immediately and then every `period`.
+    ///
+    /// Runs never overlap, a run which takes longer than `period` delays the next one.
+    pub fn spawn_periodic<F, Fut>(&self, name: impl Into<String>, period: Duration, job: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_with(name.into(), Schedule::Every(period), job);
+    }
+
+    /// Spawn a job which runs each time it is [triggered](Tasks::trigger).
+    ///
+    /// Triggers received while the job is running are coalesced into one more run.
+    pub fn spawn_on_demand<F, Fut>(&self, name: impl Into<String>, job: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_with(name.into(), Schedule::OnDemand(Arc::new(Notify::new())), job);
+    }
+
+    cfg_feature! {
+        #![feature = "cron"]
+        /// Spawn a job which runs on a cron schedule.
+        ///
+        /// A panicked run is not restarted, the job runs again at the next scheduled time.
+        pub fn spawn_cron<F, Fut>(&self, name: impl Into<String>, schedule: CronSchedule, job: F)
+        where
+            F: Fn() -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = ()> + Send + 'static,
+        {
+            self.spawn_with(name.into(), Schedule::Cron(schedule), job);
+        }
+    }
+
+    fn spawn_with<F, Fut>(&self, name: String, schedule: Schedule, job: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let token = self.inner.token.child_token();
+        let status = Arc::new(Mutex::new(TaskStatus::new(name.clone(), schedule.kind())));
+        let trigger = match &schedule {
+            Schedule::OnDemand(trigger) => Some(trigger.clone()),
+            _ => None,
+        };
+        let backoff = *self.inner.backoff.lock();
+        let handle = match schedule {
+            #[cfg(feature = "cron")]
+            Schedule::Cron(cron) => tokio::spawn(cron::run(cron, job, token.clone(), status.clone())),
+            schedule => tokio::spawn(run(schedule, job, token.clone(), status.clone(), backoff)),
+        };
+        let entry = Entry {
+            status,
+            token,
+            trigger,
+            handle: Some(handle),
+        };
+        if let Some(old) = self.inner.entries.lock().insert(name, entry) {
+            old.token.cancel();
+        }
+    }
+
+    /// Trigger an on-demand task.
+    ///
+    /// Returns `false` if there is no on-demand task with this name.
+    pub fn trigger(&self, name: &str) -> bool {
+        match self
+            .inner
+            .entries
+            .lock()
+            .get(name)
+            .and_then(|entry| entry.trigger.as_ref())
+        {
+            Some(trigger) => {
+                trigger.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel a task.
+    ///
+    /// Returns `false` if there is no task with this name.
+    pub fn cancel(&self, name: &str) -> bool {
+        match self.inner.entries.lock().get(name) {
+            Some(entry) => {
+                entry.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the status of a task.
+    pub fn status(&self, name: &str) -> Option<TaskStatus> {
+        self.inner
+            .entries
+            .lock()
+            .get(name)
+            .map(|entry| entry.status.lock().clone())
+    }
+
+    /// Get the status of all tasks, in the order they were spawned.
+    pub fn statuses(&self) -> Vec<TaskStatus> {
+        self.inner
+            .entries
+            .lock()
+            .values()
+            .map(|entry| entry.status.lock().clone())
+            .collect()
+    }
+
+    /// Register a job which runs once when the tasks are shut down.
+    ///
+    /// [`Server`](crate::Server) runs it after all connections are closed and deferred jobs finished.
+    pub fn on_shutdown<F, Fut>(&self, job: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.inner.shutdown_jobs.lock().push(Box::new(move || Box::pin(job())));
+    }
+
+    /// Cancel all tasks and wait for them to stop, then run the jobs registered with
+    /// [`on_shutdown`](Self::on_shutdown).
+    ///
+    /// Tasks spawned afterwards are stopped immediately.
+    pub async fn shutdown(&self) {
+        self.stop().await;
+        self.run_shutdown_jobs().await;
+    }
+
+    pub(crate) async fn stop(&self) {
+        self.inner.token.cancel();
+        let handles = self
+            .inner
+            .entries
+            .lock()
+            .values_mut()
+            .filter_map(|entry| entry.handle.take())
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.await.ok();
+        }
+    }
+
+    pub(crate) async fn run_shutdown_jobs(&self) {
+        let jobs = std::mem::take(&mut *self.inner.shutdown_jobs.lock());
+        for job in jobs {
+            if let Err(e) = tokio::spawn(job()).await {
+                tracing::error!(error = ?e, "shutdown job failed");
+            }
+        }
+    }
+
+    /// Create a handler which renders the status of all tasks as JSON.
+    #[inline]
+    pub fn inspector(&self) -> TasksInspector {
+        TasksInspector { tasks: self.clone() }
+    }
+}
+
+async fn run<F, Fut>(
+    schedule: Schedule,
+    job: F,
+    token: CancellationToken,
+    status: Arc<Mutex<TaskStatus>>,
+    (initial_backoff, max_backoff): (Duration, Duration),
+) where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let name = status.lock().name.clone();
+    let mut interval = match schedule {
+        Schedule::Every(period) => {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            Some(interval)
+        }
+        _ => None,
+    };
+    let mut backoff = initial_backoff;
+    loop {
+        let ready = async {
+            match (&schedule, interval.as_mut()) {
+                (Schedule::Every(_), Some(interval)) => {
+                    interval.tick().await;
+                }
+                (Schedule::OnDemand(trigger), _) => trigger.notified().await,
+                _ => {}
+            }
+        };
+        tokio::select! {
+            _ = ready => {}
+            _ = token.cancelled() => break,
+        }
+
+        status.lock().started();
+        let mut handle = tokio::spawn(job());
+        let result = tokio::select! {
+            result = &mut handle => result,
+            _ = token.cancelled() => {
+                handle.abort();
+                break;
+            }
+        };
+        match result {
+            Ok(()) => {
+                backoff = initial_backoff;
+                let mut status = status.lock();
+                status.last_finished_at = Some(now_millis());
+                if let Schedule::Once = schedule {
+                    status.state = TaskState::Finished;
+                    return;
+                }
+                status.state = TaskState::Idle;
+            }
+            Err(e) if e.is_panic() => {
+                let message = panic_message(e);
+                tracing::error!(task = %name, panic = %message, ?backoff, "background task panicked");
+                {
+                    let mut status = status.lock();
+                    status.state = TaskState::Restarting;
+                    status.panicked(message);
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = token.cancelled() => break,
+                }
+                backoff = (backoff * 2).min(max_backoff);
+                status.lock().state = TaskState::Idle;
+            }
+            Err(_) => break,
+        }
+    }
+    status.lock().state = TaskState::Stopped;
+}
+
+fn panic_message(e: JoinError) -> String {
+    let payload: Box<dyn Any + Send> = e.into_panic();
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Handler which renders the status of all [`Tasks`] as JSON.
+#[derive(Clone, Debug)]
+pub struct TasksInspector {
+    tasks: Tasks,
+}
+#[async_trait]
+impl Handler for TasksInspector {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        res.render(Json(self.tasks.statuses()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::test::{ResponseExt, TestClient};
+    use crate::{Router, Service};
+
+    fn tasks() -> Tasks {
+        Tasks::new().restart_backoff(Duration::from_millis(1), Duration::from_millis(10))
+    }
+
+    #[tokio::test]
+    async fn test_restart_on_panic() {
+        let tasks = tasks();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter2 = counter.clone();
+        tasks.spawn("flaky", move || {
+            let counter = counter2.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("boom");
+                }
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let status = tasks.status("flaky").unwrap();
+        assert_eq!(status.kind, TaskKind::Job);
+        assert_eq!(status.state, TaskState::Finished);
+        assert_eq!(status.runs, 3);
+        assert_eq!(status.panics, 2);
+        assert_eq!(status.last_panic.as_deref(), Some("boom"));
+    }
+
+    #[tokio::test]
+    async fn test_periodic_and_shutdown() {
+        let tasks = tasks();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter2 = counter.clone();
+        tasks.spawn_periodic("tick", Duration::from_millis(10), move || {
+            let counter = counter2.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        tasks.shutdown().await;
+        let count = counter.load(Ordering::SeqCst);
+        assert!(count >= 2);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(counter.load(Ordering::SeqCst), count);
+        assert_eq!(tasks.status("tick").unwrap().state, TaskState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_on_shutdown() {
+        let tasks = tasks();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter2 = counter.clone();
+        tasks.on_shutdown(move || async move {
+            counter2.fetch_add(1, Ordering::SeqCst);
+        });
+        tasks.on_shutdown(|| async { panic!("boom") });
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+        tasks.shutdown().await;
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        tasks.shutdown().await;
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_on_demand() {
+        let tasks = tasks();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter2 = counter.clone();
+        tasks.spawn_on_demand("reindex", move || {
+            let counter = counter2.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+
+        assert!(tasks.trigger("reindex"));
+        assert!(!tasks.trigger("missing"));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        assert!(tasks.cancel("reindex"));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(tasks.status("reindex").unwrap().state, TaskState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_inspector() {
+        let tasks = tasks();
+        tasks.spawn_on_demand("idle", || async {});
+        let service = Service::new(Router::new().get(tasks.inspector()));
+        let content = TestClient::get("http://127.0.0.1:5800/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(content.contains(r#""name":"idle","kind":"on_demand","state":"idle""#));
+    }
+}
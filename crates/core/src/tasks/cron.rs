@@ -0,0 +1,256 @@
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use parking_lot::Mutex;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+use super::{panic_message, TaskState, TaskStatus};
+use crate::Error;
+
+/// What to do when a cron run is due while the previous run is still running.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Overlap {
+    /// Skip the due run.
+    #[default]
+    Skip,
+    /// Start the due run after the running ones are finished.
+    Queue,
+    /// Start the due run immediately, beside the running ones.
+    Parallel,
+}
+
+/// Cron schedule of a background task.
+///
+/// Both the standard five fields expression (`min hour day month weekday`) and the expression with seconds
+/// (`sec min hour day month weekday [year]`) are supported. Times are evaluated in UTC unless a timezone is set.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::tasks::{CronSchedule, Overlap, Tz};
+///
+/// let schedule = CronSchedule::new("30 3 * * *")
+///     .unwrap()
+///     .timezone(Tz::Europe__Berlin)
+///     .overlap(Overlap::Queue);
+/// ```
+#[derive(Clone)]
+pub struct CronSchedule {
+    expr: String,
+    schedule: cron::Schedule,
+    timezone: Tz,
+    overlap: Overlap,
+}
+impl Debug for CronSchedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CronSchedule")
+            .field("expr", &self.expr)
+            .field("timezone", &self.timezone)
+            .field("overlap", &self.overlap)
+            .finish()
+    }
+}
+impl CronSchedule {
+    /// Create new `CronSchedule` from a cron expression.
+    pub fn new(expr: impl Into<String>) -> Result<Self, Error> {
+        let expr = expr.into();
+        let full_expr = if expr.split_whitespace().count() == 5 {
+            format!("0 {expr}")
+        } else {
+            expr.clone()
+        };
+        let schedule = cron::Schedule::from_str(&full_expr).map_err(Error::other)?;
+        Ok(Self {
+            expr,
+            schedule,
+            timezone: Tz::UTC,
+            overlap: Overlap::default(),
+        })
+    }
+
+    /// Set the timezone the expression is evaluated in.
+    #[inline]
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Set the overlap policy, default is [`Overlap::Skip`].
+    #[inline]
+    pub fn overlap(mut self, overlap: Overlap) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Get the cron expression.
+    #[inline]
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// Get the next time the schedule fires after `time`.
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.schedule
+            .after(&time.with_timezone(&self.timezone))
+            .next()
+            .map(|next| next.with_timezone(&Utc))
+    }
+}
+
+pub(super) async fn run<F, Fut>(
+    schedule: CronSchedule,
+    job: F,
+    token: CancellationToken,
+    status: Arc<Mutex<TaskStatus>>,
+) where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    run_with_clock(schedule, job, token, status, Utc::now).await
+}
+
+/// Runs the schedule with the current time given by `now`, tests pass a clock following the paused tokio time.
+async fn run_with_clock<F, Fut>(
+    schedule: CronSchedule,
+    job: F,
+    token: CancellationToken,
+    status: Arc<Mutex<TaskStatus>>,
+    now: impl Fn() -> DateTime<Utc>,
+) where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let name = status.lock().name.clone();
+    let mut running = JoinSet::new();
+    let mut queued = 0usize;
+    let mut next = schedule.next_after(now());
+    loop {
+        status.lock().next_run_at = next.map(|next| next.timestamp_millis() as u64);
+        let wait = match next {
+            Some(next) => (next - now()).to_std().unwrap_or(Duration::ZERO),
+            None if running.is_empty() && queued == 0 => {
+                status.lock().state = TaskState::Finished;
+                return;
+            }
+            None => Duration::MAX,
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(wait), if next.is_some() => {
+                next = next.and_then(|next| schedule.next_after(next));
+                if running.is_empty() || schedule.overlap == Overlap::Parallel {
+                    status.lock().started();
+                    running.spawn(job());
+                } else if schedule.overlap == Overlap::Queue {
+                    queued += 1;
+                } else {
+                    tracing::debug!(task = %name, "cron run skipped, previous run is still running");
+                    status.lock().skipped += 1;
+                }
+            }
+            Some(result) = running.join_next() => {
+                {
+                    let mut status = status.lock();
+                    match result {
+                        Err(e) if e.is_panic() => {
+                            let message = panic_message(e);
+                            tracing::error!(task = %name, panic = %message, "background task panicked");
+                            status.panicked(message);
+                        }
+                        _ => status.last_finished_at = Some(super::now_millis()),
+                    }
+                    status.state = TaskState::Idle;
+                }
+                if running.is_empty() && queued > 0 {
+                    queued -= 1;
+                    status.lock().started();
+                    running.spawn(job());
+                } else if !running.is_empty() {
+                    status.lock().state = TaskState::Running;
+                }
+            }
+            _ = token.cancelled() => {
+                running.abort_all();
+                break;
+            }
+        }
+    }
+    let mut status = status.lock();
+    status.state = TaskState::Stopped;
+    status.next_run_at = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::tasks::TaskKind;
+
+    #[test]
+    fn test_next_after() {
+        let schedule = CronSchedule::new("30 3 * * *").unwrap();
+        let time = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_after(time),
+            Some(Utc.with_ymd_and_hms(2024, 6, 1, 3, 30, 0).unwrap())
+        );
+
+        let schedule = schedule.timezone(Tz::Asia__Shanghai);
+        assert_eq!(
+            schedule.next_after(time),
+            Some(Utc.with_ymd_and_hms(2024, 6, 1, 19, 30, 0).unwrap())
+        );
+
+        assert!(CronSchedule::new("not a cron").is_err());
+    }
+
+    async fn count_runs(overlap: Overlap) -> (usize, TaskStatus) {
+        // The clock starts right after a whole second and follows the paused tokio time, so runs fire at 0.9s, 1.9s
+        // and 2.9s.
+        let origin = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap() + chrono::Duration::milliseconds(100);
+        let start = tokio::time::Instant::now();
+        let now = move || origin + chrono::Duration::from_std(start.elapsed()).unwrap();
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter2 = counter.clone();
+        let schedule = CronSchedule::new("* * * * * *").unwrap().overlap(overlap);
+        let job = move || {
+            let counter = counter2.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(2200)).await;
+            }
+        };
+        let token = CancellationToken::new();
+        let status = Arc::new(Mutex::new(TaskStatus::new("slow".into(), TaskKind::Cron)));
+        let handle = tokio::spawn(run_with_clock(schedule, job, token.clone(), status.clone(), now));
+        tokio::time::sleep(Duration::from_millis(3400)).await;
+        let status = status.lock().clone();
+        token.cancel();
+        handle.await.unwrap();
+        (counter.load(Ordering::SeqCst), status)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_overlap() {
+        let (skip, status) = count_runs(Overlap::Skip).await;
+        assert_eq!(status.kind, TaskKind::Cron);
+        assert_eq!(skip, 1);
+        assert_eq!(status.skipped, 2);
+
+        let (queue, _) = count_runs(Overlap::Queue).await;
+        assert_eq!(queue, 2);
+
+        let (parallel, status) = count_runs(Overlap::Parallel).await;
+        assert_eq!(parallel, 3);
+        assert_eq!(status.skipped, 0);
+    }
+}
@@ -1,13 +1,145 @@
-use http::Method;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
 
-use super::request::RequestBuilder;
+#[cfg(feature = "cookie")]
+use cookie::{Cookie, CookieJar};
+use http::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION};
+use http::{Method, StatusCode};
+#[cfg(feature = "cookie")]
+use parking_lot::Mutex;
+use url::Url;
+
+use super::request::{RequestBuilder, SendTarget};
+use crate::http::body::ReqBody;
+use crate::{Request, Response, Service};
 
 /// A type that can carry settings over multiple requests. The settings applied to the
 /// `TestClient` are applied to every request created from this `TestClient`.
-#[derive(Debug, Default)]
-pub struct TestClient;
+///
+/// Requests created by the associated functions such as [`TestClient::get`] can be sent to a `TestClient`
+/// instance, which drives the wrapped [`Service`] in-process, persists cookies in a cookie jar across
+/// requests and optionally follows redirects.
+///
+/// `TestClient` was a unit struct before, values are now created with [`TestClient::new`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::test::{ResponseExt, TestClient};
+///
+/// #[handler]
+/// async fn login(res: &mut Response) {
+///     res.add_cookie(salvo_core::http::cookie::Cookie::new("session", "abc"));
+///     res.render(Redirect::found("/me"));
+/// }
+/// #[handler]
+/// async fn me(req: &mut Request) -> String {
+///     req.cookie("session").map(|c| c.value().to_owned()).unwrap_or_default()
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let router = Router::new()
+///         .push(Router::with_path("login").post(login))
+///         .push(Router::with_path("me").get(me));
+///     let client = TestClient::new(router).follow_redirects(5);
+///     let content = TestClient::post("http://127.0.0.1:5800/login")
+///         .send(&client)
+///         .await
+///         .take_string()
+///         .await
+///         .unwrap();
+///     assert_eq!(content, "abc");
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TestClient {
+    service: Arc<Service>,
+    max_redirects: usize,
+    #[cfg(feature = "cookie")]
+    cookies: Arc<Mutex<CookieJar>>,
+}
+
+impl Debug for TestClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestClient")
+            .field("max_redirects", &self.max_redirects)
+            .finish()
+    }
+}
 
 impl TestClient {
+    /// Create a new `TestClient` which sends requests to the given service in-process.
+    pub fn new(service: impl Into<Service>) -> Self {
+        Self {
+            service: Arc::new(service.into()),
+            max_redirects: 0,
+            #[cfg(feature = "cookie")]
+            cookies: Default::default(),
+        }
+    }
+
+    /// Follow at most `max_redirects` redirects, default is `0` which does not follow redirects.
+    pub fn follow_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    cfg_feature! {
+        #![feature = "cookie"]
+        /// Get the cookies stored in the cookie jar of this client.
+        pub fn cookies(&self) -> CookieJar {
+            self.cookies.lock().clone()
+        }
+        /// Get a cookie stored in the cookie jar of this client by name.
+        pub fn cookie(&self, name: impl AsRef<str>) -> Option<Cookie<'static>> {
+            self.cookies.lock().get(name.as_ref()).cloned()
+        }
+        /// Add a cookie to the cookie jar, it will be sent with every request.
+        pub fn add_cookie(&self, cookie: Cookie<'static>) -> &Self {
+            self.cookies.lock().add_original(cookie);
+            self
+        }
+        /// Remove all cookies from the cookie jar.
+        pub fn clear_cookies(&self) -> &Self {
+            *self.cookies.lock() = CookieJar::new();
+            self
+        }
+
+        fn apply_cookies(&self, req: &mut Request) {
+            let jar = self.cookies.lock();
+            if jar.iter().next().is_none() {
+                return;
+            }
+            let cookies = req.cookies_mut();
+            for cookie in jar.iter() {
+                if cookies.get(cookie.name()).is_none() {
+                    cookies.add_original(cookie.clone());
+                }
+            }
+            let value = cookies
+                .iter()
+                .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+                .collect::<Vec<_>>()
+                .join("; ");
+            if let Ok(value) = value.parse() {
+                req.headers_mut().insert(COOKIE, value);
+            }
+        }
+
+        fn store_cookies(&self, res: &Response) {
+            let mut jar = self.cookies.lock();
+            for cookie in res.cookies.delta() {
+                if cookie.max_age().is_some_and(|max_age| max_age.is_zero()) {
+                    jar.remove(Cookie::from(cookie.name().to_owned()));
+                } else {
+                    jar.add_original(cookie.clone().into_owned());
+                }
+            }
+        }
+    }
+
     /// Create a new `RequestBuilder` with the GET method and this TestClient's settings applied on it.
     pub fn get(url: impl AsRef<str>) -> RequestBuilder {
         RequestBuilder::new(url, Method::GET)
@@ -48,3 +180,167 @@ impl TestClient {
         RequestBuilder::new(url, Method::TRACE)
     }
 }
+
+impl SendTarget for &TestClient {
+    async fn call(self, req: Request) -> Response {
+        let mut req = req;
+        let mut redirects = 0;
+        loop {
+            // Keep a copy of the body, it will be sent again for `307` and `308` redirects.
+            let body = match req.take_body() {
+                ReqBody::Once(bytes) => {
+                    req.replace_body(ReqBody::Once(bytes.clone()));
+                    Some(bytes)
+                }
+                body => {
+                    req.replace_body(body);
+                    None
+                }
+            };
+            let method = req.method().clone();
+            let url = Url::parse(&req.uri().to_string()).ok();
+            let mut headers = req.headers().clone();
+
+            #[cfg(feature = "cookie")]
+            self.apply_cookies(&mut req);
+            let res = self.service.handle(req).await;
+            #[cfg(feature = "cookie")]
+            self.store_cookies(&res);
+
+            let status = res.status_code.unwrap_or(StatusCode::OK);
+            if !status.is_redirection() || redirects >= self.max_redirects {
+                return res;
+            }
+            let origin = url.as_ref().map(|url| url.origin());
+            let location = res.headers().get(LOCATION).and_then(|value| value.to_str().ok());
+            let Some(next_url) = url.zip(location).and_then(|(url, location)| url.join(location).ok()) else {
+                return res;
+            };
+            redirects += 1;
+            let (method, body) = match status {
+                StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => (method, body),
+                _ if method == Method::HEAD => (method, None),
+                _ => (Method::GET, None),
+            };
+            headers.remove(COOKIE);
+            if body.is_none() {
+                headers.remove(CONTENT_TYPE);
+                headers.remove(CONTENT_LENGTH);
+            }
+            if origin != Some(next_url.origin()) {
+                headers.remove(AUTHORIZATION);
+            }
+            let mut builder = RequestBuilder::new(next_url, method);
+            builder.headers = headers;
+            if let Some(body) = body {
+                builder = builder.body(body);
+            }
+            req = builder.build();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cookie"))]
+mod tests {
+    use crate::prelude::*;
+    use crate::test::{MultipartForm, ResponseExt, TestClient};
+
+    #[handler]
+    async fn login(res: &mut Response) {
+        res.add_cookie(cookie::Cookie::new("session", "abc"));
+        res.render(Redirect::found("/me"));
+    }
+    #[handler]
+    async fn logout(res: &mut Response) {
+        res.remove_cookie("session");
+    }
+    #[handler]
+    async fn me(req: &mut Request) -> String {
+        req.cookie("session")
+            .map(|cookie| cookie.value().to_owned())
+            .unwrap_or_else(|| "anonymous".into())
+    }
+    #[handler]
+    async fn moved(res: &mut Response) {
+        res.render(Redirect::temporary("/echo"));
+    }
+    #[handler]
+    async fn echo(req: &mut Request) -> String {
+        let method = req.method().clone();
+        let body = req.payload().await.map(|body| body.to_vec()).unwrap_or_default();
+        format!("{method} {}", String::from_utf8_lossy(&body))
+    }
+
+    fn client() -> TestClient {
+        let router = Router::new()
+            .push(Router::with_path("login").post(login))
+            .push(Router::with_path("logout").post(logout))
+            .push(Router::with_path("me").get(me))
+            .push(Router::with_path("moved").post(moved))
+            .push(Router::with_path("echo").post(echo));
+        TestClient::new(router)
+    }
+
+    #[tokio::test]
+    async fn test_cookie_jar() {
+        let client = client();
+        let res = TestClient::post("http://127.0.0.1:5800/login").send(&client).await;
+        res.assert_status(StatusCode::FOUND);
+        assert_eq!(client.cookie("session").unwrap().value(), "abc");
+
+        let content = TestClient::get("http://127.0.0.1:5800/me")
+            .send(&client)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "abc");
+
+        TestClient::post("http://127.0.0.1:5800/logout").send(&client).await;
+        assert!(client.cookie("session").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_follow_redirects() {
+        let client = client().follow_redirects(3);
+        let content = TestClient::post("http://127.0.0.1:5800/login")
+            .send(&client)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "abc");
+
+        let content = TestClient::post("http://127.0.0.1:5800/moved")
+            .text("hello")
+            .send(&client)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "POST hello");
+    }
+
+    #[tokio::test]
+    async fn test_multipart() {
+        #[handler]
+        async fn upload(req: &mut Request) -> String {
+            let title = req.form::<String>("title").await.unwrap_or_default();
+            let file = req.file("file").await.unwrap();
+            format!("{title}: {}", file.name().unwrap_or_default())
+        }
+
+        let form =
+            MultipartForm::new()
+                .text("title", "avatar")
+                .file("file", "avatar.png", mime::IMAGE_PNG, vec![0u8; 16]);
+        let content = TestClient::post("http://127.0.0.1:5800/upload")
+            .multipart(form)
+            .send(upload)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "avatar: avatar.png");
+    }
+}
@@ -0,0 +1,158 @@
+use std::any::Any;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use crate::routing::FlowCtrl;
+use crate::{Depot, Handler, Request, Response};
+
+/// Calls handlers directly, without routers and services, so single handlers can be unit tested.
+///
+/// The [`Depot`] is kept between calls, values can be injected before calling the handler and inspected
+/// after the call.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::test::{HandlerTester, ResponseExt, TestClient};
+///
+/// struct Config {
+///     greeting: &'static str,
+/// }
+///
+/// #[handler]
+/// async fn hello(req: &mut Request, depot: &mut Depot) -> String {
+///     let config = depot.obtain::<Config>().unwrap();
+///     format!("{}, {}!", config.greeting, req.param::<String>("name").unwrap())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut tester = HandlerTester::new(hello).inject(Config { greeting: "Hello" });
+///     let mut res = tester
+///         .call(TestClient::get("http://127.0.0.1:5800/hello/world").param("name", "world"))
+///         .await;
+///     assert_eq!(res.take_string().await.unwrap(), "Hello, world!");
+/// }
+/// ```
+pub struct HandlerTester {
+    handlers: Vec<Arc<dyn Handler>>,
+    depot: Depot,
+    is_ceased: bool,
+}
+
+impl Debug for HandlerTester {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandlerTester")
+            .field("handlers", &self.handlers.len())
+            .field("is_ceased", &self.is_ceased)
+            .finish()
+    }
+}
+
+impl HandlerTester {
+    /// Create a new `HandlerTester` which calls `handler`.
+    pub fn new(handler: impl Handler) -> Self {
+        Self {
+            handlers: vec![Arc::new(handler)],
+            depot: Depot::new(),
+            is_ceased: false,
+        }
+    }
+
+    /// Add a handler which is called after the previous handlers, such as the goal of a middleware.
+    pub fn then(mut self, handler: impl Handler) -> Self {
+        self.handlers.push(Arc::new(handler));
+        self
+    }
+
+    /// Inject a value into the depot.
+    pub fn inject<V: Any + Send + Sync>(mut self, value: V) -> Self {
+        self.depot.inject(value);
+        self
+    }
+
+    /// Insert a key-value pair into the depot.
+    pub fn insert<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Any + Send + Sync,
+    {
+        self.depot.insert(key, value);
+        self
+    }
+
+    /// Get reference to the depot.
+    pub fn depot(&self) -> &Depot {
+        &self.depot
+    }
+
+    /// Get mutable reference to the depot.
+    pub fn depot_mut(&mut self) -> &mut Depot {
+        &mut self.depot
+    }
+
+    /// Check whether the flow is ceased by the handlers in the last call.
+    pub fn is_ceased(&self) -> bool {
+        self.is_ceased
+    }
+
+    /// Call the handlers with the request and returns the response.
+    pub async fn call(&mut self, req: impl Into<Request>) -> Response {
+        let mut req = req.into();
+        #[cfg(not(feature = "cookie"))]
+        let mut res = Response::new();
+        #[cfg(feature = "cookie")]
+        let mut res = Response::with_cookies(req.cookies().clone());
+        let mut ctrl = FlowCtrl::new(self.handlers.clone());
+        ctrl.call_next(&mut req, &mut self.depot, &mut res).await;
+        self.is_ceased = ctrl.is_ceased();
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::test::{HandlerTester, ResponseExt, TestClient};
+
+    #[tokio::test]
+    async fn test_handler_tester() {
+        #[handler]
+        async fn auth(req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+            match req.header::<String>("authorization") {
+                Some(token) if token == depot.get::<&str>("token").copied().unwrap_or_default() => {
+                    depot.insert("user", "admin");
+                }
+                _ => {
+                    res.status_code(StatusCode::UNAUTHORIZED);
+                    ctrl.cease();
+                }
+            }
+        }
+        #[handler]
+        async fn show(req: &mut Request, depot: &mut Depot) -> String {
+            format!(
+                "{} {}",
+                depot.get::<&str>("user").copied().unwrap_or_default(),
+                req.param::<u64>("id").unwrap_or_default()
+            )
+        }
+
+        let mut tester = HandlerTester::new(auth).then(show).insert("token", "secret");
+        let res = tester.call(TestClient::get("http://127.0.0.1:5800/users/7")).await;
+        assert_eq!(res.status_code, Some(StatusCode::UNAUTHORIZED));
+        assert!(tester.is_ceased());
+
+        let mut res = tester
+            .call(
+                TestClient::get("http://127.0.0.1:5800/users/7")
+                    .add_header("authorization", "secret", true)
+                    .param("id", 7),
+            )
+            .await;
+        assert!(!tester.is_ceased());
+        assert_eq!(res.take_string().await.unwrap(), "admin 7");
+        assert_eq!(tester.depot().get::<&str>("user").copied().unwrap(), "admin");
+    }
+}
@@ -41,8 +41,12 @@
 //! ```
 
 mod client;
+mod handler;
 mod request;
 mod response;
+mod snapshot;
 pub use client::TestClient;
-pub use request::{RequestBuilder, SendTarget};
+pub use handler::HandlerTester;
+pub use request::{MultipartForm, RequestBuilder, SendTarget};
 pub use response::ResponseExt;
+pub use snapshot::Snapshot;
@@ -7,8 +7,11 @@ use std::sync::Arc;
 use base64::engine::{general_purpose, Engine};
 use http::header::{self, HeaderMap, HeaderValue, IntoHeaderName};
 use http::uri::Scheme;
+use indexmap::IndexMap;
 use url::Url;
 
+use super::MultipartForm;
+
 use crate::http::body::ReqBody;
 use crate::http::Method;
 use crate::routing::{FlowCtrl, Router};
@@ -23,8 +26,8 @@ use crate::{Depot, Error, Handler, Request, Response, Service};
 pub struct RequestBuilder {
     url: Url,
     method: Method,
-    headers: HeaderMap,
-    // params: HashMap<String, String>,
+    pub(crate) headers: HeaderMap,
+    params: IndexMap<String, String>,
     body: ReqBody,
 }
 
@@ -42,7 +45,7 @@ impl RequestBuilder {
             url,
             method,
             headers: HeaderMap::new(),
-            // params: HeaderMap::new(),
+            params: IndexMap::new(),
             body: ReqBody::None,
         }
     }
@@ -83,30 +86,31 @@ impl RequestBuilder {
         self
     }
 
-    // /// Associate a url param to the given value.
-    // pub fn param<K, V>(mut self, key: K, value: V) -> Self
-    // where
-    //     K: AsRef<str>,
-    //     V: ToString,
-    // {
-    //     self.params.insert(key.as_ref(), &value.to_string());
-    //     self
-    // }
+    /// Associate a url param to the given value.
+    ///
+    /// Url params are normally set by the router, this is useful when a handler is called directly,
+    /// such as with [`HandlerTester`](crate::test::HandlerTester).
+    pub fn param<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: ToString,
+    {
+        self.params.insert(key.into(), value.to_string());
+        self
+    }
 
-    // /// Associated a list of url params.
-    // pub fn params<P, K, V>(mut self, pairs: P) -> Self
-    // where
-    //     P: IntoIterator,
-    //     P::Item: Borrow<(K, V)>,
-    //     K: AsRef<str>,
-    //     V: ToString,
-    // {
-    //     for pair in pairs.into_iter() {
-    //         let (key, value) = pair.borrow();
-    //         self.params.insert(key.as_ref(), &value.to_string());
-    //     }
-    //     self
-    // }
+    /// Associated a list of url params.
+    pub fn params<P, K, V>(mut self, pairs: P) -> Self
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: ToString,
+    {
+        for (key, value) in pairs.into_iter() {
+            self.params.insert(key.into(), value.to_string());
+        }
+        self
+    }
 
     /// Enable HTTP basic authentication.
     pub fn basic_auth(self, username: impl std::fmt::Display, password: Option<impl std::fmt::Display>) -> Self {
@@ -190,6 +194,14 @@ impl RequestBuilder {
             .or_insert(HeaderValue::from_static("application/x-www-form-urlencoded"));
         self.body(value.into())
     }
+    /// Sets the body of this request to be the given multipart form.
+    ///
+    /// The `Content-Type` header is always set to `multipart/form-data` with the boundary of the form.
+    pub fn multipart(mut self, form: MultipartForm) -> Self {
+        let content_type = HeaderValue::from_str(&form.content_type()).expect("invalid multipart boundary");
+        self.headers.insert(header::CONTENT_TYPE, content_type);
+        self.body(form.to_bytes())
+    }
     /// Modify a header for this response.
     ///
     /// When `overwrite` is set to `true`, If the header is already present, the value will be replaced.
@@ -212,10 +224,13 @@ impl RequestBuilder {
     }
 
     /// Build final request.
-    pub fn build(self) -> Request {
+    pub fn build(mut self) -> Request {
+        let params = std::mem::take(&mut self.params);
         let req = self.build_hyper();
         let scheme = req.uri().scheme().cloned().unwrap_or(Scheme::HTTP);
-        Request::from_hyper(req, scheme)
+        let mut req = Request::from_hyper(req, scheme);
+        req.params = params;
+        req
     }
 
     /// Build hyper request.
@@ -225,6 +240,7 @@ impl RequestBuilder {
             method,
             headers,
             body,
+            ..
         } = self;
         let mut req = hyper::Request::builder().method(method).uri(url.to_string());
         (*req.headers_mut().expect("`headers_mut` returns `None`")) = headers;
@@ -251,6 +267,13 @@ impl RequestBuilder {
     }
 }
 
+impl From<RequestBuilder> for Request {
+    #[inline]
+    fn from(builder: RequestBuilder) -> Self {
+        builder.build()
+    }
+}
+
 /// Trait for sending request to target, such as [`Router`], [`Service`], [`Handler`] for test usage.
 pub trait SendTarget {
     /// Send request to target, such as [`Router`], [`Service`], [`Handler`].
@@ -284,7 +307,7 @@ where
         #[cfg(not(feature = "cookie"))]
         let mut res = Response::new();
         #[cfg(feature = "cookie")]
-        let mut res = Response::with_cookies(req.cookies.clone());
+        let mut res = Response::with_cookies(req.cookies().clone());
         let mut ctrl = FlowCtrl::new(vec![self.clone()]);
         self.handle(&mut req, &mut depot, &mut res, &mut ctrl).await;
         res
@@ -1,3 +1,5 @@
-mod builder;
-
-pub use builder::{RequestBuilder, SendTarget};
+mod builder;
+mod multipart;
+
+pub use builder::{RequestBuilder, SendTarget};
+pub use multipart::MultipartForm;
@@ -0,0 +1,116 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use mime::Mime;
+
+/// Builder of a `multipart/form-data` body, used by [`RequestBuilder::multipart`].
+///
+/// [`RequestBuilder::multipart`]: crate::test::RequestBuilder::multipart
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::http::mime;
+/// use salvo_core::test::{MultipartForm, TestClient};
+///
+/// let form = MultipartForm::new()
+///     .text("title", "avatar")
+///     .file("file", "avatar.png", mime::IMAGE_PNG, vec![0u8; 16]);
+/// let req = TestClient::post("http://127.0.0.1:5800/upload").multipart(form).build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultipartForm {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug)]
+struct Part {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<Mime>,
+    data: Bytes,
+}
+
+impl Default for MultipartForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MultipartForm {
+    /// Create a new empty `MultipartForm` with a random boundary.
+    pub fn new() -> Self {
+        Self {
+            boundary: format!("salvo-test-boundary-{:016x}", rand::random::<u64>()),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Add a text field.
+    pub fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: None,
+            content_type: None,
+            data: Bytes::from(value.into()),
+        });
+        self
+    }
+
+    /// Add a file field.
+    pub fn file(
+        mut self,
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        content_type: Mime,
+        data: impl Into<Bytes>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file_name: Some(file_name.into()),
+            content_type: Some(content_type),
+            data: data.into(),
+        });
+        self
+    }
+
+    /// Get the boundary which separates the parts.
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Get the value of the `Content-Type` header for this form.
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Encode the form into body bytes.
+    pub fn to_bytes(&self) -> Bytes {
+        let mut body = BytesMut::new();
+        for part in &self.parts {
+            body.put_slice(format!("--{}\r\n", self.boundary).as_bytes());
+            let name = escape_quoted(&part.name);
+            match &part.file_name {
+                Some(file_name) => body.put_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{name}\"; filename=\"{}\"\r\n",
+                        escape_quoted(file_name)
+                    )
+                    .as_bytes(),
+                ),
+                None => body.put_slice(format!("Content-Disposition: form-data; name=\"{name}\"\r\n").as_bytes()),
+            }
+            if let Some(content_type) = &part.content_type {
+                body.put_slice(format!("Content-Type: {content_type}\r\n").as_bytes());
+            }
+            body.put_slice(b"\r\n");
+            body.put_slice(&part.data);
+            body.put_slice(b"\r\n");
+        }
+        body.put_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        body.freeze()
+    }
+}
+
+fn escape_quoted(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -8,12 +8,15 @@ use flate2::write::{GzDecoder, ZlibDecoder};
 use http_body_util::BodyExt;
 use mime::Mime;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
 use tokio::io::{Error as IoError, ErrorKind};
 use zstd::stream::write::Decoder as ZstdDecoder;
 
 use crate::catcher::status_error_bytes;
 use crate::http::header::{self, CONTENT_ENCODING};
 use crate::http::response::{ResBody, Response};
+use crate::http::StatusCode;
 use crate::Error;
 
 struct Writer {
@@ -58,6 +61,26 @@ pub trait ResponseExt {
     ) -> impl Future<Output = crate::Result<String>>;
     /// Take all body bytes. If body is none, it will creates and returns a new [`Bytes`].
     fn take_bytes(&mut self, content_type: Option<&Mime>) -> impl Future<Output = crate::Result<Bytes>> + Send;
+
+    /// Assert the status code of the response, a response without status code is treated as `200 OK`.
+    ///
+    /// # Panics
+    /// Panics if the status code is not equal to `status`.
+    #[track_caller]
+    fn assert_status(&self, status: StatusCode) -> &Self;
+    /// Assert the body of the response is equal to the JSON representation of `expected`.
+    ///
+    /// The body is kept in the response, so it can be read again.
+    ///
+    /// # Panics
+    /// Panics if the body is not valid JSON or not equal to `expected`.
+    fn assert_json_eq<T>(&mut self, expected: &T) -> impl Future<Output = ()> + Send
+    where
+        T: Serialize + Sync + ?Sized;
+    /// Get the value at `path` in the JSON body, such as `$.data.items[0].name` or `data.items.0.name`.
+    ///
+    /// The body is kept in the response, so this function can be called multiple times.
+    fn json_path(&mut self, path: &str) -> impl Future<Output = crate::Result<Value>> + Send;
 }
 
 impl ResponseExt for Response {
@@ -144,4 +167,85 @@ impl ResponseExt for Response {
         };
         Ok(bytes)
     }
+
+    fn assert_status(&self, status: StatusCode) -> &Self {
+        let actual = self.status_code.unwrap_or(StatusCode::OK);
+        assert_eq!(actual, status, "unexpected response status");
+        self
+    }
+    async fn assert_json_eq<T>(&mut self, expected: &T)
+    where
+        T: Serialize + Sync + ?Sized,
+    {
+        let actual = json_value(self).await.expect("response body is not valid json");
+        let expected = serde_json::to_value(expected).expect("failed to serialize expected value");
+        assert!(
+            actual == expected,
+            "response json is not equal to expected value\n  actual: {actual}\nexpected: {expected}"
+        );
+    }
+    async fn json_path(&mut self, path: &str) -> crate::Result<Value> {
+        let value = json_value(self).await?;
+        find_json_path(&value, path)
+            .cloned()
+            .ok_or_else(|| Error::other(format!("json path `{path}` does not exist")))
+    }
+}
+
+/// Read the body as JSON and put the body bytes back to the response.
+async fn json_value(res: &mut Response) -> crate::Result<Value> {
+    let bytes = res.take_bytes(Some(&mime::APPLICATION_JSON)).await?;
+    res.replace_body(ResBody::Once(bytes.clone()));
+    serde_json::from_slice(&bytes).map_err(Error::SerdeJson)
+}
+
+fn find_json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let mut value = value;
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        let (key, mut indexes) = match segment.find('[') {
+            Some(index) => segment.split_at(index),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            value = match value {
+                Value::Array(items) => items.get(key.parse::<usize>().ok()?)?,
+                _ => value.get(key)?,
+            };
+        }
+        while let Some(rest) = indexes.strip_prefix('[') {
+            let (index, rest) = rest.split_once(']')?;
+            value = value.get(index.parse::<usize>().ok()?)?;
+            indexes = rest;
+        }
+        if !indexes.is_empty() {
+            return None;
+        }
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::TestClient;
+
+    #[tokio::test]
+    async fn test_json_assertions() {
+        #[handler]
+        async fn pets(res: &mut Response) {
+            res.render(Json(json!({"data": {"items": [{"name": "Tom"}, {"name": "Jerry"}]}})));
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/").send(pets).await;
+        res.assert_status(StatusCode::OK);
+        assert_eq!(res.json_path("$.data.items[1].name").await.unwrap(), "Jerry");
+        assert_eq!(res.json_path("data.items.0.name").await.unwrap(), "Tom");
+        assert!(res.json_path("$.data.missing").await.is_err());
+        res.assert_json_eq(&json!({"data": {"items": [{"name": "Tom"}, {"name": "Jerry"}]}}))
+            .await;
+    }
 }
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::ResponseExt;
+use crate::http::header::{HeaderName, CONTENT_TYPE, DATE};
+use crate::http::response::{ResBody, Response};
+use crate::http::StatusCode;
+
+/// Normalizes a [`Response`] into a stable text form for snapshot assertions.
+///
+/// The rendered text contains the status line, the headers sorted by name and the body. JSON bodies are
+/// pretty printed with sorted keys. Values which change between runs are replaced by placeholders: the
+/// `date` and `x-request-id` headers are redacted by default, more headers, JSON keys and text patterns can be
+/// redacted with the builder functions.
+///
+/// The output is a plain `String`, so it can be used with any snapshot library, such as
+/// `insta::assert_snapshot!`.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::test::{Snapshot, TestClient};
+///
+/// #[handler]
+/// async fn hello(res: &mut Response) {
+///     res.render(Json(serde_json::json!({"name": "salvo", "created_at": "2024-01-01T00:00:00Z"})));
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut res = TestClient::get("http://127.0.0.1:5800/").send(hello).await;
+///     let snapshot = Snapshot::new().redact_json_key("created_at").render(&mut res).await.unwrap();
+///     assert_eq!(
+///         snapshot,
+///         "HTTP 200 OK\ncontent-type: application/json; charset=utf-8\n\n{\n  \"created_at\": \"[redacted]\",\n  \"name\": \"salvo\"\n}\n"
+///     );
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    headers: Vec<HeaderName>,
+    json_keys: Vec<String>,
+    patterns: Vec<(Regex, String)>,
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Snapshot {
+    /// Create a new `Snapshot` which redacts the `date` and `x-request-id` headers.
+    pub fn new() -> Self {
+        Self {
+            headers: vec![DATE, HeaderName::from_static("x-request-id")],
+            json_keys: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Redact the value of the header.
+    pub fn redact_header(mut self, name: HeaderName) -> Self {
+        self.headers.push(name);
+        self
+    }
+
+    /// Redact the value of all object fields named `key` in JSON bodies.
+    pub fn redact_json_key(mut self, key: impl Into<String>) -> Self {
+        self.json_keys.push(key.into());
+        self
+    }
+
+    /// Replace all matches of the regular expression in header values and body with `replacement`.
+    ///
+    /// # Panics
+    /// Panics if the pattern is not a valid regular expression.
+    pub fn redact_pattern(mut self, pattern: &str, replacement: impl Into<String>) -> Self {
+        let regex = Regex::new(pattern).expect("invalid redact pattern");
+        self.patterns.push((regex, replacement.into()));
+        self
+    }
+
+    /// Replace RFC 3339 date times, such as `2024-01-01T00:00:00Z`, with `[datetime]`.
+    pub fn redact_datetimes(self) -> Self {
+        self.redact_pattern(
+            r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?",
+            "[datetime]",
+        )
+    }
+
+    /// Replace UUIDs with `[uuid]`.
+    pub fn redact_uuids(self) -> Self {
+        self.redact_pattern(
+            r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+            "[uuid]",
+        )
+    }
+
+    /// Render the response, the body is kept in the response.
+    pub async fn render(&self, res: &mut Response) -> crate::Result<String> {
+        let mut out = String::new();
+        let status = res.status_code.unwrap_or(StatusCode::OK);
+        let _ = writeln!(out, "HTTP {status}");
+
+        let mut headers = BTreeMap::new();
+        for (name, value) in res.headers() {
+            let value = if self.headers.contains(name) {
+                format!("[{}]", name.as_str())
+            } else {
+                self.apply_patterns(&String::from_utf8_lossy(value.as_bytes()))
+            };
+            headers
+                .entry(name.as_str().to_owned())
+                .or_insert_with(Vec::new)
+                .push(value);
+        }
+        for (name, values) in headers {
+            for value in values {
+                let _ = writeln!(out, "{name}: {value}");
+            }
+        }
+
+        let bytes = res.take_bytes(None).await?;
+        res.replace_body(ResBody::Once(bytes.clone()));
+        if bytes.is_empty() {
+            return Ok(out);
+        }
+        out.push('\n');
+        let is_json = res
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("json"));
+        match std::str::from_utf8(&bytes) {
+            Ok(text) if is_json => match serde_json::from_str::<Value>(text) {
+                Ok(value) => {
+                    let value = self.normalize_json(value);
+                    let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+                    out.push_str(&self.apply_patterns(&text));
+                    out.push('\n');
+                }
+                Err(_) => out.push_str(&self.apply_patterns(text)),
+            },
+            Ok(text) => out.push_str(&self.apply_patterns(text)),
+            Err(_) => {
+                let _ = writeln!(out, "[{} bytes]", bytes.len());
+            }
+        }
+        Ok(out)
+    }
+
+    fn apply_patterns(&self, text: &str) -> String {
+        let mut text = text.to_owned();
+        for (regex, replacement) in &self.patterns {
+            text = regex.replace_all(&text, replacement.as_str()).into_owned();
+        }
+        text
+    }
+
+    /// Sort the keys of objects and redact the configured keys.
+    fn normalize_json(&self, value: Value) -> Value {
+        match value {
+            Value::Array(items) => Value::Array(items.into_iter().map(|item| self.normalize_json(item)).collect()),
+            Value::Object(map) => {
+                let sorted = map
+                    .into_iter()
+                    .map(|(key, item)| {
+                        let item = if self.json_keys.contains(&key) {
+                            Value::String("[redacted]".into())
+                        } else {
+                            self.normalize_json(item)
+                        };
+                        (key, item)
+                    })
+                    .collect::<BTreeMap<_, _>>();
+                Value::Object(sorted.into_iter().collect())
+            }
+            value => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::TestClient;
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        #[handler]
+        async fn user(res: &mut Response) {
+            res.headers_mut().insert("x-request-id", "01HV9Z".parse().unwrap());
+            res.headers_mut()
+                .insert(DATE, "Mon, 01 Jan 2024 00:00:00 GMT".parse().unwrap());
+            res.render(Json(json!({
+                "name": "jobs",
+                "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "created_at": "2024-01-01T08:00:00+08:00",
+                "token": "secret",
+            })));
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/").send(user).await;
+        let snapshot = Snapshot::new()
+            .redact_json_key("token")
+            .redact_datetimes()
+            .redact_uuids()
+            .render(&mut res)
+            .await
+            .unwrap();
+        assert_eq!(
+            snapshot,
+            r#"HTTP 200 OK
+content-type: application/json; charset=utf-8
+date: [date]
+x-request-id: [x-request-id]
+
+{
+  "created_at": "[datetime]",
+  "id": "[uuid]",
+  "name": "jobs",
+  "token": "[redacted]"
+}
+"#
+        );
+        assert_eq!(res.take_json::<Value>().await.unwrap()["name"], "jobs");
+    }
+}
@@ -21,6 +21,10 @@
 //!     Server::new(acceptor).serve(router).await;
 //! }
 //! ```
+//!
+//! A salvo [`Service`](crate::Service) can also be used where a tower service is expected, such as inside other
+//! tower based frameworks, with [`Service::into_tower`](crate::Service::into_tower).
+use std::convert::Infallible;
 use std::error::Error as StdError;
 use std::fmt;
 use std::future::Future;
@@ -35,7 +39,10 @@ use hyper::body::{Body, Bytes};
 use tower::buffer::Buffer;
 use tower::{Layer, Service, ServiceExt};
 
+use crate::conn::SocketAddr;
+use crate::http::uri::Scheme;
 use crate::http::{ReqBody, ResBody, StatusError};
+use crate::service::HyperHandler;
 use crate::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
 
 /// Trait for tower service compat.
@@ -258,10 +265,52 @@ where
     }
 }
 
+/// Tower service which handles requests with a salvo [`Service`](crate::Service).
+///
+/// Requests are handled with unknown local and remote addresses, the scheme is taken from the request uri or
+/// defaults to `http`.
+#[derive(Clone)]
+pub struct TowerServiceAdapter {
+    handler: HyperHandler,
+}
+impl TowerServiceAdapter {
+    /// Create a new `TowerServiceAdapter`.
+    #[inline]
+    pub fn new(service: crate::Service) -> Self {
+        Self {
+            handler: service.hyper_handler(SocketAddr::Unknown, SocketAddr::Unknown, Scheme::HTTP, None, None),
+        }
+    }
+}
+impl fmt::Debug for TowerServiceAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TowerServiceAdapter").finish_non_exhaustive()
+    }
+}
+impl<B> Service<hyper::Request<B>> for TowerServiceAdapter
+where
+    B: Into<ReqBody>,
+{
+    type Response = hyper::Response<ResBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: hyper::Request<B>) -> Self::Future {
+        let scheme = req.uri().scheme().cloned().unwrap_or(Scheme::HTTP);
+        let response = self.handler.handle(Request::from_hyper(req, scheme));
+        Box::pin(async move { Ok(response.await.into_hyper()) })
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::http::StatusCode;
     use crate::test::{ResponseExt, TestClient};
     use crate::{handler, Router};
 
@@ -313,4 +362,75 @@ mod tests {
             "Hello World"
         );
     }
+
+    #[tokio::test]
+    async fn test_hoop_layer() {
+        #[derive(Clone)]
+        struct HeaderService<S> {
+            inner: S,
+        }
+        impl<S, QB> Service<hyper::Request<QB>> for HeaderService<S>
+        where
+            S: Service<hyper::Request<QB>, Response = hyper::Response<ResBody>>,
+            S::Future: Send + 'static,
+        {
+            type Response = S::Response;
+            type Error = S::Error;
+            type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+            fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+                self.inner.poll_ready(cx)
+            }
+
+            fn call(&mut self, req: hyper::Request<QB>) -> Self::Future {
+                let fut = self.inner.call(req);
+                Box::pin(async move {
+                    let mut res = fut.await?;
+                    res.headers_mut().insert("x-layer", "on".parse().unwrap());
+                    Ok(res)
+                })
+            }
+        }
+        struct HeaderLayer;
+        impl<S> Layer<S> for HeaderLayer {
+            type Service = HeaderService<S>;
+
+            fn layer(&self, inner: S) -> Self::Service {
+                HeaderService { inner }
+            }
+        }
+
+        #[handler]
+        async fn hello() -> &'static str {
+            "Hello World"
+        }
+        let router = Router::new().hoop_layer(HeaderLayer).get(hello);
+        let mut res = TestClient::get("http://127.0.0.1:5800").send(router).await;
+        assert_eq!(res.headers().get("x-layer").unwrap(), "on");
+        assert_eq!(res.take_string().await.unwrap(), "Hello World");
+    }
+
+    #[tokio::test]
+    async fn test_tower_service_adapter() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "Hello World"
+        }
+        let mut svc = crate::Service::new(Router::with_path("hello").get(hello)).into_tower();
+        let req = hyper::Request::builder()
+            .uri("http://127.0.0.1:5800/hello")
+            .body(ReqBody::None)
+            .unwrap();
+        let res = svc.ready().await.unwrap().call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "Hello World");
+
+        let req = hyper::Request::builder()
+            .uri("http://127.0.0.1:5800/missing")
+            .body(ReqBody::None)
+            .unwrap();
+        let res = svc.ready().await.unwrap().call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,217 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::stream;
+use serde::Serialize;
+use tokio::sync::mpsc::Receiver;
+
+use super::Scribe;
+use crate::http::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use crate::http::Response;
+
+/// Framing of the items written by [`ChannelBody`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Framing {
+    /// Newline delimited JSON, with the `application/x-ndjson` content type.
+    #[default]
+    Ndjson,
+    /// Server-sent events whose data is JSON, with the `text/event-stream` content type.
+    Sse,
+    /// JSON prefixed by its length as a big endian `u32`, with the `application/octet-stream` content type.
+    LengthPrefixed,
+}
+
+/// Write the items received from a channel to the response as they arrive, such as generated tokens or progress
+/// updates.
+///
+/// Each item is serialized as JSON and framed according to [`Framing`]. The response ends when every sender of
+/// the channel is dropped. If an item fails to be serialized, the body is aborted.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::{ChannelBody, Framing};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Token {
+///     text: String,
+/// }
+/// #[handler]
+/// async fn generate() -> ChannelBody<Token> {
+///     let (tx, rx) = tokio::sync::mpsc::channel(16);
+///     tokio::spawn(async move {
+///         for text in ["Hello", " world"] {
+///             if tx.send(Token { text: text.into() }).await.is_err() {
+///                 break;
+///             }
+///         }
+///     });
+///     ChannelBody::new(rx).framing(Framing::Sse).event("token")
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ChannelBody<T> {
+    receiver: Receiver<T>,
+    framing: Framing,
+    event: Option<String>,
+}
+impl<T> ChannelBody<T>
+where
+    T: Serialize + Send + 'static,
+{
+    /// Create a new `ChannelBody` writing the items received by `receiver` as newline delimited JSON.
+    #[inline]
+    pub fn new(receiver: Receiver<T>) -> Self {
+        Self {
+            receiver,
+            framing: Framing::default(),
+            event: None,
+        }
+    }
+
+    /// Sets the framing of the items.
+    #[inline]
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Sets the name of the server-sent events, it is only used by [`Framing::Sse`].
+    #[inline]
+    pub fn event(mut self, name: impl Into<String>) -> Self {
+        self.event = Some(name.into());
+        self
+    }
+}
+
+/// Serialize an item with its framing.
+fn frame<T: Serialize>(item: &T, framing: Framing, event: Option<&str>) -> Result<Bytes, serde_json::Error> {
+    let data = serde_json::to_vec(item)?;
+    let mut buffer = BytesMut::with_capacity(data.len() + 16);
+    match framing {
+        Framing::Ndjson => {
+            buffer.put_slice(&data);
+            buffer.put_u8(b'\n');
+        }
+        Framing::Sse => {
+            if let Some(event) = event {
+                buffer.put_slice(b"event: ");
+                buffer.put_slice(event.as_bytes());
+                buffer.put_u8(b'\n');
+            }
+            // Serialized JSON has no raw newline, so the data fits in a single line.
+            buffer.put_slice(b"data: ");
+            buffer.put_slice(&data);
+            buffer.put_slice(b"\n\n");
+        }
+        Framing::LengthPrefixed => {
+            buffer.put_u32(data.len() as u32);
+            buffer.put_slice(&data);
+        }
+    }
+    Ok(buffer.freeze())
+}
+
+impl<T> Scribe for ChannelBody<T>
+where
+    T: Serialize + Send + 'static,
+{
+    fn render(self, res: &mut Response) {
+        let Self {
+            receiver,
+            framing,
+            event,
+        } = self;
+        let content_type = match framing {
+            Framing::Ndjson => "application/x-ndjson",
+            Framing::Sse => "text/event-stream",
+            Framing::LengthPrefixed => "application/octet-stream",
+        };
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        if framing == Framing::Sse {
+            res.headers_mut()
+                .insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        }
+        res.stream(stream::unfold(
+            (receiver, event, false),
+            move |(mut receiver, event, failed)| async move {
+                if failed {
+                    return None;
+                }
+                let item = receiver.recv().await?;
+                match frame(&item, framing, event.as_deref()) {
+                    Ok(bytes) => Some((Ok(bytes), (receiver, event, false))),
+                    Err(e) => {
+                        tracing::error!(error = ?e, "channel item serialize error");
+                        Some((Err(e), (receiver, event, true)))
+                    }
+                }
+            },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[derive(Serialize)]
+    struct Progress {
+        done: u32,
+    }
+
+    fn receiver() -> mpsc::Receiver<Progress> {
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            for done in [50, 100] {
+                tx.send(Progress { done }).await.unwrap();
+            }
+        });
+        rx
+    }
+
+    #[tokio::test]
+    async fn test_channel_body() {
+        #[handler]
+        async fn ndjson() -> ChannelBody<Progress> {
+            ChannelBody::new(receiver())
+        }
+        #[handler]
+        async fn sse() -> ChannelBody<Progress> {
+            ChannelBody::new(receiver()).framing(Framing::Sse).event("progress")
+        }
+        #[handler]
+        async fn prefixed() -> ChannelBody<Progress> {
+            ChannelBody::new(receiver()).framing(Framing::LengthPrefixed)
+        }
+        let router = Router::new()
+            .push(Router::with_path("ndjson").get(ndjson))
+            .push(Router::with_path("sse").get(sse))
+            .push(Router::with_path("prefixed").get(prefixed));
+        let service = Service::new(router);
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/ndjson").send(&service).await;
+        assert_eq!(res.headers().get("content-type").unwrap(), "application/x-ndjson");
+        assert_eq!(res.take_string().await.unwrap(), "{\"done\":50}\n{\"done\":100}\n");
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/sse").send(&service).await;
+        assert_eq!(res.headers().get("content-type").unwrap(), "text/event-stream");
+        assert_eq!(
+            res.take_string().await.unwrap(),
+            "event: progress\ndata: {\"done\":50}\n\nevent: progress\ndata: {\"done\":100}\n\n"
+        );
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/prefixed").send(&service).await;
+        let bytes = res.take_bytes(None).await.unwrap();
+        assert_eq!(&bytes[..4], &[0, 0, 0, 11]);
+        assert_eq!(&bytes[4..15], b"{\"done\":50}");
+        assert_eq!(&bytes[15..19], &[0, 0, 0, 12]);
+    }
+}
@@ -1,9 +1,104 @@
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Result as IoResult, Write};
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use headers::{ETag, HeaderMapExt};
+use parking_lot::RwLock;
 use serde::Serialize;
 
-use super::Scribe;
-use crate::http::header::{HeaderValue, CONTENT_TYPE};
-use crate::http::{Response, StatusError};
+use super::canonical::Canonical;
+use super::seek::none_match;
+use super::{Scribe, Writer};
+use crate::http::body::{BytesFrame, Frame};
+use crate::http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, TRAILER};
+use crate::http::{Method, Response, StatusCode, StatusError};
+use crate::{BoxedError, Depot, Request};
+
+static SERIALIZE_POLICY: RwLock<Option<SerializePolicy>> = RwLock::new(None);
+
+/// Get the global [`SerializePolicy`].
+pub fn serialize_policy() -> SerializePolicy {
+    SERIALIZE_POLICY.read().clone().unwrap_or_default()
+}
+
+/// Set the [`SerializePolicy`] of JSON responses globally.
+pub fn set_serialize_policy(policy: SerializePolicy) {
+    *SERIALIZE_POLICY.write() = Some(policy);
+}
+
+/// How errors of serializing JSON responses are handled, so clients never receive truncated JSON silently.
+///
+/// - [`Json`] always serializes the whole value before sending it, a failure renders the fallback error.
+/// - [`JsonStream`] buffers items until [`buffer_size`](Self::buffer_size) bytes are serialized, a failure before
+///   renders the fallback error. After the response is started, a failure either sends the
+///   [`trailer`](Self::trailer) and ends the body, or aborts the body so the client sees a broken connection
+///   instead of a well formed but incomplete document.
+#[derive(Clone)]
+pub struct SerializePolicy {
+    buffer_size: usize,
+    trailer: Option<HeaderName>,
+    fallback: Arc<dyn Fn(&serde_json::Error) -> StatusError + Send + Sync>,
+}
+impl Default for SerializePolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Debug for SerializePolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SerializePolicy")
+            .field("buffer_size", &self.buffer_size)
+            .field("trailer", &self.trailer)
+            .finish_non_exhaustive()
+    }
+}
+impl SerializePolicy {
+    /// Create a new `SerializePolicy`, it buffers 64KB, aborts failed streams and falls back to
+    /// `500 Internal Server Error`.
+    pub fn new() -> Self {
+        Self {
+            buffer_size: 64 * 1024,
+            trailer: None,
+            fallback: Arc::new(|_| StatusError::internal_server_error()),
+        }
+    }
+
+    /// Sets the number of bytes of streamed items which are buffered before the response is started.
+    #[inline]
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size;
+        self
+    }
+
+    /// Signal failures of started streams with a trailer named `name` instead of aborting the body.
+    ///
+    /// The trailer is announced in the `trailer` header, its value is the error message. Clients must read
+    /// trailers to notice the failure, which HTTP/1.1 clients only receive if they send `te: trailers`.
+    #[inline]
+    pub fn trailer(mut self, name: HeaderName) -> Self {
+        self.trailer = Some(name);
+        self
+    }
+
+    /// Sets the function building the error rendered when serialization fails before the response is started.
+    #[inline]
+    pub fn fallback<F>(mut self, fallback: F) -> Self
+    where
+        F: Fn(&serde_json::Error) -> StatusError + Send + Sync + 'static,
+    {
+        self.fallback = Arc::new(fallback);
+        self
+    }
+
+    fn render_error(&self, error: &serde_json::Error, res: &mut Response) {
+        tracing::error!(error = ?error, "json serialize error");
+        res.render((self.fallback)(error));
+    }
+}
 
 /// Write serializable content to response as json content.
 ///
@@ -40,11 +135,249 @@ where
                 );
                 res.write_body(bytes).ok();
             }
-            Err(e) => {
-                tracing::error!(error = ?e, "JsonContent write error");
-                res.render(StatusError::internal_server_error());
+            Err(e) => serialize_policy().render_error(&e, res),
+        }
+    }
+}
+
+/// Write serializable content to response as json content with an `ETag` of its content.
+///
+/// The value is serialized in a canonical form, keys of maps are sorted, so the `ETag` is the same for equal
+/// values even if they are kept in a `HashMap`. Requests whose `If-None-Match` header matches the `ETag` are
+/// answered with `304 Not Modified` without a body, which saves bandwidth for clients polling the same resource.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::CachedJson;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Status {
+///     jobs: u32,
+/// }
+/// #[handler]
+/// async fn status() -> CachedJson<Status> {
+///     CachedJson(Status { jobs: 3 })
+/// }
+/// ```
+pub struct CachedJson<T>(pub T);
+
+#[async_trait]
+impl<T> Writer for CachedJson<T>
+where
+    T: Serialize + Send,
+{
+    async fn write(self, req: &mut Request, _depot: &mut Depot, res: &mut Response) {
+        let mut writer = HashWriter::new();
+        if let Err(e) = serde_json::to_writer(&mut writer, &Canonical(&self.0)) {
+            serialize_policy().render_error(&e, res);
+            return;
+        }
+        let Ok(etag) = format!("\"{:032x}\"", writer.hash).parse::<ETag>() else {
+            res.render(StatusError::internal_server_error());
+            return;
+        };
+        res.headers_mut().typed_insert(etag.clone());
+        if !none_match(Some(&etag), req.headers()) {
+            if req.method() == Method::GET || req.method() == Method::HEAD {
+                res.status_code(StatusCode::NOT_MODIFIED);
+            } else {
+                res.render(StatusError::precondition_failed());
+            }
+            return;
+        }
+        res.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/json; charset=utf-8"),
+        );
+        res.write_body(writer.data).ok();
+    }
+}
+
+/// Writer keeping the written data and its 128 bits FNV-1a hash.
+struct HashWriter {
+    data: Vec<u8>,
+    hash: u128,
+}
+impl HashWriter {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        Self {
+            data: vec![],
+            hash: Self::OFFSET_BASIS,
+        }
+    }
+}
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        for byte in buf {
+            self.hash ^= *byte as u128;
+            self.hash = self.hash.wrapping_mul(Self::PRIME);
+        }
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> IoResult<()> {
+        Ok(())
+    }
+}
+
+/// Write a stream of serializable items to response as a JSON array, or as newline delimited JSON.
+///
+/// Items are buffered and the response is started when the [`SerializePolicy`] buffer is full, so small payloads
+/// are sent at once and serialization errors of them are reported with a proper status code.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::stream;
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::JsonStream;
+///
+/// #[handler]
+/// async fn numbers() -> JsonStream<impl futures_util::Stream<Item = u64> + Send> {
+///     JsonStream::new(stream::iter(0..1000))
+/// }
+/// ```
+pub struct JsonStream<S> {
+    stream: S,
+    lines: bool,
+    policy: Option<SerializePolicy>,
+}
+impl<S> Debug for JsonStream<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonStream")
+            .field("lines", &self.lines)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+impl<S, T> JsonStream<S>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    /// Create a new `JsonStream` writing the items as a JSON array.
+    #[inline]
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            lines: false,
+            policy: None,
+        }
+    }
+
+    /// Create a new `JsonStream` writing the items as newline delimited JSON, with the `application/x-ndjson`
+    /// content type.
+    #[inline]
+    pub fn lines(stream: S) -> Self {
+        Self {
+            stream,
+            lines: true,
+            policy: None,
+        }
+    }
+
+    /// Sets the [`SerializePolicy`], the global one is used if it is not set.
+    #[inline]
+    pub fn policy(mut self, policy: SerializePolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+}
+
+/// Serialize an item with its separator, nothing is written if it fails.
+fn write_item<T: Serialize>(
+    buffer: &mut Vec<u8>,
+    item: &T,
+    index: usize,
+    lines: bool,
+) -> Result<(), serde_json::Error> {
+    let start = buffer.len();
+    if !lines && index > 0 {
+        buffer.push(b',');
+    }
+    if let Err(e) = serde_json::to_writer(&mut *buffer, item) {
+        buffer.truncate(start);
+        return Err(e);
+    }
+    if lines {
+        buffer.push(b'\n');
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl<S, T> Writer for JsonStream<S>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    async fn write(self, _req: &mut Request, _depot: &mut Depot, res: &mut Response) {
+        let Self { stream, lines, policy } = self;
+        let policy = policy.unwrap_or_else(serialize_policy);
+        let mut stream: BoxStream<'static, T> = stream.boxed();
+        let content_type = if lines {
+            "application/x-ndjson"
+        } else {
+            "application/json; charset=utf-8"
+        };
+        let close: &'static [u8] = if lines { b"" } else { b"]" };
+        let mut buffer = if lines { vec![] } else { vec![b'['] };
+        let mut index = 0;
+        while buffer.len() <= policy.buffer_size {
+            let Some(item) = stream.next().await else {
+                buffer.extend_from_slice(close);
+                res.headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+                res.write_body(buffer).ok();
+                return;
+            };
+            if let Err(e) = write_item(&mut buffer, &item, index, lines) {
+                policy.render_error(&e, res);
+                return;
             }
+            index += 1;
         }
+
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        if let Some(trailer) = &policy.trailer {
+            res.headers_mut().insert(TRAILER, trailer.clone().into());
+        }
+        let head = stream::once(async move { Ok(BytesFrame::data(buffer)) });
+        let tail = stream::unfold(Some((stream, index)), move |state| {
+            let trailer = policy.trailer.clone();
+            async move {
+                let (mut stream, index) = state?;
+                let Some(item) = stream.next().await else {
+                    return Some((Ok(BytesFrame::data(Bytes::from_static(close))), None));
+                };
+                let mut buffer = vec![];
+                match write_item(&mut buffer, &item, index, lines) {
+                    Ok(()) => Some((Ok(BytesFrame::data(buffer)), Some((stream, index + 1)))),
+                    Err(e) => {
+                        tracing::error!(error = ?e, "json stream serialize error");
+                        let frame = match trailer {
+                            Some(trailer) => {
+                                let value = HeaderValue::from_str(&e.to_string())
+                                    .unwrap_or_else(|_| HeaderValue::from_static("serialize error"));
+                                let mut trailers = HeaderMap::new();
+                                trailers.insert(trailer, value);
+                                Ok(BytesFrame(Frame::trailers(trailers)))
+                            }
+                            None => Err(BoxedError::from(e)),
+                        };
+                        Some((frame, None))
+                    }
+                }
+            }
+        });
+        res.stream(head.chain(tail));
     }
 }
 
@@ -74,4 +407,97 @@ mod tests {
             "application/json; charset=utf-8"
         );
     }
+
+    #[tokio::test]
+    async fn test_write_cached_json() {
+        use std::collections::HashMap;
+
+        use crate::http::header::{ETAG, IF_NONE_MATCH};
+
+        #[handler]
+        async fn status() -> CachedJson<HashMap<String, u32>> {
+            CachedJson((0..16).map(|i| (format!("key{i}"), i)).collect())
+        }
+        let service = Service::new(Router::with_path("status").get(status));
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/status").send(&service).await;
+        let etag = res.headers().get(ETAG).unwrap().clone();
+        assert!(res
+            .take_string()
+            .await
+            .unwrap()
+            .starts_with(r#"{"key0":0,"key1":1,"key10":10"#));
+        let res = TestClient::get("http://127.0.0.1:5800/status").send(&service).await;
+        assert_eq!(res.headers().get(ETAG).unwrap(), etag);
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/status")
+            .add_header(IF_NONE_MATCH, etag, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+        assert!(res.take_string().await.unwrap().is_empty());
+        let res = TestClient::get("http://127.0.0.1:5800/status")
+            .add_header(IF_NONE_MATCH, r#""other""#, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_write_json_stream() {
+        use futures_util::stream;
+        use http_body_util::BodyExt;
+
+        struct Item(bool);
+        impl Serialize for Item {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if self.0 {
+                    serializer.serialize_u8(1)
+                } else {
+                    Err(serde::ser::Error::custom("broken item"))
+                }
+            }
+        }
+        #[handler]
+        async fn items(req: &mut Request) -> JsonStream<stream::Iter<std::array::IntoIter<Item, 3>>> {
+            let policy = SerializePolicy::new().buffer_size(req.query("buffer").unwrap_or(1024));
+            let policy = match req.query::<String>("trailer") {
+                Some(trailer) => policy.trailer(trailer.parse().unwrap()),
+                None => policy,
+            };
+            let broken = req.query::<bool>("broken").unwrap_or_default();
+            let stream = stream::iter([Item(true), Item(true), Item(!broken)]);
+            let lines = req.query::<bool>("lines").unwrap_or_default();
+            let stream = if lines {
+                JsonStream::lines(stream)
+            } else {
+                JsonStream::new(stream)
+            };
+            stream.policy(policy)
+        }
+        let service = Service::new(Router::with_path("items").get(items));
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/items").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "[1,1,1]");
+        let mut res = TestClient::get("http://127.0.0.1:5800/items?lines=true&buffer=0")
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "1\n1\n1\n");
+
+        let res = TestClient::get("http://127.0.0.1:5800/items?broken=true")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::INTERNAL_SERVER_ERROR));
+        let mut res = TestClient::get("http://127.0.0.1:5800/items?broken=true&buffer=0")
+            .send(&service)
+            .await;
+        assert!(res.take_string().await.is_err());
+        let mut res = TestClient::get("http://127.0.0.1:5800/items?broken=true&buffer=0&trailer=x-error")
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(TRAILER).unwrap(), "x-error");
+        let collected = BodyExt::collect(res.take_body()).await.unwrap();
+        assert_eq!(collected.trailers().unwrap().get("x-error").unwrap(), "broken item");
+        assert_eq!(collected.to_bytes(), "[1,1");
+    }
 }
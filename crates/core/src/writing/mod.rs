@@ -1,15 +1,23 @@
 //! Writer trait and it's implements.
 
+mod canonical;
+mod channel;
 mod json;
+mod multipart;
 mod redirect;
 mod seek;
 mod text;
+mod xml;
 
+use bytes::Bytes;
+pub use channel::{ChannelBody, Framing};
 use http::StatusCode;
-pub use json::Json;
+pub use json::{serialize_policy, set_serialize_policy, CachedJson, Json, JsonStream, SerializePolicy};
+pub use multipart::{MultipartResponse, Part};
 pub use redirect::Redirect;
 pub use seek::ReadSeeker;
 pub use text::Text;
+pub use xml::Xml;
 
 use crate::http::header::{HeaderValue, CONTENT_TYPE};
 use crate::{async_trait, Depot, Request, Response};
@@ -118,6 +126,30 @@ impl Scribe for String {
         res.write_body(self).ok();
     }
 }
+/// Writes the bytes as the body without copying them, the `content-type` header is set to
+/// `application/octet-stream` if it is not set.
+impl Scribe for Bytes {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        if !res.headers().contains_key(CONTENT_TYPE) {
+            res.headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"));
+        }
+        res.write_body(self).ok();
+    }
+}
+impl Scribe for Vec<u8> {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        Bytes::from(self).render(res);
+    }
+}
+impl Scribe for &'static [u8] {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        Bytes::from_static(self).render(res);
+    }
+}
 impl Scribe for std::convert::Infallible {
     #[inline]
     fn render(self, _res: &mut Response) {}
@@ -155,4 +187,30 @@ mod tests {
         assert_eq!(res.take_string().await.unwrap(), "hello");
         assert_eq!(res.headers().get("content-type").unwrap(), "text/plain; charset=utf-8");
     }
+
+    #[tokio::test]
+    async fn test_write_bytes() {
+        #[handler]
+        async fn shared() -> bytes::Bytes {
+            bytes::Bytes::from_static(b"hello")
+        }
+        #[handler]
+        async fn png(res: &mut Response) -> &'static [u8] {
+            res.add_header("content-type", "image/png", true).unwrap();
+            b"\x89PNG"
+        }
+
+        let service = Service::new(
+            Router::new()
+                .push(Router::with_path("bytes").get(shared))
+                .push(Router::with_path("png").get(png)),
+        );
+        let mut res = TestClient::get("http://127.0.0.1:5800/bytes").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+        assert_eq!(res.headers().get("content-type").unwrap(), "application/octet-stream");
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/png").send(&service).await;
+        assert_eq!(res.take_bytes(None).await.unwrap().as_ref(), b"\x89PNG");
+        assert_eq!(res.headers().get("content-type").unwrap(), "image/png");
+    }
 }
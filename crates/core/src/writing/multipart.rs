@@ -0,0 +1,286 @@
+use std::fmt::{self, Debug, Formatter};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+use super::Scribe;
+use crate::http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_DISPOSITION, CONTENT_TYPE};
+use crate::http::{Mime, Response, StatusError};
+use crate::BoxedError;
+
+/// Write parts to response as `multipart/*` content, such as `multipart/mixed` for batch APIs or
+/// `multipart/x-mixed-replace` for MJPEG streams.
+///
+/// Parts are streamed one after another, the body of each part can be streamed too. A random boundary is
+/// generated unless one is set with [`MultipartResponse::boundary`].
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::{MultipartResponse, Part};
+///
+/// #[handler]
+/// async fn batch() -> MultipartResponse {
+///     MultipartResponse::mixed()
+///         .part(Part::new(r#"{"id":1}"#).content_type(mime::APPLICATION_JSON))
+///         .part(Part::new(r#"{"id":2}"#).content_type(mime::APPLICATION_JSON))
+/// }
+/// ```
+pub struct MultipartResponse {
+    subtype: String,
+    boundary: String,
+    parts: Vec<Part>,
+    tail: Option<BoxStream<'static, Result<Part, BoxedError>>>,
+}
+
+impl Debug for MultipartResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartResponse")
+            .field("subtype", &self.subtype)
+            .field("boundary", &self.boundary)
+            .field("parts", &self.parts)
+            .finish()
+    }
+}
+
+impl MultipartResponse {
+    /// Create a new `MultipartResponse` with the subtype of the content type, such as `x-mixed-replace`.
+    pub fn new(subtype: impl Into<String>) -> Self {
+        let boundary = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+        Self {
+            subtype: subtype.into(),
+            boundary,
+            parts: vec![],
+            tail: None,
+        }
+    }
+
+    /// Create a new `multipart/mixed` response.
+    #[inline]
+    pub fn mixed() -> Self {
+        Self::new("mixed")
+    }
+
+    /// Create a new `multipart/form-data` response.
+    #[inline]
+    pub fn form_data() -> Self {
+        Self::new("form-data")
+    }
+
+    /// Sets the boundary, it must not occur in the content of any part.
+    #[inline]
+    pub fn boundary(mut self, boundary: impl Into<String>) -> Self {
+        self.boundary = boundary.into();
+        self
+    }
+
+    /// Get the boundary.
+    #[inline]
+    pub fn get_boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Add a part.
+    #[inline]
+    pub fn part(mut self, part: Part) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// Sets a stream of parts sent after the parts added with [`part`](Self::part), the response ends when the
+    /// stream ends, so an endless stream produces an endless response, such as MJPEG frames.
+    pub fn stream<S, E>(mut self, parts: S) -> Self
+    where
+        S: Stream<Item = Result<Part, E>> + Send + 'static,
+        E: Into<BoxedError> + 'static,
+    {
+        self.tail = Some(parts.map_err(Into::into).boxed());
+        self
+    }
+}
+
+impl Scribe for MultipartResponse {
+    fn render(self, res: &mut Response) {
+        let Self {
+            subtype,
+            boundary,
+            parts,
+            tail,
+        } = self;
+        let Ok(content_type) = HeaderValue::from_str(&format!("multipart/{subtype}; boundary={boundary}")) else {
+            tracing::error!(subtype, boundary, "invalid multipart content type");
+            res.render(StatusError::internal_server_error());
+            return;
+        };
+        res.headers_mut().insert(CONTENT_TYPE, content_type);
+
+        let parts = stream::iter(parts.into_iter().map(Ok));
+        let parts = match tail {
+            Some(tail) => parts.chain(tail).left_stream(),
+            None => parts.right_stream(),
+        };
+        let delimiter = Bytes::from(format!("--{boundary}\r\n"));
+        let close = Bytes::from(format!("--{boundary}--\r\n"));
+        let body = parts
+            .map(move |part| match part {
+                Ok(part) => part.into_stream(delimiter.clone()).left_stream(),
+                Err(e) => stream::once(async move { Err(e) }).right_stream(),
+            })
+            .flatten()
+            .chain(stream::once(async move { Ok(close) }));
+        res.stream(body);
+    }
+}
+
+/// A part of a [`MultipartResponse`].
+pub struct Part {
+    headers: HeaderMap,
+    body: BoxStream<'static, Result<Bytes, BoxedError>>,
+}
+
+impl Debug for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Part").field("headers", &self.headers).finish()
+    }
+}
+
+impl Part {
+    /// Create a new `Part` with its content.
+    pub fn new(body: impl Into<Bytes>) -> Self {
+        let body = body.into();
+        Self {
+            headers: HeaderMap::new(),
+            body: stream::once(async move { Ok(body) }).boxed(),
+        }
+    }
+
+    /// Create a new `Part` whose content is streamed.
+    pub fn stream<S, O, E>(body: S) -> Self
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'static,
+        O: Into<Bytes> + 'static,
+        E: Into<BoxedError> + 'static,
+    {
+        Self {
+            headers: HeaderMap::new(),
+            body: body.map_ok(Into::into).map_err(Into::into).boxed(),
+        }
+    }
+
+    /// Create a new `Part` of a `multipart/form-data` response, with the name of the field.
+    pub fn field(name: &str, body: impl Into<Bytes>) -> Self {
+        Self::new(body).disposition(name, None)
+    }
+
+    /// Create a new `Part` of a `multipart/form-data` response, with the name of the field and the file name.
+    pub fn file(name: &str, file_name: &str, body: impl Into<Bytes>) -> Self {
+        Self::new(body).disposition(name, Some(file_name))
+    }
+
+    /// Sets the `content-disposition` header of a `multipart/form-data` part.
+    pub fn disposition(mut self, name: &str, file_name: Option<&str>) -> Self {
+        let mut value = format!("form-data; name=\"{}\"", escape_quoted(name));
+        if let Some(file_name) = file_name {
+            value.push_str(&format!("; filename=\"{}\"", escape_quoted(file_name)));
+        }
+        match HeaderValue::from_str(&value) {
+            Ok(value) => {
+                self.headers.insert(CONTENT_DISPOSITION, value);
+            }
+            Err(e) => tracing::error!(error = ?e, name, "invalid multipart part name"),
+        }
+        self
+    }
+
+    /// Sets the `content-type` header.
+    pub fn content_type(mut self, content_type: Mime) -> Self {
+        if let Ok(value) = HeaderValue::from_str(content_type.as_ref()) {
+            self.headers.insert(CONTENT_TYPE, value);
+        }
+        self
+    }
+
+    /// Sets a header.
+    #[inline]
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Get headers reference.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    fn into_stream(self, delimiter: Bytes) -> impl Stream<Item = Result<Bytes, BoxedError>> + Send {
+        let mut head = BytesMut::from(&delimiter[..]);
+        for (name, value) in &self.headers {
+            head.put_slice(name.as_str().as_bytes());
+            head.put_slice(b": ");
+            head.put_slice(value.as_bytes());
+            head.put_slice(b"\r\n");
+        }
+        head.put_slice(b"\r\n");
+        let head = head.freeze();
+        stream::once(async move { Ok(head) })
+            .chain(self.body)
+            .chain(stream::once(async { Ok(Bytes::from_static(b"\r\n")) }))
+    }
+}
+
+fn escape_quoted(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::prelude::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_multipart() {
+        #[handler]
+        async fn form() -> MultipartResponse {
+            MultipartResponse::form_data()
+                .boundary("X-BOUNDARY")
+                .part(Part::field("name", "jobs"))
+                .part(Part::file("avatar", "a\"b.txt", "hello").content_type(mime::TEXT_PLAIN))
+                .stream(stream::iter([Ok::<_, Infallible>(Part::stream(stream::iter([
+                    Ok::<_, Infallible>("stre"),
+                    Ok("amed"),
+                ])))]))
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/")
+            .send(&Service::new(Router::new().get(form)))
+            .await;
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "multipart/form-data; boundary=X-BOUNDARY"
+        );
+        assert_eq!(
+            res.take_string().await.unwrap(),
+            "--X-BOUNDARY\r\ncontent-disposition: form-data; name=\"name\"\r\n\r\njobs\r\n\
+             --X-BOUNDARY\r\ncontent-disposition: form-data; name=\"avatar\"; filename=\"a\\\"b.txt\"\r\n\
+             content-type: text/plain\r\n\r\nhello\r\n\
+             --X-BOUNDARY\r\n\r\nstreamed\r\n\
+             --X-BOUNDARY--\r\n"
+        );
+
+        let response = MultipartResponse::mixed();
+        assert_eq!(response.get_boundary().len(), 32);
+        assert_ne!(response.get_boundary(), MultipartResponse::mixed().get_boundary());
+    }
+}
@@ -31,6 +31,7 @@ pub struct ReadSeeker<R> {
     length: u64,
     last_modified: Option<SystemTime>,
     etag: Option<ETag>,
+    buffer_size: Option<usize>,
 }
 
 impl<R> ReadSeeker<R>
@@ -44,6 +45,7 @@ where
             length,
             last_modified: None,
             etag: None,
+            buffer_size: None,
         }
     }
 
@@ -59,6 +61,12 @@ where
         self
     }
 
+    /// Set the maximum size of the chunks read from the reader at one time.
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
     ///Consume self and send content to [`Response`].
     pub async fn send(mut self, req_headers: &HeaderMap, res: &mut Response) {
         // check preconditions
@@ -88,7 +96,7 @@ where
         if let Some(lm) = self.last_modified {
             res.headers_mut().typed_insert(LastModified::from(lm));
         }
-        if let Some(etag) = self.etag {
+        if let Some(etag) = self.etag.take() {
             res.headers_mut().typed_insert(etag);
         }
         res.headers_mut().typed_insert(AcceptRanges::bytes());
@@ -138,11 +146,18 @@ where
             }
             res.headers_mut()
                 .typed_insert(ContentLength(cmp::min(length, self.length)));
-            res.stream(ReaderStream::new(self.reader));
+            res.stream(self.stream());
         } else {
             res.status_code(StatusCode::OK);
             res.headers_mut().typed_insert(ContentLength(self.length));
-            res.stream(ReaderStream::new(self.reader));
+            res.stream(self.stream());
+        }
+    }
+
+    fn stream(self) -> ReaderStream<R> {
+        match self.buffer_size {
+            Some(size) => ReaderStream::with_capacity(self.reader, size),
+            None => ReaderStream::new(self.reader),
         }
     }
 }
@@ -175,7 +190,7 @@ fn any_match(etag: Option<&ETag>, req_headers: &HeaderMap) -> bool {
 }
 
 /// Returns true if `req_headers` doesn't have an `If-None-Match` header matching `req`.
-fn none_match(etag: Option<&ETag>, req_headers: &HeaderMap) -> bool {
+pub(super) fn none_match(etag: Option<&ETag>, req_headers: &HeaderMap) -> bool {
     match req_headers.typed_get::<IfNoneMatch>() {
         None => true,
         Some(if_none_match) => {
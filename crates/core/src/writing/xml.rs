@@ -0,0 +1,103 @@
+use serde::Serialize;
+
+use super::Scribe;
+use crate::http::header::{HeaderValue, CONTENT_TYPE};
+use crate::http::{Response, StatusError};
+
+/// Write serializable content to response as xml content.
+///
+/// It will set `content-type` to `application/xml; charset=utf-8`.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_core::writing::Xml;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///    name: String,
+/// }
+/// #[handler]
+/// async fn hello() -> Xml<User> {
+///     Xml(User { name: "jobs".into() })
+/// }
+/// ```
+pub struct Xml<T>(pub T);
+
+impl<T> Scribe for Xml<T>
+where
+    T: Serialize + Send,
+{
+    fn render(self, res: &mut Response) {
+        // `serde_xml_rs` is the same serializer used by `Request::parse_xml` and the default catcher, so written
+        // and parsed xml stay symmetric without another dependency.
+        match serde_xml_rs::to_string(&self.0) {
+            Ok(content) => {
+                res.headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/xml; charset=utf-8"));
+                res.write_body(content).ok();
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "xml serialize error");
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::prelude::*;
+
+    use super::*;
+    use crate::test::{ResponseExt, TestClient};
+
+    #[tokio::test]
+    async fn test_write_xml_content() {
+        #[derive(Serialize, Debug)]
+        #[serde(rename = "user")]
+        struct User {
+            name: String,
+        }
+        #[handler]
+        async fn test() -> Xml<User> {
+            Xml(User { name: "jobs".into() })
+        }
+
+        let router = Router::new().push(Router::with_path("test").get(test));
+        let mut res = TestClient::get("http://127.0.0.1:5800/test").send(router).await;
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "application/xml; charset=utf-8"
+        );
+        assert!(res
+            .take_string()
+            .await
+            .unwrap()
+            .contains("<user><name>jobs</name></user>"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_xml_body() {
+        #[derive(Deserialize, Debug)]
+        struct User {
+            name: String,
+        }
+        #[handler]
+        async fn echo(req: &mut Request) -> String {
+            req.parse_xml::<User>().await.unwrap().name
+        }
+
+        let router = Router::with_path("echo").post(echo);
+        let mut res = TestClient::post("http://127.0.0.1:5800/echo")
+            .body("<user><name>jobs</name></user>")
+            .add_header("content-type", "application/soap+xml", true)
+            .send(router)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "jobs");
+    }
+}
@@ -0,0 +1,15 @@
+use deadpool::managed::{Manager, Pool as ManagedPool};
+
+use super::{Error, Pool};
+
+/// A `deadpool` pool is healthy if an object can be got from it, which is recycled and checked by the manager.
+impl<M> Pool for ManagedPool<M>
+where
+    M: Manager + 'static,
+    M::Type: Send,
+    M::Error: std::error::Error + Send + Sync + 'static,
+{
+    async fn ping(&self) -> Result<(), Error> {
+        self.get().await.map(drop).map_err(Error::other)
+    }
+}
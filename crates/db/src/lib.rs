@@ -0,0 +1,410 @@
+//! Database pool integration for Savlo web framework.
+//!
+//! - [`DbPool`] injects a pool into the [`Depot`], handlers get it back with [`DbDepotExt::pool`].
+//! - [`Transactional`] runs each request in a transaction, which is committed if the response status is `2xx`
+//!   and rolled back otherwise. Handlers use it with [`DbDepotExt::transaction`].
+//! - [`HealthCheck`] pings registered pools and reports their health as JSON.
+//!
+//! Pools of [`sqlx`](https://crates.io/crates/sqlx), [SeaORM](https://crates.io/crates/sea-orm) and
+//! [`deadpool`](https://crates.io/crates/deadpool) are supported with the `sqlx`, `sea-orm` and `deadpool`
+//! features. Other pools can be supported by implementing [`Pool`] and [`TransactionalPool`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use salvo_core::prelude::*;
+//! use salvo_db::{DbDepotExt, DbPool, HealthCheck, Transactional};
+//! use sqlx::PgPool;
+//!
+//! #[handler]
+//! async fn create_user(depot: &mut Depot) -> Result<&'static str, StatusError> {
+//!     let tx = depot.transaction::<PgPool>().ok_or_else(StatusError::internal_server_error)?;
+//!     sqlx::query("INSERT INTO users (name) VALUES ('salvo')")
+//!         .execute(&mut **tx)
+//!         .await
+//!         .map_err(|_| StatusError::internal_server_error())?;
+//!     Ok("created")
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let pool = PgPool::connect("postgres://localhost/salvo").await.unwrap();
+//!     let router = Router::new()
+//!         .hoop(DbPool::new(pool.clone()))
+//!         .push(Router::with_path("healthz").get(HealthCheck::new().add("primary", pool.clone())))
+//!         .push(Router::with_path("users").hoop(Transactional::new(pool)).post(create_user));
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures_util::future::{join_all, BoxFuture};
+use futures_util::FutureExt;
+use salvo_core::http::{StatusCode, StatusError};
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Request, Response};
+use serde::Serialize;
+use sync_wrapper::SyncWrapper;
+
+#[macro_use]
+mod cfg;
+
+cfg_feature! {
+    #![feature = "sqlx"]
+    mod sqlx;
+}
+cfg_feature! {
+    #![feature = "sea-orm"]
+    mod sea_orm;
+}
+cfg_feature! {
+    #![feature = "deadpool"]
+    mod deadpool;
+}
+
+/// A database connection pool.
+pub trait Pool: Clone + Send + Sync + 'static {
+    /// Check that a connection of the pool can be used.
+    fn ping(&self) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+/// A pool that can begin transactions.
+pub trait TransactionalPool: Pool {
+    /// Transaction type.
+    type Transaction: Transaction;
+
+    /// Begin a new transaction.
+    fn begin(&self) -> impl Future<Output = Result<Self::Transaction, Error>> + Send;
+}
+
+/// A database transaction.
+pub trait Transaction: Send + 'static {
+    /// Commit the transaction.
+    fn commit(self) -> impl Future<Output = Result<(), Error>> + Send;
+
+    /// Roll back the transaction.
+    fn rollback(self) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+struct TransactionSlot<P: TransactionalPool>(SyncWrapper<Option<P::Transaction>>);
+
+/// Extension trait to get pools and transactions from [`Depot`].
+pub trait DbDepotExt {
+    /// Get the pool injected by [`DbPool`].
+    fn pool<P: Pool>(&self) -> Option<&P>;
+
+    /// Get the transaction of the request begun by [`Transactional`].
+    fn transaction<P: TransactionalPool>(&mut self) -> Option<&mut P::Transaction>;
+
+    /// Take the transaction of the request, so it can be committed or rolled back by the handler.
+    fn take_transaction<P: TransactionalPool>(&mut self) -> Option<P::Transaction>;
+}
+impl DbDepotExt for Depot {
+    #[inline]
+    fn pool<P: Pool>(&self) -> Option<&P> {
+        self.obtain::<P>().ok()
+    }
+
+    #[inline]
+    fn transaction<P: TransactionalPool>(&mut self) -> Option<&mut P::Transaction> {
+        self.obtain_mut::<TransactionSlot<P>>()
+            .ok()
+            .and_then(|slot| slot.0.get_mut().as_mut())
+    }
+
+    #[inline]
+    fn take_transaction<P: TransactionalPool>(&mut self) -> Option<P::Transaction> {
+        self.obtain_mut::<TransactionSlot<P>>()
+            .ok()
+            .and_then(|slot| slot.0.get_mut().take())
+    }
+}
+
+/// Middleware that injects a pool into the [`Depot`].
+#[derive(Clone, Debug)]
+pub struct DbPool<P> {
+    pool: P,
+}
+impl<P: Pool> DbPool<P> {
+    /// Create new `DbPool`.
+    #[inline]
+    pub fn new(pool: P) -> Self {
+        Self { pool }
+    }
+}
+#[async_trait]
+impl<P: Pool> Handler for DbPool<P> {
+    async fn handle(&self, _req: &mut Request, depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        depot.inject(self.pool.clone());
+    }
+}
+
+/// Middleware that runs each request in a transaction.
+///
+/// The transaction is committed if the response status is `2xx`, and rolled back otherwise. A transaction taken by
+/// the handler with [`DbDepotExt::take_transaction`] is left to the handler.
+pub struct Transactional<P> {
+    pool: P,
+    commit_if: Arc<dyn Fn(&Response) -> bool + Send + Sync>,
+}
+impl<P: TransactionalPool> Transactional<P> {
+    /// Create new `Transactional`.
+    #[inline]
+    pub fn new(pool: P) -> Self {
+        Self {
+            pool,
+            commit_if: Arc::new(|res| res.status_code.unwrap_or(StatusCode::OK).is_success()),
+        }
+    }
+
+    /// Set the condition to commit the transaction, instead of the default `2xx` response status.
+    #[inline]
+    pub fn commit_if(mut self, commit_if: impl Fn(&Response) -> bool + Send + Sync + 'static) -> Self {
+        self.commit_if = Arc::new(commit_if);
+        self
+    }
+}
+impl<P> Debug for Transactional<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transactional").finish()
+    }
+}
+#[async_trait]
+impl<P: TransactionalPool> Handler for Transactional<P> {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => {
+                tracing::error!(error = ?e, "begin transaction failed");
+                res.render(StatusError::service_unavailable().brief("Database is unavailable."));
+                ctrl.skip_rest();
+                return;
+            }
+        };
+        depot.inject(TransactionSlot::<P>(SyncWrapper::new(Some(tx))));
+        ctrl.call_next(req, depot, res).await;
+
+        let Some(tx) = depot
+            .scrape::<TransactionSlot<P>>()
+            .ok()
+            .and_then(|slot| slot.0.into_inner())
+        else {
+            return;
+        };
+        if (self.commit_if)(res) {
+            if let Err(e) = tx.commit().await {
+                tracing::error!(error = ?e, "commit transaction failed");
+                res.render(StatusError::internal_server_error().brief("Commit transaction failed."));
+            }
+        } else if let Err(e) = tx.rollback().await {
+            tracing::error!(error = ?e, "rollback transaction failed");
+        }
+    }
+}
+
+/// Health of a database reported by [`HealthCheck`].
+#[derive(Serialize, Clone, Debug)]
+pub struct DbHealth {
+    /// Name of the database.
+    pub name: String,
+    /// Whether the database is healthy.
+    pub healthy: bool,
+    /// How long the check took, in milliseconds.
+    pub latency_ms: u64,
+    /// Error of the failed check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+type PingFn = Arc<dyn Fn() -> BoxFuture<'static, Result<(), Error>> + Send + Sync>;
+
+/// Handler that pings registered pools and reports their health as JSON.
+///
+/// The response status is `503 Service Unavailable` if any database is unhealthy.
+#[derive(Clone)]
+pub struct HealthCheck {
+    checks: Vec<(String, PingFn)>,
+    timeout: Duration,
+}
+impl Default for HealthCheck {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Debug for HealthCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HealthCheck")
+            .field("names", &self.checks.iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+impl HealthCheck {
+    /// Create new `HealthCheck`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            checks: Vec::new(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Register a pool to check.
+    #[inline]
+    pub fn add<P: Pool>(mut self, name: impl Into<String>, pool: P) -> Self {
+        let ping: PingFn = Arc::new(move || {
+            let pool = pool.clone();
+            async move { pool.ping().await }.boxed()
+        });
+        self.checks.push((name.into(), ping));
+        self
+    }
+
+    /// Set the timeout of each check, default is 5 seconds.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Check all registered pools.
+    pub async fn check(&self) -> Vec<DbHealth> {
+        join_all(self.checks.iter().map(|(name, ping)| async move {
+            let started = Instant::now();
+            let error = match tokio::time::timeout(self.timeout, ping()).await {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e.to_string()),
+                Err(_) => Some("timed out".to_owned()),
+            };
+            DbHealth {
+                name: name.clone(),
+                healthy: error.is_none(),
+                latency_ms: started.elapsed().as_millis() as u64,
+                error,
+            }
+        }))
+        .await
+    }
+}
+#[async_trait]
+impl Handler for HealthCheck {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let checks = self.check().await;
+        if checks.iter().any(|check| !check.healthy) {
+            res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        res.render(Json(checks));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct MockPool {
+        healthy: Arc<AtomicBool>,
+        commits: Arc<AtomicUsize>,
+        rollbacks: Arc<AtomicUsize>,
+    }
+    struct MockTransaction(MockPool);
+
+    impl Pool for MockPool {
+        async fn ping(&self) -> Result<(), Error> {
+            if self.healthy.load(Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(Error::other("connection refused"))
+            }
+        }
+    }
+    impl TransactionalPool for MockPool {
+        type Transaction = MockTransaction;
+
+        async fn begin(&self) -> Result<MockTransaction, Error> {
+            Ok(MockTransaction(self.clone()))
+        }
+    }
+    impl Transaction for MockTransaction {
+        async fn commit(self) -> Result<(), Error> {
+            self.0.commits.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+        async fn rollback(self) -> Result<(), Error> {
+            self.0.rollbacks.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[handler]
+    async fn ok(depot: &mut Depot) -> &'static str {
+        assert!(depot.pool::<MockPool>().is_some());
+        assert!(depot.transaction::<MockPool>().is_some());
+        "ok"
+    }
+    #[handler]
+    async fn fail() -> Result<(), StatusError> {
+        Err(StatusError::internal_server_error())
+    }
+
+    #[tokio::test]
+    async fn test_transactional() {
+        let pool = MockPool::default();
+        let router = Router::new()
+            .hoop(DbPool::new(pool.clone()))
+            .hoop(Transactional::new(pool.clone()))
+            .push(Router::with_path("ok").get(ok))
+            .push(Router::with_path("fail").get(fail));
+        let service = Service::new(router);
+
+        let res = TestClient::get("http://127.0.0.1:5800/ok").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert_eq!(pool.commits.load(Ordering::SeqCst), 1);
+
+        TestClient::get("http://127.0.0.1:5800/fail").send(&service).await;
+        assert_eq!(pool.commits.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.rollbacks.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let healthy = MockPool::default();
+        healthy.healthy.store(true, Ordering::SeqCst);
+        let service = Service::new(Router::new().get(HealthCheck::new().add("primary", healthy.clone())));
+        let mut res = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert!(res
+            .take_string()
+            .await
+            .unwrap()
+            .contains(r#""name":"primary","healthy":true"#));
+
+        let checks = HealthCheck::new()
+            .add("primary", healthy)
+            .add("replica", MockPool::default());
+        let service = Service::new(Router::new().get(checks));
+        let mut res = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(res
+            .take_string()
+            .await
+            .unwrap()
+            .contains(r#""error":"connection refused""#));
+    }
+}
@@ -0,0 +1,25 @@
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+
+use super::{Error, Pool, Transaction, TransactionalPool};
+
+impl Pool for DatabaseConnection {
+    async fn ping(&self) -> Result<(), Error> {
+        DatabaseConnection::ping(self).await.map_err(Error::other)
+    }
+}
+impl TransactionalPool for DatabaseConnection {
+    type Transaction = DatabaseTransaction;
+
+    async fn begin(&self) -> Result<Self::Transaction, Error> {
+        TransactionTrait::begin(self).await.map_err(Error::other)
+    }
+}
+impl Transaction for DatabaseTransaction {
+    async fn commit(self) -> Result<(), Error> {
+        DatabaseTransaction::commit(self).await.map_err(Error::other)
+    }
+
+    async fn rollback(self) -> Result<(), Error> {
+        DatabaseTransaction::rollback(self).await.map_err(Error::other)
+    }
+}
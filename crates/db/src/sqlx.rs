@@ -0,0 +1,26 @@
+use sqlx::{Connection, Database};
+
+use super::{Error, Pool, Transaction, TransactionalPool};
+
+impl<DB: Database> Pool for sqlx::Pool<DB> {
+    async fn ping(&self) -> Result<(), Error> {
+        let mut conn = self.acquire().await.map_err(Error::other)?;
+        conn.ping().await.map_err(Error::other)
+    }
+}
+impl<DB: Database> TransactionalPool for sqlx::Pool<DB> {
+    type Transaction = sqlx::Transaction<'static, DB>;
+
+    async fn begin(&self) -> Result<Self::Transaction, Error> {
+        sqlx::Pool::begin(self).await.map_err(Error::other)
+    }
+}
+impl<DB: Database> Transaction for sqlx::Transaction<'static, DB> {
+    async fn commit(self) -> Result<(), Error> {
+        sqlx::Transaction::commit(self).await.map_err(Error::other)
+    }
+
+    async fn rollback(self) -> Result<(), Error> {
+        sqlx::Transaction::rollback(self).await.map_err(Error::other)
+    }
+}
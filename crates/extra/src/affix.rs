@@ -53,8 +53,6 @@
 //! }
 //! ```
 
-use std::any::TypeId;
-
 use salvo_core::handler;
 use salvo_core::prelude::*;
 
@@ -76,12 +74,22 @@ where
     }
 }
 
+struct InjectCell<V>(V);
+impl<T> Affix for InjectCell<T>
+where
+    T: Send + Sync + Clone + 'static,
+{
+    fn attach(&self, depot: &mut Depot) {
+        depot.inject(self.0.clone());
+    }
+}
+
 /// Inject a value into depot.
 /// 
 /// View [module level documentation](index.html) for more details.
 #[inline]
 pub fn inject<V: Send + Sync + Clone + 'static>(value: V) -> AffixList {
-    insert(format!("{:?}", TypeId::of::<V>()), value)
+    AffixList::new().inject(value)
 }
 
 /// Insert a key-value pair into depot.
@@ -109,8 +117,9 @@ impl AffixList {
         AffixList(Vec::new())
     }
     /// Inject a value into depot.
-    pub fn inject<V: Send + Sync + Clone + 'static>(self, value: V) -> Self {
-        self.insert(format!("{:?}", TypeId::of::<V>()), value)
+    pub fn inject<V: Send + Sync + Clone + 'static>(mut self, value: V) -> Self {
+        self.0.push(Box::new(InjectCell(value)));
+        self
     }
 
     /// Insert a key-value pair into depot.
@@ -32,6 +32,7 @@ use std::future::Future;
 use base64::engine::{general_purpose, Engine};
 use salvo_core::http::header::{HeaderName, AUTHORIZATION, PROXY_AUTHORIZATION};
 use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::span::SpanDepotExt;
 use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler};
 
 /// key used when insert into depot.
@@ -152,6 +153,9 @@ where
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
         if let Ok((username, password)) = self.parse_credentials(req) {
             if self.validator.validate(&username, &password, depot).await {
+                if let Some(span) = depot.request_span() {
+                    span.record_user_id(&username);
+                }
                 depot.insert(USERNAME_KEY, username);
                 ctrl.call_next(req, depot, res).await;
                 return;
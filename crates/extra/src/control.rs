@@ -0,0 +1,705 @@
+//! Control endpoints for operating a running server.
+//!
+//! [`ControlPanel`] builds a router exposing runtime knobs:
+//!
+//! | Method | Path | Description |
+//! | --- | --- | --- |
+//! | `GET` | `stats` | Connection stats, maintenance mode and overrides as JSON. |
+//! | `PUT` | `log-level` | Change the log level, the body is the new level or filter directive. |
+//! | `PUT`, `DELETE` | `maintenance` | Turn the [`Maintenance`] mode on or off. |
+//! | `GET`, `PUT`, `DELETE` | `overrides/<name>` | Get, set or remove an [`Overrides`] value, such as a rate limit. |
+//! | `POST` | `shutdown` | Stop the server gracefully, `?timeout=<secs>` forces it after the timeout. |
+//!
+//! Every endpoint is behind the [`ControlAuth`] check given to [`ControlPanel::new`]. Serve the router on its own
+//! listener bound to localhost or a Unix domain socket, so it is never reachable from the public network.
+//!
+//! **WARNING**: [`LocalOnly`] trusts the address of the connected client. Behind a reverse proxy running on the
+//! same host, every request comes from a loopback address, so do not use it on a listener which is reachable
+//! through a proxy, use a check based on credentials instead.
+//!
+//! With the `control-rate-limiter` feature, [`OverrideQuota`] reads the quota of a `salvo-rate-limiter`
+//! `RateLimiter` from [`Overrides`], so the rate limit can be changed by the `overrides/<name>` endpoints.
+//!
+//! Changing the log level and stopping the server depend on how the application is set up, so they are done by
+//! callbacks, endpoints without a callback respond `501 Not Implemented`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::fuse::FlexFactory;
+//! use salvo_core::prelude::*;
+//! use salvo_extra::control::{ConnStats, ControlPanel, LocalOnly, Maintenance, Overrides};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let maintenance = Maintenance::new();
+//!     let stats = ConnStats::new().wrap(FlexFactory::new());
+//!     let server = Server::new(TcpListener::new("0.0.0.0:5800").bind().await).fuse_factory(stats.clone());
+//!     let handle = server.handle();
+//!
+//!     let panel = ControlPanel::new(LocalOnly)
+//!         .maintenance(maintenance.clone())
+//!         .stats(stats)
+//!         .overrides(Overrides::new())
+//!         .on_shutdown(move |timeout| handle.stop_graceful(timeout));
+//!     let control = Server::new(TcpListener::new("127.0.0.1:5899").bind().await);
+//!     tokio::spawn(control.serve(panel.router()));
+//!
+//!     server.serve(Router::new().hoop(maintenance).get(hello)).await;
+//! }
+//! ```
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use salvo_core::fuse::{FuseEvent, FuseFactory, FuseInfo, Fusewire};
+use salvo_core::http::header::{HeaderName, HeaderValue, FORWARDED, RETRY_AFTER};
+use salvo_core::http::{Request, Response, StatusCode, StatusError};
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Router};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+const X_REAL_IP: HeaderName = HeaderName::from_static("x-real-ip");
+
+/// Authorization of requests to the control endpoints.
+#[async_trait]
+pub trait ControlAuth: Send + Sync + 'static {
+    /// Returns `true` if the request is allowed.
+    async fn authorize(&self, req: &Request, depot: &Depot) -> bool;
+}
+#[async_trait]
+impl<F> ControlAuth for F
+where
+    F: Fn(&Request, &Depot) -> bool + Send + Sync + 'static,
+{
+    #[inline]
+    async fn authorize(&self, req: &Request, depot: &Depot) -> bool {
+        self(req, depot)
+    }
+}
+
+/// [`ControlAuth`] allowing clients connected from loopback addresses or Unix domain sockets.
+///
+/// Requests with a `Forwarded`, `X-Forwarded-For` or `X-Real-IP` header are rejected, since they were sent
+/// through a proxy. A proxy which does not add these headers makes every request look local, so only use it on
+/// listeners which no proxy forwards to.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocalOnly;
+#[async_trait]
+impl ControlAuth for LocalOnly {
+    async fn authorize(&self, req: &Request, _depot: &Depot) -> bool {
+        if [FORWARDED, X_FORWARDED_FOR, X_REAL_IP]
+            .iter()
+            .any(|name| req.headers().contains_key(name))
+        {
+            return false;
+        }
+        #[cfg(unix)]
+        if req.remote_addr().is_unix() {
+            return true;
+        }
+        req.remote_addr()
+            .clone()
+            .into_std()
+            .is_some_and(|addr| addr.ip().is_loopback())
+    }
+}
+
+/// Maintenance mode, use it as a hoop to answer requests with `503 Service Unavailable` while it is on.
+#[derive(Clone, Debug, Default)]
+pub struct Maintenance {
+    enabled: Arc<AtomicBool>,
+    retry_after: Option<Duration>,
+}
+impl Maintenance {
+    /// Create a new `Maintenance`, it is off.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `Retry-After` header sent while the maintenance mode is on.
+    #[inline]
+    pub fn retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    /// Returns `true` if the maintenance mode is on.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Turn the maintenance mode on or off.
+    #[inline]
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+}
+#[async_trait]
+impl Handler for Maintenance {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.is_enabled() {
+            if let Some(retry_after) = self.retry_after {
+                res.headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after.as_secs()));
+            }
+            res.render(StatusError::service_unavailable().brief("The server is under maintenance."));
+            ctrl.skip_rest();
+        }
+    }
+}
+
+/// Named values changed at runtime, such as rate limit overrides read by a quota getter.
+#[derive(Clone, Debug, Default)]
+pub struct Overrides {
+    values: Arc<RwLock<BTreeMap<String, Value>>>,
+}
+impl Overrides {
+    /// Create a new empty `Overrides`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a value, `None` if it is not set or is not a `T`.
+    pub fn get<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        let values = self.values.read().unwrap_or_else(|e| e.into_inner());
+        values.get(name).and_then(|value| T::deserialize(value).ok())
+    }
+
+    /// Set a value.
+    pub fn set(&self, name: impl Into<String>, value: Value) {
+        self.values
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.into(), value);
+    }
+
+    /// Remove a value, returns the removed value.
+    pub fn remove(&self, name: &str) -> Option<Value> {
+        self.values.write().unwrap_or_else(|e| e.into_inner()).remove(name)
+    }
+
+    fn to_json(&self) -> Value {
+        json!(&*self.values.read().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Quota getter of `salvo-rate-limiter` which reads the quota from an [`Overrides`] value.
+///
+/// The value is deserialized as the quota, such as `{"limit": 100, "period": [60, 0]}` for a `BasicQuota` of
+/// 100 requests per 60 seconds. The default quota is used while the value is not set or is not a valid quota.
+///
+/// # Example
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_extra::control::{OverrideQuota, Overrides};
+/// use salvo_rate_limiter::{BasicQuota, FixedGuard, MokaStore, RateLimiter, RemoteIpIssuer};
+///
+/// let overrides = Overrides::new();
+/// let limiter = RateLimiter::new(
+///     FixedGuard::new(),
+///     MokaStore::new(),
+///     RemoteIpIssuer,
+///     OverrideQuota::new(overrides.clone(), "rate-limit", BasicQuota::per_second(10)),
+/// );
+/// let router = Router::new().hoop(limiter);
+/// ```
+#[cfg(feature = "control-rate-limiter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "control-rate-limiter")))]
+#[derive(Debug)]
+pub struct OverrideQuota<Q> {
+    overrides: Overrides,
+    name: String,
+    default: Q,
+}
+#[cfg(feature = "control-rate-limiter")]
+impl<Q> OverrideQuota<Q> {
+    /// Create a new `OverrideQuota` reading the override `name`, `default` is used while it is not set.
+    #[inline]
+    pub fn new(overrides: Overrides, name: impl Into<String>, default: Q) -> Self {
+        Self {
+            overrides,
+            name: name.into(),
+            default,
+        }
+    }
+}
+#[cfg(feature = "control-rate-limiter")]
+impl<Key, Q> salvo_rate_limiter::QuotaGetter<Key> for OverrideQuota<Q>
+where
+    Key: std::hash::Hash + Eq + Send + Sync + 'static,
+    Q: DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    type Quota = Q;
+    type Error = std::convert::Infallible;
+
+    async fn get<T>(&self, _key: &T) -> Result<Self::Quota, Self::Error>
+    where
+        Key: std::borrow::Borrow<T>,
+        T: std::hash::Hash + Eq + Sync,
+    {
+        Ok(self.overrides.get(&self.name).unwrap_or_else(|| self.default.clone()))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    accepted: AtomicU64,
+    alive: AtomicU64,
+    read_bytes: AtomicU64,
+    written_bytes: AtomicU64,
+}
+
+/// [`FuseFactory`] counting connections and transferred bytes, set it with `Server::fuse_factory`.
+#[derive(Clone, Default)]
+pub struct ConnStats {
+    counters: Arc<Counters>,
+    inner: Option<Arc<dyn FuseFactory + Send + Sync>>,
+}
+impl Debug for ConnStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnStats").field("counters", &self.counters).finish()
+    }
+}
+impl ConnStats {
+    /// Create a new `ConnStats`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forward the events of connections to the fusewires of another factory, such as a
+    /// [`FlexFactory`](salvo_core::fuse::FlexFactory).
+    #[inline]
+    pub fn wrap(mut self, factory: impl FuseFactory + Send + Sync + 'static) -> Self {
+        self.inner = Some(Arc::new(factory));
+        self
+    }
+
+    /// Number of accepted connections.
+    #[inline]
+    pub fn accepted(&self) -> u64 {
+        self.counters.accepted.load(Ordering::Relaxed)
+    }
+
+    /// Number of open connections.
+    #[inline]
+    pub fn alive(&self) -> u64 {
+        self.counters.alive.load(Ordering::Relaxed)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "accepted": self.accepted(),
+            "alive": self.alive(),
+            "read_bytes": self.counters.read_bytes.load(Ordering::Relaxed),
+            "written_bytes": self.counters.written_bytes.load(Ordering::Relaxed),
+        })
+    }
+}
+impl FuseFactory for ConnStats {
+    fn create(&self, info: FuseInfo) -> Arc<dyn Fusewire + Sync + Send + 'static> {
+        self.counters.accepted.fetch_add(1, Ordering::Relaxed);
+        self.counters.alive.fetch_add(1, Ordering::Relaxed);
+        Arc::new(StatsFusewire {
+            counters: self.counters.clone(),
+            inner: self.inner.as_ref().map(|inner| inner.create(info)),
+        })
+    }
+}
+
+struct StatsFusewire {
+    counters: Arc<Counters>,
+    inner: Option<Arc<dyn Fusewire + Sync + Send + 'static>>,
+}
+#[async_trait]
+impl Fusewire for StatsFusewire {
+    fn event(&self, event: FuseEvent) {
+        match event {
+            FuseEvent::ReadData(len) => {
+                self.counters.read_bytes.fetch_add(len as u64, Ordering::Relaxed);
+            }
+            FuseEvent::WriteData(len) => {
+                self.counters.written_bytes.fetch_add(len as u64, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+        if let Some(inner) = &self.inner {
+            inner.event(event);
+        }
+    }
+    async fn fused(&self) {
+        match &self.inner {
+            Some(inner) => inner.fused().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+impl Drop for StatsFusewire {
+    fn drop(&mut self) {
+        self.counters.alive.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+type LogLevelSetter = dyn Fn(&str) -> Result<(), String> + Send + Sync;
+type ShutdownTrigger = dyn Fn(Option<Duration>) + Send + Sync;
+
+#[derive(Default)]
+struct Shared {
+    maintenance: Option<Maintenance>,
+    stats: Option<ConnStats>,
+    overrides: Option<Overrides>,
+    log_level: Option<Box<LogLevelSetter>>,
+    shutdown: Option<Box<ShutdownTrigger>>,
+}
+
+/// Builder of the control router.
+///
+/// View [module level documentation](index.html) for more details.
+pub struct ControlPanel {
+    shared: Shared,
+    auth: Arc<dyn ControlAuth>,
+}
+impl Debug for ControlPanel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ControlPanel")
+            .field("maintenance", &self.shared.maintenance)
+            .field("stats", &self.shared.stats)
+            .field("overrides", &self.shared.overrides)
+            .finish_non_exhaustive()
+    }
+}
+impl ControlPanel {
+    /// Create a new `ControlPanel` whose endpoints are only allowed by the `auth` check.
+    #[inline]
+    pub fn new(auth: impl ControlAuth) -> Self {
+        Self {
+            shared: Shared::default(),
+            auth: Arc::new(auth),
+        }
+    }
+
+    /// Sets the authorization check of the control endpoints.
+    #[inline]
+    pub fn auth(mut self, auth: impl ControlAuth) -> Self {
+        self.auth = Arc::new(auth);
+        self
+    }
+
+    /// Sets the maintenance mode toggled by the `maintenance` endpoint.
+    #[inline]
+    pub fn maintenance(mut self, maintenance: Maintenance) -> Self {
+        self.shared.maintenance = Some(maintenance);
+        self
+    }
+
+    /// Sets the connection stats reported by the `stats` endpoint.
+    #[inline]
+    pub fn stats(mut self, stats: ConnStats) -> Self {
+        self.shared.stats = Some(stats);
+        self
+    }
+
+    /// Sets the overrides changed by the `overrides` endpoints.
+    #[inline]
+    pub fn overrides(mut self, overrides: Overrides) -> Self {
+        self.shared.overrides = Some(overrides);
+        self
+    }
+
+    /// Sets the callback changing the log level, such as with the reload handle of a `tracing-subscriber` filter.
+    #[inline]
+    pub fn on_log_level<F>(mut self, setter: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.shared.log_level = Some(Box::new(setter));
+        self
+    }
+
+    /// Sets the callback stopping the server, such as with `ServerHandle::stop_graceful`.
+    #[inline]
+    pub fn on_shutdown<F>(mut self, trigger: F) -> Self
+    where
+        F: Fn(Option<Duration>) + Send + Sync + 'static,
+    {
+        self.shared.shutdown = Some(Box::new(trigger));
+        self
+    }
+
+    /// Build the control router.
+    pub fn router(self) -> Router {
+        let shared = Arc::new(self.shared);
+        let endpoint = |action| Endpoint {
+            shared: shared.clone(),
+            action,
+        };
+        Router::new()
+            .hoop(ControlGuard { auth: self.auth })
+            .push(Router::with_path("stats").get(endpoint(Action::Stats)))
+            .push(Router::with_path("log-level").put(endpoint(Action::LogLevel)))
+            .push(
+                Router::with_path("maintenance")
+                    .put(endpoint(Action::MaintenanceOn))
+                    .delete(endpoint(Action::MaintenanceOff)),
+            )
+            .push(
+                Router::with_path("overrides/<name>")
+                    .get(endpoint(Action::GetOverride))
+                    .put(endpoint(Action::SetOverride))
+                    .delete(endpoint(Action::RemoveOverride)),
+            )
+            .push(Router::with_path("shutdown").post(endpoint(Action::Shutdown)))
+    }
+}
+
+struct ControlGuard {
+    auth: Arc<dyn ControlAuth>,
+}
+#[async_trait]
+impl Handler for ControlGuard {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if !self.auth.authorize(req, depot).await {
+            res.render(StatusError::forbidden());
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Action {
+    Stats,
+    LogLevel,
+    MaintenanceOn,
+    MaintenanceOff,
+    GetOverride,
+    SetOverride,
+    RemoveOverride,
+    Shutdown,
+}
+
+struct Endpoint {
+    shared: Arc<Shared>,
+    action: Action,
+}
+#[async_trait]
+impl Handler for Endpoint {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let shared = &*self.shared;
+        let not_implemented = || StatusError::not_implemented().brief("This control is not configured.");
+        match self.action {
+            Action::Stats => {
+                res.render(Json(json!({
+                    "connections": shared.stats.as_ref().map(ConnStats::to_json),
+                    "maintenance": shared.maintenance.as_ref().map(Maintenance::is_enabled),
+                    "overrides": shared.overrides.as_ref().map(Overrides::to_json),
+                })));
+            }
+            Action::LogLevel => {
+                let Some(setter) = &shared.log_level else {
+                    res.render(not_implemented());
+                    return;
+                };
+                let level = match req.payload().await {
+                    Ok(payload) => String::from_utf8_lossy(payload).trim().to_owned(),
+                    Err(e) => {
+                        res.render(StatusError::bad_request().brief(e.to_string()));
+                        return;
+                    }
+                };
+                match setter(&level) {
+                    Ok(()) => {
+                        tracing::info!(%level, "log level is changed");
+                        res.status_code(StatusCode::NO_CONTENT);
+                    }
+                    Err(e) => res.render(StatusError::bad_request().brief(e)),
+                }
+            }
+            Action::MaintenanceOn | Action::MaintenanceOff => {
+                let Some(maintenance) = &shared.maintenance else {
+                    res.render(not_implemented());
+                    return;
+                };
+                let enabled = matches!(self.action, Action::MaintenanceOn);
+                maintenance.set_enabled(enabled);
+                tracing::info!(enabled, "maintenance mode is changed");
+                res.status_code(StatusCode::NO_CONTENT);
+            }
+            Action::GetOverride | Action::SetOverride | Action::RemoveOverride => {
+                let Some(overrides) = &shared.overrides else {
+                    res.render(not_implemented());
+                    return;
+                };
+                let name = req.param::<String>("name").unwrap_or_default();
+                match self.action {
+                    Action::GetOverride => match overrides.get::<Value>(&name) {
+                        Some(value) => res.render(Json(value)),
+                        None => res.render(StatusError::not_found()),
+                    },
+                    Action::SetOverride => match req.parse_json::<Value>().await {
+                        Ok(value) => {
+                            tracing::info!(%name, %value, "override is set");
+                            overrides.set(name, value);
+                            res.status_code(StatusCode::NO_CONTENT);
+                        }
+                        Err(e) => res.render(StatusError::bad_request().brief(e.to_string())),
+                    },
+                    _ => {
+                        overrides.remove(&name);
+                        res.status_code(StatusCode::NO_CONTENT);
+                    }
+                }
+            }
+            Action::Shutdown => {
+                let Some(shutdown) = &shared.shutdown else {
+                    res.render(not_implemented());
+                    return;
+                };
+                let timeout = req.query::<u64>("timeout").map(Duration::from_secs);
+                tracing::info!(?timeout, "shutdown is requested");
+                shutdown(timeout);
+                res.status_code(StatusCode::ACCEPTED);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_control_panel() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        let maintenance = Maintenance::new();
+        let overrides = Overrides::new();
+        let level = Arc::new(Mutex::new(String::new()));
+        let panel = ControlPanel::new(|req: &Request, _depot: &Depot| {
+            req.header::<String>("x-token").as_deref() == Some("secret")
+        })
+        .maintenance(maintenance.clone())
+        .overrides(overrides.clone())
+        .on_log_level({
+            let level = level.clone();
+            move |value| {
+                *level.lock().unwrap() = value.to_owned();
+                Ok(())
+            }
+        });
+        let service = Service::new(
+            Router::new()
+                .push(Router::with_path("control").push(panel.router()))
+                .push(Router::with_path("hello").hoop(maintenance).get(hello)),
+        );
+
+        let res = TestClient::put("http://127.0.0.1:5800/control/maintenance")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+        let res = TestClient::put("http://127.0.0.1:5800/control/maintenance")
+            .add_header("x-token", "secret", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        let res = TestClient::get("http://127.0.0.1:5800/hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        let res = TestClient::put("http://127.0.0.1:5800/control/overrides/rate-limit")
+            .add_header("x-token", "secret", true)
+            .json(&100)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(overrides.get::<u64>("rate-limit"), Some(100));
+
+        let res = TestClient::put("http://127.0.0.1:5800/control/log-level")
+            .add_header("x-token", "secret", true)
+            .text("debug")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(*level.lock().unwrap(), "debug");
+
+        let content = TestClient::get("http://127.0.0.1:5800/control/stats")
+            .add_header("x-token", "secret", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(
+            content,
+            r#"{"connections":null,"maintenance":true,"overrides":{"rate-limit":100}}"#
+        );
+        let res = TestClient::post("http://127.0.0.1:5800/control/shutdown")
+            .add_header("x-token", "secret", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_IMPLEMENTED));
+    }
+
+    #[tokio::test]
+    async fn test_local_only() {
+        let depot = Depot::new();
+        let mut req = Request::new();
+        *req.remote_addr_mut() = "127.0.0.1:8080".parse::<std::net::SocketAddr>().unwrap().into();
+        assert!(LocalOnly.authorize(&req, &depot).await);
+        req.headers_mut()
+            .insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7"));
+        assert!(!LocalOnly.authorize(&req, &depot).await);
+
+        let mut req = Request::new();
+        *req.remote_addr_mut() = "203.0.113.7:8080".parse::<std::net::SocketAddr>().unwrap().into();
+        assert!(!LocalOnly.authorize(&req, &depot).await);
+    }
+
+    #[cfg(feature = "control-rate-limiter")]
+    #[tokio::test]
+    async fn test_override_quota() {
+        use salvo_rate_limiter::{BasicQuota, FixedGuard, MokaStore, RateLimiter};
+
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+        let overrides = Overrides::new();
+        let limiter = RateLimiter::new(
+            FixedGuard::new(),
+            MokaStore::new(),
+            |_: &mut Request, _: &Depot| Some("client"),
+            OverrideQuota::new(overrides.clone(), "rate-limit", BasicQuota::per_minute(3)),
+        );
+        let service = Service::new(Router::new().hoop(limiter).get(hello));
+        for _ in 0..3 {
+            let res = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+            assert_eq!(res.status_code, Some(StatusCode::OK));
+        }
+        let res = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::TOO_MANY_REQUESTS));
+
+        overrides.set("rate-limit", json!({"limit": 5, "period": [60, 0]}));
+        let res = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+    }
+}
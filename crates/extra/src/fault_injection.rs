@@ -0,0 +1,314 @@
+//! Middleware for injecting faults into responses, for resilience testing.
+//!
+//! [`FaultInjector`] applies [`FaultRule`]s to a percentage of the requests which match the rule. A fault can
+//! delay the request, respond with an error, drop the connection or truncate the response body, so that
+//! clients can be tested against a misbehaving backend.
+//!
+//! **NOTE**: This middleware is intended for development and test environments only.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_core::prelude::*;
+//! use salvo_extra::fault_injection::{FaultInjector, FaultRule};
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let injector = FaultInjector::new()
+//!         // Delay 10% of the requests to `/api` by 500ms.
+//!         .rule(FaultRule::latency(Duration::from_millis(500)).percentage(10.0).path("/api"))
+//!         // Fail all requests which contain header `x-chaos: error`.
+//!         .rule(FaultRule::error(StatusCode::SERVICE_UNAVAILABLE).header("x-chaos", "error"));
+//!     let router = Router::new().hoop(injector).push(Router::with_path("api/hello").get(hello));
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+
+use std::io::{Error as IoError, ErrorKind};
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream, StreamExt};
+use salvo_core::http::body::BytesFrame;
+use salvo_core::http::header::{HeaderName, CONTENT_LENGTH};
+use salvo_core::http::{Request, ResBody, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+
+/// A fault which can be injected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// Delay the request before it is handled.
+    Latency(Duration),
+    /// Respond with an error of the status code, the request is not handled.
+    Error(StatusCode),
+    /// Drop the connection without a complete response, the request is not handled.
+    Abort,
+    /// Truncate the response body to the number of bytes, then drop the connection.
+    Truncate(usize),
+}
+
+/// A rule which injects a fault into the matched requests.
+#[derive(Clone, Debug)]
+pub struct FaultRule {
+    fault: Fault,
+    percentage: f64,
+    path: Option<String>,
+    header: Option<(HeaderName, Option<String>)>,
+}
+
+impl FaultRule {
+    /// Create a new `FaultRule` which injects the fault into all requests.
+    #[inline]
+    pub fn new(fault: Fault) -> Self {
+        Self {
+            fault,
+            percentage: 100.0,
+            path: None,
+            header: None,
+        }
+    }
+
+    /// Create a new `FaultRule` which delays the requests.
+    #[inline]
+    pub fn latency(duration: Duration) -> Self {
+        Self::new(Fault::Latency(duration))
+    }
+
+    /// Create a new `FaultRule` which responds with an error.
+    #[inline]
+    pub fn error(status_code: StatusCode) -> Self {
+        Self::new(Fault::Error(status_code))
+    }
+
+    /// Create a new `FaultRule` which drops the connections.
+    #[inline]
+    pub fn abort() -> Self {
+        Self::new(Fault::Abort)
+    }
+
+    /// Create a new `FaultRule` which truncates the response bodies.
+    #[inline]
+    pub fn truncate(len: usize) -> Self {
+        Self::new(Fault::Truncate(len))
+    }
+
+    /// Sets the percentage of the matched requests which the fault is injected into, default is `100`.
+    #[inline]
+    pub fn percentage(mut self, percentage: f64) -> Self {
+        self.percentage = percentage.clamp(0.0, 100.0);
+        self
+    }
+
+    /// Only match the requests whose path starts with `path`.
+    #[inline]
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Only match the requests which contain the header with the value.
+    ///
+    /// # Panics
+    /// Panics if the name is not a valid header name.
+    #[inline]
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("invalid header name");
+        self.header = Some((name, Some(value.into())));
+        self
+    }
+
+    /// Only match the requests which contain the header, regardless of its value.
+    ///
+    /// # Panics
+    /// Panics if the name is not a valid header name.
+    #[inline]
+    pub fn header_present(mut self, name: &str) -> Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("invalid header name");
+        self.header = Some((name, None));
+        self
+    }
+
+    fn matches(&self, req: &Request) -> bool {
+        if let Some(path) = &self.path {
+            if !req.uri().path().starts_with(path.as_str()) {
+                return false;
+            }
+        }
+        if let Some((name, value)) = &self.header {
+            let Some(header) = req.headers().get(name) else {
+                return false;
+            };
+            if value
+                .as_ref()
+                .is_some_and(|value| header.as_bytes() != value.as_bytes())
+            {
+                return false;
+            }
+        }
+        self.percentage >= 100.0 || rand::random::<f64>() * 100.0 < self.percentage
+    }
+}
+
+/// Middleware for injecting faults.
+///
+/// The rules are checked in order, at most one fault is injected into a request.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Default, Clone, Debug)]
+pub struct FaultInjector {
+    rules: Vec<FaultRule>,
+}
+
+impl FaultInjector {
+    /// Create a new `FaultInjector` without rules.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a rule.
+    #[inline]
+    pub fn rule(mut self, rule: FaultRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+#[async_trait]
+impl Handler for FaultInjector {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let Some(rule) = self.rules.iter().find(|rule| rule.matches(req)) else {
+            return;
+        };
+        tracing::debug!(fault = ?rule.fault, path = req.uri().path(), "inject fault");
+        match rule.fault {
+            Fault::Latency(duration) => {
+                tokio::time::sleep(duration).await;
+            }
+            Fault::Error(status_code) => {
+                res.render(StatusError::from_code(status_code).unwrap_or_else(StatusError::internal_server_error));
+                ctrl.skip_rest();
+            }
+            Fault::Abort => {
+                res.status_code(StatusCode::OK);
+                res.stream(stream::once(async {
+                    Err::<BytesFrame, _>(IoError::new(ErrorKind::ConnectionAborted, "fault injected"))
+                }));
+                ctrl.skip_rest();
+            }
+            Fault::Truncate(len) => {
+                ctrl.call_next(req, depot, res).await;
+                let body = res.take_body();
+                res.headers_mut().remove(CONTENT_LENGTH);
+                res.stream(truncate_body(body, len));
+            }
+        }
+    }
+}
+
+/// Pass through at most `len` bytes of the body, then fail the stream to drop the connection.
+fn truncate_body(body: ResBody, len: usize) -> impl Stream<Item = Result<BytesFrame, IoError>> + Send {
+    stream::unfold(Some((body, len)), |state| async move {
+        let (mut body, remaining) = state?;
+        if remaining == 0 {
+            return Some((Err(IoError::new(ErrorKind::ConnectionAborted, "fault injected")), None));
+        }
+        match body.next().await? {
+            Ok(frame) => match frame.into_data() {
+                Ok(mut data) => {
+                    data.truncate(remaining);
+                    let remaining = remaining - data.len();
+                    Some((Ok(BytesFrame::from(data)), Some((body, remaining))))
+                }
+                Err(frame) => Some((Ok(BytesFrame(frame)), Some((body, remaining)))),
+            },
+            Err(e) => Some((Err(e), None)),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[handler]
+    async fn hello() -> &'static str {
+        "hello world"
+    }
+
+    fn injected_service(injector: FaultInjector) -> Service {
+        Service::new(
+            Router::new()
+                .hoop(injector)
+                .push(Router::with_path("api/hello").get(hello)),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fault_error() {
+        let service = injected_service(
+            FaultInjector::new().rule(FaultRule::error(StatusCode::SERVICE_UNAVAILABLE).header("x-chaos", "error")),
+        );
+        let res = TestClient::get("http://127.0.0.1:5800/api/hello")
+            .add_header("x-chaos", "error", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/api/hello").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_fault_percentage_and_path() {
+        let service = injected_service(
+            FaultInjector::new()
+                .rule(FaultRule::error(StatusCode::BAD_GATEWAY).percentage(0.0))
+                .rule(FaultRule::error(StatusCode::GATEWAY_TIMEOUT).path("/other")),
+        );
+        let mut res = TestClient::get("http://127.0.0.1:5800/api/hello").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_fault_latency() {
+        let service = injected_service(FaultInjector::new().rule(FaultRule::latency(Duration::from_millis(100))));
+        let start = std::time::Instant::now();
+        let mut res = TestClient::get("http://127.0.0.1:5800/api/hello").send(&service).await;
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(res.take_string().await.unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_fault_abort_and_truncate() {
+        let service = injected_service(FaultInjector::new().rule(FaultRule::abort()));
+        let mut res = TestClient::get("http://127.0.0.1:5800/api/hello").send(&service).await;
+        assert!(res.take_string().await.is_err());
+
+        let service = injected_service(FaultInjector::new().rule(FaultRule::truncate(5)));
+        let mut res = TestClient::get("http://127.0.0.1:5800/api/hello").send(&service).await;
+        let mut body = res.take_body();
+        let mut data = Vec::new();
+        let mut failed = false;
+        while let Some(frame) = body.next().await {
+            match frame {
+                Ok(frame) => data.extend_from_slice(&frame.into_data().unwrap_or_default()),
+                Err(_) => failed = true,
+            }
+        }
+        assert_eq!(data, b"hello");
+        assert!(failed);
+    }
+}
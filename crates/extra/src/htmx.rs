@@ -0,0 +1,478 @@
+//! Helpers for [htmx](https://htmx.org) requests and responses.
+//!
+//! [`HtmxRequestExt`] reads the `HX-*` request headers, the response helpers set the `HX-*` response headers, and
+//! [`Fragment`] renders only a part of the page for htmx requests.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::htmx::{Fragment, HtmxRequestExt, HxTrigger, OobSwap};
+//!
+//! #[handler]
+//! async fn todos(req: &mut Request, res: &mut Response) -> Fragment {
+//!     let list = "<ul id=\"todos\"><li>Write docs</li></ul>";
+//!     if req.is_htmx() {
+//!         res.render(HxTrigger::new().event("todos-loaded"));
+//!     }
+//!     Fragment::new(format!("<html><body>{list}</body></html>"), list)
+//! }
+//!
+//! #[handler]
+//! async fn add_todo(res: &mut Response) {
+//!     res.render(HxTrigger::new().event_with("todo-added", serde_json::json!({"id": 1})));
+//!     res.render(OobSwap::new("<li>New todo</li>").swap("beforeend", "#todos", "<li>Saved</li>"));
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     let router = Router::new().get(todos).post(add_todo);
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::fmt::Write;
+
+use salvo_core::http::header::{HeaderName, HeaderValue, VARY};
+use salvo_core::writing::Text;
+use salvo_core::{async_trait, Depot, Request, Response, Scribe, Writer};
+use serde_json::{Map, Value};
+
+/// `HX-Boosted` request header.
+pub const HX_BOOSTED: HeaderName = HeaderName::from_static("hx-boosted");
+/// `HX-Current-URL` request header.
+pub const HX_CURRENT_URL: HeaderName = HeaderName::from_static("hx-current-url");
+/// `HX-History-Restore-Request` request header.
+pub const HX_HISTORY_RESTORE_REQUEST: HeaderName = HeaderName::from_static("hx-history-restore-request");
+/// `HX-Prompt` request header.
+pub const HX_PROMPT: HeaderName = HeaderName::from_static("hx-prompt");
+/// `HX-Request` request header.
+pub const HX_REQUEST: HeaderName = HeaderName::from_static("hx-request");
+/// `HX-Target` request header.
+pub const HX_TARGET: HeaderName = HeaderName::from_static("hx-target");
+/// `HX-Trigger` request and response header.
+pub const HX_TRIGGER: HeaderName = HeaderName::from_static("hx-trigger");
+/// `HX-Trigger-Name` request header.
+pub const HX_TRIGGER_NAME: HeaderName = HeaderName::from_static("hx-trigger-name");
+/// `HX-Location` response header.
+pub const HX_LOCATION: HeaderName = HeaderName::from_static("hx-location");
+/// `HX-Push-Url` response header.
+pub const HX_PUSH_URL: HeaderName = HeaderName::from_static("hx-push-url");
+/// `HX-Redirect` response header.
+pub const HX_REDIRECT: HeaderName = HeaderName::from_static("hx-redirect");
+/// `HX-Refresh` response header.
+pub const HX_REFRESH: HeaderName = HeaderName::from_static("hx-refresh");
+/// `HX-Replace-Url` response header.
+pub const HX_REPLACE_URL: HeaderName = HeaderName::from_static("hx-replace-url");
+/// `HX-Reswap` response header.
+pub const HX_RESWAP: HeaderName = HeaderName::from_static("hx-reswap");
+/// `HX-Retarget` response header.
+pub const HX_RETARGET: HeaderName = HeaderName::from_static("hx-retarget");
+/// `HX-Reselect` response header.
+pub const HX_RESELECT: HeaderName = HeaderName::from_static("hx-reselect");
+/// `HX-Trigger-After-Settle` response header.
+pub const HX_TRIGGER_AFTER_SETTLE: HeaderName = HeaderName::from_static("hx-trigger-after-settle");
+/// `HX-Trigger-After-Swap` response header.
+pub const HX_TRIGGER_AFTER_SWAP: HeaderName = HeaderName::from_static("hx-trigger-after-swap");
+
+/// Information about a request sent by htmx.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct HxRequest {
+    /// Whether the request is sent by an element using `hx-boost`.
+    pub boosted: bool,
+    /// Current URL of the browser.
+    pub current_url: Option<String>,
+    /// Whether the request is for history restoration after a miss in the local history cache.
+    pub history_restore_request: bool,
+    /// User response to an `hx-prompt`.
+    pub prompt: Option<String>,
+    /// Id of the target element.
+    pub target: Option<String>,
+    /// Id of the triggered element.
+    pub trigger: Option<String>,
+    /// Name of the triggered element.
+    pub trigger_name: Option<String>,
+}
+
+/// Extension for Request.
+pub trait HtmxRequestExt {
+    /// Whether the request is sent by htmx.
+    fn is_htmx(&self) -> bool;
+
+    /// Get the information of the htmx request, returns `None` if the request is not sent by htmx.
+    fn htmx(&self) -> Option<HxRequest>;
+}
+impl HtmxRequestExt for Request {
+    #[inline]
+    fn is_htmx(&self) -> bool {
+        header_is_true(self, &HX_REQUEST)
+    }
+
+    fn htmx(&self) -> Option<HxRequest> {
+        if !self.is_htmx() {
+            return None;
+        }
+        let header = |name: &HeaderName| self.header::<String>(name);
+        Some(HxRequest {
+            boosted: header_is_true(self, &HX_BOOSTED),
+            current_url: header(&HX_CURRENT_URL),
+            history_restore_request: header_is_true(self, &HX_HISTORY_RESTORE_REQUEST),
+            prompt: header(&HX_PROMPT),
+            target: header(&HX_TARGET),
+            trigger: header(&HX_TRIGGER),
+            trigger_name: header(&HX_TRIGGER_NAME),
+        })
+    }
+}
+
+fn header_is_true(req: &Request, name: &HeaderName) -> bool {
+    req.headers().get(name).is_some_and(|value| value == "true")
+}
+
+fn insert_header(res: &mut Response, name: HeaderName, value: &str) {
+    match HeaderValue::from_str(value) {
+        Ok(value) => {
+            res.headers_mut().insert(name, value);
+        }
+        Err(e) => tracing::error!(error = ?e, header = %name, "invalid htmx header value"),
+    }
+}
+
+/// Redirect the browser to a URL with a full page reload, with the `HX-Redirect` header.
+#[derive(Clone, Debug)]
+pub struct HxRedirect(pub String);
+impl HxRedirect {
+    /// Create new `HxRedirect`.
+    #[inline]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self(url.into())
+    }
+}
+impl Scribe for HxRedirect {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        insert_header(res, HX_REDIRECT, &self.0);
+    }
+}
+
+/// Navigate to a URL without a full page reload, with the `HX-Location` header.
+#[derive(Clone, Debug)]
+pub struct HxLocation(pub String);
+impl HxLocation {
+    /// Create new `HxLocation`.
+    #[inline]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self(url.into())
+    }
+}
+impl Scribe for HxLocation {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        insert_header(res, HX_LOCATION, &self.0);
+    }
+}
+
+/// Make the browser do a full page refresh, with the `HX-Refresh` header.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HxRefresh;
+impl Scribe for HxRefresh {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        res.headers_mut().insert(HX_REFRESH, HeaderValue::from_static("true"));
+    }
+}
+
+/// Push a URL into the browser history, with the `HX-Push-Url` header.
+#[derive(Clone, Debug)]
+pub struct HxPushUrl(pub String);
+impl HxPushUrl {
+    /// Create new `HxPushUrl`.
+    #[inline]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self(url.into())
+    }
+}
+impl Scribe for HxPushUrl {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        insert_header(res, HX_PUSH_URL, &self.0);
+    }
+}
+
+/// Replace the current URL in the browser location bar, with the `HX-Replace-Url` header.
+#[derive(Clone, Debug)]
+pub struct HxReplaceUrl(pub String);
+impl HxReplaceUrl {
+    /// Create new `HxReplaceUrl`.
+    #[inline]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self(url.into())
+    }
+}
+impl Scribe for HxReplaceUrl {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        insert_header(res, HX_REPLACE_URL, &self.0);
+    }
+}
+
+/// Change how the response is swapped, with the `HX-Reswap` header, e.g. `outerHTML` or `beforeend`.
+#[derive(Clone, Debug)]
+pub struct HxReswap(pub String);
+impl HxReswap {
+    /// Create new `HxReswap`.
+    #[inline]
+    pub fn new(swap: impl Into<String>) -> Self {
+        Self(swap.into())
+    }
+}
+impl Scribe for HxReswap {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        insert_header(res, HX_RESWAP, &self.0);
+    }
+}
+
+/// Change the target element of the response with a CSS selector, with the `HX-Retarget` header.
+#[derive(Clone, Debug)]
+pub struct HxRetarget(pub String);
+impl HxRetarget {
+    /// Create new `HxRetarget`.
+    #[inline]
+    pub fn new(selector: impl Into<String>) -> Self {
+        Self(selector.into())
+    }
+}
+impl Scribe for HxRetarget {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        insert_header(res, HX_RETARGET, &self.0);
+    }
+}
+
+/// When the events of [`HxTrigger`] are triggered on the client.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TriggerTiming {
+    /// As soon as the response is received, with the `HX-Trigger` header.
+    #[default]
+    Receive,
+    /// After the settling step, with the `HX-Trigger-After-Settle` header.
+    AfterSettle,
+    /// After the swap step, with the `HX-Trigger-After-Swap` header.
+    AfterSwap,
+}
+
+/// Trigger client side events.
+///
+/// Events without details are sent as a list of names, otherwise they are sent as a JSON object.
+#[derive(Clone, Debug, Default)]
+pub struct HxTrigger {
+    events: Vec<(String, Option<Value>)>,
+    timing: TriggerTiming,
+}
+impl HxTrigger {
+    /// Create new `HxTrigger`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trigger an event.
+    #[inline]
+    pub fn event(mut self, name: impl Into<String>) -> Self {
+        self.events.push((name.into(), None));
+        self
+    }
+
+    /// Trigger an event with details.
+    #[inline]
+    pub fn event_with(mut self, name: impl Into<String>, detail: impl Into<Value>) -> Self {
+        self.events.push((name.into(), Some(detail.into())));
+        self
+    }
+
+    /// Set when the events are triggered, default is [`TriggerTiming::Receive`].
+    #[inline]
+    pub fn timing(mut self, timing: TriggerTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Get the value of the header.
+    pub fn header_value(&self) -> String {
+        if self.events.iter().all(|(_, detail)| detail.is_none()) {
+            self.events
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        } else {
+            let events = self
+                .events
+                .iter()
+                .map(|(name, detail)| (name.clone(), detail.clone().unwrap_or(Value::Null)))
+                .collect::<Map<_, _>>();
+            Value::Object(events).to_string()
+        }
+    }
+}
+impl Scribe for HxTrigger {
+    fn render(self, res: &mut Response) {
+        let name = match self.timing {
+            TriggerTiming::Receive => HX_TRIGGER,
+            TriggerTiming::AfterSettle => HX_TRIGGER_AFTER_SETTLE,
+            TriggerTiming::AfterSwap => HX_TRIGGER_AFTER_SWAP,
+        };
+        insert_header(res, name, &self.header_value());
+    }
+}
+
+/// Render a fragment of the page for htmx requests, and the full page for other requests.
+///
+/// History restoration requests get the full page, since htmx swaps it into the whole body. The `Vary: HX-Request`
+/// header is added so that caches keep both variants apart.
+#[derive(Clone, Debug)]
+pub struct Fragment {
+    full: String,
+    fragment: String,
+}
+impl Fragment {
+    /// Create new `Fragment` with the HTML of the full page and of the fragment.
+    #[inline]
+    pub fn new(full: impl Into<String>, fragment: impl Into<String>) -> Self {
+        Self {
+            full: full.into(),
+            fragment: fragment.into(),
+        }
+    }
+}
+#[async_trait]
+impl Writer for Fragment {
+    async fn write(self, req: &mut Request, _depot: &mut Depot, res: &mut Response) {
+        res.headers_mut().append(VARY, HeaderValue::from_static("HX-Request"));
+        let partial = req.is_htmx() && !header_is_true(req, &HX_HISTORY_RESTORE_REQUEST);
+        if partial {
+            res.render(Text::Html(self.fragment));
+        } else {
+            res.render(Text::Html(self.full));
+        }
+    }
+}
+
+/// Builder of a response with out of band swaps, which update other elements beside the target.
+#[derive(Clone, Debug, Default)]
+pub struct OobSwap {
+    html: String,
+}
+impl OobSwap {
+    /// Create new `OobSwap` with the HTML swapped into the target.
+    #[inline]
+    pub fn new(primary: impl Into<String>) -> Self {
+        Self { html: primary.into() }
+    }
+
+    /// Replace the element with the id by the element with the same id and the content.
+    pub fn replace(mut self, id: &str, content: &str) -> Self {
+        let _ = write!(
+            self.html,
+            r#"<div id="{}" hx-swap-oob="true">{content}</div>"#,
+            escape_attr(id)
+        );
+        self
+    }
+
+    /// Swap the content into the elements matching the CSS selector, with a swap strategy such as `innerHTML` or
+    /// `beforeend`.
+    pub fn swap(mut self, strategy: &str, selector: &str, content: &str) -> Self {
+        let _ = write!(
+            self.html,
+            r#"<div hx-swap-oob="{}:{}">{content}</div>"#,
+            escape_attr(strategy),
+            escape_attr(selector)
+        );
+        self
+    }
+
+    /// Get the HTML of the response.
+    #[inline]
+    pub fn into_html(self) -> String {
+        self.html
+    }
+}
+impl Scribe for OobSwap {
+    #[inline]
+    fn render(self, res: &mut Response) {
+        res.render(Text::Html(self.html));
+    }
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde_json::json;
+
+    use super::*;
+
+    #[handler]
+    async fn page(req: &mut Request, res: &mut Response) -> Fragment {
+        if let Some(htmx) = req.htmx() {
+            res.render(HxTrigger::new().event("loaded").event("counted"));
+            res.render(HxPushUrl::new(htmx.current_url.unwrap_or_default()));
+        }
+        Fragment::new("<html><body><p>hi</p></body></html>", "<p>hi</p>")
+    }
+
+    #[tokio::test]
+    async fn test_fragment() {
+        let service = Service::new(Router::new().get(page));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "<html><body><p>hi</p></body></html>");
+        assert!(res.headers().get(HX_TRIGGER).is_none());
+        assert_eq!(res.headers().get(VARY).unwrap(), "HX-Request");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header(HX_REQUEST, "true", true)
+            .add_header(HX_CURRENT_URL, "http://127.0.0.1:5801/list", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "<p>hi</p>");
+        assert_eq!(res.headers().get(HX_TRIGGER).unwrap(), "loaded, counted");
+        assert_eq!(res.headers().get(HX_PUSH_URL).unwrap(), "http://127.0.0.1:5801/list");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header(HX_REQUEST, "true", true)
+            .add_header(HX_HISTORY_RESTORE_REQUEST, "true", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "<html><body><p>hi</p></body></html>");
+    }
+
+    #[test]
+    fn test_trigger_with_detail() {
+        let trigger = HxTrigger::new().event("closed").event_with("saved", json!({"id": 1}));
+        assert_eq!(trigger.header_value(), r#"{"closed":null,"saved":{"id":1}}"#);
+    }
+
+    #[test]
+    fn test_oob_swap() {
+        let html = OobSwap::new("<li>a</li>")
+            .replace("count", "2")
+            .swap("beforeend", "#log", "<p>added</p>")
+            .into_html();
+        assert_eq!(
+            html,
+            r#"<li>a</li><div id="count" hx-swap-oob="true">2</div><div hx-swap-oob="beforeend:#log"><p>added</p></div>"#
+        );
+    }
+}
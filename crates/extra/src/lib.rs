@@ -14,13 +14,20 @@
 //! | [`caching-headers`](caching_headers) | Middleware for setting caching headers |
 //! | [`catch-panic`](catch_panic) | Middleware for catching panics |
 //! | [`concurrency-limiter`](concurrency_limiter) | Middleware for limiting concurrency |
+//! | [`control`] | Router for administrating a running server |
+//! | [`fault-injection`](fault_injection) | Middleware for injecting faults for resilience testing |
 //! | [`force-https`](force_https) | Middleware for forcing HTTPS |
+//! | [`htmx`] | Helpers for htmx requests and responses |
 //! | [`logging`] | Middleware for logging requests and responses |
+//! | [`metering`] | Middleware for metering the usage of each principal |
+//! | [`recorder`] | Middleware for recording requests and responses, and replaying them |
 //! | [`request-id`](request_id) | Middleware for setting a request ID |
+//! | [`signed-url`](signed_url) | Helpers for signing URLs and middleware for validating them |
 //! | [`size-limiter`](size_limiter) | Middleware for limiting request size |
 //! | [`sse`] | Server-Sent Events (SSE) middleware |
 //! | [`timeout`] | Middleware for setting a timeout |
 //! | [`trailing-slash`](trailing_slash) | Middleware for handling trailing slashes |
+//! | [`tus`] | Router for resumable uploads with the tus protocol |
 //! | [`websocket`] | WebSocket implementation |
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
@@ -39,6 +46,10 @@ cfg_feature! {
     pub mod affix;
 }
 
+cfg_feature! {
+    #![feature = "fault-injection"]
+    pub mod fault_injection;
+}
 cfg_feature! {
     #![feature = "force-https"]
     pub mod force_https;
@@ -80,7 +91,31 @@ cfg_feature! {
     #![feature = "caching-headers"]
     pub mod caching_headers;
 }
+cfg_feature! {
+    #![feature = "recorder"]
+    pub mod recorder;
+}
 cfg_feature! {
     #![feature = "request-id"]
     pub mod request_id;
 }
+cfg_feature! {
+    #![feature = "htmx"]
+    pub mod htmx;
+}
+cfg_feature! {
+    #![feature = "signed-url"]
+    pub mod signed_url;
+}
+cfg_feature! {
+    #![feature = "control"]
+    pub mod control;
+}
+cfg_feature! {
+    #![feature = "tus"]
+    pub mod tus;
+}
+cfg_feature! {
+    #![feature = "metering"]
+    pub mod metering;
+}
@@ -0,0 +1,538 @@
+//! Middleware for metering the usage of each principal, such as an API key or a user.
+//!
+//! [`Meter`] records the number of requests, the bytes received and sent and the compute units reported by handlers
+//! with [`MeteringDepotExt::add_compute_units`]. Usage is grouped by billing [`Period`], such as `2024-05` for
+//! monthly periods, aggregated in memory and flushed to a [`UsageStore`] periodically, so API products can bill
+//! their users from the store.
+//!
+//! [`Meter::spawn_flusher`] flushes the usage on an interval with the server [`Tasks`], and once more when the server
+//! stops, after the last requests are handled. Without it, usage is only flushed by the first request after the
+//! interval, or by calling [`Meter::flush`].
+//!
+//! A quota of requests per period can be enforced with [`Meter::quota`], requests of a principal which used up its
+//! quota are rejected with `429 Too Many Requests`. The usage is checked before the request is handled, so
+//! concurrent requests may exceed the quota slightly.
+//!
+//! [`Meter::usage`] queries the usage of a principal, [`Meter::router`] exposes it as JSON.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::metering::{Meter, MemoryUsageStore, MeteringDepotExt};
+//!
+//! #[handler]
+//! async fn search(depot: &mut Depot) -> &'static str {
+//!     depot.add_compute_units(5);
+//!     "[]"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let meter = Meter::new(
+//!         |req: &Request, _depot: &Depot| req.header::<String>("x-api-key"),
+//!         MemoryUsageStore::new(),
+//!     )
+//!     .quota(|_principal: &str| Some(10_000));
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     let server = Server::new(acceptor);
+//!     meter.spawn_flusher(server.tasks());
+//!     let router = Router::new()
+//!         .push(Router::with_path("usage").push(meter.router()))
+//!         .push(Router::with_path("search").hoop(meter).get(search));
+//!     server.serve(router).await;
+//! }
+//! ```
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::ops::{Add, AddAssign};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use salvo_core::http::body::Body;
+use salvo_core::http::header::CONTENT_LENGTH;
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::tasks::Tasks;
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Router};
+use serde::{Deserialize, Serialize};
+
+/// Key of the compute units reported by handlers in the depot.
+pub const COMPUTE_UNITS_KEY: &str = "::salvo::metering::compute_units";
+
+/// Usage of a principal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Usage {
+    /// Number of requests.
+    pub requests: u64,
+    /// Bytes received in request bodies.
+    pub bytes_in: u64,
+    /// Bytes sent in response bodies, streamed bodies without `content-length` are not counted.
+    pub bytes_out: u64,
+    /// Compute units reported by handlers.
+    pub compute_units: u64,
+}
+impl Add for Usage {
+    type Output = Self;
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+impl AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        self.requests += other.requests;
+        self.bytes_in += other.bytes_in;
+        self.bytes_out += other.bytes_out;
+        self.compute_units += other.compute_units;
+    }
+}
+
+/// Billing period usage is grouped by.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Period {
+    /// Days in UTC, such as `2024-05-31`.
+    Daily,
+    /// Months in UTC, such as `2024-05`.
+    #[default]
+    Monthly,
+}
+impl Period {
+    /// Get the key of the period containing `time`.
+    pub fn key(self, time: SystemTime) -> String {
+        let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let (year, month, day) = civil_from_days((secs / (24 * 60 * 60)) as i64);
+        match self {
+            Self::Daily => format!("{year:04}-{month:02}-{day:02}"),
+            Self::Monthly => format!("{year:04}-{month:02}"),
+        }
+    }
+}
+
+/// Converts days since the Unix epoch to a date in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Identifies the principal of a request, requests without principal are not metered.
+pub trait PrincipalIssuer: Send + Sync + 'static {
+    /// Issue the principal of `req`.
+    fn issue(&self, req: &Request, depot: &Depot) -> Option<String>;
+}
+impl<F> PrincipalIssuer for F
+where
+    F: Fn(&Request, &Depot) -> Option<String> + Send + Sync + 'static,
+{
+    fn issue(&self, req: &Request, depot: &Depot) -> Option<String> {
+        self(req, depot)
+    }
+}
+
+/// Storage of usage.
+#[async_trait]
+pub trait UsageStore: Send + Sync + 'static {
+    /// Add `usage` to the usage of `principal` in `period`.
+    async fn add(&self, principal: &str, period: &str, usage: Usage) -> Result<(), Error>;
+    /// Get the usage of `principal` in `period`.
+    async fn get(&self, principal: &str, period: &str) -> Result<Usage, Error>;
+    /// List the usage of all principals in `period`.
+    async fn list(&self, period: &str) -> Result<Vec<(String, Usage)>, Error>;
+}
+
+/// [`UsageStore`] keeping usage in memory.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryUsageStore {
+    usage: Arc<Mutex<HashMap<(String, String), Usage>>>,
+}
+impl MemoryUsageStore {
+    /// Create a new `MemoryUsageStore`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+#[async_trait]
+impl UsageStore for MemoryUsageStore {
+    async fn add(&self, principal: &str, period: &str, usage: Usage) -> Result<(), Error> {
+        *self
+            .usage
+            .lock()
+            .expect("lock poisoned")
+            .entry((principal.to_owned(), period.to_owned()))
+            .or_default() += usage;
+        Ok(())
+    }
+    async fn get(&self, principal: &str, period: &str) -> Result<Usage, Error> {
+        Ok(self
+            .usage
+            .lock()
+            .expect("lock poisoned")
+            .get(&(principal.to_owned(), period.to_owned()))
+            .copied()
+            .unwrap_or_default())
+    }
+    async fn list(&self, period: &str) -> Result<Vec<(String, Usage)>, Error> {
+        Ok(self
+            .usage
+            .lock()
+            .expect("lock poisoned")
+            .iter()
+            .filter(|((_, p), _)| p == period)
+            .map(|((principal, _), usage)| (principal.clone(), *usage))
+            .collect())
+    }
+}
+
+/// Extension of [`Depot`] for reporting the compute units of the current request.
+pub trait MeteringDepotExt {
+    /// Add `units` to the compute units of the current request.
+    fn add_compute_units(&mut self, units: u64) -> &mut Self;
+    /// Get the compute units of the current request.
+    fn compute_units(&self) -> u64;
+}
+impl MeteringDepotExt for Depot {
+    #[inline]
+    fn add_compute_units(&mut self, units: u64) -> &mut Self {
+        let units = self.compute_units() + units;
+        self.insert(COMPUTE_UNITS_KEY, units)
+    }
+    #[inline]
+    fn compute_units(&self) -> u64 {
+        self.get::<u64>(COMPUTE_UNITS_KEY).copied().unwrap_or_default()
+    }
+}
+
+type QuotaFn = dyn Fn(&str) -> Option<u64> + Send + Sync;
+
+struct Pending {
+    usage: HashMap<(String, String), Usage>,
+    flushed_at: Instant,
+}
+
+/// Middleware metering the usage of each principal.
+///
+/// Cloned meters share their pending usage. View [module level documentation](index.html) for more details.
+#[derive(Clone)]
+pub struct Meter {
+    issuer: Arc<dyn PrincipalIssuer>,
+    store: Arc<dyn UsageStore>,
+    period: Period,
+    flush_interval: Duration,
+    quota: Option<Arc<QuotaFn>>,
+    pending: Arc<Mutex<Pending>>,
+}
+impl Debug for Meter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Meter")
+            .field("period", &self.period)
+            .field("flush_interval", &self.flush_interval)
+            .finish_non_exhaustive()
+    }
+}
+impl Meter {
+    /// Create a new `Meter` identifying principals with `issuer` and storing their usage in `store`.
+    #[inline]
+    pub fn new(issuer: impl PrincipalIssuer, store: impl UsageStore) -> Self {
+        Self {
+            issuer: Arc::new(issuer),
+            store: Arc::new(store),
+            period: Period::default(),
+            flush_interval: Duration::from_secs(10),
+            quota: None,
+            pending: Arc::new(Mutex::new(Pending {
+                usage: HashMap::new(),
+                flushed_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Sets the billing period, the default is [`Period::Monthly`].
+    #[inline]
+    pub fn period(mut self, period: Period) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Sets the interval the aggregated usage is flushed to the store at, the default is 10 seconds.
+    ///
+    /// Usage is flushed by the first request after the interval, or by the task spawned with
+    /// [`spawn_flusher`](Self::spawn_flusher).
+    #[inline]
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Sets the callback returning the max number of requests of a principal per period, `None` is unlimited.
+    #[inline]
+    pub fn quota<F>(mut self, quota: F) -> Self
+    where
+        F: Fn(&str) -> Option<u64> + Send + Sync + 'static,
+    {
+        self.quota = Some(Arc::new(quota));
+        self
+    }
+
+    /// Get the key of the current period.
+    #[inline]
+    pub fn current_period(&self) -> String {
+        self.period.key(SystemTime::now())
+    }
+
+    /// Get the usage of `principal` in `period`, including the usage not flushed yet.
+    pub async fn usage(&self, principal: &str, period: &str) -> Result<Usage, Error> {
+        let stored = self.store.get(principal, period).await?;
+        let pending = self
+            .pending
+            .lock()
+            .expect("lock poisoned")
+            .usage
+            .get(&(principal.to_owned(), period.to_owned()))
+            .copied()
+            .unwrap_or_default();
+        Ok(stored + pending)
+    }
+
+    /// Flush the aggregated usage to the store, usage which fails to be stored is kept for the next flush.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let usage = {
+            let mut pending = self.pending.lock().expect("lock poisoned");
+            pending.flushed_at = Instant::now();
+            std::mem::take(&mut pending.usage)
+        };
+        let mut result = Ok(());
+        let mut failed = vec![];
+        for ((principal, period), usage) in usage {
+            if let Err(e) = self.store.add(&principal, &period, usage).await {
+                result = Err(e);
+                failed.push(((principal, period), usage));
+            }
+        }
+        if !failed.is_empty() {
+            let mut pending = self.pending.lock().expect("lock poisoned");
+            for (key, usage) in failed {
+                *pending.usage.entry(key).or_default() += usage;
+            }
+        }
+        result
+    }
+
+    /// Spawn a task flushing the usage every flush interval on `tasks`, and flush it once more when `tasks` are shut
+    /// down, which [`Server`](salvo_core::Server) does after all connections are closed.
+    pub fn spawn_flusher(&self, tasks: &Tasks) {
+        let meter = self.clone();
+        tasks.spawn_periodic("metering-flush", self.flush_interval, move || {
+            let meter = meter.clone();
+            async move {
+                if let Err(e) = meter.flush().await {
+                    tracing::error!(error = ?e, "failed to flush usage");
+                }
+            }
+        });
+        let meter = self.clone();
+        tasks.on_shutdown(move || async move {
+            if let Err(e) = meter.flush().await {
+                tracing::error!(error = ?e, "failed to flush usage");
+            }
+        });
+    }
+
+    /// Build a router exposing the usage of a principal as JSON at `<principal>`, `?period=` selects another period
+    /// than the current one.
+    ///
+    /// Protect it with an authorization middleware, it exposes the usage of every principal.
+    pub fn router(&self) -> Router {
+        Router::with_path("<principal>").get(UsageQuery { meter: self.clone() })
+    }
+
+    fn record(&self, principal: String, period: String, usage: Usage) -> bool {
+        let mut pending = self.pending.lock().expect("lock poisoned");
+        *pending.usage.entry((principal, period)).or_default() += usage;
+        pending.flushed_at.elapsed() >= self.flush_interval
+    }
+}
+
+#[async_trait]
+impl Handler for Meter {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let Some(principal) = self.issuer.issue(req, depot) else {
+            return;
+        };
+        let period = self.current_period();
+        if let Some(limit) = self.quota.as_ref().and_then(|quota| quota(&principal)) {
+            match self.usage(&principal, &period).await {
+                Ok(usage) if usage.requests >= limit => {
+                    res.render(StatusError::too_many_requests().brief("The quota of this period is used up."));
+                    ctrl.skip_rest();
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!(error = ?e, "failed to get usage"),
+            }
+        }
+
+        let bytes_in = req
+            .header::<u64>(CONTENT_LENGTH)
+            .or_else(|| req.body().size_hint().exact())
+            .unwrap_or_default();
+        ctrl.call_next(req, depot, res).await;
+        let bytes_out = res
+            .body
+            .size()
+            .or_else(|| res.headers().get(CONTENT_LENGTH)?.to_str().ok()?.parse().ok())
+            .unwrap_or_default();
+        let usage = Usage {
+            requests: 1,
+            bytes_in,
+            bytes_out,
+            compute_units: depot.compute_units(),
+        };
+        if self.record(principal, period, usage) {
+            if let Err(e) = self.flush().await {
+                tracing::error!(error = ?e, "failed to flush usage");
+            }
+        }
+    }
+}
+
+struct UsageQuery {
+    meter: Meter,
+}
+#[async_trait]
+impl Handler for UsageQuery {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let principal = req.param::<String>("principal").unwrap_or_default();
+        let period = req
+            .query::<String>("period")
+            .unwrap_or_else(|| self.meter.current_period());
+        match self.meter.usage(&principal, &period).await {
+            Ok(usage) => res.render(Json(serde_json::json!({
+                "principal": principal,
+                "period": period,
+                "usage": usage,
+            }))),
+            Err(e) => {
+                tracing::error!(error = ?e, "failed to get usage");
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_period_key() {
+        let time = UNIX_EPOCH + Duration::from_secs(19_782 * 24 * 60 * 60 + 3600);
+        assert_eq!(Period::Monthly.key(time), "2024-02");
+        assert_eq!(Period::Daily.key(time), "2024-02-29");
+        let time = UNIX_EPOCH + Duration::from_secs(10_956 * 24 * 60 * 60);
+        assert_eq!(Period::Daily.key(time), "1999-12-31");
+    }
+
+    #[tokio::test]
+    async fn test_meter() {
+        #[handler]
+        async fn search(depot: &mut Depot) -> &'static str {
+            depot.add_compute_units(5);
+            "result"
+        }
+        let store = MemoryUsageStore::new();
+        let meter = Meter::new(
+            |req: &Request, _depot: &Depot| req.header::<String>("x-api-key"),
+            store.clone(),
+        )
+        .quota(|principal: &str| (principal == "free").then_some(2));
+        let service = Service::new(
+            Router::new()
+                .push(Router::with_path("usage").push(meter.router()))
+                .push(Router::with_path("search").hoop(meter.clone()).post(search)),
+        );
+
+        for _ in 0..3 {
+            let res = TestClient::post("http://127.0.0.1:5800/search")
+                .add_header("x-api-key", "paid", true)
+                .text("query")
+                .send(&service)
+                .await;
+            assert_eq!(res.status_code, Some(StatusCode::OK));
+        }
+        let period = meter.current_period();
+        let usage = meter.usage("paid", &period).await.unwrap();
+        assert_eq!(usage.requests, 3);
+        assert_eq!(usage.bytes_in, 15);
+        assert_eq!(usage.bytes_out, 18);
+        assert_eq!(usage.compute_units, 15);
+
+        for status in [StatusCode::OK, StatusCode::OK, StatusCode::TOO_MANY_REQUESTS] {
+            let res = TestClient::post("http://127.0.0.1:5800/search")
+                .add_header("x-api-key", "free", true)
+                .send(&service)
+                .await;
+            assert_eq!(res.status_code, Some(status));
+        }
+
+        meter.flush().await.unwrap();
+        assert_eq!(store.get("free", &period).await.unwrap().requests, 2);
+        assert_eq!(store.list(&period).await.unwrap().len(), 2);
+        let content = TestClient::get("http://127.0.0.1:5800/usage/paid")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(json["usage"]["requests"], 3);
+        assert_eq!(json["period"], period);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_spawn_flusher() {
+        let store = MemoryUsageStore::new();
+        let meter = Meter::new(|_req: &Request, _depot: &Depot| Some("user".to_owned()), store.clone())
+            .flush_interval(Duration::from_millis(50));
+        let tasks = Tasks::new();
+        meter.spawn_flusher(&tasks);
+        let period = meter.current_period();
+
+        meter.record(
+            "user".into(),
+            period.clone(),
+            Usage {
+                requests: 1,
+                ..Default::default()
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(store.get("user", &period).await.unwrap().requests, 1);
+
+        tasks.cancel("metering-flush");
+        meter.record(
+            "user".into(),
+            period.clone(),
+            Usage {
+                requests: 1,
+                ..Default::default()
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(store.get("user", &period).await.unwrap().requests, 1);
+        tasks.shutdown().await;
+        assert_eq!(store.get("user", &period).await.unwrap().requests, 2);
+    }
+}
@@ -0,0 +1,636 @@
+//! Middleware for recording requests and responses, and a driver for replaying them.
+//!
+//! [`Recorder`] writes sanitized request and response pairs as JSON files into a directory, a percentage
+//! of the requests can be sampled. [`Replayer`] loads the recordings and sends the requests through a
+//! [`Service`], then compares the responses with the recorded ones, so regression suites can be built
+//! from traffic samples.
+//!
+//! The `authorization`, `proxy-authorization`, `cookie` and `set-cookie` headers are redacted by default.
+//! Request bodies are only recorded when the request has a `content-length` header, response bodies are only
+//! recorded when they are not streamed. Bodies which exceed [`Recorder::max_body_size`] are not recorded.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::recorder::Recorder;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let recorder = Recorder::new("recordings").percentage(1.0);
+//!     let router = Router::new().hoop(recorder).get(hello);
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+//!
+//! Replay the recordings in a test:
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::recorder::Replayer;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "hello"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let service = Service::new(Router::new().get(hello));
+//!     let report = Replayer::load("recordings").unwrap().replay(&service).await;
+//!     report.assert_ok();
+//! }
+//! ```
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::engine::{general_purpose, Engine};
+use futures_util::stream::StreamExt;
+use salvo_core::http::body::Body;
+use salvo_core::http::header::{HeaderName, AUTHORIZATION, CONTENT_LENGTH, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE};
+use salvo_core::http::{HeaderMap, ReqBody, Request, ResBody, Response, StatusCode};
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Service};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The placeholder of redacted header values.
+pub const REDACTED: &str = "[redacted]";
+
+/// A recorded request.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RecordedRequest {
+    /// The request method.
+    pub method: String,
+    /// The request path and query.
+    pub uri: String,
+    /// The request headers.
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// The request body, it is `None` if the body is not recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Whether the body is base64 encoded, binary bodies are encoded.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub base64: bool,
+}
+
+impl RecordedRequest {
+    /// Get the decoded body.
+    pub fn body_bytes(&self) -> Result<Option<Vec<u8>>, Error> {
+        decode_body(self.body.as_deref(), self.base64)
+    }
+}
+
+/// A recorded response.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RecordedResponse {
+    /// The response status code.
+    pub status: u16,
+    /// The response headers.
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// The response body, it is `None` if the body is not recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Whether the body is base64 encoded, binary bodies are encoded.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub base64: bool,
+}
+
+impl RecordedResponse {
+    /// Get the decoded body.
+    pub fn body_bytes(&self) -> Result<Option<Vec<u8>>, Error> {
+        decode_body(self.body.as_deref(), self.base64)
+    }
+}
+
+/// A recorded request and response pair.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Recording {
+    /// The request.
+    pub request: RecordedRequest,
+    /// The response.
+    pub response: RecordedResponse,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn encode_body(data: &[u8]) -> (String, bool) {
+    match std::str::from_utf8(data) {
+        Ok(text) => (text.to_owned(), false),
+        Err(_) => (general_purpose::STANDARD.encode(data), true),
+    }
+}
+
+fn decode_body(body: Option<&str>, base64: bool) -> Result<Option<Vec<u8>>, Error> {
+    match body {
+        Some(body) if base64 => general_purpose::STANDARD.decode(body).map(Some).map_err(Error::other),
+        Some(body) => Ok(Some(body.as_bytes().to_vec())),
+        None => Ok(None),
+    }
+}
+
+fn record_headers(headers: &HeaderMap, redacted: &[HeaderName]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if redacted.contains(name) {
+                REDACTED.to_owned()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.as_str().to_owned(), value)
+        })
+        .collect()
+}
+
+type Sanitizer = dyn Fn(&mut Recording) + Send + Sync;
+
+/// Middleware for recording requests and responses.
+///
+/// View [module level documentation](index.html) for more details.
+pub struct Recorder {
+    dir: PathBuf,
+    percentage: f64,
+    max_body_size: usize,
+    redacted_headers: Vec<HeaderName>,
+    sanitizer: Option<Box<Sanitizer>>,
+    counter: AtomicU64,
+}
+
+impl fmt::Debug for Recorder {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("dir", &self.dir)
+            .field("percentage", &self.percentage)
+            .field("max_body_size", &self.max_body_size)
+            .field("redacted_headers", &self.redacted_headers)
+            .finish()
+    }
+}
+
+impl Recorder {
+    /// Create a new `Recorder` which writes recordings into `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            percentage: 100.0,
+            max_body_size: 64 * 1024,
+            redacted_headers: vec![AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE],
+            sanitizer: None,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the percentage of the requests which are recorded, default is `100`.
+    #[inline]
+    pub fn percentage(mut self, percentage: f64) -> Self {
+        self.percentage = percentage.clamp(0.0, 100.0);
+        self
+    }
+
+    /// Sets the max size of recorded bodies, default is 64KB.
+    #[inline]
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Redact the value of the header in requests and responses.
+    #[inline]
+    pub fn redact_header(mut self, name: HeaderName) -> Self {
+        self.redacted_headers.push(name);
+        self
+    }
+
+    /// Sets a function which sanitizes the recordings before they are written, such as removing
+    /// sensitive fields from bodies.
+    #[inline]
+    pub fn sanitizer(mut self, sanitizer: impl Fn(&mut Recording) + Send + Sync + 'static) -> Self {
+        self.sanitizer = Some(Box::new(sanitizer));
+        self
+    }
+
+    async fn record_request(&self, req: &mut Request) -> RecordedRequest {
+        let mut recorded = RecordedRequest {
+            method: req.method().to_string(),
+            uri: req
+                .uri()
+                .path_and_query()
+                .map(|pq| pq.as_str().to_owned())
+                .unwrap_or_else(|| "/".into()),
+            headers: record_headers(req.headers(), &self.redacted_headers),
+            body: None,
+            base64: false,
+        };
+        let content_length = req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<usize>().ok())
+            .or_else(|| req.body().size_hint().exact().map(|len| len as usize));
+        if matches!(content_length, Some(len) if len > 0 && len <= self.max_body_size) {
+            match req.payload_with_max_size(self.max_body_size).await {
+                Ok(payload) => {
+                    let payload = payload.clone();
+                    let (body, base64) = encode_body(&payload);
+                    recorded.body = Some(body);
+                    recorded.base64 = base64;
+                    req.replace_body(ReqBody::Once(payload));
+                }
+                Err(e) => {
+                    tracing::debug!(error = ?e, "failed to record request body");
+                }
+            }
+        }
+        recorded
+    }
+
+    fn record_response(&self, res: &Response) -> RecordedResponse {
+        let body = match &res.body {
+            ResBody::Once(bytes) if bytes.len() <= self.max_body_size => Some(encode_body(bytes)),
+            ResBody::Chunks(chunks) if chunks.iter().map(|chunk| chunk.len()).sum::<usize>() <= self.max_body_size => {
+                let data = chunks
+                    .iter()
+                    .flat_map(|chunk| chunk.iter().copied())
+                    .collect::<Vec<u8>>();
+                Some(encode_body(&data))
+            }
+            _ => None,
+        };
+        let (body, base64) = match body {
+            Some((body, base64)) => (Some(body), base64),
+            None => (None, false),
+        };
+        RecordedResponse {
+            status: res.status_code.unwrap_or(StatusCode::OK).as_u16(),
+            headers: record_headers(res.headers(), &self.redacted_headers),
+            body,
+            base64,
+        }
+    }
+
+    async fn write(&self, recording: &Recording) -> Result<PathBuf, Error> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        let index = self.counter.fetch_add(1, Ordering::Relaxed);
+        let path = self.dir.join(format!("{nanos:020}-{index:06}.json"));
+        let data = serde_json::to_vec_pretty(recording).map_err(Error::SerdeJson)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(&path, data).await?;
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl Handler for Recorder {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if self.percentage < 100.0 && rand::random::<f64>() * 100.0 >= self.percentage {
+            return;
+        }
+        let request = self.record_request(req).await;
+        ctrl.call_next(req, depot, res).await;
+        let response = self.record_response(res);
+        let mut recording = Recording { request, response };
+        if let Some(sanitizer) = &self.sanitizer {
+            sanitizer(&mut recording);
+        }
+        match self.write(&recording).await {
+            Ok(path) => tracing::debug!(path = ?path, "request recorded"),
+            Err(e) => tracing::error!(error = ?e, "failed to write recording"),
+        }
+    }
+}
+
+/// A response which does not match the recording.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ReplayMismatch {
+    /// The name of the recording, it is the file name if the recording is loaded from a directory.
+    pub name: String,
+    /// The request method.
+    pub method: String,
+    /// The request path and query.
+    pub uri: String,
+    /// The differences between the recorded and the actual response.
+    pub differences: Vec<String>,
+}
+
+impl Display for ReplayMismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "{} {} {}:", self.name, self.method, self.uri)?;
+        for difference in &self.differences {
+            writeln!(f, "  {difference}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of [`Replayer::replay`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct ReplayReport {
+    /// The number of replayed recordings.
+    pub total: usize,
+    /// The responses which do not match the recordings.
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+impl ReplayReport {
+    /// Check whether all responses match the recordings.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Panics with the differences if any response does not match the recording.
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("{self}");
+        }
+    }
+}
+
+impl Display for ReplayReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "{} of {} recordings mismatched", self.mismatches.len(), self.total)?;
+        for mismatch in &self.mismatches {
+            write!(f, "{mismatch}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Driver which replays recordings through a [`Service`].
+///
+/// The status code and the body of the responses are compared with the recordings by default, bodies are
+/// compared as JSON values if both of them are valid JSON.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug, Default)]
+pub struct Replayer {
+    recordings: Vec<(String, Recording)>,
+    compared_headers: Vec<HeaderName>,
+    ignored_json_keys: Vec<String>,
+}
+
+impl Replayer {
+    /// Create a new `Replayer` with recordings, they are named by their index.
+    pub fn new(recordings: impl IntoIterator<Item = Recording>) -> Self {
+        Self {
+            recordings: recordings
+                .into_iter()
+                .enumerate()
+                .map(|(index, recording)| (index.to_string(), recording))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Load all `.json` recordings in `dir`, ordered by file name.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut paths = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        let mut recordings = Vec::with_capacity(paths.len());
+        for path in paths {
+            let data = std::fs::read(&path)?;
+            let recording = serde_json::from_slice(&data).map_err(Error::SerdeJson)?;
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            recordings.push((name, recording));
+        }
+        Ok(Self {
+            recordings,
+            ..Default::default()
+        })
+    }
+
+    /// Get the recordings.
+    #[inline]
+    pub fn recordings(&self) -> impl Iterator<Item = &Recording> {
+        self.recordings.iter().map(|(_, recording)| recording)
+    }
+
+    /// Compare the value of the header with the recording.
+    #[inline]
+    pub fn compare_header(mut self, name: HeaderName) -> Self {
+        self.compared_headers.push(name);
+        self
+    }
+
+    /// Ignore all object fields named `key` when comparing JSON bodies, such as timestamps.
+    #[inline]
+    pub fn ignore_json_key(mut self, key: impl Into<String>) -> Self {
+        self.ignored_json_keys.push(key.into());
+        self
+    }
+
+    /// Send all recorded requests through the service in order and compare the responses.
+    ///
+    /// Redacted request headers are not sent.
+    pub async fn replay(&self, service: &Service) -> ReplayReport {
+        let mut report = ReplayReport {
+            total: self.recordings.len(),
+            mismatches: Vec::new(),
+        };
+        for (name, recording) in &self.recordings {
+            let differences = match self.replay_one(service, recording).await {
+                Ok(differences) => differences,
+                Err(e) => vec![format!("failed to replay: {e}")],
+            };
+            if !differences.is_empty() {
+                report.mismatches.push(ReplayMismatch {
+                    name: name.clone(),
+                    method: recording.request.method.clone(),
+                    uri: recording.request.uri.clone(),
+                    differences,
+                });
+            }
+        }
+        report
+    }
+
+    async fn replay_one(&self, service: &Service, recording: &Recording) -> Result<Vec<String>, Error> {
+        let recorded = &recording.request;
+        let mut builder = salvo_core::hyper::Request::builder()
+            .method(recorded.method.as_str())
+            .uri(recorded.uri.as_str());
+        for (name, value) in &recorded.headers {
+            if value != REDACTED {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+        }
+        let body = recorded.body_bytes()?.map(ReqBody::from).unwrap_or_default();
+        let req = builder.body(body).map_err(Error::other)?;
+
+        let res = service.call(req).await;
+        let mut differences = Vec::new();
+        let expected = &recording.response;
+        if res.status().as_u16() != expected.status {
+            differences.push(format!(
+                "status: expected `{}`, actual `{}`",
+                expected.status,
+                res.status()
+            ));
+        }
+        for name in &self.compared_headers {
+            let expected = expected
+                .headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name.as_str()))
+                .map(|(_, value)| value.as_str());
+            let actual = res.headers().get(name).and_then(|value| value.to_str().ok());
+            if expected != actual {
+                differences.push(format!("header `{name}`: expected `{expected:?}`, actual `{actual:?}`"));
+            }
+        }
+        if let Some(expected) = expected.body_bytes()? {
+            let mut body = res.into_body();
+            let mut actual = Vec::new();
+            while let Some(frame) = body.next().await {
+                if let Ok(data) = frame?.into_data() {
+                    actual.extend_from_slice(&data);
+                }
+            }
+            if !self.body_eq(&expected, &actual) {
+                differences.push(format!(
+                    "body: expected `{}`, actual `{}`",
+                    String::from_utf8_lossy(&expected),
+                    String::from_utf8_lossy(&actual)
+                ));
+            }
+        }
+        Ok(differences)
+    }
+
+    fn body_eq(&self, expected: &[u8], actual: &[u8]) -> bool {
+        match (
+            serde_json::from_slice::<Value>(expected),
+            serde_json::from_slice::<Value>(actual),
+        ) {
+            (Ok(expected), Ok(actual)) => self.strip_json(expected) == self.strip_json(actual),
+            _ => expected == actual,
+        }
+    }
+
+    fn strip_json(&self, value: Value) -> Value {
+        match value {
+            Value::Array(items) => Value::Array(items.into_iter().map(|item| self.strip_json(item)).collect()),
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .filter(|(key, _)| !self.ignored_json_keys.contains(key))
+                    .map(|(key, item)| (key, self.strip_json(item)))
+                    .collect(),
+            ),
+            value => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde_json::json;
+
+    use super::*;
+
+    #[handler]
+    async fn create_user(req: &mut Request, res: &mut Response) {
+        let user = req.parse_json::<Value>().await.unwrap_or_default();
+        res.render(Json(
+            json!({"name": user["name"], "created_at": "2024-01-01T00:00:00Z"}),
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("salvo-recorder-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let router = Router::new()
+            .hoop(Recorder::new(&dir).sanitizer(|recording| {
+                recording.request.headers.retain(|(name, _)| name != "x-secret");
+            }))
+            .push(Router::with_path("users").post(create_user));
+        let service = Service::new(router);
+
+        let mut res = TestClient::post("http://127.0.0.1:5800/users")
+            .add_header("authorization", "Bearer token", true)
+            .add_header("x-secret", "secret", true)
+            .json(&json!({"name": "jobs"}))
+            .send(&service)
+            .await;
+        assert_eq!(res.take_json::<Value>().await.unwrap()["name"], "jobs");
+
+        let replayer = Replayer::load(&dir).unwrap();
+        let recording = replayer.recordings().next().unwrap();
+        assert_eq!(recording.request.method, "POST");
+        assert_eq!(recording.request.uri, "/users");
+        assert_eq!(recording.request.body.as_deref(), Some(r#"{"name":"jobs"}"#));
+        assert!(recording
+            .request
+            .headers
+            .contains(&("authorization".to_owned(), REDACTED.to_owned())));
+        assert!(!recording.request.headers.iter().any(|(name, _)| name == "x-secret"));
+        assert_eq!(recording.response.status, 200);
+
+        let service = Service::new(Router::with_path("users").post(create_user));
+        replayer.replay(&service).await.assert_ok();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_replay_mismatch() {
+        #[handler]
+        async fn show_user(res: &mut Response) {
+            res.render(Json(json!({"name": "jobs", "created_at": "2024-02-01T00:00:00Z"})));
+        }
+        #[handler]
+        async fn changed(res: &mut Response) {
+            res.render(Json(json!({"name": "other", "created_at": "2024-02-01T00:00:00Z"})));
+        }
+
+        let recording = Recording {
+            request: RecordedRequest {
+                method: "GET".into(),
+                uri: "/users/1".into(),
+                ..Default::default()
+            },
+            response: RecordedResponse {
+                status: 200,
+                body: Some(r#"{"name":"jobs","created_at":"2024-01-01T00:00:00Z"}"#.into()),
+                ..Default::default()
+            },
+        };
+        let replayer = Replayer::new(vec![recording]).ignore_json_key("created_at");
+        let service = Service::new(Router::with_path("users/<id>").get(show_user));
+        assert!(replayer.replay(&service).await.is_ok());
+
+        let service = Service::new(Router::with_path("users/<id>").get(changed));
+        let report = replayer.replay(&service).await;
+        assert_eq!(report.mismatches.len(), 1);
+        assert!(report.mismatches[0].differences[0].starts_with("body"));
+    }
+}
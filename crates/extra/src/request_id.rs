@@ -21,6 +21,7 @@
 use ulid::Ulid;
 
 use salvo_core::http::{header::HeaderName, Request, Response};
+use salvo_core::span::SpanDepotExt;
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 
 /// Key for incoming flash messages in depot.
@@ -110,6 +111,9 @@ impl Handler for RequestId {
         }
         let id = self.generator.generate(req, depot);
         req.add_header(self.header_name.clone(), &id, true).ok();
+        if let Some(span) = depot.request_span() {
+            span.record_request_id(&id);
+        }
         depot.insert(REQUST_ID_KEY, id);
     }
 }
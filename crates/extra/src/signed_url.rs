@@ -0,0 +1,306 @@
+//! Signed URL middleware.
+//!
+//! [`UrlSigner`] appends an expiry time and an HMAC-SHA256 signature of the path, the query and the expiry to a URL,
+//! and [`SignedUrlGuard`] rejects requests whose URL was not signed by it, was tampered with or is expired. Such URLs
+//! can be handed out as download links or webhook callbacks, without sessions.
+//!
+//! The signature covers the path and the query parameters in order, so the URL must reach the guard unchanged,
+//! routers mounted behind a path rewriting proxy should sign the path the guard sees.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_core::prelude::*;
+//! use salvo_extra::signed_url::{SignedUrlGuard, UrlSigner};
+//!
+//! #[handler]
+//! async fn link() -> String {
+//!     UrlSigner::new("secret key").sign("/downloads/report.pdf?user=7", Duration::from_secs(3600))
+//! }
+//!
+//! #[handler]
+//! async fn download() -> &'static str {
+//!     "report"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::new().push(Router::with_path("link").get(link)).push(
+//!         Router::with_path("downloads/<file>")
+//!             .hoop(SignedUrlGuard::new(UrlSigner::new("secret key")))
+//!             .get(download),
+//!     );
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::fmt::{self, Formatter};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use salvo_core::http::{Request, Response, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use sha2::Sha256;
+
+/// Error of the verification of a signed URL.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SignedUrlError {
+    /// The URL has no expiry or signature parameter.
+    Missing,
+    /// The signature does not match the URL.
+    Invalid,
+    /// The URL is expired.
+    Expired,
+}
+impl fmt::Display for SignedUrlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "URL is not signed"),
+            Self::Invalid => write!(f, "invalid URL signature"),
+            Self::Expired => write!(f, "signed URL is expired"),
+        }
+    }
+}
+impl std::error::Error for SignedUrlError {}
+
+/// Sign and verify URLs with HMAC-SHA256.
+#[derive(Clone)]
+pub struct UrlSigner {
+    key: Vec<u8>,
+    fallback_keys: Vec<Vec<u8>>,
+    expires_param: String,
+    signature_param: String,
+}
+impl fmt::Debug for UrlSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UrlSigner")
+            .field("expires_param", &self.expires_param)
+            .field("signature_param", &self.signature_param)
+            .finish_non_exhaustive()
+    }
+}
+impl UrlSigner {
+    /// Create a new `UrlSigner` with the secret key.
+    #[inline]
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        Self {
+            key: key.as_ref().to_vec(),
+            fallback_keys: vec![],
+            expires_param: "expires".into(),
+            signature_param: "signature".into(),
+        }
+    }
+
+    /// Add a key which has been rotated out, URLs signed by it are still accepted until they expire.
+    #[inline]
+    pub fn add_fallback_key(mut self, key: impl AsRef<[u8]>) -> Self {
+        self.fallback_keys.push(key.as_ref().to_vec());
+        self
+    }
+
+    /// Sets the name of the query parameter of the expiry time, the default is `expires`.
+    #[inline]
+    pub fn expires_param(mut self, name: impl Into<String>) -> Self {
+        self.expires_param = name.into();
+        self
+    }
+
+    /// Sets the name of the query parameter of the signature, the default is `signature`.
+    #[inline]
+    pub fn signature_param(mut self, name: impl Into<String>) -> Self {
+        self.signature_param = name.into();
+        self
+    }
+
+    /// Sign `url` so it is valid for `ttl`.
+    ///
+    /// `url` is a path with an optional query, such as `/downloads/report.pdf?user=7`, or an absolute URL whose path
+    /// and query are signed. It must be percent-encoded as it is requested. The expiry and signature parameters are
+    /// appended to the query.
+    #[inline]
+    pub fn sign(&self, url: &str, ttl: Duration) -> String {
+        self.sign_until(url, SystemTime::now() + ttl)
+    }
+
+    /// Sign `url` so it is valid until `expires`.
+    pub fn sign_until(&self, url: &str, expires: SystemTime) -> String {
+        let expires = expires.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let url = format!("{url}{separator}{}={expires}", self.expires_param);
+        let (path, query) = split_url(&url);
+        let signature = self.signature(path, query);
+        format!("{url}&{}={signature}", self.signature_param)
+    }
+
+    /// Returns the hex encoded signature of `path` and `query` by the key, without any expiry.
+    ///
+    /// It is a building block for URLs whose expiry is not needed or is handled by the caller, [`sign`](Self::sign)
+    /// should be preferred otherwise.
+    #[inline]
+    pub fn signature(&self, path: &str, query: &str) -> String {
+        hex::encode(mac(&self.key, path, query).finalize().into_bytes())
+    }
+
+    /// Returns `true` if `signature` is the hex encoded signature of `path` and `query` by the key or a fallback key.
+    pub fn verify_signature(&self, path: &str, query: &str, signature: &str) -> bool {
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        std::iter::once(&self.key)
+            .chain(&self.fallback_keys)
+            .any(|key| mac(key, path, query).verify_slice(&signature).is_ok())
+    }
+
+    /// Verify the signature and the expiry time of a path and its query.
+    pub fn verify(&self, path: &str, query: &str) -> Result<(), SignedUrlError> {
+        let mut expires = None;
+        let mut signature = None;
+        let mut signed = Vec::new();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if name == self.signature_param {
+                signature = Some(value);
+                continue;
+            }
+            if name == self.expires_param {
+                expires = Some(value);
+            }
+            signed.push(pair);
+        }
+        let (Some(expires), Some(signature)) = (expires, signature) else {
+            return Err(SignedUrlError::Missing);
+        };
+        if !self.verify_signature(path, &signed.join("&"), signature) {
+            return Err(SignedUrlError::Invalid);
+        }
+        let expires = expires.parse::<u64>().map_err(|_| SignedUrlError::Invalid)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if expires < now {
+            Err(SignedUrlError::Expired)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verify the signature and the expiry time of the URL of a request.
+    #[inline]
+    pub fn verify_request(&self, req: &Request) -> Result<(), SignedUrlError> {
+        self.verify(req.uri().path(), req.uri().query().unwrap_or_default())
+    }
+}
+
+/// Split the path and the query of a path or an absolute URL.
+fn split_url(url: &str) -> (&str, &str) {
+    let path_and_query = match url.find("://") {
+        Some(index) => {
+            let rest = &url[index + 3..];
+            rest.find('/').map(|index| &rest[index..]).unwrap_or_default()
+        }
+        None => url,
+    };
+    path_and_query.split_once('?').unwrap_or((path_and_query, ""))
+}
+
+fn mac(key: &[u8], path: &str, query: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac can take key of any size");
+    mac.update(path.as_bytes());
+    mac.update(b"?");
+    mac.update(query.as_bytes());
+    mac
+}
+
+/// Middleware rejecting requests whose URL is not signed by its [`UrlSigner`], with `403 Forbidden`.
+#[derive(Clone, Debug)]
+pub struct SignedUrlGuard {
+    signer: UrlSigner,
+}
+impl SignedUrlGuard {
+    /// Create a new `SignedUrlGuard`.
+    #[inline]
+    pub fn new(signer: UrlSigner) -> Self {
+        Self { signer }
+    }
+}
+
+#[async_trait]
+impl Handler for SignedUrlGuard {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        match self.signer.verify_request(req) {
+            Ok(()) => {
+                ctrl.call_next(req, depot, res).await;
+            }
+            Err(e) => {
+                res.render(StatusError::forbidden().brief(e.to_string()));
+                ctrl.skip_rest();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = UrlSigner::new("secret");
+        let url = signer.sign("https://example.com/files/a.txt?user=7", Duration::from_secs(60));
+        let (path, query) = split_url(&url);
+        assert_eq!(path, "/files/a.txt");
+        assert!(signer.verify(path, query).is_ok());
+        assert_eq!(
+            signer.verify(path, &query.replace("user=7", "user=8")),
+            Err(SignedUrlError::Invalid)
+        );
+        assert_eq!(signer.verify("/files/b.txt", query), Err(SignedUrlError::Invalid));
+        assert_eq!(signer.verify(path, "user=7"), Err(SignedUrlError::Missing));
+        assert_eq!(
+            UrlSigner::new("other").verify(path, query),
+            Err(SignedUrlError::Invalid)
+        );
+        assert!(UrlSigner::new("other")
+            .add_fallback_key("secret")
+            .verify(path, query)
+            .is_ok());
+
+        let url = signer.sign_until("/files/a.txt", UNIX_EPOCH + Duration::from_secs(1));
+        let (path, query) = split_url(&url);
+        assert_eq!(signer.verify(path, query), Err(SignedUrlError::Expired));
+    }
+
+    #[tokio::test]
+    async fn test_signed_url_guard() {
+        #[handler]
+        async fn download() -> &'static str {
+            "report"
+        }
+        let signer = UrlSigner::new("secret");
+        let router = Router::with_path("downloads/<file>")
+            .hoop(SignedUrlGuard::new(signer.clone()))
+            .get(download);
+        let service = Service::new(router);
+
+        let url = signer.sign("/downloads/report.pdf", Duration::from_secs(60));
+        let content = TestClient::get(format!("http://127.0.0.1:5800{url}"))
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "report");
+
+        let res = TestClient::get("http://127.0.0.1:5800/downloads/report.pdf")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+    }
+}
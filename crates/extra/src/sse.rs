@@ -64,6 +64,8 @@ use tokio::time::{self, Sleep};
 
 use salvo_core::http::Response;
 
+pub mod test;
+
 /// Server-sent event data type
 #[derive(Clone, Debug)]
 enum DataType {
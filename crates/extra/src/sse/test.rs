@@ -0,0 +1,231 @@
+//! Test client for Server-Sent Events handlers.
+//!
+//! [`SseReader`] parses the body of a response into [`SseMessage`]s and can be used as an async iterator,
+//! so event streams can be tested inside `#[tokio::test]`.
+//!
+//! # Example
+//!
+//! ```
+//! use std::convert::Infallible;
+//!
+//! use futures_util::stream::iter;
+//! use salvo_core::prelude::*;
+//! use salvo_core::test::TestClient;
+//! use salvo_extra::sse::test::SseReader;
+//! use salvo_extra::sse::{self, SseEvent};
+//!
+//! #[handler]
+//! async fn events(res: &mut Response) {
+//!     sse::stream(res, iter(vec![Ok::<_, Infallible>(SseEvent::default().name("chat").text("hello"))]));
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut res = TestClient::get("http://127.0.0.1:5800/").send(events).await;
+//!     let mut reader = SseReader::new(&mut res);
+//!     let event = reader.next_event().await.unwrap().unwrap();
+//!     assert_eq!(event.name.as_deref(), Some("chat"));
+//!     assert_eq!(event.data, "hello");
+//! }
+//! ```
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use futures_util::stream::{Stream, StreamExt};
+use salvo_core::http::{ResBody, Response};
+use salvo_core::Error;
+use serde::de::DeserializeOwned;
+
+/// A Server-Sent Event received by [`SseReader`].
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SseMessage {
+    /// The `event` field.
+    pub name: Option<String>,
+    /// The `id` field.
+    pub id: Option<String>,
+    /// The `data` fields, joined with `\n`.
+    pub data: String,
+    /// The `retry` field.
+    pub retry: Option<Duration>,
+}
+
+impl SseMessage {
+    /// Deserialize the data of the event from JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_str(&self.data)
+    }
+
+    fn parse(block: &str) -> Option<Self> {
+        let mut message = SseMessage::default();
+        let mut data = Vec::new();
+        let mut has_field = false;
+        for line in block.lines() {
+            if line.is_empty() || line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => message.name = Some(value.to_owned()),
+                "id" => message.id = Some(value.to_owned()),
+                "data" => data.push(value),
+                "retry" => message.retry = value.parse().ok().map(Duration::from_millis),
+                _ => continue,
+            }
+            has_field = true;
+        }
+        message.data = data.join("\n");
+        has_field.then_some(message)
+    }
+}
+
+/// Reader which parses a response body into [`SseMessage`]s.
+///
+/// Blocks which only contain comments, such as the keep-alive comments sent by [`SseKeepAlive`], are skipped.
+///
+/// [`SseKeepAlive`]: super::SseKeepAlive
+pub struct SseReader {
+    body: ResBody,
+    buffer: Vec<u8>,
+    timeout: Duration,
+}
+
+impl std::fmt::Debug for SseReader {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("SseReader").field("timeout", &self.timeout).finish()
+    }
+}
+
+impl SseReader {
+    /// Create a new `SseReader` which takes the body of the response.
+    #[inline]
+    pub fn new(res: &mut Response) -> Self {
+        Self {
+            body: res.take_body(),
+            buffer: Vec::new(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Sets the timeout of [`next_event`](Self::next_event), default is 5 seconds.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Receive the next event, returns `None` if the stream is finished.
+    ///
+    /// Returns an error if no event is received within the timeout.
+    pub async fn next_event(&mut self) -> Result<Option<SseMessage>, Error> {
+        match tokio::time::timeout(self.timeout, self.next()).await {
+            Ok(Some(result)) => result.map(Some),
+            Ok(None) => Ok(None),
+            Err(_) => Err(Error::other("timed out receiving server-sent event")),
+        }
+    }
+
+    /// Receive all remaining events until the stream is finished.
+    pub async fn collect_events(&mut self) -> Result<Vec<SseMessage>, Error> {
+        let mut events = Vec::new();
+        while let Some(event) = self.next_event().await? {
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    fn take_block(&mut self) -> Option<String> {
+        loop {
+            let end = self.buffer.windows(2).position(|window| window == b"\n\n")?;
+            let block = self.buffer.drain(..end + 2).collect::<Vec<_>>();
+            let block = String::from_utf8_lossy(&block).into_owned();
+            if !block.trim().is_empty() {
+                return Some(block);
+            }
+        }
+    }
+}
+
+impl Stream for SseReader {
+    type Item = Result<SseMessage, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            while let Some(block) = self.take_block() {
+                if let Some(message) = SseMessage::parse(&block) {
+                    return Poll::Ready(Some(Ok(message)));
+                }
+            }
+            match ready!(Pin::new(&mut self.body).poll_next(cx)) {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        let data = String::from_utf8_lossy(&data).replace("\r\n", "\n");
+                        self.buffer.extend_from_slice(data.as_bytes());
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(Error::other(e)))),
+                None => {
+                    let rest = std::mem::take(&mut self.buffer);
+                    return Poll::Ready(SseMessage::parse(&String::from_utf8_lossy(&rest)).map(Ok));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::sse::{self, SseEvent, SseKeepAlive};
+
+    #[tokio::test]
+    async fn test_sse_reader() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct User {
+            name: String,
+        }
+
+        #[handler]
+        async fn chat(res: &mut Response) {
+            let events = tokio_stream::iter(vec![
+                Ok::<_, Infallible>(SseEvent::default().name("chat").text("line 1\nline 2").id("1")),
+                Ok::<_, Infallible>(SseEvent::default().json(serde_json::json!({"name": "jobs"})).unwrap()),
+                Ok::<_, Infallible>(SseEvent::default().retry(Duration::from_millis(1500))),
+            ]);
+            SseKeepAlive::new(events).comment("ping").stream(res);
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/").send(chat).await;
+        let mut reader = SseReader::new(&mut res).timeout(Duration::from_secs(1));
+        let event = reader.next_event().await.unwrap().unwrap();
+        assert_eq!(event.name.as_deref(), Some("chat"));
+        assert_eq!(event.id.as_deref(), Some("1"));
+        assert_eq!(event.data, "line 1\nline 2");
+        let event = reader.next_event().await.unwrap().unwrap();
+        assert_eq!(event.json::<User>().unwrap(), User { name: "jobs".into() });
+        let events = reader.collect_events().await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].retry, Some(Duration::from_millis(1500)));
+    }
+
+    #[tokio::test]
+    async fn test_sse_reader_timeout() {
+        #[handler]
+        async fn pending(res: &mut Response) {
+            sse::stream(res, futures_util::stream::pending::<Result<SseEvent, Infallible>>());
+        }
+
+        let mut res = TestClient::get("http://127.0.0.1:5800/").send(pending).await;
+        let mut reader = SseReader::new(&mut res).timeout(Duration::from_millis(50));
+        assert!(reader.next_event().await.is_err());
+    }
+}
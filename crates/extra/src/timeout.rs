@@ -5,6 +5,9 @@
 //!
 //! This middleware can be used to deal with slow network attacks.
 //!
+//! The [`Deadline`] of the request is stored in the [`Depot`], so outbound calls such as the proxy give up when it
+//! expires. If the caller sends its own deadline in the `X-Request-Timeout` header, the earlier one is used.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -37,6 +40,7 @@
 
 use std::time::Duration;
 
+use salvo_core::deadline::{Deadline, DeadlineDepotExt};
 use salvo_core::http::headers::{Connection, HeaderMapExt};
 use salvo_core::http::{Request, Response, StatusError};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
@@ -46,6 +50,7 @@ use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
 /// View [module level documentation](index.html) for more details.
 pub struct Timeout {
     value: Duration,
+    honor_header: bool,
     error: Box<dyn Fn() -> StatusError + Send + Sync + 'static>,
 }
 impl Timeout {
@@ -57,10 +62,20 @@ impl Timeout {
         // https://github.com/tower-rs/tower-http/issues/300
         Timeout {
             value,
+            honor_header: true,
             error: Box::new(|| StatusError::service_unavailable().brief("Server process the request timeout.")),
         }
     }
 
+    /// Sets whether the deadline sent in the `X-Request-Timeout` header is honored, the default is `true`.
+    ///
+    /// The header can only shorten the timeout.
+    #[inline]
+    pub fn honor_header(mut self, honor_header: bool) -> Self {
+        self.honor_header = honor_header;
+        self
+    }
+
     /// Custom error returned when timeout.
     ///
     /// By default, a `503 Service Unavailable` error is returned. You can set this function to other error types,
@@ -75,9 +90,16 @@ impl Timeout {
 impl Handler for Timeout {
     #[inline]
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let mut deadline = Deadline::after(self.value);
+        if self.honor_header {
+            if let Some(requested) = Deadline::from_headers(req.headers()) {
+                deadline = deadline.min(requested);
+            }
+        }
+        let deadline = depot.set_deadline(deadline);
         tokio::select! {
             _ = ctrl.call_next(req, depot, res) => {},
-            _ = tokio::time::sleep(self.value) => {
+            _ = tokio::time::sleep_until(deadline.instant().into()) => {
                 res.headers_mut().typed_insert(Connection::close());
                 res.render((self.error)());
                 ctrl.skip_rest();
@@ -93,7 +115,7 @@ mod tests {
 
     use super::*;
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn test_timeout_handler() {
         #[handler]
         async fn fast() -> &'static str {
@@ -127,4 +149,35 @@ mod tests {
             .unwrap();
         assert!(content.contains("hello"));
     }
+
+    #[tokio::test]
+    async fn test_timeout_header() {
+        #[handler]
+        async fn remaining(depot: &mut Depot) -> String {
+            depot.deadline().unwrap().remaining().as_secs().to_string()
+        }
+
+        let router = Router::new()
+            .hoop(Timeout::new(Duration::from_secs(5)))
+            .push(Router::with_path("remaining").get(remaining));
+        let service = Service::new(router);
+
+        let content = TestClient::get("http://127.0.0.1:5801/remaining")
+            .add_header("x-request-timeout", "2s", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "1");
+
+        let content = TestClient::get("http://127.0.0.1:5801/remaining")
+            .add_header("x-request-timeout", "60s", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "4");
+    }
 }
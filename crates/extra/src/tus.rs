@@ -0,0 +1,790 @@
+//! Resumable uploads with the [tus protocol](https://tus.io/protocols/resumable-upload).
+//!
+//! [`Tus`] builds a router implementing the core protocol with the `creation`, `expiration` and `termination`
+//! extensions, so large uploads survive flaky connections by resuming from the last stored offset:
+//!
+//! | Method | Path | Description |
+//! | --- | --- | --- |
+//! | `OPTIONS` | | The protocol version, the extensions and the max upload size. |
+//! | `POST` | | Create an upload from `Upload-Length` and `Upload-Metadata`, respond with its location. |
+//! | `HEAD` | `<id>` | The offset and the length of an upload. |
+//! | `PATCH` | `<id>` | Append an `application/offset+octet-stream` body at `Upload-Offset`. |
+//! | `DELETE` | `<id>` | Terminate an upload. |
+//! | `GET` | `<id>` | The progress of an upload as JSON, it is not part of the protocol. |
+//!
+//! Uploads are kept by a [`TusStore`], [`DiskStore`] writes them to a directory and [`MemoryStore`] keeps them in
+//! memory. Uploads which are not completed before they expire are rejected, remove them with
+//! [`Tus::purge_expired`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_extra::tus::{DiskStore, Tus};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let tus = Tus::new(DiskStore::new("uploads"))
+//!         .max_size(1024 * 1024 * 1024)
+//!         .expiration(std::time::Duration::from_secs(24 * 60 * 60))
+//!         .on_complete(|upload| println!("upload {} is completed", upload.id));
+//!     let router = Router::with_path("files").push(tus.router());
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Debug, Formatter};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::{general_purpose, Engine};
+use bytes::Bytes;
+use futures_util::stream::StreamExt;
+use salvo_core::http::header::{HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_TYPE, LOCATION};
+use salvo_core::http::headers::{Expires, HeaderMapExt};
+use salvo_core::http::{Method, Request, Response, StatusCode, StatusError};
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Router};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+/// The version of the tus protocol implemented.
+pub const TUS_VERSION: &str = "1.0.0";
+/// The extensions of the tus protocol implemented.
+pub const TUS_EXTENSIONS: &str = "creation,expiration,termination";
+/// The content type of `PATCH` requests.
+pub const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+const TUS_RESUMABLE: HeaderName = HeaderName::from_static("tus-resumable");
+const TUS_VERSION_HEADER: HeaderName = HeaderName::from_static("tus-version");
+const TUS_EXTENSION: HeaderName = HeaderName::from_static("tus-extension");
+const TUS_MAX_SIZE: HeaderName = HeaderName::from_static("tus-max-size");
+const UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+const UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+const UPLOAD_DEFER_LENGTH: HeaderName = HeaderName::from_static("upload-defer-length");
+const UPLOAD_METADATA: HeaderName = HeaderName::from_static("upload-metadata");
+const UPLOAD_EXPIRES: HeaderName = HeaderName::from_static("upload-expires");
+
+/// An upload.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct UploadInfo {
+    /// The id of the upload.
+    pub id: String,
+    /// The number of bytes received.
+    pub offset: u64,
+    /// The total length, it is `None` if the client deferred it.
+    pub length: Option<u64>,
+    /// The metadata sent by the client, such as the file name.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// The expiry time as seconds since the Unix epoch, it is `None` if the upload never expires.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+impl UploadInfo {
+    /// Create a new `UploadInfo` with `id`.
+    #[inline]
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if all the bytes are received.
+    #[inline]
+    pub fn is_completed(&self) -> bool {
+        self.length == Some(self.offset)
+    }
+
+    /// Returns `true` if the upload is not completed and expired.
+    pub fn is_expired(&self) -> bool {
+        !self.is_completed() && self.expires_at.is_some_and(|expires_at| expires_at <= unix_now())
+    }
+}
+
+/// Storage of uploads.
+#[async_trait]
+pub trait TusStore: Send + Sync + 'static {
+    /// Create an empty upload described by `info`.
+    async fn create(&self, info: &UploadInfo) -> Result<(), Error>;
+    /// Get the upload with `id`.
+    async fn get(&self, id: &str) -> Result<Option<UploadInfo>, Error>;
+    /// Append `data` to the end of the upload with `id`, returns the new offset.
+    async fn append(&self, id: &str, data: Bytes) -> Result<u64, Error>;
+    /// Set the length of an upload created with a deferred length.
+    async fn set_length(&self, id: &str, length: u64) -> Result<(), Error>;
+    /// Remove the upload with `id` and its content.
+    async fn remove(&self, id: &str) -> Result<(), Error>;
+    /// List all the uploads.
+    async fn list(&self) -> Result<Vec<UploadInfo>, Error>;
+}
+
+type MemoryUploads = HashMap<String, (UploadInfo, Vec<u8>)>;
+
+/// [`TusStore`] keeping uploads in memory.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore {
+    uploads: Arc<Mutex<MemoryUploads>>,
+}
+impl MemoryStore {
+    /// Create a new `MemoryStore`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the content received for the upload with `id`.
+    pub fn content(&self, id: &str) -> Option<Vec<u8>> {
+        self.uploads
+            .lock()
+            .expect("lock poisoned")
+            .get(id)
+            .map(|(_, content)| content.clone())
+    }
+}
+#[async_trait]
+impl TusStore for MemoryStore {
+    async fn create(&self, info: &UploadInfo) -> Result<(), Error> {
+        self.uploads
+            .lock()
+            .expect("lock poisoned")
+            .insert(info.id.clone(), (info.clone(), vec![]));
+        Ok(())
+    }
+    async fn get(&self, id: &str) -> Result<Option<UploadInfo>, Error> {
+        Ok(self
+            .uploads
+            .lock()
+            .expect("lock poisoned")
+            .get(id)
+            .map(|(info, _)| info.clone()))
+    }
+    async fn append(&self, id: &str, data: Bytes) -> Result<u64, Error> {
+        let mut uploads = self.uploads.lock().expect("lock poisoned");
+        let (info, content) = uploads.get_mut(id).ok_or_else(|| Error::other("upload not found"))?;
+        content.extend_from_slice(&data);
+        info.offset = content.len() as u64;
+        Ok(info.offset)
+    }
+    async fn set_length(&self, id: &str, length: u64) -> Result<(), Error> {
+        let mut uploads = self.uploads.lock().expect("lock poisoned");
+        let (info, _) = uploads.get_mut(id).ok_or_else(|| Error::other("upload not found"))?;
+        info.length = Some(length);
+        Ok(())
+    }
+    async fn remove(&self, id: &str) -> Result<(), Error> {
+        self.uploads.lock().expect("lock poisoned").remove(id);
+        Ok(())
+    }
+    async fn list(&self) -> Result<Vec<UploadInfo>, Error> {
+        Ok(self
+            .uploads
+            .lock()
+            .expect("lock poisoned")
+            .values()
+            .map(|(info, _)| info.clone())
+            .collect())
+    }
+}
+
+/// [`TusStore`] writing uploads to a directory.
+///
+/// The content of an upload is written to `<id>.bin` and its info to `<id>.json`.
+#[derive(Clone, Debug)]
+pub struct DiskStore {
+    dir: PathBuf,
+}
+impl DiskStore {
+    /// Create a new `DiskStore` writing to `dir`, it is created if it does not exist.
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Get the path of the content of the upload with `id`.
+    pub fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.bin"))
+    }
+
+    fn info_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    async fn write_info(&self, info: &UploadInfo) -> Result<(), Error> {
+        let data = serde_json::to_vec(info).map_err(Error::other)?;
+        tokio::fs::write(self.info_path(&info.id), data).await?;
+        Ok(())
+    }
+}
+#[async_trait]
+impl TusStore for DiskStore {
+    async fn create(&self, info: &UploadInfo) -> Result<(), Error> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::File::create(self.path(&info.id)).await?;
+        self.write_info(info).await
+    }
+    async fn get(&self, id: &str) -> Result<Option<UploadInfo>, Error> {
+        match tokio::fs::read(self.info_path(id)).await {
+            Ok(data) => serde_json::from_slice(&data).map(Some).map_err(Error::other),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    async fn append(&self, id: &str, data: Bytes) -> Result<u64, Error> {
+        let mut info = self.get(id).await?.ok_or_else(|| Error::other("upload not found"))?;
+        let mut file = tokio::fs::OpenOptions::new().append(true).open(self.path(id)).await?;
+        file.write_all(&data).await?;
+        file.flush().await?;
+        info.offset += data.len() as u64;
+        self.write_info(&info).await?;
+        Ok(info.offset)
+    }
+    async fn set_length(&self, id: &str, length: u64) -> Result<(), Error> {
+        let mut info = self.get(id).await?.ok_or_else(|| Error::other("upload not found"))?;
+        info.length = Some(length);
+        self.write_info(&info).await
+    }
+    async fn remove(&self, id: &str) -> Result<(), Error> {
+        for path in [self.path(id), self.info_path(id)] {
+            match tokio::fs::remove_file(path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    async fn list(&self) -> Result<Vec<UploadInfo>, Error> {
+        let mut uploads = vec![];
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(uploads),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            if let Some(id) = name.to_str().and_then(|name| name.strip_suffix(".json")) {
+                if let Some(info) = self.get(id).await? {
+                    uploads.push(info);
+                }
+            }
+        }
+        Ok(uploads)
+    }
+}
+
+type CompleteCallback = dyn Fn(&UploadInfo) + Send + Sync;
+
+/// Builder of the tus router.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone)]
+pub struct Tus {
+    store: Arc<dyn TusStore>,
+    max_size: Option<u64>,
+    expiration: Option<Duration>,
+    on_complete: Option<Arc<CompleteCallback>>,
+    locks: Arc<Mutex<HashSet<String>>>,
+}
+impl Debug for Tus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tus")
+            .field("max_size", &self.max_size)
+            .field("expiration", &self.expiration)
+            .finish_non_exhaustive()
+    }
+}
+impl Tus {
+    /// Create a new `Tus` keeping uploads in `store`.
+    #[inline]
+    pub fn new(store: impl TusStore) -> Self {
+        Self {
+            store: Arc::new(store),
+            max_size: None,
+            expiration: None,
+            on_complete: None,
+            locks: Arc::default(),
+        }
+    }
+
+    /// Sets the max size of an upload in bytes, it is not limited by default.
+    #[inline]
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Sets the duration after which uploads which are not completed expire, they never expire by default.
+    #[inline]
+    pub fn expiration(mut self, expiration: Duration) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    /// Sets the callback called when an upload is completed.
+    #[inline]
+    pub fn on_complete<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&UploadInfo) + Send + Sync + 'static,
+    {
+        self.on_complete = Some(Arc::new(callback));
+        self
+    }
+
+    /// Get the progress of the upload with `id`.
+    pub async fn progress(&self, id: &str) -> Result<Option<UploadInfo>, Error> {
+        self.store.get(id).await
+    }
+
+    /// Remove the expired uploads, returns the number of removed uploads.
+    ///
+    /// Call it periodically, such as from a cron task.
+    pub async fn purge_expired(&self) -> Result<usize, Error> {
+        let mut removed = 0;
+        for info in self.store.list().await? {
+            if info.is_expired() {
+                self.store.remove(&info.id).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Build the tus router.
+    pub fn router(&self) -> Router {
+        let endpoint = |action| Endpoint {
+            tus: self.clone(),
+            action,
+        };
+        Router::new()
+            .hoop(VersionCheck)
+            .options(endpoint(Action::Options))
+            .post(endpoint(Action::Create))
+            .push(
+                Router::with_path("<id>")
+                    .head(endpoint(Action::Head))
+                    .patch(endpoint(Action::Patch))
+                    .delete(endpoint(Action::Delete))
+                    .get(endpoint(Action::Progress)),
+            )
+    }
+
+    fn lock(&self, id: &str) -> Option<LockGuard> {
+        self.locks
+            .lock()
+            .expect("lock poisoned")
+            .insert(id.to_owned())
+            .then(|| LockGuard {
+                locks: self.locks.clone(),
+                id: id.to_owned(),
+            })
+    }
+}
+
+struct LockGuard {
+    locks: Arc<Mutex<HashSet<String>>>,
+    id: String,
+}
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        self.locks.lock().expect("lock poisoned").remove(&self.id);
+    }
+}
+
+struct VersionCheck;
+#[async_trait]
+impl Handler for VersionCheck {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        res.headers_mut()
+            .insert(TUS_RESUMABLE, HeaderValue::from_static(TUS_VERSION));
+        if matches!(*req.method(), Method::OPTIONS | Method::GET) {
+            return;
+        }
+        if req.headers().get(TUS_RESUMABLE).map(HeaderValue::as_bytes) != Some(TUS_VERSION.as_bytes()) {
+            res.headers_mut()
+                .insert(TUS_VERSION_HEADER, HeaderValue::from_static(TUS_VERSION));
+            res.render(StatusError::precondition_failed().brief("The tus protocol version is not supported."));
+            ctrl.skip_rest();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Action {
+    Options,
+    Create,
+    Head,
+    Patch,
+    Delete,
+    Progress,
+}
+
+struct Endpoint {
+    tus: Tus,
+    action: Action,
+}
+#[async_trait]
+impl Handler for Endpoint {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let result = match self.action {
+            Action::Options => {
+                self.options(res);
+                Ok(())
+            }
+            Action::Create => self.create(req, res).await,
+            action => {
+                let id = req.param::<String>("id").unwrap_or_default();
+                if id.is_empty() || !id.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                    res.render(StatusError::not_found());
+                    return;
+                }
+                match action {
+                    Action::Head => self.head(&id, res).await,
+                    Action::Patch => self.patch(&id, req, res).await,
+                    Action::Delete => self.delete(&id, res).await,
+                    _ => self.progress(&id, res).await,
+                }
+            }
+        };
+        if let Err(e) = result {
+            tracing::error!(error = ?e, "tus store error");
+            res.render(StatusError::internal_server_error());
+        }
+    }
+}
+
+impl Endpoint {
+    fn options(&self, res: &mut Response) {
+        let headers = res.headers_mut();
+        headers.insert(TUS_VERSION_HEADER, HeaderValue::from_static(TUS_VERSION));
+        headers.insert(TUS_EXTENSION, HeaderValue::from_static(TUS_EXTENSIONS));
+        if let Some(max_size) = self.tus.max_size {
+            headers.insert(TUS_MAX_SIZE, HeaderValue::from(max_size));
+        }
+        res.status_code(StatusCode::NO_CONTENT);
+    }
+
+    async fn create(&self, req: &mut Request, res: &mut Response) -> Result<(), Error> {
+        let length = match (
+            req.header::<u64>(UPLOAD_LENGTH),
+            req.header::<String>(UPLOAD_DEFER_LENGTH),
+        ) {
+            (Some(length), None) => Some(length),
+            (None, Some(defer)) if defer == "1" => None,
+            _ => {
+                res.render(
+                    StatusError::bad_request().brief("Either `Upload-Length` or `Upload-Defer-Length` is required."),
+                );
+                return Ok(());
+            }
+        };
+        if length.zip(self.tus.max_size).is_some_and(|(length, max)| length > max) {
+            res.render(StatusError::payload_too_large());
+            return Ok(());
+        }
+        let metadata = match req.header::<String>(UPLOAD_METADATA) {
+            Some(value) => match parse_metadata(&value) {
+                Some(metadata) => metadata,
+                None => {
+                    res.render(StatusError::bad_request().brief("The `Upload-Metadata` header is invalid."));
+                    return Ok(());
+                }
+            },
+            None => BTreeMap::new(),
+        };
+        let info = UploadInfo {
+            id: ulid::Ulid::new().to_string(),
+            offset: 0,
+            length,
+            metadata,
+            expires_at: self.tus.expiration.map(|expiration| unix_now() + expiration.as_secs()),
+        };
+        self.tus.store.create(&info).await?;
+        tracing::debug!(id = %info.id, ?length, "upload is created");
+
+        let location = format!("{}/{}", req.uri().path().trim_end_matches('/'), info.id);
+        if let Ok(location) = HeaderValue::from_str(&location) {
+            res.headers_mut().insert(LOCATION, location);
+        }
+        set_expires(res, &info);
+        res.status_code(StatusCode::CREATED);
+        Ok(())
+    }
+
+    async fn head(&self, id: &str, res: &mut Response) -> Result<(), Error> {
+        let Some(info) = self.tus.store.get(id).await? else {
+            res.render(StatusError::not_found());
+            return Ok(());
+        };
+        if info.is_expired() {
+            res.render(StatusError::gone());
+            return Ok(());
+        }
+        let headers = res.headers_mut();
+        headers.insert(UPLOAD_OFFSET, HeaderValue::from(info.offset));
+        match info.length {
+            Some(length) => headers.insert(UPLOAD_LENGTH, HeaderValue::from(length)),
+            None => headers.insert(UPLOAD_DEFER_LENGTH, HeaderValue::from_static("1")),
+        };
+        if !info.metadata.is_empty() {
+            if let Ok(metadata) = HeaderValue::from_str(&encode_metadata(&info.metadata)) {
+                headers.insert(UPLOAD_METADATA, metadata);
+            }
+        }
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        set_expires(res, &info);
+        res.status_code(StatusCode::OK);
+        Ok(())
+    }
+
+    async fn patch(&self, id: &str, req: &mut Request, res: &mut Response) -> Result<(), Error> {
+        if req.headers().get(CONTENT_TYPE).map(HeaderValue::as_bytes) != Some(OFFSET_OCTET_STREAM.as_bytes()) {
+            res.render(StatusError::unsupported_media_type());
+            return Ok(());
+        }
+        let Some(offset) = req.header::<u64>(UPLOAD_OFFSET) else {
+            res.render(StatusError::bad_request().brief("The `Upload-Offset` header is required."));
+            return Ok(());
+        };
+        let Some(_guard) = self.tus.lock(id) else {
+            res.render(StatusError::locked().brief("The upload is being written by another request."));
+            return Ok(());
+        };
+        let Some(mut info) = self.tus.store.get(id).await? else {
+            res.render(StatusError::not_found());
+            return Ok(());
+        };
+        if info.is_expired() {
+            res.render(StatusError::gone());
+            return Ok(());
+        }
+        if offset != info.offset {
+            res.render(StatusError::conflict().brief("The `Upload-Offset` does not match the offset of the upload."));
+            return Ok(());
+        }
+        if let (None, Some(length)) = (info.length, req.header::<u64>(UPLOAD_LENGTH)) {
+            if length < info.offset || self.tus.max_size.is_some_and(|max| length > max) {
+                res.render(StatusError::payload_too_large());
+                return Ok(());
+            }
+            self.tus.store.set_length(id, length).await?;
+            info.length = Some(length);
+        }
+
+        let limit = info.length.or(self.tus.max_size);
+        let mut body = req.take_body();
+        let mut exceeded = false;
+        while let Some(frame) = body.next().await {
+            let data = match frame {
+                Ok(frame) => match frame.into_data() {
+                    Ok(data) => data,
+                    Err(_) => continue,
+                },
+                Err(e) => {
+                    // The received bytes are kept, the client resumes from the stored offset.
+                    tracing::debug!(error = ?e, %id, "upload is interrupted");
+                    break;
+                }
+            };
+            if limit.is_some_and(|limit| info.offset + data.len() as u64 > limit) {
+                exceeded = true;
+                break;
+            }
+            info.offset = self.tus.store.append(id, data).await?;
+        }
+        if exceeded {
+            res.render(StatusError::payload_too_large());
+            return Ok(());
+        }
+
+        if info.is_completed() {
+            tracing::debug!(%id, "upload is completed");
+            if let Some(on_complete) = &self.tus.on_complete {
+                on_complete(&info);
+            }
+        }
+        res.headers_mut().insert(UPLOAD_OFFSET, HeaderValue::from(info.offset));
+        set_expires(res, &info);
+        res.status_code(StatusCode::NO_CONTENT);
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str, res: &mut Response) -> Result<(), Error> {
+        let Some(_guard) = self.tus.lock(id) else {
+            res.render(StatusError::locked().brief("The upload is being written by another request."));
+            return Ok(());
+        };
+        if self.tus.store.get(id).await?.is_none() {
+            res.render(StatusError::not_found());
+            return Ok(());
+        }
+        self.tus.store.remove(id).await?;
+        res.status_code(StatusCode::NO_CONTENT);
+        Ok(())
+    }
+
+    async fn progress(&self, id: &str, res: &mut Response) -> Result<(), Error> {
+        match self.tus.store.get(id).await? {
+            Some(info) => res.render(Json(serde_json::json!({
+                "id": info.id,
+                "offset": info.offset,
+                "length": info.length,
+                "completed": info.is_completed(),
+                "expired": info.is_expired(),
+                "metadata": info.metadata,
+                "expires_at": info.expires_at,
+            }))),
+            None => res.render(StatusError::not_found()),
+        }
+        Ok(())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn set_expires(res: &mut Response, info: &UploadInfo) {
+    if info.is_completed() {
+        return;
+    }
+    if let Some(expires_at) = info.expires_at {
+        let mut headers = salvo_core::http::HeaderMap::new();
+        headers.typed_insert(Expires::from(UNIX_EPOCH + Duration::from_secs(expires_at)));
+        if let Some(value) = headers.remove(salvo_core::http::header::EXPIRES) {
+            res.headers_mut().insert(UPLOAD_EXPIRES, value);
+        }
+    }
+}
+
+/// Parse an `Upload-Metadata` header, such as `filename d29ybGRfZG9taW5hdGlvbl9wbGFuLnBkZg==,is_confidential`.
+fn parse_metadata(value: &str) -> Option<BTreeMap<String, String>> {
+    let mut metadata = BTreeMap::new();
+    for pair in value.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let mut parts = pair.splitn(2, ' ');
+        let key = parts.next()?;
+        let value = match parts.next() {
+            Some(value) => String::from_utf8(general_purpose::STANDARD.decode(value.trim()).ok()?).ok()?,
+            None => String::new(),
+        };
+        metadata.insert(key.to_owned(), value);
+    }
+    Some(metadata)
+}
+
+fn encode_metadata(metadata: &BTreeMap<String, String>) -> String {
+    metadata
+        .iter()
+        .map(|(key, value)| {
+            if value.is_empty() {
+                key.clone()
+            } else {
+                format!("{key} {}", general_purpose::STANDARD.encode(value))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tus() {
+        let store = MemoryStore::new();
+        let completed = Arc::new(Mutex::new(vec![]));
+        let tus = Tus::new(store.clone()).max_size(16).on_complete({
+            let completed = completed.clone();
+            move |info| completed.lock().unwrap().push(info.id.clone())
+        });
+        let service = Service::new(Router::with_path("files").push(tus.router()));
+
+        let res = TestClient::post("http://127.0.0.1:5800/files")
+            .add_header("upload-length", "11", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PRECONDITION_FAILED));
+
+        let res = TestClient::post("http://127.0.0.1:5800/files")
+            .add_header("tus-resumable", TUS_VERSION, true)
+            .add_header("upload-length", "11", true)
+            .add_header("upload-metadata", "filename aGVsbG8udHh0,private", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        let location = res.headers().get(LOCATION).unwrap().to_str().unwrap().to_owned();
+        assert!(location.starts_with("/files/"));
+        let url = format!("http://127.0.0.1:5800{location}");
+        let id = location.trim_start_matches("/files/");
+        let info = store.get(id).await.unwrap().unwrap();
+        assert_eq!(info.metadata["filename"], "hello.txt");
+        assert_eq!(info.metadata["private"], "");
+
+        let patch = |offset: u64, body: &'static str| {
+            TestClient::patch(&url)
+                .add_header("tus-resumable", TUS_VERSION, true)
+                .add_header("content-type", OFFSET_OCTET_STREAM, true)
+                .add_header("upload-offset", offset, true)
+                .body(body)
+        };
+        let res = patch(0, "hello ").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(res.headers().get(UPLOAD_OFFSET).unwrap(), "6");
+        let res = patch(0, "hello ").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CONFLICT));
+
+        let res = TestClient::head(&url)
+            .add_header("tus-resumable", TUS_VERSION, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(UPLOAD_OFFSET).unwrap(), "6");
+        assert_eq!(res.headers().get(UPLOAD_LENGTH).unwrap(), "11");
+
+        let res = patch(6, "world!").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::PAYLOAD_TOO_LARGE));
+        let res = patch(6, "world").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert_eq!(store.content(id).unwrap(), b"hello world");
+        assert_eq!(*completed.lock().unwrap(), [id.to_owned()]);
+
+        let content = TestClient::get(&url).send(&service).await.take_string().await.unwrap();
+        let progress: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(progress["offset"], 11);
+        assert_eq!(progress["completed"], true);
+
+        let res = TestClient::delete(&url)
+            .add_header("tus-resumable", TUS_VERSION, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        assert!(store.get(id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired() {
+        let store = MemoryStore::new();
+        let tus = Tus::new(store.clone());
+        let mut expired = UploadInfo::new("expired");
+        expired.length = Some(10);
+        expired.expires_at = Some(unix_now() - 1);
+        store.create(&expired).await.unwrap();
+        let mut pending = UploadInfo::new("pending");
+        pending.expires_at = Some(unix_now() + 3600);
+        store.create(&pending).await.unwrap();
+
+        assert_eq!(tus.purge_expired().await.unwrap(), 1);
+        assert!(store.get("expired").await.unwrap().is_none());
+        assert!(tus.progress("pending").await.unwrap().is_some());
+    }
+}
@@ -97,6 +97,8 @@ use tokio_tungstenite::{
     WebSocketStream,
 };
 
+pub mod test;
+
 /// Creates a WebSocket Handler.
 /// Request:
 /// - Method must be `GET`
@@ -0,0 +1,274 @@
+//! Test client for websocket handlers.
+//!
+//! [`WebSocketClient`] upgrades a request to a [`Service`] over an in-memory connection, so websocket
+//! handlers can be tested inside `#[tokio::test]` without binding a port.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_extra::websocket::test::WebSocketClient;
+//! use salvo_extra::websocket::WebSocketUpgrade;
+//!
+//! #[handler]
+//! async fn echo(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+//!     WebSocketUpgrade::new()
+//!         .upgrade(req, res, |mut ws| async move {
+//!             while let Some(Ok(msg)) = ws.recv().await {
+//!                 if ws.send(msg).await.is_err() {
+//!                     return;
+//!                 }
+//!             }
+//!         })
+//!         .await
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let router = Router::with_path("ws").goal(echo);
+//!     let mut client = WebSocketClient::connect(router, "http://127.0.0.1:5800/ws").await.unwrap();
+//!     client.send_text("hello").await.unwrap();
+//!     assert_eq!(client.recv_text().await.unwrap(), "hello");
+//! }
+//! ```
+use std::convert::Infallible;
+use std::fmt::{self, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::sink::SinkExt;
+use futures_util::stream::StreamExt;
+use hyper::body::Incoming;
+use hyper::upgrade::Upgraded;
+use salvo_core::http::header::{CONNECTION, HOST, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_VERSION, UPGRADE};
+use salvo_core::http::{HeaderMap, ReqBody, StatusCode};
+use salvo_core::rt::tokio::TokioIo;
+use salvo_core::{Error, Service};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio_tungstenite::tungstenite::handshake::client::generate_key;
+use tokio_tungstenite::tungstenite::protocol::{self, Role};
+use tokio_tungstenite::WebSocketStream;
+
+use super::Message;
+
+/// A websocket client connected to a [`Service`] in-process.
+///
+/// Every send and receive operation fails with an error if it does not complete within the timeout,
+/// which is 5 seconds by default.
+pub struct WebSocketClient {
+    inner: WebSocketStream<TokioIo<Upgraded>>,
+    timeout: Duration,
+}
+
+impl fmt::Debug for WebSocketClient {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("WebSocketClient")
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl WebSocketClient {
+    /// Connect to `url` of the service and upgrade the connection to websocket.
+    pub async fn connect(service: impl Into<Service>, url: impl AsRef<str>) -> Result<Self, Error> {
+        Self::connect_with_headers(service, url, HeaderMap::new()).await
+    }
+
+    /// Connect to `url` of the service with additional request headers, such as cookies or authorization.
+    pub async fn connect_with_headers(
+        service: impl Into<Service>,
+        url: impl AsRef<str>,
+        headers: HeaderMap,
+    ) -> Result<Self, Error> {
+        let service = Arc::new(service.into());
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            let handler = hyper::service::service_fn(move |req: hyper::Request<Incoming>| {
+                let service = service.clone();
+                async move { Ok::<_, Infallible>(service.call(req).await) }
+            });
+            if let Err(e) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(server_io), handler)
+                .with_upgrades()
+                .await
+            {
+                tracing::debug!(error = ?e, "test websocket server connection error");
+            }
+        });
+
+        let (mut sender, conn) = hyper::client::conn::http1::handshake::<_, ReqBody>(TokioIo::new(client_io))
+            .await
+            .map_err(Error::other)?;
+        tokio::spawn(async move {
+            if let Err(e) = conn.with_upgrades().await {
+                tracing::debug!(error = ?e, "test websocket client connection error");
+            }
+        });
+
+        let uri: hyper::Uri = url.as_ref().parse().map_err(Error::other)?;
+        let mut builder = hyper::Request::builder().uri(uri.clone());
+        if let Some(authority) = uri.authority() {
+            builder = builder.header(HOST, authority.as_str());
+        }
+        let mut req = builder
+            .header(CONNECTION, "Upgrade")
+            .header(UPGRADE, "websocket")
+            .header(SEC_WEBSOCKET_VERSION, "13")
+            .header(SEC_WEBSOCKET_KEY, generate_key())
+            .body(ReqBody::None)
+            .map_err(Error::other)?;
+        req.headers_mut().extend(headers);
+
+        let res = sender.send_request(req).await.map_err(Error::other)?;
+        if res.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return Err(Error::other(format!(
+                "websocket upgrade failed with status `{}`",
+                res.status()
+            )));
+        }
+        let upgraded = hyper::upgrade::on(res).await.map_err(Error::other)?;
+        let inner = WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Client, None).await;
+        Ok(Self {
+            inner,
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// Sets the timeout of send and receive operations.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send a message.
+    pub async fn send(&mut self, msg: Message) -> Result<(), Error> {
+        tokio::time::timeout(self.timeout, self.inner.send(msg.inner))
+            .await
+            .map_err(|_| Error::other("timed out sending websocket message"))?
+            .map_err(Error::other)
+    }
+
+    /// Send a text message.
+    #[inline]
+    pub async fn send_text(&mut self, text: impl Into<String>) -> Result<(), Error> {
+        self.send(Message::text(text)).await
+    }
+
+    /// Send a binary message.
+    #[inline]
+    pub async fn send_binary(&mut self, data: impl Into<Vec<u8>>) -> Result<(), Error> {
+        self.send(Message::binary(data)).await
+    }
+
+    /// Send the JSON representation of `value` as a text message.
+    pub async fn send_json<T: Serialize + Sync>(&mut self, value: &T) -> Result<(), Error> {
+        let text = serde_json::to_string(value).map_err(Error::SerdeJson)?;
+        self.send_text(text).await
+    }
+
+    /// Receive the next message, including ping, pong and close messages.
+    ///
+    /// Returns an error if the connection is closed or no message is received within the timeout.
+    pub async fn recv(&mut self) -> Result<Message, Error> {
+        match tokio::time::timeout(self.timeout, self.inner.next()).await {
+            Ok(Some(Ok(inner))) => Ok(Message { inner }),
+            Ok(Some(Err(e))) => Err(Error::other(e)),
+            Ok(None) => Err(Error::other("websocket is closed")),
+            Err(_) => Err(Error::other("timed out receiving websocket message")),
+        }
+    }
+
+    /// Receive the next text message, ping and pong messages are skipped.
+    pub async fn recv_text(&mut self) -> Result<String, Error> {
+        loop {
+            let msg = self.recv().await?;
+            match msg.inner {
+                protocol::Message::Text(text) => return Ok(text),
+                protocol::Message::Ping(_) | protocol::Message::Pong(_) => continue,
+                other => return Err(Error::other(format!("expected text message, received `{other:?}`"))),
+            }
+        }
+    }
+
+    /// Receive the next binary message, ping and pong messages are skipped.
+    pub async fn recv_binary(&mut self) -> Result<Vec<u8>, Error> {
+        loop {
+            let msg = self.recv().await?;
+            match msg.inner {
+                protocol::Message::Binary(data) => return Ok(data),
+                protocol::Message::Ping(_) | protocol::Message::Pong(_) => continue,
+                other => return Err(Error::other(format!("expected binary message, received `{other:?}`"))),
+            }
+        }
+    }
+
+    /// Receive the next text message and deserialize it from JSON.
+    pub async fn recv_json<T: DeserializeOwned>(&mut self) -> Result<T, Error> {
+        let text = self.recv_text().await?;
+        serde_json::from_str(&text).map_err(Error::SerdeJson)
+    }
+
+    /// Gracefully close the websocket.
+    pub async fn close(mut self) -> Result<(), Error> {
+        tokio::time::timeout(self.timeout, self.inner.close(None))
+            .await
+            .map_err(|_| Error::other("timed out closing websocket"))?
+            .map_err(Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::websocket::WebSocketUpgrade;
+
+    #[handler]
+    async fn echo(req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+        WebSocketUpgrade::new()
+            .upgrade(req, res, |mut ws| async move {
+                while let Some(Ok(msg)) = ws.recv().await {
+                    if msg.is_close() || ws.send(msg).await.is_err() {
+                        return;
+                    }
+                }
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_websocket_client() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Chat {
+            text: String,
+        }
+
+        let router = Router::with_path("ws").goal(echo);
+        let mut client = WebSocketClient::connect(router, "http://127.0.0.1:5800/ws")
+            .await
+            .unwrap()
+            .timeout(Duration::from_millis(500));
+        client.send_text("hello").await.unwrap();
+        assert_eq!(client.recv_text().await.unwrap(), "hello");
+        client.send_binary(vec![1, 2, 3]).await.unwrap();
+        assert_eq!(client.recv_binary().await.unwrap(), vec![1, 2, 3]);
+        let chat = Chat { text: "hi".into() };
+        client.send_json(&chat).await.unwrap();
+        assert_eq!(client.recv_json::<Chat>().await.unwrap(), chat);
+        client.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_client_rejected() {
+        let router = Router::with_path("ws").get(echo);
+        let err = WebSocketClient::connect(router, "http://127.0.0.1:5800/other")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404"));
+    }
+}
@@ -0,0 +1,550 @@
+//! GraphQL support for Savlo web framework, built on [`async_graphql`].
+//!
+//! - [`GraphQL`] executes queries and mutations sent with `GET` or `POST`, including batch requests and file
+//!   uploads of the [GraphQL multipart request spec](https://github.com/jaydenseric/graphql-multipart-request-spec).
+//! - [`GraphQLSubscription`] serves subscriptions over WebSocket with the `graphql-transport-ws` or the legacy
+//!   `graphql-ws` protocol, selected by the `Sec-WebSocket-Protocol` header.
+//! - [`GraphiQL`] and [`Playground`] serve the bundled GraphQL IDEs.
+//!
+//! Request bodies are read with the max body size of the request's [`ParseConfig`](salvo_core::http::ParseConfig),
+//! or the global [`secure_max_size`](salvo_core::http::request::secure_max_size), it can be raised for file uploads
+//! with [`GraphQL::max_size`].
+//!
+//! Only [`async_graphql`] is supported, schemas built with juniper are not served by this crate.
+//!
+//! [`receive_request`] and [`write_response`] can be used to write customized handlers, for example to attach
+//! data from the [`Depot`](salvo_core::Depot) to the request.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+//! use salvo_core::prelude::*;
+//! use salvo_graphql::{GraphQL, GraphiQL};
+//!
+//! struct Query;
+//!
+//! #[Object]
+//! impl Query {
+//!     async fn hello(&self) -> &str {
+//!         "Hello World"
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+//!     let router = Router::with_path("graphql")
+//!         .get(GraphiQL::new("/graphql"))
+//!         .post(GraphQL::new(schema));
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::{ready, Future};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_graphql::http::{
+    playground_source, GraphQLPlaygroundConfig, GraphiQLSource, MultipartOptions, WebSocketProtocols, WsMessage,
+};
+use async_graphql::{BatchRequest, BatchResponse, Data, Executor, ParseRequestError};
+use futures_util::{SinkExt, StreamExt, TryStreamExt};
+use salvo_core::http::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE, SEC_WEBSOCKET_PROTOCOL};
+use salvo_core::http::{Method, StatusCode, StatusError};
+use salvo_core::writing::{Json, Text};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+use salvo_extra::websocket::{Message, WebSocketUpgrade};
+
+pub use async_graphql;
+
+/// Parse a GraphQL request from a `GET` query string or a `POST` body.
+///
+/// `POST` bodies may be a single request, a batch request or a multipart request with file uploads. The body is read
+/// with the max body size of the request's [`ParseConfig`](salvo_core::http::ParseConfig), or the global
+/// [`secure_max_size`](salvo_core::http::request::secure_max_size).
+#[inline]
+pub async fn receive_request(req: &mut Request, opts: MultipartOptions) -> Result<BatchRequest, ParseRequestError> {
+    let max_size = req.parse_config().body_size_limit();
+    receive_request_with_max_size(req, opts, max_size).await
+}
+
+/// Parse a GraphQL request from a `GET` query string or a `POST` body, reading at most `max_size` bytes of the body.
+///
+/// Returns [`ParseRequestError::PayloadTooLarge`] if the body is larger.
+pub async fn receive_request_with_max_size(
+    req: &mut Request,
+    opts: MultipartOptions,
+    max_size: usize,
+) -> Result<BatchRequest, ParseRequestError> {
+    if req.method() == Method::GET {
+        let query = req.uri().query().unwrap_or_default();
+        return async_graphql::http::parse_query_string(query).map(Into::into);
+    }
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(ToOwned::to_owned);
+    let exceeded = Arc::new(AtomicBool::new(false));
+    let mut remaining = max_size;
+    let body = req
+        .take_body()
+        .try_filter_map(|frame| ready(Ok(frame.into_data().ok())))
+        .and_then({
+            let exceeded = exceeded.clone();
+            move |data| {
+                let result = match remaining.checked_sub(data.len()) {
+                    Some(rest) => {
+                        remaining = rest;
+                        Ok(data)
+                    }
+                    None => {
+                        exceeded.store(true, Ordering::Relaxed);
+                        Err(std::io::Error::other("body size exceeds the limit"))
+                    }
+                };
+                ready(result)
+            }
+        })
+        .into_async_read();
+    let result = async_graphql::http::receive_batch_body(content_type, body, opts).await;
+    // The error of the body may be wrapped by the multipart parser, the flag tells whether it is caused by the limit.
+    if result.is_err() && exceeded.load(Ordering::Relaxed) {
+        return Err(ParseRequestError::PayloadTooLarge);
+    }
+    result
+}
+
+/// Write a GraphQL response as JSON, with its cache control and HTTP headers.
+pub fn write_response(res: &mut Response, resp: BatchResponse) {
+    if resp.is_ok() {
+        if let Some(value) = resp.cache_control().value() {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                res.headers_mut().insert(CACHE_CONTROL, value);
+            }
+        }
+    }
+    for (name, value) in resp.http_headers_iter() {
+        res.headers_mut().append(name, value);
+    }
+    res.render(Json(resp));
+}
+
+/// Handler that executes GraphQL queries and mutations with an [`Executor`], usually an
+/// [`async_graphql::Schema`].
+pub struct GraphQL<E> {
+    executor: E,
+    multipart_options: MultipartOptions,
+    max_size: Option<usize>,
+}
+impl<E> GraphQL<E>
+where
+    E: Executor,
+{
+    /// Create new `GraphQL` handler.
+    #[inline]
+    pub fn new(executor: E) -> Self {
+        Self {
+            executor,
+            multipart_options: MultipartOptions::default(),
+            max_size: None,
+        }
+    }
+
+    /// Set options of multipart requests, such as the max size and count of uploaded files.
+    #[inline]
+    pub fn multipart_options(mut self, opts: MultipartOptions) -> Self {
+        self.multipart_options = opts;
+        self
+    }
+
+    /// Set the max size of request bodies, including the uploaded files.
+    ///
+    /// The max body size of the request's [`ParseConfig`](salvo_core::http::ParseConfig), or the global
+    /// [`secure_max_size`](salvo_core::http::request::secure_max_size), is used if it is not set.
+    #[inline]
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+}
+impl<E> Debug for GraphQL<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GraphQL").finish()
+    }
+}
+
+#[async_trait]
+impl<E> Handler for GraphQL<E>
+where
+    E: Executor,
+{
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        if req.method() != Method::GET && req.method() != Method::POST {
+            res.status_code(StatusCode::METHOD_NOT_ALLOWED);
+            return;
+        }
+        let max_size = self.max_size.unwrap_or_else(|| req.parse_config().body_size_limit());
+        match receive_request_with_max_size(req, self.multipart_options, max_size).await {
+            Ok(batch) => {
+                let resp = self.executor.execute_batch(batch).await;
+                write_response(res, resp);
+            }
+            Err(ParseRequestError::PayloadTooLarge) => {
+                res.render(StatusError::payload_too_large());
+            }
+            Err(e) => {
+                tracing::debug!(error = ?e, "parse graphql request failed");
+                res.render(StatusError::bad_request().brief(e.to_string()));
+            }
+        }
+    }
+}
+
+type ConnectionInit =
+    dyn Fn(serde_json::Value) -> Pin<Box<dyn Future<Output = async_graphql::Result<Data>> + Send>> + Send + Sync;
+
+/// Handler that serves GraphQL subscriptions over WebSocket.
+pub struct GraphQLSubscription<E> {
+    executor: E,
+    on_connection_init: Option<Arc<ConnectionInit>>,
+}
+impl<E> GraphQLSubscription<E>
+where
+    E: Executor,
+{
+    /// Create new `GraphQLSubscription` handler.
+    #[inline]
+    pub fn new(executor: E) -> Self {
+        Self {
+            executor,
+            on_connection_init: None,
+        }
+    }
+
+    /// Set a callback invoked with the payload of the connection init message.
+    ///
+    /// The returned data is available to all operations of the connection, and an error closes it.
+    #[inline]
+    pub fn on_connection_init<F, Fut>(mut self, callback: F) -> Self
+    where
+        F: Fn(serde_json::Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = async_graphql::Result<Data>> + Send + 'static,
+    {
+        self.on_connection_init = Some(Arc::new(move |value| Box::pin(callback(value))));
+        self
+    }
+}
+impl<E> Debug for GraphQLSubscription<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GraphQLSubscription").finish()
+    }
+}
+
+#[async_trait]
+impl<E> Handler for GraphQLSubscription<E>
+where
+    E: Executor,
+{
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let protocol = req
+            .headers()
+            .get(SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').find_map(|p| WebSocketProtocols::from_str(p.trim()).ok()));
+        let Some(protocol) = protocol else {
+            res.render(StatusError::bad_request().brief("Unsupported websocket sub protocol."));
+            return;
+        };
+        res.headers_mut().insert(
+            SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static(protocol.sec_websocket_protocol()),
+        );
+
+        let executor = self.executor.clone();
+        let on_connection_init = self.on_connection_init.clone();
+        let result = WebSocketUpgrade::new()
+            .upgrade(req, res, move |ws| async move {
+                let (mut sink, stream) = ws.split();
+                let stream = stream
+                    .take_while(|msg| ready(matches!(msg, Ok(msg) if !msg.is_close())))
+                    .filter_map(|msg| {
+                        ready(match msg {
+                            Ok(msg) if msg.is_text() || msg.is_binary() => Some(msg.into_bytes()),
+                            _ => None,
+                        })
+                    });
+                let ws = async_graphql::http::WebSocket::new(executor, stream, protocol);
+                let mut ws = match on_connection_init {
+                    Some(callback) => ws.on_connection_init(move |value| callback(value)).boxed(),
+                    None => ws.boxed(),
+                };
+                while let Some(msg) = ws.next().await {
+                    let msg = match msg {
+                        WsMessage::Text(text) => Message::text(text),
+                        WsMessage::Close(code, reason) => Message::close_with(code, reason),
+                    };
+                    if let Err(e) = sink.send(msg).await {
+                        tracing::debug!(error = ?e, "send graphql websocket message failed");
+                        break;
+                    }
+                }
+            })
+            .await;
+        if let Err(e) = result {
+            res.render(e);
+        }
+    }
+}
+
+/// Handler that serves the GraphiQL IDE.
+#[derive(Clone, Debug)]
+pub struct GraphiQL {
+    endpoint: String,
+    subscription_endpoint: Option<String>,
+    title: Option<String>,
+}
+impl GraphiQL {
+    /// Create new `GraphiQL` handler which sends requests to `endpoint`.
+    #[inline]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            subscription_endpoint: None,
+            title: None,
+        }
+    }
+
+    /// Set the endpoint of subscriptions.
+    #[inline]
+    pub fn subscription_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.subscription_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Set the title of the page.
+    #[inline]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Render the page to HTML.
+    pub fn to_html(&self) -> String {
+        let mut source = GraphiQLSource::build().endpoint(&self.endpoint);
+        if let Some(endpoint) = &self.subscription_endpoint {
+            source = source.subscription_endpoint(endpoint);
+        }
+        if let Some(title) = &self.title {
+            source = source.title(title);
+        }
+        source.finish()
+    }
+}
+#[async_trait]
+impl Handler for GraphiQL {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        res.render(Text::Html(self.to_html()));
+    }
+}
+
+/// Handler that serves the GraphQL Playground IDE.
+#[derive(Clone, Debug)]
+pub struct Playground {
+    endpoint: String,
+    subscription_endpoint: Option<String>,
+    title: Option<String>,
+}
+impl Playground {
+    /// Create new `Playground` handler which sends requests to `endpoint`.
+    #[inline]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            subscription_endpoint: None,
+            title: None,
+        }
+    }
+
+    /// Set the endpoint of subscriptions.
+    #[inline]
+    pub fn subscription_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.subscription_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Set the title of the page.
+    #[inline]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Render the page to HTML.
+    pub fn to_html(&self) -> String {
+        let mut config = GraphQLPlaygroundConfig::new(&self.endpoint);
+        if let Some(endpoint) = &self.subscription_endpoint {
+            config = config.subscription_endpoint(endpoint);
+        }
+        if let Some(title) = &self.title {
+            config = config.title(title);
+        }
+        playground_source(config)
+    }
+}
+#[async_trait]
+impl Handler for Playground {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        res.render(Text::Html(self.to_html()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{Context, EmptySubscription, Object, Schema, Upload};
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    struct Query;
+    #[Object]
+    impl Query {
+        async fn add(&self, a: i32, b: i32) -> i32 {
+            a + b
+        }
+    }
+
+    struct Mutation;
+    #[Object]
+    impl Mutation {
+        async fn upload(&self, ctx: &Context<'_>, file: Upload) -> async_graphql::Result<String> {
+            let value = file.value(ctx)?;
+            Ok(format!("{}:{}", value.filename, value.size()?))
+        }
+    }
+
+    fn service() -> Service {
+        let schema = Schema::new(Query, Mutation, EmptySubscription);
+        let router = Router::with_path("graphql")
+            .get(GraphQL::new(schema.clone()))
+            .post(GraphQL::new(schema));
+        Service::new(router)
+    }
+
+    #[tokio::test]
+    async fn test_get() {
+        let content = TestClient::get("http://127.0.0.1:5800/graphql?query=%7Badd(a%3A1%2Cb%3A2)%7D")
+            .send(&service())
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, r#"{"data":{"add":3}}"#);
+    }
+
+    #[tokio::test]
+    async fn test_post() {
+        let service = service();
+        let content = TestClient::post("http://127.0.0.1:5800/graphql")
+            .raw_json(r#"{"query":"{ add(a: 2, b: 3) }"}"#)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, r#"{"data":{"add":5}}"#);
+
+        let content = TestClient::post("http://127.0.0.1:5800/graphql")
+            .raw_json(r#"[{"query":"{ add(a: 1, b: 1) }"},{"query":"{ add(a: 2, b: 2) }"}]"#)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, r#"[{"data":{"add":2}},{"data":{"add":4}}]"#);
+
+        let res = TestClient::post("http://127.0.0.1:5800/graphql")
+            .raw_json("{")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_upload() {
+        let body = "--BOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"operations\"\r\n\r\n\
+            {\"query\":\"mutation($file: Upload!) { upload(file: $file) }\",\"variables\":{\"file\":null}}\r\n\
+            --BOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"map\"\r\n\r\n\
+            {\"0\":[\"variables.file\"]}\r\n\
+            --BOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"0\"; filename=\"a.txt\"\r\n\
+            Content-Type: text/plain\r\n\r\n\
+            hello\r\n\
+            --BOUNDARY--\r\n";
+        let content = TestClient::post("http://127.0.0.1:5800/graphql")
+            .add_header(CONTENT_TYPE, "multipart/form-data; boundary=BOUNDARY", true)
+            .body(body)
+            .send(&service())
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, r#"{"data":{"upload":"a.txt:5"}}"#);
+
+        let schema = Schema::new(Query, Mutation, EmptySubscription);
+        let service = Service::new(Router::with_path("graphql").post(GraphQL::new(schema).max_size(64)));
+        let res = TestClient::post("http://127.0.0.1:5800/graphql")
+            .add_header(CONTENT_TYPE, "multipart/form-data; boundary=BOUNDARY", true)
+            .body(body)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PAYLOAD_TOO_LARGE));
+        let res = TestClient::post("http://127.0.0.1:5800/graphql")
+            .raw_json(format!(
+                r#"{{"query":"{{ add(a: 2, b: 3) }}","variables":{{"a":"{}"}}}}"#,
+                "a".repeat(64)
+            ))
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PAYLOAD_TOO_LARGE));
+    }
+
+    #[tokio::test]
+    async fn test_subscription_protocol() {
+        let schema = Schema::new(Query, Mutation, EmptySubscription);
+        let service = Service::new(Router::new().goal(GraphQLSubscription::new(schema)));
+        let res = TestClient::get("http://127.0.0.1:5800/")
+            .add_header(SEC_WEBSOCKET_PROTOCOL, "unknown", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_ide() {
+        let service = Service::new(Router::new().get(GraphiQL::new("/graphql").subscription_endpoint("/graphql/ws")));
+        let content = TestClient::get("http://127.0.0.1:5800/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(content.contains("/graphql/ws"));
+
+        let html = Playground::new("/graphql").title("Salvo").to_html();
+        assert!(html.contains("/graphql"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Catalogs of [Fluent](https://projectfluent.org) messages.
+use std::fmt::{self, Debug, Formatter};
+use std::path::Path;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use salvo_core::Error;
+use unic_langid::LanguageIdentifier;
+
+use crate::{ArgValue, Catalog};
+
+/// A [`Catalog`] of Fluent messages of a locale.
+///
+/// Message attributes are translated with the `message.attribute` key.
+pub struct FluentCatalog {
+    bundle: FluentBundle<FluentResource>,
+}
+impl Debug for FluentCatalog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FluentCatalog")
+            .field("locales", &self.bundle.locales)
+            .finish()
+    }
+}
+impl FluentCatalog {
+    /// Create new empty `FluentCatalog`.
+    pub fn new(locale: LanguageIdentifier) -> Self {
+        let mut bundle = FluentBundle::new_concurrent(vec![locale]);
+        bundle.set_use_isolating(false);
+        Self { bundle }
+    }
+
+    /// Add messages in Fluent syntax.
+    pub fn add_source(&mut self, source: impl Into<String>) -> Result<(), Error> {
+        let resource = FluentResource::try_new(source.into())
+            .map_err(|(_, errors)| Error::other(format!("invalid fluent source: {errors:?}")))?;
+        self.bundle
+            .add_resource(resource)
+            .map_err(|errors| Error::other(format!("add fluent resource failed: {errors:?}")))
+    }
+
+    /// Add messages from a `.ftl` file.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.add_source(std::fs::read_to_string(path)?)
+    }
+}
+impl Catalog for FluentCatalog {
+    fn translate(&self, key: &str, args: &[(&str, ArgValue)]) -> Option<String> {
+        let (id, attribute) = match key.split_once('.') {
+            Some((id, attribute)) => (id, Some(attribute)),
+            None => (key, None),
+        };
+        let message = self.bundle.get_message(id)?;
+        let pattern = match attribute {
+            Some(attribute) => message.get_attribute(attribute)?.value(),
+            None => message.value()?,
+        };
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            let value = match value {
+                ArgValue::String(value) => FluentValue::from(value.as_str()),
+                ArgValue::Number(value) => FluentValue::from(*value),
+            };
+            fluent_args.set(*name, value);
+        }
+        let mut errors = Vec::new();
+        let text = self.bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+        if !errors.is_empty() {
+            tracing::warn!(key, ?errors, "format fluent message failed");
+        }
+        Some(text.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plural() {
+        let mut catalog = FluentCatalog::new("en".parse().unwrap());
+        catalog
+            .add_source(
+                r#"
+inbox = { $count ->
+    [0] Your inbox is empty
+    [one] You have one message
+   *[other] You have { $count } messages
+}
+login = Log in
+    .title = Log in to { $site }
+"#,
+            )
+            .unwrap();
+        let inbox = |count: u32| catalog.translate("inbox", &[("count", count.into())]).unwrap();
+        assert_eq!(inbox(0), "Your inbox is empty");
+        assert_eq!(inbox(1), "You have one message");
+        assert_eq!(inbox(5), "You have 5 messages");
+        assert_eq!(catalog.translate("login", &[]).unwrap(), "Log in");
+        assert_eq!(
+            catalog.translate("login.title", &[("site", "Salvo".into())]).unwrap(),
+            "Log in to Salvo"
+        );
+        assert!(catalog.translate("missing", &[]).is_none());
+        assert!(catalog.add_source("= broken").is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! Catalogs of gettext messages.
+use std::fmt::{self, Debug, Formatter};
+use std::io::Read;
+
+use salvo_core::Error;
+
+use crate::{ArgValue, Catalog};
+
+/// A [`Catalog`] of messages compiled into a gettext `.mo` file.
+///
+/// Plural forms are selected with the `count` argument, and `{name}` placeholders in the messages are replaced
+/// with the arguments.
+pub struct GettextCatalog {
+    catalog: gettext::Catalog,
+}
+impl Debug for GettextCatalog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GettextCatalog").finish()
+    }
+}
+impl GettextCatalog {
+    /// Parse a `.mo` file.
+    pub fn parse(reader: impl Read) -> Result<Self, Error> {
+        let catalog = gettext::Catalog::parse(reader).map_err(Error::other)?;
+        Ok(Self { catalog })
+    }
+}
+impl Catalog for GettextCatalog {
+    fn translate(&self, key: &str, args: &[(&str, ArgValue)]) -> Option<String> {
+        let count = args.iter().find_map(|(name, value)| match value {
+            ArgValue::Number(count) if *name == "count" => Some(*count),
+            _ => None,
+        });
+        let text = match count {
+            Some(count) => self.catalog.ngettext(key, key, count.abs() as u64),
+            None => self.catalog.gettext(key),
+        };
+        // gettext returns the message id itself for missing messages.
+        if std::ptr::eq(text, key) {
+            return None;
+        }
+        let mut text = text.to_owned();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        Some(text)
+    }
+}
@@ -0,0 +1,467 @@
+//! Internationalization for Savlo web framework.
+//!
+//! [`I18n`] is a middleware which negotiates the locale of each request and injects a [`Translator`] into the
+//! [`Depot`]. The locale is taken from, in order of priority:
+//!
+//! 1. the `lang` query parameter;
+//! 2. the `lang` cookie;
+//! 3. the `Accept-Language` header;
+//! 4. the default locale.
+//!
+//! A requested locale matches a catalog of the same locale, or else a catalog of the same language, so `en-US`
+//! falls back to `en`. Messages missing in the negotiated locale are translated with the default locale, and
+//! messages missing in all catalogs are translated to the key itself.
+//!
+//! Messages are loaded from [Fluent](https://projectfluent.org) files with the `fluent` feature, or gettext
+//! `.mo` files with the `gettext` feature. Other formats can be supported by implementing [`Catalog`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_i18n::{t, I18n};
+//!
+//! #[handler]
+//! async fn hello(depot: &mut Depot) -> String {
+//!     t!(depot, "inbox", name = "Chris", count = 3)
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let i18n = I18n::new("en").load_fluent_dir("locales").unwrap();
+//!     let router = Router::new().hoop(i18n).get(hello);
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+//!
+//! # Templates
+//!
+//! [`Translator`] is cheap to clone, so it can be passed to templates:
+//!
+//! ```ignore
+//! #[derive(Template)]
+//! #[template(source = "<h1>{{ t.t(\"welcome\") }}</h1>", ext = "html")]
+//! struct WelcomeTemplate {
+//!     t: Translator,
+//! }
+//!
+//! #[handler]
+//! async fn welcome(depot: &mut Depot, res: &mut Response) {
+//!     let t = depot.translator().unwrap().clone();
+//!     res.render(Text::Html(WelcomeTemplate { t }.render().unwrap()));
+//! }
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::Arc;
+
+use salvo_core::http::header::{HeaderValue, ACCEPT_LANGUAGE, CONTENT_LANGUAGE};
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Request, Response};
+pub use unic_langid::LanguageIdentifier;
+
+#[macro_use]
+mod cfg;
+
+cfg_feature! {
+    #![feature = "fluent"]
+    mod fluent;
+    pub use fluent::FluentCatalog;
+}
+
+cfg_feature! {
+    #![feature = "gettext"]
+    mod gettext;
+    pub use gettext::GettextCatalog;
+}
+
+/// Value of a message argument.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArgValue {
+    /// A string.
+    String(String),
+    /// A number, which also selects plural forms.
+    Number(f64),
+}
+impl Display for ArgValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) => Display::fmt(value, f),
+            Self::Number(value) => Display::fmt(value, f),
+        }
+    }
+}
+impl From<&str> for ArgValue {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+impl From<String> for ArgValue {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+impl From<&String> for ArgValue {
+    #[inline]
+    fn from(value: &String) -> Self {
+        Self::String(value.clone())
+    }
+}
+macro_rules! impl_from_number {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for ArgValue {
+                #[inline]
+                fn from(value: $ty) -> Self {
+                    Self::Number(value as f64)
+                }
+            }
+        )*
+    };
+}
+impl_from_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// A catalog of translated messages of a locale.
+pub trait Catalog: Send + Sync + 'static {
+    /// Translate a message with the arguments, returns `None` if the message does not exist.
+    fn translate(&self, key: &str, args: &[(&str, ArgValue)]) -> Option<String>;
+}
+
+/// Translator of the negotiated locale, injected into the [`Depot`] by [`I18n`].
+#[derive(Clone)]
+pub struct Translator {
+    locale: LanguageIdentifier,
+    catalogs: Vec<Arc<dyn Catalog>>,
+}
+impl Debug for Translator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Translator").field("locale", &self.locale).finish()
+    }
+}
+impl Translator {
+    /// Get the negotiated locale.
+    #[inline]
+    pub fn locale(&self) -> &LanguageIdentifier {
+        &self.locale
+    }
+
+    /// Translate a message.
+    #[inline]
+    pub fn t(&self, key: &str) -> String {
+        self.t_with(key, &[])
+    }
+
+    /// Translate a message with the arguments.
+    pub fn t_with(&self, key: &str, args: &[(&str, ArgValue)]) -> String {
+        self.catalogs
+            .iter()
+            .find_map(|catalog| catalog.translate(key, args))
+            .unwrap_or_else(|| {
+                tracing::debug!(key, locale = %self.locale, "message is missing");
+                key.to_owned()
+            })
+    }
+}
+
+/// Translate a message with the [`Translator`] in the [`Depot`].
+///
+/// Arguments are given as `name = value`, where the value is converted to [`ArgValue`]. The key itself is
+/// returned if there is no translator in the depot.
+///
+/// ```
+/// # use salvo_core::Depot;
+/// # use salvo_i18n::t;
+/// # let depot = Depot::new();
+/// let count = 3;
+/// let greeting = t!(&depot, "hello");
+/// let inbox = t!(&depot, "inbox", name = "Chris", count = count);
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($depot:expr, $key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        match $crate::I18nDepotExt::translator(&*$depot) {
+            Some(translator) => translator.t_with($key, &[$((stringify!($name), $crate::ArgValue::from($value))),*]),
+            None => ::std::string::ToString::to_string($key),
+        }
+    };
+}
+
+/// Extension for Depot.
+pub trait I18nDepotExt {
+    /// Get the [`Translator`] injected by [`I18n`].
+    fn translator(&self) -> Option<&Translator>;
+}
+impl I18nDepotExt for Depot {
+    #[inline]
+    fn translator(&self) -> Option<&Translator> {
+        self.obtain::<Translator>().ok()
+    }
+}
+
+/// Middleware which negotiates the locale of requests and injects a [`Translator`] into the [`Depot`].
+pub struct I18n {
+    default_locale: LanguageIdentifier,
+    catalogs: Vec<(LanguageIdentifier, Arc<dyn Catalog>)>,
+    query_param: Option<String>,
+    cookie: Option<String>,
+}
+impl Debug for I18n {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("I18n")
+            .field("default_locale", &self.default_locale)
+            .field("locales", &self.locales().collect::<Vec<_>>())
+            .field("query_param", &self.query_param)
+            .field("cookie", &self.cookie)
+            .finish()
+    }
+}
+impl I18n {
+    /// Create new `I18n` with the default locale.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the default locale is not a valid language identifier.
+    pub fn new(default_locale: &str) -> Self {
+        Self {
+            default_locale: default_locale.parse().expect("default locale is invalid"),
+            catalogs: Vec::new(),
+            query_param: Some("lang".into()),
+            cookie: Some("lang".into()),
+        }
+    }
+
+    /// Add the catalog of a locale, it replaces the existing catalog of the locale.
+    pub fn catalog(mut self, locale: LanguageIdentifier, catalog: impl Catalog) -> Self {
+        let catalog = Arc::new(catalog) as Arc<dyn Catalog>;
+        match self.catalogs.iter_mut().find(|(existing, _)| *existing == locale) {
+            Some((_, existing)) => *existing = catalog,
+            None => self.catalogs.push((locale, catalog)),
+        }
+        self
+    }
+
+    /// Set the query parameter the locale is taken from, `None` to disable, default is `lang`.
+    #[inline]
+    pub fn query_param(mut self, name: Option<impl Into<String>>) -> Self {
+        self.query_param = name.map(Into::into);
+        self
+    }
+
+    /// Set the cookie the locale is taken from, `None` to disable, default is `lang`.
+    #[inline]
+    pub fn cookie(mut self, name: Option<impl Into<String>>) -> Self {
+        self.cookie = name.map(Into::into);
+        self
+    }
+
+    /// Get the locales with a catalog.
+    #[inline]
+    pub fn locales(&self) -> impl Iterator<Item = &LanguageIdentifier> {
+        self.catalogs.iter().map(|(locale, _)| locale)
+    }
+
+    /// Get the [`Translator`] of the locale which best matches the requested locales, in order of preference.
+    pub fn translator<'a>(&self, requested: impl IntoIterator<Item = &'a str>) -> Translator {
+        let locale = requested
+            .into_iter()
+            .filter_map(|locale| locale.parse::<LanguageIdentifier>().ok())
+            .find_map(|requested| self.negotiate(&requested))
+            .unwrap_or(&self.default_locale);
+        let mut catalogs = Vec::with_capacity(2);
+        for wanted in [locale, &self.default_locale] {
+            if let Some((_, catalog)) = self.catalogs.iter().find(|(locale, _)| locale == wanted) {
+                if !catalogs.iter().any(|existing| Arc::ptr_eq(existing, catalog)) {
+                    catalogs.push(catalog.clone());
+                }
+            }
+        }
+        Translator {
+            locale: locale.clone(),
+            catalogs,
+        }
+    }
+
+    fn negotiate(&self, requested: &LanguageIdentifier) -> Option<&LanguageIdentifier> {
+        self.locales()
+            .find(|locale| *locale == requested)
+            .or_else(|| self.locales().find(|locale| locale.language == requested.language))
+    }
+
+    fn requested_locales(&self, req: &Request) -> Vec<String> {
+        let mut locales = Vec::new();
+        if let Some(locale) = self.query_param.as_deref().and_then(|name| req.query::<String>(name)) {
+            locales.push(locale);
+        }
+        if let Some(cookie) = self.cookie.as_deref().and_then(|name| req.cookie(name)) {
+            locales.push(cookie.value().to_owned());
+        }
+        if let Some(accept) = req.headers().get(ACCEPT_LANGUAGE).and_then(|value| value.to_str().ok()) {
+            locales.extend(parse_accept_language(accept));
+        }
+        locales
+    }
+}
+
+cfg_feature! {
+    #![feature = "fluent"]
+    impl I18n {
+        /// Add messages of a locale in Fluent syntax.
+        pub fn add_fluent(self, locale: &str, source: impl Into<String>) -> Result<Self, Error> {
+            let locale: LanguageIdentifier = locale.parse().map_err(Error::other)?;
+            let mut catalog = FluentCatalog::new(locale.clone());
+            catalog.add_source(source)?;
+            Ok(self.catalog(locale, catalog))
+        }
+
+        /// Load Fluent messages from a directory of locales, laid out as `<dir>/<locale>/*.ftl`.
+        pub fn load_fluent_dir(mut self, dir: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                let Some(locale) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                if !path.is_dir() {
+                    continue;
+                }
+                let locale: LanguageIdentifier = locale.parse().map_err(Error::other)?;
+                let mut catalog = FluentCatalog::new(locale.clone());
+                for file in std::fs::read_dir(&path)? {
+                    let file = file?.path();
+                    if file.extension().is_some_and(|ext| ext == "ftl") {
+                        catalog.add_file(&file)?;
+                    }
+                }
+                self = self.catalog(locale, catalog);
+            }
+            Ok(self)
+        }
+    }
+}
+
+cfg_feature! {
+    #![feature = "gettext"]
+    impl I18n {
+        /// Add messages of a locale from a gettext `.mo` file.
+        pub fn add_gettext(self, locale: &str, reader: impl std::io::Read) -> Result<Self, Error> {
+            let locale: LanguageIdentifier = locale.parse().map_err(Error::other)?;
+            Ok(self.catalog(locale, GettextCatalog::parse(reader)?))
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for I18n {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let requested = self.requested_locales(req);
+        let translator = self.translator(requested.iter().map(String::as_str));
+        if let Ok(value) = HeaderValue::from_str(&translator.locale.to_string()) {
+            res.headers_mut().insert(CONTENT_LANGUAGE, value);
+        }
+        depot.inject(translator);
+        ctrl.call_next(req, depot, res).await;
+    }
+}
+
+/// Parse the locales in an `Accept-Language` header, ordered by quality.
+fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut locales = header
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let locale = parts.next()?.trim();
+            let quality = parts
+                .find_map(|part| part.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!locale.is_empty() && locale != "*" && quality > 0.0).then(|| (locale.to_owned(), quality))
+        })
+        .collect::<Vec<_>>();
+    locales.sort_by(|a, b| b.1.total_cmp(&a.1));
+    locales.into_iter().map(|(locale, _)| locale).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_accept_language() {
+        assert_eq!(
+            parse_accept_language("fr-CH, fr;q=0.9, en;q=0.8, de;q=0.95, *;q=0.5"),
+            vec!["fr-CH", "de", "fr", "en"]
+        );
+        assert_eq!(parse_accept_language("en;q=0, zh"), vec!["zh"]);
+    }
+
+    #[handler]
+    async fn inbox(depot: &mut Depot) -> String {
+        let count = 2;
+        format!(
+            "{} {}",
+            t!(depot, "hello", name = "Chris"),
+            t!(depot, "inbox", count = count)
+        )
+    }
+
+    fn service() -> Service {
+        let i18n = I18n::new("en")
+            .add_fluent(
+                "en",
+                r#"
+hello = Hello { $name }
+inbox = { $count ->
+    [one] one message
+   *[other] { $count } messages
+}
+"#,
+            )
+            .unwrap()
+            .add_fluent("zh-CN", "hello = 你好 { $name }")
+            .unwrap();
+        Service::new(Router::new().hoop(i18n).get(inbox))
+    }
+
+    #[tokio::test]
+    async fn test_negotiate() {
+        let service = service();
+        let mut res = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "Hello Chris 2 messages");
+        assert_eq!(res.headers().get(CONTENT_LANGUAGE).unwrap(), "en");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header(ACCEPT_LANGUAGE, "de, zh-TW;q=0.9, en;q=0.5", true)
+            .send(&service)
+            .await;
+        // `zh-TW` falls back to `zh-CN`, and `inbox` falls back to the default locale.
+        assert_eq!(res.take_string().await.unwrap(), "你好 Chris 2 messages");
+        assert_eq!(res.headers().get(CONTENT_LANGUAGE).unwrap(), "zh-CN");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/?lang=en")
+            .add_header(ACCEPT_LANGUAGE, "zh", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "Hello Chris 2 messages");
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/")
+            .add_header("cookie", "lang=zh-CN", true)
+            .add_header(ACCEPT_LANGUAGE, "en", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "你好 Chris 2 messages");
+    }
+
+    #[test]
+    fn test_missing_translator() {
+        let depot = Depot::new();
+        assert_eq!(t!(&depot, "hello", name = "Chris"), "hello");
+    }
+}
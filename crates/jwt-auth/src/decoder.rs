@@ -2,8 +2,13 @@ use jsonwebtoken::errors::Error as JwtError;
 use jsonwebtoken::{decode, Algorithm, DecodingKey, TokenData, Validation};
 use serde::Deserialize;
 use std::future::Future;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::sync::{Arc, RwLock};
 
+use futures_util::StreamExt;
+use salvo_core::secret::{Secret, SecretProvider, SecretWatcher};
 use salvo_core::Depot;
+use tokio::task::JoinHandle;
 
 /// JwtAuthDecoder is used to decode token to claims.
 pub trait JwtAuthDecoder {
@@ -120,3 +125,68 @@ impl JwtAuthDecoder for ConstDecoder {
         decode::<C>(token, &self.decoding_key, &self.validation)
     }
 }
+
+/// SecretDecoder will decode token with a key loaded from a [`SecretProvider`], and reload the key when it rotates.
+///
+/// Tokens are decoded with the previous key until the new key is loaded. If the new key is invalid, the previous key
+/// is kept and the error is logged.
+pub struct SecretDecoder {
+    current: Arc<RwLock<Arc<ConstDecoder>>>,
+    reloader: JoinHandle<()>,
+}
+
+impl SecretDecoder {
+    /// Create a new `SecretDecoder`, `build` creates the decoder from the secret, such as
+    /// `|secret| Ok(ConstDecoder::from_secret(secret.expose()))`.
+    ///
+    /// Returns an error if the secret can not be loaded, or an error of kind [`ErrorKind::InvalidData`] if the
+    /// decoder can not be built.
+    pub async fn new<P, F>(watcher: SecretWatcher<P>, name: &str, build: F) -> IoResult<Self>
+    where
+        P: SecretProvider + ?Sized,
+        F: Fn(&Secret) -> Result<ConstDecoder, JwtError> + Send + Sync + 'static,
+    {
+        let secrets = watcher.load([name]).await?;
+        let decoder = build(&secrets[0]).map_err(|e| IoError::new(ErrorKind::InvalidData, e))?;
+        let current = Arc::new(RwLock::new(Arc::new(decoder)));
+        let mut updates = Box::pin(watcher.watch([name]));
+        let reloader = tokio::spawn({
+            let current = current.clone();
+            async move {
+                while let Some(secrets) = updates.next().await {
+                    match build(&secrets[0]) {
+                        Ok(decoder) => {
+                            *current.write().expect("lock should not be poisoned") = Arc::new(decoder);
+                        }
+                        Err(e) => {
+                            tracing::error!(error = ?e, "failed to build decoder from rotated secret");
+                        }
+                    }
+                }
+            }
+        });
+        Ok(Self { current, reloader })
+    }
+
+    fn current(&self) -> Arc<ConstDecoder> {
+        self.current.read().expect("lock should not be poisoned").clone()
+    }
+}
+
+impl Drop for SecretDecoder {
+    fn drop(&mut self) {
+        self.reloader.abort();
+    }
+}
+
+impl JwtAuthDecoder for SecretDecoder {
+    type Error = JwtError;
+
+    async fn decode<C>(&self, token: &str, _depot: &mut Depot) -> Result<TokenData<C>, Self::Error>
+    where
+        C: for<'de> Deserialize<'de>,
+    {
+        let decoder = self.current();
+        decode::<C>(token, &decoder.decoding_key, &decoder.validation)
+    }
+}
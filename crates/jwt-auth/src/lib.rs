@@ -118,7 +118,7 @@ mod finder;
 pub use finder::{CookieFinder, FormFinder, HeaderFinder, JwtTokenFinder, QueryFinder};
 
 mod decoder;
-pub use decoder::{ConstDecoder, JwtAuthDecoder};
+pub use decoder::{ConstDecoder, JwtAuthDecoder, SecretDecoder};
 
 #[macro_use]
 mod cfg;
@@ -431,4 +431,33 @@ mod tests {
         let content = access(&service, &token).await;
         assert!(content.contains("Forbidden"));
     }
+
+    #[tokio::test]
+    async fn test_secret_decoder() {
+        use salvo_core::secret::{EnvSecrets, SecretWatcher};
+
+        let secrets = EnvSecrets::with_vars([("SALVO_JWT_TEST_KEY", "ABCDEF")]).prefix("SALVO_JWT_TEST_");
+        let watcher = SecretWatcher::new(secrets);
+        let decoder = SecretDecoder::new(watcher, "key", |secret| Ok(ConstDecoder::from_secret(secret.expose())))
+            .await
+            .unwrap();
+        let claim = JwtClaims {
+            user: "root".into(),
+            exp: (OffsetDateTime::now_utc() + Duration::days(1)).unix_timestamp(),
+        };
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claim,
+            &EncodingKey::from_secret(b"ABCDEF"),
+        )
+        .unwrap();
+        let data = decoder.decode::<JwtClaims>(&token, &mut Depot::new()).await.unwrap();
+        assert_eq!(data.claims.user, "root");
+
+        let watcher = SecretWatcher::new(EnvSecrets::new().prefix("SALVO_JWT_MISSING_"));
+        assert!(matches!(
+            SecretDecoder::new(watcher, "key", |secret| Ok(ConstDecoder::from_secret(secret.expose()))).await,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound
+        ));
+    }
 }
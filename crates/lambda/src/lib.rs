@@ -0,0 +1,225 @@
+//! AWS Lambda support for Savlo web framework.
+//!
+//! [`run`] serves a [`Service`] with the [`lambda_http`] runtime, so the same router used with a [`Server`] can be
+//! deployed to Lambda unchanged. Events of API Gateway REST APIs (v1), HTTP APIs (v2) and Application Load
+//! Balancers are translated by `lambda_http`, including base64 encoded bodies and multi-value headers and queries.
+//!
+//! The remote address of requests is taken from the source IP of API Gateway events, or from the last
+//! `X-Forwarded-For` address of ALB events, which is the address the load balancer received the request from. The
+//! other addresses are sent by the client and can not be trusted.
+//!
+//! [`Server`]: salvo_core::Server
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), salvo_lambda::Error> {
+//!     let router = Router::new().get(hello);
+//!     salvo_lambda::run(router).await
+//! }
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use std::net::{IpAddr, SocketAddr as StdSocketAddr};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use lambda_http::request::RequestContext;
+use lambda_http::{service_fn, Body, RequestExt};
+use salvo_core::conn::SocketAddr;
+use salvo_core::http::header::{CONTENT_ENCODING, CONTENT_TYPE};
+use salvo_core::http::uri::Scheme;
+use salvo_core::http::{ReqBody, ResBody};
+use salvo_core::{hyper, Request, Service};
+
+pub use lambda_http::Error;
+
+/// Serve `service` with the Lambda runtime until it is shut down.
+pub async fn run(service: impl Into<Service>) -> Result<(), Error> {
+    let service = Arc::new(service.into());
+    lambda_http::run(service_fn(move |req: lambda_http::Request| {
+        let service = service.clone();
+        async move { handle(&service, req).await }
+    }))
+    .await
+}
+
+/// Handle a Lambda request with `service`.
+///
+/// It is useful for testing the service with Lambda events, or for running it with a customized runtime.
+pub async fn handle(service: &Service, req: lambda_http::Request) -> Result<lambda_http::Response<Body>, Error> {
+    let req = into_request(req);
+    let handler = service.hyper_handler(
+        SocketAddr::Unknown,
+        req.remote_addr().clone(),
+        req.scheme().clone(),
+        None,
+        None,
+    );
+    let res = handler.handle(req).await.into_hyper();
+    into_lambda_response(res).await
+}
+
+/// Convert a Lambda request to a salvo [`Request`].
+pub fn into_request(req: lambda_http::Request) -> Request {
+    let scheme = req
+        .headers()
+        .get("x-forwarded-proto")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .or_else(|| req.uri().scheme().cloned())
+        .unwrap_or(Scheme::HTTPS);
+    let remote_addr = remote_addr(&req);
+    let req = req.map(|body| match body {
+        Body::Empty => ReqBody::None,
+        Body::Text(text) => ReqBody::from(text),
+        Body::Binary(data) => ReqBody::from(data),
+    });
+    let mut req = Request::from_hyper(req, scheme);
+    *req.remote_addr_mut() = remote_addr;
+    req
+}
+
+fn remote_addr(req: &lambda_http::Request) -> SocketAddr {
+    #[allow(unreachable_patterns)]
+    let source_ip = match req.request_context_ref() {
+        Some(RequestContext::ApiGatewayV1(ctx)) => ctx.identity.source_ip.clone(),
+        Some(RequestContext::ApiGatewayV2(ctx)) => ctx.http.source_ip.clone(),
+        _ => req
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit(',').next())
+            .map(|v| v.trim().to_owned()),
+    };
+    source_ip
+        .and_then(|ip| ip.parse::<IpAddr>().ok())
+        .map(|ip| StdSocketAddr::new(ip, 0).into())
+        .unwrap_or(SocketAddr::Unknown)
+}
+
+/// Convert a salvo response to a Lambda response.
+///
+/// Text bodies are returned as is, other bodies are returned as binary and base64 encoded by the runtime.
+pub async fn into_lambda_response(res: hyper::Response<ResBody>) -> Result<lambda_http::Response<Body>, Error> {
+    let (parts, body) = res.into_parts();
+    let data: Bytes = body.collect().await?.to_bytes();
+    let body = if data.is_empty() {
+        Body::Empty
+    } else if is_text(&parts.headers) {
+        match String::from_utf8(data.to_vec()) {
+            Ok(text) => Body::Text(text),
+            Err(e) => Body::Binary(e.into_bytes()),
+        }
+    } else {
+        Body::Binary(data.to_vec())
+    };
+    Ok(lambda_http::Response::from_parts(parts, body))
+}
+
+fn is_text(headers: &salvo_core::http::HeaderMap) -> bool {
+    if headers.contains_key(CONTENT_ENCODING) {
+        return false;
+    }
+    let Some(ctype) = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let ctype = ctype.split(';').next().unwrap_or_default().trim();
+    ctype.starts_with("text/")
+        || ctype.ends_with("+json")
+        || ctype.ends_with("+xml")
+        || matches!(
+            ctype,
+            "application/json" | "application/javascript" | "application/xml" | "application/x-www-form-urlencoded"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+
+    use super::*;
+
+    #[handler]
+    async fn echo(req: &mut Request, res: &mut Response) {
+        let body = String::from_utf8(req.payload().await.unwrap().to_vec()).unwrap();
+        let tags = req.headers().get_all("x-tag").iter().count();
+        res.render(format!(
+            "{} {} {} {:?} tags={} {}",
+            req.method(),
+            req.uri().path(),
+            req.remote_addr(),
+            req.queries().get_vec("tag"),
+            tags,
+            body
+        ));
+    }
+
+    fn service() -> Service {
+        Service::new(Router::with_path("<**>").goal(echo))
+    }
+
+    async fn call(event: &str) -> lambda_http::Response<Body> {
+        let req = lambda_http::request::from_str(event).unwrap();
+        handle(&service(), req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_apigw_v2() {
+        let res = call(include_str!("../tests/events/apigw_v2.json")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let Body::Text(text) = res.body() else {
+            panic!("body should be text");
+        };
+        assert_eq!(text, "POST /hello socket://203.0.113.7:0 None tags=0 hello");
+    }
+
+    #[tokio::test]
+    async fn test_apigw_v1() {
+        let res = call(include_str!("../tests/events/apigw_v1.json")).await;
+        let Body::Text(text) = res.body() else {
+            panic!("body should be text");
+        };
+        // The stage is kept in the path of REST API events.
+        assert_eq!(
+            text,
+            "GET /prod/hello socket://192.0.2.10:0 Some([\"a\", \"b\"]) tags=2 "
+        );
+    }
+
+    #[tokio::test]
+    async fn test_alb() {
+        let res = call(include_str!("../tests/events/alb.json")).await;
+        let Body::Text(text) = res.body() else {
+            panic!("body should be text");
+        };
+        assert_eq!(text, "GET /hello socket://198.51.100.1:0 Some([\"a\", \"b\"]) tags=2 ");
+    }
+
+    #[tokio::test]
+    async fn test_binary_response() {
+        let res = hyper::Response::builder()
+            .header(CONTENT_TYPE, "image/png")
+            .body(ResBody::Once(Bytes::from_static(&[0x89, 0x50])))
+            .unwrap();
+        let res = into_lambda_response(res).await.unwrap();
+        assert!(matches!(res.body(), Body::Binary(data) if data == &[0x89, 0x50]));
+
+        let res = hyper::Response::builder().body(ResBody::None).unwrap();
+        let res = into_lambda_response(res).await.unwrap();
+        assert!(matches!(res.body(), Body::Empty));
+    }
+}
@@ -4,7 +4,7 @@ use syn::parse::Parse;
 use syn::parse::ParseStream;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{DeriveInput, Error, Expr, ExprLit, Field, Generics, Lit, Meta, MetaNameValue, Token, Type};
+use syn::{DeriveInput, Error, Expr, ExprLit, Field, Generics, Lit, LitBool, Meta, MetaNameValue, Token, Type};
 
 use crate::{
     attribute, omit_type_path_lifetimes, salvo_crate,
@@ -19,6 +19,7 @@ struct FieldInfo {
     rename: Option<String>,
     serde_rename: Option<String>,
     flatten: bool,
+    explode: Option<bool>,
 }
 impl TryFrom<&Field> for FieldInfo {
     type Error = Error;
@@ -30,6 +31,7 @@ impl TryFrom<&Field> for FieldInfo {
         let mut aliases = Vec::with_capacity(field.attrs.len());
         let mut rename = None;
         let mut flatten = None;
+        let mut explode = None;
         for attr in attrs {
             if attr.path().is_ident("salvo") {
                 if let Ok(Some(metas)) = attribute::find_nested_list(&attr, "extract") {
@@ -43,6 +45,9 @@ impl TryFrom<&Field> for FieldInfo {
                     if info.flatten.is_some() {
                         flatten = info.flatten;
                     }
+                    if info.explode.is_some() {
+                        explode = info.explode;
+                    }
                 }
             }
         }
@@ -73,6 +78,7 @@ impl TryFrom<&Field> for FieldInfo {
             rename,
             serde_rename,
             flatten,
+            explode,
         })
     }
 }
@@ -83,6 +89,7 @@ struct ExtractFieldInfo {
     aliases: Vec<String>,
     rename: Option<String>,
     flatten: Option<bool>,
+    explode: Option<bool>,
 }
 impl Parse for ExtractFieldInfo {
     fn parse(input: ParseStream) -> syn::Result<Self> {
@@ -108,6 +115,14 @@ impl Parse for ExtractFieldInfo {
                 "flatten" => {
                     extract.flatten = Some(true);
                 }
+                "explode" => {
+                    let mut explode = true;
+                    if input.peek(Token![=]) {
+                        input.parse::<Token![=]>()?;
+                        explode = input.parse::<LitBool>()?.value();
+                    }
+                    extract.explode = Some(explode);
+                }
                 _ => {
                     return Err(input.error("unexpected attribute"));
                 }
@@ -337,6 +352,11 @@ pub(crate) fn generate(args: DeriveInput) -> Result<TokenStream, Error> {
                 field = field.serde_rename(#serde_rename);
             }
         });
+        let explode = field.explode.map(|explode| {
+            quote! {
+                field = field.explode(#explode);
+            }
+        });
         fields.push(quote! {
             let mut field = #salvo::extract::metadata::Field::new(#field_ident);
             #nested_metadata
@@ -344,6 +364,7 @@ pub(crate) fn generate(args: DeriveInput) -> Result<TokenStream, Error> {
             #(#aliases)*
             #rename
             #serde_rename
+            #explode
             metadata = metadata.add_field(field);
         });
     }
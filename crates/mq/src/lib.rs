@@ -0,0 +1,342 @@
+//! Message queue consumers for Savlo web framework.
+//!
+//! A [`Subscription`] consumes messages from a [`Broker`] and passes them to a [`MessageHandler`]. It runs as a
+//! background task of the server's [`Tasks`], so it is cancelled when the server is gracefully stopped.
+//!
+//! Brokers are adapters for message queues, implemented with the [`Broker`], [`Consumer`] and [`Delivery`]
+//! traits. [`MemoryBroker`] is an in-process broker, and NATS is supported with the `nats` feature. Other message
+//! queues, such as Kafka or AMQP, are not provided yet, they can be supported by implementing these traits.
+//!
+//! Each message is handled with a new [`Depot`], populated with the values given to [`Subscription::inject`], and
+//! inside a tracing span of the subscription. A message is acknowledged if the handler succeeds, and negatively
+//! acknowledged if it fails. Messages in flight when the subscription is cancelled are not acknowledged, so the
+//! broker can redeliver them.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_core::Error;
+//! use salvo_mq::{MemoryBroker, Message, MessageHandler, Subscription};
+//!
+//! struct PrintHandler;
+//! #[async_trait]
+//! impl MessageHandler for PrintHandler {
+//!     async fn handle(&self, msg: &Message, _depot: &mut Depot) -> Result<(), Error> {
+//!         println!("{}: {:?}", msg.topic, msg.payload);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     let server = Server::new(acceptor);
+//!     let broker = MemoryBroker::new();
+//!     Subscription::new("print", broker.clone(), PrintHandler)
+//!         .concurrency(4)
+//!         .spawn(server.tasks());
+//!     server.serve(Router::new()).await;
+//! }
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use salvo_core::tasks::Tasks;
+use salvo_core::{async_trait, Depot, Error};
+use tokio::task::JoinSet;
+use tracing::Instrument;
+
+#[macro_use]
+mod cfg;
+
+mod memory;
+pub use memory::{MemoryBroker, MemoryConsumer, MemoryDelivery};
+
+cfg_feature! {
+    #![feature = "nats"]
+    pub mod nats;
+}
+
+/// A message received from a broker.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Message {
+    /// Topic, subject or queue the message was received from.
+    pub topic: String,
+    /// Key of the message.
+    pub key: Option<Bytes>,
+    /// Headers of the message.
+    pub headers: Vec<(String, String)>,
+    /// Payload of the message.
+    pub payload: Bytes,
+}
+impl Message {
+    /// Create new `Message`.
+    #[inline]
+    pub fn new(topic: impl Into<String>, payload: impl Into<Bytes>) -> Self {
+        Self {
+            topic: topic.into(),
+            payload: payload.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the key of the message.
+    #[inline]
+    pub fn key(mut self, key: impl Into<Bytes>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Add a header to the message.
+    #[inline]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Adapter of a message queue, which creates consumers.
+pub trait Broker: Send + Sync + 'static {
+    /// Consumer type.
+    type Consumer: Consumer;
+
+    /// Connect to the message queue and create a consumer.
+    ///
+    /// It is called again to reconnect when the consumer fails.
+    fn consume(&self) -> impl Future<Output = Result<Self::Consumer, Error>> + Send;
+}
+
+/// A consumer receiving messages from a broker.
+pub trait Consumer: Send + 'static {
+    /// Delivery type.
+    type Delivery: Delivery;
+
+    /// Receive the next message, returns `None` if the consumer is closed.
+    fn recv(&mut self) -> impl Future<Output = Result<Option<Self::Delivery>, Error>> + Send;
+}
+
+/// A delivered message, which should be acknowledged once handled.
+pub trait Delivery: Send + 'static {
+    /// Get the message.
+    fn message(&self) -> &Message;
+
+    /// Acknowledge the message is handled.
+    fn ack(self) -> impl Future<Output = Result<(), Error>> + Send;
+
+    /// Negatively acknowledge the message, so the broker can redeliver it.
+    fn nack(self) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+/// Handler of messages.
+#[async_trait]
+pub trait MessageHandler: Send + Sync + 'static {
+    /// Handle a message, the message is negatively acknowledged if it returns an error.
+    async fn handle(&self, msg: &Message, depot: &mut Depot) -> Result<(), Error>;
+}
+
+type Injector = Arc<dyn Fn(&mut Depot) + Send + Sync>;
+
+/// A subscription which consumes messages from a broker and handles them.
+pub struct Subscription<B, H> {
+    name: String,
+    broker: B,
+    handler: H,
+    concurrency: usize,
+    reconnect_delay: Duration,
+    injectors: Vec<Injector>,
+}
+impl<B, H> Debug for Subscription<B, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("name", &self.name)
+            .field("concurrency", &self.concurrency)
+            .field("reconnect_delay", &self.reconnect_delay)
+            .finish()
+    }
+}
+impl<B, H> Subscription<B, H>
+where
+    B: Broker,
+    H: MessageHandler,
+{
+    /// Create new `Subscription`.
+    #[inline]
+    pub fn new(name: impl Into<String>, broker: B, handler: H) -> Self {
+        Self {
+            name: name.into(),
+            broker,
+            handler,
+            concurrency: 1,
+            reconnect_delay: Duration::from_secs(5),
+            injectors: Vec::new(),
+        }
+    }
+
+    /// Set how many messages are handled concurrently, default is 1.
+    #[inline]
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the delay to reconnect when the broker or the consumer fails, default is 5 seconds.
+    #[inline]
+    pub fn reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// Inject a value into the [`Depot`] of each message.
+    #[inline]
+    pub fn inject<V: Clone + Send + Sync + 'static>(mut self, value: V) -> Self {
+        self.injectors.push(Arc::new(move |depot| {
+            depot.inject(value.clone());
+        }));
+        self
+    }
+
+    /// Spawn the subscription as a background task named after the subscription.
+    pub fn spawn(self, tasks: &Tasks) {
+        let name = format!("mq:{}", self.name);
+        let subscription = Arc::new(self);
+        tasks.spawn(name, move || subscription.clone().run());
+    }
+
+    /// Consume messages until the broker is closed.
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            let consumer = match self.broker.consume().await {
+                Ok(consumer) => consumer,
+                Err(e) => {
+                    tracing::error!(subscription = %self.name, error = ?e, "connect to broker failed");
+                    tokio::time::sleep(self.reconnect_delay).await;
+                    continue;
+                }
+            };
+            tracing::info!(subscription = %self.name, "subscription started");
+            match self.clone().consume(consumer).await {
+                Ok(()) => {
+                    tracing::info!(subscription = %self.name, "subscription closed");
+                    return;
+                }
+                Err(e) => {
+                    tracing::error!(subscription = %self.name, error = ?e, "receive message failed");
+                    tokio::time::sleep(self.reconnect_delay).await;
+                }
+            }
+        }
+    }
+
+    async fn consume(self: Arc<Self>, mut consumer: B::Consumer) -> Result<(), Error> {
+        let mut in_flight = JoinSet::new();
+        let result = loop {
+            while in_flight.len() >= self.concurrency {
+                if let Some(Err(e)) = in_flight.join_next().await {
+                    tracing::error!(subscription = %self.name, error = ?e, "message handler panicked");
+                }
+            }
+            match consumer.recv().await {
+                Ok(Some(delivery)) => {
+                    let span = tracing::info_span!(
+                        "mq.message",
+                        subscription = %self.name,
+                        topic = %delivery.message().topic
+                    );
+                    in_flight.spawn(self.clone().process(delivery).instrument(span));
+                }
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        while let Some(joined) = in_flight.join_next().await {
+            if let Err(e) = joined {
+                tracing::error!(subscription = %self.name, error = ?e, "message handler panicked");
+            }
+        }
+        result
+    }
+
+    async fn process(self: Arc<Self>, delivery: <B::Consumer as Consumer>::Delivery) {
+        let mut depot = Depot::new();
+        for injector in &self.injectors {
+            injector(&mut depot);
+        }
+        match self.handler.handle(delivery.message(), &mut depot).await {
+            Ok(()) => {
+                if let Err(e) = delivery.ack().await {
+                    tracing::error!(error = ?e, "ack message failed");
+                }
+            }
+            Err(e) => {
+                tracing::warn!(error = ?e, "handle message failed");
+                if let Err(e) = delivery.nack().await {
+                    tracing::error!(error = ?e, "nack message failed");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use salvo_core::tasks::TaskState;
+    use tokio::sync::mpsc::{self, UnboundedSender};
+
+    use super::*;
+
+    /// Counts handled messages and notifies the test after each success.
+    #[derive(Clone)]
+    struct Counter(Arc<AtomicUsize>, UnboundedSender<()>);
+
+    struct CountHandler;
+    #[async_trait]
+    impl MessageHandler for CountHandler {
+        async fn handle(&self, msg: &Message, depot: &mut Depot) -> Result<(), Error> {
+            let counter = depot.obtain::<Counter>().unwrap();
+            // Fail the first attempt of `retry`, it is redelivered after nack.
+            if msg.payload == "retry" && counter.0.fetch_add(100, Ordering::SeqCst) < 100 {
+                return Err(Error::other("retry later"));
+            }
+            counter.0.fetch_add(1, Ordering::SeqCst);
+            counter.1.send(()).ok();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription() {
+        let tasks = Tasks::new();
+        let broker = MemoryBroker::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let counter = Counter(Arc::default(), tx);
+        Subscription::new("count", broker.clone(), CountHandler)
+            .concurrency(2)
+            .inject(counter.clone())
+            .spawn(&tasks);
+
+        broker.publish(Message::new("events", "a"));
+        broker.publish(Message::new("events", "retry"));
+        broker.publish(Message::new("events", "b"));
+        for _ in 0..3 {
+            rx.recv().await.unwrap();
+        }
+        assert_eq!(counter.0.load(Ordering::SeqCst), 203);
+        assert_eq!(tasks.status("mq:count").unwrap().state, TaskState::Running);
+
+        tasks.shutdown().await;
+        assert_eq!(tasks.status("mq:count").unwrap().state, TaskState::Stopped);
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use salvo_core::Error;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use super::{Broker, Consumer, Delivery, Message};
+
+/// In-process broker, useful for tests and for decoupling work inside an application.
+///
+/// Messages are delivered to one consumer at a time, negatively acknowledged messages are requeued.
+#[derive(Clone, Debug)]
+pub struct MemoryBroker {
+    tx: UnboundedSender<Message>,
+    rx: Arc<Mutex<UnboundedReceiver<Message>>>,
+}
+impl Default for MemoryBroker {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl MemoryBroker {
+    /// Create new `MemoryBroker`.
+    #[inline]
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx: Arc::new(Mutex::new(rx)),
+        }
+    }
+
+    /// Publish a message.
+    #[inline]
+    pub fn publish(&self, msg: Message) {
+        self.tx.send(msg).ok();
+    }
+}
+impl Broker for MemoryBroker {
+    type Consumer = MemoryConsumer;
+
+    async fn consume(&self) -> Result<Self::Consumer, Error> {
+        Ok(MemoryConsumer {
+            tx: self.tx.clone(),
+            rx: self.rx.clone().lock_owned().await,
+        })
+    }
+}
+
+/// Consumer of [`MemoryBroker`].
+#[derive(Debug)]
+pub struct MemoryConsumer {
+    tx: UnboundedSender<Message>,
+    rx: OwnedMutexGuard<UnboundedReceiver<Message>>,
+}
+impl Consumer for MemoryConsumer {
+    type Delivery = MemoryDelivery;
+
+    async fn recv(&mut self) -> Result<Option<Self::Delivery>, Error> {
+        Ok(self.rx.recv().await.map(|msg| MemoryDelivery {
+            msg,
+            tx: self.tx.clone(),
+        }))
+    }
+}
+
+/// Delivery of [`MemoryBroker`].
+#[derive(Debug)]
+pub struct MemoryDelivery {
+    msg: Message,
+    tx: UnboundedSender<Message>,
+}
+impl Delivery for MemoryDelivery {
+    #[inline]
+    fn message(&self) -> &Message {
+        &self.msg
+    }
+
+    async fn ack(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn nack(self) -> Result<(), Error> {
+        self.tx.send(self.msg).map_err(Error::other)
+    }
+}
@@ -0,0 +1,94 @@
+//! [NATS](https://nats.io) broker built on [`async_nats`].
+//!
+//! Core NATS has no acknowledgement, so acknowledging a delivery does nothing and failed messages are not
+//! redelivered.
+use futures_util::StreamExt;
+use salvo_core::Error;
+
+use super::{Broker, Consumer, Delivery, Message};
+
+/// Broker which subscribes to a NATS subject.
+#[derive(Clone, Debug)]
+pub struct NatsBroker {
+    client: async_nats::Client,
+    subject: String,
+    queue_group: Option<String>,
+}
+impl NatsBroker {
+    /// Create new `NatsBroker` which subscribes to `subject` with `client`.
+    #[inline]
+    pub fn new(client: async_nats::Client, subject: impl Into<String>) -> Self {
+        Self {
+            client,
+            subject: subject.into(),
+            queue_group: None,
+        }
+    }
+
+    /// Join a queue group, so each message is delivered to only one subscriber of the group.
+    #[inline]
+    pub fn queue_group(mut self, group: impl Into<String>) -> Self {
+        self.queue_group = Some(group.into());
+        self
+    }
+}
+impl Broker for NatsBroker {
+    type Consumer = NatsConsumer;
+
+    async fn consume(&self) -> Result<Self::Consumer, Error> {
+        let subscriber = match &self.queue_group {
+            Some(group) => self.client.queue_subscribe(self.subject.clone(), group.clone()).await,
+            None => self.client.subscribe(self.subject.clone()).await,
+        }
+        .map_err(Error::other)?;
+        Ok(NatsConsumer { subscriber })
+    }
+}
+
+/// Consumer of [`NatsBroker`].
+#[derive(Debug)]
+pub struct NatsConsumer {
+    subscriber: async_nats::Subscriber,
+}
+impl Consumer for NatsConsumer {
+    type Delivery = NatsDelivery;
+
+    async fn recv(&mut self) -> Result<Option<Self::Delivery>, Error> {
+        Ok(self.subscriber.next().await.map(|msg| {
+            let headers = msg
+                .headers
+                .iter()
+                .flat_map(|headers| headers.iter())
+                .flat_map(|(name, values)| values.iter().map(move |value| (name.to_string(), value.to_string())))
+                .collect();
+            NatsDelivery {
+                msg: Message {
+                    topic: msg.subject.to_string(),
+                    key: None,
+                    headers,
+                    payload: msg.payload,
+                },
+            }
+        }))
+    }
+}
+
+/// Delivery of [`NatsBroker`].
+#[derive(Debug)]
+pub struct NatsDelivery {
+    msg: Message,
+}
+impl Delivery for NatsDelivery {
+    #[inline]
+    fn message(&self) -> &Message {
+        &self.msg
+    }
+
+    async fn ack(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn nack(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenTree};
 use syn::punctuated::Punctuated;
 use syn::{parenthesized, parse::Parse};
-use syn::{Expr, LitStr};
+use syn::{Expr, ExprPath, LitStr};
 
-use crate::operation::request_body::RequestBodyAttr;
+use crate::operation::{request_body::RequestBodyAttr, Callback};
 use crate::{parse_utils, security_requirement::SecurityRequirementsAttr, Array, Parameter, Response, Token};
 
 #[derive(Default, Debug)]
@@ -15,16 +15,21 @@ pub(crate) struct EndpointAttr<'p> {
     pub(crate) tags: Option<Vec<String>>,
     pub(crate) parameters: Vec<Parameter<'p>>,
     pub(crate) security: Option<Array<'p, SecurityRequirementsAttr>>,
+    pub(crate) callbacks: Vec<Callback>,
+    pub(crate) links: Vec<LinkAttr>,
 
     pub(crate) doc_comments: Option<Vec<String>>,
     pub(crate) deprecated: Option<bool>,
+    pub(crate) hidden: bool,
+    pub(crate) sunset: Option<LitStr>,
+    pub(crate) sunset_link: Option<LitStr>,
     pub(crate) description: Option<parse_utils::Value>,
     pub(crate) summary: Option<parse_utils::Value>,
 }
 
 impl Parse for EndpointAttr<'_> {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        const EXPECTED_ATTRIBUTE_MESSAGE: &str = "unexpected identifier, expected any of: operation_id, path, get, post, put, delete, options, head, patch, trace, connect, request_body, responses, params, tag, security, context_path, description, summary";
+        const EXPECTED_ATTRIBUTE_MESSAGE: &str = "unexpected identifier, expected any of: operation_id, path, get, post, put, delete, options, head, patch, trace, connect, request_body, responses, params, tag, security, callback, context_path, description, summary, deprecated, sunset, sunset_link, hidden, links";
         let mut attr = EndpointAttr::default();
 
         while !input.is_empty() {
@@ -69,6 +74,29 @@ impl Parse for EndpointAttr<'_> {
                     parenthesized!(security in input);
                     attr.security = Some(parse_utils::parse_groups(&security)?)
                 }
+                "callback" => attr.callbacks.push(input.parse::<Callback>()?),
+                "links" => {
+                    let links;
+                    parenthesized!(links in input);
+                    attr.links = Punctuated::<LinkAttr, Token![,]>::parse_terminated(&links)
+                        .map(|punctuated| punctuated.into_iter().collect::<Vec<LinkAttr>>())?;
+                }
+                "deprecated" => {
+                    attr.deprecated = Some(if input.peek(Token![=]) {
+                        parse_utils::parse_next(input, || input.parse::<syn::LitBool>())?.value
+                    } else {
+                        true
+                    });
+                }
+                "hidden" => {
+                    attr.hidden = if input.peek(Token![=]) {
+                        parse_utils::parse_next(input, || input.parse::<syn::LitBool>())?.value
+                    } else {
+                        true
+                    };
+                }
+                "sunset" => attr.sunset = Some(parse_utils::parse_next(input, || input.parse::<LitStr>())?),
+                "sunset_link" => attr.sunset_link = Some(parse_utils::parse_next(input, || input.parse::<LitStr>())?),
                 "description" => attr.description = Some(parse_utils::parse_next_literal_str_or_expr(input)?),
                 "summary" => attr.summary = Some(parse_utils::parse_next_literal_str_or_expr(input)?),
                 _ => {
@@ -84,3 +112,72 @@ impl Parse for EndpointAttr<'_> {
         Ok(attr)
     }
 }
+
+/// Target operation of a link, the endpoint type whose operation id is assigned automatically or an operation id.
+#[derive(Debug)]
+pub(crate) enum LinkTarget {
+    Endpoint(ExprPath),
+    OperationId(LitStr),
+}
+
+impl Parse for LinkTarget {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            Ok(Self::OperationId(input.parse()?))
+        } else {
+            Ok(Self::Endpoint(input.parse()?))
+        }
+    }
+}
+
+/// Link of `#[endpoint(links(...))]`, such as `create_user -> get_user via "$response.body#/id"`.
+///
+/// The source is optional and must be the endpoint itself. The runtime expression is a string literal or the tokens
+/// until the next comma, which can not contain `#` since Rust 2021 reserves prefixes such as `body#`.
+#[derive(Debug)]
+pub(crate) struct LinkAttr {
+    pub(crate) source: Option<ExprPath>,
+    pub(crate) target: LinkTarget,
+    pub(crate) expression: String,
+}
+
+impl Parse for LinkAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut source = None;
+        let mut target = input.parse::<LinkTarget>()?;
+        if input.peek(Token![->]) {
+            input.parse::<Token![->]>()?;
+            match target {
+                LinkTarget::Endpoint(path) => source = Some(path),
+                LinkTarget::OperationId(id) => {
+                    return Err(syn::Error::new(id.span(), "the source of a link must be the endpoint"));
+                }
+            }
+            target = input.parse::<LinkTarget>()?;
+        }
+        let via = input.parse::<Ident>()?;
+        if via != "via" {
+            return Err(syn::Error::new(
+                via.span(),
+                "expected `via` followed by a runtime expression",
+            ));
+        }
+        let expression = if input.peek(LitStr) {
+            input.parse::<LitStr>()?.value()
+        } else {
+            let mut expression = String::new();
+            while !input.is_empty() && !input.peek(Token![,]) {
+                expression.push_str(&input.parse::<TokenTree>()?.to_string());
+            }
+            expression
+        };
+        if expression.is_empty() {
+            return Err(input.error("expected a runtime expression, such as `\"$response.body#/id\"`"));
+        }
+        Ok(Self {
+            source,
+            target,
+            expression,
+        })
+    }
+}
@@ -7,6 +7,7 @@ use crate::{omit_type_path_lifetimes, parse_input_type, Array, DiagResult, Input
 
 mod attr;
 pub(crate) use attr::EndpointAttr;
+use attr::LinkTarget;
 
 fn metadata(
     salvo: &Ident,
@@ -23,6 +24,24 @@ fn metadata(
         &format!("__macro_gen_oapi_endpoint_creator_{}", name),
         Span::call_site(),
     );
+    let hidden = attr.hidden;
+    let mut links = Vec::with_capacity(attr.links.len());
+    for link in &attr.links {
+        if let Some(source) = &link.source {
+            if !source.path.is_ident(name) {
+                return Err(syn::Error::new_spanned(source, "the source of a link must be the endpoint").into());
+            }
+        }
+        let target = match &link.target {
+            LinkTarget::Endpoint(path) => quote! { #oapi::oapi::naming::assign_operation_id::<#path>() },
+            LinkTarget::OperationId(id) => quote! { ::std::string::String::from(#id) },
+        };
+        let expression = &link.expression;
+        links.push(quote! {
+            let target = #target;
+            operation.responses.add_link(target.clone(), #oapi::oapi::Link::to_operation(target, #expression));
+        });
+    }
     let opt = Operation::new(&attr);
     modifiers.append(opt.modifiers()?.as_mut());
     let status_codes = Array::from_iter(attr.status_codes.iter().map(|expr| match expr {
@@ -56,7 +75,7 @@ fn metadata(
             let mut operation = #oapi::oapi::Operation::new();
             #modifiers
             if operation.operation_id.is_none() {
-                operation.operation_id = Some(#oapi::oapi::naming::assign_name::<#name>(#oapi::oapi::naming::NameRule::Auto));
+                operation.operation_id = Some(#oapi::oapi::naming::assign_operation_id::<#name>());
             }
             if !status_codes.is_empty() {
                 let responses = std::ops::DerefMut::deref_mut(&mut operation.responses);
@@ -68,9 +87,11 @@ fn metadata(
                     }
                 });
             }
+            #({ #links })*
             #oapi::oapi::Endpoint{
                 operation,
                 components,
+                hidden: #hidden,
             }
         }
         #oapi::oapi::__private::inventory::submit! {
@@ -110,13 +131,13 @@ pub(crate) fn generate(mut attr: EndpointAttr, input: Item) -> syn::Result<Token
             };
 
             attr.doc_comments = Some(CommentAttributes::from_attributes(attrs).0);
-            attr.deprecated = if attrs.iter().any(|attr| attr.path().is_ident("deprecated")) {
-                Some(true)
-            } else {
-                None
-            };
+            let (deprecation, sunset) = deprecation(&oapi, &mut attr)?;
+            if attrs.iter().any(|attr| attr.path().is_ident("deprecated")) {
+                attr.deprecated = Some(true);
+            }
 
-            let (hfn, modifiers) = handle_fn(&salvo, &oapi, sig)?;
+            let (hfn, mut modifiers) = handle_fn(&salvo, &oapi, sig, deprecation)?;
+            modifiers.extend(sunset);
             let meta = metadata(&salvo, &oapi, attr, name, modifiers)?;
             Ok(quote! {
                 #sdef
@@ -131,11 +152,10 @@ pub(crate) fn generate(mut attr: EndpointAttr, input: Item) -> syn::Result<Token
             let attrs = &item_impl.attrs;
 
             attr.doc_comments = Some(CommentAttributes::from_attributes(attrs).0);
-            attr.deprecated = if attrs.iter().any(|attr| attr.path().is_ident("deprecated")) {
-                Some(true)
-            } else {
-                None
-            };
+            let (deprecation, sunset) = deprecation(&oapi, &mut attr)?;
+            if attrs.iter().any(|attr| attr.path().is_ident("deprecated")) {
+                attr.deprecated = Some(true);
+            }
 
             let mut hmtd = None;
             for item in &item_impl.items {
@@ -148,7 +168,8 @@ pub(crate) fn generate(mut attr: EndpointAttr, input: Item) -> syn::Result<Token
             let Some(hmtd) = hmtd else {
                 return Err(syn::Error::new_spanned(item_impl.impl_token, "missing handle function"));
             };
-            let (hfn, modifiers) = handle_fn(&salvo, &oapi, &hmtd.sig)?;
+            let (hfn, mut modifiers) = handle_fn(&salvo, &oapi, &hmtd.sig, deprecation)?;
+            modifiers.extend(sunset);
             let ty = &item_impl.self_ty;
             let (impl_generics, _, where_clause) = &item_impl.generics.split_for_impl();
             let name = Ident::new(&ty.to_token_stream().to_string(), Span::call_site());
@@ -170,9 +191,82 @@ pub(crate) fn generate(mut attr: EndpointAttr, input: Item) -> syn::Result<Token
     }
 }
 
-fn handle_fn(salvo: &Ident, oapi: &Ident, sig: &Signature) -> syn::Result<(TokenStream, Vec<TokenStream>)> {
+/// Returns the statements writing the deprecation headers to the response, and the modifier documenting
+/// the sunset date. `sunset` implies `deprecated`. Rust's own `#[deprecated]` attribute only marks the operation
+/// as deprecated in the document.
+fn deprecation(oapi: &Ident, attr: &mut EndpointAttr) -> syn::Result<(Option<TokenStream>, Option<TokenStream>)> {
+    if attr.sunset.is_some() {
+        attr.deprecated = Some(true);
+    }
+    if attr.deprecated != Some(true) {
+        if let Some(sunset_link) = &attr.sunset_link {
+            return Err(syn::Error::new(
+                sunset_link.span(),
+                "`sunset_link` requires `deprecated` or `sunset`",
+            ));
+        }
+        return Ok((None, None));
+    }
+    let mut builder = quote! { #oapi::oapi::deprecation::Deprecation::new() };
+    let mut modifier = None;
+    if let Some(sunset) = &attr.sunset {
+        let date = sunset.value();
+        let seconds = parse_date(&date)
+            .ok_or_else(|| syn::Error::new(sunset.span(), "`sunset` must be a date formatted as `YYYY-MM-DD`"))?;
+        builder = quote! {
+            #builder.sunset(::std::time::UNIX_EPOCH + ::std::time::Duration::from_secs(#seconds))
+        };
+        modifier = Some(quote! {
+            operation.extensions.insert("x-sunset", #date);
+        });
+    }
+    if let Some(sunset_link) = &attr.sunset_link {
+        builder = quote! { #builder.link(#sunset_link) };
+    }
+    Ok((
+        Some(quote! {
+            #builder.write_headers(__macro_gen_res);
+        }),
+        modifier,
+    ))
+}
+
+/// Parses a `YYYY-MM-DD` date to the seconds since unix epoch at midnight UTC.
+fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next().filter(|part| part.len() == 4)?.parse::<i64>().ok()?;
+    let month = parts.next().filter(|part| part.len() == 2)?.parse::<i64>().ok()?;
+    let day = parts.next().filter(|part| part.len() == 2)?.parse::<i64>().ok()?;
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    if year < 1970 || day < 1 || day > days_in_month {
+        return None;
+    }
+    // Days from civil algorithm, see <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    u64::try_from(days * 86_400).ok()
+}
+
+fn handle_fn(
+    salvo: &Ident,
+    oapi: &Ident,
+    sig: &Signature,
+    deprecation: Option<TokenStream>,
+) -> syn::Result<(TokenStream, Vec<TokenStream>)> {
     let name = &sig.ident;
-    let mut extract_ts = Vec::with_capacity(sig.inputs.len());
+    let mut extract_ts = Vec::with_capacity(sig.inputs.len() + 1);
+    extract_ts.extend(deprecation);
     let mut call_args: Vec<Ident> = Vec::with_capacity(sig.inputs.len());
     let mut modifiers = Vec::new();
     for input in &sig.inputs {
@@ -274,3 +368,18 @@ fn handle_fn(salvo: &Ident, oapi: &Ident, sig: &Signature) -> syn::Result<(Token
     };
     Ok((hfn, modifiers))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_date;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-03-01"), Some(951_868_800));
+        assert_eq!(parse_date("2026-01-01"), Some(1_767_225_600));
+        assert_eq!(parse_date("2025-02-29"), None);
+        assert_eq!(parse_date("2026-1-01"), None);
+        assert_eq!(parse_date("tomorrow"), None);
+    }
+}
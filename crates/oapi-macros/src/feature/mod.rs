@@ -10,6 +10,8 @@ mod macros;
 pub(crate) use macros::*;
 mod items;
 pub(crate) use items::*;
+mod validate;
+pub(crate) use validate::parse_validate_features;
 
 use crate::schema_type::SchemaType;
 use crate::type_tree::{GenericType, TypeTree};
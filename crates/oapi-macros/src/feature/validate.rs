@@ -0,0 +1,152 @@
+//! Map the rules of `#[validate(...)]` attributes of the `validator` crate to schema features.
+use proc_macro2::{Ident, Span, TokenStream};
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Expr, ExprLit, ExprUnary, Lit, UnOp};
+
+use super::{
+    ExclusiveMaximum, ExclusiveMinimum, Feature, Format, MaxItems, MaxLength, Maximum, MinItems, MinLength, Minimum,
+};
+use crate::schema_type::{SchemaFormat, SchemaType, Variant};
+use crate::type_tree::{GenericType, TypeTree};
+
+/// Returns the schema features described by the `#[validate(...)]` attributes of a field.
+///
+/// Only the rules with literal values are mapped: `length`, `range`, `email` and `url`. Rules which
+/// do not apply to the type of the field, other rules and malformed attributes are ignored, they are
+/// reported by the `Validate` derive itself.
+pub(crate) fn parse_validate_features(attributes: &[Attribute], type_tree: &TypeTree) -> Vec<Feature> {
+    let value_tree = unwrap_option(type_tree);
+    let is_array = matches!(
+        value_tree.generic_type,
+        Some(GenericType::Vec | GenericType::LinkedList | GenericType::Set)
+    );
+    #[cfg(feature = "smallvec")]
+    let is_array = is_array || matches!(value_tree.generic_type, Some(GenericType::SmallVec));
+    let schema_type = value_tree
+        .path
+        .as_ref()
+        .filter(|_| value_tree.generic_type.is_none())
+        .map(|path| SchemaType(path.as_ref()));
+    let is_string = schema_type.as_ref().map(SchemaType::is_string).unwrap_or(false);
+    let is_number = schema_type.as_ref().map(SchemaType::is_number).unwrap_or(false);
+
+    let mut features = Vec::new();
+    for attribute in attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("validate"))
+    {
+        let span = attribute
+            .path()
+            .get_ident()
+            .map(Ident::span)
+            .unwrap_or_else(Span::call_site);
+        let _ = attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("length") && (is_array || is_string) {
+                meta.parse_nested_meta(|meta| {
+                    let value = nested_value(&meta)?.and_then(|value| number(&value));
+                    let Some(value) = value.filter(|value| *value >= 0.0).map(|value| value as usize) else {
+                        return Ok(());
+                    };
+                    let (min, max) = if meta.path.is_ident("min") {
+                        (Some(value), None)
+                    } else if meta.path.is_ident("max") {
+                        (None, Some(value))
+                    } else if meta.path.is_ident("equal") {
+                        (Some(value), Some(value))
+                    } else {
+                        (None, None)
+                    };
+                    if let Some(min) = min {
+                        features.push(if is_array {
+                            Feature::MinItems(MinItems(min, Ident::new("min_items", span)))
+                        } else {
+                            Feature::MinLength(MinLength(min, Ident::new("min_length", span)))
+                        });
+                    }
+                    if let Some(max) = max {
+                        features.push(if is_array {
+                            Feature::MaxItems(MaxItems(max, Ident::new("max_items", span)))
+                        } else {
+                            Feature::MaxLength(MaxLength(max, Ident::new("max_length", span)))
+                        });
+                    }
+                    Ok(())
+                })
+            } else if meta.path.is_ident("range") && is_number {
+                meta.parse_nested_meta(|meta| {
+                    let Some(value) = nested_value(&meta)?.and_then(|value| number(&value)) else {
+                        return Ok(());
+                    };
+                    if meta.path.is_ident("min") {
+                        features.push(Feature::Minimum(Minimum(value, Ident::new("minimum", span))));
+                    } else if meta.path.is_ident("max") {
+                        features.push(Feature::Maximum(Maximum(value, Ident::new("maximum", span))));
+                    } else if meta.path.is_ident("exclusive_min") {
+                        features.push(Feature::ExclusiveMinimum(ExclusiveMinimum(
+                            value,
+                            Ident::new("exclusive_minimum", span),
+                        )));
+                    } else if meta.path.is_ident("exclusive_max") {
+                        features.push(Feature::ExclusiveMaximum(ExclusiveMaximum(
+                            value,
+                            Ident::new("exclusive_maximum", span),
+                        )));
+                    }
+                    Ok(())
+                })
+            } else if (meta.path.is_ident("email") || meta.path.is_ident("url")) && is_string {
+                let format = if meta.path.is_ident("email") { "email" } else { "uri" };
+                skip_rule(&meta)?;
+                features.push(Feature::Format(Format(SchemaFormat::Variant(Variant::Custom(
+                    format.to_owned(),
+                )))));
+                Ok(())
+            } else {
+                skip_rule(&meta)
+            }
+        });
+    }
+    features
+}
+
+fn unwrap_option<'a, 't>(type_tree: &'a TypeTree<'t>) -> &'a TypeTree<'t> {
+    match (
+        &type_tree.generic_type,
+        type_tree.children.as_ref().and_then(|children| children.first()),
+    ) {
+        (Some(GenericType::Option), Some(child)) => unwrap_option(child),
+        _ => type_tree,
+    }
+}
+
+fn nested_value(meta: &ParseNestedMeta) -> syn::Result<Option<Expr>> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse().map(Some)
+    } else {
+        skip_rule(meta).map(|_| None)
+    }
+}
+
+fn skip_rule(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<TokenStream>()?;
+    }
+    Ok(())
+}
+
+fn number(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Lit(ExprLit { lit: Lit::Int(lit), .. }) => lit.base10_parse().ok(),
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(lit), ..
+        }) => lit.base10_parse().ok(),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_), expr, ..
+        }) => number(expr).map(|value| -value),
+        _ => None,
+    }
+}
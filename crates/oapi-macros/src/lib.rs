@@ -105,6 +105,17 @@ pub fn to_responses(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Register the responses of an error enum for every endpoint returning it, [Read more][more].
+///
+/// [more]: ../salvo_oapi/derive.EndpointOutRegister.html
+#[proc_macro_derive(EndpointOutRegister, attributes(salvo))] //attributes(error)
+pub fn derive_endpoint_out_register(input: TokenStream) -> TokenStream {
+    match response::endpoint_out_register(syn::parse_macro_input!(input)) {
+        Ok(stream) => stream.into(),
+        Err(e) => e.emit_as_item_tokens().into(),
+    }
+}
+
 #[doc(hidden)]
 #[proc_macro]
 pub fn schema(input: TokenStream) -> TokenStream {
@@ -204,7 +215,7 @@ mod tests {
                     let status_codes: &[salvo::http::StatusCode] = &[];
                     let mut operation = salvo::oapi::Operation::new();
                     if operation.operation_id.is_none() {
-                        operation.operation_id = Some(salvo::oapi::naming::assign_name::<hello>(salvo::oapi::naming::NameRule::Auto));
+                        operation.operation_id = Some(salvo::oapi::naming::assign_operation_id::<hello>());
                     }
                     if !status_codes.is_empty() {
                         let responses = std::ops::DerefMut::deref_mut(&mut operation.responses);
@@ -219,6 +230,7 @@ mod tests {
                     salvo::oapi::Endpoint {
                         operation,
                         components,
+                        hidden: false,
                     }
                 }
                 salvo::oapi::__private::inventory::submit! {
@@ -0,0 +1,101 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Expr, Token, TypePath};
+
+use crate::parse_utils;
+
+/// Parsed representation of `callback(...)` attribute of `#[endpoint]`.
+///
+/// `callback(name = "onEvent", expression = "{$request.body#/callbackUrl}", method = post, endpoint = on_event)`
+/// documents the request described by the `on_event` endpoint as a callback of the operation.
+#[derive(Debug)]
+pub(crate) struct Callback {
+    name: Expr,
+    expression: Expr,
+    method: Ident,
+    endpoint: TypePath,
+}
+
+impl Parse for Callback {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        const EXPECTED_ATTRIBUTE_MESSAGE: &str =
+            "unexpected attribute, expected any of: name, expression, method, endpoint";
+        let content;
+        parenthesized!(content in input);
+
+        let mut name = None;
+        let mut expression = None;
+        let mut method = None;
+        let mut endpoint = None;
+        while !content.is_empty() {
+            let ident = content
+                .parse::<Ident>()
+                .map_err(|error| syn::Error::new(error.span(), format!("{EXPECTED_ATTRIBUTE_MESSAGE}, {error}")))?;
+            match &*ident.to_string() {
+                "name" => name = Some(parse_utils::parse_next(&content, || content.parse::<Expr>())?),
+                "expression" => expression = Some(parse_utils::parse_next(&content, || content.parse::<Expr>())?),
+                "method" => {
+                    let value = parse_utils::parse_next(&content, || content.parse::<Ident>())?;
+                    method = Some(path_item_type(&value)?);
+                }
+                "endpoint" => endpoint = Some(parse_utils::parse_next(&content, || content.parse::<TypePath>())?),
+                _ => return Err(syn::Error::new(ident.span(), EXPECTED_ATTRIBUTE_MESSAGE)),
+            }
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        let span = input.span();
+        Ok(Self {
+            name: name.ok_or_else(|| syn::Error::new(span, "callback requires `name`"))?,
+            expression: expression.ok_or_else(|| syn::Error::new(span, "callback requires `expression`"))?,
+            method: method.unwrap_or_else(|| Ident::new("Post", span)),
+            endpoint: endpoint.ok_or_else(|| syn::Error::new(span, "callback requires `endpoint`"))?,
+        })
+    }
+}
+
+fn path_item_type(method: &Ident) -> syn::Result<Ident> {
+    let variant = match &*method.to_string().to_lowercase() {
+        "get" => "Get",
+        "post" => "Post",
+        "put" => "Put",
+        "delete" => "Delete",
+        "options" => "Options",
+        "head" => "Head",
+        "patch" => "Patch",
+        "trace" => "Trace",
+        "connect" => "Connect",
+        _ => {
+            return Err(syn::Error::new(
+                method.span(),
+                "unexpected method, expected any of: get, post, put, delete, options, head, patch, trace, connect",
+            ))
+        }
+    };
+    Ok(Ident::new(variant, method.span()))
+}
+
+impl Callback {
+    pub(crate) fn modifier(&self, oapi: &Ident) -> TokenStream {
+        let Self {
+            name,
+            expression,
+            method,
+            endpoint,
+        } = self;
+        quote! {
+            if let Some(creator) = #oapi::oapi::EndpointRegistry::find(&::std::any::TypeId::of::<#endpoint>()) {
+                let #oapi::oapi::Endpoint { operation: callback, components: mut callback_components, .. } = creator();
+                components.append(&mut callback_components);
+                operation
+                    .callbacks
+                    .entry(::std::string::String::from(#name))
+                    .or_default()
+                    .insert(#expression, #oapi::oapi::PathItem::new(#oapi::oapi::PathItemType::#method, callback));
+            }
+        }
+    }
+}
@@ -11,6 +11,8 @@ use crate::security_requirement::SecurityRequirementsAttr;
 use crate::type_tree::{GenericType, TypeTree};
 use crate::{parse_utils, Array, DiagResult, TryToTokens};
 
+pub(crate) mod callback;
+pub(crate) use self::callback::Callback;
 pub(crate) mod example;
 pub(crate) mod request_body;
 pub(crate) use self::request_body::RequestBodyAttr;
@@ -25,6 +27,7 @@ pub(crate) struct Operation<'a> {
     parameters: &'a Vec<Parameter<'a>>,
     request_body: Option<&'a RequestBodyAttr<'a>>,
     responses: &'a Vec<Response<'a>>,
+    callbacks: &'a Vec<Callback>,
     security: Option<&'a Array<'a, SecurityRequirementsAttr>>,
     summary: Option<Summary<'a>>,
     description: Option<Description<'a>>,
@@ -64,6 +67,7 @@ impl<'a> Operation<'a> {
             parameters: attr.parameters.as_ref(),
             request_body: attr.request_body.as_ref(),
             responses: attr.responses.as_ref(),
+            callbacks: attr.callbacks.as_ref(),
             security: attr.security.as_ref(),
             summary,
             description,
@@ -175,6 +179,9 @@ impl<'a> Operation<'a> {
                 }
             }
         }
+        for callback in self.callbacks {
+            modifiers.push(callback.modifier(&oapi));
+        }
         Ok(modifiers)
     }
 }
@@ -355,6 +362,11 @@ impl PathTypeTree for TypeTree<'_> {
                         .any(|path| SchemaType(path).is_byte())
                 })
                 .unwrap_or(false)
+            || self
+                .path
+                .as_ref()
+                .map(|path| SchemaType(path.deref()).is_binary())
+                .unwrap_or(false)
         {
             "application/octet-stream"
         } else if self
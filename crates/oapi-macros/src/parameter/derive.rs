@@ -16,7 +16,7 @@ use crate::feature::{
     Inline, MaxItems, MaxLength, Maximum, Merge, MinItems, MinLength, Minimum, MultipleOf, Nullable, Pattern, ReadOnly,
     Rename, RenameAll, SchemaWith, Style, ToParametersNames, TryToTokensExt, WriteOnly, XmlAttr,
 };
-use crate::parameter::ParameterIn;
+use crate::parameter::{ParameterIn, ParameterStyle};
 use crate::serde_util::{self, RenameRule, SerdeContainer, SerdeValue};
 use crate::type_tree::TypeTree;
 use crate::{attribute, Array, DiagLevel, DiagResult, Diagnostic, FieldRename, IntoInner, Required, TryToTokens};
@@ -459,6 +459,8 @@ impl Parameter<'_> {
                 .as_ref()
                 .map(|rename| quote!(.serde_rename(#rename)))
         });
+        let explode =
+            pop_feature_as_inner!(param_features => Feature::Explode(_v)).map(|explode| quote!(.explode(#explode)));
         if let Some(parameter_in) = param_features.pop_parameter_in_feature() {
             let source = match parameter_in {
                 feature::ParameterIn(crate::parameter::ParameterIn::Query) => {
@@ -479,12 +481,14 @@ impl Parameter<'_> {
                     .add_source(#source)
                     #rename
                     #serde_rename
+                    #explode
             })
         } else {
             Ok(quote! {
                 #salvo::extract::metadata::Field::new(#name)
                 #rename
                 #serde_rename
+                #explode
             })
         }
     }
@@ -540,10 +544,23 @@ impl TryToTokens for Parameter<'_> {
             tokens.extend(parameter_in.try_to_token_stream()?);
         }
 
-        if let Some(style) = param_features.pop_style_feature() {
+        let default_style = match &self.container_attributes.default_style {
+            Some(Feature::DefaultStyle(style)) => Some(style.0),
+            _ => None,
+        };
+        let style = param_features
+            .pop_style_feature()
+            .map(|style| style.0)
+            .or(default_style);
+        if let Some(style) = style {
             tokens.extend(quote! { .style(#style) });
-        } else if let Some(style) = &self.container_attributes.default_style {
-            tokens.extend(style.try_to_token_stream());
+            // `deepObject` is only defined for exploded objects, but `explode` defaults to `false` for it.
+            let has_explode = param_features
+                .iter()
+                .any(|feature| matches!(feature, Feature::Explode(_)));
+            if matches!(style, ParameterStyle::DeepObject) && !has_explode {
+                tokens.extend(quote! { .explode(true) });
+            }
         }
 
         if let Some(deprecated) = crate::get_deprecated(&field.attrs) {
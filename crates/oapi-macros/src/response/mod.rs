@@ -16,6 +16,8 @@ use crate::{attribute, parse_utils, AnyValue, Array, DiagResult, Diagnostic, Try
 mod derive;
 use derive::{ToResponse, ToResponses};
 mod parse;
+mod register;
+use register::EndpointOutRegister;
 
 pub(crate) fn to_response(input: DeriveInput) -> DiagResult<TokenStream> {
     let DeriveInput {
@@ -45,6 +47,23 @@ pub(crate) fn to_responses(input: DeriveInput) -> DiagResult<TokenStream> {
     .try_to_token_stream()
 }
 
+pub(crate) fn endpoint_out_register(input: DeriveInput) -> DiagResult<TokenStream> {
+    let DeriveInput {
+        attrs,
+        ident,
+        data,
+        generics,
+        ..
+    } = input;
+    EndpointOutRegister {
+        attributes: &attrs,
+        ident: &ident,
+        generics: &generics,
+        data: &data,
+    }
+    .try_to_token_stream()
+}
+
 #[derive(Debug)]
 pub(crate) enum Response<'r> {
     /// A type that implements `salvo_oapi::ToResponses`.
@@ -0,0 +1,140 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{Attribute, Data, Error, Generics, LitStr, Token, Type};
+
+use crate::doc_comment::CommentAttributes;
+use crate::{attribute, parse_utils, DiagLevel, DiagResult, Diagnostic, TryToTokens};
+
+use super::ResponseStatusCode;
+
+/// `#[salvo(error(...))]` attributes of an error type or of its variants.
+#[derive(Default)]
+struct ErrorAttr {
+    status_code: Option<ResponseStatusCode>,
+    description: Option<parse_utils::Value>,
+    body: Option<Type>,
+    content_type: Option<LitStr>,
+}
+
+impl ErrorAttr {
+    fn from_attributes(attrs: &[Attribute]) -> DiagResult<Self> {
+        let mut merged = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("salvo")) {
+            if let Some(list) = attribute::find_nested_list(attr, "error")? {
+                let attr = list.parse_args::<Self>()?;
+                merged.status_code = attr.status_code.or(merged.status_code);
+                merged.description = attr.description.or(merged.description);
+                merged.body = attr.body.or(merged.body);
+                merged.content_type = attr.content_type.or(merged.content_type);
+            }
+        }
+        Ok(merged)
+    }
+}
+
+impl Parse for ErrorAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        const EXPECTED_ATTRIBUTE_MESSAGE: &str =
+            "unexpected attribute, expected any of: status_code, description, body, content_type";
+        let mut attr = ErrorAttr::default();
+        while !input.is_empty() {
+            let ident = input
+                .parse::<Ident>()
+                .map_err(|error| Error::new(error.span(), format!("{EXPECTED_ATTRIBUTE_MESSAGE}, {error}")))?;
+            match &*ident.to_string() {
+                "status_code" => {
+                    attr.status_code = Some(parse_utils::parse_next(input, || input.parse())?);
+                }
+                "description" => {
+                    attr.description = Some(parse_utils::parse_next_literal_str_or_expr(input)?);
+                }
+                "body" => {
+                    attr.body = Some(parse_utils::parse_next(input, || input.parse())?);
+                }
+                "content_type" => {
+                    attr.content_type = Some(parse_utils::parse_next(input, || input.parse())?);
+                }
+                _ => return Err(Error::new(ident.span(), EXPECTED_ATTRIBUTE_MESSAGE)),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(attr)
+    }
+}
+
+pub(crate) struct EndpointOutRegister<'a> {
+    pub(crate) attributes: &'a [Attribute],
+    pub(crate) ident: &'a Ident,
+    pub(crate) generics: &'a Generics,
+    pub(crate) data: &'a Data,
+}
+
+impl TryToTokens for EndpointOutRegister<'_> {
+    fn try_to_tokens(&self, tokens: &mut TokenStream) -> DiagResult<()> {
+        let oapi = crate::oapi_crate();
+        let Data::Enum(enum_value) = self.data else {
+            return Err(Diagnostic::spanned(
+                self.ident.span(),
+                DiagLevel::Error,
+                "`EndpointOutRegister` can only be derived for `enum` types",
+            ));
+        };
+        let defaults = ErrorAttr::from_attributes(self.attributes)?;
+        let responses = enum_value
+            .variants
+            .iter()
+            .map(|variant| {
+                let attr = ErrorAttr::from_attributes(&variant.attrs)?;
+                let status_code = attr
+                    .status_code
+                    .as_ref()
+                    .or(defaults.status_code.as_ref())
+                    .map(ToTokens::to_token_stream)
+                    .unwrap_or_else(|| quote!("500"));
+                let description = match attr.description {
+                    Some(description) => description.to_token_stream(),
+                    None => {
+                        let comment = CommentAttributes::from_attributes(&variant.attrs).as_formatted_string();
+                        if comment.is_empty() {
+                            variant.ident.to_string().to_token_stream()
+                        } else {
+                            comment.to_token_stream()
+                        }
+                    }
+                };
+                let response = match attr.body.as_ref().or(defaults.body.as_ref()) {
+                    Some(body) => {
+                        let content_type = attr
+                            .content_type
+                            .as_ref()
+                            .or(defaults.content_type.as_ref())
+                            .map(|content_type| content_type.value())
+                            .unwrap_or_else(|| "application/json".to_owned());
+                        quote! {
+                            #oapi::oapi::Response::new(#description)
+                                .add_content(#content_type, <#body as #oapi::oapi::ToSchema>::to_schema(components))
+                        }
+                    }
+                    None => quote!(#oapi::oapi::Response::new(#description)),
+                };
+                Ok(quote! {
+                    operation.responses.merge(#status_code, #response);
+                })
+            })
+            .collect::<DiagResult<Vec<_>>>()?;
+
+        let ident = self.ident;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        tokens.extend(quote! {
+            impl #impl_generics #oapi::oapi::EndpointOutRegister for #ident #ty_generics #where_clause {
+                fn register(components: &mut #oapi::oapi::Components, operation: &mut #oapi::oapi::Operation) {
+                    #(#responses)*
+                }
+            }
+        });
+        Ok(())
+    }
+}
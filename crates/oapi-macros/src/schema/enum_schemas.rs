@@ -775,7 +775,7 @@ impl ComplexEnum<'_> {
                     } else {
                         Ok(Some(quote! {
                             #unnamed_enum
-                                #name
+                                #title
                                 .schema_type(#oapi::oapi::schema::SchemaType::Object)
                                 .property(#tag, #variant_name_tokens)
                                 .required(#tag)
@@ -889,63 +889,55 @@ impl ComplexEnum<'_> {
                 }))
             }
             Fields::Unnamed(unnamed_fields) => {
-                if unnamed_fields.unnamed.len() == 1 {
-                    let (title_features, mut unnamed_struct_features) = variant
-                        .attrs
-                        .parse_features::<EnumUnnamedFieldVariantFeatures>()?
-                        .into_inner()
-                        .map(|features| features.split_for_title())
-                        .unwrap_or_default();
+                // Tuple variants with multiple fields are serialized to array as content.
+                let (title_features, mut unnamed_struct_features) = variant
+                    .attrs
+                    .parse_features::<EnumUnnamedFieldVariantFeatures>()?
+                    .into_inner()
+                    .map(|features| features.split_for_title())
+                    .unwrap_or_default();
 
-                    if unnamed_struct_features.is_skipped() {
-                        return Ok(None);
-                    }
+                if unnamed_struct_features.is_skipped() {
+                    return Ok(None);
+                }
 
-                    let variant_name = rename_enum_variant(
-                        name.as_ref(),
-                        &mut unnamed_struct_features,
-                        variant_rules,
-                        container_rules,
-                        rename_all,
-                    );
+                let variant_name = rename_enum_variant(
+                    name.as_ref(),
+                    &mut unnamed_struct_features,
+                    variant_rules,
+                    container_rules,
+                    rename_all,
+                );
 
-                    let unnamed_enum = UnnamedStructSchema {
-                        struct_name: Cow::Borrowed(&*self.enum_name),
-                        description: None,
-                        attributes: &variant.attrs,
-                        features: Some(unnamed_struct_features),
-                        fields: &unnamed_fields.unnamed,
-                        name: None,
-                        aliases: None,
-                        inline: None,
-                    }
-                    .try_to_token_stream()?;
+                let unnamed_enum = UnnamedStructSchema {
+                    struct_name: Cow::Borrowed(&*self.enum_name),
+                    description: None,
+                    attributes: &variant.attrs,
+                    features: Some(unnamed_struct_features),
+                    fields: &unnamed_fields.unnamed,
+                    name: None,
+                    aliases: None,
+                    inline: None,
+                }
+                .try_to_token_stream()?;
 
-                    let title = title_features
-                        .first()
-                        .map(TryToTokens::try_to_token_stream)
-                        .transpose()?
-                        .map(|title| quote! { .title(#title)});
-                    let variant_name_tokens = Enum::new([SimpleEnumVariant {
-                        value: variant_name.unwrap_or(Cow::Borrowed(&name)).to_token_stream(),
-                    }]);
+                let title = title_features
+                    .first()
+                    .map(TryToTokens::try_to_token_stream)
+                    .transpose()?;
+                let variant_name_tokens = Enum::new([SimpleEnumVariant {
+                    value: variant_name.unwrap_or(Cow::Borrowed(&name)).to_token_stream(),
+                }]);
 
-                    Ok(Some(quote! {
-                        #oapi::oapi::schema::Object::new()
-                            #title
-                            .schema_type(#oapi::oapi::schema::SchemaType::Object)
-                            .property(#tag, #variant_name_tokens)
-                            .required(#tag)
-                            .property(#content, #unnamed_enum)
-                            .required(#content)
-                    }))
-                } else {
-                    Err(Diagnostic::spanned(
-                        variant.span(),
-                        DiagLevel::Error,
-                        "Unnamed (tuple) enum variants are unsupported for adjacently tagged enums using the `tag = <tag>, content = <content>` serde attribute"
-                    ).help("Try using a different serde enum representation").note("See more about enum limitations here: `https://serde.rs/enum-representations.html#adjacently-tagged`"))
-                }
+                Ok(Some(quote! {
+                    #oapi::oapi::schema::Object::new()
+                        #title
+                        .schema_type(#oapi::oapi::schema::SchemaType::Object)
+                        .property(#tag, #variant_name_tokens)
+                        .required(#tag)
+                        .property(#content, #unnamed_enum)
+                        .required(#content)
+                }))
             }
             Fields::Unit => {
                 // In this case `content` is simply ignored - there is nothing to put in it.
@@ -995,11 +987,20 @@ impl TryToTokens for ComplexEnum<'_> {
             .as_ref()
             .map(|rules| rules.enum_repr.clone())
             .unwrap_or_default();
+        // Variants marked with `#[serde(untagged)]` do not carry the tag, so the tag can not
+        // be used as discriminator when there are any.
+        let has_untagged_variant = self.variants.iter().any(|variant| {
+            serde_util::parse_value(&variant.attrs)
+                .map(|rules| rules.untagged && !rules.skip)
+                .unwrap_or(false)
+        });
         let tag = match &enum_repr {
-            SerdeEnumRepr::AdjacentlyTagged { tag, .. } | SerdeEnumRepr::InternallyTagged { tag } => Some(tag),
-            SerdeEnumRepr::ExternallyTagged
-            | SerdeEnumRepr::Untagged
-            | SerdeEnumRepr::UnfinishedAdjacentlyTagged { .. } => None,
+            SerdeEnumRepr::AdjacentlyTagged { tag, .. } | SerdeEnumRepr::InternallyTagged { tag }
+                if !has_untagged_variant =>
+            {
+                Some(tag)
+            }
+            _ => None,
         };
         let ts = self
             .variants
@@ -1014,6 +1015,13 @@ impl TryToTokens for ComplexEnum<'_> {
             })
             .map(|(variant, variant_serde_rules)| {
                 let variant_name = &*variant.ident.to_string();
+                if variant_serde_rules
+                    .as_ref()
+                    .map(|rules| rules.untagged)
+                    .unwrap_or(false)
+                {
+                    return self.untagged_variant_tokens(variant);
+                }
 
                 match &enum_repr {
                     SerdeEnumRepr::ExternallyTagged => self.variant_tokens(
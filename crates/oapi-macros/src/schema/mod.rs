@@ -169,10 +169,41 @@ impl TryToTokens for ToSchema<'_> {
                 }
             }
         });
+        if is_extractible(self.data, self.attributes) {
+            tokens.extend(quote! {
+                impl #impl_generics #oapi::oapi::EndpointArgRegister for #ident #ty_generics #where_clause {
+                    fn register(components: &mut #oapi::oapi::Components, operation: &mut #oapi::oapi::Operation, _arg: &str) {
+                        #oapi::oapi::extract::register_extractible::<Self>(components, operation);
+                    }
+                }
+            });
+        }
         Ok(())
     }
 }
 
+/// Returns `true` if the struct is configured for the `Extractible` derive by `#[salvo(extract(...))]`.
+fn is_extractible(data: &Data, attributes: &[Attribute]) -> bool {
+    let has_extract = |attributes: &[Attribute]| {
+        attributes.iter().any(|attribute| {
+            attribute.path().is_ident("salvo")
+                && crate::attribute::find_nested_list(attribute, "extract")
+                    .ok()
+                    .flatten()
+                    .is_some()
+        })
+    };
+    match data {
+        Data::Struct(content) => match &content.fields {
+            Fields::Named(fields) => {
+                has_extract(attributes) || fields.named.iter().any(|field| has_extract(&field.attrs))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 #[derive(Debug)]
 enum SchemaVariant<'a> {
     Named(NamedStructSchema<'a>),
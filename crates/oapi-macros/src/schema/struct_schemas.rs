@@ -8,8 +8,8 @@ use syn::{punctuated::Punctuated, spanned::Spanned, Attribute, Field, Generics,
 use crate::component::{ComponentDescription, ComponentSchemaProps};
 use crate::doc_comment::CommentAttributes;
 use crate::feature::{
-    pop_feature, pop_feature_as_inner, Alias, Bound, Feature, FeaturesExt, IsSkipped, Name, RenameAll, SkipBound,
-    TryToTokensExt,
+    parse_validate_features, pop_feature, pop_feature_as_inner, Alias, Bound, Feature, FeaturesExt, IsSkipped, Name,
+    RenameAll, SkipBound, TryToTokensExt,
 };
 use crate::schema::{Description, Inline};
 use crate::type_tree::TypeTree;
@@ -60,6 +60,18 @@ impl NamedStructSchema<'_> {
         let type_tree = &mut TypeTree::from_type(&field.ty)?;
 
         let mut field_features = field.attrs.parse_features::<NamedFieldFeatures>()?.into_inner();
+        let validate_features = parse_validate_features(&field.attrs, type_tree);
+        if !validate_features.is_empty() {
+            let features_inner = field_features.get_or_insert(vec![]);
+            for feature in validate_features {
+                if !features_inner
+                    .iter()
+                    .any(|f| std::mem::discriminant(f) == std::mem::discriminant(&feature))
+                {
+                    features_inner.push(feature);
+                }
+            }
+        }
 
         let schema_default = self
             .features
@@ -342,6 +354,7 @@ impl TryToTokens for UnnamedStructSchema<'_> {
                 .all(|schema_part| first_part == schema_part);
 
         let deprecated = crate::get_deprecated(self.attributes);
+        let mut schema = TokenStream::new();
         if all_fields_are_same {
             let mut unnamed_struct_features = self.features.clone();
             let value_type = unnamed_struct_features
@@ -371,7 +384,7 @@ impl TryToTokens for UnnamedStructSchema<'_> {
                 .map(ComponentDescription::Description)
                 .or(Some(ComponentDescription::CommentAttributes(&comments)));
 
-            tokens.extend(
+            schema.extend(
                 ComponentSchema::new(ComponentSchemaProps {
                     type_tree: override_type_tree.as_ref().unwrap_or(first_part),
                     features: unnamed_struct_features,
@@ -386,12 +399,12 @@ impl TryToTokens for UnnamedStructSchema<'_> {
             // See: https://serde.rs/json.html
             // Typically OpenAPI does not support multi type arrays thus we simply consider the case
             // as generic object array
-            tokens.extend(quote! {
+            schema.extend(quote! {
                 #oapi::oapi::Object::new()
             });
 
             if let Some(deprecated) = deprecated {
-                tokens.extend(quote! { .deprecated(#deprecated) });
+                schema.extend(quote! { .deprecated(#deprecated) });
             }
 
             if let Some(ref attrs) = self.features {
@@ -399,7 +412,7 @@ impl TryToTokens for UnnamedStructSchema<'_> {
                     .iter()
                     .map(TryToTokens::try_to_token_stream)
                     .collect::<DiagResult<TokenStream>>()?;
-                tokens.extend(attrs)
+                schema.extend(attrs)
             }
         };
 
@@ -411,11 +424,13 @@ impl TryToTokens for UnnamedStructSchema<'_> {
                 .map(ComponentDescription::Description)
                 .or(Some(ComponentDescription::CommentAttributes(&comments)));
             tokens.extend(quote! {
-            .to_array_builder()
-                .max_items(Some(#fields_len))
-                .min_items(Some(#fields_len))
-                #description
+                #oapi::oapi::Array::new(#schema)
+                    .max_items(#fields_len)
+                    .min_items(#fields_len)
+                    #description
             })
+        } else {
+            tokens.extend(schema);
         }
         Ok(())
     }
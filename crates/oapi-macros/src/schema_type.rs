@@ -110,6 +110,14 @@ impl SchemaType<'_> {
     pub(crate) fn is_byte(&self) -> bool {
         matches!(&*self.last_segment_to_string(), "u8")
     }
+
+    /// Check whether the type holds raw binary data, like file parts and streamed bodies.
+    pub(crate) fn is_binary(&self) -> bool {
+        matches!(
+            &*self.last_segment_to_string(),
+            "Bytes" | "BytesMut" | "FilePart" | "FormFile" | "NamedFile" | "ResBody"
+        )
+    }
 }
 
 #[inline]
@@ -374,6 +382,8 @@ pub(crate) enum Variant {
     Binary,
     Date,
     DateTime,
+    Time,
+    Duration,
     Password,
     #[cfg(feature = "url")]
     Url,
@@ -386,9 +396,9 @@ pub(crate) enum Variant {
 
 impl Parse for Variant {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        const FORMATS: [&str; 12] = [
-            "Int32", "Int64", "Float", "Double", "Byte", "Binary", "Date", "DateTime", "Password", "Ulid", "Uuid",
-            "Url",
+        const FORMATS: [&str; 14] = [
+            "Int32", "Int64", "Float", "Double", "Byte", "Binary", "Date", "DateTime", "Time", "Duration", "Password",
+            "Ulid", "Uuid", "Url",
         ];
         let excluded_format: &[&str] = &[
             #[cfg(not(feature = "url"))]
@@ -417,6 +427,8 @@ impl Parse for Variant {
                 "Binary" => Ok(Self::Binary),
                 "Date" => Ok(Self::Date),
                 "DateTime" => Ok(Self::DateTime),
+                "Time" => Ok(Self::Time),
+                "Duration" => Ok(Self::Duration),
                 "Password" => Ok(Self::Password),
                 #[cfg(feature = "url")]
                 "Url" => Ok(Self::Url),
@@ -469,6 +481,12 @@ impl ToTokens for Variant {
             Self::DateTime => stream.extend(quote!(#oapi::oapi::SchemaFormat::KnownFormat(
                 #oapi::oapi::KnownFormat::DateTime
             ))),
+            Self::Time => stream.extend(quote!(#oapi::oapi::SchemaFormat::KnownFormat(
+                #oapi::oapi::KnownFormat::Time
+            ))),
+            Self::Duration => stream.extend(quote!(#oapi::oapi::SchemaFormat::KnownFormat(
+                #oapi::oapi::KnownFormat::Duration
+            ))),
             Self::Password => stream.extend(quote!(#oapi::oapi::SchemaFormat::KnownFormat(
                 #oapi::oapi::KnownFormat::Password
             ))),
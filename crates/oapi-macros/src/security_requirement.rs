@@ -29,14 +29,19 @@ impl Parse for SecurityRequirementsAttr {
 impl Parse for SecurityRequirementsAttrItem {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let name = input.parse::<LitStr>()?.value();
-        input.parse::<Token![=]>()?;
+        // Scopes can be omitted for schemes that do not use them, e.g. `("api_key")`.
+        let scopes = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
 
-        let scopes_stream;
-        bracketed!(scopes_stream in input);
-        let scopes = Punctuated::<LitStr, Token![,]>::parse_terminated(&scopes_stream)?
-            .iter()
-            .map(LitStr::value)
-            .collect::<Vec<_>>();
+            let scopes_stream;
+            bracketed!(scopes_stream in input);
+            Punctuated::<LitStr, Token![,]>::parse_terminated(&scopes_stream)?
+                .iter()
+                .map(LitStr::value)
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
 
         Ok(Self {
             name: Some(name),
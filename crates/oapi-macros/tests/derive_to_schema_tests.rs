@@ -249,3 +249,130 @@ fn test_derive_to_schema_enum() {
         })
     );
 }
+
+#[test]
+fn test_derive_to_schema_tagged_enum() {
+    #[derive(Serialize, ToSchema)]
+    #[serde(tag = "kind")]
+    #[allow(dead_code)]
+    enum Shape {
+        Circle {
+            radius: f64,
+        },
+        #[serde(rename = "square")]
+        Square {
+            side: f64,
+        },
+    }
+
+    #[derive(Serialize, ToSchema)]
+    #[serde(tag = "t", content = "c")]
+    #[allow(dead_code)]
+    enum Message {
+        Text(String),
+        Pair(i32, i32),
+        Ping,
+    }
+
+    #[derive(Serialize, ToSchema)]
+    #[serde(tag = "kind")]
+    #[allow(dead_code)]
+    enum Event {
+        Click {
+            x: i32,
+        },
+        #[serde(untagged)]
+        Raw(String),
+    }
+
+    fn schema_of<T: ToSchema>() -> serde_json::Value {
+        let mut components = salvo::oapi::Components::new();
+        T::to_schema(&mut components);
+        serde_json::to_value(components.schemas.into_iter().next().unwrap().1).unwrap()
+    }
+
+    let shape = schema_of::<Shape>();
+    assert_eq!(shape["discriminator"], json!({"propertyName": "kind"}));
+    assert_eq!(shape["oneOf"][0]["properties"]["kind"]["enum"], json!(["Circle"]));
+    assert_eq!(shape["oneOf"][1]["properties"]["kind"]["enum"], json!(["square"]));
+    assert_eq!(shape["oneOf"][1]["required"], json!(["side", "kind"]));
+
+    let message = schema_of::<Message>();
+    assert_eq!(message["discriminator"], json!({"propertyName": "t"}));
+    assert_eq!(message["oneOf"][0]["properties"]["t"]["enum"], json!(["Text"]));
+    assert_eq!(message["oneOf"][0]["properties"]["c"]["type"], json!("string"));
+    assert_eq!(message["oneOf"][1]["properties"]["c"]["type"], json!("array"));
+    assert_eq!(message["oneOf"][1]["properties"]["c"]["maxItems"], json!(2));
+    assert_eq!(message["oneOf"].as_array().unwrap().len(), 3);
+
+    let event = schema_of::<Event>();
+    assert!(event.get("discriminator").is_none());
+    assert_eq!(event["oneOf"][0]["properties"]["kind"]["enum"], json!(["Click"]));
+    assert_eq!(event["oneOf"][1]["type"], json!("string"));
+}
+
+#[test]
+fn test_derive_to_schema_content_example() {
+    /// A pet in the store.
+    #[derive(Deserialize, Serialize, ToSchema, Debug)]
+    #[salvo(schema(name = ExamplePet))]
+    struct Pet {
+        /// Id of the pet.
+        #[salvo(schema(example = 1))]
+        id: u64,
+        /// Name of the pet.
+        #[salvo(schema(example = "bob"))]
+        name: String,
+        tag: Option<String>,
+    }
+
+    #[endpoint]
+    async fn create_pet(pet: JsonBody<Pet>) -> Json<Pet> {
+        Json(pet.into_inner())
+    }
+
+    let router = Router::with_path("pets").post(create_pet);
+    let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+    let value = serde_json::to_value(&doc).unwrap();
+
+    let schema = value.pointer("/components/schemas/ExamplePet").unwrap();
+    assert_eq!(schema["description"], json!("A pet in the store."));
+    assert_eq!(schema["properties"]["name"]["description"], json!("Name of the pet."));
+
+    let operation = value.pointer("/paths/~1pets/post").unwrap();
+    let example = json!({"id": 1, "name": "bob"});
+    assert_eq!(
+        operation["requestBody"]["content"]["application/json"]["example"],
+        example
+    );
+    assert_eq!(
+        operation["responses"]["200"]["content"]["application/json"]["example"],
+        example
+    );
+}
+
+#[test]
+fn test_derive_to_schema_validate_attributes() {
+    #[derive(Deserialize, ToSchema, validator::Validate)]
+    struct User {
+        #[validate(length(min = 1, max = 64))]
+        name: String,
+        #[validate(email)]
+        email: Option<String>,
+        #[validate(range(min = 0, max = 150))]
+        #[salvo(schema(maximum = 130))]
+        age: u8,
+        #[validate(length(max = 8))]
+        tags: Vec<String>,
+    }
+
+    let mut components = salvo::oapi::Components::new();
+    User::to_schema(&mut components);
+    let schema = serde_json::to_value(components.schemas.into_iter().next().unwrap().1).unwrap();
+    assert_eq!(schema["properties"]["name"]["minLength"], json!(1));
+    assert_eq!(schema["properties"]["name"]["maxLength"], json!(64));
+    assert_eq!(schema["properties"]["email"]["format"], json!("email"));
+    assert_eq!(schema["properties"]["age"]["minimum"], json!(0.0));
+    assert_eq!(schema["properties"]["age"]["maximum"], json!(130.0));
+    assert_eq!(schema["properties"]["tags"]["maxItems"], json!(8));
+}
@@ -1,6 +1,9 @@
 use assert_json_diff::assert_json_eq;
 use salvo::oapi::extract::*;
+use salvo::oapi::security::SecurityRequirement;
+use salvo::oapi::{ParameterIn, PathItemType, Required};
 use salvo::prelude::*;
+use serde::Deserialize;
 use serde_json::json;
 
 #[test]
@@ -43,3 +46,174 @@ fn test_endpoint_hello() {
         })
     );
 }
+
+#[test]
+fn test_endpoint_callback() {
+    #[derive(Deserialize, ToSchema)]
+    struct PaymentEvent {
+        id: u64,
+    }
+
+    /// Payment is paid.
+    #[endpoint]
+    async fn payment_paid(event: JsonBody<PaymentEvent>) -> StatusCode {
+        if event.id > 0 {
+            StatusCode::OK
+        } else {
+            StatusCode::BAD_REQUEST
+        }
+    }
+
+    #[endpoint(callback(
+        name = "onPaid",
+        expression = "{$request.body#/callbackUrl}",
+        method = post,
+        endpoint = payment_paid
+    ))]
+    async fn create_payment() -> &'static str {
+        "created"
+    }
+
+    let router = Router::with_path("payments").post(create_payment);
+    let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+    let callback = &doc.paths["/payments"].operations[&PathItemType::Post].callbacks["onPaid"];
+    let operation = &callback["{$request.body#/callbackUrl}"].operations[&PathItemType::Post];
+    assert_eq!(operation.summary.as_deref(), Some("Payment is paid."));
+    assert!(operation.request_body.is_some());
+    assert!(!doc.components.schemas.is_empty());
+}
+
+#[test]
+fn test_endpoint_security() {
+    #[endpoint(security(("oauth2" = ["read:items"]), ("api_key", "session"), ()))]
+    async fn list_items() -> &'static str {
+        "items"
+    }
+
+    let router = Router::with_path("items")
+        .oapi_security(SecurityRequirement::new("oauth2", ["read:items"]))
+        .oapi_security(SecurityRequirement::new("mtls", [] as [&str; 0]))
+        .get(list_items);
+    let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+    assert_json_eq!(
+        doc.paths["/items"].operations[&PathItemType::Get].securities,
+        json!([
+            {"oauth2": ["read:items"]},
+            {"api_key": [], "session": []},
+            {},
+            {"mtls": []}
+        ])
+    );
+}
+
+#[test]
+fn test_endpoint_extractible_parameters() {
+    #[derive(Deserialize, Extractible, ToSchema, Debug)]
+    #[salvo(extract(default_source(from = "body")))]
+    #[allow(dead_code)]
+    struct UpdatePet {
+        /// Id of the pet.
+        #[salvo(extract(source(from = "param")))]
+        id: u64,
+        /// Whether to notify the owner.
+        #[salvo(extract(source(from = "query")))]
+        notify: Option<bool>,
+        #[salvo(extract(source(from = "header"), rename = "x-request-id"))]
+        request_id: String,
+        name: String,
+    }
+
+    #[endpoint]
+    async fn update_pet(pet: UpdatePet) -> String {
+        pet.name
+    }
+
+    let router = Router::with_path("pets/<id>").patch(update_pet);
+    let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+    let operation = &doc.paths["/pets/{id}"].operations[&PathItemType::Patch];
+    let parameters = operation
+        .parameters
+        .0
+        .iter()
+        .map(|parameter| {
+            (
+                parameter.name.as_str(),
+                parameter.parameter_in,
+                parameter.required == Required::True,
+                parameter.description.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        parameters,
+        vec![
+            ("id", ParameterIn::Path, true, Some("Id of the pet.")),
+            (
+                "notify",
+                ParameterIn::Query,
+                false,
+                Some("Whether to notify the owner.")
+            ),
+            ("x-request-id", ParameterIn::Header, true, None),
+        ]
+    );
+    assert_json_eq!(
+        operation.request_body,
+        json!({
+            "content": {
+                "application/json": {
+                    "schema": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {"name": {"type": "string"}}
+                    }
+                }
+            },
+            "required": true
+        })
+    );
+}
+
+#[test]
+fn test_endpoint_file_upload_and_binary_response() {
+    #[endpoint(responses((status_code = 200, body = salvo::http::ResBody, description = "Thumbnail stream")))]
+    async fn upload(avatar: FormFile, res: &mut Response) {
+        res.render(avatar.size().to_string());
+    }
+
+    let router = Router::with_path("upload").post(upload);
+    let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+    let operation = &doc.paths["/upload"].operations[&PathItemType::Post];
+    assert_json_eq!(
+        operation.request_body.as_ref().unwrap().contents["multipart/form-data"],
+        json!({
+            "schema": {
+                "type": "object",
+                "required": ["avatar"],
+                "properties": {"avatar": {"type": "string", "format": "binary"}}
+            },
+            "encoding": {"avatar": {"contentType": "application/octet-stream"}}
+        })
+    );
+    assert_json_eq!(
+        operation.responses["200"],
+        json!({
+            "description": "Thumbnail stream",
+            "content": {"application/octet-stream": {"schema": {"type": "string", "format": "binary"}}}
+        })
+    );
+}
+
+#[test]
+fn test_endpoint_deprecated_sunset() {
+    #[endpoint(sunset = "2026-01-01", sunset_link = "https://example.com/migrate")]
+    async fn legacy() -> &'static str {
+        "legacy"
+    }
+
+    let router = Router::with_path("legacy").get(legacy);
+    let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+    let operation = serde_json::to_value(&doc.paths["/legacy"].operations[&PathItemType::Get]).unwrap();
+    assert_eq!(operation["deprecated"], json!(true));
+    assert_eq!(operation["x-sunset"], json!("2026-01-01"));
+}
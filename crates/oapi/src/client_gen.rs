@@ -0,0 +1,650 @@
+//! Generate a typed Rust client from the [`OpenApi`] document of a router.
+//!
+//! [`ClientGenerator`] renders a Rust module which contains a struct or enum for every schema in components
+//! and a `Client` with one async method per operation. The generated code depends on `reqwest` (with the
+//! `json` feature), `serde` and `serde_json`.
+//!
+//! Since the document is built from the same router which serves the requests, the client can be
+//! regenerated in a test to keep in-workspace consumers in sync with the server:
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_oapi::client_gen::ClientGenerator;
+//! use salvo_oapi::OpenApi;
+//!
+//! #[salvo_oapi::endpoint]
+//! async fn hello(name: salvo_oapi::extract::QueryParam<String, true>) -> String {
+//!     format!("Hello, {}!", name.into_inner())
+//! }
+//!
+//! let router = Router::new().push(Router::with_path("hello").get(hello));
+//! let doc = OpenApi::new("hello", "1.0.0").merge_router(&router);
+//! let code = ClientGenerator::new(&doc).generate();
+//! // Compare with the checked in module, and update it when the api changes.
+//! let checked_in = std::fs::read_to_string("src/client.rs").unwrap_or_default();
+//! if code != checked_in {
+//!     std::fs::write("src/client.rs", &code).unwrap();
+//!     panic!("client is outdated and has been regenerated");
+//! }
+//! ```
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::naming::NameCase;
+use crate::schema::AdditionalProperties;
+use crate::{
+    Content, KnownFormat, OpenApi, Operation, Parameter, ParameterIn, PathItemType, RefOr, Required, Response, Schema,
+    SchemaFormat, SchemaType,
+};
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+    "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop", "macro", "match", "mod",
+    "move", "mut", "override", "priv", "pub", "ref", "return", "static", "struct", "super", "trait", "true", "try",
+    "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Generator of a typed Rust client.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug)]
+pub struct ClientGenerator<'a> {
+    openapi: &'a OpenApi,
+    client_name: String,
+}
+
+impl<'a> ClientGenerator<'a> {
+    /// Create a new `ClientGenerator` for the given document.
+    pub fn new(openapi: &'a OpenApi) -> Self {
+        Self {
+            openapi,
+            client_name: "Client".into(),
+        }
+    }
+
+    /// Sets the name of the generated client struct, default is `Client`.
+    pub fn client_name(mut self, client_name: impl Into<String>) -> Self {
+        self.client_name = client_name.into();
+        self
+    }
+
+    /// Generate the source code of the client module.
+    pub fn generate(&self) -> String {
+        let mut out = String::new();
+        let info = &self.openapi.info;
+        let _ = writeln!(
+            out,
+            "//! Client of `{} {}`, generated by `salvo_oapi::client_gen`. Do not edit by hand.",
+            info.title, info.version
+        );
+        if let Some(description) = &info.description {
+            out.push_str("//!\n");
+            write_doc(&mut out, "//!", " ", description);
+        }
+        out.push_str("use serde::{Deserialize, Serialize};\n");
+        for (name, schema) in self.openapi.components.schemas.iter() {
+            out.push('\n');
+            self.write_schema(&mut out, name, schema);
+        }
+        out.push('\n');
+        self.write_client(&mut out);
+        out
+    }
+
+    fn write_schema(&self, out: &mut String, name: &str, schema: &RefOr<Schema>) {
+        let type_name = type_ident(name);
+        match schema {
+            RefOr::T(Schema::Object(object))
+                if object.schema_type == SchemaType::Object && object.additional_properties.is_none() =>
+            {
+                write_schema_doc(out, "", schema);
+                out.push_str("#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]\n");
+                let _ = writeln!(out, "pub struct {type_name} {{");
+                let mut idents = HashSet::new();
+                for (property, property_schema) in object.properties.iter() {
+                    let ident = unique_ident(&mut idents, &field_ident(property));
+                    let mut ty = self.type_of(property_schema);
+                    let required = object.required.contains(property);
+                    write_schema_doc(out, "    ", property_schema);
+                    let raw_ident = ident.trim_start_matches("r#");
+                    if raw_ident != property {
+                        let _ = writeln!(out, "    #[serde(rename = {property:?})]");
+                    }
+                    if !required {
+                        out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+                        if !ty.starts_with("Option<") {
+                            ty = format!("Option<{ty}>");
+                        }
+                    }
+                    let _ = writeln!(out, "    pub {ident}: {ty},");
+                }
+                out.push_str("}\n");
+            }
+            RefOr::T(Schema::Object(object))
+                if object.schema_type == SchemaType::String
+                    && object
+                        .enum_values
+                        .as_ref()
+                        .is_some_and(|values| !values.is_empty() && values.iter().all(|value| value.is_string())) =>
+            {
+                write_schema_doc(out, "", schema);
+                out.push_str("#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]\n");
+                let _ = writeln!(out, "pub enum {type_name} {{");
+                let mut variants = HashSet::new();
+                for value in object.enum_values.iter().flatten().filter_map(|value| value.as_str()) {
+                    let variant = unique_ident(&mut variants, &variant_ident(value));
+                    if variant != value {
+                        let _ = writeln!(out, "    #[serde(rename = {value:?})]");
+                    }
+                    let _ = writeln!(out, "    {variant},");
+                }
+                out.push_str("}\n");
+            }
+            _ => {
+                write_schema_doc(out, "", schema);
+                let _ = writeln!(out, "pub type {type_name} = {};", self.type_of(schema));
+            }
+        }
+    }
+
+    fn write_client(&self, out: &mut String) {
+        let client = &self.client_name;
+        let _ = writeln!(out, "/// Typed client of `{}`.", self.openapi.info.title);
+        out.push_str("#[derive(Clone, Debug)]\n");
+        let _ = writeln!(out, "pub struct {client} {{");
+        out.push_str("    base_url: String,\n    http: reqwest::Client,\n}\n\n");
+        let _ = writeln!(out, "impl {client} {{");
+        out.push_str(concat!(
+            "    /// Create a new client which sends requests to `base_url`.\n",
+            "    pub fn new(base_url: impl Into<String>) -> Self {\n",
+            "        Self::with_http_client(base_url, reqwest::Client::new())\n",
+            "    }\n\n",
+            "    /// Create a new client which sends requests with the given `reqwest::Client`.\n",
+            "    pub fn with_http_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {\n",
+            "        let base_url = base_url.into().trim_end_matches('/').to_owned();\n",
+            "        Self { base_url, http }\n",
+            "    }\n",
+        ));
+        let mut methods = HashSet::new();
+        for (path, path_item) in self.openapi.paths.iter() {
+            for (path_item_type, operation) in path_item.operations.iter() {
+                let method = path_item_type_name(path_item_type);
+                let name = operation
+                    .operation_id
+                    .clone()
+                    .unwrap_or_else(|| format!("{}_{path}", method.to_lowercase()));
+                let name = unique_ident(&mut methods, &field_ident(&name));
+                let mut parameters = operation.parameters.0.iter().collect::<Vec<_>>();
+                for parameter in path_item.parameters.0.iter() {
+                    if !parameters
+                        .iter()
+                        .any(|p| p.name == parameter.name && p.parameter_in == parameter.parameter_in)
+                    {
+                        parameters.push(parameter);
+                    }
+                }
+                out.push('\n');
+                self.write_operation(out, &name, method, path, operation, &parameters);
+            }
+        }
+        out.push_str("}\n");
+    }
+
+    fn write_operation(
+        &self,
+        out: &mut String,
+        name: &str,
+        method: &str,
+        path: &str,
+        operation: &Operation,
+        parameters: &[&Parameter],
+    ) {
+        let mut args = Vec::new();
+        let mut statements = Vec::new();
+        let mut idents = HashSet::from(["self".to_owned(), "request".to_owned(), "response".to_owned()]);
+        let mut path_idents = Vec::new();
+        for parameter in parameters {
+            if parameter.parameter_in == ParameterIn::Cookie {
+                continue;
+            }
+            let ident = unique_ident(&mut idents, &field_ident(&parameter.name));
+            let ty = parameter
+                .schema
+                .as_ref()
+                .map(|schema| self.type_of(schema))
+                .unwrap_or_else(|| "String".into());
+            let required = parameter.parameter_in == ParameterIn::Path || parameter.required == Required::True;
+            let key = &parameter.name;
+            match parameter.parameter_in {
+                ParameterIn::Path => path_idents.push((key.clone(), ident.clone())),
+                ParameterIn::Query => {
+                    let value = if ty.starts_with("Vec<") { "value" } else { &*ident };
+                    let mut statement = format!("request = request.query(&[({key:?}, {value})]);");
+                    if ty.starts_with("Vec<") {
+                        statement = format!("for value in {ident} {{ {statement} }}");
+                    }
+                    statements.push(optional_statement(&ident, required, statement));
+                }
+                ParameterIn::Header => {
+                    let statement = format!("request = request.header({key:?}, {ident}.to_string());");
+                    statements.push(optional_statement(&ident, required, statement));
+                }
+                _ => continue,
+            }
+            let ty = match parameter.parameter_in {
+                ParameterIn::Query if ty.starts_with("Vec<") => format!("&[{}]", &ty[4..ty.len() - 1]),
+                ParameterIn::Query | ParameterIn::Header if !ty.starts_with("Option<") => format!("&{ty}"),
+                _ => ty,
+            };
+            let ty = if required || ty.starts_with("Option<") {
+                ty
+            } else {
+                format!("Option<{ty}>")
+            };
+            args.push(format!("{ident}: {ty}"));
+        }
+        // Placeholders which are not documented as parameters are passed as `&str`.
+        let mut url_format = String::from("{}");
+        let mut url_args = Vec::new();
+        let mut rest = path;
+        while let Some((start, end)) = rest
+            .find('{')
+            .and_then(|start| Some((start, start + rest[start..].find('}')?)))
+        {
+            url_format.push_str(&escape_format(&rest[..start]));
+            url_format.push_str("{}");
+            let key = &rest[start + 1..end];
+            let ident = match path_idents.iter().find(|(name, _)| name == key) {
+                Some((_, ident)) => ident.clone(),
+                None => {
+                    let ident = unique_ident(&mut idents, &field_ident(key));
+                    args.insert(url_args.len(), format!("{ident}: &str"));
+                    ident
+                }
+            };
+            url_args.push(ident);
+            rest = &rest[end + 1..];
+        }
+        url_format.push_str(&escape_format(rest));
+        if let Some(request_body) = &operation.request_body {
+            let body = if let Some(content) = json_content(&request_body.contents) {
+                Some((content, "json"))
+            } else {
+                request_body
+                    .contents
+                    .get("application/x-www-form-urlencoded")
+                    .map(|content| (content, "form"))
+            };
+            if let Some((content, encode)) = body {
+                let ident = unique_ident(&mut idents, "body");
+                let ty = format!("&{}", self.type_of(&content.schema));
+                let required = request_body.required == Some(Required::True);
+                statements.push(optional_statement(
+                    &ident,
+                    required,
+                    format!("request = request.{encode}({ident});"),
+                ));
+                args.push(if required {
+                    format!("{ident}: {ty}")
+                } else {
+                    format!("{ident}: Option<{ty}>")
+                });
+            }
+        }
+        let (output, read) = self.response_of(operation);
+
+        if let Some(summary) = &operation.summary {
+            write_doc(out, "    ///", " ", summary);
+        }
+        if let Some(description) = &operation.description {
+            if operation.summary.is_some() {
+                out.push_str("    ///\n");
+            }
+            write_doc(out, "    ///", " ", description);
+        }
+        if operation.deprecated == Some(crate::Deprecated::True) {
+            out.push_str("    #[deprecated]\n");
+        }
+        let mut signature = format!("    pub async fn {name}(&self");
+        for arg in &args {
+            signature.push_str(", ");
+            signature.push_str(arg);
+        }
+        let _ = writeln!(out, "{signature}) -> Result<{output}, reqwest::Error> {{");
+        let _ = writeln!(
+            out,
+            "        let url = format!({url_format:?}, self.base_url{});",
+            url_args.iter().map(|arg| format!(", {arg}")).collect::<String>()
+        );
+        let mutability = if statements.is_empty() { "" } else { "mut " };
+        let _ = writeln!(
+            out,
+            "        let {mutability}request = self.http.request(reqwest::Method::{method}, url);"
+        );
+        for statement in statements {
+            let _ = writeln!(out, "        {statement}");
+        }
+        out.push_str("        let response = request.send().await?.error_for_status()?;\n");
+        let _ = writeln!(out, "        {read}");
+        out.push_str("    }\n");
+    }
+
+    /// Returns the output type and the statement which reads the first successful response.
+    fn response_of(&self, operation: &Operation) -> (String, &'static str) {
+        let response = operation
+            .responses
+            .iter()
+            .find(|(status, _)| status.starts_with('2'))
+            .and_then(|(_, response)| self.resolve_response(response));
+        let Some(response) = response else {
+            return ("()".into(), "let _ = response;\n        Ok(())");
+        };
+        if let Some(content) = json_content(&response.contents) {
+            (self.type_of(&content.schema), "response.json().await")
+        } else if response.contents.keys().any(|key| key.starts_with("text/")) {
+            ("String".into(), "response.text().await")
+        } else if response.contents.is_empty() {
+            ("()".into(), "let _ = response;\n        Ok(())")
+        } else {
+            ("Vec<u8>".into(), "Ok(response.bytes().await?.to_vec())")
+        }
+    }
+
+    fn resolve_response<'r>(&'r self, response: &'r RefOr<Response>) -> Option<&'r Response> {
+        match response {
+            RefOr::T(response) => Some(response),
+            RefOr::Ref(reference) => reference
+                .ref_location
+                .strip_prefix("#/components/responses/")
+                .and_then(|name| self.openapi.components.responses.get(name))
+                .and_then(|response| match response {
+                    RefOr::T(response) => Some(response),
+                    RefOr::Ref(_) => None,
+                }),
+        }
+    }
+
+    /// Returns the Rust type of the schema.
+    fn type_of(&self, schema: &RefOr<Schema>) -> String {
+        match schema {
+            RefOr::Ref(reference) => reference
+                .ref_location
+                .strip_prefix("#/components/schemas/")
+                .map(type_ident)
+                .unwrap_or_else(|| "serde_json::Value".into()),
+            RefOr::T(Schema::Object(object)) => {
+                let ty = match object.schema_type {
+                    SchemaType::String => match object.format {
+                        Some(SchemaFormat::KnownFormat(KnownFormat::Binary)) => "Vec<u8>".into(),
+                        _ => "String".into(),
+                    },
+                    SchemaType::Integer => integer_type(object.format.as_ref()).into(),
+                    SchemaType::Number => match object.format {
+                        Some(SchemaFormat::KnownFormat(KnownFormat::Float)) => "f32".into(),
+                        _ => "f64".into(),
+                    },
+                    SchemaType::Boolean => "bool".into(),
+                    SchemaType::Object if object.properties.is_empty() => match object.additional_properties.as_deref()
+                    {
+                        Some(AdditionalProperties::RefOr(value)) => {
+                            format!("std::collections::HashMap<String, {}>", self.type_of(value))
+                        }
+                        _ => "serde_json::Value".into(),
+                    },
+                    _ => "serde_json::Value".into(),
+                };
+                if object.nullable {
+                    format!("Option<{ty}>")
+                } else {
+                    ty
+                }
+            }
+            RefOr::T(Schema::Array(array)) => {
+                let ty = format!("Vec<{}>", self.type_of(&array.items));
+                if array.nullable {
+                    format!("Option<{ty}>")
+                } else {
+                    ty
+                }
+            }
+            RefOr::T(_) => "serde_json::Value".into(),
+        }
+    }
+}
+
+fn json_content(contents: &indexmap::IndexMap<String, Content>) -> Option<&Content> {
+    contents
+        .iter()
+        .find(|(key, _)| key.starts_with("application/") && key.ends_with("json"))
+        .map(|(_, content)| content)
+}
+
+fn escape_format(text: &str) -> String {
+    text.replace('{', "{{").replace('}', "}}")
+}
+
+fn optional_statement(ident: &str, required: bool, statement: String) -> String {
+    if required {
+        statement
+    } else {
+        format!("if let Some({ident}) = {ident} {{ {statement} }}")
+    }
+}
+
+fn integer_type(format: Option<&SchemaFormat>) -> &'static str {
+    match format {
+        Some(SchemaFormat::KnownFormat(format)) => match format {
+            KnownFormat::Int8 => "i8",
+            KnownFormat::Int16 => "i16",
+            KnownFormat::Int32 => "i32",
+            KnownFormat::UInt8 => "u8",
+            KnownFormat::UInt16 => "u16",
+            KnownFormat::UInt32 => "u32",
+            KnownFormat::UInt64 => "u64",
+            _ => "i64",
+        },
+        _ => "i64",
+    }
+}
+
+fn path_item_type_name(path_item_type: &PathItemType) -> &'static str {
+    match path_item_type {
+        PathItemType::Get => "GET",
+        PathItemType::Post => "POST",
+        PathItemType::Put => "PUT",
+        PathItemType::Delete => "DELETE",
+        PathItemType::Options => "OPTIONS",
+        PathItemType::Head => "HEAD",
+        PathItemType::Patch => "PATCH",
+        PathItemType::Trace => "TRACE",
+        PathItemType::Connect => "CONNECT",
+    }
+}
+
+fn write_schema_doc(out: &mut String, indent: &str, schema: &RefOr<Schema>) {
+    let description = match schema {
+        RefOr::T(Schema::Object(object)) => object.description.as_deref(),
+        RefOr::T(Schema::Array(array)) => array.description.as_deref(),
+        _ => None,
+    };
+    if let Some(description) = description {
+        write_doc(out, &format!("{indent}///"), " ", description);
+    }
+}
+
+fn write_doc(out: &mut String, prefix: &str, separator: &str, text: &str) {
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            let _ = writeln!(out, "{prefix}");
+        } else {
+            let _ = writeln!(out, "{prefix}{separator}{}", line.trim_end());
+        }
+    }
+}
+
+/// Replace the characters which are not allowed in identifiers with `_`.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn type_ident(name: &str) -> String {
+    let ident = NameCase::Pascal.convert(&sanitize(name));
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("T{ident}")
+    } else {
+        ident
+    }
+}
+
+fn variant_ident(value: &str) -> String {
+    let ident = NameCase::Pascal.convert(&sanitize(value));
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("V{ident}")
+    } else {
+        ident
+    }
+}
+
+fn field_ident(name: &str) -> String {
+    let ident = NameCase::Snake.convert(&sanitize(name));
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{ident}")
+    } else if KEYWORDS.contains(&&*ident) {
+        format!("r#{ident}")
+    } else {
+        ident
+    }
+}
+
+fn unique_ident(idents: &mut HashSet<String>, ident: &str) -> String {
+    let mut unique = ident.to_owned();
+    let mut index = 2;
+    while !idents.insert(unique.clone()) {
+        unique = format!("{ident}_{index}");
+        index += 1;
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Array, Object, PathItem, RequestBody};
+
+    fn openapi() -> OpenApi {
+        let pet = Object::new()
+            .description("A pet in the store.")
+            .property(
+                "id",
+                Object::with_type(SchemaType::Integer).format(SchemaFormat::KnownFormat(KnownFormat::Int64)),
+            )
+            .required("id")
+            .property("petName", Object::with_type(SchemaType::String))
+            .required("petName")
+            .property("type", crate::Ref::from_schema_name("PetKind"))
+            .property("tags", Array::new(Object::with_type(SchemaType::String)));
+        let kind = Object::with_type(SchemaType::String).enum_values(["dog", "cat", "sea-lion"]);
+        let get_pet = Operation::new()
+            .operation_id("get_pet")
+            .summary("Get a pet by id.")
+            .add_parameter(
+                Parameter::new("id")
+                    .parameter_in(ParameterIn::Path)
+                    .required(Required::True)
+                    .schema(
+                        Object::with_type(SchemaType::Integer).format(SchemaFormat::KnownFormat(KnownFormat::Int64)),
+                    ),
+            )
+            .add_parameter(
+                Parameter::new("x-request-id")
+                    .parameter_in(ParameterIn::Header)
+                    .required(Required::False)
+                    .schema(Object::with_type(SchemaType::String)),
+            )
+            .add_response(
+                "200",
+                Response::new("Found")
+                    .add_content("application/json", Content::new(crate::Ref::from_schema_name("Pet"))),
+            );
+        let list_pets = Operation::new()
+            .operation_id("list_pets")
+            .add_parameter(
+                Parameter::new("limit")
+                    .parameter_in(ParameterIn::Query)
+                    .required(Required::False)
+                    .schema(
+                        Object::with_type(SchemaType::Integer).format(SchemaFormat::KnownFormat(KnownFormat::Int32)),
+                    ),
+            )
+            .add_response(
+                "200",
+                Response::new("Pets").add_content(
+                    "application/json",
+                    Content::new(Array::new(crate::Ref::from_schema_name("Pet"))),
+                ),
+            );
+        let create_pet = Operation::new()
+            .operation_id("create_pet")
+            .request_body(
+                RequestBody::new()
+                    .add_content("application/json", Content::new(crate::Ref::from_schema_name("Pet")))
+                    .required(Required::True),
+            )
+            .add_response("201", Response::new("Created"));
+        let mut doc = OpenApi::new("Pet Store", "1.0.0")
+            .add_path("/pets", PathItem::new(PathItemType::Get, list_pets))
+            .add_path("/pets", PathItem::new(PathItemType::Post, create_pet))
+            .add_path("/pets/{id}", PathItem::new(PathItemType::Get, get_pet));
+        doc.components.schemas.insert("Pet", pet);
+        doc.components.schemas.insert("PetKind", kind);
+        doc
+    }
+
+    #[test]
+    fn test_generate_types() {
+        let code = ClientGenerator::new(&openapi()).generate();
+        assert!(code.starts_with("//! Client of `Pet Store 1.0.0`"));
+        assert!(code.contains(
+            "/// A pet in the store.\n\
+             #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]\n\
+             pub struct Pet {\n    \
+                 pub id: i64,\n    \
+                 #[serde(rename = \"petName\")]\n    \
+                 pub pet_name: String,\n"
+        ));
+        assert!(code.contains("    pub tags: Option<Vec<String>>,\n"));
+        assert!(code.contains(
+            "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub r#type: Option<PetKind>,\n"
+        ));
+        assert!(code.contains(
+            "pub enum PetKind {\n    \
+                 #[serde(rename = \"dog\")]\n    Dog,\n    \
+                 #[serde(rename = \"cat\")]\n    Cat,\n    \
+                 #[serde(rename = \"sea-lion\")]\n    SeaLion,\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_operations() {
+        let code = ClientGenerator::new(&openapi()).client_name("PetClient").generate();
+        assert!(code.contains("pub struct PetClient {"));
+        assert!(code.contains(
+            "    /// Get a pet by id.\n    \
+             pub async fn get_pet(&self, id: i64, x_request_id: Option<&String>) -> Result<Pet, reqwest::Error> {\n        \
+                 let url = format!(\"{}/pets/{}\", self.base_url, id);\n        \
+                 let mut request = self.http.request(reqwest::Method::GET, url);\n        \
+                 if let Some(x_request_id) = x_request_id { request = request.header(\"x-request-id\", x_request_id.to_string()); }\n        \
+                 let response = request.send().await?.error_for_status()?;\n        \
+                 response.json().await\n    \
+             }\n"
+        ));
+        assert!(
+            code.contains("pub async fn list_pets(&self, limit: Option<&i32>) -> Result<Vec<Pet>, reqwest::Error> {")
+        );
+        assert!(code.contains("if let Some(limit) = limit { request = request.query(&[(\"limit\", limit)]); }"));
+        assert!(code.contains("pub async fn create_pet(&self, body: &Pet) -> Result<(), reqwest::Error> {"));
+        assert!(code.contains("request = request.json(body);"));
+    }
+}
@@ -0,0 +1,118 @@
+//! Signal deprecated endpoints to clients with response headers.
+//!
+//! [`Deprecation`] writes the `Deprecation` header, the `Sunset` header defined by
+//! [RFC 8594](https://www.rfc-editor.org/rfc/rfc8594) and a `Link` header with `rel="sunset"` pointing to
+//! the migration documentation. It is installed automatically by `#[endpoint(deprecated, sunset = "2026-01-01")]`,
+//! and can also be used as a hoop for routers which are not documented by endpoints.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::{Duration, UNIX_EPOCH};
+//!
+//! use salvo_core::prelude::*;
+//! use salvo_oapi::deprecation::Deprecation;
+//!
+//! #[handler]
+//! async fn legacy() -> &'static str {
+//!     "legacy"
+//! }
+//!
+//! let router = Router::with_path("v1").hoop(
+//!     Deprecation::new()
+//!         .sunset(UNIX_EPOCH + Duration::from_secs(1_767_225_600))
+//!         .link("https://example.com/docs/migrate-to-v2"),
+//! ).get(legacy);
+//! ```
+use std::time::SystemTime;
+
+use salvo_core::http::header::{HeaderName, HeaderValue, LINK};
+use salvo_core::http::headers::{Header, LastModified};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+
+/// A hoop which marks the responses as deprecated.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Default, Debug)]
+pub struct Deprecation {
+    sunset: Option<SystemTime>,
+    links: Vec<String>,
+}
+
+impl Deprecation {
+    /// Create a new `Deprecation`.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the time after which the endpoint is expected to become unresponsive.
+    #[inline]
+    pub fn sunset(mut self, sunset: SystemTime) -> Self {
+        self.sunset = Some(sunset);
+        self
+    }
+
+    /// Add a link to the documentation of the deprecation, sent as `Link: <url>; rel="sunset"`.
+    #[inline]
+    pub fn link(mut self, url: impl Into<String>) -> Self {
+        self.links.push(url.into());
+        self
+    }
+
+    /// Write the deprecation headers to the response.
+    pub fn write_headers(&self, res: &mut Response) {
+        let headers = res.headers_mut();
+        headers.insert(HeaderName::from_static("deprecation"), HeaderValue::from_static("true"));
+        if let Some(sunset) = self.sunset {
+            let mut values = Vec::with_capacity(1);
+            LastModified::from(sunset).encode(&mut values);
+            if let Some(value) = values.pop() {
+                headers.insert(HeaderName::from_static("sunset"), value);
+            }
+        }
+        for link in &self.links {
+            if let Ok(value) = HeaderValue::from_str(&format!("<{link}>; rel=\"sunset\"")) {
+                headers.append(LINK, value);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for Deprecation {
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        self.write_headers(res);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deprecation_headers() {
+        #[handler]
+        async fn hello() -> &'static str {
+            "hello"
+        }
+
+        let router = Router::new()
+            .hoop(
+                Deprecation::new()
+                    .sunset(UNIX_EPOCH + Duration::from_secs(1_767_225_600))
+                    .link("https://example.com/migrate"),
+            )
+            .get(hello);
+        let res = TestClient::get("http://127.0.0.1:5800/").send(router).await;
+        let headers = res.headers();
+        assert_eq!(headers["deprecation"], "true");
+        assert_eq!(headers["sunset"], "Thu, 01 Jan 2026 00:00:00 GMT");
+        assert_eq!(headers[LINK], "<https://example.com/migrate>; rel=\"sunset\"");
+    }
+}
@@ -1,5 +1,6 @@
 use std::any::TypeId;
 
+use salvo_core::fs::NamedFile;
 use salvo_core::http::StatusCode;
 use salvo_core::{prelude::StatusError, writing};
 
@@ -13,12 +14,18 @@ pub struct Endpoint {
     pub operation: Operation,
     /// The OpenApi components section of the endpoint.
     pub components: Components,
+    /// Whether the endpoint is left out of the documents, set with `#[endpoint(hidden)]`.
+    pub hidden: bool,
 }
 
 impl Endpoint {
     /// Create new `Endpoint` with given operation and components.
     pub fn new(operation: Operation, components: Components) -> Self {
-        Self { operation, components }
+        Self {
+            operation,
+            components,
+            hidden: false,
+        }
     }
 }
 
@@ -42,6 +49,15 @@ where
         operation.responses.insert("200", Self::to_response(components));
     }
 }
+impl<C> EndpointOutRegister for writing::Xml<C>
+where
+    C: ToSchema,
+{
+    #[inline]
+    fn register(components: &mut Components, operation: &mut Operation) {
+        operation.responses.insert("200", Self::to_response(components));
+    }
+}
 impl<T, E> EndpointOutRegister for Result<T, E>
 where
     T: EndpointOutRegister + Send,
@@ -141,6 +157,16 @@ impl<'a> EndpointOutRegister for &'a String {
     }
 }
 
+impl EndpointOutRegister for NamedFile {
+    #[inline]
+    fn register(components: &mut Components, operation: &mut Operation) {
+        operation.responses.insert(
+            "200",
+            Response::new("File content").add_content("application/octet-stream", bytes::Bytes::to_schema(components)),
+        );
+    }
+}
+
 /// A registry for all endpoints.
 #[doc(hidden)]
 #[non_exhaustive]
@@ -167,3 +193,49 @@ impl EndpointRegistry {
     }
 }
 inventory::collect!(EndpointRegistry);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndpointOutRegister, RefOr};
+
+    #[test]
+    fn test_derive_endpoint_out_register() {
+        #[allow(dead_code)]
+        #[derive(ToSchema)]
+        struct ErrorBody {
+            message: String,
+        }
+
+        #[allow(dead_code)]
+        #[derive(EndpointOutRegister)]
+        #[salvo(error(body = ErrorBody))]
+        enum UserError {
+            /// User not found.
+            #[salvo(error(status_code = 404))]
+            UserNotFound,
+            #[salvo(error(status_code = StatusCode::NOT_FOUND, description = "Post not found."))]
+            PostNotFound,
+            #[salvo(error(status_code = 503, body = String, content_type = "text/plain"))]
+            Database(String),
+            Unknown,
+        }
+
+        let mut components = Components::new();
+        let mut operation = Operation::new();
+        <UserError as EndpointOutRegister>::register(&mut components, &mut operation);
+
+        let response = |code: &str| match operation.responses.get(code) {
+            Some(RefOr::T(response)) => response.clone(),
+            _ => panic!("missing response {code}"),
+        };
+        assert_eq!(operation.responses.len(), 3);
+        let not_found = response("404");
+        assert_eq!(not_found.description, "User not found.\n\nPost not found.");
+        assert!(not_found.contents.contains_key("application/json"));
+        let unavailable = response("503");
+        assert_eq!(unavailable.description, "Database");
+        assert!(unavailable.contents.contains_key("text/plain"));
+        assert_eq!(response("500").description, "Unknown");
+    }
+}
@@ -0,0 +1,234 @@
+use salvo_core::extract::metadata::{Field, Source, SourceFrom};
+use salvo_core::extract::{Extractible, Metadata};
+
+use crate::{
+    Components, Content, Object, Operation, Parameter, ParameterIn, RefOr, RequestBody, Required, Schema, ToSchema,
+};
+
+/// Register the fields of an [`Extractible`] type to the operation.
+///
+/// Fields extracted from url param, query, header and cookie are documented as parameters, fields
+/// extracted from body are documented as a json request body. The schema of each field is read from the
+/// [`ToSchema`] implementation of the type. This function is called by macros internal for types which
+/// derive both `Extractible` and `ToSchema`.
+pub fn register_extractible<'de, T>(components: &mut Components, operation: &mut Operation)
+where
+    T: Extractible<'de> + ToSchema,
+{
+    let schema = T::to_schema(components);
+    let metadata = T::metadata();
+    let mut body = Object::new();
+    register_fields(components, operation, metadata, &schema, &mut body);
+    if body.properties.is_empty() || operation.request_body.is_some() {
+        return;
+    }
+    let all_in_body = metadata.fields.iter().all(|field| {
+        !field.flatten && field_sources(metadata, field).first().map(|s| s.from) == Some(SourceFrom::Body)
+    });
+    let body_schema = if all_in_body { schema } else { body.into() };
+    operation.request_body = Some(
+        RequestBody::new()
+            .add_content("application/json", Content::new(body_schema))
+            .required(Required::True),
+    );
+}
+
+fn register_fields(
+    components: &Components,
+    operation: &mut Operation,
+    metadata: &Metadata,
+    schema: &RefOr<Schema>,
+    body: &mut Object,
+) {
+    let Some(object) = resolve_object(components, schema) else {
+        return;
+    };
+    for field in &metadata.fields {
+        let property_name = property_name(metadata, field);
+        let property = object.properties.get(&property_name).cloned();
+        if field.flatten {
+            if let (Some(nested), Some(property)) = (field.metadata, &property) {
+                register_fields(components, operation, nested, property, body);
+            }
+            continue;
+        }
+        let Some(source) = field_sources(metadata, field).first() else {
+            continue;
+        };
+        let required = object.required.contains(&property_name);
+        let parameter_in = match source.from {
+            SourceFrom::Param => ParameterIn::Path,
+            SourceFrom::Query => ParameterIn::Query,
+            SourceFrom::Header => ParameterIn::Header,
+            SourceFrom::Cookie => ParameterIn::Cookie,
+            SourceFrom::Body => {
+                if let Some(property) = property {
+                    body.properties.insert(property_name.clone(), property);
+                    if required {
+                        body.required.insert(property_name);
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let mut parameter = Parameter::new(extract_name(metadata, field))
+            .parameter_in(parameter_in)
+            .required(required || parameter_in == ParameterIn::Path);
+        if let Some(property) = property {
+            if let Some(description) = description(components, &property) {
+                parameter = parameter.description(description);
+            }
+            parameter = parameter.schema(property);
+        }
+        operation.parameters.insert(parameter);
+    }
+}
+
+fn resolve_object<'a>(components: &'a Components, schema: &'a RefOr<Schema>) -> Option<&'a Object> {
+    match schema {
+        RefOr::T(Schema::Object(object)) => Some(object),
+        RefOr::T(_) => None,
+        RefOr::Ref(reference) => reference
+            .ref_location
+            .strip_prefix("#/components/schemas/")
+            .and_then(|name| components.schemas.get(name))
+            .filter(|schema| !matches!(schema, RefOr::Ref(_)))
+            .and_then(|schema| resolve_object(components, schema)),
+    }
+}
+
+fn description(components: &Components, schema: &RefOr<Schema>) -> Option<String> {
+    match schema {
+        RefOr::T(Schema::Object(object)) => object.description.clone(),
+        RefOr::T(Schema::Array(array)) => array.description.clone(),
+        _ => resolve_object(components, schema).and_then(|object| object.description.clone()),
+    }
+}
+
+fn field_sources<'a>(metadata: &'a Metadata, field: &'a Field) -> &'a [Source] {
+    if field.sources.is_empty() {
+        &metadata.default_sources
+    } else {
+        &field.sources
+    }
+}
+
+/// The name used by the schema, which follows the serde attributes.
+fn property_name(metadata: &Metadata, field: &Field) -> String {
+    if let Some(serde_rename) = field.serde_rename {
+        serde_rename.to_owned()
+    } else if let Some(serde_rename_all) = metadata.serde_rename_all {
+        serde_rename_all.apply_to_field(field.decl_name)
+    } else {
+        field.decl_name.to_owned()
+    }
+}
+
+/// The name used to extract the value from request, same as the request deserializer.
+fn extract_name(metadata: &Metadata, field: &Field) -> String {
+    if let Some(rename) = field.rename {
+        rename.to_owned()
+    } else if let Some(serde_rename) = field.serde_rename {
+        serde_rename.to_owned()
+    } else if let Some(rename_all) = metadata.rename_all {
+        rename_all.apply_to_field(field.decl_name)
+    } else if let Some(serde_rename_all) = metadata.serde_rename_all {
+        serde_rename_all.apply_to_field(field.decl_name)
+    } else {
+        field.decl_name.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use assert_json_diff::assert_json_eq;
+    use salvo_core::extract::metadata::SourceParser;
+    use salvo_core::http::{ParseError, Request};
+    use salvo_core::Writer;
+    use serde_json::json;
+
+    use super::*;
+    use crate::SchemaType;
+
+    struct UpdatePet;
+
+    impl<'ex> Extractible<'ex> for UpdatePet {
+        fn metadata() -> &'ex Metadata {
+            static METADATA: once_cell::sync::OnceCell<Metadata> = once_cell::sync::OnceCell::new();
+            METADATA.get_or_init(|| {
+                Metadata::new("UpdatePet")
+                    .add_default_source(Source::new(SourceFrom::Body, SourceParser::Json))
+                    .add_field(Field::new("id").add_source(Source::new(SourceFrom::Param, SourceParser::MultiMap)))
+                    .add_field(
+                        Field::new("request_id")
+                            .rename("x-request-id")
+                            .add_source(Source::new(SourceFrom::Header, SourceParser::MultiMap)),
+                    )
+                    .add_field(Field::new("name"))
+            })
+        }
+        async fn extract(_req: &'ex mut Request) -> Result<Self, impl Writer + Send + Debug + 'static> {
+            Err::<Self, _>(ParseError::other("not supported"))
+        }
+    }
+
+    impl ToSchema for UpdatePet {
+        fn to_schema(components: &mut Components) -> RefOr<Schema> {
+            let schema = Object::new()
+                .property("id", Object::with_type(SchemaType::Integer))
+                .required("id")
+                .property(
+                    "request_id",
+                    Object::with_type(SchemaType::String).description("Request id"),
+                )
+                .property("name", Object::with_type(SchemaType::String))
+                .required("name");
+            components.schemas.insert("UpdatePet", schema);
+            crate::Ref::from_schema_name("UpdatePet").into()
+        }
+    }
+
+    #[test]
+    fn test_register_extractible() {
+        let mut components = Components::new();
+        let mut operation = Operation::new();
+        register_extractible::<UpdatePet>(&mut components, &mut operation);
+
+        assert_json_eq!(
+            operation,
+            json!({
+                "parameters": [
+                    {
+                        "name": "id",
+                        "in": "path",
+                        "required": true,
+                        "schema": {"type": "integer"}
+                    },
+                    {
+                        "name": "x-request-id",
+                        "in": "header",
+                        "description": "Request id",
+                        "required": false,
+                        "schema": {"type": "string", "description": "Request id"}
+                    }
+                ],
+                "requestBody": {
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "type": "object",
+                                "required": ["name"],
+                                "properties": {"name": {"type": "string"}}
+                            }
+                        }
+                    },
+                    "required": true
+                },
+                "responses": {}
+            })
+        );
+    }
+}
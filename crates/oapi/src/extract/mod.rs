@@ -1,5 +1,8 @@
 //! Request extractors for the API operation.
 //!
+mod extractible;
+pub use extractible::register_extractible;
+
 mod parameter;
 pub use parameter::*;
 
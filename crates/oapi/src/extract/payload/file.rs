@@ -8,8 +8,10 @@ use salvo_core::http::{HeaderMap, Mime, ParseError};
 use salvo_core::{async_trait, Request};
 
 use crate::endpoint::EndpointArgRegister;
+use crate::schema::AllOf;
 use crate::{
-    Array, Components, Content, KnownFormat, Object, Operation, RequestBody, Schema, SchemaFormat, SchemaType,
+    Array, Components, Content, Encoding, KnownFormat, Object, Operation, RefOr, RequestBody, Required, Schema,
+    SchemaFormat, SchemaType, ToSchema,
 };
 
 /// Represents the upload file.
@@ -92,24 +94,25 @@ impl<'ex> Extractible<'ex> for FormFile {
     }
 }
 
+impl ToSchema for FormFile {
+    fn to_schema(components: &mut Components) -> RefOr<Schema> {
+        FilePart::to_schema(components)
+    }
+}
+
+impl ToSchema for FilePart {
+    fn to_schema(_components: &mut Components) -> RefOr<Schema> {
+        Object::with_type(SchemaType::String)
+            .format(SchemaFormat::KnownFormat(KnownFormat::Binary))
+            .into()
+    }
+}
+
 #[async_trait]
 impl EndpointArgRegister for FormFile {
-    fn register(_components: &mut Components, operation: &mut Operation, arg: &str) {
-        let schema = Schema::from(Object::new().property(
-            arg,
-            Object::with_type(SchemaType::String).format(SchemaFormat::KnownFormat(KnownFormat::Binary)),
-        ));
-
-        if let Some(request_body) = &mut operation.request_body {
-            request_body
-                .contents
-                .insert("multipart/form-data".into(), Content::new(schema));
-        } else {
-            let request_body = RequestBody::new()
-                .description("Upload a file.")
-                .add_content("multipart/form-data", Content::new(schema));
-            operation.request_body = Some(request_body);
-        }
+    fn register(components: &mut Components, operation: &mut Operation, arg: &str) {
+        let schema = Self::to_schema(components);
+        register_multipart_file(operation, arg, schema, "Upload a file.");
     }
 }
 
@@ -163,24 +166,82 @@ impl<'ex> Extractible<'ex> for FormFiles {
     }
 }
 
+impl ToSchema for FormFiles {
+    fn to_schema(components: &mut Components) -> RefOr<Schema> {
+        Array::new(FilePart::to_schema(components)).into()
+    }
+}
+
 #[async_trait]
 impl EndpointArgRegister for FormFiles {
-    fn register(_components: &mut Components, operation: &mut Operation, arg: &str) {
-        let schema = Schema::from(Object::new().property(
-            arg,
-            Array::new(Schema::from(
-                Object::with_type(SchemaType::String).format(SchemaFormat::KnownFormat(KnownFormat::Binary)),
-            )),
-        ));
-        if let Some(request_body) = &mut operation.request_body {
-            request_body
-                .contents
-                .insert("multipart/form-data".into(), Content::new(schema));
-        } else {
-            let request_body = RequestBody::new()
-                .description("Upload files.")
-                .add_content("multipart/form-data", Content::new(schema));
-            operation.request_body = Some(request_body);
+    fn register(components: &mut Components, operation: &mut Operation, arg: &str) {
+        let schema = Self::to_schema(components);
+        register_multipart_file(operation, arg, schema, "Upload files.");
+    }
+}
+
+/// Add the file field `arg` to the `multipart/form-data` request body of the operation, keeping the
+/// fields registered by other arguments.
+fn register_multipart_file(operation: &mut Operation, arg: &str, schema: RefOr<Schema>, description: &str) {
+    let request_body = operation
+        .request_body
+        .get_or_insert_with(|| RequestBody::new().description(description));
+    request_body.required = Some(Required::True);
+    let content = request_body
+        .contents
+        .entry("multipart/form-data".into())
+        .or_insert_with(|| Content::new(Object::new()));
+    match &mut content.schema {
+        RefOr::T(Schema::Object(object)) if object.schema_type == SchemaType::Object => {
+            object.properties.insert(arg.into(), schema);
+            object.required.insert(arg.into());
         }
+        existing => {
+            let file = Object::new().property(arg, schema).required(arg);
+            *existing = AllOf::new().item(existing.clone()).item(file).into();
+        }
+    }
+    content
+        .encoding
+        .insert(arg.into(), Encoding::default().content_type("application/octet-stream"));
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_json_diff::assert_json_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_form_file_register() {
+        let mut components = Components::new();
+        let mut operation = Operation::new();
+        FormFile::register(&mut components, &mut operation, "avatar");
+        FormFiles::register(&mut components, &mut operation, "photos");
+
+        assert_json_eq!(
+            operation.request_body,
+            json!({
+                "description": "Upload a file.",
+                "content": {
+                    "multipart/form-data": {
+                        "schema": {
+                            "type": "object",
+                            "required": ["avatar", "photos"],
+                            "properties": {
+                                "avatar": {"type": "string", "format": "binary"},
+                                "photos": {"type": "array", "items": {"type": "string", "format": "binary"}}
+                            }
+                        },
+                        "encoding": {
+                            "avatar": {"contentType": "application/octet-stream"},
+                            "photos": {"contentType": "application/octet-stream"}
+                        }
+                    }
+                },
+                "required": true
+            })
+        );
     }
 }
@@ -2,7 +2,9 @@
 mod file;
 mod form;
 mod json;
+mod xml;
 
 pub use file::{FormFile, FormFiles};
 pub use form::FormBody;
 pub use json::JsonBody;
+pub use xml::XmlBody;
@@ -0,0 +1,160 @@
+use std::fmt::{self, Formatter};
+use std::ops::{Deref, DerefMut};
+
+use salvo_core::extract::{Extractible, Metadata};
+use salvo_core::{Request, Writer};
+use serde::{Deserialize, Deserializer};
+
+use crate::endpoint::EndpointArgRegister;
+use crate::{Components, Content, Operation, RequestBody, ToRequestBody, ToSchema};
+
+/// Represents the xml payload of the request body.
+///
+/// The `xml` attribute of [`ToSchema`] can be used to describe element names, wrapped arrays and attributes in
+/// the generated schema.
+pub struct XmlBody<T>(pub T);
+impl<T> XmlBody<T> {
+    /// Consumes self and returns the value of the parameter.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for XmlBody<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for XmlBody<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'de, T> ToRequestBody for XmlBody<T>
+where
+    T: Deserialize<'de> + ToSchema,
+{
+    fn to_request_body(components: &mut Components) -> RequestBody {
+        RequestBody::new()
+            .description("Extract xml format data from request.")
+            .add_content("application/xml", Content::new(T::to_schema(components)))
+    }
+}
+
+impl<T> fmt::Debug for XmlBody<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> fmt::Display for XmlBody<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<'ex, T> Extractible<'ex> for XmlBody<T>
+where
+    T: Deserialize<'ex> + Send,
+{
+    fn metadata() -> &'ex Metadata {
+        static METADATA: Metadata = Metadata::new("");
+        &METADATA
+    }
+    async fn extract(req: &'ex mut Request) -> Result<Self, impl Writer + Send + fmt::Debug + 'static> {
+        req.parse_xml().await
+    }
+    async fn extract_with_arg(
+        req: &'ex mut Request,
+        _arg: &str,
+    ) -> Result<Self, impl Writer + Send + fmt::Debug + 'static> {
+        Self::extract(req).await
+    }
+}
+
+impl<'de, T> Deserialize<'de> for XmlBody<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(XmlBody)
+    }
+}
+
+impl<'de, T> EndpointArgRegister for XmlBody<T>
+where
+    T: Deserialize<'de> + ToSchema,
+{
+    fn register(components: &mut Components, operation: &mut Operation, _arg: &str) {
+        let request_body = Self::to_request_body(components);
+        let _ = <T as ToSchema>::to_schema(components);
+        operation.request_body = Some(request_body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_json_diff::assert_json_eq;
+    use salvo_core::test::TestClient;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_xml_body_into_inner() {
+        let form = XmlBody::<String>("xml_body".to_string());
+        assert_eq!(form.into_inner(), "xml_body".to_string());
+    }
+
+    #[test]
+    fn test_xml_body_to_request_body() {
+        let mut components = Components::default();
+        let request_body = XmlBody::<String>::to_request_body(&mut components);
+        assert_json_eq!(
+            request_body,
+            json!({
+                "description": "Extract xml format data from request.",
+                "content": {
+                    "application/xml": {
+                        "schema": {
+                            "type": "string"
+                        }
+                    }
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_xml_body_extract() {
+        #[derive(Deserialize, Debug)]
+        struct User {
+            name: String,
+        }
+        let mut req = TestClient::post("http://127.0.0.1:5800/")
+            .body("<user><name>jobs</name></user>")
+            .add_header("content-type", "application/xml", true)
+            .build();
+        let result = XmlBody::<User>::extract(&mut req).await;
+        assert_eq!("jobs", result.unwrap().name);
+
+        let mut req = TestClient::post("http://127.0.0.1:5800/")
+            .body("<user><name>jobs</name></user>")
+            .add_header("content-type", "application/json", true)
+            .build();
+        assert!(XmlBody::<User>::extract(&mut req).await.is_err());
+    }
+}
@@ -14,9 +14,11 @@ pub mod endpoint;
 pub use endpoint::{Endpoint, EndpointArgRegister, EndpointOutRegister, EndpointRegistry};
 pub mod extract;
 mod routing;
-pub use routing::RouterExt;
+pub use routing::{RouterExt, OAPI_ENV_VAR};
+pub mod deprecation;
 /// Module for name schemas.
 pub mod naming;
+pub mod validation;
 
 cfg_feature! {
     #![feature ="swagger-ui"]
@@ -34,9 +36,15 @@ cfg_feature! {
     #![feature ="redoc"]
     pub mod redoc;
 }
+cfg_feature! {
+    #![feature ="client-gen"]
+    pub mod client_gen;
+}
 
 #[doc = include_str!("../docs/endpoint.md")]
 pub use salvo_oapi_macros::endpoint;
+#[doc = include_str!("../docs/derive_endpoint_out_register.md")]
+pub use salvo_oapi_macros::EndpointOutRegister;
 pub(crate) use salvo_oapi_macros::schema;
 #[doc = include_str!("../docs/derive_to_parameters.md")]
 pub use salvo_oapi_macros::ToParameters;
@@ -47,7 +55,7 @@ pub use salvo_oapi_macros::ToResponses;
 #[doc = include_str!("../docs/derive_to_schema.md")]
 pub use salvo_oapi_macros::ToSchema;
 
-use std::collections::{BTreeMap, HashMap, LinkedList};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList};
 use std::marker::PhantomData;
 
 use salvo_core::http::StatusError;
@@ -224,6 +232,72 @@ impl<K: ToSchema, V: ToSchema> ToSchema for indexmap::IndexMap<K, V> {
         schema!(#[inline] indexmap::IndexMap<K, V>).into()
     }
 }
+#[cfg(feature = "indexmap")]
+impl<T: ToSchema> ToSchema for indexmap::IndexSet<T> {
+    fn to_schema(components: &mut Components) -> RefOr<schema::Schema> {
+        schema::Array::new(T::to_schema(components)).unique_items(true).into()
+    }
+}
+#[cfg(feature = "chrono")]
+impl ToSchema for chrono::NaiveTime {
+    fn to_schema(_components: &mut Components) -> RefOr<schema::Schema> {
+        schema::Object::with_type(SchemaType::String)
+            .format(SchemaFormat::KnownFormat(KnownFormat::Time))
+            .into()
+    }
+}
+#[cfg(feature = "time")]
+impl ToSchema for time::Time {
+    fn to_schema(_components: &mut Components) -> RefOr<schema::Schema> {
+        schema::Object::with_type(SchemaType::String)
+            .format(SchemaFormat::KnownFormat(KnownFormat::Time))
+            .into()
+    }
+}
+#[cfg(feature = "secrecy")]
+impl<S> ToSchema for secrecy::SecretBox<S>
+where
+    S: ToSchema + secrecy::zeroize::Zeroize + ?Sized,
+{
+    fn to_schema(components: &mut Components) -> RefOr<schema::Schema> {
+        let mut schema = S::to_schema(components);
+        if let RefOr::T(schema::Schema::Object(object)) = &mut schema {
+            if object.schema_type == SchemaType::String {
+                object.format = Some(SchemaFormat::KnownFormat(KnownFormat::Password));
+            }
+        }
+        schema
+    }
+}
+
+impl ToSchema for bytes::Bytes {
+    fn to_schema(_components: &mut Components) -> RefOr<schema::Schema> {
+        schema::Object::with_type(SchemaType::String)
+            .format(SchemaFormat::KnownFormat(KnownFormat::Binary))
+            .into()
+    }
+}
+impl ToSchema for bytes::BytesMut {
+    fn to_schema(components: &mut Components) -> RefOr<schema::Schema> {
+        bytes::Bytes::to_schema(components)
+    }
+}
+impl ToSchema for salvo_core::http::ResBody {
+    fn to_schema(components: &mut Components) -> RefOr<schema::Schema> {
+        bytes::Bytes::to_schema(components)
+    }
+}
+
+impl<T: ToSchema> ToSchema for BTreeSet<T> {
+    fn to_schema(components: &mut Components) -> RefOr<schema::Schema> {
+        schema::Array::new(T::to_schema(components)).unique_items(true).into()
+    }
+}
+impl<T: ToSchema> ToSchema for HashSet<T> {
+    fn to_schema(components: &mut Components) -> RefOr<schema::Schema> {
+        schema::Array::new(T::to_schema(components)).unique_items(true).into()
+    }
+}
 
 impl<T: ToSchema> ToSchema for Vec<T> {
     fn to_schema(components: &mut Components) -> RefOr<schema::Schema> {
@@ -515,6 +589,19 @@ where
     }
 }
 
+impl<C> ToResponses for writing::Xml<C>
+where
+    C: ToSchema,
+{
+    fn to_responses(components: &mut Components) -> Responses {
+        Responses::new().response(
+            "200",
+            Response::new("Response xml format data")
+                .add_content("application/xml", Content::new(C::to_schema(components))),
+        )
+    }
+}
+
 impl ToResponses for StatusError {
     fn to_responses(components: &mut Components) -> Responses {
         let mut responses = Responses::new();
@@ -574,6 +661,60 @@ impl ToResponses for salvo_core::Error {
     }
 }
 
+/// Reusable set of the responses documented by `R`.
+///
+/// The responses are registered once in [`Components`] and operations only reference them, so a
+/// set like the standard error responses is not repeated in every operation. The response components
+/// are named after `R` and the status code, e.g. `StandardErrors_400`.
+///
+/// # Examples
+///
+/// ```
+/// use salvo_core::prelude::*;
+/// use salvo_oapi::{endpoint, SharedResponses, ToResponses, ToSchema};
+///
+/// #[derive(ToSchema)]
+/// struct ProblemDetails {
+///     title: String,
+/// }
+///
+/// #[derive(ToResponses)]
+/// enum StandardErrors {
+///     /// Bad request
+///     #[salvo(response(status_code = 400))]
+///     BadRequest(ProblemDetails),
+///     /// Internal server error
+///     #[salvo(response(status_code = 500))]
+///     Internal(ProblemDetails),
+/// }
+///
+/// #[endpoint(responses(SharedResponses::<StandardErrors>))]
+/// async fn create_user() {}
+/// ```
+pub struct SharedResponses<R>(PhantomData<R>);
+impl<R> ToResponses for SharedResponses<R>
+where
+    R: ToResponses + 'static,
+{
+    fn to_responses(components: &mut Components) -> Responses {
+        let name = naming::assign_name::<R>(naming::NameRule::Auto);
+        R::to_responses(components)
+            .into_iter()
+            .map(|(status, response)| {
+                let response = match response {
+                    RefOr::Ref(reference) => RefOr::Ref(reference),
+                    RefOr::T(response) => {
+                        let response_name = format!("{}_{}", name, status);
+                        components.responses.insert(response_name.clone(), response);
+                        RefOr::Ref(Ref::from_response_name(response_name))
+                    }
+                };
+                (status, response)
+            })
+            .collect()
+    }
+}
+
 /// This trait is implemented to document a type which represents a single response which can be
 /// referenced or reused as a component in multiple operations.
 ///
@@ -610,6 +751,18 @@ where
     }
 }
 
+impl<C> ToResponse for writing::Xml<C>
+where
+    C: ToSchema,
+{
+    fn to_response(components: &mut Components) -> RefOr<Response> {
+        let schema = <C as ToSchema>::to_schema(components);
+        Response::new("Response with xml format data")
+            .add_content("application/xml", Content::new(schema))
+            .into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_json_diff::assert_json_eq;
@@ -693,4 +846,46 @@ mod tests {
             assert_json_eq!(schema, value);
         }
     }
+
+    #[test]
+    fn test_ecosystem_schema() {
+        let mut components = Components::new();
+        assert_json_eq!(
+            bytes::Bytes::to_schema(&mut components),
+            json!({"type": "string", "format": "binary"})
+        );
+        assert_json_eq!(
+            BTreeSet::<String>::to_schema(&mut components),
+            json!({"type": "array", "items": {"type": "string"}, "uniqueItems": true})
+        );
+        #[cfg(feature = "chrono")]
+        assert_json_eq!(
+            chrono::NaiveTime::to_schema(&mut components),
+            json!({"type": "string", "format": "time"})
+        );
+        #[cfg(feature = "secrecy")]
+        assert_json_eq!(
+            secrecy::SecretString::to_schema(&mut components),
+            json!({"type": "string", "format": "password"})
+        );
+    }
+
+    #[cfg(all(feature = "chrono", feature = "time"))]
+    #[test]
+    fn test_derive_duration_schema() {
+        #[derive(ToSchema)]
+        #[allow(dead_code)]
+        struct Timeout {
+            elapsed: time::Duration,
+            limit: chrono::Duration,
+        }
+        let mut components = Components::new();
+        Timeout::to_schema(&mut components);
+        let schemas = serde_json::to_value(&components.schemas).unwrap();
+        let schema = schemas.as_object().and_then(|schemas| schemas.values().next()).unwrap();
+        assert_json_eq!(
+            schema["properties"],
+            json!({"elapsed": {"type": "string"}, "limit": {"type": "string"}})
+        );
+    }
 }
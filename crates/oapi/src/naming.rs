@@ -17,6 +17,8 @@ pub enum NameRule {
 
 static GLOBAL_NAMER: Lazy<RwLock<Box<dyn Namer>>> = Lazy::new(|| RwLock::new(Box::new(FlexNamer::new())));
 static NAME_TYPES: Lazy<RwLock<BTreeMap<String, (TypeId, &'static str)>>> = Lazy::new(Default::default);
+static OPERATION_IDS: Lazy<RwLock<BTreeMap<String, TypeId>>> = Lazy::new(Default::default);
+static MODULE_PATH_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"([^<>]*::)+").expect("Invalid regex"));
 
 /// Set global namer.
 ///
@@ -36,6 +38,7 @@ static NAME_TYPES: Lazy<RwLock<BTreeMap<String, (TypeId, &'static str)>>> = Lazy
 pub fn set_namer(namer: impl Namer) {
     *GLOBAL_NAMER.write() = Box::new(namer);
     NAME_TYPES.write().clear();
+    OPERATION_IDS.write().clear();
 }
 
 #[doc(hidden)]
@@ -53,6 +56,11 @@ pub fn set_name_type_info(name: String, type_id: TypeId, type_name: &'static str
     NAME_TYPES.write().insert(name.clone(), (type_id, type_name))
 }
 
+/// Get the type id of the endpoint handler which the operation id is assigned to.
+pub fn type_id_by_operation_id(operation_id: &str) -> Option<TypeId> {
+    OPERATION_IDS.read().get(operation_id).copied()
+}
+
 /// Assign name to type and returns the name.
 ///
 /// If the type is already named, return the existing name.
@@ -67,6 +75,21 @@ pub fn assign_name<T: 'static>(rule: NameRule) -> String {
     namer().assign_name(type_id, type_name, rule)
 }
 
+/// Assign operation id to endpoint handler type and returns the operation id.
+///
+/// If the handler already has an operation id, return the existing one.
+pub fn assign_operation_id<T: 'static>() -> String {
+    let type_id = TypeId::of::<T>();
+    for (operation_id, exist_id) in OPERATION_IDS.read().iter() {
+        if *exist_id == type_id {
+            return operation_id.clone();
+        }
+    }
+    let operation_id = namer().assign_operation_id(type_id, std::any::type_name::<T>());
+    OPERATION_IDS.write().insert(operation_id.clone(), type_id);
+    operation_id
+}
+
 /// Get the name of the type. Panic if the name is not exist.
 pub fn get_name<T: 'static>() -> String {
     let type_id = TypeId::of::<T>();
@@ -87,6 +110,124 @@ fn type_generic_part(type_name: &str) -> String {
 pub trait Namer: Sync + Send + 'static {
     /// Assign name to type.
     fn assign_name(&self, type_id: TypeId, type_name: &'static str, rule: NameRule) -> String;
+
+    /// Assign operation id to endpoint handler type.
+    ///
+    /// The handler is named like any other type by default.
+    fn assign_operation_id(&self, type_id: TypeId, type_name: &'static str) -> String {
+        self.assign_name(type_id, type_name, NameRule::Auto)
+    }
+}
+
+/// Case of the identifiers in generated names.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum NameCase {
+    /// Keep identifiers as they are written in Rust.
+    #[default]
+    Original,
+    /// `PascalCase`, e.g. `UserProfile`.
+    Pascal,
+    /// `camelCase`, e.g. `userProfile`.
+    Camel,
+    /// `snake_case`, e.g. `user_profile`.
+    Snake,
+}
+impl NameCase {
+    /// Convert an identifier to this case.
+    pub fn convert(&self, ident: &str) -> String {
+        let words = split_words(ident);
+        match self {
+            NameCase::Original => ident.to_owned(),
+            NameCase::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+            NameCase::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| {
+                    if index == 0 {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            NameCase::Snake => words
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+        }
+    }
+
+    fn convert_idents(&self, name: &str) -> String {
+        if *self == NameCase::Original {
+            return name.to_owned();
+        }
+        let mut result = String::with_capacity(name.len());
+        let mut ident = String::new();
+        for c in name.chars() {
+            if c.is_alphanumeric() || c == '_' {
+                ident.push(c);
+            } else {
+                result.push_str(&self.convert(&ident));
+                ident.clear();
+                result.push(c);
+            }
+        }
+        result.push_str(&self.convert(&ident));
+        result
+    }
+}
+
+fn split_words(ident: &str) -> Vec<&str> {
+    let chars = ident.char_indices().collect::<Vec<_>>();
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, &(offset, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if let Some(start) = start.take() {
+                words.push(&ident[start..offset]);
+            }
+            continue;
+        }
+        if let Some(word_start) = start {
+            let prev = chars[index - 1].1;
+            let next = chars.get(index + 1).map(|(_, c)| *c);
+            let boundary = c.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_uppercase() && next.is_some_and(|next| next.is_lowercase())));
+            if boundary {
+                words.push(&ident[word_start..offset]);
+                start = Some(offset);
+            }
+        } else {
+            start = Some(offset);
+        }
+    }
+    if let Some(start) = start {
+        words.push(&ident[start..]);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+/// How [`FlexNamer`] resolves a name which is already assigned to another type.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Collision {
+    /// Append an increasing number to the name, e.g. `User2`.
+    #[default]
+    Suffix,
+    /// Panic, so the duplicated names are found before the document is published.
+    Panic,
 }
 
 /// A namer that generates wordy names.
@@ -94,6 +235,11 @@ pub trait Namer: Sync + Send + 'static {
 pub struct FlexNamer {
     short_mode: bool,
     generic_delimiter: Option<(String, String)>,
+    generic_separator: Option<String>,
+    case: NameCase,
+    prefix: Option<String>,
+    collision: Collision,
+    operation_id_case: Option<NameCase>,
 }
 impl FlexNamer {
     /// Create a new FlexNamer.
@@ -112,59 +258,121 @@ impl FlexNamer {
         self.generic_delimiter = Some((open.into(), close.into()));
         self
     }
+
+    /// Set the separator between the arguments of generic types, `, ` is used by default.
+    pub fn generic_separator(mut self, separator: impl Into<String>) -> Self {
+        self.generic_separator = Some(separator.into());
+        self
+    }
+
+    /// Set the case of the identifiers in automatically generated names.
+    ///
+    /// Names given by `#[salvo(schema(name = ...))]` are kept as they are.
+    pub fn case(mut self, case: NameCase) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Set the prefix of automatically generated names.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set how a name which is already assigned to another type is resolved.
+    pub fn collision(mut self, collision: Collision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Name operations after the handler function only, converted to the given case.
+    ///
+    /// By default operation ids are generated like the names of other types.
+    pub fn operation_id_case(mut self, case: NameCase) -> Self {
+        self.operation_id_case = Some(case);
+        self
+    }
+
+    fn strip_path(&self, type_name: &str) -> String {
+        if self.short_mode {
+            MODULE_PATH_REGEX.replace_all(type_name, "").into_owned()
+        } else {
+            type_name.replace("::", ".")
+        }
+    }
+
+    fn mangle_generics(&self, mut name: String) -> String {
+        if let Some(separator) = &self.generic_separator {
+            name = name.replace(", ", separator);
+        }
+        if let Some((open, close)) = &self.generic_delimiter {
+            name = name.replace('<', open).replace('>', close);
+        }
+        name
+    }
+
+    fn resolve_collision<F>(&self, base: String, type_name: &'static str, exists: F) -> String
+    where
+        F: Fn(&str) -> Option<&'static str>,
+    {
+        let mut name = base.clone();
+        let mut count = 1;
+        while let Some(exist_name) = exists(&name) {
+            match self.collision {
+                Collision::Suffix => {
+                    count += 1;
+                    name = format!("{}{}", base, count);
+                }
+                Collision::Panic => panic!("Duplicate name `{}` for types: {}, {}", name, exist_name, type_name),
+            }
+        }
+        name
+    }
 }
 impl Namer for FlexNamer {
     fn assign_name(&self, type_id: TypeId, type_name: &'static str, rule: NameRule) -> String {
-        let name = match rule {
+        let base = match rule {
             NameRule::Auto => {
-                let mut base = if self.short_mode {
-                    let re = Regex::new(r"([^<>]*::)+").expect("Invalid regex");
-                    re.replace_all(type_name, "").to_string()
-                } else {
-                    type_name.replace("::", ".")
-                };
-                if let Some((open, close)) = &self.generic_delimiter {
-                    base = base.replace('<', open).replace('>', close);
+                let base = self.mangle_generics(self.case.convert_idents(&self.strip_path(type_name)));
+                match &self.prefix {
+                    Some(prefix) => format!("{}{}", prefix, base),
+                    None => base,
                 }
-                let mut name = base.to_string();
-                let mut count = 1;
-                while let Some(exist_id) = type_info_by_name(&name).map(|t| t.0) {
-                    if exist_id != type_id {
-                        count += 1;
-                        name = format!("{}{}", base, count);
-                    } else {
-                        break;
-                    }
-                }
-                name
             }
             NameRule::Force(force_name) => {
-                let mut base = if self.short_mode {
-                    let re = Regex::new(r"([^<>]*::)+").expect("Invalid regex");
-                    re.replace_all(type_name, "").to_string()
+                let base = if self.short_mode {
+                    self.strip_path(type_name)
                 } else {
-                    format! {"{}{}", force_name, type_generic_part(type_name).replace("::", ".")}
+                    format!("{}{}", force_name, self.strip_path(&type_generic_part(type_name)))
                 };
-                if let Some((open, close)) = &self.generic_delimiter {
-                    base = base.replace('<', open).replace('>', close).to_string();
-                }
-                let mut name = base.to_string();
-                let mut count = 1;
-                while let Some((exist_id, exist_name)) = type_info_by_name(&name) {
-                    if exist_id != type_id {
-                        count += 1;
-                        tracing::error!("Duplicate name for types: {}, {}", exist_name, type_name);
-                        name = format!("{}{}", base, count);
-                    } else {
-                        break;
-                    }
-                }
-                name.to_string()
+                self.mangle_generics(base)
             }
         };
+        let name = self.resolve_collision(base, type_name, |name| match type_info_by_name(name) {
+            Some((exist_id, exist_name)) if exist_id != type_id => {
+                if let NameRule::Force(_) = rule {
+                    tracing::error!("Duplicate name for types: {}, {}", exist_name, type_name);
+                }
+                Some(exist_name)
+            }
+            _ => None,
+        });
         set_name_type_info(name.clone(), type_id, type_name);
         name
     }
+
+    fn assign_operation_id(&self, type_id: TypeId, type_name: &'static str) -> String {
+        let Some(case) = self.operation_id_case else {
+            return self.assign_name(type_id, type_name, NameRule::Auto);
+        };
+        let path = type_name.split('<').next().unwrap_or(type_name);
+        let ident = path.rsplit("::").next().unwrap_or(path);
+        self.resolve_collision(case.convert(ident), type_name, |operation_id| {
+            type_id_by_operation_id(operation_id)
+                .filter(|exist_id| *exist_id != type_id)
+                .map(|_| "another handler")
+        })
+    }
 }
 
 mod tests {
@@ -216,4 +424,65 @@ mod tests {
         // let name = assign_name::<nest::MyString>(NameRule::Auto);
         // assert_eq!(name, "MyString2");
     }
+
+    #[test]
+    fn test_name_case() {
+        use super::*;
+
+        assert_eq!(NameCase::Pascal.convert("HTTPServer_error"), "HttpServerError");
+        assert_eq!(NameCase::Camel.convert("get_user2_profile"), "getUser2Profile");
+        assert_eq!(NameCase::Snake.convert("UserProfile"), "user_profile");
+        assert_eq!(NameCase::Original.convert("User_Profile"), "User_Profile");
+    }
+
+    #[test]
+    fn test_flex_namer_policy() {
+        use super::*;
+
+        struct UserRecord;
+        struct Page<T>(T);
+        mod other {
+            pub(crate) struct UserRecord;
+        }
+
+        let namer = FlexNamer::new()
+            .short_mode(true)
+            .case(NameCase::Snake)
+            .prefix("api_")
+            .generic_delimiter("_of_", "");
+        let name = namer.assign_name(
+            TypeId::of::<Page<UserRecord>>(),
+            std::any::type_name::<Page<UserRecord>>(),
+            NameRule::Auto,
+        );
+        assert_eq!(name, "api_page_of_user_record");
+
+        let name = namer.assign_name(
+            TypeId::of::<UserRecord>(),
+            std::any::type_name::<UserRecord>(),
+            NameRule::Auto,
+        );
+        assert_eq!(name, "api_user_record");
+        let name = namer.assign_name(
+            TypeId::of::<other::UserRecord>(),
+            std::any::type_name::<other::UserRecord>(),
+            NameRule::Auto,
+        );
+        assert_eq!(name, "api_user_record2");
+    }
+
+    #[test]
+    fn test_flex_namer_operation_id() {
+        use super::*;
+
+        #[allow(non_camel_case_types)]
+        struct list_user_records;
+
+        let namer = FlexNamer::new().operation_id_case(NameCase::Camel);
+        let operation_id = namer.assign_operation_id(
+            TypeId::of::<list_user_records>(),
+            std::any::type_name::<list_user_records>(),
+        );
+        assert_eq!(operation_id, "listUserRecords");
+    }
 }
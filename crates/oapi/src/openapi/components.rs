@@ -6,7 +6,7 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{RefOr, Response, Responses, Schema, Schemas, SecurityScheme};
+use crate::{Extensions, Link, RefOr, Response, Responses, Schema, Schemas, SecurityScheme};
 
 /// Implements [OpenAPI Components Object][components] which holds supported
 /// reusable objects.
@@ -38,6 +38,16 @@ pub struct Components {
     /// [security_scheme]: https://spec.openapis.org/oas/latest.html#security-scheme-object
     #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
     pub security_schemes: BTreeMap<String, SecurityScheme>,
+
+    /// Map of reusable [OpenAPI Link Object][link]s.
+    ///
+    /// [link]: https://spec.openapis.org/oas/latest.html#link-object
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub links: BTreeMap<String, RefOr<Link>>,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Components {
@@ -132,6 +142,12 @@ impl Components {
         self
     }
 
+    /// Add a reusable [`Link`] and returns `self`.
+    pub fn add_link<S: Into<String>, L: Into<RefOr<Link>>>(mut self, name: S, link: L) -> Self {
+        self.links.insert(name.into(), link.into());
+        self
+    }
+
     /// Moves all elements from `other` into `self`, leaving `other` empty.
     ///
     /// If a key from `other` is already present in `self`, the respective
@@ -147,10 +163,25 @@ impl Components {
             .security_schemes
             .retain(|name, _| !self.security_schemes.contains_key(name));
         self.security_schemes.append(&mut other.security_schemes);
+
+        other.links.retain(|name, _| !self.links.contains_key(name));
+        self.links.append(&mut other.links);
+
+        other.extensions.retain(|name, _| !self.extensions.contains_key(name));
+        self.extensions.append(&mut other.extensions);
     }
 
     /// Returns `true` if instance contains no elements.
     pub fn is_empty(&self) -> bool {
-        self.schemas.is_empty() && self.responses.is_empty() && self.security_schemes.is_empty()
+        self.schemas.is_empty()
+            && self.responses.is_empty()
+            && self.security_schemes.is_empty()
+            && self.links.is_empty()
+    }
+
+    /// Add a specification extension to the [`Components`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
     }
 }
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::example::Example;
-use super::{encoding::Encoding, RefOr, Schema};
+use super::{encoding::Encoding, Components, Extensions, RefOr, Schema};
 
 /// Content holds request body content or response content.
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
@@ -23,7 +23,7 @@ pub struct Content {
     /// media type and specified schema if present. [`Content::examples`] and
     /// [`Content::example`] are mutually exclusive. If both are defined `examples` will
     /// override value in `example`.
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
     pub examples: BTreeMap<String, RefOr<Example>>,
 
     /// A map between a property name and its encoding information.
@@ -36,6 +36,10 @@ pub struct Content {
     /// multipart or `application/x-www-form-urlencoded`.
     #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
     pub encoding: BTreeMap<String, Encoding>,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Content {
@@ -91,6 +95,84 @@ impl Content {
         self.encoding.insert(property_name.into(), encoding.into());
         self
     }
+
+    /// Fill [`Content::example`] from the schema when neither `example` nor `examples` is defined.
+    ///
+    /// The example of the schema is used if it has one, otherwise it is assembled from the examples
+    /// of the properties when all the required properties have one. References are resolved
+    /// from `components`.
+    pub fn fill_example(&mut self, components: &Components) {
+        if self.example.is_none() && self.examples.is_empty() {
+            self.example = schema_example(&self.schema, components, 0);
+        }
+    }
+
+    /// Add a specification extension to the [`Content`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
+}
+
+/// Limits the nesting followed when assembling an example, recursive schemas would never end.
+const MAX_EXAMPLE_DEPTH: usize = 8;
+
+fn schema_example(schema: &RefOr<Schema>, components: &Components, depth: usize) -> Option<Value> {
+    if depth > MAX_EXAMPLE_DEPTH {
+        return None;
+    }
+    let schema = match schema {
+        RefOr::T(schema) => schema,
+        RefOr::Ref(reference) => {
+            let name = reference.ref_location.strip_prefix("#/components/schemas/")?;
+            return schema_example(components.schemas.get(name)?, components, depth + 1);
+        }
+    };
+    match schema {
+        Schema::Object(object) => {
+            if object.example.is_some() || object.properties.is_empty() {
+                return object.example.clone();
+            }
+            let mut example = serde_json::Map::new();
+            for (name, property) in &object.properties {
+                if let Some(value) = schema_example(property, components, depth + 1) {
+                    example.insert(name.clone(), value);
+                } else if object.required.contains(name) {
+                    return None;
+                }
+            }
+            (!example.is_empty()).then_some(Value::Object(example))
+        }
+        Schema::Array(array) => array
+            .example
+            .clone()
+            .or_else(|| schema_example(&array.items, components, depth + 1).map(|item| Value::Array(vec![item]))),
+        Schema::OneOf(one_of) => one_of.example.clone().or_else(|| {
+            one_of
+                .items
+                .iter()
+                .find_map(|item| schema_example(item, components, depth + 1))
+        }),
+        Schema::AnyOf(any_of) => any_of.example.clone().or_else(|| {
+            any_of
+                .items
+                .iter()
+                .find_map(|item| schema_example(item, components, depth + 1))
+        }),
+        Schema::AllOf(all_of) => {
+            if all_of.example.is_some() {
+                return all_of.example.clone();
+            }
+            let mut example = serde_json::Map::new();
+            for item in &all_of.items {
+                match schema_example(item, components, depth + 1)? {
+                    Value::Object(map) => example.extend(map),
+                    _ => return None,
+                }
+            }
+            (!example.is_empty()).then_some(Value::Object(example))
+        }
+    }
 }
 
 impl From<RefOr<Schema>> for Content {
@@ -167,4 +249,42 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_fill_example() {
+        use crate::schema::{Array, Object, SchemaType};
+
+        let mut components = Components::new();
+        components.schemas.insert(
+            "Pet",
+            RefOr::T(Schema::Object(
+                Object::new()
+                    .property("id", Object::with_type(SchemaType::Integer).example(json!(1)))
+                    .required("id")
+                    .property("name", Object::with_type(SchemaType::String).example(json!("bob")))
+                    .property("age", Object::with_type(SchemaType::Integer)),
+            )),
+        );
+        components.schemas.insert(
+            "Owner",
+            RefOr::T(Schema::Object(
+                Object::new()
+                    .property("pet", crate::Ref::from_schema_name("Pet"))
+                    .property("name", Object::with_type(SchemaType::String))
+                    .required("name"),
+            )),
+        );
+
+        let mut content = Content::new(Array::new(crate::Ref::from_schema_name("Pet")));
+        content.fill_example(&components);
+        assert_eq!(content.example, Some(json!([{"id": 1, "name": "bob"}])));
+
+        let mut content = Content::new(crate::Ref::from_schema_name("Owner"));
+        content.fill_example(&components);
+        assert_eq!(content.example, None);
+
+        let mut content = Content::new(crate::Ref::from_schema_name("Pet")).example(json!({"id": 2}));
+        content.fill_example(&components);
+        assert_eq!(content.example, Some(json!({"id": 2})));
+    }
 }
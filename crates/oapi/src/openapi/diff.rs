@@ -0,0 +1,883 @@
+//! Compare two [`OpenApi`] documents and report the changes between them.
+//!
+//! The diff is meant to be used as a CI gate against accidental API breakage: the document of the
+//! released API is loaded with [`OpenApi::from_json`] and compared with the document generated by
+//! the current code.
+//!
+//! ```
+//! # use salvo_oapi::{OpenApi, PathItem, PathItemType, Operation};
+//! let released = OpenApi::new("api", "1.0.0").add_path("/users", PathItem::new(PathItemType::Get, Operation::new()));
+//! let current = OpenApi::new("api", "1.1.0");
+//!
+//! let diff = released.diff(&current);
+//! assert!(diff.has_breaking_changes());
+//! for change in diff.breaking_changes() {
+//!     println!("{change}");
+//! }
+//! ```
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+use serde_json::Value;
+
+use super::{
+    Content, OpenApi, Operation, Parameter, PathItem, PropMap, RefOr, Required, Response, Schema, SchemaFormat,
+    SchemaType,
+};
+
+/// Changes between two [`OpenApi`] documents, created by [`OpenApi::diff`].
+#[non_exhaustive]
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct ApiDiff {
+    /// All the changes found, in document order.
+    pub changes: Vec<Change>,
+}
+
+impl ApiDiff {
+    /// Returns `true` if the documents are equivalent for the compared parts.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    /// Returns `true` if any change may break existing clients.
+    pub fn has_breaking_changes(&self) -> bool {
+        self.changes.iter().any(|change| change.breaking)
+    }
+    /// Iterate over the changes which may break existing clients.
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|change| change.breaking)
+    }
+}
+
+impl Display for ApiDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A single change between two [`OpenApi`] documents.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    /// Where the change happened, for example `GET /users/{id} parameter path.id`.
+    pub location: String,
+    /// What changed.
+    pub kind: ChangeKind,
+    /// Whether the change may break existing clients.
+    pub breaking: bool,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let marker = if self.breaking { "breaking" } else { "non-breaking" };
+        write!(f, "[{marker}] {}: {}", self.location, self.kind)
+    }
+}
+
+/// Kind of a [`Change`].
+///
+/// Schemas used by requests are compared as inputs of the API and schemas used by responses as
+/// outputs, so narrowing a schema is breaking in a request while widening it is breaking in a response.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// A path was added.
+    PathAdded,
+    /// A path was removed.
+    PathRemoved,
+    /// An operation was added to an existing path.
+    OperationAdded,
+    /// An operation was removed from an existing path.
+    OperationRemoved,
+    /// A parameter was added.
+    ParameterAdded {
+        /// Whether the new parameter is required.
+        required: bool,
+    },
+    /// A parameter was removed.
+    ParameterRemoved,
+    /// An optional parameter became required.
+    ParameterRequired,
+    /// A request body was added.
+    RequestBodyAdded {
+        /// Whether the new request body is required.
+        required: bool,
+    },
+    /// The request body was removed.
+    RequestBodyRemoved,
+    /// An optional request body became required.
+    RequestBodyRequired,
+    /// A response status was added.
+    ResponseAdded,
+    /// A response status was removed.
+    ResponseRemoved,
+    /// A media type was added to a request body or a response.
+    ContentAdded,
+    /// A media type was removed from a request body or a response.
+    ContentRemoved,
+    /// A referenced schema is missing from the components of the new document.
+    SchemaRemoved,
+    /// A property was added to an object schema.
+    PropertyAdded {
+        /// Whether the new property is required.
+        required: bool,
+    },
+    /// A property was removed from an object schema.
+    PropertyRemoved,
+    /// An optional property became required.
+    PropertyRequired,
+    /// A required property became optional.
+    PropertyOptional,
+    /// The type of a schema changed.
+    TypeChanged {
+        /// The old type.
+        from: String,
+        /// The new type.
+        to: String,
+    },
+    /// The set of accepted values of a schema got smaller.
+    Narrowed(String),
+    /// The set of accepted values of a schema got larger.
+    Widened(String),
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PathAdded => f.write_str("path added"),
+            Self::PathRemoved => f.write_str("path removed"),
+            Self::OperationAdded => f.write_str("operation added"),
+            Self::OperationRemoved => f.write_str("operation removed"),
+            Self::ParameterAdded { required: true } => f.write_str("required parameter added"),
+            Self::ParameterAdded { required: false } => f.write_str("optional parameter added"),
+            Self::ParameterRemoved => f.write_str("parameter removed"),
+            Self::ParameterRequired => f.write_str("parameter became required"),
+            Self::RequestBodyAdded { required: true } => f.write_str("required request body added"),
+            Self::RequestBodyAdded { required: false } => f.write_str("optional request body added"),
+            Self::RequestBodyRemoved => f.write_str("request body removed"),
+            Self::RequestBodyRequired => f.write_str("request body became required"),
+            Self::ResponseAdded => f.write_str("response added"),
+            Self::ResponseRemoved => f.write_str("response removed"),
+            Self::ContentAdded => f.write_str("media type added"),
+            Self::ContentRemoved => f.write_str("media type removed"),
+            Self::SchemaRemoved => f.write_str("schema removed"),
+            Self::PropertyAdded { required: true } => f.write_str("required property added"),
+            Self::PropertyAdded { required: false } => f.write_str("optional property added"),
+            Self::PropertyRemoved => f.write_str("property removed"),
+            Self::PropertyRequired => f.write_str("property became required"),
+            Self::PropertyOptional => f.write_str("property became optional"),
+            Self::TypeChanged { from, to } => write!(f, "type changed from `{from}` to `{to}`"),
+            Self::Narrowed(detail) => write!(f, "narrowed: {detail}"),
+            Self::Widened(detail) => write!(f, "widened: {detail}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Direction {
+    Request,
+    Response,
+}
+
+struct Differ<'a> {
+    old: &'a OpenApi,
+    new: &'a OpenApi,
+    visited: HashSet<(String, Direction)>,
+    changes: Vec<Change>,
+}
+
+pub(super) fn diff(old: &OpenApi, new: &OpenApi) -> ApiDiff {
+    let mut differ = Differ {
+        old,
+        new,
+        visited: HashSet::new(),
+        changes: Vec::new(),
+    };
+    // Paths keep the insertion order with the `preserve-path-order` feature, sort them so the changes are stable.
+    let mut old_paths = old.paths.iter().collect::<Vec<_>>();
+    old_paths.sort_by_key(|(path, _)| *path);
+    for (path, old_item) in old_paths {
+        match new.paths.get(path) {
+            Some(new_item) => differ.path_item(path, old_item, new_item),
+            None => differ.push(path, ChangeKind::PathRemoved, true),
+        }
+    }
+    let mut new_paths = new.paths.keys().collect::<Vec<_>>();
+    new_paths.sort();
+    for path in new_paths {
+        if !old.paths.contains_key(path) {
+            differ.push(path, ChangeKind::PathAdded, false);
+        }
+    }
+    ApiDiff {
+        changes: differ.changes,
+    }
+}
+
+impl Differ<'_> {
+    fn push(&mut self, location: impl Into<String>, kind: ChangeKind, breaking: bool) {
+        self.changes.push(Change {
+            location: location.into(),
+            kind,
+            breaking,
+        });
+    }
+
+    fn path_item(&mut self, path: &str, old: &PathItem, new: &PathItem) {
+        for (method, old_operation) in old.operations.iter() {
+            let location = format!("{} {path}", method_name(method));
+            match new.operations.get(method) {
+                Some(new_operation) => self.operation(&location, old, old_operation, new, new_operation),
+                None => self.push(location, ChangeKind::OperationRemoved, true),
+            }
+        }
+        for method in new.operations.keys() {
+            if !old.operations.contains_key(method) {
+                self.push(
+                    format!("{} {path}", method_name(method)),
+                    ChangeKind::OperationAdded,
+                    false,
+                );
+            }
+        }
+    }
+
+    fn operation(
+        &mut self,
+        location: &str,
+        old_item: &PathItem,
+        old: &Operation,
+        new_item: &PathItem,
+        new: &Operation,
+    ) {
+        let old_parameters = parameters(old_item, old);
+        let new_parameters = parameters(new_item, new);
+        for old_parameter in &old_parameters {
+            let parameter_location = format!("{location} parameter {}", parameter_name(old_parameter));
+            let new_parameter = new_parameters.iter().find(|parameter| {
+                parameter.name == old_parameter.name && parameter.parameter_in == old_parameter.parameter_in
+            });
+            let Some(new_parameter) = new_parameter else {
+                self.push(parameter_location, ChangeKind::ParameterRemoved, false);
+                continue;
+            };
+            if !is_required(&old_parameter.required) && is_required(&new_parameter.required) {
+                self.push(&parameter_location, ChangeKind::ParameterRequired, true);
+            }
+            if let (Some(old_schema), Some(new_schema)) = (&old_parameter.schema, &new_parameter.schema) {
+                self.schema(&parameter_location, old_schema, new_schema, Direction::Request);
+            }
+        }
+        for new_parameter in &new_parameters {
+            let exists = old_parameters.iter().any(|parameter| {
+                parameter.name == new_parameter.name && parameter.parameter_in == new_parameter.parameter_in
+            });
+            if !exists {
+                let required = is_required(&new_parameter.required);
+                self.push(
+                    format!("{location} parameter {}", parameter_name(new_parameter)),
+                    ChangeKind::ParameterAdded { required },
+                    required,
+                );
+            }
+        }
+
+        let body_location = format!("{location} request body");
+        match (&old.request_body, &new.request_body) {
+            (Some(old_body), Some(new_body)) => {
+                let required = new_body.required == Some(Required::True);
+                if old_body.required != Some(Required::True) && required {
+                    self.push(&body_location, ChangeKind::RequestBodyRequired, true);
+                }
+                self.contents(
+                    &body_location,
+                    &old_body.contents,
+                    &new_body.contents,
+                    Direction::Request,
+                );
+            }
+            (Some(_), None) => self.push(body_location, ChangeKind::RequestBodyRemoved, false),
+            (None, Some(new_body)) => {
+                let required = new_body.required == Some(Required::True);
+                self.push(body_location, ChangeKind::RequestBodyAdded { required }, required);
+            }
+            (None, None) => {}
+        }
+
+        for (status, old_response) in old.responses.iter() {
+            let response_location = format!("{location} response {status}");
+            match new.responses.get(status) {
+                Some(new_response) => self.response(&response_location, old_response, new_response),
+                None => self.push(response_location, ChangeKind::ResponseRemoved, status.starts_with('2')),
+            }
+        }
+        for status in new.responses.keys() {
+            if !old.responses.contains_key(status) {
+                self.push(
+                    format!("{location} response {status}"),
+                    ChangeKind::ResponseAdded,
+                    false,
+                );
+            }
+        }
+    }
+
+    fn response(&mut self, location: &str, old: &RefOr<Response>, new: &RefOr<Response>) {
+        match (old, new) {
+            (RefOr::T(old), RefOr::T(new)) => {
+                self.contents(location, &old.contents, &new.contents, Direction::Response)
+            }
+            (RefOr::Ref(old), RefOr::Ref(new)) if old.ref_location == new.ref_location => {}
+            _ => self.push(
+                location,
+                ChangeKind::TypeChanged {
+                    from: response_name(old),
+                    to: response_name(new),
+                },
+                true,
+            ),
+        }
+    }
+
+    fn contents<'c>(
+        &mut self,
+        location: &str,
+        old: impl IntoIterator<Item = (&'c String, &'c Content)>,
+        new: impl IntoIterator<Item = (&'c String, &'c Content)> + Clone,
+        direction: Direction,
+    ) {
+        let old = old.into_iter().collect::<Vec<_>>();
+        for (media_type, old_content) in &old {
+            let content_location = format!("{location} {media_type}");
+            match new.clone().into_iter().find(|(name, _)| name == media_type) {
+                Some((_, new_content)) => {
+                    self.schema(&content_location, &old_content.schema, &new_content.schema, direction)
+                }
+                None => self.push(content_location, ChangeKind::ContentRemoved, true),
+            }
+        }
+        for (media_type, _) in new {
+            if !old.iter().any(|(name, _)| *name == media_type) {
+                self.push(format!("{location} {media_type}"), ChangeKind::ContentAdded, false);
+            }
+        }
+    }
+
+    fn schema(&mut self, location: &str, old: &RefOr<Schema>, new: &RefOr<Schema>, direction: Direction) {
+        match (old, new) {
+            (RefOr::Ref(old_ref), RefOr::Ref(new_ref)) if old_ref.ref_location == new_ref.ref_location => {
+                let reference = &old_ref.ref_location;
+                if !self.visited.insert((reference.clone(), direction)) {
+                    return;
+                }
+                let old_schema = resolve_schema(self.old, reference);
+                let new_schema = resolve_schema(self.new, reference);
+                match (old_schema, new_schema) {
+                    (Some(old_schema), Some(new_schema)) => self.schema(reference, old_schema, new_schema, direction),
+                    (Some(_), None) => self.push(reference.clone(), ChangeKind::SchemaRemoved, true),
+                    _ => {}
+                }
+            }
+            (RefOr::T(old), RefOr::T(new)) => self.inline_schema(location, old, new, direction),
+            _ => self.push(
+                location,
+                ChangeKind::TypeChanged {
+                    from: schema_name(old),
+                    to: schema_name(new),
+                },
+                true,
+            ),
+        }
+    }
+
+    fn inline_schema(&mut self, location: &str, old: &Schema, new: &Schema, direction: Direction) {
+        match (old, new) {
+            (Schema::Object(old), Schema::Object(new)) => {
+                if old.schema_type != new.schema_type || old.format != new.format {
+                    let from = type_name(&old.schema_type, old.format.as_ref());
+                    let to = type_name(&new.schema_type, new.format.as_ref());
+                    let breaking = match (&old.schema_type, &new.schema_type) {
+                        (SchemaType::Integer, SchemaType::Number) => direction == Direction::Response,
+                        (SchemaType::Number, SchemaType::Integer) => direction == Direction::Request,
+                        _ => true,
+                    };
+                    self.push(location, ChangeKind::TypeChanged { from, to }, breaking);
+                    return;
+                }
+                self.nullable(location, old.nullable, new.nullable, direction);
+                self.enum_values(
+                    location,
+                    old.enum_values.as_deref(),
+                    new.enum_values.as_deref(),
+                    direction,
+                );
+                self.upper_bound(location, "maxLength", old.max_length, new.max_length, direction);
+                self.lower_bound(location, "minLength", old.min_length, new.min_length, direction);
+                self.upper_bound(location, "maximum", old.maximum, new.maximum, direction);
+                self.lower_bound(location, "minimum", old.minimum, new.minimum, direction);
+                self.upper_bound(
+                    location,
+                    "exclusiveMaximum",
+                    old.exclusive_maximum,
+                    new.exclusive_maximum,
+                    direction,
+                );
+                self.lower_bound(
+                    location,
+                    "exclusiveMinimum",
+                    old.exclusive_minimum,
+                    new.exclusive_minimum,
+                    direction,
+                );
+                if old.pattern != new.pattern {
+                    let detail = match &new.pattern {
+                        Some(pattern) => format!("pattern set to `{pattern}`"),
+                        None => "pattern removed".to_owned(),
+                    };
+                    self.constraint(location, detail, new.pattern.is_some(), direction);
+                }
+                self.properties(location, old, new, direction);
+            }
+            (Schema::Array(old), Schema::Array(new)) => {
+                self.nullable(location, old.nullable, new.nullable, direction);
+                self.upper_bound(location, "maxItems", old.max_items, new.max_items, direction);
+                self.lower_bound(location, "minItems", old.min_items, new.min_items, direction);
+                if old.unique_items != new.unique_items {
+                    let detail = if new.unique_items {
+                        "items must be unique"
+                    } else {
+                        "items may be duplicated"
+                    };
+                    self.constraint(location, detail.to_owned(), new.unique_items, direction);
+                }
+                self.schema(&format!("{location}[]"), &old.items, &new.items, direction);
+            }
+            (Schema::OneOf(old), Schema::OneOf(new)) => {
+                self.variants(location, "oneOf", &old.items, &new.items, direction)
+            }
+            (Schema::AnyOf(old), Schema::AnyOf(new)) => {
+                self.variants(location, "anyOf", &old.items, &new.items, direction)
+            }
+            (Schema::AllOf(old), Schema::AllOf(new)) => {
+                // More `allOf` items means more constraints.
+                self.variants(location, "allOf", &new.items, &old.items, direction)
+            }
+            _ => self.push(
+                location,
+                ChangeKind::TypeChanged {
+                    from: inline_schema_name(old),
+                    to: inline_schema_name(new),
+                },
+                true,
+            ),
+        }
+    }
+
+    fn properties(&mut self, location: &str, old: &super::Object, new: &super::Object, direction: Direction) {
+        let old_properties: &PropMap<String, RefOr<Schema>> = &old.properties;
+        for (name, old_property) in old_properties.iter() {
+            let property_location = format!("{location}.{name}");
+            let Some(new_property) = new.properties.get(name) else {
+                self.push(
+                    property_location,
+                    ChangeKind::PropertyRemoved,
+                    direction == Direction::Response,
+                );
+                continue;
+            };
+            match (old.required.contains(name), new.required.contains(name)) {
+                (false, true) => self.push(
+                    &property_location,
+                    ChangeKind::PropertyRequired,
+                    direction == Direction::Request,
+                ),
+                (true, false) => self.push(
+                    &property_location,
+                    ChangeKind::PropertyOptional,
+                    direction == Direction::Response,
+                ),
+                _ => {}
+            }
+            self.schema(&property_location, old_property, new_property, direction);
+        }
+        for name in new.properties.keys() {
+            if !old.properties.contains_key(name) {
+                let required = new.required.contains(name);
+                self.push(
+                    format!("{location}.{name}"),
+                    ChangeKind::PropertyAdded { required },
+                    required && direction == Direction::Request,
+                );
+            }
+        }
+    }
+
+    fn variants(
+        &mut self,
+        location: &str,
+        keyword: &str,
+        old: &[RefOr<Schema>],
+        new: &[RefOr<Schema>],
+        direction: Direction,
+    ) {
+        if new.len() < old.len() {
+            self.constraint(location, format!("{keyword} variants removed"), true, direction);
+        } else if new.len() > old.len() {
+            self.constraint(location, format!("{keyword} variants added"), false, direction);
+        }
+        for (index, (old, new)) in old.iter().zip(new).enumerate() {
+            self.schema(&format!("{location}/{keyword}/{index}"), old, new, direction);
+        }
+    }
+
+    fn nullable(&mut self, location: &str, old: bool, new: bool, direction: Direction) {
+        if old != new {
+            let detail = if new { "became nullable" } else { "no longer nullable" };
+            self.constraint(location, detail.to_owned(), !new, direction);
+        }
+    }
+
+    fn enum_values(&mut self, location: &str, old: Option<&[Value]>, new: Option<&[Value]>, direction: Direction) {
+        match (old, new) {
+            (Some(old), Some(new)) => {
+                let removed = old.iter().filter(|value| !new.contains(value)).collect::<Vec<_>>();
+                let added = new.iter().filter(|value| !old.contains(value)).collect::<Vec<_>>();
+                if !removed.is_empty() {
+                    self.constraint(
+                        location,
+                        format!("enum values removed: {}", values(&removed)),
+                        true,
+                        direction,
+                    );
+                }
+                if !added.is_empty() {
+                    self.constraint(
+                        location,
+                        format!("enum values added: {}", values(&added)),
+                        false,
+                        direction,
+                    );
+                }
+            }
+            (None, Some(new)) => {
+                let new = new.iter().collect::<Vec<_>>();
+                self.constraint(
+                    location,
+                    format!("restricted to enum values: {}", values(&new)),
+                    true,
+                    direction,
+                );
+            }
+            (Some(_), None) => self.constraint(location, "enum restriction removed".to_owned(), false, direction),
+            (None, None) => {}
+        }
+    }
+
+    fn upper_bound<T: PartialOrd + Display>(
+        &mut self,
+        location: &str,
+        keyword: &str,
+        old: Option<T>,
+        new: Option<T>,
+        direction: Direction,
+    ) {
+        let narrowed = match (&old, &new) {
+            (None, Some(_)) => true,
+            (Some(_), None) => false,
+            (Some(old), Some(new)) if new < old => true,
+            (Some(old), Some(new)) if new > old => false,
+            _ => return,
+        };
+        self.constraint(location, bound_detail(keyword, old, new), narrowed, direction);
+    }
+
+    fn lower_bound<T: PartialOrd + Display>(
+        &mut self,
+        location: &str,
+        keyword: &str,
+        old: Option<T>,
+        new: Option<T>,
+        direction: Direction,
+    ) {
+        let narrowed = match (&old, &new) {
+            (None, Some(_)) => true,
+            (Some(_), None) => false,
+            (Some(old), Some(new)) if new > old => true,
+            (Some(old), Some(new)) if new < old => false,
+            _ => return,
+        };
+        self.constraint(location, bound_detail(keyword, old, new), narrowed, direction);
+    }
+
+    fn constraint(&mut self, location: &str, detail: String, narrowed: bool, direction: Direction) {
+        if narrowed {
+            self.push(location, ChangeKind::Narrowed(detail), direction == Direction::Request);
+        } else {
+            self.push(location, ChangeKind::Widened(detail), direction == Direction::Response);
+        }
+    }
+}
+
+fn parameters<'a>(item: &'a PathItem, operation: &'a Operation) -> Vec<&'a Parameter> {
+    let mut parameters = operation.parameters.0.iter().collect::<Vec<_>>();
+    for parameter in item.parameters.0.iter() {
+        if !operation.parameters.contains(&parameter.name, parameter.parameter_in) {
+            parameters.push(parameter);
+        }
+    }
+    parameters
+}
+
+fn resolve_schema<'a>(openapi: &'a OpenApi, reference: &str) -> Option<&'a RefOr<Schema>> {
+    reference
+        .strip_prefix("#/components/schemas/")
+        .and_then(|name| openapi.components.schemas.get(name))
+}
+
+fn is_required(required: &Required) -> bool {
+    matches!(required, Required::True)
+}
+
+fn method_name(method: &super::PathItemType) -> String {
+    serde_json::to_value(method)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_uppercase))
+        .unwrap_or_default()
+}
+
+fn parameter_name(parameter: &Parameter) -> String {
+    let parameter_in = serde_json::to_value(parameter.parameter_in)
+        .ok()
+        .and_then(|value| value.as_str().map(ToOwned::to_owned))
+        .unwrap_or_default();
+    format!("{parameter_in}.{}", parameter.name)
+}
+
+fn response_name(response: &RefOr<Response>) -> String {
+    match response {
+        RefOr::Ref(reference) => reference.ref_location.clone(),
+        RefOr::T(_) => "inline response".to_owned(),
+    }
+}
+
+fn schema_name(schema: &RefOr<Schema>) -> String {
+    match schema {
+        RefOr::Ref(reference) => reference.ref_location.clone(),
+        RefOr::T(schema) => inline_schema_name(schema),
+    }
+}
+
+fn inline_schema_name(schema: &Schema) -> String {
+    match schema {
+        Schema::Object(object) => type_name(&object.schema_type, object.format.as_ref()),
+        Schema::Array(_) => "array".to_owned(),
+        Schema::OneOf(_) => "oneOf".to_owned(),
+        Schema::AllOf(_) => "allOf".to_owned(),
+        Schema::AnyOf(_) => "anyOf".to_owned(),
+    }
+}
+
+fn type_name(schema_type: &SchemaType, format: Option<&SchemaFormat>) -> String {
+    let name = |value: serde_json::Result<Value>| {
+        value
+            .ok()
+            .and_then(|value| value.as_str().map(ToOwned::to_owned))
+            .unwrap_or_default()
+    };
+    match format {
+        Some(format) => format!(
+            "{}({})",
+            name(serde_json::to_value(schema_type)),
+            name(serde_json::to_value(format))
+        ),
+        None => name(serde_json::to_value(schema_type)),
+    }
+}
+
+fn bound_detail<T: Display>(keyword: &str, old: Option<T>, new: Option<T>) -> String {
+    match (old, new) {
+        (Some(old), Some(new)) => format!("{keyword} changed from {old} to {new}"),
+        (None, Some(new)) => format!("{keyword} set to {new}"),
+        (Some(old), None) => format!("{keyword} {old} removed"),
+        (None, None) => String::new(),
+    }
+}
+
+fn values(values: &[&Value]) -> String {
+    values.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Array, Object, ParameterIn, PathItemType, RequestBody};
+
+    fn api(operation: Operation) -> OpenApi {
+        OpenApi::new("api", "1.0.0").add_path("/users/{id}", PathItem::new(PathItemType::Post, operation))
+    }
+
+    fn kinds(diff: &ApiDiff) -> Vec<(&str, &ChangeKind, bool)> {
+        diff.changes
+            .iter()
+            .map(|change| (change.location.as_str(), &change.kind, change.breaking))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_same_document() {
+        let openapi = api(Operation::new().add_parameter(Parameter::new("id").required(Required::True)));
+        assert!(openapi.diff(&openapi.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_removed_paths_and_operations() {
+        let old = OpenApi::new("api", "1.0.0")
+            .add_path("/users", PathItem::new(PathItemType::Get, Operation::new()))
+            .add_path("/users", PathItem::new(PathItemType::Post, Operation::new()))
+            .add_path("/pets", PathItem::new(PathItemType::Get, Operation::new()));
+        let new = OpenApi::new("api", "1.0.0")
+            .add_path("/users", PathItem::new(PathItemType::Get, Operation::new()))
+            .add_path("/users", PathItem::new(PathItemType::Put, Operation::new()))
+            .add_path("/stores", PathItem::new(PathItemType::Get, Operation::new()));
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            kinds(&diff),
+            vec![
+                ("/pets", &ChangeKind::PathRemoved, true),
+                ("POST /users", &ChangeKind::OperationRemoved, true),
+                ("PUT /users", &ChangeKind::OperationAdded, false),
+                ("/stores", &ChangeKind::PathAdded, false),
+            ]
+        );
+        assert_eq!(diff.breaking_changes().count(), 2);
+    }
+
+    #[test]
+    fn test_diff_parameters_and_request_body() {
+        let old = api(Operation::new()
+            .add_parameter(Parameter::new("id").required(Required::True))
+            .add_parameter(Parameter::new("page").parameter_in(ParameterIn::Query))
+            .request_body(RequestBody::new().add_content("application/json", Content::new(Object::new()))));
+        let new = api(Operation::new()
+            .add_parameter(Parameter::new("id").required(Required::True))
+            .add_parameter(
+                Parameter::new("page")
+                    .parameter_in(ParameterIn::Query)
+                    .required(Required::True),
+            )
+            .add_parameter(Parameter::new("x-tenant").parameter_in(ParameterIn::Header))
+            .request_body(
+                RequestBody::new()
+                    .add_content("application/json", Content::new(Object::new()))
+                    .required(Required::True),
+            ));
+
+        assert_eq!(
+            kinds(&old.diff(&new)),
+            vec![
+                (
+                    "POST /users/{id} parameter query.page",
+                    &ChangeKind::ParameterRequired,
+                    true
+                ),
+                (
+                    "POST /users/{id} parameter header.x-tenant",
+                    &ChangeKind::ParameterAdded { required: false },
+                    false
+                ),
+                ("POST /users/{id} request body", &ChangeKind::RequestBodyRequired, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_schema_direction() {
+        let old_schema = Object::new()
+            .property("name", Object::with_type(SchemaType::String).max_length(20))
+            .property("age", Object::with_type(SchemaType::Integer))
+            .required("name");
+        let new_schema = Object::new()
+            .property("name", Object::with_type(SchemaType::String).max_length(10))
+            .property("age", Object::with_type(SchemaType::Number))
+            .property("email", Object::with_type(SchemaType::String))
+            .required("name")
+            .required("email");
+        let request = |schema: Object| {
+            api(Operation::new().request_body(RequestBody::new().add_content("application/json", Content::new(schema))))
+        };
+        let response = |schema: Object| {
+            api(Operation::new().add_response(
+                "200",
+                Response::new("ok").add_content("application/json", Content::new(schema)),
+            ))
+        };
+
+        let location = "POST /users/{id} request body application/json";
+        let mut changes = request(old_schema.clone()).diff(&request(new_schema.clone())).changes;
+        changes.sort_by(|a, b| a.location.cmp(&b.location));
+        assert_eq!(
+            changes,
+            vec![
+                Change {
+                    location: format!("{location}.age"),
+                    kind: ChangeKind::TypeChanged {
+                        from: "integer".into(),
+                        to: "number".into()
+                    },
+                    breaking: false,
+                },
+                Change {
+                    location: format!("{location}.email"),
+                    kind: ChangeKind::PropertyAdded { required: true },
+                    breaking: true,
+                },
+                Change {
+                    location: format!("{location}.name"),
+                    kind: ChangeKind::Narrowed("maxLength changed from 20 to 10".into()),
+                    breaking: true,
+                },
+            ]
+        );
+
+        let diff = response(old_schema).diff(&response(new_schema));
+        assert_eq!(
+            diff.breaking_changes()
+                .map(|change| (change.location.as_str(), &change.kind))
+                .collect::<Vec<_>>(),
+            vec![(
+                "POST /users/{id} response 200 application/json.age",
+                &ChangeKind::TypeChanged {
+                    from: "integer".into(),
+                    to: "number".into()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diff_referenced_schema() {
+        let old = api(Operation::new().request_body(
+            RequestBody::new().add_content("application/json", Content::new(crate::Ref::from_schema_name("User"))),
+        ))
+        .add_schema(
+            "User",
+            Object::new().property("tags", Array::new(Object::with_type(SchemaType::String))),
+        );
+        let new = old.clone().add_schema(
+            "User",
+            Object::new().property("tags", Array::new(Object::with_type(SchemaType::String)).max_items(3)),
+        );
+
+        assert_eq!(
+            kinds(&old.diff(&new)),
+            vec![(
+                "#/components/schemas/User.tags",
+                &ChangeKind::Narrowed("maxItems set to 3".into()),
+                true
+            )]
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! Export an [`OpenApi`] document as one or more files.
+//!
+//! Large documents break some downstream tools, [`OpenApi::export`] can write the document split into
+//! multiple files, one per path under `paths/` and one per schema under `components/schemas/`, which are
+//! linked together with relative `$ref`s. It can also inline all schema references into a single
+//! self-contained file.
+//!
+//! ```no_run
+//! # use salvo_oapi::OpenApi;
+//! use salvo_oapi::export::{ExportLayout, ExportOptions};
+//!
+//! let doc = OpenApi::new("api", "1.0.0");
+//! let export = doc.export(&ExportOptions::new().layout(ExportLayout::Split)).unwrap();
+//! export.write_to("openapi").unwrap();
+//! ```
+use std::collections::{BTreeMap, HashSet};
+use std::io::Result as IoResult;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use super::OpenApi;
+
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+
+/// How the document is laid out in files.
+#[non_exhaustive]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum ExportLayout {
+    /// A single file, the same as [`OpenApi::to_pretty_json`].
+    #[default]
+    Single,
+    /// The main file with a file per path under `paths/` and a file per schema under `components/schemas/`.
+    Split,
+    /// A single file where the references to schemas are replaced by the schemas.
+    ///
+    /// Recursive schemas can not be inlined, they are kept in `components/schemas`.
+    Inlined,
+}
+
+/// Format of the exported files.
+#[non_exhaustive]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Pretty printed JSON files.
+    #[default]
+    Json,
+    /// YAML files.
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            #[cfg(feature = "yaml")]
+            Self::Yaml => "yaml",
+        }
+    }
+
+    fn render(&self, value: &Value) -> Result<String, serde_json::Error> {
+        match self {
+            Self::Json => serde_json::to_string_pretty(value),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => serde_yaml::to_string(value).map_err(<serde_json::Error as serde::ser::Error>::custom),
+        }
+    }
+}
+
+/// Options of [`OpenApi::export`].
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    layout: ExportLayout,
+    format: ExportFormat,
+    main_file: String,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExportOptions {
+    /// Create new `ExportOptions` which export a single JSON file named `openapi.json`.
+    pub fn new() -> Self {
+        Self {
+            layout: ExportLayout::default(),
+            format: ExportFormat::default(),
+            main_file: "openapi".into(),
+        }
+    }
+
+    /// Sets the layout of the exported files.
+    pub fn layout(mut self, layout: ExportLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets the format of the exported files.
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the name of the main file without extension, default is `openapi`.
+    pub fn main_file(mut self, main_file: impl Into<String>) -> Self {
+        self.main_file = main_file.into();
+        self
+    }
+}
+
+/// Files of an exported [`OpenApi`] document, created by [`OpenApi::export`].
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct Export {
+    /// Name of the main file which is the entry of the document.
+    pub main_file: String,
+    /// Contents of the files by their paths relative to the output directory, separated by `/`.
+    pub files: BTreeMap<String, String>,
+}
+
+impl Export {
+    /// Get the content of the main file.
+    pub fn main(&self) -> &str {
+        self.files.get(&self.main_file).map(String::as_str).unwrap_or_default()
+    }
+
+    /// Write all files into `dir`, creating the directories as needed.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> IoResult<()> {
+        let dir = dir.as_ref();
+        for (name, content) in &self.files {
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn export(openapi: &OpenApi, options: &ExportOptions) -> Result<Export, serde_json::Error> {
+    let mut document = serde_json::to_value(openapi)?;
+    let ext = options.format.extension();
+    let main_file = format!("{}.{ext}", options.main_file);
+    let mut files = BTreeMap::new();
+    match options.layout {
+        ExportLayout::Single => {}
+        ExportLayout::Inlined => inline_schemas(&mut document),
+        ExportLayout::Split => {
+            let mut names = HashSet::new();
+            let schemas = document
+                .pointer_mut("/components/schemas")
+                .and_then(Value::as_object_mut)
+                .map(std::mem::take)
+                .unwrap_or_default();
+            let mut schema_files = BTreeMap::new();
+            for name in schemas.keys() {
+                let file = unique_file_name("components/schemas", &file_stem(name), ext, &mut names);
+                schema_files.insert(name.clone(), file);
+            }
+            let main_refs = schema_files
+                .iter()
+                .map(|(name, file)| (name.clone(), ref_value(relative_path(&main_file, file))))
+                .collect::<Map<_, _>>();
+            for (name, mut schema) in schemas {
+                let file = &schema_files[&name];
+                rewrite_refs(&mut schema, file, &main_file, &schema_files);
+                files.insert(file.clone(), schema);
+            }
+            if let Some(components) = document.get_mut("components").and_then(Value::as_object_mut) {
+                if main_refs.is_empty() {
+                    components.remove("schemas");
+                } else {
+                    components.insert("schemas".into(), Value::Object(main_refs));
+                }
+            }
+
+            if let Some(paths) = document.get_mut("paths").and_then(Value::as_object_mut) {
+                for (path, item) in paths.iter_mut() {
+                    let stem = match file_stem(path.trim_matches('/')) {
+                        stem if stem.is_empty() => "root".to_owned(),
+                        stem => stem,
+                    };
+                    let file = unique_file_name("paths", &stem, ext, &mut names);
+                    let mut item = std::mem::replace(item, ref_value(relative_path(&main_file, &file)));
+                    rewrite_refs(&mut item, &file, &main_file, &schema_files);
+                    files.insert(file, item);
+                }
+            }
+            // The references to the moved paths and schemas are already relative file paths, only the references
+            // left in the other parts of the main file are rewritten.
+            rewrite_refs(&mut document, &main_file, &main_file, &schema_files);
+        }
+    }
+    files.insert(main_file.clone(), document);
+
+    let files = files
+        .into_iter()
+        .map(|(name, value)| Ok((name, options.format.render(&value)?)))
+        .collect::<Result<_, serde_json::Error>>()?;
+    Ok(Export { main_file, files })
+}
+
+fn ref_value(target: String) -> Value {
+    let mut map = Map::new();
+    map.insert("$ref".into(), Value::String(target));
+    Value::Object(map)
+}
+
+/// Replace characters which are not safe in file names.
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '{' | '}') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn unique_file_name(dir: &str, stem: &str, ext: &str, names: &mut HashSet<String>) -> String {
+    let mut name = format!("{dir}/{stem}.{ext}");
+    let mut index = 1;
+    while !names.insert(name.clone()) {
+        index += 1;
+        name = format!("{dir}/{stem}_{index}.{ext}");
+    }
+    name
+}
+
+/// Get the path of `target` relative to the directory of `from`, both are relative to the output directory.
+fn relative_path(from: &str, target: &str) -> String {
+    let from_dirs = from.split('/').rev().skip(1).collect::<Vec<_>>();
+    let mut from_dirs = from_dirs.into_iter().rev().peekable();
+    let mut target_parts = target.split('/').peekable();
+    while let (Some(from), Some(target)) = (from_dirs.peek(), target_parts.peek()) {
+        if from != target {
+            break;
+        }
+        from_dirs.next();
+        target_parts.next();
+    }
+    from_dirs
+        .map(|_| "..")
+        .chain(target_parts)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Rewrite the local `$ref`s in a value moved to `file`, so they point to the schema files or to the main file.
+fn rewrite_refs(value: &mut Value, file: &str, main_file: &str, schema_files: &BTreeMap<String, String>) {
+    match value {
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| rewrite_refs(item, file, main_file, schema_files)),
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                match item {
+                    Value::String(target) if key == "$ref" && target.starts_with('#') => {
+                        *target = match target
+                            .strip_prefix(SCHEMA_REF_PREFIX)
+                            .and_then(|name| schema_files.get(name))
+                        {
+                            Some(schema_file) => relative_path(file, schema_file),
+                            None if file == main_file => continue,
+                            None => format!("{}{target}", relative_path(file, main_file)),
+                        };
+                    }
+                    _ => rewrite_refs(item, file, main_file, schema_files),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace the references to schemas by the schemas, and remove the schemas which are not referenced any more.
+fn inline_schemas(document: &mut Value) {
+    let Some(schemas) = document
+        .pointer_mut("/components/schemas")
+        .and_then(Value::as_object_mut)
+        .map(std::mem::take)
+    else {
+        return;
+    };
+    let mut inlined = Map::new();
+    for (name, schema) in &schemas {
+        let mut schema = schema.clone();
+        inline_refs(&mut schema, &schemas, &mut vec![name.as_str()]);
+        inlined.insert(name.clone(), schema);
+    }
+    inline_refs(document, &schemas, &mut vec![]);
+
+    let mut referenced = HashSet::new();
+    collect_schema_refs(document, &mut referenced);
+    inlined.retain(|name, _| referenced.contains(name));
+    let Some(document) = document.as_object_mut() else {
+        return;
+    };
+    if let Some(components) = document.get_mut("components").and_then(Value::as_object_mut) {
+        if inlined.is_empty() {
+            components.remove("schemas");
+        } else {
+            components.insert("schemas".into(), Value::Object(inlined));
+        }
+        if components.is_empty() {
+            document.remove("components");
+        }
+    }
+}
+
+/// Inline the schema references in `value`, the schemas in `stack` are being inlined and are kept as references.
+fn inline_refs<'a>(value: &mut Value, schemas: &'a Map<String, Value>, stack: &mut Vec<&'a str>) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|item| inline_refs(item, schemas, stack)),
+        Value::Object(map) => {
+            let target = map
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|target| target.strip_prefix(SCHEMA_REF_PREFIX))
+                .and_then(|name| schemas.get_key_value(name));
+            match target {
+                Some((name, schema)) if !stack.contains(&name.as_str()) => {
+                    let mut schema = schema.clone();
+                    stack.push(name);
+                    inline_refs(&mut schema, schemas, stack);
+                    stack.pop();
+                    *value = schema;
+                }
+                Some(_) => {}
+                None => map.values_mut().for_each(|item| inline_refs(item, schemas, stack)),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_schema_refs(value: &Value, names: &mut HashSet<String>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_schema_refs(item, names)),
+        Value::Object(map) => {
+            for (key, item) in map {
+                match item {
+                    Value::String(target) if key == "$ref" => {
+                        if let Some(name) = target.strip_prefix(SCHEMA_REF_PREFIX) {
+                            names.insert(name.to_owned());
+                        }
+                    }
+                    _ => collect_schema_refs(item, names),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Content, Object, Operation, PathItem, PathItemType, Ref, Response, SchemaType};
+
+    fn api() -> OpenApi {
+        OpenApi::new("api", "1.0.0")
+            .add_schema(
+                "Pet",
+                Object::new()
+                    .property("name", Object::with_type(SchemaType::String))
+                    .property("owner", Ref::from_schema_name("User")),
+            )
+            .add_schema(
+                "User",
+                Object::new().property("name", Object::with_type(SchemaType::String)),
+            )
+            .add_path(
+                "/pets/{id}",
+                PathItem::new(
+                    PathItemType::Get,
+                    Operation::new().add_response(
+                        "200",
+                        Response::new("pet")
+                            .add_content("application/json", Content::new(Ref::from_schema_name("Pet"))),
+                    ),
+                ),
+            )
+    }
+
+    fn parse(export: &Export, file: &str) -> Value {
+        serde_json::from_str(&export.files[file]).unwrap()
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(relative_path("openapi.json", "paths/a.json"), "paths/a.json");
+        assert_eq!(relative_path("paths/a.json", "openapi.json"), "../openapi.json");
+        assert_eq!(
+            relative_path("paths/a.json", "components/schemas/Pet.json"),
+            "../components/schemas/Pet.json"
+        );
+        assert_eq!(
+            relative_path("components/schemas/Pet.json", "components/schemas/User.json"),
+            "User.json"
+        );
+    }
+
+    #[test]
+    fn test_export_single() {
+        let doc = api();
+        let export = doc.export(&ExportOptions::new()).unwrap();
+        assert_eq!(export.files.len(), 1);
+        assert_eq!(parse(&export, "openapi.json"), serde_json::to_value(&doc).unwrap());
+    }
+
+    #[test]
+    fn test_export_split() {
+        let export = api().export(&ExportOptions::new().layout(ExportLayout::Split)).unwrap();
+        assert_eq!(
+            export.files.keys().collect::<Vec<_>>(),
+            [
+                "components/schemas/Pet.json",
+                "components/schemas/User.json",
+                "openapi.json",
+                "paths/pets_{id}.json"
+            ]
+        );
+
+        let main = parse(&export, "openapi.json");
+        assert_eq!(main["paths"]["/pets/{id}"], json!({"$ref": "paths/pets_{id}.json"}));
+        assert_eq!(
+            main["components"]["schemas"]["Pet"],
+            json!({"$ref": "components/schemas/Pet.json"})
+        );
+        let path = parse(&export, "paths/pets_{id}.json");
+        assert_eq!(
+            path["get"]["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({"$ref": "../components/schemas/Pet.json"})
+        );
+        let pet = parse(&export, "components/schemas/Pet.json");
+        assert_eq!(pet["properties"]["owner"], json!({"$ref": "User.json"}));
+    }
+
+    #[test]
+    fn test_export_inlined() {
+        let doc = api().add_schema("Node", Object::new().property("next", Ref::from_schema_name("Node")));
+        let doc = doc.add_path(
+            "/nodes",
+            PathItem::new(
+                PathItemType::Get,
+                Operation::new().add_response(
+                    "200",
+                    Response::new("node").add_content("application/json", Content::new(Ref::from_schema_name("Node"))),
+                ),
+            ),
+        );
+        let export = doc.export(&ExportOptions::new().layout(ExportLayout::Inlined)).unwrap();
+        assert_eq!(export.files.len(), 1);
+
+        let main = parse(&export, "openapi.json");
+        let schema = &main["paths"]["/pets/{id}"]["get"]["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(schema["properties"]["owner"]["properties"]["name"]["type"], "string");
+        // Recursive schemas are kept as references.
+        let schema = &main["paths"]["/nodes"]["get"]["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(
+            schema["properties"]["next"],
+            json!({"$ref": "#/components/schemas/Node"})
+        );
+        assert_eq!(
+            main["components"]["schemas"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["Node"]
+        );
+    }
+}
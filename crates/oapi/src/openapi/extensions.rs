@@ -0,0 +1,133 @@
+//! Implements [OpenAPI Extensions][extensions].
+//!
+//! [extensions]: https://spec.openapis.org/oas/latest.html#specification-extensions
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+const EXTENSION_PREFIX: &str = "x-";
+
+/// Specification extensions attached to an OpenAPI object, e.g. `x-amazon-apigateway-integration`.
+///
+/// Extensions are flattened into the object holding them. Only the fields starting with `x-` are
+/// kept when deserializing.
+#[derive(Serialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct Extensions(BTreeMap<String, Value>);
+
+impl Extensions {
+    /// Construct a new empty [`Extensions`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add an extension, the `x-` prefix is added to the name if it is missing.
+    pub fn add<K: Into<String>, V: Into<Value>>(mut self, name: K, value: V) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Insert an extension, the `x-` prefix is added to the name if it is missing.
+    pub fn insert<K: Into<String>, V: Into<Value>>(&mut self, name: K, value: V) {
+        let mut name = name.into();
+        if !name.starts_with(EXTENSION_PREFIX) {
+            name.insert_str(0, EXTENSION_PREFIX);
+        }
+        self.0.insert(name, value.into());
+    }
+
+    /// Check if there is no extension.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Moves all extensions from `other` into `self`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Extensions) {
+        self.0.append(&mut other.0);
+    }
+}
+
+impl Deref for Extensions {
+    type Target = BTreeMap<String, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for Extensions {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Extensions
+where
+    K: Into<String>,
+    V: Into<Value>,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut extensions = Extensions::new();
+        for (name, value) in iter {
+            extensions.insert(name, value);
+        }
+        extensions
+    }
+}
+
+impl From<Extensions> for BTreeMap<String, Value> {
+    fn from(extensions: Extensions) -> Self {
+        extensions.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Extensions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ExtensionsVisitor;
+        impl<'de> Visitor<'de> for ExtensionsVisitor {
+            type Value = Extensions;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of specification extensions")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut extensions = BTreeMap::new();
+                while let Some(name) = map.next_key::<String>()? {
+                    if name.starts_with(EXTENSION_PREFIX) {
+                        extensions.insert(name, map.next_value::<Value>()?);
+                    } else {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+                Ok(Extensions(extensions))
+            }
+        }
+        deserializer.deserialize_map(ExtensionsVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_extensions_prefix() {
+        let extensions = Extensions::new().add("codegen", true).add("x-internal", "yes");
+        assert_eq!(
+            serde_json::to_value(&extensions).unwrap(),
+            json!({"x-codegen": true, "x-internal": "yes"})
+        );
+    }
+
+    #[test]
+    fn test_extensions_deserialize() {
+        let extensions: Extensions = serde_json::from_value(json!({"x-codegen": {"a": 1}, "other": 2})).unwrap();
+        assert_eq!(extensions.len(), 1);
+        assert_eq!(extensions["x-codegen"], json!({"a": 1}));
+    }
+}
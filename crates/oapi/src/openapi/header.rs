@@ -4,7 +4,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::{Object, RefOr, Schema, SchemaType};
+use super::{Extensions, Object, RefOr, Schema, SchemaType};
 
 /// Implements [OpenAPI Header Object][header] for response headers.
 ///
@@ -18,6 +18,10 @@ pub struct Header {
     /// Additional description of the header value.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Header {
@@ -54,6 +58,12 @@ impl Header {
         self.description = Some(description.into());
         self
     }
+
+    /// Add a specification extension to the [`Header`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 impl Default for Header {
@@ -61,6 +71,7 @@ impl Default for Header {
         Self {
             description: Default::default(),
             schema: Object::with_type(SchemaType::String).into(),
+            extensions: Default::default(),
         }
     }
 }
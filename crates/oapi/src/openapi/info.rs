@@ -6,10 +6,10 @@
 //! [info]: <https://spec.openapis.org/oas/latest.html#info-object>
 //! [openapi_trait]: ../../trait.OpenApi.html
 //! [derive]: ../../derive.OpenApi.html
-use std::collections::HashMap;
-
 use serde::{Deserialize, Serialize};
 
+use super::Extensions;
+
 /// # Examples
 ///
 /// Create [`Info`]].
@@ -57,9 +57,9 @@ pub struct Info {
     /// Document version typically the API version.
     pub version: String,
 
-    /// Optional extensions "x-something"
-    #[serde(skip_serializing_if = "Option::is_none", flatten)]
-    pub extensions: Option<HashMap<String, serde_json::Value>>,
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Info {
@@ -116,6 +116,12 @@ impl Info {
         self.license = Some(license);
         self
     }
+
+    /// Add a specification extension to the [`Info`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 /// OpenAPI [Contact][contact] information of the API.
@@ -0,0 +1,157 @@
+//! Implements [OpenAPI Link Object][link] types.
+//!
+//! [link]: https://spec.openapis.org/oas/latest.html#link-object
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Extensions, Server};
+use crate::{Ref, RefOr};
+
+/// Implements [OpenAPI Link Object][link].
+///
+/// A link is a relationship between a response and another operation, the values of the parameters of the
+/// operation are taken from the response with runtime expressions such as `$response.body#/id`.
+///
+/// [link]: https://spec.openapis.org/oas/latest.html#link-object
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Link {
+    /// Relative or absolute URI reference to the target operation, mutually exclusive with `operation_id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_ref: Option<String>,
+
+    /// Operation id of the target operation, mutually exclusive with `operation_ref`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+
+    /// Map of parameter names of the target operation to constants or runtime expressions.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub parameters: BTreeMap<String, serde_json::Value>,
+
+    /// Constant or runtime expression used as the request body of the target operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<serde_json::Value>,
+
+    /// Description of the link. Description supports markdown syntax.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// [`Server`] of the target operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<Server>,
+
+    /// Runtime expression of the parameter of the target operation which is not named yet.
+    ///
+    /// It is moved to `parameters` under the name of the last path parameter of the target operation, or of its
+    /// first required parameter, when the router is merged into the [`OpenApi`](crate::OpenApi).
+    #[serde(skip)]
+    pub auto_parameter: Option<String>,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
+}
+
+impl Link {
+    /// Construct a new empty [`Link`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Construct a new [`Link`] to the operation with `operation_id`, whose parameter is `expression`.
+    ///
+    /// The parameter is named after the target operation when the router is merged into the
+    /// [`OpenApi`](crate::OpenApi), see [`Link::auto_parameter`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_oapi::Link;
+    /// let link = Link::to_operation("get_user", "$response.body#/id");
+    /// ```
+    pub fn to_operation<I: Into<String>, E: Into<String>>(operation_id: I, expression: E) -> Self {
+        Self::new().operation_id(operation_id).auto_parameter(expression)
+    }
+
+    /// Add the URI reference of the target operation.
+    pub fn operation_ref<S: Into<String>>(mut self, operation_ref: S) -> Self {
+        self.operation_ref = Some(operation_ref.into());
+        self
+    }
+
+    /// Add the operation id of the target operation.
+    pub fn operation_id<S: Into<String>>(mut self, operation_id: S) -> Self {
+        self.operation_id = Some(operation_id.into());
+        self
+    }
+
+    /// Add a parameter of the target operation with a constant or a runtime expression.
+    pub fn add_parameter<N: Into<String>, V: Into<serde_json::Value>>(mut self, name: N, value: V) -> Self {
+        self.parameters.insert(name.into(), value.into());
+        self
+    }
+
+    /// Add the request body of the target operation with a constant or a runtime expression.
+    pub fn request_body<V: Into<serde_json::Value>>(mut self, request_body: V) -> Self {
+        self.request_body = Some(request_body.into());
+        self
+    }
+
+    /// Add description. Description supports markdown syntax.
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add the [`Server`] of the target operation.
+    pub fn server(mut self, server: Server) -> Self {
+        self.server = Some(server);
+        self
+    }
+
+    /// Add the runtime expression of the parameter of the target operation which is named automatically.
+    pub fn auto_parameter<S: Into<String>>(mut self, expression: S) -> Self {
+        self.auto_parameter = Some(expression.into());
+        self
+    }
+
+    /// Add a specification extension to the [`Link`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
+}
+
+impl From<Ref> for RefOr<Link> {
+    fn from(r: Ref) -> Self {
+        Self::Ref(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_json_diff::assert_json_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_link_builder() {
+        let link = Link::new()
+            .operation_id("get_user")
+            .add_parameter("id", "$response.body#/id")
+            .description("The user created");
+        assert_json_eq!(
+            link,
+            json!({
+                "operationId": "get_user",
+                "parameters": {
+                    "id": "$response.body#/id"
+                },
+                "description": "The user created"
+            })
+        );
+    }
+}
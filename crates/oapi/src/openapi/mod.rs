@@ -2,11 +2,15 @@
 
 mod components;
 mod content;
+pub mod diff;
 mod encoding;
 mod example;
+pub mod export;
+mod extensions;
 mod external_docs;
 mod header;
 pub mod info;
+mod link;
 pub mod operation;
 pub mod parameter;
 pub mod path;
@@ -19,7 +23,7 @@ mod tag;
 mod xml;
 
 use crate::{routing::NormNode, Endpoint};
-use std::collections::{btree_map, BTreeSet};
+use std::collections::{btree_map, BTreeMap, BTreeSet};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -29,10 +33,15 @@ use serde::{de::Visitor, Deserialize, Serialize, Serializer};
 pub use self::{
     components::Components,
     content::Content,
+    diff::{ApiDiff, Change, ChangeKind},
+    encoding::Encoding,
     example::Example,
+    export::{Export, ExportFormat, ExportLayout, ExportOptions},
+    extensions::Extensions,
     external_docs::ExternalDocs,
     header::Header,
     info::{Contact, Info, License},
+    link::Link,
     operation::{Operation, Operations},
     parameter::{Parameter, ParameterIn, ParameterStyle, Parameters},
     path::{PathItem, PathItemType, Paths},
@@ -68,9 +77,13 @@ pub type PropMap<K, V> = indexmap::IndexMap<K, V>;
 /// just changing one thing thus you can also use the [`OpenApi::new`] to use builder to
 /// construct a new [`OpenApi`] object.
 ///
+/// The document declares OpenAPI `3.1.0` by default, but its schemas keep the OpenAPI 3.0 form for the
+/// existing consumers. Use [`OpenApi::v31`] to serialize it as OpenAPI 3.1, where nullable schemas are
+/// expressed with `type: [T, "null"]` and [`OpenApi::webhooks`] are included.
+///
 /// See more details at <https://spec.openapis.org/oas/latest.html#openapi-object>.
 #[non_exhaustive]
-#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[derive(Deserialize, Default, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct OpenApi {
     /// OpenAPI document version.
@@ -86,7 +99,7 @@ pub struct OpenApi {
     /// This is implicitly one server with `url` set to `/`.
     ///
     /// See more details at <https://spec.openapis.org/oas/latest.html#server-object>.
-    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    #[serde(skip_serializing_if = "BTreeSet::is_empty", default)]
     pub servers: BTreeSet<Server>,
 
     /// Available paths and operations for the API.
@@ -94,12 +107,18 @@ pub struct OpenApi {
     /// See more details at <https://spec.openapis.org/oas/latest.html#paths-object>.
     pub paths: Paths,
 
+    /// Incoming webhooks that may be received as part of this API, only serialized with OpenAPI 3.1.
+    ///
+    /// See more details at <https://spec.openapis.org/oas/v3.1.0#fixed-fields>.
+    #[serde(default)]
+    pub webhooks: Paths,
+
     /// Holds various reusable schemas for the OpenAPI document.
     ///
     /// Few of these elements are security schemas and object schemas.
     ///
     /// See more details at <https://spec.openapis.org/oas/latest.html#components-object>.
-    #[serde(skip_serializing_if = "Components::is_empty")]
+    #[serde(skip_serializing_if = "Components::is_empty", default)]
     pub components: Components,
 
     /// Declaration of global security mechanisms that can be used across the API. The individual operations
@@ -107,15 +126,19 @@ pub struct OpenApi {
     /// optional by adding it to the list of securities.
     ///
     /// See more details at <https://spec.openapis.org/oas/latest.html#security-requirement-object>.
-    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    #[serde(skip_serializing_if = "BTreeSet::is_empty", default)]
     pub security: BTreeSet<SecurityRequirement>,
 
     /// List of tags can be used to add additional documentation to matching tags of operations.
     ///
     /// See more details at <https://spec.openapis.org/oas/latest.html#tag-object>.
-    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    #[serde(skip_serializing_if = "BTreeSet::is_empty", default)]
     pub tags: BTreeSet<Tag>,
 
+    /// Serialize the schemas in the JSON Schema form of OpenAPI 3.1.
+    #[serde(skip)]
+    v31: bool,
+
     /// Global additional documentation reference.
     ///
     /// See more details at <https://spec.openapis.org/oas/latest.html#external-documentation-object>.
@@ -157,6 +180,13 @@ impl OpenApi {
         }
     }
 
+    /// Serialize the document as OpenAPI 3.1.
+    pub fn v31(mut self) -> Self {
+        self.openapi = OpenApiVersion::Version31;
+        self.v31 = true;
+        self
+    }
+
     /// Converts this [`OpenApi`] to JSON String. This method essentially calls [`serde_json::to_string`] method.
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
@@ -167,12 +197,52 @@ impl OpenApi {
         serde_json::to_string_pretty(self)
     }
 
+    /// Converts this [`OpenApi`] to a canonical pretty JSON String, which is stable for snapshot tests.
+    ///
+    /// The keys of all objects are sorted, so the output does not depend on the order in which paths,
+    /// schemas and properties are registered, or on the `preserve-order` features.
+    pub fn to_canonical_json(&self) -> Result<String, serde_json::Error> {
+        let value = serde_json::to_value(self)?;
+        serde_json::to_string_pretty(&canonicalize(value))
+    }
+
+    /// Parses an [`OpenApi`] from JSON String. This method essentially calls [`serde_json::from_str`] method.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
     cfg_feature! {
         #![feature ="yaml"]
         /// Converts this [`OpenApi`] to YAML String. This method essentially calls [`serde_yaml::to_string`] method.
         pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
             serde_yaml::to_string(self)
         }
+
+        /// Converts this [`OpenApi`] to a canonical YAML String with sorted keys, see [`OpenApi::to_canonical_json`].
+        pub fn to_canonical_yaml(&self) -> Result<String, serde_yaml::Error> {
+            let value = serde_json::to_value(self).map_err(<serde_yaml::Error as serde::ser::Error>::custom)?;
+            serde_yaml::to_string(&canonicalize(value))
+        }
+
+        /// Parses an [`OpenApi`] from YAML String. This method essentially calls [`serde_yaml::from_str`] method.
+        pub fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+            serde_yaml::from_str(yaml)
+        }
+    }
+
+    /// Compare this [`OpenApi`] with a newer version of the document and report the changes, marking
+    /// the ones which may break existing clients.
+    ///
+    /// Paths, operations, parameters, request bodies and responses are compared, following the schemas
+    /// referenced from them. See [`diff`] module for more details.
+    pub fn diff(&self, other: &OpenApi) -> ApiDiff {
+        diff::diff(self, other)
+    }
+
+    /// Export this [`OpenApi`] as one or more files, which can be split by paths and schemas with relative
+    /// `$ref`s or fully inlined into a single file. See [`export`] module for more details.
+    pub fn export(&self, options: &ExportOptions) -> Result<Export, serde_json::Error> {
+        export::export(self, options)
     }
 
     /// Merge `other` [`OpenApi`] consuming it and resuming it's content.
@@ -191,6 +261,7 @@ impl OpenApi {
     pub fn merge(mut self, mut other: OpenApi) -> Self {
         self.servers.append(&mut other.servers);
         self.paths.append(&mut other.paths);
+        self.webhooks.append(&mut other.webhooks);
         self.components.append(&mut other.components);
         self.security.append(&mut other.security);
         self.tags.append(&mut other.tags);
@@ -232,6 +303,18 @@ impl OpenApi {
         self
     }
 
+    /// Add [`PathItem`] describing a webhook of the API and returns `Self`.
+    ///
+    /// Webhooks are only serialized with OpenAPI 3.1.
+    pub fn add_webhook<N, I>(mut self, name: N, item: I) -> Self
+    where
+        N: Into<String>,
+        I: Into<PathItem>,
+    {
+        self.webhooks.insert(name.into(), item.into());
+        self
+    }
+
     /// Add [`Components`] to configure reusable schemas.
     pub fn components(mut self, components: impl Into<Components>) -> Self {
         self.components = components.into();
@@ -334,6 +417,31 @@ impl OpenApi {
         self
     }
 
+    /// Add a [`Link`] from the success responses of the operation `source` to the operation `target`, whose
+    /// parameter is the runtime `expression`, such as `$response.body#/id`.
+    ///
+    /// The parameter is the last path parameter of `target`, or its first required parameter. Operations are
+    /// identified by their operation id, call it after the routers are merged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_oapi::OpenApi;
+    /// let doc = OpenApi::new("users api", "1.0.0").link("create_user", "get_user", "$response.body#/id");
+    /// ```
+    pub fn link(mut self, source: &str, target: &str, expression: impl Into<String>) -> Self {
+        let link = Link::to_operation(target, expression);
+        for path_item in self.paths.values_mut() {
+            for operation in path_item.operations.values_mut() {
+                if operation.operation_id.as_deref() == Some(source) {
+                    operation.responses.add_link(target, link.clone());
+                }
+            }
+        }
+        self.resolve_links();
+        self
+    }
+
     /// Add iterator of [`Tag`]s to add additional documentation for **operations** tags.
     pub fn tags<I, T>(mut self, tags: I) -> Self
     where
@@ -361,13 +469,99 @@ impl OpenApi {
     }
 
     /// Consusmes the [`OpenApi`] and informations from a [`Router`] with base path.
-    pub fn merge_router_with_base(mut self, router: &Router, base: impl AsRef<str>) -> Self {
+    pub fn merge_router_with_base(self, router: &Router, base: impl AsRef<str>) -> Self {
+        self.merge_router_filtered_with_base(router, base, |_| true)
+    }
+
+    /// Consusmes the [`OpenApi`] and informations of the endpoints accepted by `filter` from a [`Router`].
+    ///
+    /// This allows to publish several documents from one router, e.g. for public and internal apis.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_core::Router;
+    /// # use salvo_oapi::{OpenApi, RouterExt};
+    /// let router = Router::new()
+    ///     .push(Router::with_path("pets").oapi_tag("public"))
+    ///     .push(Router::with_path("admin").oapi_group("internal"));
+    ///
+    /// let public_doc = OpenApi::new("public api", "1.0.0").merge_router_filtered(&router, |info| info.has_tag("public"));
+    /// let internal_doc = OpenApi::new("internal api", "1.0.0")
+    ///     .merge_router_filtered(&router, |info| info.in_group("internal") || info.path.starts_with("/admin"));
+    /// let router = router
+    ///     .push(public_doc.into_router("/api-doc/public.json"))
+    ///     .push(internal_doc.into_router("/api-doc/internal.json"));
+    /// ```
+    pub fn merge_router_filtered<F>(self, router: &Router, filter: F) -> Self
+    where
+        F: Fn(&EndpointInfo<'_>) -> bool,
+    {
+        self.merge_router_filtered_with_base(router, "/", filter)
+    }
+
+    /// Consusmes the [`OpenApi`] and informations of the endpoints accepted by `filter` from a [`Router`]
+    /// with base path.
+    pub fn merge_router_filtered_with_base<F>(mut self, router: &Router, base: impl AsRef<str>, filter: F) -> Self
+    where
+        F: Fn(&EndpointInfo<'_>) -> bool,
+    {
         let mut node = NormNode::new(router, Default::default());
-        self.merge_norm_node(&mut node, base.as_ref());
+        let environment = std::env::var(crate::OAPI_ENV_VAR).ok();
+        self.merge_norm_node(&mut node, base.as_ref(), environment.as_deref(), &filter);
+        self.resolve_links();
         self
     }
 
-    fn merge_norm_node(&mut self, node: &mut NormNode, base_path: &str) {
+    /// Name the automatic parameters of the links whose target operation is known, see [`Link::auto_parameter`].
+    fn resolve_links(&mut self) {
+        let mut targets = BTreeMap::new();
+        for path_item in self.paths.values() {
+            for operation in path_item.operations.values() {
+                let Some(operation_id) = &operation.operation_id else {
+                    continue;
+                };
+                let parameters = &operation.parameters.0;
+                let parameter = parameters
+                    .iter()
+                    .rev()
+                    .find(|p| p.parameter_in == ParameterIn::Path)
+                    .or_else(|| parameters.iter().find(|p| p.required == Required::True));
+                if let Some(parameter) = parameter {
+                    targets.insert(operation_id.clone(), parameter.name.clone());
+                }
+            }
+        }
+        let links = self
+            .paths
+            .values_mut()
+            .flat_map(|path_item| path_item.operations.values_mut())
+            .flat_map(|operation| operation.responses.values_mut())
+            .filter_map(|response| match response {
+                RefOr::T(response) => Some(response.links.values_mut()),
+                RefOr::Ref(_) => None,
+            })
+            .flatten();
+        for link in links {
+            let RefOr::T(link) = link else {
+                continue;
+            };
+            let Some(name) = link.operation_id.as_ref().and_then(|id| targets.get(id)) else {
+                continue;
+            };
+            if let Some(expression) = link.auto_parameter.take() {
+                link.parameters.insert(name.clone(), expression.into());
+            }
+        }
+    }
+
+    fn merge_norm_node(
+        &mut self,
+        node: &mut NormNode,
+        base_path: &str,
+        environment: Option<&str>,
+        filter: &dyn Fn(&EndpointInfo<'_>) -> bool,
+    ) {
         fn join_path(a: &str, b: &str) -> String {
             if a.is_empty() {
                 b.to_owned()
@@ -389,16 +583,31 @@ impl OpenApi {
                     .next()
             })
             .collect::<Vec<_>>();
+        if !node.metadata.is_visible(environment) {
+            return;
+        }
         if let Some(handler_type_id) = &node.handler_type_id {
-            if let Some(creator) = crate::EndpointRegistry::find(handler_type_id) {
+            let endpoint = crate::EndpointRegistry::find(handler_type_id)
+                .map(|creator| creator())
+                .filter(|endpoint| !endpoint.hidden);
+            if let Some(endpoint) = endpoint {
                 let Endpoint {
                     mut operation,
                     mut components,
                     ..
-                } = (creator)();
+                } = endpoint;
                 operation.tags.extend(node.metadata.tags.iter().cloned());
-                operation.securities.extend(node.metadata.securities.iter().cloned());
-                let methods = if let Some(method) = &node.method {
+                for security in &node.metadata.securities {
+                    if !operation.securities.contains(security) {
+                        operation.securities.push(security.clone());
+                    }
+                }
+                for to_responses in &node.metadata.responses {
+                    for (status, response) in to_responses(&mut components) {
+                        operation.responses.entry(status).or_insert(response);
+                    }
+                }
+                let mut methods = if let Some(method) = &node.method {
                     vec![*method]
                 } else {
                     vec![
@@ -408,45 +617,91 @@ impl OpenApi {
                         PathItemType::Patch,
                     ]
                 };
-                let not_exist_parameters = operation
-                    .parameters
-                    .0
-                    .iter()
-                    .filter(|p| p.parameter_in == ParameterIn::Path && !path_parameter_names.contains(&p.name))
-                    .map(|p| &p.name)
-                    .collect::<Vec<_>>();
-                if !not_exist_parameters.is_empty() {
-                    tracing::warn!(parameters = ?not_exist_parameters, path, handler_name = node.handler_type_name, "information for not exist parameters");
-                }
-                let meta_not_exist_parameters = path_parameter_names
-                    .iter()
-                    .filter(|name| {
-                        !name.starts_with('*')
-                            && !operation.parameters.0.iter().any(|parameter| {
-                                parameter.name == **name && parameter.parameter_in == ParameterIn::Path
-                            })
+                methods.retain(|method| {
+                    filter(&EndpointInfo {
+                        path: &path,
+                        method: *method,
+                        operation: &operation,
+                        groups: &node.metadata.groups,
                     })
-                    .collect::<Vec<_>>();
-                if !meta_not_exist_parameters.is_empty() {
-                    tracing::warn!(parameters = ?meta_not_exist_parameters, path, handler_name = node.handler_type_name, "parameters information not provided");
-                }
-                let path_item = self.paths.entry(path.clone()).or_default();
-                for method in methods {
-                    if let btree_map::Entry::Vacant(e) = path_item.operations.entry(method) {
-                        e.insert(operation.clone());
-                    } else {
-                        tracing::warn!("path `{}` already contains operation for method `{:?}`", path, method);
+                });
+                if !methods.is_empty() {
+                    let not_exist_parameters = operation
+                        .parameters
+                        .0
+                        .iter()
+                        .filter(|p| p.parameter_in == ParameterIn::Path && !path_parameter_names.contains(&p.name))
+                        .map(|p| &p.name)
+                        .collect::<Vec<_>>();
+                    if !not_exist_parameters.is_empty() {
+                        tracing::warn!(parameters = ?not_exist_parameters, path, handler_name = node.handler_type_name, "information for not exist parameters");
+                    }
+                    let meta_not_exist_parameters = path_parameter_names
+                        .iter()
+                        .filter(|name| {
+                            !name.starts_with('*')
+                                && !operation.parameters.0.iter().any(|parameter| {
+                                    parameter.name == **name && parameter.parameter_in == ParameterIn::Path
+                                })
+                        })
+                        .collect::<Vec<_>>();
+                    if !meta_not_exist_parameters.is_empty() {
+                        tracing::warn!(parameters = ?meta_not_exist_parameters, path, handler_name = node.handler_type_name, "parameters information not provided");
+                    }
+                    self.components.append(&mut components);
+                    let request_contents = operation
+                        .request_body
+                        .iter_mut()
+                        .flat_map(|body| body.contents.values_mut());
+                    let response_contents = operation.responses.values_mut().flat_map(|response| match response {
+                        RefOr::T(response) => Some(response.contents.values_mut()),
+                        RefOr::Ref(_) => None,
+                    });
+                    for content in request_contents.chain(response_contents.flatten()) {
+                        content.fill_example(&self.components);
+                    }
+                    let path_item = self.paths.entry(path.clone()).or_default();
+                    for method in methods {
+                        if let btree_map::Entry::Vacant(e) = path_item.operations.entry(method) {
+                            e.insert(operation.clone());
+                        } else {
+                            tracing::warn!("path `{}` already contains operation for method `{:?}`", path, method);
+                        }
                     }
                 }
-                self.components.append(&mut components);
             }
         }
         for child in &mut node.children {
-            self.merge_norm_node(child, &path);
+            self.merge_norm_node(child, &path, environment, filter);
         }
     }
 }
 
+/// Information of an endpoint given to the filter of [`OpenApi::merge_router_filtered`].
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct EndpointInfo<'a> {
+    /// Full path of the endpoint, e.g. `/pets/{id}`.
+    pub path: &'a str,
+    /// Method of the operation.
+    pub method: PathItemType,
+    /// The operation, including tags and securities inherited from the routers.
+    pub operation: &'a Operation,
+    /// Groups added to the routers with [`RouterExt::oapi_group`](crate::RouterExt::oapi_group).
+    pub groups: &'a BTreeSet<String>,
+}
+impl EndpointInfo<'_> {
+    /// Check if the operation has the tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.operation.tags.iter().any(|t| t == tag)
+    }
+
+    /// Check if the endpoint belongs to the group.
+    pub fn in_group(&self, group: &str) -> bool {
+        self.groups.contains(group)
+    }
+}
+
 #[async_trait]
 impl Handler for OpenApi {
     async fn handle(
@@ -465,20 +720,142 @@ impl Handler for OpenApi {
         res.render(writing::Text::Json(&content));
     }
 }
+impl Serialize for OpenApi {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Document<'a> {
+            openapi: &'a OpenApiVersion,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            json_schema_dialect: Option<&'a str>,
+            info: &'a Info,
+            #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+            servers: &'a BTreeSet<Server>,
+            paths: &'a Paths,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            webhooks: Option<&'a Paths>,
+            #[serde(skip_serializing_if = "Components::is_empty")]
+            components: &'a Components,
+            #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+            security: &'a BTreeSet<SecurityRequirement>,
+            #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+            tags: &'a BTreeSet<Tag>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            external_docs: Option<&'a ExternalDocs>,
+        }
+
+        let v31 = self.v31;
+        let document = Document {
+            openapi: &self.openapi,
+            json_schema_dialect: v31.then_some(JSON_SCHEMA_DIALECT),
+            info: &self.info,
+            servers: &self.servers,
+            paths: &self.paths,
+            webhooks: (v31 && !self.webhooks.is_empty()).then_some(&self.webhooks),
+            components: &self.components,
+            security: &self.security,
+            tags: &self.tags,
+            external_docs: self.external_docs.as_ref(),
+        };
+        if v31 {
+            let mut value = serde_json::to_value(document).map_err(serde::ser::Error::custom)?;
+            nullable_to_v31(&mut value);
+            value.serialize(serializer)
+        } else {
+            document.serialize(serializer)
+        }
+    }
+}
+
+/// Sort the keys of all objects in the value.
+fn canonicalize(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        Value::Object(map) => {
+            let sorted = map
+                .into_iter()
+                .map(|(key, item)| (key, canonicalize(item)))
+                .collect::<std::collections::BTreeMap<_, _>>();
+            Value::Object(sorted.into_iter().collect())
+        }
+        value => value,
+    }
+}
+
+/// The default JSON Schema dialect of OpenAPI 3.1 documents.
+const JSON_SCHEMA_DIALECT: &str = "https://spec.openapis.org/oas/3.1/dialect/base";
+
+/// Rewrite the OpenAPI 3.0 `nullable` keyword of schemas to the JSON Schema form used by OpenAPI 3.1.
+fn nullable_to_v31(value: &mut serde_json::Value) {
+    use serde_json::{json, Value};
+
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(nullable_to_v31),
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                match (key.as_str(), item) {
+                    // Examples and enum values are user data, not schemas.
+                    ("example" | "examples" | "default" | "enum", _) => {}
+                    // The keys of these maps are names, so a property named `default` is still a schema.
+                    (
+                        "properties" | "patternProperties" | "schemas" | "paths" | "webhooks" | "responses"
+                        | "requestBodies" | "parameters" | "headers" | "content" | "callbacks",
+                        Value::Object(named),
+                    ) => named.values_mut().for_each(nullable_to_v31),
+                    (_, item) => nullable_to_v31(item),
+                }
+            }
+            let Some(Value::Bool(nullable)) = map.get("nullable").cloned() else {
+                return;
+            };
+            map.remove("nullable");
+            if !nullable {
+                return;
+            }
+            if let Some(Value::String(schema_type)) = map.get("type").cloned() {
+                map.insert("type".into(), json!([schema_type, "null"]));
+                if let Some(Value::Array(values)) = map.get_mut("enum") {
+                    if !values.contains(&Value::Null) {
+                        values.push(Value::Null);
+                    }
+                }
+            } else if let Some(key) = ["oneOf", "anyOf"].into_iter().find(|key| map.contains_key(*key)) {
+                if let Some(Value::Array(schemas)) = map.get_mut(key) {
+                    schemas.push(json!({"type": "null"}));
+                }
+            } else {
+                let schema = Value::Object(std::mem::take(map));
+                map.insert("anyOf".into(), json!([schema, {"type": "null"}]));
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Represents available [OpenAPI versions][version].
 ///
 /// [version]: <https://spec.openapis.org/oas/latest.html#versions>
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
 pub enum OpenApiVersion {
-    /// Will serialize to `3.1.0` the latest from 3.0 serde.
+    /// Will serialize to `3.0.3`.
+    #[serde(rename = "3.0.3")]
+    Version30,
+    /// Will serialize to `3.1.0`.
+    #[default]
     #[serde(rename = "3.1.0")]
-    Version3,
+    Version31,
 }
 
-impl Default for OpenApiVersion {
-    fn default() -> Self {
-        Self::Version3
-    }
+impl OpenApiVersion {
+    /// Will serialize to `3.1.0`.
+    #[deprecated(note = "use `OpenApiVersion::Version31` instead")]
+    #[allow(non_upper_case_globals)]
+    pub const Version3: Self = Self::Version31;
 }
 
 /// Value used to indicate whether reusable schema, parameter or operation is deprecated.
@@ -623,14 +1000,16 @@ mod tests {
         extract::*,
         security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme},
         server::Server,
-        ToSchema,
+        RouterExt, SharedResponses, ToResponses, ToSchema, OAPI_ENV_VAR,
     };
 
     use salvo_core::{http::ResBody, prelude::*};
 
     #[test]
     fn serialize_deserialize_openapi_version_success() -> Result<(), serde_json::Error> {
-        assert_eq!(serde_json::to_value(&OpenApiVersion::Version3)?, "3.1.0");
+        assert_eq!(serde_json::to_value(&OpenApiVersion::Version30)?, "3.0.3");
+        assert_eq!(serde_json::to_value(&OpenApiVersion::Version31)?, "3.1.0");
+        assert_eq!(OpenApiVersion::default(), OpenApiVersion::Version31);
         Ok(())
     }
 
@@ -1074,6 +1453,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_openapi_from_json() -> Result<(), serde_json::Error> {
+        let doc = OpenApi::new("My api", "1.0.0")
+            .add_path(
+                "/users/{id}",
+                PathItem::new(
+                    PathItemType::Get,
+                    Operation::new()
+                        .add_parameter(Parameter::new("id").required(Required::True))
+                        .add_response("200", Response::new("ok")),
+                ),
+            )
+            .add_schema(
+                "User",
+                Object::new().property("name", Object::with_type(SchemaType::String)),
+            );
+        let serialized = doc.to_json()?;
+
+        let parsed = OpenApi::from_json(&serialized)?;
+        assert_eq!(Value::from_str(&parsed.to_json()?)?, Value::from_str(&serialized)?);
+        assert!(doc.diff(&parsed).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_openapi_to_canonical_json() -> Result<(), serde_json::Error> {
+        let schema = |names: &[&str]| {
+            names.iter().fold(Object::new(), |object, name| {
+                object.property(*name, Object::with_type(SchemaType::String))
+            })
+        };
+        let doc1 = OpenApi::new("My api", "1.0.0")
+            .add_schema("User", schema(&["name", "email"]))
+            .add_schema("Pet", schema(&["tag"]));
+        let doc2 = OpenApi::new("My api", "1.0.0")
+            .add_schema("Pet", schema(&["tag"]))
+            .add_schema("User", schema(&["email", "name"]));
+
+        let canonical = doc1.to_canonical_json()?;
+        assert_eq!(canonical, doc2.to_canonical_json()?);
+        assert!(canonical.find("\"Pet\"").unwrap() < canonical.find("\"User\"").unwrap());
+        assert!(canonical.find("\"email\"").unwrap() < canonical.find("\"name\"").unwrap());
+        Ok(())
+    }
+
     #[test]
     fn test_deprecated_from_bool() {
         assert_eq!(Deprecated::True, Deprecated::from(true));
@@ -1348,4 +1772,226 @@ mod tests {
             Value::from_str(&doc.to_json().unwrap()).unwrap()
         );
     }
+
+    #[test]
+    fn test_openapi_v31() {
+        let doc = OpenApi::new("pet api", "0.1.0")
+            .add_schema(
+                "Pet",
+                Object::new()
+                    .property("name", Object::new().schema_type(SchemaType::String).nullable(true))
+                    .property(
+                        "kind",
+                        Object::new()
+                            .schema_type(SchemaType::String)
+                            .enum_values(["cat", "dog"])
+                            .nullable(true),
+                    )
+                    .property("owner", Object::new().schema_type(SchemaType::Integer))
+                    .property(
+                        "default",
+                        Object::new()
+                            .schema_type(SchemaType::Integer)
+                            .nullable(true)
+                            .default_value(json!(1)),
+                    ),
+            )
+            .add_webhook(
+                "newPet",
+                PathItem::new(
+                    PathItemType::Post,
+                    Operation::new().add_response("200", Response::new("Return 200 if received")),
+                ),
+            );
+
+        let value = serde_json::to_value(&doc).unwrap();
+        assert_eq!(value["openapi"], "3.1.0");
+        assert!(value.get("jsonSchemaDialect").is_none());
+        assert!(value.get("webhooks").is_none());
+        assert_eq!(
+            value["components"]["schemas"]["Pet"]["properties"]["name"]["nullable"],
+            true
+        );
+
+        let v31 = serde_json::to_value(doc.v31()).unwrap();
+        assert_eq!(v31["openapi"], "3.1.0");
+        assert_eq!(v31["jsonSchemaDialect"], JSON_SCHEMA_DIALECT);
+        assert_eq!(
+            v31["components"]["schemas"]["Pet"]["properties"],
+            json!({
+                "default": {"type": ["integer", "null"], "default": 1},
+                "kind": {"type": ["string", "null"], "enum": ["cat", "dog", null]},
+                "name": {"type": ["string", "null"]},
+                "owner": {"type": "integer"}
+            })
+        );
+        assert_eq!(
+            v31["webhooks"]["newPet"]["post"]["responses"]["200"]["description"],
+            "Return 200 if received"
+        );
+    }
+
+    #[test]
+    fn test_merge_router_filtered() {
+        #[salvo_oapi::endpoint]
+        async fn list_pets() -> &'static str {
+            "pets"
+        }
+        #[salvo_oapi::endpoint]
+        async fn list_users() -> &'static str {
+            "users"
+        }
+        #[salvo_oapi::endpoint(tags("public"))]
+        async fn status() -> &'static str {
+            "ok"
+        }
+
+        let router = Router::new()
+            .push(Router::with_path("pets").oapi_tag("public").get(list_pets))
+            .push(Router::with_path("admin/users").oapi_group("internal").get(list_users))
+            .push(Router::with_path("status").get(status));
+
+        let public = OpenApi::new("public api", "0.1.0").merge_router_filtered(&router, |info| info.has_tag("public"));
+        assert_eq!(public.paths.keys().collect::<Vec<_>>(), ["/pets", "/status"]);
+
+        let internal =
+            OpenApi::new("internal api", "0.1.0").merge_router_filtered(&router, |info| info.in_group("internal"));
+        assert_eq!(internal.paths.keys().collect::<Vec<_>>(), ["/admin/users"]);
+
+        let by_prefix = OpenApi::new("admin api", "0.1.0").merge_router_filtered(&router, |info| {
+            info.path.starts_with("/admin") && info.method == PathItemType::Get
+        });
+        assert_eq!(by_prefix.paths.keys().collect::<Vec<_>>(), ["/admin/users"]);
+    }
+
+    #[test]
+    fn test_merge_router_hidden() {
+        #[salvo_oapi::endpoint]
+        async fn list_pets() -> &'static str {
+            "pets"
+        }
+        #[salvo_oapi::endpoint(hidden)]
+        async fn metrics() -> &'static str {
+            "metrics"
+        }
+        #[salvo_oapi::endpoint]
+        async fn dump() -> &'static str {
+            "dump"
+        }
+        #[salvo_oapi::endpoint]
+        async fn seed() -> &'static str {
+            "seed"
+        }
+
+        let router = Router::new()
+            .push(Router::with_path("pets").get(list_pets))
+            .push(Router::with_path("metrics").get(metrics))
+            .push(
+                Router::with_path("debug")
+                    .oapi_hidden()
+                    .push(Router::with_path("dump").get(dump)),
+            )
+            .push(
+                Router::with_path("seed")
+                    .oapi_environments(["dev", "staging"])
+                    .push(Router::new().oapi_environments(["dev"]).get(seed)),
+            );
+
+        std::env::remove_var(OAPI_ENV_VAR);
+        let doc = OpenApi::new("pet api", "0.1.0").merge_router(&router);
+        assert_eq!(doc.paths.keys().collect::<Vec<_>>(), ["/pets"]);
+
+        std::env::set_var(OAPI_ENV_VAR, "staging");
+        let doc = OpenApi::new("pet api", "0.1.0").merge_router(&router);
+        assert_eq!(doc.paths.keys().collect::<Vec<_>>(), ["/pets"]);
+
+        std::env::set_var(OAPI_ENV_VAR, "dev");
+        let doc = OpenApi::new("pet api", "0.1.0").merge_router(&router);
+        assert_eq!(doc.paths.keys().collect::<Vec<_>>(), ["/pets", "/seed"]);
+        std::env::remove_var(OAPI_ENV_VAR);
+    }
+
+    #[test]
+    fn test_merge_router_links() {
+        #[salvo_oapi::endpoint(
+            responses((status_code = 201, description = "User created")),
+            links(create_user -> get_user via "$response.body#/id")
+        )]
+        async fn create_user() {}
+        #[salvo_oapi::endpoint(responses((status_code = 200, description = "User")))]
+        async fn get_user(id: PathParam<u64>) -> String {
+            id.to_string()
+        }
+        #[salvo_oapi::endpoint(operation_id = "deleteUser", responses((status_code = 204)))]
+        async fn delete_user(id: PathParam<u64>) {
+            let _ = id;
+        }
+
+        let router = Router::with_path("users")
+            .post(create_user)
+            .push(Router::with_path("<id>").get(get_user).delete(delete_user));
+        let doc = OpenApi::new("user api", "0.1.0").merge_router(&router).link(
+            &crate::naming::assign_operation_id::<create_user>(),
+            "deleteUser",
+            "$response.body#/id",
+        );
+
+        let get_user_id = crate::naming::assign_operation_id::<get_user>();
+        let create = &doc.paths["/users"].operations[&PathItemType::Post];
+        let RefOr::T(response) = &create.responses["201"] else {
+            panic!("response should be inlined");
+        };
+        let links = serde_json::to_value(&response.links).unwrap();
+        assert_eq!(
+            links,
+            json!({
+                &get_user_id: {
+                    "operationId": &get_user_id,
+                    "parameters": {"id": "$response.body#/id"}
+                },
+                "deleteUser": {
+                    "operationId": "deleteUser",
+                    "parameters": {"id": "$response.body#/id"}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_router_shared_responses() {
+        struct StandardErrors;
+        impl ToResponses for StandardErrors {
+            fn to_responses(_components: &mut Components) -> Responses {
+                Responses::new()
+                    .response("400", Response::new("Bad request"))
+                    .response("500", Response::new("Internal server error"))
+            }
+        }
+        #[salvo_oapi::endpoint(responses((status_code = 400, description = "Invalid pet")))]
+        async fn create_pet() {}
+        #[salvo_oapi::endpoint]
+        async fn list_pets() -> &'static str {
+            "pets"
+        }
+
+        let router = Router::with_path("pets")
+            .oapi_responses::<SharedResponses<StandardErrors>>()
+            .get(list_pets)
+            .post(create_pet);
+        let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+        let name = crate::naming::assign_name::<StandardErrors>(crate::naming::NameRule::Auto);
+        assert_eq!(doc.components.responses.len(), 2);
+
+        let responses = &doc.paths["/pets"].operations[&PathItemType::Get].responses;
+        assert_eq!(
+            responses["400"],
+            RefOr::Ref(Ref::from_response_name(format!("{name}_400")))
+        );
+        let responses = &doc.paths["/pets"].operations[&PathItemType::Post].responses;
+        assert!(matches!(&responses["400"], RefOr::T(response) if response.description == "Invalid pet"));
+        assert_eq!(
+            responses["500"],
+            RefOr::Ref(Ref::from_response_name(format!("{name}_500")))
+        );
+    }
 }
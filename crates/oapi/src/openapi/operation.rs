@@ -1,21 +1,54 @@
 //! Implements [OpenAPI Operation Object][operation] types.
 //!
 //! [operation]: https://spec.openapis.org/oas/latest.html#operation-object
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::ops::{Deref, DerefMut};
 
-use serde::{Deserialize, Serialize};
+use serde::de::{IgnoredAny, IntoDeserializer, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use super::{
     request_body::RequestBody,
     response::{Response, Responses},
     Deprecated, ExternalDocs, RefOr, SecurityRequirement, Server,
 };
-use crate::{Parameter, Parameters, PathItemType, Servers};
+use crate::{Extensions, Parameter, Parameters, PathItem, PathItemType, Paths, Servers};
 
 /// Collection for save [`Operation`]s.
-#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[derive(Serialize, Default, Clone, PartialEq, Debug)]
 pub struct Operations(pub BTreeMap<PathItemType, Operation>);
+impl<'de> Deserialize<'de> for Operations {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OperationsVisitor;
+        impl<'de> Visitor<'de> for OperationsVisitor {
+            type Value = Operations;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of operations")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut operations = BTreeMap::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    // Operations are flattened into `PathItem`, skip its other fields and extensions.
+                    let item_type: Result<PathItemType, serde::de::value::Error> =
+                        PathItemType::deserialize(key.as_str().into_deserializer());
+                    match item_type {
+                        Ok(item_type) => {
+                            operations.insert(item_type, map.next_value::<Operation>()?);
+                        }
+                        Err(_) => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(Operations(operations))
+            }
+        }
+        deserializer.deserialize_map(OperationsVisitor)
+    }
+}
 impl Deref for Operations {
     type Target = BTreeMap<PathItemType, Operation>;
 
@@ -91,7 +124,7 @@ pub struct Operation {
     ///
     /// [derive_path]: ../../attr.path.html
     /// [derive_openapi]: ../../derive.OpenApi.html
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub tags: Vec<String>,
 
     /// Short summary what [`Operation`] does.
@@ -126,7 +159,7 @@ pub struct Operation {
     pub external_docs: Option<ExternalDocs>,
 
     /// List of applicable parameters for this [`Operation`].
-    #[serde(skip_serializing_if = "Parameters::is_empty")]
+    #[serde(skip_serializing_if = "Parameters::is_empty", default)]
     pub parameters: Parameters,
 
     /// Optional request body for this [`Operation`].
@@ -136,9 +169,14 @@ pub struct Operation {
     /// List of possible responses returned by the [`Operation`].
     pub responses: Responses,
 
-    /// Callback information.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub callbacks: Option<String>,
+    /// Out of band callbacks the API may initiate related to this [`Operation`], keyed by callback name.
+    ///
+    /// Each callback maps runtime expressions, such as `{$request.body#/callbackUrl}`, to the [`PathItem`]
+    /// describing the request sent to the evaluated URL.
+    ///
+    /// See more details at <https://spec.openapis.org/oas/latest.html#callback-object>.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub callbacks: BTreeMap<String, Paths>,
 
     /// Define whether the operation is deprecated or not and thus should be avoided consuming.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -149,17 +187,17 @@ pub struct Operation {
     ///
     /// Security for the [`Operation`] can be set to optional by adding empty security with
     /// [`SecurityRequirement::default`].
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
     #[serde(rename = "security")]
     pub securities: Vec<SecurityRequirement>,
 
     /// Alternative [`Server`]s for this [`Operation`].
-    #[serde(skip_serializing_if = "Servers::is_empty")]
+    #[serde(skip_serializing_if = "Servers::is_empty", default)]
     pub servers: Servers,
 
-    /// Optional extensions "x-something"
-    #[serde(skip_serializing_if = "Option::is_none", flatten)]
-    pub extensions: Option<HashMap<String, serde_json::Value>>,
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Operation {
@@ -233,6 +271,20 @@ impl Operation {
         self
     }
 
+    /// Append a callback request described by [`PathItem`] to the [`Operation`] callbacks and returns `Self`.
+    ///
+    /// * `name` is the name of the callback.
+    /// * `expression` is the runtime expression evaluated to the callback URL.
+    pub fn add_callback<N, E, I>(mut self, name: N, expression: E, item: I) -> Self
+    where
+        N: Into<String>,
+        E: Into<String>,
+        I: Into<PathItem>,
+    {
+        self.callbacks.entry(name.into()).or_default().insert(expression, item);
+        self
+    }
+
     /// Add or change deprecated status of the [`Operation`].
     pub fn deprecated<D: Into<Deprecated>>(mut self, deprecated: D) -> Self {
         self.deprecated = Some(deprecated.into());
@@ -268,6 +320,12 @@ impl Operation {
     {
         func(self)
     }
+
+    /// Add a specification extension to the [`Operation`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -277,7 +335,8 @@ mod tests {
 
     use super::{Operation, Operations};
     use crate::{
-        security::SecurityRequirement, server::Server, Deprecated, Parameter, PathItemType, RequestBody, Responses,
+        security::SecurityRequirement, server::Server, Deprecated, Parameter, PathItem, PathItemType, RequestBody,
+        Response, Responses,
     };
 
     #[test]
@@ -292,7 +351,7 @@ mod tests {
         assert!(operation.parameters.is_empty());
         assert!(operation.request_body.is_none());
         assert!(operation.responses.is_empty());
-        assert!(operation.callbacks.is_none());
+        assert!(operation.callbacks.is_empty());
         assert!(operation.deprecated.is_none());
         assert!(operation.securities.is_empty());
         assert!(operation.servers.is_empty());
@@ -348,6 +407,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_operation_callbacks() {
+        let operation = Operation::new().add_callback(
+            "onEvent",
+            "{$request.body#/callbackUrl}",
+            PathItem::new(
+                PathItemType::Post,
+                Operation::new().add_response("200", Response::new("Callback received")),
+            ),
+        );
+
+        assert_json_eq!(
+            operation,
+            json!({
+                "responses": {},
+                "callbacks": {
+                    "onEvent": {
+                        "{$request.body#/callbackUrl}": {
+                            "post": {
+                                "responses": {
+                                    "200": {
+                                        "description": "Callback received"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+        );
+    }
+
     #[test]
     fn operation_security() {
         let security_requirement1 = SecurityRequirement::new("api_oauth2_flow", ["edit:items", "read:items"]);
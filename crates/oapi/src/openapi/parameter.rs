@@ -1,12 +1,10 @@
 //! Implements [OpenAPI Parameter Object][parameter] types.
 //!
 //! [parameter]: https://spec.openapis.org/oas/latest.html#parameter-object
-use std::collections::HashMap;
-
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use super::{Deprecated, RefOr, Required, Schema};
+use super::{Deprecated, Extensions, RefOr, Required, Schema};
 
 /// Collection for OpenAPI Parameter Objects.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
@@ -140,9 +138,9 @@ pub struct Parameter {
     #[serde(skip_serializing_if = "Option::is_none")]
     example: Option<Value>,
 
-    /// Optional extensions "x-something"
-    #[serde(skip_serializing_if = "Option::is_none", flatten)]
-    pub extensions: Option<HashMap<String, serde_json::Value>>,
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Parameter {
@@ -182,7 +180,7 @@ impl Parameter {
             explode,
             allow_reserved,
             example,
-            extensions,
+            mut extensions,
         } = other;
         if name != self.name || parameter_in != self.parameter_in {
             return false;
@@ -213,16 +211,7 @@ impl Parameter {
         if let Some(example) = example {
             self.example = Some(example);
         }
-        if let Some(extensions) = extensions {
-            if self.extensions.is_none() {
-                self.extensions = Some(HashMap::new());
-            } else {
-                self.extensions
-                    .as_mut()
-                    .expect("Parameter extensions should not be None.")
-                    .extend(extensions);
-            }
-        }
+        self.extensions.append(&mut extensions);
         true
     }
 
@@ -279,6 +268,12 @@ impl Parameter {
         self.example = Some(example);
         self
     }
+
+    /// Add a specification extension to the [`Parameter`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 /// In definition of [`Parameter`].
@@ -396,14 +391,8 @@ mod tests {
             .allow_reserved(true)
             .example(Value::String("example".to_string()));
 
-        parameter1.extensions = Some(HashMap::from([(
-            "key1".to_string(),
-            Value::String("value1".to_string()),
-        )]));
-        parameter2.extensions = Some(HashMap::from([(
-            "key2".to_string(),
-            Value::String("value2".to_string()),
-        )]));
+        parameter1.extensions = Extensions::new().add("key1", "value1");
+        parameter2.extensions = Extensions::new().add("key2", "value2");
 
         assert!(parameter1.merge(parameter2));
         assert_json_eq!(
@@ -421,8 +410,8 @@ mod tests {
                 "explode": true,
                 "allowReserved": true,
                 "example": "example",
-                "key1": "value1",
-                "key2": "value2"
+                "x-key1": "value1",
+                "x-key2": "value2"
             })
         )
     }
@@ -440,10 +429,7 @@ mod tests {
             .allow_reserved(true)
             .example(Value::String("example".to_string()));
 
-        parameter2.extensions = Some(HashMap::from([(
-            "key2".to_string(),
-            Value::String("value2".to_string()),
-        )]));
+        parameter2.extensions = Extensions::new().add("key2", "value2");
 
         assert!(parameter1.merge(parameter2));
         assert_json_eq!(
@@ -461,6 +447,7 @@ mod tests {
                 "explode": true,
                 "allowReserved": true,
                 "example": "example",
+                "x-key2": "value2"
             })
         )
     }
@@ -7,7 +7,7 @@ use std::ops::{Deref, DerefMut};
 
 use serde::{Deserialize, Serialize};
 
-use super::{Operation, Operations, Parameter, Parameters, PathMap, Server, Servers};
+use super::{Extensions, Operation, Operations, Parameter, Parameters, PathMap, Server, Servers};
 
 /// Implements [OpenAPI Path Object][paths] types.
 ///
@@ -52,6 +52,7 @@ impl Paths {
                 item.servers.append(&mut value.servers);
                 item.parameters.append(&mut value.parameters);
                 item.operations.append(&mut value.operations);
+                item.extensions.append(&mut value.extensions);
             })
             .or_insert(value);
     }
@@ -97,20 +98,23 @@ pub struct PathItem {
 
     /// Alternative [`Server`] array to serve all [`Operation`]s in this [`PathItem`] overriding
     /// the global server array.
-    #[serde(skip_serializing_if = "Servers::is_empty")]
+    #[serde(skip_serializing_if = "Servers::is_empty", default)]
     pub servers: Servers,
 
     /// List of [`Parameter`]s common to all [`Operation`]s in this [`PathItem`]. Parameters cannot
     /// contain duplicate parameters. They can be overridden in [`Operation`] level but cannot be
     /// removed there.
-    #[serde(skip_serializing_if = "Parameters::is_empty")]
-    #[serde(flatten)]
+    #[serde(skip_serializing_if = "Parameters::is_empty", default)]
     pub parameters: Parameters,
 
     /// Map of operations in this [`PathItem`]. Operations can hold only one operation
     /// per [`PathItemType`].
     #[serde(flatten)]
     pub operations: Operations,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl PathItem {
@@ -131,6 +135,7 @@ impl PathItem {
         self.operations.append(&mut other.operations);
         self.servers.append(&mut other.servers);
         self.parameters.append(&mut other.parameters);
+        self.extensions.append(&mut other.extensions);
         if other.description.is_some() {
             self.description = other.description.take();
         }
@@ -171,6 +176,12 @@ impl PathItem {
         self.parameters = Parameters(parameters.into_iter().collect());
         self
     }
+
+    /// Add a specification extension to the [`PathItem`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 /// Path item operation type.
@@ -343,4 +354,26 @@ mod tests {
         let paths = Paths::new();
         assert_eq!(0, paths.len());
     }
+
+    #[test]
+    fn test_path_item_extensions() {
+        let path_item = PathItem::new(
+            PathItemType::Get,
+            Operation::new().add_extension("codegen-request-body-name", "body"),
+        )
+        .add_extension("x-amazon-apigateway-any-method", json!({"isDefaultRoute": true}));
+        let value = json!({
+            "get": {
+                "responses": {},
+                "x-codegen-request-body-name": "body"
+            },
+            "x-amazon-apigateway-any-method": {
+                "isDefaultRoute": true
+            }
+        });
+        assert_json_eq!(path_item, value);
+
+        let deserialized: PathItem = serde_json::from_value(value).unwrap();
+        assert_eq!(deserialized, path_item);
+    }
 }
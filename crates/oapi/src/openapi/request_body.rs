@@ -4,7 +4,7 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use super::{Content, Required};
+use super::{Content, Extensions, Required};
 
 /// Implements [OpenAPI Request Body][request_body].
 ///
@@ -24,6 +24,10 @@ pub struct RequestBody {
     /// Determines whether request body is required in the request or not.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Required>,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl RequestBody {
@@ -55,6 +59,7 @@ impl RequestBody {
             description,
             contents,
             required,
+            mut extensions,
         } = other;
         if let Some(description) = description {
             if !description.is_empty() {
@@ -65,6 +70,13 @@ impl RequestBody {
         if let Some(required) = required {
             self.required = Some(required);
         }
+        self.extensions.append(&mut extensions);
+    }
+
+    /// Add a specification extension to the [`RequestBody`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
     }
 }
 
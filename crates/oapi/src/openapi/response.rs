@@ -1,15 +1,16 @@
 //! Implements [OpenApi Responses][responses].
 //!
 //! [responses]: https://spec.openapis.org/oas/latest.html#responses-object
-use std::collections::{BTreeMap, HashMap};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
 use std::ops::{Deref, DerefMut};
 
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use crate::{Ref, RefOr};
+use crate::{Extensions, Ref, RefOr};
 
-use super::{header::Header, Content};
+use super::{header::Header, link::Link, Content};
 
 /// Implements [OpenAPI Responses Object][responses].
 ///
@@ -91,6 +92,52 @@ impl Responses {
         self.0.append(&mut other.0);
     }
 
+    /// Inserts a response, if a response with the same key is already present, the response is merged into it.
+    ///
+    /// Descriptions of merged responses are joined as paragraphs, contents and headers which are already present
+    /// are kept. It is used when several variants of an error type share the same status code.
+    pub fn merge<S: Into<String>>(&mut self, key: S, response: Response) {
+        match self.0.entry(key.into()) {
+            Entry::Occupied(mut entry) => {
+                let RefOr::T(existing) = entry.get_mut() else {
+                    return;
+                };
+                if existing.description.is_empty() {
+                    existing.description = response.description;
+                } else if !response.description.is_empty()
+                    && !existing.description.split("\n\n").any(|d| d == response.description)
+                {
+                    existing.description = format!("{}\n\n{}", existing.description, response.description);
+                }
+                for (content_type, content) in response.contents {
+                    existing.contents.entry(content_type).or_insert(content);
+                }
+                for (name, header) in response.headers {
+                    existing.headers.entry(name).or_insert(header);
+                }
+                for (name, link) in response.links {
+                    existing.links.entry(name).or_insert(link);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(RefOr::T(response));
+            }
+        }
+    }
+
+    /// Add a [`Link`] to all success responses, whose status code is `2XX` or starts with `2`.
+    ///
+    /// Responses which are references are skipped, add links to the referenced responses in the components
+    /// instead.
+    pub fn add_link<S: Into<String>>(&mut self, name: S, link: Link) {
+        let name = name.into();
+        for (_, response) in self.0.iter_mut().filter(|(status, _)| status.starts_with('2')) {
+            if let RefOr::T(response) = response {
+                response.links.insert(name.clone(), RefOr::T(link.clone()));
+            }
+        }
+    }
+
     /// Add responses from an iterator over a pair of `(status_code, response): (String, Response)`.
     pub fn extend<I, C, R>(&mut self, iter: I)
     where
@@ -145,9 +192,13 @@ pub struct Response {
     #[serde(rename = "content")]
     pub contents: IndexMap<String, Content>,
 
-    /// Optional extensions "x-something"
-    #[serde(skip_serializing_if = "Option::is_none", flatten)]
-    pub extensions: Option<HashMap<String, serde_json::Value>>,
+    /// Map of [`Link`]s to operations which can be called with values of the response, identified by their name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub links: BTreeMap<String, RefOr<Link>>,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Response {
@@ -177,6 +228,18 @@ impl Response {
         self.headers.insert(name.into(), header);
         self
     }
+
+    /// Add a [`Link`] to an operation which can be called with values of the response and returns `Self`.
+    pub fn add_link<S: Into<String>, L: Into<RefOr<Link>>>(mut self, name: S, link: L) -> Self {
+        self.links.insert(name.into(), link.into());
+        self
+    }
+
+    /// Add a specification extension to the [`Response`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 impl From<Ref> for RefOr<Response> {
@@ -231,6 +294,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_responses_merge() {
+        let mut responses = Responses::new();
+        responses.merge("404", Response::new("User not found"));
+        responses.merge(
+            "404",
+            Response::new("Post not found")
+                .add_content("application/json", Content::new(Ref::from_schema_name("Error"))),
+        );
+        responses.merge("404", Response::new("User not found"));
+        responses.merge("500", Response::new("Internal error"));
+
+        assert_json_eq!(
+            responses,
+            json!({
+              "404": {
+                "description": "User not found\n\nPost not found",
+                "content": {
+                  "application/json": {
+                    "schema": {
+                      "$ref": "#/components/schemas/Error"
+                    }
+                  }
+                }
+              },
+              "500": {
+                "description": "Internal error"
+              }
+            })
+        );
+    }
+
     #[test]
     fn test_responses_from_btree_map() {
         let input = BTreeMap::from([
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::{Discriminator, RefOr, Schema};
+use crate::{Discriminator, Extensions, RefOr, Schema};
 
 /// AllOf [Composite Object][allof] component holds
 /// multiple components together where API endpoint will return a combination of all of them.
@@ -40,6 +40,10 @@ pub struct AllOf {
     /// Set `true` to allow `"null"` to be used as value for given type.
     #[serde(default, skip_serializing_if = "super::is_false")]
     pub nullable: bool,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl AllOf {
@@ -110,6 +114,12 @@ impl AllOf {
         self.nullable = nullable;
         self
     }
+
+    /// Add a specification extension to the [`AllOf`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 impl From<AllOf> for Schema {
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::{Deprecated, RefOr, Schema, SchemaType, Xml};
+use crate::{Deprecated, Extensions, RefOr, Schema, SchemaType, Xml};
 
 /// Array represents [`Vec`] or [`slice`] type  of items.
 ///
@@ -57,6 +57,10 @@ pub struct Array {
     /// Set `true` to allow `"null"` to be used as value for given type.
     #[serde(default, skip_serializing_if = "super::is_false")]
     pub nullable: bool,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Default for Array {
@@ -74,6 +78,7 @@ impl Default for Array {
             min_items: Default::default(),
             xml: Default::default(),
             nullable: Default::default(),
+            extensions: Default::default(),
         }
     }
 }
@@ -159,6 +164,12 @@ impl Array {
         self.nullable = nullable;
         self
     }
+
+    /// Add a specification extension to the [`Array`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 impl From<Array> for Schema {
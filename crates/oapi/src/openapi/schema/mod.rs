@@ -373,6 +373,10 @@ pub enum KnownFormat {
     /// ISO-8601 full date time [FRC3339](https://xml2rfc.ietf.org/public/rfc/html/rfc3339.html#anchor14).
     #[serde(rename = "date-time")]
     DateTime,
+    /// ISO-8601 partial time [RFC3339](https://xml2rfc.ietf.org/public/rfc/html/rfc3339.html#anchor14).
+    Time,
+    /// ISO-8601 duration [RFC3339](https://xml2rfc.ietf.org/public/rfc/html/rfc3339.html#appendix-A).
+    Duration,
     /// Hint to UI to obscure input.
     Password,
     /// Used with [`String`] values to indicate value is in decimal format.
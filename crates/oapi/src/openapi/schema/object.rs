@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::AdditionalProperties;
-use crate::{Deprecated, PropMap, RefOr, Schema, SchemaFormat, SchemaType, ToArray, Xml};
+use crate::{Deprecated, Extensions, PropMap, RefOr, Schema, SchemaFormat, SchemaType, ToArray, Xml};
 
 /// Implements subset of [OpenAPI Schema Object][schema] which allows
 /// adding other [`Schema`]s as **properties** to this [`Schema`].
@@ -134,6 +134,10 @@ pub struct Object {
     /// `0` will have same effect as omitting the attribute.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_properties: Option<usize>,
+
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl Object {
@@ -312,6 +316,12 @@ impl Object {
         self.min_properties = Some(min_properties);
         self
     }
+
+    /// Add a specification extension to the [`Object`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 impl From<Object> for Schema {
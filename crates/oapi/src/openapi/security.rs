@@ -3,13 +3,12 @@
 //! Refer to [`SecurityScheme`] for usage and more details.
 //!
 //! [security]: https://spec.openapis.org/oas/latest.html#security-scheme-object
-use std::{
-    collections::{BTreeMap, HashMap},
-    iter,
-};
+use std::{collections::BTreeMap, iter};
 
 use serde::{Deserialize, Serialize};
 
+use super::Extensions;
+
 /// OpenAPI [security requirement][security] object.
 ///
 /// Security requirement holds list of required [`SecurityScheme`] *names* and possible *scopes* required
@@ -81,6 +80,29 @@ impl SecurityRequirement {
 
         self
     }
+
+    /// Combine with another requirement, all schemes of both requirements must be satisfied.
+    ///
+    /// Scopes of a scheme present in both requirements are merged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_oapi::security::SecurityRequirement;
+    /// let requirement = SecurityRequirement::new("api_key", [] as [&str; 0])
+    ///     .and(SecurityRequirement::new("oauth2", ["read:items"]));
+    /// ```
+    pub fn and(mut self, other: SecurityRequirement) -> Self {
+        for (name, scopes) in other.value {
+            let existing = self.value.entry(name).or_default();
+            for scope in scopes {
+                if !existing.contains(&scope) {
+                    existing.push(scope);
+                }
+            }
+        }
+        self
+    }
 }
 
 /// OpenAPI [security scheme][security] for path operations.
@@ -359,6 +381,30 @@ impl OpenIdConnect {
             description: Some(description.into()),
         }
     }
+
+    /// Construct a new open id connect security schema from the issuer url.
+    ///
+    /// The discovery url is the issuer url followed by `/.well-known/openid-configuration`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_oapi::security::OpenIdConnect;
+    /// let oidc = OpenIdConnect::from_issuer("https://accounts.example.com/");
+    /// assert_eq!(oidc.open_id_connect_url, "https://accounts.example.com/.well-known/openid-configuration");
+    /// ```
+    pub fn from_issuer<S: AsRef<str>>(issuer: S) -> Self {
+        Self::new(format!(
+            "{}/.well-known/openid-configuration",
+            issuer.as_ref().trim_end_matches('/')
+        ))
+    }
+
+    /// Add or change description supporting markdown syntax.
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
+    }
 }
 
 /// OAuth2 [`Flow`] configuration for [`SecurityScheme`].
@@ -366,15 +412,16 @@ impl OpenIdConnect {
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub struct OAuth2 {
     /// Map of supported OAuth2 flows.
+    #[serde(deserialize_with = "deserialize_flows")]
     pub flows: BTreeMap<String, Flow>,
 
     /// Optional description for the [`OAuth2`] [`Flow`] [`SecurityScheme`].
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
-    /// Optional extensions "x-something"
-    #[serde(skip_serializing_if = "Option::is_none", flatten)]
-    pub extensions: Option<HashMap<String, serde_json::Value>>,
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 
 impl OAuth2 {
@@ -415,7 +462,7 @@ impl OAuth2 {
                     .map(|auth_flow| (String::from(auth_flow.get_type_as_str()), auth_flow)),
             ),
             description: None,
-            extensions: None,
+            extensions: Extensions::new(),
         }
     }
 
@@ -455,11 +502,54 @@ impl OAuth2 {
                     .map(|auth_flow| (String::from(auth_flow.get_type_as_str()), auth_flow)),
             ),
             description: Some(description.into()),
-            extensions: None,
+            extensions: Extensions::new(),
         }
     }
+
+    /// Add a [`Flow`], replacing the flow of the same type if it exists.
+    pub fn add_flow(mut self, flow: Flow) -> Self {
+        self.flows.insert(String::from(flow.get_type_as_str()), flow);
+        self
+    }
+
+    /// Add or change description supporting markdown syntax.
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a specification extension to the [`OAuth2`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
+/// Deserialize flows by their type key, since the shapes of the flows alone are ambiguous.
+fn deserialize_flows<'de, D>(deserializer: D) -> Result<BTreeMap<String, Flow>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let values = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    values
+        .into_iter()
+        .map(|(kind, value)| {
+            let flow = match &*kind {
+                "implicit" => serde_json::from_value(value).map(Flow::Implicit),
+                "password" => serde_json::from_value(value).map(Flow::Password),
+                "clientCredentials" => serde_json::from_value(value).map(Flow::ClientCredentials),
+                "authorizationCode" => serde_json::from_value(value).map(Flow::AuthorizationCode),
+                _ => return Err(D::Error::unknown_variant(&kind, FLOW_TYPES)),
+            };
+            flow.map(|flow| (kind, flow)).map_err(D::Error::custom)
+        })
+        .collect()
+}
+
+const FLOW_TYPES: &[&str] = &["implicit", "password", "clientCredentials", "authorizationCode"];
+
 /// [`OAuth2`] flow configuration object.
 ///
 ///
@@ -480,6 +570,16 @@ pub enum Flow {
 }
 
 impl Flow {
+    /// Get the scopes of the flow.
+    pub fn scopes(&self) -> &Scopes {
+        match self {
+            Self::Implicit(flow) => &flow.scopes,
+            Self::Password(flow) => &flow.scopes,
+            Self::ClientCredentials(flow) => &flow.scopes,
+            Self::AuthorizationCode(flow) => &flow.scopes,
+        }
+    }
+
     fn get_type_as_str(&self) -> &str {
         match self {
             Self::Implicit(_) => "implicit",
@@ -566,6 +666,12 @@ impl Implicit {
             scopes,
         }
     }
+
+    /// Add or change the refresh token url.
+    pub fn refresh_url<S: Into<String>>(mut self, refresh_url: S) -> Self {
+        self.refresh_url = Some(refresh_url.into());
+        self
+    }
 }
 
 /// Authorization code [`Flow`] configuration for [`OAuth2`].
@@ -656,6 +762,12 @@ impl AuthorizationCode {
             scopes,
         }
     }
+
+    /// Add or change the refresh token url.
+    pub fn refresh_url<S: Into<String>>(mut self, refresh_url: S) -> Self {
+        self.refresh_url = Some(refresh_url.into());
+        self
+    }
 }
 
 /// Password [`Flow`] configuration for [`OAuth2`].
@@ -737,6 +849,12 @@ impl Password {
             scopes,
         }
     }
+
+    /// Add or change the refresh token url.
+    pub fn refresh_url<S: Into<String>>(mut self, refresh_url: S) -> Self {
+        self.refresh_url = Some(refresh_url.into());
+        self
+    }
 }
 
 /// Client credentials [`Flow`] configuration for [`OAuth2`].
@@ -818,6 +936,12 @@ impl ClientCredentials {
             scopes,
         }
     }
+
+    /// Add or change the refresh token url.
+    pub fn refresh_url<S: Into<String>>(mut self, refresh_url: S) -> Self {
+        self.refresh_url = Some(refresh_url.into());
+        self
+    }
 }
 
 /// [`OAuth2`] flow scopes object defines required permissions for oauth flow.
@@ -888,6 +1012,29 @@ impl Scopes {
             scopes: BTreeMap::from_iter(iter::once_with(|| (scope.into(), description.into()))),
         }
     }
+
+    /// Add a scope with its description.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use salvo_oapi::security::Scopes;
+    /// let scopes = Scopes::new().add("edit:items", "edit my items").add("read:items", "read my items");
+    /// ```
+    pub fn add<S: Into<String>, D: Into<String>>(mut self, scope: S, description: D) -> Self {
+        self.scopes.insert(scope.into(), description.into());
+        self
+    }
+
+    /// Check if the scope is defined.
+    pub fn contains(&self, scope: &str) -> bool {
+        self.scopes.contains_key(scope)
+    }
+
+    /// Check if there are no scopes.
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
 }
 
 impl<I> FromIterator<(I, I)> for Scopes
@@ -1351,4 +1498,56 @@ mod tests {
   "description": "authorization is performed with client side certificate"
 }"###
     }
+
+    test_fn! {
+        security_scheme_correct_openid_connect_from_issuer:
+        SecurityScheme::OpenIdConnect(OpenIdConnect::from_issuer("https://localhost/").description("openid"));
+        r###"{
+  "type": "openIdConnect",
+  "openIdConnectUrl": "https://localhost/.well-known/openid-configuration",
+  "description": "openid"
+}"###
+    }
+
+    #[test]
+    fn test_oauth2_flows_roundtrip() {
+        let oauth2 = OAuth2::new([
+            Flow::Password(Password::new(
+                "https://localhost/token",
+                Scopes::new().add("read", "read items"),
+            )),
+            Flow::ClientCredentials(
+                ClientCredentials::new("https://localhost/token", Scopes::one("read", "read items"))
+                    .refresh_url("https://localhost/refresh"),
+            ),
+        ])
+        .add_flow(Flow::Implicit(Implicit::new("https://localhost/auth", Scopes::new())))
+        .description("all flows");
+        let scheme = SecurityScheme::OAuth2(oauth2);
+
+        let value = serde_json::to_value(&scheme).unwrap();
+        assert_eq!(
+            value["flows"]["clientCredentials"]["refreshUrl"],
+            serde_json::json!("https://localhost/refresh")
+        );
+        let parsed: SecurityScheme = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, scheme);
+        let SecurityScheme::OAuth2(parsed) = parsed else {
+            panic!("expected oauth2 scheme");
+        };
+        assert!(matches!(parsed.flows["password"], Flow::Password(_)));
+        assert!(matches!(parsed.flows["clientCredentials"], Flow::ClientCredentials(_)));
+        assert!(parsed.flows["password"].scopes().contains("read"));
+        assert!(parsed.flows["implicit"].scopes().is_empty());
+    }
+
+    #[test]
+    fn test_security_requirement_and() {
+        let requirement = SecurityRequirement::new("oauth2", ["read"])
+            .and(SecurityRequirement::new("oauth2", ["read", "write"]).add("api_key", [] as [&str; 0]));
+        assert_eq!(
+            serde_json::to_value(&requirement).unwrap(),
+            serde_json::json!({"api_key": [], "oauth2": ["read", "write"]})
+        );
+    }
 }
@@ -2,11 +2,10 @@
 //!
 //! [tag]: https://spec.openapis.org/oas/latest.html#tag-object
 use std::cmp::Ordering;
-use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use super::external_docs::ExternalDocs;
+use super::{external_docs::ExternalDocs, Extensions};
 
 /// Implements [OpenAPI Tag Object][tag].
 ///
@@ -28,9 +27,9 @@ pub struct Tag {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_docs: Option<ExternalDocs>,
 
-    /// Optional extensions "x-something"
-    #[serde(skip_serializing_if = "Option::is_none", flatten)]
-    pub extensions: Option<HashMap<String, serde_json::Value>>,
+    /// Optional extensions "x-something".
+    #[serde(skip_serializing_if = "Extensions::is_empty", flatten)]
+    pub extensions: Extensions,
 }
 impl Ord for Tag {
     fn cmp(&self, other: &Self) -> Ordering {
@@ -83,6 +82,12 @@ impl Tag {
         self.external_docs = Some(external_docs);
         self
     }
+
+    /// Add a specification extension to the [`Tag`], e.g. `x-codegen`.
+    pub fn add_extension<K: Into<String>, V: Into<serde_json::Value>>(mut self, name: K, value: V) -> Self {
+        self.extensions.insert(name, value);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -96,7 +101,7 @@ mod tests {
         assert_eq!(tag.name, "tag name");
         assert!(tag.description.is_none());
         assert!(tag.external_docs.is_none());
-        assert!(tag.extensions.is_none());
+        assert!(tag.extensions.is_empty());
 
         let tag = tag.name("new tag name");
         assert_eq!(tag.name, "new tag name");
@@ -20,7 +20,7 @@ const INDEX_TMPL: &str = r#"
     <script type="module" src="{{lib_url}}"></script>
   </head>
   <body>
-    <rapi-doc spec-url="{{spec_url}}"></rapi-doc>
+    <rapi-doc spec-url="{{spec_url}}"{{attrs}}></rapi-doc>
   </body>
 </html>
 "#;
@@ -39,7 +39,31 @@ pub struct RapiDoc {
     pub lib_url: Cow<'static, str>,
     /// The spec url path.
     pub spec_url: Cow<'static, str>,
+    /// The color theme, RapiDoc uses the light theme if it is not set.
+    pub theme: Option<Theme>,
+    /// Whether the authentication entered by the user is kept across browser refresh.
+    pub persist_auth: bool,
+    /// Extra attributes of the `rapi-doc` element, see <https://rapidocweb.com/api.html>.
+    pub attributes: Vec<(Cow<'static, str>, Cow<'static, str>)>,
 }
+
+/// Color theme of [`RapiDoc`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Theme {
+    /// Light theme.
+    Light,
+    /// Dark theme.
+    Dark,
+}
+impl Theme {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+}
+
 impl RapiDoc {
     /// Create a new [`RapiDoc`] for given path.
     ///
@@ -59,6 +83,9 @@ impl RapiDoc {
             description: None,
             lib_url: "https://unpkg.com/rapidoc/dist/rapidoc-min.js".into(),
             spec_url: spec_url.into(),
+            theme: None,
+            persist_auth: false,
+            attributes: Vec::new(),
         }
     }
 
@@ -86,6 +113,37 @@ impl RapiDoc {
         self
     }
 
+    /// Set the spec url path.
+    pub fn spec_url(mut self, spec_url: impl Into<Cow<'static, str>>) -> Self {
+        self.spec_url = spec_url.into();
+        self
+    }
+
+    /// Set the color theme.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Set whether the authentication entered by the user is kept across browser refresh.
+    pub fn persist_auth(mut self, persist_auth: bool) -> Self {
+        self.persist_auth = persist_auth;
+        self
+    }
+
+    /// Add an extra attribute to the `rapi-doc` element.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use salvo_oapi::rapidoc::RapiDoc;
+    /// let doc = RapiDoc::new("/openapi.json").attribute("render-style", "read");
+    /// ```
+    pub fn attribute(mut self, name: impl Into<Cow<'static, str>>, value: impl Into<Cow<'static, str>>) -> Self {
+        self.attributes.push((name.into(), value.into()));
+        self
+    }
+
     /// Consusmes the [`RapiDoc`] and returns [`Router`] with the [`RapiDoc`] as handler.
     pub fn into_router(self, path: impl Into<String>) -> Router {
         Router::with_path(path.into()).goal(self)
@@ -110,8 +168,19 @@ impl Handler for RapiDoc {
             .as_ref()
             .map(|s| format!("<meta name=\"description\" content=\"{}\">", s))
             .unwrap_or_default();
+        let mut attrs = String::new();
+        if let Some(theme) = self.theme {
+            attrs.push_str(&format!(" theme=\"{}\"", theme.as_str()));
+        }
+        if self.persist_auth {
+            attrs.push_str(" persist-auth=\"true\"");
+        }
+        for (name, value) in &self.attributes {
+            attrs.push_str(&format!(" {}=\"{}\"", name, value.replace('"', "&quot;")));
+        }
         let html = INDEX_TMPL
             .replacen("{{spec_url}}", &self.spec_url, 1)
+            .replacen("{{attrs}}", &attrs, 1)
             .replacen("{{lib_url}}", &self.lib_url, 1)
             .replacen("{{description}}", &description, 1)
             .replacen("{{keywords}}", &keywords, 1)
@@ -119,3 +188,29 @@ impl Handler for RapiDoc {
         res.render(Text::Html(html));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rapidoc_options() {
+        let router = RapiDoc::new("/openapi.json")
+            .spec_url("/api-doc/openapi.json")
+            .theme(Theme::Dark)
+            .persist_auth(true)
+            .attribute("render-style", "read")
+            .into_router("rapidoc");
+        let body = TestClient::get("http://127.0.0.1:5800/rapidoc")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(body.contains(
+            r#"<rapi-doc spec-url="/api-doc/openapi.json" theme="dark" persist-auth="true" render-style="read">"#
+        ));
+    }
+}
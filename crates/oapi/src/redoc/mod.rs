@@ -31,7 +31,7 @@ const INDEX_TMPL: &str = r#"
     <script>
       Redoc.init(
         "{{spec_url}}",
-        {},
+        {{options}},
         document.getElementById("redoc-container")
       );
     </script>
@@ -53,6 +53,8 @@ pub struct ReDoc {
     pub lib_url: Cow<'static, str>,
     /// The spec url path.
     pub spec_url: Cow<'static, str>,
+    /// Options passed to ReDoc, see <https://redocly.com/docs/redoc/config>.
+    pub options: serde_json::Map<String, serde_json::Value>,
 }
 
 impl ReDoc {
@@ -74,6 +76,7 @@ impl ReDoc {
             description: None,
             lib_url: "https://cdn.redoc.ly/redoc/latest/bundles/redoc.standalone.js".into(),
             spec_url: spec_url.into(),
+            options: serde_json::Map::new(),
         }
     }
 
@@ -101,6 +104,30 @@ impl ReDoc {
         self
     }
 
+    /// Set the spec url path.
+    pub fn spec_url(mut self, spec_url: impl Into<Cow<'static, str>>) -> Self {
+        self.spec_url = spec_url.into();
+        self
+    }
+
+    /// Set the theme option, e.g. `json!({"colors": {"primary": {"main": "#32329f"}}})`.
+    pub fn theme(self, theme: serde_json::Value) -> Self {
+        self.option("theme", theme)
+    }
+
+    /// Set an option passed to ReDoc.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use salvo_oapi::redoc::ReDoc;
+    /// let doc = ReDoc::new("/openapi.json").option("hideDownloadButton", true);
+    /// ```
+    pub fn option(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.options.insert(name.into(), value.into());
+        self
+    }
+
     /// Consusmes the [`ReDoc`] and returns [`Router`] with the [`ReDoc`] as handler.
     pub fn into_router(self, path: impl Into<String>) -> Router {
         Router::with_path(path.into()).goal(self)
@@ -125,8 +152,12 @@ impl Handler for ReDoc {
             .as_ref()
             .map(|s| format!("<meta name=\"description\" content=\"{}\">", s))
             .unwrap_or_default();
+        let options = serde_json::to_string(&self.options)
+            .unwrap_or_else(|_| "{}".into())
+            .replace("</", "<\\/");
         let html = INDEX_TMPL
             .replacen("{{spec_url}}", &self.spec_url, 1)
+            .replacen("{{options}}", &options, 1)
             .replacen("{{lib_url}}", &self.lib_url, 1)
             .replacen("{{description}}", &description, 1)
             .replacen("{{keywords}}", &keywords, 1)
@@ -134,3 +165,26 @@ impl Handler for ReDoc {
         res.render(Text::Html(html));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::test::{ResponseExt, TestClient};
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_redoc_options() {
+        let router = ReDoc::new("/openapi.json")
+            .theme(json!({"sidebar": {"width": "300px"}}))
+            .option("hideDownloadButton", true)
+            .into_router("redoc");
+        let body = TestClient::get("http://127.0.0.1:5800/redoc")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(body.contains(r#"{"hideDownloadButton":true,"theme":{"sidebar":{"width":"300px"}}}"#));
+    }
+}
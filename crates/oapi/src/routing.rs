@@ -6,7 +6,7 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use salvo_core::Router;
 
-use crate::{path::PathItemType, SecurityRequirement};
+use crate::{path::PathItemType, Components, Responses, SecurityRequirement, ToResponses};
 
 #[derive(Debug, Default)]
 pub(crate) struct NormNode {
@@ -32,6 +32,15 @@ impl NormNode {
         if let Some(metadata) = registry.get(&router.id) {
             node.metadata.tags.extend(metadata.tags.iter().cloned());
             node.metadata.securities.extend(metadata.securities.iter().cloned());
+            node.metadata.groups.extend(metadata.groups.iter().cloned());
+            node.metadata.responses.extend(metadata.responses.iter().cloned());
+            node.metadata.hidden |= metadata.hidden;
+            if let Some(environments) = &metadata.environments {
+                node.metadata.environments = Some(match node.metadata.environments.take() {
+                    Some(inherited) => inherited.intersection(environments).cloned().collect(),
+                    None => environments.clone(),
+                });
+            }
         }
 
         let regex = Regex::new(r#"<([^/:>]+)(:[^>]*)?>"#).expect("invalid regex");
@@ -74,6 +83,10 @@ impl NormNode {
     }
 }
 
+/// Environment variable naming the environment of the documents, see
+/// [`RouterExt::oapi_environments`].
+pub const OAPI_ENV_VAR: &str = "SALVO_OAPI_ENV";
+
 /// A component for save router metadata.
 type MetadataMap = RwLock<HashMap<usize, Metadata>>;
 static METADATA_REGISTRY: Lazy<MetadataMap> = Lazy::new(MetadataMap::default);
@@ -104,6 +117,35 @@ pub trait RouterExt {
     where
         I: IntoIterator<Item = V>,
         V: Into<String>;
+
+    /// Add the router to a group.
+    ///
+    /// All endpoints in the router and it's descents will belong to the group, which can be used to
+    /// split them into separate documents with [`OpenApi::merge_router_filtered`](crate::OpenApi::merge_router_filtered).
+    fn oapi_group(self, group: impl Into<String>) -> Self;
+
+    /// Add the responses documented by `R` to the router.
+    ///
+    /// All endpoints in the router and it's descents will document these responses, unless they
+    /// declare a response with the same status code. Use [`SharedResponses`](crate::SharedResponses)
+    /// to register the responses once in components.
+    fn oapi_responses<R: ToResponses>(self) -> Self;
+
+    /// Leave the router out of the documents.
+    ///
+    /// All endpoints in the router and it's descents are hidden, they are still routed. Use it for internal and
+    /// debug routes sharing the router with the published ones.
+    fn oapi_hidden(self) -> Self;
+
+    /// Only document the router in the environments.
+    ///
+    /// All endpoints in the router and it's descents are only merged into documents when the
+    /// [`OAPI_ENV_VAR`](crate::OAPI_ENV_VAR) environment variable is one of `environments`. Restrictions of
+    /// nested routers are combined, an endpoint must be allowed by all of them.
+    fn oapi_environments<I, V>(self, environments: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<String>;
 }
 
 impl RouterExt for Router {
@@ -146,6 +188,45 @@ impl RouterExt for Router {
         metadata.tags.extend(iter.into_iter().map(Into::into));
         self
     }
+    fn oapi_group(self, group: impl Into<String>) -> Self {
+        let mut guard = METADATA_REGISTRY
+            .write()
+            .expect("failed to lock METADATA_REGISTRY for write");
+        let metadata = guard.entry(self.id).or_default();
+        metadata.groups.insert(group.into());
+        self
+    }
+    fn oapi_responses<R: ToResponses>(self) -> Self {
+        let mut guard = METADATA_REGISTRY
+            .write()
+            .expect("failed to lock METADATA_REGISTRY for write");
+        let metadata = guard.entry(self.id).or_default();
+        metadata.responses.push(R::to_responses);
+        self
+    }
+    fn oapi_hidden(self) -> Self {
+        let mut guard = METADATA_REGISTRY
+            .write()
+            .expect("failed to lock METADATA_REGISTRY for write");
+        let metadata = guard.entry(self.id).or_default();
+        metadata.hidden = true;
+        self
+    }
+    fn oapi_environments<I, V>(self, iter: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<String>,
+    {
+        let mut guard = METADATA_REGISTRY
+            .write()
+            .expect("failed to lock METADATA_REGISTRY for write");
+        let metadata = guard.entry(self.id).or_default();
+        metadata
+            .environments
+            .get_or_insert_with(BTreeSet::new)
+            .extend(iter.into_iter().map(Into::into));
+        self
+    }
 }
 
 #[non_exhaustive]
@@ -153,4 +234,19 @@ impl RouterExt for Router {
 pub(crate) struct Metadata {
     pub(crate) tags: BTreeSet<String>,
     pub(crate) securities: Vec<SecurityRequirement>,
+    pub(crate) groups: BTreeSet<String>,
+    pub(crate) responses: Vec<fn(&mut Components) -> Responses>,
+    pub(crate) hidden: bool,
+    pub(crate) environments: Option<BTreeSet<String>>,
+}
+
+impl Metadata {
+    /// Check if the endpoints are documented in `environment`.
+    pub(crate) fn is_visible(&self, environment: Option<&str>) -> bool {
+        !self.hidden
+            && match &self.environments {
+                Some(environments) => environment.is_some_and(|environment| environments.contains(environment)),
+                None => true,
+            }
+    }
 }
@@ -24,7 +24,7 @@ const INDEX_TMPL: &str = r#"
   </head>
 
   <body>{{header}}
-    <script id="api-reference" data-url="{{spec_url}}"></script>
+    <script id="api-reference" data-url="{{spec_url}}" data-configuration="{{configuration}}"></script>
     <script src="{{lib_url}}"></script>
   </body>
 </html>
@@ -48,6 +48,8 @@ pub struct Scalar {
     pub lib_url: Cow<'static, str>,
     /// The spec url path.
     pub spec_url: Cow<'static, str>,
+    /// Configuration passed to Scalar, see <https://github.com/scalar/scalar/blob/main/documentation/configuration.md>.
+    pub configuration: serde_json::Map<String, serde_json::Value>,
 }
 impl Scalar {
     /// Create a new [`Scalar`] for given path.
@@ -70,6 +72,7 @@ impl Scalar {
             header: None,
             lib_url: "https://cdn.jsdelivr.net/npm/@scalar/api-reference".into(),
             spec_url: spec_url.into(),
+            configuration: serde_json::Map::new(),
         }
     }
 
@@ -97,6 +100,35 @@ impl Scalar {
         self
     }
 
+    /// Set the spec url path.
+    pub fn spec_url(mut self, spec_url: impl Into<Cow<'static, str>>) -> Self {
+        self.spec_url = spec_url.into();
+        self
+    }
+
+    /// Set the theme, e.g. `"purple"`, `"moon"` or `"none"` to use only the custom style.
+    pub fn theme(self, theme: impl Into<String>) -> Self {
+        self.configuration("theme", theme.into())
+    }
+
+    /// Set whether the authentication entered by the user is kept across browser refresh.
+    pub fn persist_auth(self, persist_auth: bool) -> Self {
+        self.configuration("persistAuth", persist_auth)
+    }
+
+    /// Set a configuration value passed to Scalar.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use salvo_oapi::scalar::Scalar;
+    /// let doc = Scalar::new("/openapi.json").configuration("darkMode", true);
+    /// ```
+    pub fn configuration(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.configuration.insert(name.into(), value.into());
+        self
+    }
+
     /// Consusmes the [`Scalar`] and returns [`Router`] with the [`Scalar`] as handler.
     pub fn into_router(self, path: impl Into<String>) -> Router {
         Router::with_path(path.into()).goal(self)
@@ -125,9 +157,14 @@ impl Handler for Scalar {
             .as_ref()
             .map(|s| format!("<style>{}</style>", s))
             .unwrap_or_default();
+        let configuration = serde_json::to_string(&self.configuration)
+            .unwrap_or_else(|_| "{}".into())
+            .replace('&', "&amp;")
+            .replace('"', "&quot;");
         let html = INDEX_TMPL
             .replacen("{{lib_url}}", &self.lib_url, 1)
             .replacen("{{spec_url}}", &self.spec_url, 1)
+            .replacen("{{configuration}}", &configuration, 1)
             .replacen("{{header}}", self.header.as_deref().unwrap_or_default(), 1)
             .replacen("{{style}}", &style, 1)
             .replacen("{{description}}", &description, 1)
@@ -248,3 +285,27 @@ const DEFAULT_STYLE: &str = r#":root {
     height: 32px;
   }
 "#;
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scalar_configuration() {
+        let router = Scalar::new("/openapi.json")
+            .theme("moon")
+            .persist_auth(true)
+            .into_router("scalar");
+        let body = TestClient::get("http://127.0.0.1:5800/scalar")
+            .send(router)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(
+            body.contains(r#"data-configuration="{&quot;persistAuth&quot;:true,&quot;theme&quot;:&quot;moon&quot;}""#)
+        );
+    }
+}
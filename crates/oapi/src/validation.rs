@@ -0,0 +1,841 @@
+//! Validate requests and responses against the [`OpenApi`] document.
+//!
+//! [`OpenApiValidator`] is a hoop which finds the documented operation of each request, and validates the path,
+//! query, header and cookie parameters and the JSON request body against its schemas. Invalid requests are
+//! rejected with `400 Bad Request`, the detail of the error lists every violation.
+//!
+//! In debug builds it can also validate the JSON responses, violations are logged as warnings to catch the
+//! drift between the handlers and the document.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_oapi::extract::*;
+//! use salvo_oapi::validation::OpenApiValidator;
+//! use salvo_oapi::{endpoint, OpenApi};
+//!
+//! #[endpoint]
+//! async fn hello(name: QueryParam<String, true>) -> String {
+//!     format!("Hello, {}!", name.into_inner())
+//! }
+//!
+//! let router = Router::new().push(Router::with_path("hello").get(hello));
+//! let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+//! let router = Router::new()
+//!     .hoop(OpenApiValidator::new(&doc).validate_responses(true))
+//!     .push(router)
+//!     .push(doc.into_router("/api-doc/openapi.json"));
+//! ```
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+
+use regex::Regex;
+use salvo_core::http::{Method, ResBody, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+use serde_json::Value;
+
+use crate::schema::AdditionalProperties;
+use crate::{Components, Content, OpenApi, Operation, ParameterIn, PathItemType, RefOr, Required, Schema, SchemaType};
+
+/// A violation of the schema.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationError {
+    /// Location of the invalid value, such as `query.limit` or `body.items[0].name`.
+    pub location: String,
+    /// Description of the violation.
+    pub message: String,
+}
+impl ValidationError {
+    fn new(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+}
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Validate a JSON value against a schema, the references are resolved in the components.
+pub fn validate_value(
+    value: &Value,
+    schema: &RefOr<Schema>,
+    components: &Components,
+    location: &str,
+) -> Vec<ValidationError> {
+    let mut patterns = Patterns::default();
+    patterns.collect(schema);
+    for schema in components.schemas.values() {
+        patterns.collect(schema);
+    }
+    let mut errors = Vec::new();
+    validate(value, schema, components, &patterns, location, &mut errors);
+    errors
+}
+
+/// The `pattern` keywords of the schemas, compiled once and keyed by their source.
+#[derive(Default)]
+struct Patterns(HashMap<String, Regex>);
+impl Patterns {
+    /// Compile the patterns of `schema` and its inline subschemas, references are collected with the components.
+    fn collect(&mut self, schema: &RefOr<Schema>) {
+        let RefOr::T(schema) = schema else {
+            return;
+        };
+        match schema {
+            Schema::Object(object) => {
+                if let Some(pattern) = &object.pattern {
+                    if !self.0.contains_key(pattern) {
+                        match Regex::new(pattern) {
+                            Ok(regex) => {
+                                self.0.insert(pattern.clone(), regex);
+                            }
+                            Err(e) => tracing::warn!(pattern, error = ?e, "invalid schema pattern"),
+                        }
+                    }
+                }
+                for schema in object.properties.values() {
+                    self.collect(schema);
+                }
+                if let Some(AdditionalProperties::RefOr(schema)) = object.additional_properties.as_deref() {
+                    self.collect(schema);
+                }
+            }
+            Schema::Array(array) => self.collect(&array.items),
+            Schema::OneOf(one_of) => one_of.items.iter().for_each(|schema| self.collect(schema)),
+            Schema::AnyOf(any_of) => any_of.items.iter().for_each(|schema| self.collect(schema)),
+            Schema::AllOf(all_of) => all_of.items.iter().for_each(|schema| self.collect(schema)),
+        }
+    }
+}
+
+fn resolve<'a>(schema: &'a RefOr<Schema>, components: &'a Components) -> Option<&'a Schema> {
+    match schema {
+        RefOr::T(schema) => Some(schema),
+        RefOr::Ref(reference) => {
+            let name = reference.ref_location.strip_prefix("#/components/schemas/")?;
+            resolve(components.schemas.get(name)?, components)
+        }
+    }
+}
+
+fn is_valid(value: &Value, schema: &RefOr<Schema>, components: &Components, patterns: &Patterns) -> bool {
+    let mut errors = Vec::new();
+    validate(value, schema, components, patterns, "", &mut errors);
+    errors.is_empty()
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn validate(
+    value: &Value,
+    schema: &RefOr<Schema>,
+    components: &Components,
+    patterns: &Patterns,
+    location: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let Some(schema) = resolve(schema, components) else {
+        return;
+    };
+    match schema {
+        Schema::Object(object) => {
+            if value.is_null() && object.nullable {
+                return;
+            }
+            // An object schema without any constraint is used for free-form values.
+            let free_form = object.schema_type == SchemaType::Object
+                && object.properties.is_empty()
+                && object.required.is_empty()
+                && object.additional_properties.is_none();
+            if free_form {
+                return;
+            }
+            let type_matched = match object.schema_type {
+                SchemaType::Object => value.is_object(),
+                SchemaType::String => value.is_string(),
+                SchemaType::Integer => value.is_i64() || value.is_u64(),
+                SchemaType::Number => value.is_number(),
+                SchemaType::Boolean => value.is_boolean(),
+                SchemaType::Array => value.is_array(),
+            };
+            if !type_matched {
+                let expected = serde_json::to_value(&object.schema_type).unwrap_or_default();
+                errors.push(ValidationError::new(
+                    location,
+                    format!(
+                        "expected {}, found {}",
+                        expected.as_str().unwrap_or_default(),
+                        type_name(value)
+                    ),
+                ));
+                return;
+            }
+            if let Some(enum_values) = &object.enum_values {
+                if !enum_values.contains(value) {
+                    errors.push(ValidationError::new(location, "value is not one of the allowed values"));
+                }
+            }
+            match value {
+                Value::Number(number) => {
+                    let number = number.as_f64().unwrap_or_default();
+                    if let Some(minimum) = object.minimum {
+                        if number < minimum {
+                            errors.push(ValidationError::new(
+                                location,
+                                format!("must be greater than or equal to {minimum}"),
+                            ));
+                        }
+                    }
+                    if let Some(maximum) = object.maximum {
+                        if number > maximum {
+                            errors.push(ValidationError::new(
+                                location,
+                                format!("must be less than or equal to {maximum}"),
+                            ));
+                        }
+                    }
+                    if let Some(minimum) = object.exclusive_minimum {
+                        if number <= minimum {
+                            errors.push(ValidationError::new(
+                                location,
+                                format!("must be greater than {minimum}"),
+                            ));
+                        }
+                    }
+                    if let Some(maximum) = object.exclusive_maximum {
+                        if number >= maximum {
+                            errors.push(ValidationError::new(location, format!("must be less than {maximum}")));
+                        }
+                    }
+                    if let Some(multiple_of) = object.multiple_of {
+                        if multiple_of > 0.0 && (number / multiple_of).fract() != 0.0 {
+                            errors.push(ValidationError::new(
+                                location,
+                                format!("must be a multiple of {multiple_of}"),
+                            ));
+                        }
+                    }
+                }
+                Value::String(string) => {
+                    let length = string.chars().count();
+                    if let Some(min_length) = object.min_length {
+                        if length < min_length {
+                            errors.push(ValidationError::new(
+                                location,
+                                format!("must be at least {min_length} characters"),
+                            ));
+                        }
+                    }
+                    if let Some(max_length) = object.max_length {
+                        if length > max_length {
+                            errors.push(ValidationError::new(
+                                location,
+                                format!("must be at most {max_length} characters"),
+                            ));
+                        }
+                    }
+                    // Invalid patterns are not compiled, they are warned about once when collected.
+                    let regex = object.pattern.as_ref().and_then(|pattern| patterns.0.get(pattern));
+                    if let Some(regex) = regex {
+                        if !regex.is_match(string) {
+                            errors.push(ValidationError::new(
+                                location,
+                                format!("must match pattern `{}`", regex.as_str()),
+                            ));
+                        }
+                    }
+                }
+                Value::Object(map) => {
+                    for name in &object.required {
+                        if !map.contains_key(name) {
+                            errors.push(ValidationError::new(
+                                join(location, name),
+                                "required property is missing",
+                            ));
+                        }
+                    }
+                    for (name, value) in map {
+                        let location = join(location, name);
+                        if let Some(schema) = object.properties.get(name) {
+                            validate(value, schema, components, patterns, &location, errors);
+                        } else {
+                            match object.additional_properties.as_deref() {
+                                Some(AdditionalProperties::FreeForm(false)) => {
+                                    errors.push(ValidationError::new(location, "unknown property"));
+                                }
+                                Some(AdditionalProperties::RefOr(schema)) => {
+                                    validate(value, schema, components, patterns, &location, errors);
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    if object.min_properties.is_some_and(|min| map.len() < min) {
+                        errors.push(ValidationError::new(location, "has too few properties"));
+                    }
+                    if object.max_properties.is_some_and(|max| map.len() > max) {
+                        errors.push(ValidationError::new(location, "has too many properties"));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Schema::Array(array) => {
+            if value.is_null() && array.nullable {
+                return;
+            }
+            let Value::Array(items) = value else {
+                errors.push(ValidationError::new(
+                    location,
+                    format!("expected array, found {}", type_name(value)),
+                ));
+                return;
+            };
+            if let Some(min) = array.min_items {
+                if items.len() < min {
+                    errors.push(ValidationError::new(
+                        location,
+                        format!("must have at least {min} items"),
+                    ));
+                }
+            }
+            if let Some(max) = array.max_items {
+                if items.len() > max {
+                    errors.push(ValidationError::new(location, format!("must have at most {max} items")));
+                }
+            }
+            if array.unique_items
+                && items
+                    .iter()
+                    .enumerate()
+                    .any(|(index, item)| items[..index].contains(item))
+            {
+                errors.push(ValidationError::new(location, "items must be unique"));
+            }
+            for (index, item) in items.iter().enumerate() {
+                validate(
+                    item,
+                    &array.items,
+                    components,
+                    patterns,
+                    &format!("{location}[{index}]"),
+                    errors,
+                );
+            }
+        }
+        Schema::OneOf(one_of) => {
+            if value.is_null() && one_of.nullable {
+                return;
+            }
+            let matched = one_of
+                .items
+                .iter()
+                .filter(|schema| is_valid(value, schema, components, patterns))
+                .count();
+            if matched != 1 {
+                errors.push(ValidationError::new(
+                    location,
+                    format!("must match exactly one schema, matched {matched}"),
+                ));
+            }
+        }
+        Schema::AnyOf(any_of) => {
+            if value.is_null() && any_of.nullable {
+                return;
+            }
+            if !any_of
+                .items
+                .iter()
+                .any(|schema| is_valid(value, schema, components, patterns))
+            {
+                errors.push(ValidationError::new(location, "must match at least one schema"));
+            }
+        }
+        Schema::AllOf(all_of) => {
+            if value.is_null() && all_of.nullable {
+                return;
+            }
+            for schema in &all_of.items {
+                validate(value, schema, components, patterns, location, errors);
+            }
+        }
+    }
+}
+
+fn join(location: &str, name: &str) -> String {
+    if location.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{location}.{name}")
+    }
+}
+
+/// Convert a parameter string to the JSON value of the type of the schema.
+fn coerce(raw: &str, schema: Option<&Schema>) -> Value {
+    let schema_type = match schema {
+        Some(Schema::Object(object)) => &object.schema_type,
+        _ => return Value::String(raw.to_owned()),
+    };
+    match schema_type {
+        SchemaType::Integer => raw
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| raw.parse::<u64>().map(Value::from))
+            .unwrap_or_else(|_| Value::String(raw.to_owned())),
+        SchemaType::Number => raw
+            .parse::<f64>()
+            .ok()
+            .and_then(|number| serde_json::Number::from_f64(number).map(Value::Number))
+            .unwrap_or_else(|| Value::String(raw.to_owned())),
+        SchemaType::Boolean => raw
+            .parse::<bool>()
+            .map(Value::Bool)
+            .unwrap_or_else(|_| Value::String(raw.to_owned())),
+        _ => Value::String(raw.to_owned()),
+    }
+}
+
+fn find_json_content<'a>(contents: impl IntoIterator<Item = (&'a String, &'a Content)>) -> Option<&'a Content> {
+    contents
+        .into_iter()
+        .find(|(content_type, _)| is_json(content_type))
+        .map(|(_, content)| content)
+}
+
+fn is_json(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+struct Route {
+    regex: Regex,
+    names: Vec<String>,
+    operations: BTreeMap<PathItemType, Operation>,
+}
+
+/// Hoop which validates requests, and optionally responses, against the [`OpenApi`] document.
+///
+/// Requests to paths or methods not documented are passed through without validation.
+pub struct OpenApiValidator {
+    routes: Vec<Route>,
+    components: Components,
+    patterns: Patterns,
+    validate_responses: bool,
+}
+impl fmt::Debug for OpenApiValidator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenApiValidator")
+            .field("routes", &self.routes.len())
+            .field("validate_responses", &self.validate_responses)
+            .finish()
+    }
+}
+impl OpenApiValidator {
+    /// Create new `OpenApiValidator` from the document.
+    pub fn new(doc: &OpenApi) -> Self {
+        let template = Regex::new(r"\{(\*{0,2})([^}:]+)[^}]*\}").expect("invalid regex");
+        let mut routes = doc
+            .paths
+            .iter()
+            .map(|(path, item)| {
+                let mut pattern = String::from("^");
+                let mut names = Vec::new();
+                let mut last = 0;
+                for captures in template.captures_iter(path.trim_end_matches('/')) {
+                    let whole = captures.get(0).expect("regex captures should not be none");
+                    pattern.push_str(&regex::escape(&path[last..whole.start()]));
+                    pattern.push_str(if captures[1].is_empty() { "([^/]+)" } else { "(.*)" });
+                    names.push(captures[2].to_owned());
+                    last = whole.end();
+                }
+                pattern.push_str(&regex::escape(&path.trim_end_matches('/')[last..]));
+                pattern.push_str("/?$");
+                Route {
+                    regex: Regex::new(&pattern).expect("invalid path pattern"),
+                    names,
+                    operations: item.operations.0.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        // Match the literal paths before the templated paths.
+        routes.sort_by_key(|route| route.names.len());
+        let mut patterns = Patterns::default();
+        for schema in doc.components.schemas.values() {
+            patterns.collect(schema);
+        }
+        for operation in routes.iter().flat_map(|route| route.operations.values()) {
+            for schema in operation
+                .parameters
+                .0
+                .iter()
+                .filter_map(|parameter| parameter.schema.as_ref())
+            {
+                patterns.collect(schema);
+            }
+            if let Some(request_body) = &operation.request_body {
+                for content in request_body.contents.values() {
+                    patterns.collect(&content.schema);
+                }
+            }
+            for response in operation.responses.values() {
+                if let RefOr::T(response) = response {
+                    for content in response.contents.values() {
+                        patterns.collect(&content.schema);
+                    }
+                }
+            }
+        }
+        for response in doc.components.responses.values() {
+            if let RefOr::T(response) = response {
+                for content in response.contents.values() {
+                    patterns.collect(&content.schema);
+                }
+            }
+        }
+        Self {
+            routes,
+            components: doc.components.clone(),
+            patterns,
+            validate_responses: false,
+        }
+    }
+
+    /// Set whether to validate responses, default is `false`. It only takes effect in debug builds.
+    #[inline]
+    pub fn validate_responses(mut self, validate_responses: bool) -> Self {
+        self.validate_responses = validate_responses;
+        self
+    }
+
+    fn find(&self, method: &Method, path: &str) -> Option<(&Operation, Vec<(&str, String)>)> {
+        let method = match *method {
+            Method::GET => PathItemType::Get,
+            Method::POST => PathItemType::Post,
+            Method::PUT => PathItemType::Put,
+            Method::DELETE => PathItemType::Delete,
+            Method::HEAD => PathItemType::Head,
+            Method::OPTIONS => PathItemType::Options,
+            Method::PATCH => PathItemType::Patch,
+            Method::TRACE => PathItemType::Trace,
+            Method::CONNECT => PathItemType::Connect,
+            _ => return None,
+        };
+        self.routes.iter().find_map(|route| {
+            let captures = route.regex.captures(path)?;
+            let operation = route.operations.get(&method)?;
+            let params = route
+                .names
+                .iter()
+                .zip(captures.iter().skip(1))
+                .filter_map(|(name, value)| Some((name.as_str(), value?.as_str().to_owned())))
+                .collect();
+            Some((operation, params))
+        })
+    }
+
+    /// Validate the request against the operation.
+    pub async fn validate_request(
+        &self,
+        req: &mut Request,
+        operation: &Operation,
+        path_params: &[(&str, String)],
+    ) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        for parameter in operation.parameters.0.iter() {
+            let schema = parameter.schema.as_ref();
+            let resolved = schema.and_then(|schema| resolve(schema, &self.components));
+            let (prefix, raw) = match parameter.parameter_in {
+                ParameterIn::Path => (
+                    "path",
+                    path_params
+                        .iter()
+                        .find(|(name, _)| *name == parameter.name)
+                        .map(|(_, value)| vec![value.clone()]),
+                ),
+                ParameterIn::Query => ("query", req.queries().get_vec(&parameter.name).cloned()),
+                ParameterIn::Header => (
+                    "header",
+                    req.headers()
+                        .get(&parameter.name)
+                        .and_then(|value| value.to_str().ok())
+                        .map(|value| vec![value.to_owned()]),
+                ),
+                ParameterIn::Cookie => (
+                    "cookie",
+                    req.cookie(&parameter.name)
+                        .map(|cookie| vec![cookie.value().to_owned()]),
+                ),
+            };
+            let location = format!("{prefix}.{}", parameter.name);
+            let Some(raw) = raw else {
+                if parameter.required == Required::True {
+                    errors.push(ValidationError::new(location, "required parameter is missing"));
+                }
+                continue;
+            };
+            let Some(schema) = schema else {
+                continue;
+            };
+            let value = match resolved {
+                Some(Schema::Array(array)) => {
+                    let items = resolve(&array.items, &self.components);
+                    Value::Array(raw.iter().map(|raw| coerce(raw, items)).collect())
+                }
+                _ => coerce(raw.first().map(String::as_str).unwrap_or_default(), resolved),
+            };
+            validate(&value, schema, &self.components, &self.patterns, &location, &mut errors);
+        }
+
+        if let Some(request_body) = &operation.request_body {
+            let payload = match req.payload().await {
+                Ok(payload) => payload.clone(),
+                Err(e) => {
+                    errors.push(ValidationError::new("body", format!("read request body failed: {e}")));
+                    return errors;
+                }
+            };
+            if payload.is_empty() {
+                if request_body.required == Some(Required::True) {
+                    errors.push(ValidationError::new("body", "request body is required"));
+                }
+                return errors;
+            }
+            let content_type = req.content_type().map(|mime| mime.essence_str().to_owned());
+            match content_type {
+                Some(content_type) if is_json(&content_type) => {
+                    if let Some(content) = find_json_content(&request_body.contents) {
+                        match serde_json::from_slice::<Value>(&payload) {
+                            Ok(value) => validate(
+                                &value,
+                                &content.schema,
+                                &self.components,
+                                &self.patterns,
+                                "body",
+                                &mut errors,
+                            ),
+                            Err(e) => errors.push(ValidationError::new("body", format!("invalid JSON: {e}"))),
+                        }
+                    }
+                }
+                Some(content_type) => {
+                    let supported = request_body.contents.is_empty()
+                        || request_body.contents.keys().any(|key| {
+                            let key = key.split(';').next().unwrap_or_default().trim();
+                            key == content_type
+                                || key == "*/*"
+                                || (key.ends_with("/*") && content_type.starts_with(key.trim_end_matches('*')))
+                        });
+                    if !supported {
+                        errors.push(ValidationError::new(
+                            "body",
+                            format!("unsupported content type `{content_type}`"),
+                        ));
+                    }
+                }
+                None => {}
+            }
+        }
+        errors
+    }
+
+    /// Validate the response against the operation.
+    pub fn validate_response(&self, res: &Response, operation: &Operation) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let status_code = res.status_code.unwrap_or(StatusCode::OK);
+        let status = status_code.as_str();
+        let range = format!("{}XX", &status[..1]);
+        let Some(response) = operation
+            .responses
+            .get(status)
+            .or_else(|| operation.responses.get(&range))
+            .or_else(|| operation.responses.get("default"))
+        else {
+            errors.push(ValidationError::new(
+                "response",
+                format!("status code {status} is not documented"),
+            ));
+            return errors;
+        };
+        let response = match response {
+            RefOr::T(response) => response,
+            RefOr::Ref(reference) => {
+                let Some(RefOr::T(response)) = reference
+                    .ref_location
+                    .strip_prefix("#/components/responses/")
+                    .and_then(|name| self.components.responses.get(name))
+                else {
+                    return errors;
+                };
+                response
+            }
+        };
+        let is_json_response = res.content_type().is_some_and(|mime| is_json(mime.essence_str()));
+        if let (true, ResBody::Once(bytes)) = (is_json_response, &res.body) {
+            if let Some(content) = find_json_content(&response.contents) {
+                match serde_json::from_slice::<Value>(bytes) {
+                    Ok(value) => validate(
+                        &value,
+                        &content.schema,
+                        &self.components,
+                        &self.patterns,
+                        "response",
+                        &mut errors,
+                    ),
+                    Err(e) => errors.push(ValidationError::new("response", format!("invalid JSON: {e}"))),
+                }
+            }
+        }
+        errors
+    }
+}
+
+#[async_trait]
+impl Handler for OpenApiValidator {
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let path = req.uri().path().to_owned();
+        let Some((operation, path_params)) = self.find(req.method(), &path) else {
+            ctrl.call_next(req, depot, res).await;
+            return;
+        };
+        let errors = self.validate_request(req, operation, &path_params).await;
+        if !errors.is_empty() {
+            let detail = errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n");
+            res.render(
+                StatusError::bad_request()
+                    .brief("Request does not match the API schema.")
+                    .detail(detail),
+            );
+            ctrl.skip_rest();
+            return;
+        }
+        ctrl.call_next(req, depot, res).await;
+        if cfg!(debug_assertions) && self.validate_responses {
+            let errors = self.validate_response(res, operation);
+            if !errors.is_empty() {
+                tracing::warn!(
+                    method = %req.method(),
+                    path,
+                    errors = ?errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                    "response does not match the API schema"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+    use salvo_core::test::TestClient;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::*;
+    use crate::extract::*;
+    use crate::{endpoint, Object, ToSchema};
+
+    #[derive(Deserialize, Serialize, ToSchema)]
+    struct Pet {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    #[endpoint]
+    async fn create_pet(limit: QueryParam<u32, true>, pet: JsonBody<Pet>) -> Json<Pet> {
+        let _ = limit;
+        Json(pet.into_inner())
+    }
+
+    fn validator() -> (OpenApiValidator, Router) {
+        let router = Router::with_path("pets/<id>").post(create_pet);
+        let doc = OpenApi::new("test api", "0.0.1").merge_router(&router);
+        (OpenApiValidator::new(&doc), router)
+    }
+
+    async fn violations(validator: &OpenApiValidator, mut req: Request) -> Vec<String> {
+        let path = req.uri().path().to_owned();
+        let (operation, path_params) = validator
+            .find(req.method(), &path)
+            .expect("operation should be documented");
+        validator
+            .validate_request(&mut req, operation, &path_params)
+            .await
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_validate_request() {
+        let (validator, router) = validator();
+        let service = Service::new(Router::new().hoop(validator).push(router));
+
+        let res = TestClient::post("http://127.0.0.1:5801/pets/1?limit=10")
+            .json(&json!({"name": "Tom", "tags": ["cat"]}))
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+
+        let res = TestClient::post("http://127.0.0.1:5801/pets/1?limit=-1")
+            .json(&json!({"name": 1, "tags": "cat"}))
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_validate_request_violations() {
+        let (validator, _) = validator();
+
+        let req = TestClient::post("http://127.0.0.1:5801/pets/1?limit=-1")
+            .json(&json!({"name": 1, "tags": "cat"}))
+            .build();
+        let errors = violations(&validator, req).await;
+        assert!(errors.contains(&"query.limit: must be greater than or equal to 0".to_owned()));
+        assert!(errors.contains(&"body.name: expected string, found integer".to_owned()));
+        assert!(errors.contains(&"body.tags: expected array, found string".to_owned()));
+
+        let req = TestClient::post("http://127.0.0.1:5801/pets/1")
+            .json(&json!({"tags": []}))
+            .build();
+        let errors = violations(&validator, req).await;
+        assert!(errors.contains(&"query.limit: required parameter is missing".to_owned()));
+        assert!(errors.contains(&"body.name: required property is missing".to_owned()));
+    }
+
+    #[test]
+    fn test_validate_value() {
+        let schema = RefOr::T(Schema::Object(
+            Object::new()
+                .schema_type(SchemaType::String)
+                .min_length(2)
+                .pattern("^[a-z]+$")
+                .nullable(true),
+        ));
+        let components = Components::new();
+        assert!(validate_value(&json!("abc"), &schema, &components, "name").is_empty());
+        assert!(validate_value(&json!(null), &schema, &components, "name").is_empty());
+        assert_eq!(
+            validate_value(&json!("A"), &schema, &components, "name"),
+            vec![
+                ValidationError::new("name", "must be at least 2 characters"),
+                ValidationError::new("name", "must match pattern `^[a-z]+$`"),
+            ]
+        );
+    }
+}
@@ -8,5 +8,5 @@
 mod metrics;
 mod tracing;
 
-pub use metrics::Metrics;
+pub use metrics::{ConnMetrics, Metrics};
 pub use tracing::Tracing;
@@ -1,8 +1,11 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 
-use opentelemetry::metrics::{Counter, Histogram, Unit};
+use opentelemetry::metrics::{Counter, Histogram, Unit, UpDownCounter};
 use opentelemetry::{global, KeyValue};
 use opentelemetry_semantic_conventions::trace;
+use salvo_core::fuse::{FuseEvent, FuseFactory, FuseInfo, Fusewire};
 use salvo_core::http::ResBody;
 use salvo_core::prelude::*;
 
@@ -68,3 +71,197 @@ impl Handler for Metrics {
         self.duration.record(elapsed.as_secs_f64() * 1000.0, &labels);
     }
 }
+
+/// Connection metrics with OpenTelemetry.
+///
+/// `ConnMetrics` is a [`FuseFactory`] set on the server with [`Server::fuse_factory`], it records metrics of the
+/// connection layer which request metrics can not see:
+///
+/// | Name | Kind | Description |
+/// | --- | --- | --- |
+/// | `salvo_connection_accepted` | Counter | Accepted connections. |
+/// | `salvo_connection_active` | UpDownCounter | Open connections. |
+/// | `salvo_connection_tls_handshake_failures` | Counter | Connections closed before the TLS handshake completed. |
+/// | `salvo_connection_read_bytes` | Counter | Bytes read from connections. |
+/// | `salvo_connection_written_bytes` | Counter | Bytes written to connections. |
+///
+/// All metrics have a `protocol` attribute, which is `tcp` for plain TCP connections, `tls` for TCP connections
+/// which started a TLS handshake and `quic` for QUIC connections.
+///
+/// [`Server::fuse_factory`]: salvo_core::Server::fuse_factory
+///
+/// # Example
+///
+/// ```no_run
+/// use salvo_core::fuse::FlexFactory;
+/// use salvo_core::prelude::*;
+/// use salvo_otel::ConnMetrics;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+///     let metrics = ConnMetrics::new().wrap(FlexFactory::new());
+///     Server::new(acceptor).fuse_factory(metrics).serve(Router::new()).await;
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ConnMetrics {
+    instruments: Arc<ConnInstruments>,
+    inner: Option<Arc<dyn FuseFactory + Send + Sync>>,
+}
+
+struct ConnInstruments {
+    accepted: Counter<u64>,
+    active: UpDownCounter<i64>,
+    tls_handshake_failures: Counter<u64>,
+    read_bytes: Counter<u64>,
+    written_bytes: Counter<u64>,
+}
+
+impl Default for ConnMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnMetrics {
+    /// Create `ConnMetrics` with the global meter.
+    pub fn new() -> Self {
+        let meter = global::meter("salvo");
+        Self {
+            instruments: Arc::new(ConnInstruments {
+                accepted: meter
+                    .u64_counter("salvo_connection_accepted")
+                    .with_description("accepted connection count (since start of service)")
+                    .init(),
+                active: meter
+                    .i64_up_down_counter("salvo_connection_active")
+                    .with_description("open connection count")
+                    .init(),
+                tls_handshake_failures: meter
+                    .u64_counter("salvo_connection_tls_handshake_failures")
+                    .with_description("failed tls handshake count (since start of service)")
+                    .init(),
+                read_bytes: meter
+                    .u64_counter("salvo_connection_read_bytes")
+                    .with_unit(Unit::new("By"))
+                    .with_description("bytes read from connections (since start of service)")
+                    .init(),
+                written_bytes: meter
+                    .u64_counter("salvo_connection_written_bytes")
+                    .with_unit(Unit::new("By"))
+                    .with_description("bytes written to connections (since start of service)")
+                    .init(),
+            }),
+            inner: None,
+        }
+    }
+
+    /// Forward the events of connections to the fusewires of another factory, such as a
+    /// [`FlexFactory`](salvo_core::fuse::FlexFactory).
+    pub fn wrap(mut self, factory: impl FuseFactory + Send + Sync + 'static) -> Self {
+        self.inner = Some(Arc::new(factory));
+        self
+    }
+}
+
+impl FuseFactory for ConnMetrics {
+    fn create(&self, info: FuseInfo) -> Arc<dyn Fusewire + Sync + Send + 'static> {
+        let state = if info.trans_proto.is_quic() {
+            ConnState::Quic
+        } else {
+            ConnState::Tcp
+        };
+        let attributes = [state.attribute()];
+        self.instruments.accepted.add(1, &attributes);
+        self.instruments.active.add(1, &attributes);
+        Arc::new(MetricsFusewire {
+            instruments: self.instruments.clone(),
+            state: AtomicU8::new(state as u8),
+            inner: self.inner.as_ref().map(|inner| inner.create(info)),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum ConnState {
+    Tcp,
+    TlsHandshaking,
+    Tls,
+    Quic,
+}
+impl ConnState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::TlsHandshaking,
+            2 => Self::Tls,
+            3 => Self::Quic,
+            _ => Self::Tcp,
+        }
+    }
+    fn attribute(self) -> KeyValue {
+        let protocol = match self {
+            Self::Tcp => "tcp",
+            Self::TlsHandshaking | Self::Tls => "tls",
+            Self::Quic => "quic",
+        };
+        KeyValue::new("protocol", protocol)
+    }
+}
+
+struct MetricsFusewire {
+    instruments: Arc<ConnInstruments>,
+    state: AtomicU8,
+    inner: Option<Arc<dyn Fusewire + Sync + Send + 'static>>,
+}
+impl MetricsFusewire {
+    fn state(&self) -> ConnState {
+        ConnState::from_u8(self.state.load(Ordering::Relaxed))
+    }
+}
+#[async_trait]
+impl Fusewire for MetricsFusewire {
+    fn event(&self, event: FuseEvent) {
+        match event {
+            FuseEvent::TlsHandshaking => {
+                let previous = ConnState::from_u8(self.state.swap(ConnState::TlsHandshaking as u8, Ordering::Relaxed));
+                if previous == ConnState::Tcp {
+                    // The connection moves from plain tcp to tls.
+                    self.instruments.active.add(-1, &[previous.attribute()]);
+                    self.instruments.active.add(1, &[ConnState::TlsHandshaking.attribute()]);
+                }
+            }
+            FuseEvent::TlsHandshaked => {
+                self.state.store(ConnState::Tls as u8, Ordering::Relaxed);
+            }
+            FuseEvent::ReadData(len) => {
+                self.instruments.read_bytes.add(len as u64, &[self.state().attribute()]);
+            }
+            FuseEvent::WriteData(len) => {
+                self.instruments
+                    .written_bytes
+                    .add(len as u64, &[self.state().attribute()]);
+            }
+            _ => {}
+        }
+        if let Some(inner) = &self.inner {
+            inner.event(event);
+        }
+    }
+    async fn fused(&self) {
+        match &self.inner {
+            Some(inner) => inner.fused().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+impl Drop for MetricsFusewire {
+    fn drop(&mut self) {
+        let state = self.state();
+        if state == ConnState::TlsHandshaking {
+            self.instruments.tls_handshake_failures.add(1, &[state.attribute()]);
+        }
+        self.instruments.active.add(-1, &[state.attribute()]);
+    }
+}
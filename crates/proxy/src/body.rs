@@ -0,0 +1,115 @@
+use std::future::Future;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::stream::Stream;
+use hyper::body::{Bytes, Frame};
+use salvo_core::http::body::BytesFrame;
+use salvo_core::http::ResBody;
+use tokio::time::Sleep;
+
+/// Response body streamed from upstream frame by frame.
+///
+/// Frames are passed through as soon as they are received, so at most one frame is held in memory and long-lived
+/// streaming responses like Server-Sent Events are never buffered. Data frames larger than `max_chunk_size` are split
+/// without copying, and the stream fails when no frame is received for `idle_timeout`.
+pub(crate) struct StreamingBody {
+    body: ResBody,
+    max_chunk_size: Option<usize>,
+    idle_timeout: Option<Duration>,
+    sleep: Option<Pin<Box<Sleep>>>,
+    remaining: Option<Frame<Bytes>>,
+}
+impl StreamingBody {
+    pub(crate) fn new(body: ResBody, max_chunk_size: Option<usize>, idle_timeout: Option<Duration>) -> Self {
+        Self {
+            body,
+            max_chunk_size: max_chunk_size.filter(|size| *size > 0),
+            idle_timeout,
+            sleep: idle_timeout.map(|timeout| Box::pin(tokio::time::sleep(timeout))),
+            remaining: None,
+        }
+    }
+
+    fn split(&mut self, frame: Frame<Bytes>) -> Frame<Bytes> {
+        let Some(max_chunk_size) = self.max_chunk_size else {
+            return frame;
+        };
+        match frame.into_data() {
+            Ok(mut data) => {
+                if data.len() > max_chunk_size {
+                    let chunk = data.split_to(max_chunk_size);
+                    self.remaining = Some(Frame::data(data));
+                    Frame::data(chunk)
+                } else {
+                    Frame::data(data)
+                }
+            }
+            Err(frame) => frame,
+        }
+    }
+}
+impl Stream for StreamingBody {
+    type Item = IoResult<BytesFrame>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(frame) = this.remaining.take() {
+            return Poll::Ready(Some(Ok(BytesFrame(this.split(frame)))));
+        }
+        match Pin::new(&mut this.body).poll_next(cx) {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let (Some(sleep), Some(timeout)) = (this.sleep.as_mut(), this.idle_timeout) {
+                    sleep.as_mut().reset(tokio::time::Instant::now() + timeout);
+                }
+                Poll::Ready(Some(Ok(BytesFrame(this.split(frame)))))
+            }
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => match this.sleep.as_mut() {
+                Some(sleep) => match sleep.as_mut().poll(cx) {
+                    Poll::Ready(()) => Poll::Ready(Some(Err(IoError::new(
+                        ErrorKind::TimedOut,
+                        "proxied response idle timeout",
+                    )))),
+                    Poll::Pending => Poll::Pending,
+                },
+                None => Poll::Pending,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_idle_timeout() {
+        let (mut tx, body) = ResBody::channel();
+        let mut body = StreamingBody::new(body, None, Some(Duration::from_millis(50)));
+        tx.send_data("data: hello\n\n").await.unwrap();
+        match body.next().await {
+            Some(Ok(frame)) => assert_eq!(frame.data_ref().map(|d| &d[..]), Some(&b"data: hello\n\n"[..])),
+            _ => panic!("expected data frame"),
+        }
+        match body.next().await {
+            Some(Err(e)) => assert_eq!(e.kind(), ErrorKind::TimedOut),
+            _ => panic!("expected idle timeout"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_chunk_size() {
+        let body = ResBody::Once(vec![7u8; 10 * 1024 + 1].into());
+        let sizes = StreamingBody::new(body, Some(4096), None)
+            .map(|frame| frame.unwrap().data_ref().map(|d| d.len()).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(sizes, vec![4096, 4096, 2049]);
+    }
+}
@@ -0,0 +1,751 @@
+//! HTTP caching of proxied responses.
+//!
+//! [`HttpCache`] makes [`Proxy`](crate::Proxy) a caching reverse proxy following the rules of
+//! [RFC 9111](https://www.rfc-editor.org/rfc/rfc9111):
+//!
+//! - Only responses to `GET` requests are stored, `HEAD` requests are answered from them. Responses are not stored
+//!   when `no-store` is sent in the request or the response, when the response is `private` to a client, or when the
+//!   request is authorized and the response does not allow it explicitly. A shared cache removes `Set-Cookie` from
+//!   stored responses, the cookies are only sent to the client which caused the response to be stored.
+//! - The freshness lifetime comes from `s-maxage`, `max-age` or `Expires`. Without them it is estimated from
+//!   `Last-Modified` for heuristically cacheable status codes, see [`HttpCache::heuristic_fraction`].
+//! - The age of stored responses is computed from `Age`, `Date` and the time they were received. Requests can
+//!   constrain it with `max-age`, `min-fresh`, `max-stale`, `no-cache` and `only-if-cached`.
+//! - Stale responses are revalidated with `If-None-Match` and `If-Modified-Since`, a `304 Not Modified` from the
+//!   upstream refreshes the stored response which is then served.
+//! - Responses with `Vary` are only served to requests with the same values of the listed headers, a response
+//!   varying on `*` is never stored.
+//! - Successful responses to unsafe methods such as `POST` invalidate the stored response of their URI.
+//! - Conditional requests of clients are answered with `304 Not Modified` when the stored response matches.
+//!
+//! Responses are stored in a [`CacheStore`] of `salvo-cache`, such as its `MokaStore`, with keys made of the
+//! host and the path and query of requests. Bodies larger than [`HttpCache::max_body_size`] are passed through
+//! without being stored.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_cache::MokaStore;
+//! use salvo_core::prelude::*;
+//! use salvo_proxy::{HttpCache, Proxy};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let store = MokaStore::builder().time_to_live(Duration::from_secs(3600)).build();
+//!     let proxy = Proxy::use_hyper_client("https://www.rust-lang.org").cache(HttpCache::new(store));
+//!     let router = Router::with_path("<**rest>").goal(proxy);
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::collections::VecDeque;
+use std::io::Result as IoResult;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::future::{BoxFuture, FutureExt};
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use hyper::body::{Bytes, Frame};
+use salvo_cache::{CacheStore, CachedBody, CachedEntry};
+use salvo_core::http::body::BytesFrame;
+use salvo_core::http::header::{
+    HeaderMap, HeaderName, HeaderValue, AGE, AUTHORIZATION, CACHE_CONTROL, CONTENT_LENGTH, ETAG, EXPIRES, HOST,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, PRAGMA, SET_COOKIE, VARY,
+};
+use salvo_core::http::headers::{
+    CacheControl, Date, ETag, Expires, HeaderMapExt, IfModifiedSince, IfNoneMatch, LastModified,
+};
+use salvo_core::http::{Method, ResBody, StatusCode};
+use salvo_core::Request;
+
+use crate::HyperResponse;
+
+/// Prefix of the headers holding the cache metadata in stored responses, they are never sent to clients.
+const INTERNAL_PREFIX: &str = "x-salvo-cache-";
+const REQUEST_TIME: HeaderName = HeaderName::from_static("x-salvo-cache-request-time");
+const RESPONSE_TIME: HeaderName = HeaderName::from_static("x-salvo-cache-response-time");
+const INVALID: HeaderName = HeaderName::from_static("x-salvo-cache-invalid");
+
+/// Object safe wrapper of [`CacheStore`].
+trait DynStore: Send + Sync + 'static {
+    fn load(&self, key: String) -> BoxFuture<'_, Option<CachedEntry>>;
+    fn save(&self, key: String, entry: CachedEntry) -> BoxFuture<'_, ()>;
+}
+impl<S> DynStore for S
+where
+    S: CacheStore<Key = String>,
+{
+    fn load(&self, key: String) -> BoxFuture<'_, Option<CachedEntry>> {
+        async move { self.load_entry(&key).await }.boxed()
+    }
+    fn save(&self, key: String, entry: CachedEntry) -> BoxFuture<'_, ()> {
+        async move {
+            if let Err(e) = self.save_entry(key, entry).await {
+                tracing::error!(error = ?e, "save cached response failed");
+            }
+        }
+        .boxed()
+    }
+}
+
+/// HTTP cache of proxied responses.
+///
+/// View [module level documentation](index.html) for more details.
+pub struct HttpCache {
+    store: Box<dyn DynStore>,
+    shared: bool,
+    heuristic_fraction: f64,
+    max_heuristic_lifetime: Duration,
+    max_body_size: usize,
+}
+
+impl HttpCache {
+    /// Create a new shared `HttpCache` backed by `store`.
+    pub fn new(store: impl CacheStore<Key = String>) -> Self {
+        Self {
+            store: Box::new(store),
+            shared: true,
+            heuristic_fraction: 0.1,
+            max_heuristic_lifetime: Duration::from_secs(24 * 60 * 60),
+            max_body_size: 1024 * 1024,
+        }
+    }
+
+    /// Sets whether the cache is shared between clients, the default is `true`.
+    ///
+    /// A shared cache honors `s-maxage` and does not store responses marked `private` or responses to authorized
+    /// requests which are not explicitly allowed to be stored. It also removes `Set-Cookie` from stored responses.
+    #[inline]
+    pub fn shared(mut self, shared: bool) -> Self {
+        self.shared = shared;
+        self
+    }
+
+    /// Sets the fraction of the time elapsed since `Last-Modified` used as heuristic freshness lifetime, the
+    /// default is `0.1`.
+    #[inline]
+    pub fn heuristic_fraction(mut self, fraction: f64) -> Self {
+        self.heuristic_fraction = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the max heuristic freshness lifetime, the default is one day.
+    #[inline]
+    pub fn max_heuristic_lifetime(mut self, lifetime: Duration) -> Self {
+        self.max_heuristic_lifetime = lifetime;
+        self
+    }
+
+    /// Sets the max size of stored bodies, the default is 1 MiB.
+    #[inline]
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Find the stored response usable for `req`.
+    pub(crate) async fn lookup(&self, req: &Request) -> Lookup {
+        if !matches!(*req.method(), Method::GET | Method::HEAD) {
+            return Lookup::Miss;
+        }
+        let directives = req.headers().typed_get::<CacheControl>();
+        let only_if_cached = directives.as_ref().is_some_and(|d| d.only_if_cached());
+        let key = primary_key(req);
+        let entry = match self.store.load(key.clone()).await {
+            Some(entry) if vary_matches(entry.headers(), req.headers()) => entry,
+            _ if only_if_cached => return Lookup::Unsatisfiable,
+            _ => return Lookup::Miss,
+        };
+        let stored = Stored { key, entry };
+        if self.is_fresh(&stored.entry, req.headers(), directives.as_ref()) {
+            Lookup::Fresh(stored)
+        } else if only_if_cached {
+            Lookup::Unsatisfiable
+        } else {
+            Lookup::Stale(stored)
+        }
+    }
+
+    /// Stores, refreshes or invalidates responses according to the `response` received from the upstream.
+    ///
+    /// `stored` is the response which has been revalidated by the proxied request, if any.
+    pub(crate) async fn on_response(
+        &self,
+        req: &Request,
+        stored: Option<Stored>,
+        request_time: SystemTime,
+        response: HyperResponse,
+    ) -> HyperResponse {
+        let status = response.status();
+        if !req.method().is_safe() {
+            if status.is_success() || status.is_redirection() {
+                self.invalidate(req).await;
+            }
+            return response;
+        }
+        if status == StatusCode::NOT_MODIFIED {
+            return match stored {
+                Some(stored) => self.refresh(req, stored, request_time, response).await,
+                None => response,
+            };
+        }
+        if req.method() != Method::GET || !self.is_storable(req.headers(), status, response.headers()) {
+            return response;
+        }
+        self.save(req, request_time, response).await
+    }
+
+    async fn invalidate(&self, req: &Request) {
+        let key = primary_key(req);
+        if let Some(mut entry) = self.store.load(key.clone()).await {
+            entry.headers.insert(INVALID, HeaderValue::from_static("1"));
+            self.store.save(key, entry).await;
+        }
+    }
+
+    /// Update the stored response with the headers of a `304 Not Modified` response, RFC 9111 section 4.3.4.
+    async fn refresh(
+        &self,
+        req: &Request,
+        stored: Stored,
+        request_time: SystemTime,
+        response: HyperResponse,
+    ) -> HyperResponse {
+        let Stored { key, mut entry } = stored;
+        for name in response.headers().keys() {
+            if *name == CONTENT_LENGTH || name.as_str().starts_with(INTERNAL_PREFIX) {
+                continue;
+            }
+            entry.headers.remove(name);
+            for value in response.headers().get_all(name) {
+                entry.headers.append(name.clone(), value.clone());
+            }
+        }
+        entry.headers.remove(INVALID);
+        set_times(&mut entry.headers, request_time, SystemTime::now());
+        let status = entry.status().unwrap_or(StatusCode::OK);
+        if self.is_storable(req.headers(), status, entry.headers()) {
+            let mut stored = entry.clone();
+            self.strip_private_headers(&mut stored.headers);
+            self.store.save(key.clone(), stored).await;
+        }
+        Stored { key, entry }.into_response(req)
+    }
+
+    /// Read the body of `response` and store it, the body is passed through if it is larger than `max_body_size`.
+    async fn save(&self, req: &Request, request_time: SystemTime, response: HyperResponse) -> HyperResponse {
+        let (parts, mut body) = response.into_parts();
+        let too_large = parts
+            .headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+            .is_some_and(|len| len > self.max_body_size as u64);
+        if too_large {
+            return HyperResponse::from_parts(parts, body);
+        }
+        let mut chunks = VecDeque::new();
+        let mut size = 0;
+        while let Some(frame) = body.next().await {
+            let data = match frame.map(Frame::into_data) {
+                Ok(Ok(data)) => data,
+                // Trailers are not stored, responses having them are passed through.
+                Ok(Err(frame)) => return HyperResponse::from_parts(parts, resume(chunks, Ok(frame), body)),
+                Err(e) => return HyperResponse::from_parts(parts, resume(chunks, Err(e), body)),
+            };
+            size += data.len();
+            if size > self.max_body_size {
+                return HyperResponse::from_parts(parts, resume(chunks, Ok(Frame::data(data)), body));
+            }
+            chunks.push_back(data);
+        }
+
+        let mut headers = parts.headers.clone();
+        self.strip_private_headers(&mut headers);
+        set_times(&mut headers, request_time, SystemTime::now());
+        for name in vary_names(&parts.headers) {
+            if let (Ok(internal), Some(value)) = (
+                HeaderName::from_bytes(format!("{INTERNAL_PREFIX}vary-{name}").as_bytes()),
+                joined_value(req.headers(), &name).and_then(|value| HeaderValue::from_str(&value).ok()),
+            ) {
+                headers.insert(internal, value);
+            }
+        }
+        let entry = CachedEntry::new(Some(parts.status), headers, CachedBody::Chunks(chunks.clone()));
+        self.store.save(primary_key(req), entry).await;
+        HyperResponse::from_parts(parts, ResBody::Chunks(chunks))
+    }
+
+    /// Remove the headers which must not be served to other clients from a response to store.
+    fn strip_private_headers(&self, headers: &mut HeaderMap) {
+        if self.shared {
+            headers.remove(SET_COOKIE);
+        }
+    }
+
+    /// Whether a response is allowed to be stored, RFC 9111 section 3.
+    fn is_storable(&self, req_headers: &HeaderMap, status: StatusCode, headers: &HeaderMap) -> bool {
+        if status.is_informational() || status == StatusCode::PARTIAL_CONTENT || status == StatusCode::NOT_MODIFIED {
+            return false;
+        }
+        if req_headers
+            .typed_get::<CacheControl>()
+            .is_some_and(|directives| directives.no_store())
+        {
+            return false;
+        }
+        let directives = headers.typed_get::<CacheControl>();
+        if let Some(directives) = &directives {
+            if directives.no_store() || (self.shared && directives.private()) {
+                return false;
+            }
+        }
+        let explicitly_allowed = directives.as_ref().is_some_and(|directives| {
+            directives.public() || directives.must_revalidate() || (self.shared && directives.s_max_age().is_some())
+        });
+        if self.shared && req_headers.contains_key(AUTHORIZATION) && !explicitly_allowed {
+            return false;
+        }
+        if vary_names(headers).iter().any(|name| name == "*") {
+            return false;
+        }
+        explicitly_allowed
+            || directives
+                .as_ref()
+                .is_some_and(|directives| directives.max_age().is_some() || directives.no_cache())
+            || headers.contains_key(EXPIRES)
+            || is_heuristically_cacheable(status)
+    }
+
+    /// Whether the stored response can be served without validation, RFC 9111 section 4.2.
+    fn is_fresh(&self, entry: &CachedEntry, req_headers: &HeaderMap, directives: Option<&CacheControl>) -> bool {
+        let headers = entry.headers();
+        if headers.contains_key(INVALID) {
+            return false;
+        }
+        let response_directives = headers.typed_get::<CacheControl>();
+        if response_directives.as_ref().is_some_and(|d| d.no_cache()) {
+            return false;
+        }
+        match directives {
+            Some(directives) if directives.no_cache() => return false,
+            None if req_headers.get_all(PRAGMA).iter().any(|value| value == "no-cache") => return false,
+            _ => {}
+        }
+
+        let lifetime = self.freshness_lifetime(entry, response_directives.as_ref());
+        let age = current_age(headers, SystemTime::now());
+        let Some(directives) = directives else {
+            return age < lifetime;
+        };
+        if directives.max_age().is_some_and(|max_age| age > max_age) {
+            return false;
+        }
+        let age = age + directives.min_fresh().unwrap_or_default();
+        if age < lifetime {
+            return true;
+        }
+        let must_revalidate = response_directives.as_ref().is_some_and(|d| {
+            d.must_revalidate() || (self.shared && (proxy_revalidate(headers) || d.s_max_age().is_some()))
+        });
+        !must_revalidate
+            && directives
+                .max_stale()
+                .is_some_and(|max_stale| age - lifetime <= max_stale)
+    }
+
+    fn freshness_lifetime(&self, entry: &CachedEntry, directives: Option<&CacheControl>) -> Duration {
+        if let Some(directives) = directives {
+            if let Some(lifetime) = directives.s_max_age().filter(|_| self.shared) {
+                return lifetime;
+            }
+            if let Some(lifetime) = directives.max_age() {
+                return lifetime;
+            }
+        }
+        let headers = entry.headers();
+        let date = headers
+            .typed_get::<Date>()
+            .map(SystemTime::from)
+            .or_else(|| header_time(headers, &RESPONSE_TIME))
+            .unwrap_or(UNIX_EPOCH);
+        if headers.contains_key(EXPIRES) {
+            // An invalid `Expires` means the response has already expired.
+            return headers
+                .typed_get::<Expires>()
+                .and_then(|expires| SystemTime::from(expires).duration_since(date).ok())
+                .unwrap_or_default();
+        }
+        let cacheable = is_heuristically_cacheable(entry.status().unwrap_or(StatusCode::OK))
+            || directives.is_some_and(|directives| directives.public());
+        match headers.typed_get::<LastModified>() {
+            Some(last_modified) if cacheable => date
+                .duration_since(last_modified.into())
+                .unwrap_or_default()
+                .mul_f64(self.heuristic_fraction)
+                .min(self.max_heuristic_lifetime),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Result of [`HttpCache::lookup`].
+pub(crate) enum Lookup {
+    /// The stored response can be served without contacting the upstream.
+    Fresh(Stored),
+    /// The stored response must be validated by the upstream.
+    Stale(Stored),
+    /// No response is stored and the request is `only-if-cached`.
+    Unsatisfiable,
+    /// No response is stored.
+    Miss,
+}
+
+/// A response loaded from the store.
+pub(crate) struct Stored {
+    key: String,
+    entry: CachedEntry,
+}
+
+impl Stored {
+    /// Add the validators of the stored response to the proxied request.
+    ///
+    /// Returns `false` if the request has its own validators or if the stored response has no validator, the
+    /// response of the upstream is then not a revalidation of the stored response.
+    pub(crate) fn add_validators(&self, headers: &mut HeaderMap) -> bool {
+        if headers.contains_key(IF_NONE_MATCH) || headers.contains_key(IF_MODIFIED_SINCE) {
+            return false;
+        }
+        if let Some(etag) = self.entry.headers().get(ETAG) {
+            headers.insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = self.entry.headers().get(LAST_MODIFIED) {
+            headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+        headers.contains_key(IF_NONE_MATCH) || headers.contains_key(IF_MODIFIED_SINCE)
+    }
+
+    /// Build the response served to the client, with its current age.
+    pub(crate) fn into_response(self, req: &Request) -> HyperResponse {
+        let age = current_age(self.entry.headers(), SystemTime::now());
+        let CachedEntry {
+            status,
+            mut headers,
+            body,
+            ..
+        } = self.entry;
+        let internal = headers
+            .keys()
+            .filter(|name| name.as_str().starts_with(INTERNAL_PREFIX))
+            .cloned()
+            .collect::<Vec<_>>();
+        for name in internal {
+            headers.remove(name);
+        }
+        headers.insert(AGE, HeaderValue::from(age.as_secs()));
+        let status = status.unwrap_or(StatusCode::OK);
+        let not_modified = status == StatusCode::OK && is_not_modified(req.headers(), &headers);
+        let mut response = if not_modified {
+            let mut response = HyperResponse::new(ResBody::None);
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            response
+        } else {
+            let mut response = HyperResponse::new(body.into());
+            *response.status_mut() = status;
+            response
+        };
+        *response.headers_mut() = headers;
+        response
+    }
+}
+
+/// Key of the responses of the target URI of `req`.
+fn primary_key(req: &Request) -> String {
+    let authority = req
+        .uri()
+        .authority()
+        .map(|authority| authority.as_str())
+        .or_else(|| req.headers().get(HOST).and_then(|host| host.to_str().ok()))
+        .unwrap_or_default();
+    let path = req.uri().path_and_query().map(|path| path.as_str()).unwrap_or("/");
+    format!("{authority}{path}")
+}
+
+/// Status codes defined as heuristically cacheable, RFC 9110 section 15.1.
+fn is_heuristically_cacheable(status: StatusCode) -> bool {
+    matches!(
+        status.as_u16(),
+        200 | 203 | 204 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+    )
+}
+
+fn vary_names(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Whether the response has the `proxy-revalidate` directive, which has no accessor in [`CacheControl`].
+fn proxy_revalidate(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("proxy-revalidate"))
+}
+
+fn joined_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// Whether the request has the same values as the stored response for the headers listed in its `Vary`.
+fn vary_matches(headers: &HeaderMap, req_headers: &HeaderMap) -> bool {
+    vary_names(headers).iter().all(|name| {
+        name != "*"
+            && joined_value(headers, &format!("{INTERNAL_PREFIX}vary-{name}")) == joined_value(req_headers, name)
+    })
+}
+
+fn is_not_modified(req_headers: &HeaderMap, headers: &HeaderMap) -> bool {
+    if let Some(if_none_match) = req_headers.typed_get::<IfNoneMatch>() {
+        return headers
+            .typed_get::<ETag>()
+            .is_some_and(|etag| !if_none_match.precondition_passes(&etag));
+    }
+    match (
+        req_headers.typed_get::<IfModifiedSince>(),
+        headers.typed_get::<LastModified>(),
+    ) {
+        (Some(since), Some(last_modified)) => !since.is_modified(last_modified.into()),
+        _ => false,
+    }
+}
+
+fn set_times(headers: &mut HeaderMap, request_time: SystemTime, response_time: SystemTime) {
+    for (name, time) in [(REQUEST_TIME, request_time), (RESPONSE_TIME, response_time)] {
+        let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        headers.insert(name, HeaderValue::from(secs));
+    }
+}
+
+fn header_time(headers: &HeaderMap, name: &HeaderName) -> Option<SystemTime> {
+    let secs = headers.get(name)?.to_str().ok()?.parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Current age of a stored response, RFC 9111 section 4.2.3.
+fn current_age(headers: &HeaderMap, now: SystemTime) -> Duration {
+    let response_time = header_time(headers, &RESPONSE_TIME).unwrap_or(now);
+    let request_time = header_time(headers, &REQUEST_TIME).unwrap_or(response_time);
+    let date = headers
+        .typed_get::<Date>()
+        .map(SystemTime::from)
+        .unwrap_or(response_time);
+    let age_value = headers
+        .get(AGE)
+        .and_then(|value| value.to_str().ok()?.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_default();
+    let apparent_age = response_time.duration_since(date).unwrap_or_default();
+    let response_delay = response_time.duration_since(request_time).unwrap_or_default();
+    let corrected_initial_age = apparent_age.max(age_value + response_delay);
+    corrected_initial_age + now.duration_since(response_time).unwrap_or_default()
+}
+
+/// Body made of the chunks already read, the next frame and the rest of the body.
+fn resume(chunks: VecDeque<Bytes>, next: IoResult<Frame<Bytes>>, rest: ResBody) -> ResBody {
+    let read = stream::iter(chunks.into_iter().map(|chunk| Ok(Frame::data(chunk))).chain([next]));
+    ResBody::stream(read.chain(rest).map_ok(BytesFrame))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::hash::Hash;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use hyper::upgrade::OnUpgrade;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+    use crate::{Client, HyperRequest, Proxy};
+
+    #[derive(Default)]
+    struct MemoryStore(Mutex<HashMap<String, CachedEntry>>);
+    impl CacheStore for MemoryStore {
+        type Error = Infallible;
+        type Key = String;
+
+        async fn load_entry<Q>(&self, key: &Q) -> Option<CachedEntry>
+        where
+            Self::Key: Borrow<Q>,
+            Q: Hash + Eq + Sync,
+        {
+            self.0.lock().unwrap().get(key).cloned()
+        }
+        async fn save_entry(&self, key: Self::Key, entry: CachedEntry) -> Result<(), Self::Error> {
+            self.0.lock().unwrap().insert(key, entry);
+            Ok(())
+        }
+    }
+
+    /// Upstream answering with an `ETag` and the given `Cache-Control`, and `304` to matching validators.
+    #[derive(Clone)]
+    struct Upstream {
+        calls: Arc<AtomicUsize>,
+        cache_control: &'static str,
+    }
+    impl Client for Upstream {
+        type Error = Infallible;
+
+        async fn execute(&self, req: HyperRequest, _upgraded: Option<OnUpgrade>) -> Result<HyperResponse, Infallible> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut response = if req.headers().get(IF_NONE_MATCH).is_some_and(|etag| etag == "\"v1\"") {
+                let mut response = HyperResponse::new(ResBody::None);
+                *response.status_mut() = StatusCode::NOT_MODIFIED;
+                response
+            } else {
+                HyperResponse::new(ResBody::Once(format!("{} {calls}", req.method()).into()))
+            };
+            let headers = response.headers_mut();
+            headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+            headers.insert("cache-control", HeaderValue::from_static(self.cache_control));
+            headers.insert(VARY, HeaderValue::from_static("accept-language"));
+            if let Ok(cookie) = HeaderValue::from_str(&format!("session={calls}")) {
+                headers.insert(SET_COOKIE, cookie);
+            }
+            Ok(response)
+        }
+    }
+
+    fn service(cache_control: &'static str) -> (Service, Arc<AtomicUsize>) {
+        service_with_cache(cache_control, HttpCache::new(MemoryStore::default()))
+    }
+
+    fn service_with_cache(cache_control: &'static str, cache: HttpCache) -> (Service, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let upstream = Upstream {
+            calls: calls.clone(),
+            cache_control,
+        };
+        let proxy = Proxy::new("http://upstream.local", upstream).cache(cache);
+        (Service::new(Router::with_path("<**rest>").goal(proxy)), calls)
+    }
+
+    #[tokio::test]
+    async fn test_fresh_and_vary() {
+        let (service, calls) = service("max-age=60");
+        let url = "http://127.0.0.1:5801/data";
+        let mut res = TestClient::get(url).send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "GET 1");
+
+        let mut res = TestClient::get(url).send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        assert!(res.headers().contains_key(AGE));
+        assert!(!res.headers().contains_key(RESPONSE_TIME));
+        assert_eq!(res.take_string().await.unwrap(), "GET 1");
+
+        let res = TestClient::get(url)
+            .add_header(IF_NONE_MATCH, "\"v1\"", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let mut res = TestClient::get(url)
+            .add_header("accept-language", "fr", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "GET 2");
+
+        TestClient::post(url).send(&service).await;
+        let mut res = TestClient::get(url)
+            .add_header("accept-language", "fr", true)
+            .send(&service)
+            .await;
+        // The invalidated response is revalidated.
+        assert_eq!(res.take_string().await.unwrap(), "GET 2");
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_revalidate() {
+        let (service, calls) = service("no-cache");
+        let url = "http://127.0.0.1:5801/data";
+        for _ in 0..3 {
+            let mut res = TestClient::get(url).send(&service).await;
+            assert_eq!(res.status_code, Some(StatusCode::OK));
+            assert_eq!(res.take_string().await.unwrap(), "GET 1");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let res = TestClient::get(url)
+            .add_header("cache-control", "only-if-cached", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::GATEWAY_TIMEOUT));
+    }
+
+    #[tokio::test]
+    async fn test_set_cookie() {
+        let url = "http://127.0.0.1:5801/data";
+        let (service, calls) = service("max-age=60");
+        let res = TestClient::get(url).send(&service).await;
+        assert_eq!(res.headers().get(SET_COOKIE).unwrap(), "session=1");
+        let mut res = TestClient::get(url).send(&service).await;
+        assert!(!res.headers().contains_key(SET_COOKIE));
+        assert_eq!(res.take_string().await.unwrap(), "GET 1");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // A 304 refreshing the stored response sends its cookie to the client only.
+        let (service, _) = service_with_cache("no-cache", HttpCache::new(MemoryStore::default()));
+        TestClient::get(url).send(&service).await;
+        let res = TestClient::get(url).send(&service).await;
+        assert_eq!(res.headers().get(SET_COOKIE).unwrap(), "session=2");
+        let res = TestClient::get(url).send(&service).await;
+        assert_eq!(res.headers().get(SET_COOKIE).unwrap(), "session=3");
+
+        let (service, _) = service_with_cache("max-age=60", HttpCache::new(MemoryStore::default()).shared(false));
+        TestClient::get(url).send(&service).await;
+        let res = TestClient::get(url).send(&service).await;
+        assert_eq!(res.headers().get(SET_COOKIE).unwrap(), "session=1");
+    }
+
+    #[test]
+    fn test_heuristic_freshness() {
+        let cache = HttpCache::new(MemoryStore::default());
+        let now = SystemTime::now();
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(Date::from(now));
+        headers.typed_insert(LastModified::from(now - Duration::from_secs(1000)));
+        set_times(&mut headers, now, now);
+        let entry = CachedEntry::new(Some(StatusCode::OK), headers.clone(), CachedBody::None);
+        let lifetime = cache.freshness_lifetime(&entry, None).as_secs();
+        assert!((99..=101).contains(&lifetime));
+        assert!(cache.is_fresh(&entry, &HeaderMap::new(), None));
+
+        headers.insert(AGE, HeaderValue::from_static("200"));
+        let entry = CachedEntry::new(Some(StatusCode::OK), headers, CachedBody::None);
+        assert!(current_age(entry.headers(), now) >= Duration::from_secs(200));
+        assert!(!cache.is_fresh(&entry, &HeaderMap::new(), None));
+        let entry = CachedEntry::new(Some(StatusCode::PARTIAL_CONTENT), HeaderMap::new(), CachedBody::None);
+        assert!(!cache.is_storable(&HeaderMap::new(), StatusCode::PARTIAL_CONTENT, entry.headers()));
+    }
+}
@@ -0,0 +1,377 @@
+//! Outbound HTTP client.
+//!
+//! [`HttpClient`] wraps any [`Client`] implementation, the same ones used by [`Proxy`](crate::Proxy), so handlers
+//! make outbound calls with shared connection pooling, timeouts and TLS config. Tests can swap the real client for a
+//! [`MockClient`] without touching the handlers.
+//!
+//! Headers used for tracing, such as `x-request-id` and `traceparent`, are copied from the incoming request when
+//! the outbound request is sent with [`HttpClient::send_with`].
+//!
+//! [`HttpClient::send_in`] also honors the [`Deadline`] of the incoming request stored in the [`Depot`], the
+//! outbound request fails with `504 Gateway Timeout` once it expires, and the remaining time is sent in the
+//! `X-Request-Timeout` header so the next hop can honor it too.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_proxy::client::HttpClient;
+//! use salvo_proxy::HyperClient;
+//!
+//! struct Hello {
+//!     client: HttpClient,
+//! }
+//! #[handler]
+//! impl Hello {
+//!     async fn handle(&self, req: &mut Request, depot: &mut Depot) -> Result<String, salvo_core::Error> {
+//!         let res = self
+//!             .client
+//!             .send_in(req, depot, HttpClient::get_request("http://127.0.0.1:8080/hello")?)
+//!             .await?;
+//!         Ok(res.status().to_string())
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = HyperClient::builder().allow_http(true).build().unwrap();
+//!     let client = HttpClient::new(client).timeout(std::time::Duration::from_secs(10));
+//!     let router = Router::new().get(Hello { client });
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::convert::Infallible;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures_util::future::BoxFuture;
+use hyper::upgrade::OnUpgrade;
+use salvo_core::deadline::{Deadline, DeadlineDepotExt};
+use salvo_core::http::header::{HeaderMap, HeaderName, HeaderValue};
+use salvo_core::http::{Method, ReqBody, StatusCode};
+use salvo_core::{BoxedError, Depot, Error, Request};
+use tracing::Instrument;
+
+use crate::{Client, HyperRequest, HyperResponse};
+
+/// Headers copied from the incoming request by default.
+pub const DEFAULT_PROPAGATED_HEADERS: [&str; 3] = ["x-request-id", "traceparent", "tracestate"];
+
+trait DynClient: Send + Sync + 'static {
+    fn execute(
+        &self,
+        req: HyperRequest,
+        upgraded: Option<OnUpgrade>,
+    ) -> BoxFuture<'_, Result<HyperResponse, BoxedError>>;
+}
+impl<C> DynClient for C
+where
+    C: Client,
+{
+    fn execute(
+        &self,
+        req: HyperRequest,
+        upgraded: Option<OnUpgrade>,
+    ) -> BoxFuture<'_, Result<HyperResponse, BoxedError>> {
+        Box::pin(async move { Client::execute(self, req, upgraded).await.map_err(Into::into) })
+    }
+}
+
+/// Outbound HTTP client shared by handlers.
+///
+/// Cloning is cheap, all clones share the same underlying client and connection pool.
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: Arc<dyn DynClient>,
+    timeout: Option<Duration>,
+    default_headers: HeaderMap,
+    propagated_headers: Vec<HeaderName>,
+}
+impl Debug for HttpClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpClient")
+            .field("timeout", &self.timeout)
+            .field("default_headers", &self.default_headers)
+            .field("propagated_headers", &self.propagated_headers)
+            .finish()
+    }
+}
+
+#[cfg(feature = "hyper-client")]
+impl Default for HttpClient {
+    #[inline]
+    fn default() -> Self {
+        Self::new(crate::HyperClient::default())
+    }
+}
+
+impl HttpClient {
+    /// Create a new `HttpClient` with the given [`Client`].
+    pub fn new(client: impl Client) -> Self {
+        Self {
+            inner: Arc::new(client),
+            timeout: None,
+            default_headers: HeaderMap::new(),
+            propagated_headers: DEFAULT_PROPAGATED_HEADERS
+                .into_iter()
+                .map(HeaderName::from_static)
+                .collect(),
+        }
+    }
+
+    /// Set timeout for receiving the response headers.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Add a header to every outbound request if it is not already set.
+    #[inline]
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    /// Add a header copied from the incoming request by [`send_with`](Self::send_with).
+    #[inline]
+    pub fn propagate_header(mut self, name: HeaderName) -> Self {
+        if !self.propagated_headers.contains(&name) {
+            self.propagated_headers.push(name);
+        }
+        self
+    }
+
+    /// Set headers copied from the incoming request by [`send_with`](Self::send_with), replacing the defaults.
+    #[inline]
+    pub fn propagated_headers(mut self, names: impl IntoIterator<Item = HeaderName>) -> Self {
+        self.propagated_headers = names.into_iter().collect();
+        self
+    }
+
+    /// Build a `GET` request without body.
+    pub fn get_request(uri: &str) -> Result<HyperRequest, Error> {
+        hyper::Request::builder()
+            .method(Method::GET)
+            .uri(uri)
+            .body(ReqBody::None)
+            .map_err(Error::other)
+    }
+
+    /// Send a `GET` request to `uri`.
+    pub async fn get(&self, uri: &str) -> Result<HyperResponse, Error> {
+        self.send(Self::get_request(uri)?).await
+    }
+
+    /// Send an outbound request.
+    pub async fn send(&self, req: HyperRequest) -> Result<HyperResponse, Error> {
+        self.execute_inner(req, None, None).await
+    }
+
+    /// Send an outbound request on behalf of `origin`, headers listed in
+    /// [`propagated_headers`](Self::propagated_headers) are copied from `origin` unless already set.
+    pub async fn send_with(&self, origin: &Request, mut req: HyperRequest) -> Result<HyperResponse, Error> {
+        self.propagate_headers(origin, &mut req);
+        self.send(req).await
+    }
+
+    /// Same as [`send_with`](Self::send_with), but the outbound request also honors the [`Deadline`] of `origin`
+    /// stored in the `depot`.
+    pub async fn send_in(
+        &self,
+        origin: &Request,
+        depot: &Depot,
+        mut req: HyperRequest,
+    ) -> Result<HyperResponse, Error> {
+        self.propagate_headers(origin, &mut req);
+        let deadline = depot.deadline();
+        if let Some(deadline) = deadline {
+            deadline.apply(req.headers_mut());
+        }
+        self.execute_inner(req, None, deadline).await
+    }
+
+    fn propagate_headers(&self, origin: &Request, req: &mut HyperRequest) {
+        for name in &self.propagated_headers {
+            if req.headers().contains_key(name) {
+                continue;
+            }
+            for value in origin.headers().get_all(name) {
+                req.headers_mut().append(name.clone(), value.clone());
+            }
+        }
+    }
+
+    fn execute_inner(
+        &self,
+        mut req: HyperRequest,
+        upgraded: Option<OnUpgrade>,
+        deadline: Option<Deadline>,
+    ) -> impl Future<Output = Result<HyperResponse, Error>> + Send + '_ {
+        for (name, value) in &self.default_headers {
+            if !req.headers().contains_key(name) {
+                req.headers_mut().insert(name.clone(), value.clone());
+            }
+        }
+        let span = tracing::debug_span!("http_client", method = %req.method(), uri = %req.uri());
+        async move {
+            let started = Instant::now();
+            // The deadline of the incoming request is used when it expires before the timeout of the client.
+            let timeout = match (self.timeout, deadline.map(|deadline| deadline.remaining())) {
+                (Some(timeout), Some(remaining)) if timeout < remaining => Some((timeout, false)),
+                (_, Some(remaining)) => Some((remaining, true)),
+                (timeout, None) => timeout.map(|timeout| (timeout, false)),
+            };
+            let deadline_exceeded =
+                || Error::other("request deadline exceeded").with_status(StatusCode::GATEWAY_TIMEOUT);
+            if matches!(timeout, Some((remaining, true)) if remaining.is_zero()) {
+                return Err(deadline_exceeded());
+            }
+            let fut = self.inner.execute(req, upgraded);
+            let result = match timeout {
+                Some((timeout, by_deadline)) => match tokio::time::timeout(timeout, fut).await {
+                    Ok(result) => result,
+                    Err(_) if by_deadline => return Err(deadline_exceeded()),
+                    Err(_) => return Err(Error::other("outbound request timed out")),
+                },
+                None => fut.await,
+            };
+            match &result {
+                Ok(res) => {
+                    tracing::debug!(status = %res.status(), elapsed = ?started.elapsed(), "outbound request finished")
+                }
+                Err(e) => tracing::debug!(error = ?e, elapsed = ?started.elapsed(), "outbound request failed"),
+            }
+            result.map_err(Error::other)
+        }
+        .instrument(span)
+    }
+}
+
+impl Client for HttpClient {
+    type Error = Error;
+
+    #[inline]
+    async fn execute(&self, req: HyperRequest, upgraded: Option<OnUpgrade>) -> Result<HyperResponse, Self::Error> {
+        self.execute_inner(req, upgraded, None).await
+    }
+}
+
+/// A [`Client`] which answers requests with a function, useful for testing.
+pub struct MockClient<F> {
+    handler: F,
+}
+impl<F> MockClient<F>
+where
+    F: Fn(HyperRequest) -> HyperResponse + Send + Sync + 'static,
+{
+    /// Create a new `MockClient`.
+    #[inline]
+    pub fn new(handler: F) -> Self {
+        Self { handler }
+    }
+}
+impl<F> Client for MockClient<F>
+where
+    F: Fn(HyperRequest) -> HyperResponse + Send + Sync + 'static,
+{
+    type Error = Infallible;
+
+    async fn execute(&self, req: HyperRequest, _upgraded: Option<OnUpgrade>) -> Result<HyperResponse, Self::Error> {
+        Ok((self.handler)(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::ResBody;
+
+    use super::*;
+
+    fn echo_header(name: &'static str) -> MockClient<impl Fn(HyperRequest) -> HyperResponse + Send + Sync + 'static> {
+        MockClient::new(move |req: HyperRequest| {
+            let value = req
+                .headers()
+                .get(name)
+                .map(|v| v.to_str().unwrap().to_owned())
+                .unwrap_or_default();
+            hyper::Response::builder()
+                .status(StatusCode::OK)
+                .header("x-echo", value)
+                .body(ResBody::None)
+                .unwrap()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_propagate_headers() {
+        let client = HttpClient::new(echo_header("x-request-id"));
+        let mut origin = Request::default();
+        origin
+            .headers_mut()
+            .insert("x-request-id", HeaderValue::from_static("abc"));
+        let res = client
+            .send_with(&origin, HttpClient::get_request("http://example.com").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get("x-echo").unwrap(), "abc");
+
+        let res = client.get("http://example.com").await.unwrap();
+        assert_eq!(res.headers().get("x-echo").unwrap(), "");
+    }
+
+    #[tokio::test]
+    async fn test_default_header() {
+        let client = HttpClient::new(echo_header("user-agent"))
+            .default_header(HeaderName::from_static("user-agent"), HeaderValue::from_static("salvo"));
+        let res = client.get("http://example.com").await.unwrap();
+        assert_eq!(res.headers().get("x-echo").unwrap(), "salvo");
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        struct SlowClient;
+        impl Client for SlowClient {
+            type Error = Infallible;
+            async fn execute(&self, _: HyperRequest, _: Option<OnUpgrade>) -> Result<HyperResponse, Self::Error> {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(hyper::Response::new(ResBody::None))
+            }
+        }
+        let client = HttpClient::new(SlowClient).timeout(Duration::from_millis(20));
+        assert!(client.get("http://example.com").await.is_err());
+
+        let client = HttpClient::new(SlowClient).timeout(Duration::from_secs(10));
+        let mut depot = Depot::new();
+        depot.set_deadline(Deadline::after(Duration::from_millis(20)));
+        let err = client
+            .send_in(
+                &Request::default(),
+                &depot,
+                HttpClient::get_request("http://example.com").unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_header() {
+        let client = HttpClient::new(echo_header("x-request-timeout"));
+        let mut depot = Depot::new();
+        depot.set_deadline(Deadline::after(Duration::from_secs(2)));
+        let res = client
+            .send_in(
+                &Request::default(),
+                &depot,
+                HttpClient::get_request("http://example.com").unwrap(),
+            )
+            .await
+            .unwrap();
+        let millis: u64 = res.headers().get("x-echo").unwrap().to_str().unwrap().parse().unwrap();
+        assert!(millis > 1000 && millis <= 2000);
+    }
+}
@@ -0,0 +1,432 @@
+use std::fmt::{self, Display, Formatter};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use salvo_core::http::header::{
+    HeaderMap, HeaderName, HeaderValue, CONNECTION, FORWARDED, HOST, PROXY_AUTHENTICATE, PROXY_AUTHORIZATION, TE,
+    TRAILER, TRANSFER_ENCODING, UPGRADE,
+};
+use salvo_core::http::uri::Uri;
+use salvo_core::{Error, Request};
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+const X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host");
+const KEEP_ALIVE: HeaderName = HeaderName::from_static("keep-alive");
+const PROXY_CONNECTION: HeaderName = HeaderName::from_static("proxy-connection");
+
+/// Forwarding headers added to proxied requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ForwardedHeaders {
+    /// Do not add forwarding headers, incoming forwarding headers are passed through unchanged.
+    #[default]
+    None,
+    /// Add `X-Forwarded-For`, `X-Forwarded-Proto` and `X-Forwarded-Host`.
+    XForwarded,
+    /// Add RFC 7239 `Forwarded`.
+    Forwarded,
+    /// Add both `X-Forwarded-*` and `Forwarded`.
+    Both,
+}
+impl ForwardedHeaders {
+    fn x_forwarded(self) -> bool {
+        matches!(self, Self::XForwarded | Self::Both)
+    }
+    fn forwarded(self) -> bool {
+        matches!(self, Self::Forwarded | Self::Both)
+    }
+}
+
+/// How the `Host` header of proxied requests is set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HostRewrite {
+    /// Use the authority of the elected upstream.
+    #[default]
+    Upstream,
+    /// Keep the `Host` header sent by the downstream client.
+    Preserve,
+    /// Use a fixed value.
+    Custom(HeaderValue),
+}
+
+/// An IP address or a CIDR network, such as `10.0.0.0/8` or `::1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+impl IpNetwork {
+    /// Create a new `IpNetwork`, `prefix` is truncated to the length of the address.
+    pub fn new(addr: IpAddr, prefix: u8) -> Self {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        Self {
+            addr,
+            prefix: prefix.min(max),
+        }
+    }
+    /// Check whether `ip` is inside this network.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+impl From<IpAddr> for IpNetwork {
+    #[inline]
+    fn from(addr: IpAddr) -> Self {
+        Self::new(addr, 128)
+    }
+}
+impl FromStr for IpNetwork {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr = addr.trim().parse::<IpAddr>().map_err(Error::other)?;
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse::<u8>().map_err(Error::other)?,
+            None => 128,
+        };
+        Ok(Self::new(addr, prefix))
+    }
+}
+impl Display for IpNetwork {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// Policy applied to the headers of proxied requests and responses.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct HeaderPolicy {
+    /// Forwarding headers added to proxied requests.
+    pub forwarded: ForwardedHeaders,
+    /// How the `Host` header is set.
+    pub host: HostRewrite,
+    /// Downstream proxies whose forwarding headers are trusted and appended to.
+    ///
+    /// Forwarding headers sent by other peers are discarded when [`forwarded`](Self::forwarded) is enabled.
+    pub trusted_downstreams: Vec<IpNetwork>,
+    /// Remove hop-by-hop headers from proxied requests and responses.
+    pub strip_hop_by_hop: bool,
+    /// Headers set on proxied requests.
+    pub set_request_headers: HeaderMap,
+    /// Headers removed from proxied requests.
+    pub remove_request_headers: Vec<HeaderName>,
+    /// Headers set on responses.
+    pub set_response_headers: HeaderMap,
+    /// Headers removed from responses.
+    pub remove_response_headers: Vec<HeaderName>,
+}
+impl Default for HeaderPolicy {
+    fn default() -> Self {
+        Self {
+            forwarded: ForwardedHeaders::None,
+            host: HostRewrite::Upstream,
+            trusted_downstreams: vec![],
+            strip_hop_by_hop: true,
+            set_request_headers: HeaderMap::new(),
+            remove_request_headers: vec![],
+            set_response_headers: HeaderMap::new(),
+            remove_response_headers: vec![],
+        }
+    }
+}
+
+impl HeaderPolicy {
+    /// Create a new `HeaderPolicy`.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Check whether the remote address of the request is a trusted downstream.
+    pub fn is_trusted(&self, req: &Request) -> bool {
+        match client_ip(req) {
+            Some(ip) => self.trusted_downstreams.iter().any(|net| net.contains(&ip)),
+            None => false,
+        }
+    }
+
+    /// Build headers of the proxied request from the downstream request.
+    pub fn request_headers(&self, req: &Request, forward_url: &Uri) -> HeaderMap {
+        let mut headers = req.headers().clone();
+        if self.strip_hop_by_hop {
+            remove_hop_by_hop(
+                &mut headers,
+                crate::get_upgrade_type(req.headers()).map(|s| s.to_owned()),
+            );
+        }
+
+        let original_host = req.headers().get(HOST).cloned().or_else(|| {
+            req.uri()
+                .authority()
+                .and_then(|a| HeaderValue::from_str(a.as_str()).ok())
+        });
+        match &self.host {
+            HostRewrite::Upstream => {
+                if let Some(host) = forward_url
+                    .authority()
+                    .and_then(|a| HeaderValue::from_str(a.as_str()).ok())
+                {
+                    headers.insert(HOST, host);
+                }
+            }
+            HostRewrite::Preserve => {}
+            HostRewrite::Custom(host) => {
+                headers.insert(HOST, host.clone());
+            }
+        }
+
+        if self.forwarded != ForwardedHeaders::None {
+            let trusted = self.is_trusted(req);
+            if !trusted {
+                for name in [X_FORWARDED_FOR, X_FORWARDED_PROTO, X_FORWARDED_HOST, FORWARDED] {
+                    headers.remove(name);
+                }
+            }
+            let client = client_ip(req);
+            let proto = req.scheme().as_str().to_owned();
+            if self.forwarded.x_forwarded() {
+                let client = client.map(|ip| ip.to_string()).unwrap_or_else(|| "unknown".into());
+                append_list(&mut headers, X_FORWARDED_FOR, &client);
+                if !headers.contains_key(X_FORWARDED_PROTO) {
+                    if let Ok(value) = HeaderValue::from_str(&proto) {
+                        headers.insert(X_FORWARDED_PROTO, value);
+                    }
+                }
+                if !headers.contains_key(X_FORWARDED_HOST) {
+                    if let Some(host) = &original_host {
+                        headers.insert(X_FORWARDED_HOST, host.clone());
+                    }
+                }
+            }
+            if self.forwarded.forwarded() {
+                let mut element = match client {
+                    Some(IpAddr::V6(ip)) => format!("for=\"[{ip}]\""),
+                    Some(ip) => format!("for={ip}"),
+                    None => "for=unknown".into(),
+                };
+                element.push_str(";proto=");
+                element.push_str(&proto);
+                if let Some(host) = original_host.as_ref().and_then(|h| h.to_str().ok()) {
+                    element.push_str(";host=\"");
+                    element.push_str(host);
+                    element.push('"');
+                }
+                append_list(&mut headers, FORWARDED, &element);
+            }
+        }
+
+        for name in &self.remove_request_headers {
+            headers.remove(name);
+        }
+        for (name, value) in &self.set_request_headers {
+            headers.insert(name.clone(), value.clone());
+        }
+        headers
+    }
+
+    /// Apply this policy to the response headers received from upstream.
+    pub fn response_headers(&self, headers: &mut HeaderMap, upgraded: bool) {
+        if self.strip_hop_by_hop {
+            let upgrade = if upgraded {
+                crate::get_upgrade_type(headers).map(|s| s.to_owned())
+            } else {
+                None
+            };
+            remove_hop_by_hop(headers, upgrade);
+        }
+        for name in &self.remove_response_headers {
+            headers.remove(name);
+        }
+        for (name, value) in &self.set_response_headers {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+fn client_ip(req: &Request) -> Option<IpAddr> {
+    req.remote_addr()
+        .clone()
+        .into_std()
+        .map(|addr| addr.ip().to_canonical())
+}
+
+/// Joins all lines of the list header and the value into one line, such as `X-Forwarded-For: a, b, value`.
+fn append_list(headers: &mut HeaderMap, name: HeaderName, value: &str) {
+    let value = headers
+        .get_all(&name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .chain([value])
+        .collect::<Vec<_>>()
+        .join(", ");
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        headers.insert(name, value);
+    }
+}
+
+/// Remove hop-by-hop headers, the `Connection` and `Upgrade` headers are kept for protocol upgrades.
+pub(crate) fn remove_hop_by_hop(headers: &mut HeaderMap, upgrade: Option<String>) {
+    let listed = headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect::<Vec<_>>();
+    for name in listed {
+        headers.remove(name);
+    }
+    let keep_trailers = headers
+        .get(TE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|t| t.trim().eq_ignore_ascii_case("trailers")))
+        .unwrap_or(false);
+    for name in [
+        CONNECTION,
+        KEEP_ALIVE,
+        PROXY_CONNECTION,
+        PROXY_AUTHENTICATE,
+        PROXY_AUTHORIZATION,
+        TE,
+        TRAILER,
+        TRANSFER_ENCODING,
+        UPGRADE,
+    ] {
+        headers.remove(name);
+    }
+    if keep_trailers {
+        headers.insert(TE, HeaderValue::from_static("trailers"));
+    }
+    if let Some(upgrade) = upgrade.and_then(|u| HeaderValue::from_str(&u).ok()) {
+        headers.insert(CONNECTION, HeaderValue::from_static("upgrade"));
+        headers.insert(UPGRADE, upgrade);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+
+    fn request(remote: &str) -> Request {
+        let mut req = Request::default();
+        *req.uri_mut() = "http://example.com/path".parse().unwrap();
+        req.headers_mut().insert(HOST, HeaderValue::from_static("example.com"));
+        *req.remote_addr_mut() = remote.parse::<SocketAddr>().unwrap().into();
+        req
+    }
+
+    #[test]
+    fn test_ip_network() {
+        let net: IpNetwork = "10.0.0.0/8".parse().unwrap();
+        assert!(net.contains(&"10.1.2.3".parse().unwrap()));
+        assert!(!net.contains(&"11.1.2.3".parse().unwrap()));
+        let net: IpNetwork = "::1".parse().unwrap();
+        assert!(net.contains(&"::1".parse().unwrap()));
+        assert!(!net.contains(&"::2".parse().unwrap()));
+        assert!("10.0.0.0/abc".parse::<IpNetwork>().is_err());
+    }
+
+    #[test]
+    fn test_hop_by_hop() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONNECTION, HeaderValue::from_static("keep-alive, x-custom"));
+        headers.insert("x-custom", HeaderValue::from_static("1"));
+        headers.insert(KEEP_ALIVE, HeaderValue::from_static("timeout=5"));
+        headers.insert(TE, HeaderValue::from_static("trailers"));
+        headers.insert("x-other", HeaderValue::from_static("2"));
+        remove_hop_by_hop(&mut headers, None);
+        assert!(!headers.contains_key(CONNECTION));
+        assert!(!headers.contains_key("x-custom"));
+        assert!(!headers.contains_key(KEEP_ALIVE));
+        assert_eq!(headers.get(TE).unwrap(), "trailers");
+        assert_eq!(headers.get("x-other").unwrap(), "2");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONNECTION, HeaderValue::from_static("upgrade"));
+        headers.insert(UPGRADE, HeaderValue::from_static("websocket"));
+        remove_hop_by_hop(&mut headers, Some("websocket".into()));
+        assert_eq!(headers.get(CONNECTION).unwrap(), "upgrade");
+        assert_eq!(headers.get(UPGRADE).unwrap(), "websocket");
+    }
+
+    #[test]
+    fn test_x_forwarded_untrusted() {
+        let mut req = request("192.168.1.5:1234");
+        req.headers_mut()
+            .insert(X_FORWARDED_FOR, HeaderValue::from_static("1.1.1.1"));
+        let policy = HeaderPolicy {
+            forwarded: ForwardedHeaders::Both,
+            ..Default::default()
+        };
+        let headers = policy.request_headers(&req, &"http://upstream:8080/path".parse().unwrap());
+        assert_eq!(headers.get(X_FORWARDED_FOR).unwrap(), "192.168.1.5");
+        assert_eq!(headers.get(X_FORWARDED_PROTO).unwrap(), "http");
+        assert_eq!(headers.get(X_FORWARDED_HOST).unwrap(), "example.com");
+        assert_eq!(
+            headers.get(FORWARDED).unwrap(),
+            "for=192.168.1.5;proto=http;host=\"example.com\""
+        );
+        assert_eq!(headers.get(HOST).unwrap(), "upstream:8080");
+    }
+
+    #[test]
+    fn test_x_forwarded_trusted() {
+        let mut req = request("10.0.0.2:1234");
+        req.headers_mut()
+            .insert(X_FORWARDED_FOR, HeaderValue::from_static("1.1.1.1"));
+        let policy = HeaderPolicy {
+            forwarded: ForwardedHeaders::XForwarded,
+            host: HostRewrite::Preserve,
+            trusted_downstreams: vec!["10.0.0.0/8".parse().unwrap()],
+            ..Default::default()
+        };
+        let headers = policy.request_headers(&req, &"http://upstream:8080/path".parse().unwrap());
+        assert_eq!(headers.get(X_FORWARDED_FOR).unwrap(), "1.1.1.1, 10.0.0.2");
+        assert_eq!(headers.get(HOST).unwrap(), "example.com");
+        assert!(!headers.contains_key(FORWARDED));
+
+        // All lines of the header are kept, in order.
+        req.headers_mut()
+            .append(X_FORWARDED_FOR, HeaderValue::from_static("2.2.2.2, 3.3.3.3"));
+        let headers = policy.request_headers(&req, &"http://upstream:8080/path".parse().unwrap());
+        assert_eq!(
+            headers.get_all(X_FORWARDED_FOR).iter().collect::<Vec<_>>(),
+            vec!["1.1.1.1, 2.2.2.2, 3.3.3.3, 10.0.0.2"]
+        );
+    }
+
+    #[test]
+    fn test_set_and_remove_headers() {
+        let mut req = request("10.0.0.2:1234");
+        req.headers_mut().insert("x-secret", HeaderValue::from_static("1"));
+        let mut policy = HeaderPolicy::new();
+        policy.remove_request_headers.push(HeaderName::from_static("x-secret"));
+        policy
+            .set_request_headers
+            .insert("x-env", HeaderValue::from_static("prod"));
+        let headers = policy.request_headers(&req, &"http://upstream/path".parse().unwrap());
+        assert!(!headers.contains_key("x-secret"));
+        assert_eq!(headers.get("x-env").unwrap(), "prod");
+    }
+}
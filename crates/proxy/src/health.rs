@@ -0,0 +1,575 @@
+//! Health checking for proxy upstreams.
+//!
+//! [`HealthyUpstreams`] wraps a list of upstreams and only elects the ones considered healthy. Two kinds of
+//! checks are supported and can be combined:
+//!
+//! - **Passive checks**: the [`Proxy`](crate::Proxy) reports the outcome of every proxied request; after a number
+//!   of consecutive failures the upstream is ejected for a recovery window, then it is given traffic again.
+//! - **Active checks**: a background task periodically probes every upstream with a TCP connect or an HTTP request
+//!   and marks it unhealthy/healthy after configurable thresholds.
+//!
+//! If every upstream is unhealthy, requests are spread over all upstreams instead of failing outright.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use salvo_core::prelude::*;
+//! use salvo_proxy::health::{ActiveCheck, HealthyUpstreams, PassiveCheck};
+//! use salvo_proxy::{HyperClient, Proxy};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = HyperClient::builder().allow_http(true).build().unwrap();
+//!     let upstreams = HealthyUpstreams::new(vec!["http://10.0.0.1:8080", "http://10.0.0.2:8080"])
+//!         .passive(PassiveCheck::new(3, Duration::from_secs(30)))
+//!         .active(ActiveCheck::http("/healthz").interval(Duration::from_secs(5)), client.clone());
+//!     let router = Router::new()
+//!         .push(Router::with_path("upstreams/health").get(upstreams.reporter()))
+//!         .push(Router::with_path("<**rest>").goal(Proxy::new(upstreams, client)));
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use std::fmt::{self, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use salvo_core::http::uri::Uri;
+use salvo_core::http::{ReqBody, StatusCode};
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Request, Response};
+use serde::Serialize;
+use tokio::net::TcpStream;
+
+use crate::{Client, Upstreams};
+
+type ProbeFuture = Pin<Box<dyn Future<Output = bool> + Send>>;
+type Prober = Arc<dyn Fn(String) -> ProbeFuture + Send + Sync>;
+
+/// Passive health check configuration.
+///
+/// An upstream is ejected after `max_failures` consecutive failed requests and will receive traffic again once
+/// `ejection` elapsed. A request is considered failed when the client returns an error or the upstream answers
+/// with `502 Bad Gateway`, `503 Service Unavailable` or `504 Gateway Timeout`.
+#[derive(Clone, Copy, Debug)]
+pub struct PassiveCheck {
+    /// Consecutive failures before the upstream is ejected.
+    pub max_failures: usize,
+    /// How long an ejected upstream is kept out of rotation.
+    pub ejection: Duration,
+}
+impl Default for PassiveCheck {
+    #[inline]
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+impl PassiveCheck {
+    /// Create a new `PassiveCheck`.
+    #[inline]
+    pub fn new(max_failures: usize, ejection: Duration) -> Self {
+        Self {
+            max_failures: max_failures.max(1),
+            ejection,
+        }
+    }
+}
+
+/// Probe kind used by [`ActiveCheck`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Probe {
+    /// Open a TCP connection to the upstream host and port.
+    Tcp,
+    /// Send a `GET` request to the given path, a `2xx` or `3xx` response is considered healthy.
+    Http(String),
+}
+
+/// Active health check configuration.
+#[derive(Clone, Debug)]
+pub struct ActiveCheck {
+    /// Probe kind.
+    pub probe: Probe,
+    /// Interval between two probes of the same upstream.
+    pub interval: Duration,
+    /// Timeout of a single probe.
+    pub timeout: Duration,
+    /// Consecutive failed probes before an upstream is marked unhealthy.
+    pub unhealthy_threshold: usize,
+    /// Consecutive successful probes before an unhealthy upstream is marked healthy again.
+    pub healthy_threshold: usize,
+}
+impl ActiveCheck {
+    /// Create a new `ActiveCheck` with the given probe.
+    #[inline]
+    pub fn new(probe: Probe) -> Self {
+        Self {
+            probe,
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(2),
+            unhealthy_threshold: 3,
+            healthy_threshold: 2,
+        }
+    }
+    /// Create a new `ActiveCheck` which opens TCP connections.
+    #[inline]
+    pub fn tcp() -> Self {
+        Self::new(Probe::Tcp)
+    }
+    /// Create a new `ActiveCheck` which sends HTTP `GET` requests to `path`.
+    #[inline]
+    pub fn http(path: impl Into<String>) -> Self {
+        Self::new(Probe::Http(path.into()))
+    }
+    /// Set probe interval.
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+    /// Set probe timeout.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    /// Set unhealthy threshold.
+    #[inline]
+    pub fn unhealthy_threshold(mut self, threshold: usize) -> Self {
+        self.unhealthy_threshold = threshold.max(1);
+        self
+    }
+    /// Set healthy threshold.
+    #[inline]
+    pub fn healthy_threshold(mut self, threshold: usize) -> Self {
+        self.healthy_threshold = threshold.max(1);
+        self
+    }
+}
+
+/// Snapshot of the health of an upstream.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UpstreamHealth {
+    /// Upstream url.
+    pub upstream: String,
+    /// Whether the upstream currently receives traffic.
+    pub healthy: bool,
+    /// Consecutive failed requests reported by the passive check.
+    pub consecutive_failures: usize,
+    /// Whether the upstream is marked down by the active check.
+    pub probe_failed: bool,
+    /// Remaining ejection time in milliseconds, if the upstream is ejected by the passive check.
+    pub ejected_ms: Option<u64>,
+    /// Total failed requests reported.
+    pub total_failures: usize,
+    /// Total requests reported.
+    pub total_requests: usize,
+}
+
+#[derive(Default, Debug)]
+struct UpstreamState {
+    consecutive_failures: AtomicUsize,
+    total_failures: AtomicUsize,
+    total_requests: AtomicUsize,
+    ejected_until: Mutex<Option<Instant>>,
+    probe_failed: AtomicBool,
+    probe_failures: AtomicUsize,
+    probe_successes: AtomicUsize,
+}
+impl UpstreamState {
+    fn ejected_for(&self, now: Instant) -> Option<Duration> {
+        let ejected_until = self.ejected_until.lock().unwrap_or_else(|e| e.into_inner());
+        ejected_until.and_then(|until| until.checked_duration_since(now))
+    }
+    fn is_healthy(&self, now: Instant) -> bool {
+        !self.probe_failed.load(Ordering::Relaxed) && self.ejected_for(now).is_none()
+    }
+}
+
+struct HealthInner<T> {
+    upstreams: Vec<T>,
+    states: Vec<UpstreamState>,
+    passive: Option<PassiveCheck>,
+    active: Option<(ActiveCheck, Prober)>,
+    active_started: AtomicBool,
+}
+
+/// Upstreams list which elects only healthy upstreams.
+///
+/// View [module level documentation](index.html) for more details.
+pub struct HealthyUpstreams<T> {
+    inner: Arc<HealthInner<T>>,
+}
+impl<T> Clone for HealthyUpstreams<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<T> fmt::Debug for HealthyUpstreams<T>
+where
+    T: AsRef<str>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HealthyUpstreams")
+            .field(
+                "upstreams",
+                &self.inner.upstreams.iter().map(|u| u.as_ref()).collect::<Vec<_>>(),
+            )
+            .field("passive", &self.inner.passive)
+            .field("active", &self.inner.active.as_ref().map(|(check, _)| check))
+            .finish()
+    }
+}
+
+impl<T> HealthyUpstreams<T>
+where
+    T: AsRef<str> + Send + Sync + 'static,
+{
+    /// Create a new `HealthyUpstreams` with passive check enabled with default settings.
+    pub fn new(upstreams: Vec<T>) -> Self {
+        let states = upstreams.iter().map(|_| UpstreamState::default()).collect();
+        Self {
+            inner: Arc::new(HealthInner {
+                upstreams,
+                states,
+                passive: Some(PassiveCheck::default()),
+                active: None,
+                active_started: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut HealthInner<T> {
+        Arc::get_mut(&mut self.inner).expect("`HealthyUpstreams` can not be configured after it is cloned")
+    }
+
+    /// Set passive check.
+    ///
+    /// **Note**: This function must be called before the `HealthyUpstreams` is cloned.
+    #[inline]
+    pub fn passive(mut self, check: PassiveCheck) -> Self {
+        self.inner_mut().passive = Some(check);
+        self
+    }
+    /// Disable passive check.
+    ///
+    /// **Note**: This function must be called before the `HealthyUpstreams` is cloned.
+    #[inline]
+    pub fn without_passive(mut self) -> Self {
+        self.inner_mut().passive = None;
+        self
+    }
+
+    /// Set active check, `client` is used to send HTTP probes.
+    ///
+    /// The probing task is started when the first request is elected and stops when all clones of this
+    /// `HealthyUpstreams` are dropped.
+    ///
+    /// **Note**: This function must be called before the `HealthyUpstreams` is cloned.
+    pub fn active<C>(mut self, check: ActiveCheck, client: C) -> Self
+    where
+        C: Client,
+    {
+        let client = Arc::new(client);
+        let probe = check.probe.clone();
+        let timeout = check.timeout;
+        let prober: Prober = Arc::new(move |upstream: String| {
+            let client = client.clone();
+            let probe = probe.clone();
+            Box::pin(async move {
+                tokio::time::timeout(timeout, run_probe(&probe, &upstream, &*client))
+                    .await
+                    .unwrap_or(false)
+            })
+        });
+        self.inner_mut().active = Some((check, prober));
+        self
+    }
+
+    /// Returns health snapshot of all upstreams.
+    pub fn snapshot(&self) -> Vec<UpstreamHealth> {
+        let now = Instant::now();
+        self.inner
+            .upstreams
+            .iter()
+            .zip(self.inner.states.iter())
+            .map(|(upstream, state)| UpstreamHealth {
+                upstream: upstream.as_ref().to_owned(),
+                healthy: state.is_healthy(now),
+                consecutive_failures: state.consecutive_failures.load(Ordering::Relaxed),
+                probe_failed: state.probe_failed.load(Ordering::Relaxed),
+                ejected_ms: state.ejected_for(now).map(|d| d.as_millis() as u64),
+                total_failures: state.total_failures.load(Ordering::Relaxed),
+                total_requests: state.total_requests.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Returns a handler which renders [`snapshot`](Self::snapshot) as JSON.
+    ///
+    /// The status code is `503 Service Unavailable` when no upstream is healthy.
+    #[inline]
+    pub fn reporter(&self) -> HealthReporter<T> {
+        HealthReporter {
+            upstreams: self.clone(),
+        }
+    }
+
+    /// Returns the number of upstreams.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.upstreams.len()
+    }
+    /// Returns `true` if there is no upstream.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.upstreams.is_empty()
+    }
+    /// Returns the upstream at `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.inner.upstreams.get(index).map(|u| u.as_ref())
+    }
+    /// Returns `true` if the upstream at `index` is healthy.
+    #[inline]
+    pub fn is_healthy(&self, index: usize) -> bool {
+        self.inner
+            .states
+            .get(index)
+            .map(|state| state.is_healthy(Instant::now()))
+            .unwrap_or(false)
+    }
+    /// Returns indexes of all healthy upstreams.
+    pub fn healthy_indices(&self) -> Vec<usize> {
+        let now = Instant::now();
+        self.inner
+            .states
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| state.is_healthy(now))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub(crate) fn position(&self, upstream: &str) -> Option<usize> {
+        self.inner.upstreams.iter().position(|u| u.as_ref() == upstream)
+    }
+
+    pub(crate) fn start_active(&self) {
+        let Some((check, prober)) = &self.inner.active else {
+            return;
+        };
+        if self.inner.active_started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let interval = check.interval;
+        let prober = prober.clone();
+        let weak = Arc::downgrade(&self.inner);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if !probe_all(&weak, &prober).await {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+async fn probe_all<T>(weak: &Weak<HealthInner<T>>, prober: &Prober) -> bool
+where
+    T: AsRef<str> + Send + Sync,
+{
+    let Some(inner) = weak.upgrade() else {
+        return false;
+    };
+    let Some((check, _)) = &inner.active else {
+        return false;
+    };
+    let results = futures_util::future::join_all(inner.upstreams.iter().map(|u| prober(u.as_ref().to_owned()))).await;
+    for ((upstream, state), ok) in inner.upstreams.iter().zip(inner.states.iter()).zip(results) {
+        if ok {
+            state.probe_failures.store(0, Ordering::Relaxed);
+            let successes = state.probe_successes.fetch_add(1, Ordering::Relaxed) + 1;
+            if successes >= check.healthy_threshold && state.probe_failed.swap(false, Ordering::Relaxed) {
+                tracing::info!(upstream = upstream.as_ref(), "upstream marked healthy by active check");
+            }
+        } else {
+            state.probe_successes.store(0, Ordering::Relaxed);
+            let failures = state.probe_failures.fetch_add(1, Ordering::Relaxed) + 1;
+            if failures >= check.unhealthy_threshold && !state.probe_failed.swap(true, Ordering::Relaxed) {
+                tracing::warn!(
+                    upstream = upstream.as_ref(),
+                    "upstream marked unhealthy by active check"
+                );
+            }
+        }
+    }
+    true
+}
+
+async fn run_probe<C: Client>(probe: &Probe, upstream: &str, client: &C) -> bool {
+    match probe {
+        Probe::Tcp => {
+            let Ok(uri) = upstream.parse::<Uri>() else {
+                return false;
+            };
+            let Some(host) = uri.host() else {
+                return false;
+            };
+            let port = uri
+                .port_u16()
+                .unwrap_or(if uri.scheme_str() == Some("https") { 443 } else { 80 });
+            TcpStream::connect((host.trim_start_matches('[').trim_end_matches(']'), port))
+                .await
+                .is_ok()
+        }
+        Probe::Http(path) => {
+            let url = format!("{}/{}", upstream.trim_end_matches('/'), path.trim_start_matches('/'));
+            let Ok(req) = hyper::Request::get(url).body(ReqBody::None) else {
+                return false;
+            };
+            match client.execute(req, None).await {
+                Ok(res) => res.status().is_success() || res.status().is_redirection(),
+                Err(_) => false,
+            }
+        }
+    }
+}
+
+impl<T> Upstreams for HealthyUpstreams<T>
+where
+    T: AsRef<str> + Send + Sync + 'static,
+{
+    type Error = Error;
+
+    async fn elect(&self) -> Result<&str, Self::Error> {
+        let upstreams = &self.inner.upstreams;
+        if upstreams.is_empty() {
+            return Err(Error::other("upstreams is empty"));
+        }
+        self.start_active();
+        let healthy = self.healthy_indices();
+        let index = if healthy.is_empty() {
+            tracing::warn!("no healthy upstream available, electing from all upstreams");
+            fastrand::usize(..upstreams.len())
+        } else {
+            healthy[fastrand::usize(..healthy.len())]
+        };
+        Ok(upstreams[index].as_ref())
+    }
+
+    fn report(&self, upstream: &str, success: bool) {
+        let Some(index) = self.position(upstream) else {
+            return;
+        };
+        let state = &self.inner.states[index];
+        state.total_requests.fetch_add(1, Ordering::Relaxed);
+        if success {
+            state.consecutive_failures.store(0, Ordering::Relaxed);
+            return;
+        }
+        state.total_failures.fetch_add(1, Ordering::Relaxed);
+        let failures = state.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(passive) = &self.inner.passive {
+            if failures >= passive.max_failures {
+                state.consecutive_failures.store(0, Ordering::Relaxed);
+                *state.ejected_until.lock().unwrap_or_else(|e| e.into_inner()) =
+                    Some(Instant::now() + passive.ejection);
+                tracing::warn!(upstream, failures, ejection = ?passive.ejection, "upstream ejected by passive check");
+            }
+        }
+    }
+}
+
+/// Handler which renders the health of upstreams as JSON.
+///
+/// Created by [`HealthyUpstreams::reporter`].
+pub struct HealthReporter<T> {
+    upstreams: HealthyUpstreams<T>,
+}
+#[async_trait]
+impl<T> Handler for HealthReporter<T>
+where
+    T: AsRef<str> + Send + Sync + 'static,
+{
+    async fn handle(&self, _req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let snapshot = self.upstreams.snapshot();
+        if !snapshot.iter().any(|h| h.healthy) {
+            res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        res.render(Json(snapshot));
+    }
+}
+
+/// Whether a proxied response should be reported as a failure to passive health checks.
+#[inline]
+pub(crate) fn is_failure_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_passive_ejection_and_recovery() {
+        let upstreams = HealthyUpstreams::new(vec!["http://a.local", "http://b.local"])
+            .passive(PassiveCheck::new(2, Duration::from_millis(100)));
+        upstreams.report("http://a.local", false);
+        assert!(upstreams.snapshot()[0].healthy);
+        upstreams.report("http://a.local", false);
+        assert!(!upstreams.snapshot()[0].healthy);
+        for _ in 0..20 {
+            assert_eq!(upstreams.elect().await.unwrap(), "http://b.local");
+        }
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let snapshot = upstreams.snapshot();
+        assert!(snapshot[0].healthy);
+        assert_eq!(snapshot[0].total_failures, 2);
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failures() {
+        let upstreams =
+            HealthyUpstreams::new(vec!["http://a.local"]).passive(PassiveCheck::new(2, Duration::from_secs(60)));
+        upstreams.report("http://a.local", false);
+        upstreams.report("http://a.local", true);
+        upstreams.report("http://a.local", false);
+        let snapshot = upstreams.snapshot();
+        assert!(snapshot[0].healthy);
+        assert_eq!(snapshot[0].consecutive_failures, 1);
+        assert_eq!(snapshot[0].total_requests, 3);
+    }
+
+    #[tokio::test]
+    async fn test_all_unhealthy_fallback() {
+        let upstreams =
+            HealthyUpstreams::new(vec!["http://a.local"]).passive(PassiveCheck::new(1, Duration::from_secs(60)));
+        upstreams.report("http://a.local", false);
+        assert!(!upstreams.snapshot()[0].healthy);
+        assert_eq!(upstreams.elect().await.unwrap(), "http://a.local");
+    }
+
+    #[test]
+    fn test_failure_status() {
+        assert!(is_failure_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_failure_status(StatusCode::NOT_FOUND));
+        assert!(!is_failure_status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+}
@@ -1,15 +1,20 @@
+use std::time::Duration;
+
 use hyper::upgrade::OnUpgrade;
 use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
 use hyper_util::client::legacy::{connect::HttpConnector, Client as HyperUtilClient};
 use hyper_util::rt::TokioExecutor;
+use rustls::ClientConfig;
 use salvo_core::http::{ReqBody, ResBody, StatusCode};
 use salvo_core::rt::tokio::TokioIo;
 use salvo_core::Error;
-use tokio::io::copy_bidirectional;
 
-use crate::{Client, HyperRequest,Proxy, BoxedError, Upstreams,HyperResponse};
+use crate::{BoxedError, Client, HyperRequest, HyperResponse, IdleTimeout, Proxy, Upstreams};
 
 /// A [`Client`] implementation based on [`hyper_util::client::legacy::Client`].
+///
+/// The default client only connects to `https` upstreams, use [`HyperClientBuilder::allow_http`] to proxy to plain
+/// `http` upstreams.
 #[derive(Clone, Debug)]
 pub struct HyperClient {
     inner: HyperUtilClient<HttpsConnector<HttpConnector>, ReqBody>,
@@ -17,16 +22,105 @@ pub struct HyperClient {
 
 impl Default for HyperClient {
     fn default() -> Self {
-        let https = HttpsConnectorBuilder::new()
-            .with_native_roots()
+        HyperClientBuilder::new()
+            .build()
             .expect("no native root CA certificates found")
-            .https_only()
-            .enable_http1()
-            .build();
+    }
+}
+
+/// Builder for [`HyperClient`].
+#[derive(Clone, Debug)]
+pub struct HyperClientBuilder {
+    connect_timeout: Option<Duration>,
+    pool_idle_timeout: Option<Duration>,
+    pool_max_idle_per_host: usize,
+    http2: bool,
+    allow_http: bool,
+    tls_config: Option<ClientConfig>,
+}
+impl Default for HyperClientBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl HyperClientBuilder {
+    /// Create a new `HyperClientBuilder`.
+    #[inline]
+    pub fn new() -> Self {
         Self {
-            inner: HyperUtilClient::builder(TokioExecutor::new()).build(https),
+            connect_timeout: None,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            pool_max_idle_per_host: usize::MAX,
+            http2: false,
+            allow_http: false,
+            tls_config: None,
         }
     }
+    /// Set timeout for establishing connections.
+    #[inline]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+    /// Set how long idle connections are kept in the pool, `None` keeps them forever.
+    #[inline]
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+    /// Set the maximum idle connections kept in the pool per host.
+    #[inline]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = max;
+        self
+    }
+    /// Enable HTTP/2, negotiated by ALPN for `https` upstreams.
+    #[inline]
+    pub fn http2(mut self, enabled: bool) -> Self {
+        self.http2 = enabled;
+        self
+    }
+    /// Allow connecting to plain `http` upstreams, only `https` upstreams are allowed by default.
+    ///
+    /// Traffic to `http` upstreams is not encrypted, only enable it for trusted networks such as a local backend.
+    #[inline]
+    pub fn allow_http(mut self, allowed: bool) -> Self {
+        self.allow_http = allowed;
+        self
+    }
+    /// Use a custom TLS config instead of the platform's native root certificates.
+    #[inline]
+    pub fn tls_config(mut self, config: ClientConfig) -> Self {
+        self.tls_config = Some(config);
+        self
+    }
+    /// Build the [`HyperClient`].
+    pub fn build(self) -> std::io::Result<HyperClient> {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_connect_timeout(self.connect_timeout);
+        let builder = match self.tls_config {
+            Some(config) => HttpsConnectorBuilder::new().with_tls_config(config),
+            None => HttpsConnectorBuilder::new().with_native_roots()?,
+        };
+        let builder = if self.allow_http {
+            builder.https_or_http()
+        } else {
+            builder.https_only()
+        }
+        .enable_http1();
+        let https = if self.http2 {
+            builder.enable_http2().wrap_connector(http)
+        } else {
+            builder.wrap_connector(http)
+        };
+        let inner = HyperUtilClient::builder(TokioExecutor::new())
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .build(https);
+        Ok(HyperClient { inner })
+    }
 }
 
 impl<U> Proxy<U, HyperClient>
@@ -41,6 +135,11 @@ where
 }
 
 impl HyperClient {
+    /// Create a new [`HyperClientBuilder`].
+    #[inline]
+    pub fn builder() -> HyperClientBuilder {
+        HyperClientBuilder::new()
+    }
     /// Create a new `HyperClient` with the given `HyperClient`.
     pub fn new(inner: HyperUtilClient<HttpsConnector<HttpConnector>, ReqBody>) -> Self {
         Self { inner }
@@ -56,6 +155,7 @@ impl Client for HyperClient {
         request_upgraded: Option<OnUpgrade>,
     ) -> Result<HyperResponse, Self::Error> {
         let request_upgrade_type = crate::get_upgrade_type(proxied_request.headers()).map(|s| s.to_owned());
+        let idle_timeout = proxied_request.extensions().get::<IdleTimeout>().map(|t| t.0);
 
         let mut response = self.inner.request(proxied_request).await.map_err(Error::other)?;
 
@@ -64,21 +164,7 @@ impl Client for HyperClient {
             if request_upgrade_type.as_deref() == response_upgrade_type {
                 let response_upgraded = hyper::upgrade::on(&mut response).await?;
                 if let Some(request_upgraded) = request_upgraded {
-                    tokio::spawn(async move {
-                        match request_upgraded.await {
-                            Ok(request_upgraded) => {
-                                let mut request_upgraded = TokioIo::new(request_upgraded);
-                                let mut response_upgraded = TokioIo::new(response_upgraded);
-                                if let Err(e) = copy_bidirectional(&mut response_upgraded, &mut request_upgraded).await
-                                {
-                                    tracing::error!(error = ?e, "coping between upgraded connections failed.");
-                                }
-                            }
-                            Err(e) => {
-                                tracing::error!(error = ?e, "upgrade request failed.");
-                            }
-                        }
-                    });
+                    crate::upgrade::spawn_upgraded(request_upgraded, TokioIo::new(response_upgraded), idle_timeout);
                 } else {
                     return Err(Error::other("request does not have an upgrade extension."));
                 }
@@ -90,7 +176,6 @@ impl Client for HyperClient {
     }
 }
 
-
 // Unit tests for Proxy
 #[cfg(test)]
 mod tests {
@@ -98,7 +183,7 @@ mod tests {
     use salvo_core::test::*;
 
     use super::*;
-    use crate::{Upstreams, Proxy};
+    use crate::{Proxy, Upstreams};
 
     #[tokio::test]
     async fn test_upstreams_elect() {
@@ -111,7 +196,8 @@ mod tests {
     #[tokio::test]
     async fn test_hyper_client() {
         let router = Router::new().push(
-            Router::with_path("rust/<**rest>").goal(Proxy::new(vec!["https://www.rust-lang.org"], HyperClient::default())),
+            Router::with_path("rust/<**rest>")
+                .goal(Proxy::new(vec!["https://www.rust-lang.org"], HyperClient::default())),
         );
 
         let content = TestClient::get("http://127.0.0.1:5801/rust/tools/install")
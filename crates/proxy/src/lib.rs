@@ -30,6 +30,24 @@
 //!     Server::new(acceptor).serve(router).await;
 //! }
 //! ```
+//!
+//! # Streaming
+//!
+//! Request and response bodies are streamed between the downstream client and the upstream frame by frame, they
+//! are never buffered entirely, so large uploads and downloads pass through the proxy with bounded memory. Protocol
+//! upgrades such as WebSocket are handled by copying data between both upgraded connections.
+//!
+//! # Deadline
+//!
+//! If a [`Deadline`](salvo_core::deadline::Deadline) is stored in the [`Depot`], for example by the timeout
+//! middleware, the remaining time is sent to the upstream in the `X-Request-Timeout` header, and the proxy responds
+//! `504 Gateway Timeout` when the deadline expires before the upstream responds.
+//!
+//! # Caching
+//!
+//! With the `cache` feature, an [`HttpCache`] set with [`Proxy::cache`] stores the responses of the upstreams and
+//! serves them while they are fresh, stale responses are revalidated with conditional requests. View the
+//! [`cache`] module for more details.
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -37,10 +55,12 @@
 use std::convert::Infallible;
 use std::error::Error as StdError;
 use std::future::Future;
+use std::time::Duration;
 
 use hyper::upgrade::OnUpgrade;
 use percent_encoding::{utf8_percent_encode, CONTROLS};
-use salvo_core::http::header::{HeaderMap, HeaderName, HeaderValue, CONNECTION, HOST, UPGRADE};
+use salvo_core::deadline::DeadlineDepotExt;
+use salvo_core::http::header::{HeaderMap, HeaderName, HeaderValue, CONNECTION, UPGRADE};
 use salvo_core::http::uri::Uri;
 use salvo_core::http::{ReqBody, ResBody, StatusCode};
 use salvo_core::{async_trait, BoxedError, Depot, Error, FlowCtrl, Handler, Request, Response};
@@ -48,6 +68,21 @@ use salvo_core::{async_trait, BoxedError, Depot, Error, FlowCtrl, Handler, Reque
 #[macro_use]
 mod cfg;
 
+mod body;
+cfg_feature! {
+    #![feature = "cache"]
+    pub mod cache;
+    pub use cache::HttpCache;
+}
+pub mod client;
+mod headers;
+pub use headers::{ForwardedHeaders, HeaderPolicy, HostRewrite, IpNetwork};
+pub mod health;
+pub mod sticky;
+pub mod tunnel;
+mod upgrade;
+pub use upgrade::{copy_upgraded, IdleTimeout};
+
 cfg_feature! {
     #![feature = "hyper-client"]
     mod hyper_client;
@@ -72,6 +107,10 @@ pub(crate) fn encode_url_path(path: &str) -> String {
 }
 
 /// Client trait.
+///
+/// When the upstream switches protocols, implementations should copy data between `upgraded` and the upgraded
+/// upstream connection, for example with [`copy_upgraded`], honoring the [`IdleTimeout`] found in the extensions
+/// of the proxied request.
 pub trait Client: Send + Sync + 'static {
     /// Error type.
     type Error: StdError + Send + Sync + 'static;
@@ -89,6 +128,26 @@ pub trait Upstreams: Send + Sync + 'static {
     type Error: StdError + Send + Sync + 'static;
     /// Elect a upstream to process current request.
     fn elect(&self) -> impl Future<Output = Result<&str, Self::Error>> + Send;
+    /// Elect a upstream to process `req`, this is called by [`Proxy`].
+    ///
+    /// This is used by upstreams which elect by the request, such as [`StickyUpstreams`](sticky::StickyUpstreams).
+    /// The default implementation calls [`elect`](Self::elect).
+    #[inline]
+    fn elect_for(&self, _req: &Request, _depot: &Depot) -> impl Future<Output = Result<&str, Self::Error>> + Send {
+        self.elect()
+    }
+    /// Report the result of a request proxied to `upstream`.
+    ///
+    /// This is called by [`Proxy`] after each proxied request, it is used by health aware upstreams such as
+    /// [`HealthyUpstreams`](health::HealthyUpstreams). The default implementation does nothing.
+    #[inline]
+    fn report(&self, _upstream: &str, _success: bool) {}
+    /// Called by [`Proxy`] before the response of `upstream` is sent to the downstream client.
+    ///
+    /// This is used by session affinity aware upstreams such as [`StickyUpstreams`](sticky::StickyUpstreams) to
+    /// pin the client to the elected upstream. The default implementation does nothing.
+    #[inline]
+    fn on_response(&self, _upstream: &str, _req: &Request, _res: &mut Response) {}
 }
 impl Upstreams for &'static str {
     type Error = Infallible;
@@ -161,6 +220,15 @@ where
     pub url_path_getter: UrlPartGetter,
     /// Url query getter.
     pub url_query_getter: UrlPartGetter,
+    /// Idle timeout for upgraded connections and streaming response bodies.
+    pub idle_timeout: Option<Duration>,
+    /// Policy applied to the headers of proxied requests and responses.
+    pub header_policy: HeaderPolicy,
+    /// Max size of data chunks written to the downstream client.
+    pub max_chunk_size: Option<usize>,
+    /// HTTP cache of proxied responses.
+    #[cfg(feature = "cache")]
+    pub cache: Option<HttpCache>,
 }
 
 impl<U, C> Proxy<U, C>
@@ -176,6 +244,11 @@ where
             client,
             url_path_getter: Box::new(default_url_path_getter),
             url_query_getter: Box::new(default_url_query_getter),
+            idle_timeout: None,
+            header_policy: HeaderPolicy::default(),
+            max_chunk_size: None,
+            #[cfg(feature = "cache")]
+            cache: None,
         }
     }
 
@@ -199,6 +272,98 @@ where
         self
     }
 
+    /// Set idle timeout.
+    ///
+    /// Upgraded connections (such as WebSocket) are closed and streaming response bodies (such as Server-Sent
+    /// Events) are aborted when no data is transferred for this duration. By default there is no idle timeout.
+    #[inline]
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set max size of data chunks written to the downstream client.
+    ///
+    /// Response bodies are always streamed frame by frame, bigger frames received from upstream are split into
+    /// chunks of at most this size without copying.
+    #[inline]
+    pub fn max_chunk_size(mut self, size: usize) -> Self {
+        self.max_chunk_size = Some(size);
+        self
+    }
+
+    /// Set HTTP cache, responses of the upstreams are stored and reused following RFC 9111.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    #[inline]
+    pub fn cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Set header policy.
+    #[inline]
+    pub fn header_policy(mut self, header_policy: HeaderPolicy) -> Self {
+        self.header_policy = header_policy;
+        self
+    }
+
+    /// Set forwarding headers added to proxied requests.
+    #[inline]
+    pub fn forwarded_headers(mut self, forwarded: ForwardedHeaders) -> Self {
+        self.header_policy.forwarded = forwarded;
+        self
+    }
+
+    /// Set how the `Host` header of proxied requests is set.
+    #[inline]
+    pub fn host_rewrite(mut self, host: HostRewrite) -> Self {
+        self.header_policy.host = host;
+        self
+    }
+
+    /// Add a trusted downstream proxy, its forwarding headers are appended to instead of replaced.
+    #[inline]
+    pub fn trusted_downstream(mut self, network: impl Into<IpNetwork>) -> Self {
+        self.header_policy.trusted_downstreams.push(network.into());
+        self
+    }
+
+    /// Set whether hop-by-hop headers are removed, default is `true`.
+    #[inline]
+    pub fn strip_hop_by_hop(mut self, strip: bool) -> Self {
+        self.header_policy.strip_hop_by_hop = strip;
+        self
+    }
+
+    /// Set a header on proxied requests.
+    #[inline]
+    pub fn set_request_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.header_policy.set_request_headers.insert(name, value);
+        self
+    }
+
+    /// Remove a header from proxied requests.
+    #[inline]
+    pub fn remove_request_header(mut self, name: HeaderName) -> Self {
+        self.header_policy.remove_request_headers.push(name);
+        self
+    }
+
+    /// Set a header on responses.
+    #[inline]
+    pub fn set_response_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.header_policy.set_response_headers.insert(name, value);
+        self
+    }
+
+    /// Remove a header from responses.
+    #[inline]
+    pub fn remove_response_header(mut self, name: HeaderName) -> Self {
+        self.header_policy.remove_response_headers.push(name);
+        self
+    }
+
     /// Get upstreams list.
     #[inline]
     pub fn upstreams(&self) -> &U {
@@ -221,13 +386,21 @@ where
         &mut self.client
     }
 
-    async fn build_proxied_request(&self, req: &mut Request, depot: &Depot) -> Result<HyperRequest, Error> {
-        let upstream = self.upstreams.elect().await.map_err(Error::other)?;
+    async fn elect_upstream(&self, req: &Request, depot: &Depot) -> Result<&str, Error> {
+        let upstream = self.upstreams.elect_for(req, depot).await.map_err(Error::other)?;
         if upstream.is_empty() {
             tracing::error!("upstreams is empty");
             return Err(Error::other("upstreams is empty"));
         }
+        Ok(upstream)
+    }
 
+    async fn build_proxied_request(
+        &self,
+        req: &mut Request,
+        depot: &Depot,
+        upstream: &str,
+    ) -> Result<HyperRequest, Error> {
         let path = encode_url_path(&(self.url_path_getter)(req, depot).unwrap_or_default());
         let query = (self.url_query_getter)(req, depot);
         let rest = if let Some(query) = query {
@@ -249,34 +422,54 @@ where
             format!("{}/{}", upstream, rest)
         };
         let forward_url: Uri = TryFrom::try_from(forward_url).map_err(Error::other)?;
-        let mut build = hyper::Request::builder().method(req.method()).uri(&forward_url);
-        for (key, value) in req.headers() {
-            if key != HOST {
-                build = build.header(key, value);
+        let headers = self.header_policy.request_headers(req, &forward_url);
+        let mut proxied_request = hyper::Request::builder()
+            .method(req.method())
+            .uri(&forward_url)
+            .body(req.take_body())
+            .map_err(Error::other)?;
+        *proxied_request.headers_mut() = headers;
+        Ok(proxied_request)
+    }
+
+    /// Write the response of `upstream` to `res`, `upstream` is `None` if the response comes from the cache.
+    fn write_response(&self, upstream: Option<&str>, req: &Request, res: &mut Response, response: HyperResponse) {
+        let (
+            salvo_core::http::response::Parts {
+                status,
+                // version,
+                headers,
+                // extensions,
+                ..
+            },
+            body,
+        ) = response.into_parts();
+        let mut headers = headers;
+        self.header_policy
+            .response_headers(&mut headers, status == StatusCode::SWITCHING_PROTOCOLS);
+        res.status_code(status);
+        let mut last_name = None;
+        for (name, value) in headers {
+            if let Some(name) = name {
+                res.headers.remove(&name);
+                last_name = Some(name);
             }
+            if let Some(name) = &last_name {
+                res.headers.append(name.clone(), value);
+            }
+        }
+        if let Some(upstream) = upstream {
+            self.upstreams.on_response(upstream, req, res);
         }
-        if let Some(host) = forward_url.host().and_then(|host| HeaderValue::from_str(host).ok()) {
-            build = build.header(HeaderName::from_static("host"), host);
+        if status != StatusCode::SWITCHING_PROTOCOLS && (self.idle_timeout.is_some() || self.max_chunk_size.is_some()) {
+            res.body(ResBody::stream(body::StreamingBody::new(
+                body,
+                self.max_chunk_size,
+                self.idle_timeout,
+            )));
+        } else {
+            res.body(body);
         }
-        // let x_forwarded_for_header_name = "x-forwarded-for";
-        // // Add forwarding information in the headers
-        // match request.headers_mut().entry(x_forwarded_for_header_name) {
-        //     Ok(header_entry) => {
-        //         match header_entry {
-        //             hyper::header::Entry::Vacant(entry) => {
-        //                 let addr = format!("{}", client_ip);
-        //                 entry.insert(addr.parse().unwrap());
-        //             },
-        //             hyper::header::Entry::Occupied(mut entry) => {
-        //                 let addr = format!("{}, {}", entry.get().to_str().unwrap(), client_ip);
-        //                 entry.insert(addr.parse().unwrap());
-        //             }
-        //         }
-        //     }
-        //     // shouldn't happen...
-        //     Err(_) => panic!("Invalid header name: {}", x_forwarded_for_header_name),
-        // }
-        build.body(req.take_body()).map_err(Error::other)
     }
 }
 
@@ -288,31 +481,74 @@ where
     C: Client,
 {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
-        match self.build_proxied_request(req, depot).await {
-            Ok(proxied_request) => {
-                match self
-                    .client
-                    .execute(proxied_request, req.extensions_mut().remove())
-                    .await
-                {
-                    Ok(response) => {
-                        let (
-                            salvo_core::http::response::Parts {
-                                status,
-                                // version,
-                                headers,
-                                // extensions,
-                                ..
-                            },
-                            body,
-                        ) = response.into_parts();
-                        res.status_code(status);
-                        for (name, value) in headers {
-                            if let Some(name) = name {
-                                res.headers.insert(name, value);
-                            }
+        #[cfg(feature = "cache")]
+        let mut stored = None;
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache {
+            match cache.lookup(req).await {
+                cache::Lookup::Fresh(fresh) => {
+                    let response = fresh.into_response(req);
+                    self.write_response(None, req, res, response);
+                    return;
+                }
+                cache::Lookup::Unsatisfiable => {
+                    res.status_code(StatusCode::GATEWAY_TIMEOUT);
+                    return;
+                }
+                cache::Lookup::Stale(stale) => stored = Some(stale),
+                cache::Lookup::Miss => {}
+            }
+        }
+        let upstream = match self.elect_upstream(req, depot).await {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                tracing::error!(error = ?e, "elect upstream failed");
+                res.status_code(StatusCode::BAD_GATEWAY);
+                return;
+            }
+        };
+        match self.build_proxied_request(req, depot, upstream).await {
+            Ok(mut proxied_request) => {
+                if let Some(idle_timeout) = self.idle_timeout {
+                    proxied_request.extensions_mut().insert(IdleTimeout(idle_timeout));
+                }
+                let deadline = depot.deadline();
+                if let Some(deadline) = deadline {
+                    if deadline.is_expired() {
+                        res.status_code(StatusCode::GATEWAY_TIMEOUT);
+                        return;
+                    }
+                    deadline.apply(proxied_request.headers_mut());
+                }
+                #[cfg(feature = "cache")]
+                let stored = stored.filter(|stored| stored.add_validators(proxied_request.headers_mut()));
+                #[cfg(feature = "cache")]
+                let request_time = std::time::SystemTime::now();
+                let fut = self.client.execute(proxied_request, req.extensions_mut().remove());
+                let result = match deadline {
+                    Some(deadline) => match tokio::time::timeout_at(deadline.instant().into(), fut).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            // The upstream is not to blame for the budget spent before it was called.
+                            tracing::warn!(uri = ?req.uri(), "request deadline exceeded");
+                            res.status_code(StatusCode::GATEWAY_TIMEOUT);
+                            return;
                         }
-                        res.body(body);
+                    },
+                    None => fut.await,
+                };
+                self.upstreams.report(
+                    upstream,
+                    matches!(&result, Ok(response) if !health::is_failure_status(response.status())),
+                );
+                match result {
+                    Ok(response) => {
+                        #[cfg(feature = "cache")]
+                        let response = match &self.cache {
+                            Some(cache) => cache.on_response(req, stored, request_time, response).await,
+                            None => response,
+                        };
+                        self.write_response(Some(upstream), req, res, response);
                     }
                     Err(e) => {
                         tracing::error!( error = ?e, uri = ?req.uri(), "get response data failed: {}", e);
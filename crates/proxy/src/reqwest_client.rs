@@ -2,11 +2,9 @@ use futures_util::TryStreamExt;
 use hyper::upgrade::OnUpgrade;
 use reqwest::Client as InnerClient;
 use salvo_core::http::{ResBody, StatusCode};
-use salvo_core::rt::tokio::TokioIo;
 use salvo_core::Error;
-use tokio::io::copy_bidirectional;
 
-use crate::{Client, HyperRequest, BoxedError, Proxy, Upstreams, HyperResponse};
+use crate::{BoxedError, Client, HyperRequest, HyperResponse, IdleTimeout, Proxy, Upstreams};
 
 /// A [`Client`] implementation based on [`reqwest::Client`].
 #[derive(Default, Clone, Debug)]
@@ -41,6 +39,7 @@ impl Client for ReqwestClient {
         request_upgraded: Option<OnUpgrade>,
     ) -> Result<HyperResponse, Self::Error> {
         let request_upgrade_type = crate::get_upgrade_type(proxied_request.headers()).map(|s| s.to_owned());
+        let idle_timeout = proxied_request.extensions().get::<IdleTimeout>().map(|t| t.0);
 
         let proxied_request =
             proxied_request.map(|s| reqwest::Body::wrap_stream(s.map_ok(|s| s.into_data().unwrap_or_default())));
@@ -59,25 +58,12 @@ impl Client for ReqwestClient {
             let response_upgrade_type = crate::get_upgrade_type(response.headers());
 
             if request_upgrade_type.as_deref() == response_upgrade_type {
-                let mut response_upgraded = response
+                let response_upgraded = response
                     .upgrade()
                     .await
                     .map_err(|e| Error::other(format!("response does not have an upgrade extension. {}", e)))?;
                 if let Some(request_upgraded) = request_upgraded {
-                    tokio::spawn(async move {
-                        match request_upgraded.await {
-                            Ok(request_upgraded) => {
-                                let mut request_upgraded = TokioIo::new(request_upgraded);
-                                if let Err(e) = copy_bidirectional(&mut response_upgraded, &mut request_upgraded).await
-                                {
-                                    tracing::error!(error = ?e, "coping between upgraded connections failed");
-                                }
-                            }
-                            Err(e) => {
-                                tracing::error!(error = ?e, "upgrade request failed");
-                            }
-                        }
-                    });
+                    crate::upgrade::spawn_upgraded(request_upgraded, response_upgraded, idle_timeout);
                 } else {
                     return Err(Error::other("request does not have an upgrade extension"));
                 }
@@ -102,7 +88,7 @@ mod tests {
     use salvo_core::test::*;
 
     use super::*;
-    use crate::{Upstreams, Proxy};
+    use crate::{Proxy, Upstreams};
 
     #[tokio::test]
     async fn test_upstreams_elect() {
@@ -115,7 +101,8 @@ mod tests {
     #[tokio::test]
     async fn test_reqwest_client() {
         let router = Router::new().push(
-            Router::with_path("rust/<**rest>").goal(Proxy::new(vec!["https://www.rust-lang.org"], ReqwestClient::default())),
+            Router::with_path("rust/<**rest>")
+                .goal(Proxy::new(vec!["https://www.rust-lang.org"], ReqwestClient::default())),
         );
 
         let content = TestClient::get("http://127.0.0.1:5801/rust/tools/install")
@@ -0,0 +1,383 @@
+//! Session affinity for proxied backends.
+//!
+//! [`StickyUpstreams`] keeps clients pinned to the same upstream, which is required by stateful backends that keep
+//! sessions in memory. Two kinds of affinity are supported:
+//!
+//! - [`Affinity::Cookie`]: the elected upstream is remembered in a cookie set on the response. The cookie value is a
+//!   hash of the upstream url, so the internal addresses are not leaked to clients.
+//! - [`Affinity::Hash`]: a key extracted from the request (client IP, header, cookie or query parameter) is mapped to
+//!   an upstream with consistent hashing, adding or removing an upstream only moves a small part of the clients.
+//!
+//! Health of upstreams is tracked by the wrapped [`HealthyUpstreams`], [`Failover`] controls what happens when the
+//! pinned upstream is unhealthy.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_proxy::health::HealthyUpstreams;
+//! use salvo_proxy::sticky::{Affinity, StickyUpstreams};
+//! use salvo_proxy::{HyperClient, Proxy};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let upstreams = HealthyUpstreams::new(vec!["http://10.0.0.1:8080", "http://10.0.0.2:8080"]);
+//!     let upstreams = StickyUpstreams::new(upstreams, Affinity::cookie("backend"));
+//!     let client = HyperClient::builder().allow_http(true).build().unwrap();
+//!     let router = Router::with_path("<**rest>").goal(Proxy::new(upstreams, client));
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(router).await;
+//! }
+//! ```
+use salvo_core::http::header::{HeaderName, HeaderValue, COOKIE, SET_COOKIE};
+use salvo_core::{Depot, Error, Request, Response};
+
+use crate::health::HealthyUpstreams;
+use crate::Upstreams;
+
+const VIRTUAL_NODES: usize = 160;
+
+/// Key used by [`Affinity::Hash`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HashKey {
+    /// Client IP address.
+    ClientIp,
+    /// Value of a request header.
+    Header(HeaderName),
+    /// Value of a request cookie.
+    Cookie(String),
+    /// Value of a query parameter.
+    Query(String),
+}
+
+/// Session affinity kind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Affinity {
+    /// Pin clients with a cookie.
+    Cookie {
+        /// Cookie name.
+        name: String,
+        /// Cookie path.
+        path: String,
+        /// Cookie max age in seconds, session cookie if `None`.
+        max_age: Option<u64>,
+        /// Set `Secure` attribute.
+        secure: bool,
+    },
+    /// Pin clients by consistent hashing of a request key.
+    Hash(HashKey),
+}
+impl Affinity {
+    /// Create a cookie affinity with the given cookie name.
+    #[inline]
+    pub fn cookie(name: impl Into<String>) -> Self {
+        Self::Cookie {
+            name: name.into(),
+            path: "/".into(),
+            max_age: None,
+            secure: false,
+        }
+    }
+    /// Create a consistent hash affinity with the given key.
+    #[inline]
+    pub fn hash(key: HashKey) -> Self {
+        Self::Hash(key)
+    }
+}
+
+/// What to do when the pinned upstream is unhealthy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Failover {
+    /// Elect another healthy upstream, the client is pinned to the new upstream.
+    #[default]
+    Repin,
+    /// Fail the request, the client stays pinned and will reach the upstream again once it recovered.
+    Reject,
+}
+
+/// Upstreams which keep clients pinned to the same upstream.
+///
+/// View [module level documentation](index.html) for more details.
+pub struct StickyUpstreams<T> {
+    upstreams: HealthyUpstreams<T>,
+    affinity: Affinity,
+    failover: Failover,
+    ids: Vec<String>,
+    ring: Vec<(u64, usize)>,
+}
+
+impl<T> StickyUpstreams<T>
+where
+    T: AsRef<str> + Send + Sync + 'static,
+{
+    /// Create a new `StickyUpstreams`.
+    pub fn new(upstreams: HealthyUpstreams<T>, affinity: Affinity) -> Self {
+        let ids = (0..upstreams.len())
+            .map(|index| format!("{:016x}", fnv1a(upstreams.get(index).unwrap_or_default().as_bytes())))
+            .collect();
+        let mut ring = Vec::with_capacity(upstreams.len() * VIRTUAL_NODES);
+        for index in 0..upstreams.len() {
+            let upstream = upstreams.get(index).unwrap_or_default();
+            for node in 0..VIRTUAL_NODES {
+                ring.push((ring_hash(format!("{upstream}#{node}").as_bytes()), index));
+            }
+        }
+        ring.sort_unstable();
+        Self {
+            upstreams,
+            affinity,
+            failover: Failover::default(),
+            ids,
+            ring,
+        }
+    }
+
+    /// Set failover behavior.
+    #[inline]
+    pub fn failover(mut self, failover: Failover) -> Self {
+        self.failover = failover;
+        self
+    }
+
+    /// Get the wrapped health aware upstreams.
+    #[inline]
+    pub fn upstreams(&self) -> &HealthyUpstreams<T> {
+        &self.upstreams
+    }
+
+    fn hash_key(&self, key: &HashKey, req: &Request) -> Option<String> {
+        match key {
+            HashKey::ClientIp => req
+                .remote_addr()
+                .clone()
+                .into_std()
+                .map(|addr| addr.ip().to_canonical().to_string()),
+            HashKey::Header(name) => req.headers().get(name).and_then(|v| v.to_str().ok()).map(Into::into),
+            HashKey::Cookie(name) => request_cookie(req, name),
+            HashKey::Query(name) => req.queries().get(name).cloned(),
+        }
+    }
+
+    /// Walk the hash ring from `key`, returns the first upstream and the first healthy upstream.
+    fn lookup(&self, key: &str) -> (usize, Option<usize>) {
+        let hash = ring_hash(key.as_bytes());
+        let start = self.ring.partition_point(|(h, _)| *h < hash);
+        let first = self.ring[start % self.ring.len()].1;
+        let healthy = (0..self.ring.len())
+            .map(|offset| self.ring[(start + offset) % self.ring.len()].1)
+            .find(|index| self.upstreams.is_healthy(*index));
+        (first, healthy)
+    }
+
+    fn pinned(&self, req: &Request) -> Option<(usize, Option<usize>)> {
+        match &self.affinity {
+            Affinity::Cookie { name, .. } => {
+                let id = request_cookie(req, name)?;
+                let index = self.ids.iter().position(|i| *i == id)?;
+                if self.upstreams.is_healthy(index) {
+                    Some((index, Some(index)))
+                } else {
+                    Some((index, None))
+                }
+            }
+            Affinity::Hash(key) => {
+                let key = self.hash_key(key, req)?;
+                Some(self.lookup(&key))
+            }
+        }
+    }
+
+    fn elect_any(&self) -> usize {
+        let healthy = self.upstreams.healthy_indices();
+        if healthy.is_empty() {
+            tracing::warn!("no healthy upstream available, electing from all upstreams");
+            fastrand::usize(..self.upstreams.len())
+        } else {
+            healthy[fastrand::usize(..healthy.len())]
+        }
+    }
+}
+
+impl<T> Upstreams for StickyUpstreams<T>
+where
+    T: AsRef<str> + Send + Sync + 'static,
+{
+    type Error = Error;
+
+    /// Elect a healthy upstream without affinity.
+    async fn elect(&self) -> Result<&str, Self::Error> {
+        if self.upstreams.is_empty() {
+            return Err(Error::other("upstreams is empty"));
+        }
+        self.upstreams.start_active();
+        self.upstreams
+            .get(self.elect_any())
+            .ok_or_else(|| Error::other("upstream index out of range"))
+    }
+
+    async fn elect_for(&self, req: &Request, _depot: &Depot) -> Result<&str, Self::Error> {
+        if self.upstreams.is_empty() {
+            return Err(Error::other("upstreams is empty"));
+        }
+        self.upstreams.start_active();
+        let index = match self.pinned(req) {
+            Some((pinned, Some(healthy))) if pinned == healthy || self.failover == Failover::Repin => healthy,
+            Some((pinned, _)) => match self.failover {
+                Failover::Repin => {
+                    tracing::debug!(
+                        upstream = self.upstreams.get(pinned),
+                        "pinned upstream is unhealthy, repin"
+                    );
+                    self.elect_any()
+                }
+                Failover::Reject => {
+                    return Err(Error::other("pinned upstream is unhealthy"));
+                }
+            },
+            None => self.elect_any(),
+        };
+        self.upstreams
+            .get(index)
+            .ok_or_else(|| Error::other("upstream index out of range"))
+    }
+
+    #[inline]
+    fn report(&self, upstream: &str, success: bool) {
+        self.upstreams.report(upstream, success);
+    }
+
+    fn on_response(&self, upstream: &str, req: &Request, res: &mut Response) {
+        let Affinity::Cookie {
+            name,
+            path,
+            max_age,
+            secure,
+        } = &self.affinity
+        else {
+            return;
+        };
+        let Some(id) = self.upstreams.position(upstream).and_then(|index| self.ids.get(index)) else {
+            return;
+        };
+        if request_cookie(req, name).as_deref() == Some(id.as_str()) {
+            return;
+        }
+        let mut cookie = format!("{name}={id}; Path={path}; HttpOnly; SameSite=Lax");
+        if let Some(max_age) = max_age {
+            cookie.push_str(&format!("; Max-Age={max_age}"));
+        }
+        if *secure {
+            cookie.push_str("; Secure");
+        }
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            res.headers_mut().append(SET_COOKIE, value);
+        }
+    }
+}
+
+fn request_cookie(req: &Request, name: &str) -> Option<String> {
+    req.headers()
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.trim_matches('"').to_owned())
+}
+
+/// 64-bit FNV-1a hash, stable across processes and platforms.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// FNV-1a followed by the murmur3 finalizer, so similar keys are spread evenly over the ring.
+fn ring_hash(bytes: &[u8]) -> u64 {
+    let mut hash = fnv1a(bytes);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^ (hash >> 33)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::health::PassiveCheck;
+
+    const UPSTREAMS: [&str; 3] = ["http://a.local", "http://b.local", "http://c.local"];
+
+    fn request_with_header(name: &'static str, value: &str) -> Request {
+        let mut req = Request::default();
+        req.headers_mut().insert(name, HeaderValue::from_str(value).unwrap());
+        req
+    }
+
+    #[tokio::test]
+    async fn test_cookie_affinity() {
+        let upstreams = StickyUpstreams::new(HealthyUpstreams::new(UPSTREAMS.to_vec()), Affinity::cookie("backend"));
+        let depot = Depot::new();
+
+        let req = Request::default();
+        let elected = upstreams.elect_for(&req, &depot).await.unwrap();
+        let mut res = Response::new();
+        upstreams.on_response(elected, &req, &mut res);
+        let set_cookie = res.headers().get(SET_COOKIE).unwrap().to_str().unwrap().to_owned();
+        let pair = set_cookie.split(';').next().unwrap().to_owned();
+
+        let req = request_with_header("cookie", &pair);
+        for _ in 0..10 {
+            assert_eq!(upstreams.elect_for(&req, &depot).await.unwrap(), elected);
+        }
+        let mut res = Response::new();
+        upstreams.on_response(elected, &req, &mut res);
+        assert!(res.headers().get(SET_COOKIE).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hash_affinity_and_failover() {
+        let healthy = HealthyUpstreams::new(UPSTREAMS.to_vec()).passive(PassiveCheck::new(1, Duration::from_secs(60)));
+        let upstreams = StickyUpstreams::new(
+            healthy,
+            Affinity::hash(HashKey::Header(HeaderName::from_static("x-user"))),
+        );
+        let depot = Depot::new();
+        let req = request_with_header("x-user", "alice");
+        let pinned = upstreams.elect_for(&req, &depot).await.unwrap();
+        for _ in 0..10 {
+            assert_eq!(upstreams.elect_for(&req, &depot).await.unwrap(), pinned);
+        }
+
+        upstreams.report(pinned, false);
+        let repinned = upstreams.elect_for(&req, &depot).await.unwrap();
+        assert_ne!(repinned, pinned);
+
+        let upstreams = upstreams.failover(Failover::Reject);
+        assert!(upstreams.elect_for(&req, &depot).await.is_err());
+    }
+
+    #[test]
+    fn test_consistent_hash_distribution() {
+        let upstreams = StickyUpstreams::new(
+            HealthyUpstreams::new(UPSTREAMS.to_vec()),
+            Affinity::hash(HashKey::ClientIp),
+        );
+        let mut counts = [0usize; 3];
+        for i in 0..3000 {
+            counts[upstreams.lookup(&format!("user-{i}")).0] += 1;
+        }
+        assert!(counts.iter().all(|count| *count > 500), "{counts:?}");
+    }
+}
@@ -0,0 +1,230 @@
+//! Forward proxy support with `CONNECT` tunneling.
+//!
+//! [`ConnectTunnel`] handles `CONNECT` requests by opening a TCP connection to the requested target and copying data
+//! between the client and the target once the connection is upgraded. Other requests are passed through, so it is
+//! usually added as a hoop of the [`Service`](salvo_core::Service), because `CONNECT` requests only carry an
+//! authority and do not match any router path.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::http::uri::Authority;
+//! use salvo_core::prelude::*;
+//! use salvo_proxy::tunnel::ConnectTunnel;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let tunnel = ConnectTunnel::new(|req: &Request, _target: &Authority| {
+//!         req.headers().get("proxy-authorization").map(|v| v == "Basic cm9vdDpwd2Q=").unwrap_or(false)
+//!     })
+//!     .proxy_authenticate("Basic realm=\"proxy\"");
+//!     let service = Service::new(Router::new()).hoop(tunnel);
+//!
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     Server::new(acceptor).serve(service).await;
+//! }
+//! ```
+use std::future::Future;
+use std::time::Duration;
+
+use hyper::upgrade::OnUpgrade;
+use salvo_core::http::header::{HeaderValue, PROXY_AUTHENTICATE};
+use salvo_core::http::uri::Authority;
+use salvo_core::http::{Method, StatusCode};
+use salvo_core::rt::tokio::TokioIo;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+use tokio::net::TcpStream;
+
+use crate::copy_upgraded;
+
+/// Decide whether a `CONNECT` request is allowed to open a tunnel to `target`.
+pub trait TunnelAuthorizer: Send + Sync + 'static {
+    /// Returns `true` if the tunnel is allowed.
+    fn authorize(&self, req: &Request, depot: &mut Depot, target: &Authority) -> impl Future<Output = bool> + Send;
+}
+impl<F> TunnelAuthorizer for F
+where
+    F: Fn(&Request, &Authority) -> bool + Send + Sync + 'static,
+{
+    #[inline]
+    async fn authorize(&self, req: &Request, _depot: &mut Depot, target: &Authority) -> bool {
+        self(req, target)
+    }
+}
+
+/// Handler for `CONNECT` requests.
+///
+/// View [module level documentation](index.html) for more details.
+pub struct ConnectTunnel<A> {
+    authorizer: A,
+    allowed_ports: Vec<u16>,
+    connect_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    proxy_authenticate: Option<HeaderValue>,
+}
+
+impl<A> ConnectTunnel<A>
+where
+    A: TunnelAuthorizer,
+{
+    /// Create a new `ConnectTunnel`.
+    ///
+    /// Only port `443` is allowed by default, use [`allowed_ports`](Self::allowed_ports) to change it.
+    #[inline]
+    pub fn new(authorizer: A) -> Self {
+        Self {
+            authorizer,
+            allowed_ports: vec![443],
+            connect_timeout: Duration::from_secs(10),
+            idle_timeout: None,
+            proxy_authenticate: None,
+        }
+    }
+
+    /// Set ports allowed as tunnel targets, all ports are allowed if empty.
+    #[inline]
+    pub fn allowed_ports(mut self, ports: impl IntoIterator<Item = u16>) -> Self {
+        self.allowed_ports = ports.into_iter().collect();
+        self
+    }
+
+    /// Set timeout for connecting to the target.
+    #[inline]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Close the tunnel when no data is transferred in either direction for `timeout`.
+    #[inline]
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Respond with `407 Proxy Authentication Required` and this `Proxy-Authenticate` challenge when the request
+    /// is not authorized, `403 Forbidden` is used otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `challenge` is not a valid header value.
+    #[inline]
+    pub fn proxy_authenticate(mut self, challenge: &str) -> Self {
+        self.proxy_authenticate = Some(HeaderValue::from_str(challenge).expect("invalid proxy authenticate challenge"));
+        self
+    }
+
+    fn is_port_allowed(&self, port: u16) -> bool {
+        self.allowed_ports.is_empty() || self.allowed_ports.contains(&port)
+    }
+}
+
+#[async_trait]
+impl<A> Handler for ConnectTunnel<A>
+where
+    A: TunnelAuthorizer,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        if req.method() != Method::CONNECT {
+            return;
+        }
+        ctrl.skip_rest();
+
+        let Some(target) = req.uri().authority().cloned() else {
+            res.status_code(StatusCode::BAD_REQUEST);
+            return;
+        };
+        let Some(port) = target.port_u16() else {
+            res.status_code(StatusCode::BAD_REQUEST);
+            return;
+        };
+        if !self.is_port_allowed(port) {
+            tracing::debug!(authority = %target, "tunnel port is not allowed");
+            res.status_code(StatusCode::FORBIDDEN);
+            return;
+        }
+        if !self.authorizer.authorize(req, depot, &target).await {
+            if let Some(challenge) = &self.proxy_authenticate {
+                res.headers_mut().insert(PROXY_AUTHENTICATE, challenge.clone());
+                res.status_code(StatusCode::PROXY_AUTHENTICATION_REQUIRED);
+            } else {
+                res.status_code(StatusCode::FORBIDDEN);
+            }
+            return;
+        }
+        let Some(on_upgrade) = req.extensions_mut().remove::<OnUpgrade>() else {
+            tracing::error!("request does not have an upgrade extension");
+            res.status_code(StatusCode::BAD_REQUEST);
+            return;
+        };
+
+        let stream = match tokio::time::timeout(self.connect_timeout, TcpStream::connect(target.as_str())).await {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(e)) => {
+                tracing::debug!(error = ?e, authority = %target, "connect tunnel target failed");
+                res.status_code(StatusCode::BAD_GATEWAY);
+                return;
+            }
+            Err(_) => {
+                tracing::debug!(authority = %target, "connect tunnel target timed out");
+                res.status_code(StatusCode::GATEWAY_TIMEOUT);
+                return;
+            }
+        };
+        let idle_timeout = self.idle_timeout;
+        tokio::spawn(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => {
+                    if let Err(e) = copy_upgraded(TokioIo::new(upgraded), stream, idle_timeout).await {
+                        tracing::debug!(error = ?e, authority = %target, "tunnel closed");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = ?e, "upgrade connect request failed");
+                }
+            }
+        });
+        res.status_code(StatusCode::OK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::prelude::*;
+
+    use super::*;
+
+    fn connect_request(target: &str) -> Request {
+        let mut req = Request::default();
+        *req.method_mut() = Method::CONNECT;
+        *req.uri_mut() = target.parse().unwrap();
+        req
+    }
+
+    #[tokio::test]
+    async fn test_port_not_allowed() {
+        let tunnel = ConnectTunnel::new(|_: &Request, _: &Authority| true);
+        let service = Service::new(Router::new()).hoop(tunnel);
+        let res = service.handle(connect_request("example.com:22")).await;
+        assert_eq!(res.status_code, Some(StatusCode::FORBIDDEN));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_authenticate() {
+        let tunnel =
+            ConnectTunnel::new(|req: &Request, _: &Authority| req.headers().contains_key("proxy-authorization"))
+                .proxy_authenticate("Basic realm=\"proxy\"");
+        let service = Service::new(Router::new()).hoop(tunnel);
+        let res = service.handle(connect_request("example.com:443")).await;
+        assert_eq!(res.status_code, Some(StatusCode::PROXY_AUTHENTICATION_REQUIRED));
+        assert_eq!(res.headers().get(PROXY_AUTHENTICATE).unwrap(), "Basic realm=\"proxy\"");
+    }
+
+    #[tokio::test]
+    async fn test_missing_port() {
+        let tunnel = ConnectTunnel::new(|_: &Request, _: &Authority| true);
+        let service = Service::new(Router::new()).hoop(tunnel);
+        let res = service.handle(connect_request("example.com")).await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,166 @@
+use std::io::{Error as IoError, ErrorKind, IoSlice, Result as IoResult};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use hyper::upgrade::OnUpgrade;
+use salvo_core::rt::tokio::TokioIo;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Idle timeout applied to proxied connections.
+///
+/// [`Proxy`](crate::Proxy) inserts this value into the extensions of the proxied request when
+/// [`Proxy::idle_timeout`](crate::Proxy::idle_timeout) is set, [`Client`](crate::Client) implementations should pass
+/// it to [`copy_upgraded`] when the connection is upgraded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdleTimeout(pub Duration);
+
+/// Copy data between the upgraded downstream connection and the upgraded upstream connection until both sides are
+/// closed, or no data is transferred in either direction for `idle_timeout`.
+pub async fn copy_upgraded<A, B>(downstream: A, upstream: B, idle_timeout: Option<Duration>) -> IoResult<(u64, u64)>
+where
+    A: AsyncRead + AsyncWrite + Unpin,
+    B: AsyncRead + AsyncWrite + Unpin,
+{
+    let Some(idle_timeout) = idle_timeout else {
+        let (mut downstream, mut upstream) = (downstream, upstream);
+        return tokio::io::copy_bidirectional(&mut downstream, &mut upstream).await;
+    };
+    let started = Instant::now();
+    let active = Arc::new(AtomicU64::new(0));
+    let mut downstream = Tracked::new(downstream, started, active.clone());
+    let mut upstream = Tracked::new(upstream, started, active.clone());
+    let copy = tokio::io::copy_bidirectional(&mut downstream, &mut upstream);
+    tokio::pin!(copy);
+    loop {
+        let deadline = started + Duration::from_millis(active.load(Ordering::Relaxed)) + idle_timeout;
+        tokio::select! {
+            result = &mut copy => return result,
+            _ = tokio::time::sleep_until(deadline.into()) => {
+                if Instant::now() >= started + Duration::from_millis(active.load(Ordering::Relaxed)) + idle_timeout {
+                    return Err(IoError::new(ErrorKind::TimedOut, "upgraded connection idle timeout"));
+                }
+            }
+        }
+    }
+}
+
+/// Spawn a task which waits for the downstream upgrade and copies data with the upgraded upstream connection.
+pub(crate) fn spawn_upgraded<B>(request_upgraded: OnUpgrade, response_upgraded: B, idle_timeout: Option<Duration>)
+where
+    B: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        match request_upgraded.await {
+            Ok(request_upgraded) => {
+                if let Err(e) = copy_upgraded(TokioIo::new(request_upgraded), response_upgraded, idle_timeout).await {
+                    if e.kind() == ErrorKind::TimedOut {
+                        tracing::debug!(error = ?e, "upgraded connections closed.");
+                    } else {
+                        tracing::error!(error = ?e, "coping between upgraded connections failed.");
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "upgrade request failed.");
+            }
+        }
+    });
+}
+
+/// IO wrapper recording the last time data is transferred.
+struct Tracked<T> {
+    inner: T,
+    started: Instant,
+    active: Arc<AtomicU64>,
+}
+impl<T> Tracked<T> {
+    fn new(inner: T, started: Instant, active: Arc<AtomicU64>) -> Self {
+        Self { inner, started, active }
+    }
+    fn touch(&self) {
+        self.active
+            .store(self.started.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+}
+impl<T> AsyncRead for Tracked<T>
+where
+    T: AsyncRead + Unpin,
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<IoResult<()>> {
+        let filled = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if matches!(result, Poll::Ready(Ok(()))) && buf.filled().len() > filled {
+            self.touch();
+        }
+        result
+    }
+}
+impl<T> AsyncWrite for Tracked<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<IoResult<usize>> {
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if matches!(result, Poll::Ready(Ok(n)) if n > 0) {
+            self.touch();
+        }
+        result
+    }
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<IoResult<usize>> {
+        let result = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        if matches!(result, Poll::Ready(Ok(n)) if n > 0) {
+            self.touch();
+        }
+        result
+    }
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_copy_upgraded_idle_timeout() {
+        let (downstream, _downstream_peer) = tokio::io::duplex(64);
+        let (upstream, _upstream_peer) = tokio::io::duplex(64);
+        let result = copy_upgraded(downstream, upstream, Some(Duration::from_millis(50))).await;
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_copy_upgraded_transfer() {
+        let (downstream, mut downstream_peer) = tokio::io::duplex(64);
+        let (upstream, mut upstream_peer) = tokio::io::duplex(64);
+        let task = tokio::spawn(copy_upgraded(downstream, upstream, Some(Duration::from_secs(5))));
+
+        downstream_peer.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        upstream_peer.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+        upstream_peer.write_all(b"pong").await.unwrap();
+        downstream_peer.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"pong");
+
+        drop(downstream_peer);
+        drop(upstream_peer);
+        assert_eq!(task.await.unwrap().unwrap(), (4, 4));
+    }
+}
@@ -31,6 +31,10 @@ impl FixedGuard {
 impl RateGuard for FixedGuard {
     type Quota = BasicQuota;
     async fn verify(&mut self, quota: &Self::Quota) -> bool {
+        self.verify_cost(quota, 1).await
+    }
+
+    async fn verify_cost(&mut self, quota: &Self::Quota, cost: usize) -> bool {
         if self.quota.is_none() || OffsetDateTime::now_utc() > self.reset || self.quota.as_ref() != Some(quota) {
             if self.quota.as_ref() != Some(quota) {
                 let mut quota = quota.clone();
@@ -40,18 +44,24 @@ impl RateGuard for FixedGuard {
                 self.quota = Some(quota);
             }
             self.reset = OffsetDateTime::now_utc() + quota.period;
-            self.count = 1;
-            true
-        } else if self.count < quota.limit {
-            self.count += 1;
+            self.count = 0;
+        }
+        // The first request of a window is checked too, so a single request can not cost more than the limit.
+        let limit = self.quota.as_ref().map_or(quota.limit, |quota| quota.limit);
+        if self.count + cost.max(1) <= limit {
+            self.count += cost;
             true
         } else {
             false
         }
     }
 
+    async fn adjust(&mut self, _: &Self::Quota, delta: isize) {
+        self.count = self.count.saturating_add_signed(delta);
+    }
+
     async fn remaining(&self, quota: &Self::Quota) -> usize {
-        quota.limit - self.count
+        quota.limit.saturating_sub(self.count)
     }
 
     async fn reset(&self, _: &Self::Quota) -> i64 {
@@ -10,6 +10,11 @@
 //!
 //! [`RateGuard`] is strategy to verify is the request exceeded quota.
 //!
+//! By default every request costs 1. For APIs where one request is not equal to another, such as by query
+//! complexity or bytes processed, set the cost charged before the request is handled with
+//! [`RateLimiter::pre_charge`], and report the actual cost from the handler with
+//! [`RateCostDepotExt::set_rate_cost`], the difference is charged after the request is handled.
+//!
 //! Read more: <https://salvo.rs>
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
@@ -51,6 +56,28 @@ cfg_feature! {
     pub use sliding_guard::SlidingGuard;
 }
 
+/// Key of the cost of the current request reported by handlers in the depot.
+pub const RATE_COST_KEY: &str = "::salvo::rate_limiter::cost";
+
+/// Extension of [`Depot`] for reporting the cost of the current request.
+pub trait RateCostDepotExt {
+    /// Set the actual cost of the current request, it is charged against the quota after the request is handled.
+    fn set_rate_cost(&mut self, cost: usize) -> &mut Self;
+    /// Get the cost of the current request reported by handlers.
+    fn rate_cost(&self) -> Option<usize>;
+}
+
+impl RateCostDepotExt for Depot {
+    #[inline]
+    fn set_rate_cost(&mut self, cost: usize) -> &mut Self {
+        self.insert(RATE_COST_KEY, cost)
+    }
+    #[inline]
+    fn rate_cost(&self) -> Option<usize> {
+        self.get::<usize>(RATE_COST_KEY).ok().copied()
+    }
+}
+
 /// Issuer is used to identify every request.
 pub trait RateIssuer: Send + Sync + 'static {
     /// The key is used to identify the rate limit.
@@ -89,6 +116,23 @@ pub trait RateGuard: Clone + Send + Sync + 'static {
     /// Verify is current request exceed the quota.
     fn verify(&mut self, quota: &Self::Quota) -> impl Future<Output = bool> + Send;
 
+    /// Verify is current request exceed the quota when it costs `cost`, the cost is charged if it does not.
+    ///
+    /// The default charges the request as [`verify`](Self::verify) does, whatever the cost is.
+    fn verify_cost(&mut self, quota: &Self::Quota, cost: usize) -> impl Future<Output = bool> + Send {
+        let _ = cost;
+        self.verify(quota)
+    }
+
+    /// Adjust the charged cost by `delta` after the request is handled, when its actual cost differs from the
+    /// pre-charged one.
+    ///
+    /// The default does nothing.
+    fn adjust(&mut self, quota: &Self::Quota, delta: isize) -> impl Future<Output = ()> + Send {
+        let _ = (quota, delta);
+        async {}
+    }
+
     /// Returns the remaining quota.
     fn remaining(&self, quota: &Self::Quota) -> impl Future<Output = usize> + Send;
 
@@ -127,6 +171,7 @@ pub struct RateLimiter<G, S, I, Q> {
     issuer: I,
     quota_getter: Q,
     add_headers: bool,
+    pre_charge: usize,
     skipper: Box<dyn Skipper>,
 }
 
@@ -140,6 +185,7 @@ impl<G: RateGuard, S: RateStore, I: RateIssuer, P: QuotaGetter<I::Key>> RateLimi
             issuer,
             quota_getter,
             add_headers: false,
+            pre_charge: 1,
             skipper: Box::new(none_skipper),
         }
     }
@@ -158,6 +204,16 @@ impl<G: RateGuard, S: RateStore, I: RateIssuer, P: QuotaGetter<I::Key>> RateLimi
         self.add_headers = add_headers;
         self
     }
+
+    /// Sets the cost charged before the request is handled and returns new `RateLimiter`, the default is 1.
+    ///
+    /// When a handler reports the actual cost with [`RateCostDepotExt::set_rate_cost`], the difference with the
+    /// pre-charged cost is charged after the request is handled.
+    #[inline]
+    pub fn pre_charge(mut self, cost: usize) -> Self {
+        self.pre_charge = cost;
+        self
+    }
 }
 
 #[async_trait]
@@ -167,6 +223,8 @@ where
     S: RateStore<Key = I::Key, Guard = G>,
     P: QuotaGetter<I::Key>,
     I: RateIssuer,
+    // Already required by the key of the store, the key is saved before and after the request is handled.
+    I::Key: Clone,
 {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
         if self.skipper.skipped(req, depot) {
@@ -198,7 +256,7 @@ where
                 return;
             }
         };
-        let verified = guard.verify(&quota).await;
+        let verified = guard.verify_cost(&quota, self.pre_charge).await;
 
         if self.add_headers {
             res.headers_mut().insert(
@@ -218,6 +276,29 @@ where
             res.status_code(StatusCode::TOO_MANY_REQUESTS);
             ctrl.skip_rest();
         }
+        if let Err(e) = self.store.save_guard(key.clone(), guard).await {
+            tracing::error!(error = ?e, "RateLimiter save guard failed");
+        }
+        if !verified {
+            return;
+        }
+
+        ctrl.call_next(req, depot, res).await;
+        let Some(cost) = depot.rate_cost() else {
+            return;
+        };
+        let delta = cost as isize - self.pre_charge as isize;
+        if delta == 0 {
+            return;
+        }
+        let mut guard = match self.store.load_guard(&key, &self.guard).await {
+            Ok(guard) => guard,
+            Err(e) => {
+                tracing::error!(error = ?e, "RateLimiter error: {}", e);
+                return;
+            }
+        };
+        guard.adjust(&quota, delta).await;
         if let Err(e) = self.store.save_guard(key, guard).await {
             tracing::error!(error = ?e, "RateLimiter save guard failed");
         }
@@ -407,4 +488,75 @@ mod tests {
         assert_eq!(respone.status_code, Some(StatusCode::OK));
         assert_eq!(respone.take_string().await.unwrap(), "Limited page");
     }
+
+    #[tokio::test]
+    async fn test_fixed_cost() {
+        #[handler]
+        async fn query(req: &mut Request, depot: &mut Depot) -> &'static str {
+            depot.set_rate_cost(req.query::<usize>("cost").unwrap_or(1));
+            "Query result"
+        }
+        let limiter = RateLimiter::new(
+            FixedGuard::default(),
+            MokaStore::default(),
+            UserIssuer,
+            BasicQuota::per_minute(10),
+        )
+        .pre_charge(1)
+        .add_headers(true);
+        let router = Router::new().push(Router::with_path("query").hoop(limiter).get(query));
+        let service = Service::new(router);
+
+        for cost in [6, 3, 1] {
+            let respone = TestClient::get(format!("http://127.0.0.1:5800/query?user=user1&cost={cost}"))
+                .send(&service)
+                .await;
+            assert_eq!(respone.status_code, Some(StatusCode::OK));
+        }
+        let respone = TestClient::get("http://127.0.0.1:5800/query?user=user1&cost=1")
+            .send(&service)
+            .await;
+        assert_eq!(respone.status_code, Some(StatusCode::TOO_MANY_REQUESTS));
+
+        let respone = TestClient::get("http://127.0.0.1:5800/query?user=user2&cost=12")
+            .send(&service)
+            .await;
+        assert_eq!(respone.status_code, Some(StatusCode::OK));
+        let respone = TestClient::get("http://127.0.0.1:5800/query?user=user2&cost=1")
+            .send(&service)
+            .await;
+        assert_eq!(respone.status_code, Some(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(respone.headers().get("X-RateLimit-Remaining").unwrap(), "0");
+    }
+
+    #[tokio::test]
+    async fn test_first_cost_over_limit() {
+        let quota = BasicQuota::per_minute(5);
+        let mut guard = FixedGuard::new();
+        assert!(!guard.verify_cost(&quota, 6).await);
+        assert!(guard.verify_cost(&quota, 5).await);
+        assert!(!guard.verify_cost(&quota, 1).await);
+
+        let quota = CelledQuota::per_minute(5, 5);
+        let mut guard = SlidingGuard::new();
+        assert!(!guard.verify_cost(&quota, 6).await);
+        assert!(guard.verify_cost(&quota, 5).await);
+        assert!(!guard.verify_cost(&quota, 1).await);
+    }
+
+    #[tokio::test]
+    async fn test_sliding_cost() {
+        let quota = CelledQuota::per_minute(10, 5);
+        let mut guard = SlidingGuard::new();
+        assert!(guard.verify_cost(&quota, 4).await);
+        assert!(guard.verify_cost(&quota, 4).await);
+        // Denied requests are not charged.
+        assert!(!guard.verify_cost(&quota, 3).await);
+        assert_eq!(guard.remaining(&quota).await, 2);
+        // The refund is taken from the cells charged by the previous requests.
+        guard.adjust(&quota, -6).await;
+        assert_eq!(guard.remaining(&quota).await, 8);
+        assert!(guard.verify_cost(&quota, 8).await);
+        assert_eq!(guard.remaining(&quota).await, 0);
+    }
 }
@@ -34,6 +34,10 @@ impl SlidingGuard {
 impl RateGuard for SlidingGuard {
     type Quota = CelledQuota;
     async fn verify(&mut self, quota: &Self::Quota) -> bool {
+        self.verify_cost(quota, 1).await
+    }
+
+    async fn verify_cost(&mut self, quota: &Self::Quota, cost: usize) -> bool {
         if self.quota.is_none() || self.quota.as_ref() != Some(quota) {
             let mut quota = quota.clone();
             if quota.limit == 0 {
@@ -49,16 +53,23 @@ impl RateGuard for SlidingGuard {
             self.cell_span = quota.period / (quota.cells as u32);
             self.counts = vec![0; quota.cells];
             self.head = 0;
-            self.counts[0] = 1;
+            let allowed = cost <= quota.limit;
+            if allowed {
+                self.counts[0] = cost;
+            }
             self.quota = Some(quota);
-            return true;
+            return allowed;
         }
         let mut delta = OffsetDateTime::now_utc() - self.cell_inst;
         if delta > quota.period {
             self.counts = vec![0; quota.cells];
             self.head = 0;
-            self.counts[0] = 1;
             self.cell_inst = OffsetDateTime::now_utc();
+            let limit = self.quota.as_ref().map_or(quota.limit, |quota| quota.limit);
+            if cost > limit {
+                return false;
+            }
+            self.counts[0] = cost;
             return true;
         } else {
             while delta > self.cell_span {
@@ -67,17 +78,41 @@ impl RateGuard for SlidingGuard {
                 self.counts[self.head] = 0;
             }
             self.head = (self.head + 1) % self.counts.len();
-            self.counts[self.head] += 1;
             self.cell_inst = OffsetDateTime::now_utc();
         }
-        self.counts.iter().cloned().sum::<usize>() <= quota.limit
+        // A request without cost is only allowed while the quota is not used up, a denied request is not charged.
+        let used = self.counts.iter().cloned().sum::<usize>();
+        if used + cost.max(1) > quota.limit {
+            return false;
+        }
+        self.counts[self.head] += cost;
+        true
+    }
+
+    async fn adjust(&mut self, _: &Self::Quota, delta: isize) {
+        if self.counts.is_empty() {
+            return;
+        }
+        if delta >= 0 {
+            self.counts[self.head] += delta.unsigned_abs();
+            return;
+        }
+        // Refunds are taken from the latest cells first, the head cell may hold less than the pre-charged cost.
+        let mut refund = delta.unsigned_abs();
+        let len = self.counts.len();
+        for i in 0..len {
+            if refund == 0 {
+                break;
+            }
+            let count = &mut self.counts[(self.head + len - i) % len];
+            let taken = refund.min(*count);
+            *count -= taken;
+            refund -= taken;
+        }
     }
 
     async fn remaining(&self, quota: &Self::Quota) -> usize {
-        quota
-            .limit
-            .checked_sub(self.counts.iter().cloned().sum::<usize>())
-            .unwrap_or_default()
+        quota.limit.saturating_sub(self.counts.iter().cloned().sum::<usize>())
     }
 
     async fn reset(&self, quota: &Self::Quota) -> i64 {
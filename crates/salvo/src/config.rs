@@ -0,0 +1,710 @@
+//! Build servers from configuration files and environment variables.
+//!
+//! A [`ServerConfig`] describes the listeners, the limits, the middlewares and the static directories of a server.
+//! [`ConfigLoader`] layers TOML, YAML or JSON files and environment variables, later sources override earlier ones,
+//! so a deployment can change ports and certificates without recompiling.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo::config::ConfigLoader;
+//! use salvo::prelude::*;
+//!
+//! #[handler]
+//! async fn hello() -> &'static str {
+//!     "Hello World"
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let config = ConfigLoader::new()
+//!         .toml(r#"
+//!             [[listeners]]
+//!             addr = "0.0.0.0:5800"
+//!
+//!             [limits]
+//!             request_timeout = 30
+//!         "#)
+//!         .file("salvo.toml")
+//!         .env("SALVO")
+//!         .build()
+//!         .unwrap();
+//!     config.serve(Router::new().get(hello)).await.unwrap();
+//! }
+//! ```
+//!
+//! With the loader above, `SALVO_LISTENERS__0__ADDR=0.0.0.0:8080` moves the first listener to port 8080. Nested keys
+//! are separated by `__`, and array items are selected by their index. Variables which do not name a field of
+//! [`ServerConfig`], such as `SALVO_LOG`, are ignored.
+use std::fs;
+use std::io::{Error as IoError, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures_util::future::{join_all, BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::conn::{Listener, TcpListener};
+use crate::routing::Router;
+use crate::{Server, Service};
+
+/// Errors returned when loading or validating a [`ServerConfig`].
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// A configuration file can not be read, or a listener can not be bound.
+    #[error("io error: {0}")]
+    Io(#[from] IoError),
+    /// A source or the merged configuration can not be parsed.
+    #[error("parse error: {0}")]
+    Parse(String),
+    /// The configuration is parsed but invalid, all problems are listed.
+    #[error("invalid config: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+}
+
+/// Configuration of a server.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ServerConfig {
+    /// Addresses the server listens on.
+    pub listeners: Vec<ListenerConfig>,
+    /// Limits applied to all requests.
+    pub limits: LimitsConfig,
+    /// Middlewares enabled for all requests.
+    pub middlewares: MiddlewaresConfig,
+    /// Directories served as static files.
+    pub statics: Vec<StaticConfig>,
+}
+
+/// Configuration of a listener.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ListenerConfig {
+    /// Address to listen on, such as `0.0.0.0:5800`.
+    pub addr: String,
+    /// Serve HTTPS with the certificate, requires the `rustls` feature.
+    pub tls: Option<TlsConfig>,
+}
+
+/// Certificate and private key of a TLS listener.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct TlsConfig {
+    /// Path of the PEM encoded certificate chain.
+    pub cert: PathBuf,
+    /// Path of the PEM encoded private key.
+    pub key: PathBuf,
+}
+
+/// Limits applied to all requests.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct LimitsConfig {
+    /// Max size of request bodies in bytes, requires the `size-limiter` feature.
+    pub max_body_size: Option<u64>,
+    /// Timeout of requests in seconds, requires the `timeout` feature.
+    pub request_timeout: Option<u64>,
+}
+
+/// Middlewares enabled for all requests, each requires the feature of the same name.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct MiddlewaresConfig {
+    /// Catch panics of handlers.
+    pub catch_panic: bool,
+    /// Log requests and responses.
+    pub logging: bool,
+    /// Set a request ID header.
+    pub request_id: bool,
+    /// Redirect HTTP requests to HTTPS.
+    pub force_https: bool,
+    /// Compress responses.
+    pub compression: bool,
+}
+
+/// Directories served as static files under a path, requires the `serve-static` feature.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct StaticConfig {
+    /// Path the directories are mounted at, such as `assets`.
+    pub path: String,
+    /// Directories searched in order.
+    pub dirs: Vec<PathBuf>,
+    /// List the files of directories without a default file.
+    pub listing: bool,
+    /// Files served for directory requests, such as `index.html`.
+    pub defaults: Vec<String>,
+}
+
+impl ServerConfig {
+    /// Check the configuration, all problems are collected in [`ConfigError::Invalid`].
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        if self.listeners.is_empty() {
+            problems.push("no listener is configured".to_owned());
+        }
+        for (index, listener) in self.listeners.iter().enumerate() {
+            let valid_port = listener
+                .addr
+                .rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+            if !valid_port {
+                problems.push(format!("listeners[{index}]: invalid address `{}`", listener.addr));
+            }
+            if let Some(tls) = &listener.tls {
+                if !cfg!(feature = "rustls") {
+                    problems.push(format!("listeners[{index}]: tls requires the `rustls` feature"));
+                }
+                for path in [&tls.cert, &tls.key] {
+                    if !path.is_file() {
+                        problems.push(format!("listeners[{index}]: file `{}` not found", path.display()));
+                    }
+                }
+            }
+        }
+
+        if let Some(size) = self.limits.max_body_size {
+            check_enabled(
+                &mut problems,
+                "limits.max_body_size",
+                cfg!(feature = "size-limiter"),
+                "size-limiter",
+            );
+            if size == 0 {
+                problems.push("limits.max_body_size: must be greater than 0".to_owned());
+            }
+        }
+        if let Some(secs) = self.limits.request_timeout {
+            check_enabled(
+                &mut problems,
+                "limits.request_timeout",
+                cfg!(feature = "timeout"),
+                "timeout",
+            );
+            if secs == 0 {
+                problems.push("limits.request_timeout: must be greater than 0".to_owned());
+            }
+        }
+
+        let middlewares = &self.middlewares;
+        for (name, on, enabled, feature) in [
+            (
+                "catch_panic",
+                middlewares.catch_panic,
+                cfg!(feature = "catch-panic"),
+                "catch-panic",
+            ),
+            ("logging", middlewares.logging, cfg!(feature = "logging"), "logging"),
+            (
+                "request_id",
+                middlewares.request_id,
+                cfg!(feature = "request-id"),
+                "request-id",
+            ),
+            (
+                "force_https",
+                middlewares.force_https,
+                cfg!(feature = "force-https"),
+                "force-https",
+            ),
+            (
+                "compression",
+                middlewares.compression,
+                cfg!(feature = "compression"),
+                "compression",
+            ),
+        ] {
+            if on {
+                check_enabled(&mut problems, &format!("middlewares.{name}"), enabled, feature);
+            }
+        }
+
+        for (index, statics) in self.statics.iter().enumerate() {
+            check_enabled(
+                &mut problems,
+                &format!("statics[{index}]"),
+                cfg!(feature = "serve-static"),
+                "serve-static",
+            );
+            if statics.dirs.is_empty() {
+                problems.push(format!("statics[{index}]: no directory is configured"));
+            }
+            for dir in &statics.dirs {
+                if !dir.is_dir() {
+                    problems.push(format!("statics[{index}]: directory `{}` not found", dir.display()));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    /// Wrap the router with the configured middlewares and static directories.
+    ///
+    /// Middlewares are added as hoops of a new root router, static directories are mounted before the router, so
+    /// the router can still use a catch-all path.
+    pub fn router(&self, router: Router) -> Router {
+        #[allow(unused_mut)]
+        let mut root = Router::new();
+        #[cfg(feature = "catch-panic")]
+        if self.middlewares.catch_panic {
+            root = root.hoop(salvo_extra::catch_panic::CatchPanic::new());
+        }
+        #[cfg(feature = "logging")]
+        if self.middlewares.logging {
+            root = root.hoop(salvo_extra::logging::Logger::new());
+        }
+        #[cfg(feature = "request-id")]
+        if self.middlewares.request_id {
+            root = root.hoop(salvo_extra::request_id::RequestId::new());
+        }
+        #[cfg(feature = "force-https")]
+        if self.middlewares.force_https {
+            root = root.hoop(salvo_extra::force_https::ForceHttps::new());
+        }
+        #[cfg(feature = "timeout")]
+        if let Some(secs) = self.limits.request_timeout {
+            root = root.hoop(salvo_extra::timeout::Timeout::new(std::time::Duration::from_secs(secs)));
+        }
+        #[cfg(feature = "size-limiter")]
+        if let Some(size) = self.limits.max_body_size {
+            root = root.hoop(salvo_extra::size_limiter::max_size(size));
+        }
+        #[cfg(feature = "compression")]
+        if self.middlewares.compression {
+            root = root.hoop(salvo_compression::Compression::new());
+        }
+        #[cfg(feature = "serve-static")]
+        for statics in &self.statics {
+            let dir = salvo_serve_static::StaticDir::new(statics.dirs.clone())
+                .auto_list(statics.listing)
+                .defaults(statics.defaults.clone());
+            let path = statics.path.trim_matches('/');
+            let path = if path.is_empty() {
+                "<**path>".to_owned()
+            } else {
+                format!("{path}/<**path>")
+            };
+            root = root.push(Router::with_path(path).get(dir));
+        }
+        root.push(router)
+    }
+
+    /// Validate the configuration, bind all listeners and serve the router on them.
+    ///
+    /// Returns an error if the configuration is invalid or a listener can not be bound, otherwise runs until all
+    /// servers stop.
+    pub async fn serve(self, router: Router) -> Result<(), ConfigError> {
+        self.validate()?;
+        let router = Arc::new(self.router(router));
+        let mut servers: Vec<BoxFuture<'static, ()>> = Vec::with_capacity(self.listeners.len());
+        for listener in &self.listeners {
+            let tcp = TcpListener::new(listener.addr.clone());
+            match &listener.tls {
+                #[cfg(feature = "rustls")]
+                Some(tls) => {
+                    use crate::conn::rustls::{Keycert, RustlsConfig};
+
+                    let keycert = Keycert::new().cert_from_path(&tls.cert)?.key_from_path(&tls.key)?;
+                    let acceptor = tcp
+                        .rustls(RustlsConfig::new(keycert))
+                        .try_bind()
+                        .await
+                        .map_err(IoError::other)?;
+                    servers.push(Server::new(acceptor).serve(Service::new(router.clone())).boxed());
+                }
+                #[cfg(not(feature = "rustls"))]
+                Some(_) => unreachable!("tls listeners are rejected by validation"),
+                None => {
+                    let acceptor = tcp.try_bind().await.map_err(IoError::other)?;
+                    servers.push(Server::new(acceptor).serve(Service::new(router.clone())).boxed());
+                }
+            }
+        }
+        join_all(servers).await;
+        Ok(())
+    }
+}
+
+fn check_enabled(problems: &mut Vec<String>, key: &str, enabled: bool, feature: &str) {
+    if !enabled {
+        problems.push(format!("{key}: requires the `{feature}` feature"));
+    }
+}
+
+/// Layers configuration sources into a [`ServerConfig`].
+///
+/// Sources are merged in the order they are added: objects are merged key by key, and other values, including arrays,
+/// are replaced. Errors of sources are reported by [`ConfigLoader::build`].
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Debug)]
+pub struct ConfigLoader {
+    merged: Value,
+    error: Option<ConfigError>,
+}
+
+impl ConfigLoader {
+    /// Create a new `ConfigLoader` without sources.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            merged: Value::Object(Map::new()),
+            error: None,
+        }
+    }
+
+    /// Add a file, the format is detected from the extension: `toml`, `yaml`, `yml` or `json`.
+    ///
+    /// Missing files are skipped, so optional files such as `salvo.local.toml` can be layered.
+    pub fn file(self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return self,
+            Err(e) => return self.fail(e.into()),
+        };
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => self.toml(&content),
+            Some("yaml" | "yml") => self.yaml(&content),
+            Some("json") => self.json(&content),
+            _ => self.fail(ConfigError::Parse(format!(
+                "unknown format of config file `{}`",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Add a TOML source.
+    pub fn toml(self, content: &str) -> Self {
+        match toml::from_str::<Value>(content) {
+            Ok(value) => self.layer(value),
+            Err(e) => self.fail(ConfigError::Parse(e.to_string())),
+        }
+    }
+
+    /// Add a YAML source.
+    pub fn yaml(self, content: &str) -> Self {
+        match serde_yaml::from_str::<Value>(content) {
+            Ok(value) => self.layer(value),
+            Err(e) => self.fail(ConfigError::Parse(e.to_string())),
+        }
+    }
+
+    /// Add a JSON source.
+    pub fn json(self, content: &str) -> Self {
+        match serde_json::from_str::<Value>(content) {
+            Ok(value) => self.layer(value),
+            Err(e) => self.fail(ConfigError::Parse(e.to_string())),
+        }
+    }
+
+    /// Add the environment variables starting with `{prefix}_`.
+    ///
+    /// See [`ConfigLoader::env_vars`] for how the variables are mapped.
+    #[inline]
+    pub fn env(self, prefix: &str) -> Self {
+        self.env_vars(prefix, std::env::vars())
+    }
+
+    /// Add the variables starting with `{prefix}_`.
+    ///
+    /// The rest of a name is lowercased and split by `__` into nested keys, numeric keys select array items. Only
+    /// variables naming a field of [`ServerConfig`] are used, others are ignored. Values are parsed as the type of
+    /// the field: `true` or `false` for flags, numbers for limits, and a JSON array such as `["index.html"]` or a
+    /// comma separated list for lists. Other fields take the value as is, even if it looks like a number.
+    pub fn env_vars<I, K, V>(mut self, prefix: &str, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let prefix = format!("{prefix}_");
+        let mut vars = vars
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let key = key.as_ref().strip_prefix(&prefix)?.to_lowercase();
+                (!key.is_empty()).then(|| (key, value.as_ref().to_owned()))
+            })
+            .collect::<Vec<_>>();
+        // Sorted, so the result does not depend on the order of the environment.
+        vars.sort();
+        for (key, value) in vars {
+            let keys = key.split("__").collect::<Vec<_>>();
+            let Some(kind) = EnvKind::of(&keys) else {
+                continue;
+            };
+            set_path(&mut self.merged, &keys, kind.parse(value));
+        }
+        self
+    }
+
+    /// Merge the sources and validate the result.
+    pub fn build(self) -> Result<ServerConfig, ConfigError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let config: ServerConfig =
+            serde_json::from_value(self.merged).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn layer(mut self, value: Value) -> Self {
+        merge(&mut self.merged, value);
+        self
+    }
+
+    fn fail(mut self, error: ConfigError) -> Self {
+        self.error.get_or_insert(error);
+        self
+    }
+}
+
+impl Default for ConfigLoader {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Type of a field of [`ServerConfig`] which can be set by an environment variable.
+enum EnvKind {
+    String,
+    Bool,
+    Number,
+    List,
+}
+
+impl EnvKind {
+    /// Returns the type of the field named by the keys, or `None` if no field has the name.
+    fn of(keys: &[&str]) -> Option<Self> {
+        let index = |key: &str| key.parse::<usize>().is_ok();
+        match keys {
+            ["listeners", i, "addr"] if index(i) => Some(Self::String),
+            ["listeners", i, "tls", "cert" | "key"] if index(i) => Some(Self::String),
+            ["limits", "max_body_size" | "request_timeout"] => Some(Self::Number),
+            ["middlewares", "catch_panic" | "logging" | "request_id" | "force_https" | "compression"] => {
+                Some(Self::Bool)
+            }
+            ["statics", i, "path"] if index(i) => Some(Self::String),
+            ["statics", i, "listing"] if index(i) => Some(Self::Bool),
+            ["statics", i, "dirs" | "defaults"] if index(i) => Some(Self::List),
+            ["statics", i, "dirs" | "defaults", j] if index(i) && index(j) => Some(Self::String),
+            _ => None,
+        }
+    }
+
+    /// Values which can not be parsed are kept as strings, so they are reported when the config is built.
+    fn parse(&self, value: String) -> Value {
+        match self {
+            Self::String => Value::String(value),
+            Self::Bool => value.parse::<bool>().map(Value::Bool).unwrap_or(Value::String(value)),
+            Self::Number => value.parse::<u64>().map(Value::from).unwrap_or(Value::String(value)),
+            Self::List => match serde_json::from_str::<Vec<String>>(&value) {
+                Ok(items) => items.into(),
+                Err(_) => value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_owned()))
+                    .collect(),
+            },
+        }
+    }
+}
+
+fn merge(target: &mut Value, value: Value) {
+    match (target, value) {
+        (Value::Object(target), Value::Object(value)) => {
+            for (key, value) in value {
+                match target.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, value) => *target = value,
+    }
+}
+
+fn set_path(target: &mut Value, keys: &[&str], value: Value) {
+    let Some((key, rest)) = keys.split_first() else {
+        *target = value;
+        return;
+    };
+    let next = match key.parse::<usize>() {
+        Ok(index) => {
+            if !target.is_array() {
+                *target = Value::Array(Vec::new());
+            }
+            let items = target.as_array_mut().expect("target should be an array");
+            if items.len() <= index {
+                items.resize(index + 1, Value::Object(Map::new()));
+            }
+            &mut items[index]
+        }
+        Err(_) => {
+            if !target.is_object() {
+                *target = Value::Object(Map::new());
+            }
+            target
+                .as_object_mut()
+                .expect("target should be an object")
+                .entry(key.to_string())
+                .or_insert(Value::Null)
+        }
+    };
+    set_path(next, rest, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"
+        [[listeners]]
+        addr = "127.0.0.1:5800"
+
+        [[listeners]]
+        addr = "127.0.0.1:5801"
+    "#;
+
+    #[test]
+    fn test_config_layers() {
+        let config = ConfigLoader::new()
+            .toml(BASE)
+            .yaml("listeners:\n  - addr: \"0.0.0.0:8080\"\n")
+            .build()
+            .unwrap();
+        assert_eq!(config.listeners.len(), 1);
+        assert_eq!(config.listeners[0].addr, "0.0.0.0:8080");
+
+        let config = ConfigLoader::new()
+            .toml(BASE)
+            .env_vars(
+                "APP",
+                [
+                    ("APP_LISTENERS__1__ADDR", "0.0.0.0:9090"),
+                    ("APP_MIDDLEWARES__LOGGING", "false"),
+                    ("OTHER_LISTENERS__0__ADDR", "0.0.0.0:1"),
+                ],
+            )
+            .build()
+            .unwrap();
+        assert_eq!(config.listeners[0].addr, "127.0.0.1:5800");
+        assert_eq!(config.listeners[1].addr, "0.0.0.0:9090");
+        assert!(!config.middlewares.logging);
+    }
+
+    #[test]
+    fn test_config_env_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = ConfigLoader::new().toml(BASE).env_vars(
+            "APP",
+            [
+                ("APP_LOG", "debug"),
+                ("APP_LIMITS__UNKNOWN", "1"),
+                ("APP_LIMITS__REQUEST_TIMEOUT", "30"),
+                ("APP_STATICS__0__PATH", "123"),
+                ("APP_STATICS__0__DIRS", &*dir.path().to_string_lossy()),
+                ("APP_STATICS__0__LISTING", "true"),
+                ("APP_STATICS__0__DEFAULTS", r#"["index.html", "true"]"#),
+                ("APP_STATICS__1__PATH", "null"),
+                ("APP_STATICS__1__DIRS", "a, b"),
+            ],
+        );
+        let config: ServerConfig = serde_json::from_value(loader.merged).unwrap();
+        assert_eq!(config.limits.request_timeout, Some(30));
+        assert_eq!(config.statics[0].path, "123");
+        assert_eq!(config.statics[0].dirs, [dir.path()]);
+        assert!(config.statics[0].listing);
+        assert_eq!(config.statics[0].defaults, ["index.html", "true"]);
+        assert_eq!(config.statics[1].path, "null");
+        assert_eq!(config.statics[1].dirs, [PathBuf::from("a"), PathBuf::from("b")]);
+
+        assert!(matches!(
+            ConfigLoader::new()
+                .toml(BASE)
+                .env_vars("APP", [("APP_MIDDLEWARES__LOGGING", "yes")])
+                .build(),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("salvo.toml");
+        fs::write(&path, BASE).unwrap();
+        let config = ConfigLoader::new()
+            .file(&path)
+            .file(dir.path().join("missing.yaml"))
+            .build()
+            .unwrap();
+        assert_eq!(config.listeners.len(), 2);
+
+        let path = dir.path().join("salvo.ini");
+        fs::write(&path, BASE).unwrap();
+        assert!(matches!(
+            ConfigLoader::new().file(&path).build(),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_config_invalid() {
+        assert!(matches!(
+            ConfigLoader::new().toml("[[listeners]]\nport = 80").build(),
+            Err(ConfigError::Parse(_))
+        ));
+
+        let Err(ConfigError::Invalid(problems)) = ConfigLoader::new()
+            .toml(
+                r#"
+                [[listeners]]
+                addr = "127.0.0.1"
+
+                [[listeners]]
+                addr = "127.0.0.1:5800"
+                tls = { cert = "missing.pem", key = "missing.key" }
+
+                [limits]
+                request_timeout = 0
+
+                [[statics]]
+                path = "assets"
+                dirs = ["missing"]
+            "#,
+            )
+            .build()
+        else {
+            panic!("config should be invalid");
+        };
+        assert!(problems.iter().any(|p| p.contains("invalid address `127.0.0.1`")));
+        assert!(problems.iter().any(|p| p.contains("file `missing.pem` not found")));
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("request_timeout: must be greater than 0")));
+        assert!(problems.iter().any(|p| p.contains("directory `missing` not found")));
+
+        assert!(matches!(
+            ConfigLoader::new().build(),
+            Err(ConfigError::Invalid(problems)) if problems == ["no listener is configured"]
+        ));
+    }
+}
@@ -0,0 +1,420 @@
+//! Development mode which reloads the service and the browser when files change.
+//!
+//! [`watch`] takes a factory building the [`Service`], such as a function which loads the configuration and the
+//! templates and returns the router. The files under the watched paths are polled, when they change the factory
+//! is called again and the new service replaces the old one in the running server, without dropping the listener.
+//! Requests which are in flight finish with the old service, so the restart is graceful. If the factory panics,
+//! the error is logged and the old service is kept.
+//!
+//! Pages served as `text/html` get a small script injected, which long-polls the livereload endpoint and reloads
+//! the browser after the service is rebuilt.
+//!
+//! Rust code is compiled into the binary, changes to it still need a rebuild, use this for the routes, templates and
+//! configuration which are loaded at runtime. Do not enable it in production.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo::prelude::*;
+//!
+//! #[handler]
+//! async fn hello() -> Text<String> {
+//!     Text::Html(std::fs::read_to_string("templates/hello.html").unwrap_or_default())
+//! }
+//!
+//! fn build() -> Router {
+//!     Router::new().get(hello)
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let acceptor = TcpListener::new("127.0.0.1:5800").bind().await;
+//!     salvo::dev::watch(build).path("templates").path("salvo.toml").serve(acceptor).await;
+//! }
+//! ```
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::watch::Sender;
+
+use crate::conn::Acceptor;
+use crate::http::header::{CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE};
+use crate::http::{Method, Request, ResBody, Response};
+use crate::routing::Router;
+use crate::writing::Text;
+use crate::{async_trait, Depot, FlowCtrl, Handler, Server, Service};
+
+/// Path of the livereload script injected into html pages.
+pub const LIVERELOAD_SCRIPT_PATH: &str = "/__salvo_dev/livereload.js";
+/// Path of the livereload endpoint, it responds the version of the service when it differs from `?version=`.
+pub const LIVERELOAD_PATH: &str = "/__salvo_dev/livereload";
+
+const LIVERELOAD_SCRIPT: &str = r#"(function () {
+  var version = null;
+  function poll() {
+    var query = version === null ? "" : "?version=" + version;
+    fetch("/__salvo_dev/livereload" + query, { cache: "no-store" })
+      .then(function (res) { return res.text(); })
+      .then(function (text) {
+        if (version !== null && text !== version) {
+          location.reload();
+          return;
+        }
+        version = text;
+        poll();
+      })
+      .catch(function () { setTimeout(poll, 1000); });
+  }
+  poll();
+})();
+"#;
+
+/// How long a livereload request waits for a new version.
+const POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Snapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// Create a [`DevServer`] which serves the service built by `factory` and rebuilds it when files change.
+///
+/// The factory is called once immediately.
+pub fn watch<F, S>(factory: F) -> DevServer
+where
+    F: Fn() -> S + Send + Sync + 'static,
+    S: Into<Service>,
+{
+    let service = factory().into();
+    let (version, _) = tokio::sync::watch::channel(0);
+    DevServer {
+        state: Arc::new(DevState {
+            factory: Box::new(move || factory().into()),
+            current: RwLock::new(Arc::new(service)),
+            version,
+        }),
+        paths: Vec::new(),
+        ignores: vec!["target".into(), "node_modules".into()],
+        interval: Duration::from_millis(500),
+        livereload: true,
+    }
+}
+
+struct DevState {
+    factory: Box<dyn Fn() -> Service + Send + Sync>,
+    current: RwLock<Arc<Service>>,
+    version: Sender<u64>,
+}
+
+/// Server which rebuilds its service when the watched files change, created by [`watch`].
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone)]
+pub struct DevServer {
+    state: Arc<DevState>,
+    paths: Vec<PathBuf>,
+    ignores: Vec<String>,
+    interval: Duration,
+    livereload: bool,
+}
+
+impl Debug for DevServer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DevServer")
+            .field("paths", &self.paths)
+            .field("ignores", &self.ignores)
+            .field("interval", &self.interval)
+            .field("livereload", &self.livereload)
+            .finish()
+    }
+}
+
+impl DevServer {
+    /// Add a file or a directory to watch, directories are watched recursively.
+    ///
+    /// The current directory is watched if no path is added.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Ignore files and directories with this name, default are `target` and `node_modules`.
+    ///
+    /// Hidden files and directories, whose names start with `.`, are always ignored.
+    pub fn ignore(mut self, name: impl Into<String>) -> Self {
+        self.ignores.push(name.into());
+        self
+    }
+
+    /// Sets the interval of polling the watched files, default is 500 milliseconds.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets whether the livereload endpoint is served and its script is injected into html pages, default is `true`.
+    pub fn livereload(mut self, livereload: bool) -> Self {
+        self.livereload = livereload;
+        self
+    }
+
+    /// Get the version of the service, it is increased every time the service is rebuilt.
+    pub fn version(&self) -> u64 {
+        *self.state.version.borrow()
+    }
+
+    /// Rebuild the service now, returns `false` if the factory panicked and the old service is kept.
+    pub fn reload(&self) -> bool {
+        match panic::catch_unwind(AssertUnwindSafe(|| (self.state.factory)())) {
+            Ok(service) => {
+                *self.state.current.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(service);
+                self.state.version.send_modify(|version| *version += 1);
+                tracing::info!(version = self.version(), "service reloaded");
+                true
+            }
+            Err(_) => {
+                tracing::error!("rebuilding service panicked, the previous service is kept");
+                false
+            }
+        }
+    }
+
+    /// Get the [`Service`] which forwards requests to the current service and serves the livereload endpoint.
+    ///
+    /// Files are not watched, use [`DevServer::serve`] or call [`DevServer::reload`] to rebuild the service.
+    pub fn service(&self) -> Service {
+        Service::new(Router::new()).request_span(false).hoop(Reloader {
+            state: self.state.clone(),
+            livereload: self.livereload,
+        })
+    }
+
+    /// Watch the files and serve the service on `acceptor`.
+    pub async fn serve<A>(self, acceptor: A)
+    where
+        A: Acceptor + Send,
+    {
+        tokio::spawn(self.clone().watch_files());
+        Server::new(acceptor).serve(self.service()).await;
+    }
+
+    async fn watch_files(self) {
+        let mut last = self.scan().await;
+        loop {
+            tokio::time::sleep(self.interval).await;
+            let current = self.scan().await;
+            if current == last {
+                continue;
+            }
+            last = current;
+            // Wait until the files stop changing, editors and build tools often write them in several steps.
+            loop {
+                tokio::time::sleep(self.interval).await;
+                let current = self.scan().await;
+                if current == last {
+                    break;
+                }
+                last = current;
+            }
+            self.reload();
+        }
+    }
+
+    async fn scan(&self) -> Snapshot {
+        let paths = if self.paths.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            self.paths.clone()
+        };
+        let ignores = self.ignores.clone();
+        tokio::task::spawn_blocking(move || snapshot(&paths, &ignores))
+            .await
+            .unwrap_or_default()
+    }
+}
+
+/// Collect the modified time and the size of all files under `paths`.
+fn snapshot(paths: &[PathBuf], ignores: &[String]) -> Snapshot {
+    let mut files = Snapshot::new();
+    let mut pending = paths.to_vec();
+    while let Some(path) = pending.pop() {
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        if !metadata.is_dir() {
+            files.insert(path, (metadata.modified().ok(), metadata.len()));
+            continue;
+        }
+        for entry in fs::read_dir(&path).into_iter().flatten().flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if !name.starts_with('.') && !ignores.iter().any(|ignore| *ignore == name) {
+                pending.push(entry.path());
+            }
+        }
+    }
+    files
+}
+
+/// Inject the livereload script into an html page.
+fn inject_script(res: &mut Response) {
+    let is_html = res
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/html"));
+    if !is_html {
+        return;
+    }
+    let ResBody::Once(bytes) = &res.body else {
+        return;
+    };
+    let tag = format!(r#"<script src="{LIVERELOAD_SCRIPT_PATH}"></script>"#);
+    let mut html = bytes.to_vec();
+    let at = html
+        .windows(7)
+        .rposition(|window| window.eq_ignore_ascii_case(b"</body>"))
+        .unwrap_or(html.len());
+    html.splice(at..at, tag.bytes());
+    res.headers_mut().remove(CONTENT_LENGTH);
+    res.body = ResBody::Once(html.into());
+}
+
+struct Reloader {
+    state: Arc<DevState>,
+    livereload: bool,
+}
+
+#[async_trait]
+impl Handler for Reloader {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        ctrl.skip_rest();
+        if self.livereload && *req.method() == Method::GET {
+            match req.uri().path() {
+                LIVERELOAD_SCRIPT_PATH => {
+                    res.render(Text::Js(LIVERELOAD_SCRIPT));
+                    return;
+                }
+                LIVERELOAD_PATH => {
+                    let mut rx = self.state.version.subscribe();
+                    if req.query::<u64>("version") == Some(*rx.borrow()) {
+                        let _ = tokio::time::timeout(POLL_TIMEOUT, rx.changed()).await;
+                    }
+                    let version = *rx.borrow();
+                    res.add_header(CACHE_CONTROL, "no-store", true).ok();
+                    res.render(Text::Plain(version.to_string()));
+                    return;
+                }
+                _ => {}
+            }
+        }
+        let service = self
+            .state
+            .current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        *res = service.handle(std::mem::replace(req, Request::new())).await;
+        if self.livereload {
+            inject_script(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::handler;
+    use crate::http::StatusCode;
+
+    #[handler]
+    async fn hello() -> Text<&'static str> {
+        Text::Html("<html><body>hello</body></html>")
+    }
+
+    async fn get(service: &Service, path: &str) -> Response {
+        let mut req = Request::new();
+        *req.uri_mut() = format!("http://127.0.0.1:5800{path}").parse().unwrap();
+        service.handle(req).await
+    }
+
+    fn body(res: &Response) -> String {
+        match &res.body {
+            ResBody::Once(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+            _ => String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload_service() {
+        let builds = Arc::new(AtomicUsize::new(0));
+        let dev = watch({
+            let builds = builds.clone();
+            move || {
+                let build = builds.fetch_add(1, Ordering::SeqCst) + 1;
+                if build == 3 {
+                    panic!("broken template");
+                }
+                Router::with_path(format!("v{build}")).get(hello)
+            }
+        });
+        let service = dev.service();
+        assert_eq!(get(&service, "/v1").await.status_code, Some(StatusCode::OK));
+
+        assert!(dev.reload());
+        assert_eq!(dev.version(), 1);
+        assert_eq!(get(&service, "/v1").await.status_code, Some(StatusCode::NOT_FOUND));
+        assert_eq!(get(&service, "/v2").await.status_code, Some(StatusCode::OK));
+
+        assert!(!dev.reload());
+        assert_eq!(dev.version(), 1);
+        assert_eq!(get(&service, "/v2").await.status_code, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_livereload() {
+        let dev = watch(|| Router::new().get(hello));
+        let service = Arc::new(dev.service());
+        assert_eq!(
+            body(&get(&service, "/").await),
+            r#"<html><body>hello<script src="/__salvo_dev/livereload.js"></script></body></html>"#
+        );
+        assert_eq!(body(&get(&service, LIVERELOAD_PATH).await), "0");
+
+        let poll = tokio::spawn({
+            let service = service.clone();
+            async move { body(&get(&service, &format!("{LIVERELOAD_PATH}?version=0")).await) }
+        });
+        // Reload once the poll is waiting for a new version.
+        while dev.state.version.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        dev.reload();
+        assert_eq!(poll.await.unwrap(), "1");
+
+        let service = dev.clone().livereload(false).service();
+        assert_eq!(body(&get(&service, "/").await), "<html><body>hello</body></html>");
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("templates")).unwrap();
+        fs::create_dir_all(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("templates/index.html"), "index").unwrap();
+        fs::write(dir.path().join("target/build.log"), "log").unwrap();
+        fs::write(dir.path().join(".swp"), "swap").unwrap();
+
+        let paths = [dir.path().to_path_buf()];
+        let ignores = ["target".to_owned()];
+        let before = snapshot(&paths, &ignores);
+        assert_eq!(before.len(), 1);
+
+        fs::write(dir.path().join("templates/index.html"), "changed index").unwrap();
+        assert_ne!(snapshot(&paths, &ignores), before);
+    }
+}
@@ -19,9 +19,13 @@
 //! | `openssl` | TLS built on [`openssl-tls`](https://crates.io/crates/openssl) | ❌ |
 //! | `native-tls` | TLS built on [`native-tls`](https://crates.io/crates/native-tls) | ❌ |
 //! | `unix` | Listener based on unix socket | ❌ |
+//! | `vault` | Load secrets from [HashiCorp Vault](https://www.vaultproject.io) | ❌ |
 //! | `tower-compat` | Adapters for `tower::Layer` and `tower::Service` | ❌ |
+//! | `cron` | Cron schedules for background tasks | ❌ |
 //! | `anyhow` | Integrate with the [`anyhow`](https://crates.io/crates/anyhow) crate | ❌ |
 //! | `eyre` | Integrate with the [`eyre`](https://crates.io/crates/eyre) crate | ❌ |
+//! | `sqlx` | Convert [`sqlx`](https://crates.io/crates/sqlx) errors into `Error` | ❌ |
+//! | `reqwest` | Convert [`reqwest`](https://crates.io/crates/reqwest) errors into `Error` | ❌ |
 //! | `affix` | Middleware for adding prefix and suffix to the request path | ❌ |
 //! | `basic-auth` | Middleware for basic authentication | ❌ |
 //! | `caching-headers` | Middleware for setting caching headers | ❌ |
@@ -30,11 +34,26 @@
 //! | `force-https` | Middleware for forcing HTTPS | ❌ |
 //! | `logging` | Middleware for logging requests and responses | ❌ |
 //! | `request-id` | Middleware for setting a request ID | ❌ |
+//! | `signed-url` | Helpers for signing URLs and middleware for validating them | ❌ |
+//! | `control` | Router for administrating a running server | ❌ |
+//! | `tus` | Router for resumable uploads with the tus protocol | ❌ |
+//! | `metering` | Middleware for metering the usage of each principal | ❌ |
+//! | `htmx` | Helpers for htmx requests and responses | ❌ |
 //! | `size-limiter` | Middleware for limiting request size | ❌ |
 //! | `sse` | Server-Sent Events (SSE) middleware | ❌ |
 //! | `timeout` | Middleware for setting a timeout | ❌ |
 //! | `trailing-slash` | Middleware for handling trailing slashes | ❌ |
 //! | `websocket` | WebSocket implementation | ❌ |
+//! | `client` | Outbound HTTP client shared by handlers and proxy | ❌ |
+//! | `lambda` | Run services on AWS Lambda | ❌ |
+//! | `graphql` | GraphQL handlers built on [`async-graphql`](https://crates.io/crates/async-graphql) | ❌ |
+//! | `db` | Database pool injection, transactions and health checks | ❌ |
+//! | `mq` | Message queue consumers run as server tasks | ❌ |
+//! | `webhook` | Outbound webhooks with signing and retries | ❌ |
+//! | `i18n` | Locale negotiation and translation with Fluent or gettext catalogs | ❌ |
+//! | `bench` | Load generator reporting throughput and latency percentiles | ❌ |
+//! | `config` | Build listeners and middlewares from TOML, YAML or environment configuration | ❌ |
+//! | `dev` | Development mode reloading the service and the browser when files change | ❌ |
 #![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -112,6 +131,31 @@ cfg_feature! {
     // #[doc(no_inline)]
     pub use salvo_extra::request_id;
 }
+cfg_feature! {
+    #![feature ="signed-url"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::signed_url;
+}
+cfg_feature! {
+    #![feature ="control"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::control;
+}
+cfg_feature! {
+    #![feature ="tus"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::tus;
+}
+cfg_feature! {
+    #![feature ="metering"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::metering;
+}
+cfg_feature! {
+    #![feature ="htmx"]
+    // #[doc(no_inline)]
+    pub use salvo_extra::htmx;
+}
 cfg_feature! {
     #![feature ="cache"]
     #[doc(no_inline)]
@@ -147,6 +191,11 @@ cfg_feature! {
     #[doc(no_inline)]
     pub use salvo_proxy as proxy;
 }
+cfg_feature! {
+    #![feature ="client"]
+    #[doc(no_inline)]
+    pub use salvo_proxy::client;
+}
 cfg_feature! {
     #![feature ="rate-limiter"]
     #[doc(no_inline)]
@@ -172,6 +221,49 @@ cfg_feature! {
     #[doc(no_inline)]
     pub use salvo_oapi as oapi;
 }
+cfg_feature! {
+    #![feature ="lambda"]
+    #[doc(no_inline)]
+    pub use salvo_lambda as lambda;
+}
+cfg_feature! {
+    #![feature ="graphql"]
+    #[doc(no_inline)]
+    pub use salvo_graphql as graphql;
+}
+cfg_feature! {
+    #![feature ="db"]
+    #[doc(no_inline)]
+    pub use salvo_db as db;
+}
+cfg_feature! {
+    #![feature ="mq"]
+    #[doc(no_inline)]
+    pub use salvo_mq as mq;
+}
+cfg_feature! {
+    #![feature ="webhook"]
+    #[doc(no_inline)]
+    pub use salvo_webhook as webhook;
+}
+cfg_feature! {
+    #![feature ="i18n"]
+    #[doc(no_inline)]
+    pub use salvo_i18n as i18n;
+}
+cfg_feature! {
+    #![feature ="bench"]
+    #[doc(no_inline)]
+    pub use salvo_bench as bench;
+}
+cfg_feature! {
+    #![feature ="config"]
+    pub mod config;
+}
+cfg_feature! {
+    #![feature ="dev"]
+    pub mod dev;
+}
 
 /// A list of things that automatically imports into application use salvo.
 pub mod prelude {
@@ -248,6 +340,18 @@ pub mod prelude {
         #![feature ="request-id"]
         pub use salvo_extra::request_id::RequestId;
     }
+    cfg_feature! {
+        #![feature ="signed-url"]
+        pub use salvo_extra::signed_url::SignedUrlGuard;
+    }
+    cfg_feature! {
+        #![feature ="control"]
+        pub use salvo_extra::control::Maintenance;
+    }
+    cfg_feature! {
+        #![feature ="htmx"]
+        pub use salvo_extra::htmx::HtmxRequestExt;
+    }
     cfg_feature! {
         #![feature ="serve-static"]
         pub use salvo_serve_static::{StaticFile, StaticDir};
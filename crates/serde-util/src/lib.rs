@@ -38,6 +38,8 @@ pub struct SerdeValue {
     pub skip_serializing_if: bool,
     /// Double option.
     pub double_option: bool,
+    /// Untagged enum variant.
+    pub untagged: bool,
 }
 
 impl SerdeValue {
@@ -55,6 +57,7 @@ impl SerdeValue {
                     }
                     TokenTree::Ident(ident) if ident == "skip_serializing_if" => value.skip_serializing_if = true,
                     TokenTree::Ident(ident) if ident == "flatten" => value.flatten = true,
+                    TokenTree::Ident(ident) if ident == "untagged" => value.untagged = true,
                     TokenTree::Ident(ident) if ident == "rename" => {
                         if let Some((literal, _)) = parse_next_lit_str(next) {
                             value.rename = Some(literal)
@@ -234,6 +237,9 @@ pub fn parse_value(attributes: &[Attribute]) -> Option<SerdeValue> {
             if value.double_option {
                 acc.double_option = value.double_option;
             }
+            if value.untagged {
+                acc.untagged = value.untagged;
+            }
 
             Some(acc)
         })
@@ -274,7 +280,7 @@ pub fn parse_container(attributes: &[Attribute]) -> Option<SerdeContainer> {
 
 #[cfg(test)]
 mod tests {
-    use super::{case::RENAME_RULES, parse_container, RenameRule, SerdeContainer};
+    use super::{case::RENAME_RULES, parse_container, parse_value, RenameRule, SerdeContainer};
     use syn::{parse_quote, Attribute};
 
     #[test]
@@ -308,6 +314,18 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_serde_parse_value_untagged() {
+        let attributes: &[Attribute] = &[
+            parse_quote! { #[serde(untagged)] },
+            parse_quote! { #[serde(rename = "a")] },
+        ];
+
+        let result = parse_value(attributes).unwrap();
+        assert!(result.untagged);
+        assert_eq!(result.rename.as_deref(), Some("a"));
+    }
+
     #[test]
     fn test_serde_rename_rule_from_str() {
         for (s, _) in RENAME_RULES {
@@ -0,0 +1,43 @@
+//! Access control of served files.
+//!
+//! An [`Authorizer`] set on [`StaticDir`](crate::StaticDir) is called with the path of every requested file or
+//! directory before it is served, so private areas and signed URLs are checked without duplicating the file serving
+//! code. The identity of the user is usually read from the [`Depot`], where an authentication middleware put it.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_serve_static::StaticDir;
+//!
+//! let dir = StaticDir::new(["static"]).authorizer(|path: &str, _req: &Request, depot: &Depot| {
+//!     if path.starts_with("private/") && depot.get::<String>("user").is_err() {
+//!         Err(StatusError::forbidden())
+//!     } else {
+//!         Ok(())
+//!     }
+//! });
+//! ```
+use salvo_core::http::{Request, StatusError};
+use salvo_core::{async_trait, Depot};
+
+/// Authorization of the files requested from static handlers.
+#[async_trait]
+pub trait Authorizer: Send + Sync + 'static {
+    /// Authorize access to `path`, relative to the static root.
+    ///
+    /// The returned error is rendered instead of the file, return [`StatusError::not_found`] to hide the existence
+    /// of private files.
+    async fn authorize(&self, path: &str, req: &Request, depot: &Depot) -> Result<(), StatusError>;
+}
+
+#[async_trait]
+impl<F> Authorizer for F
+where
+    F: Fn(&str, &Request, &Depot) -> Result<(), StatusError> + Send + Sync + 'static,
+{
+    #[inline]
+    async fn authorize(&self, path: &str, req: &Request, depot: &Depot) -> Result<(), StatusError> {
+        self(path, req, depot)
+    }
+}
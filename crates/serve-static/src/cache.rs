@@ -0,0 +1,186 @@
+//! Cache policy for static files.
+//!
+//! [`CachePolicy`] chooses the `Cache-Control` header of a served file by matching its path against glob patterns,
+//! the first matched rule wins. Fingerprinted files, whose names contain a content hash such as `app.3f2a9c1b.js`,
+//! can be cached forever because their content never changes under the same name.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_serve_static::cache::CachePolicy;
+//! use salvo_serve_static::StaticDir;
+//!
+//! let policy = CachePolicy::new()
+//!     .fingerprinted("public, max-age=31536000, immutable")
+//!     .rule("*.html", "no-cache")
+//!     .rule("images/**", "public, max-age=86400");
+//! let dir = StaticDir::new(["static"]).cache_policy(policy);
+//! ```
+use salvo_core::http::header::{HeaderValue, CACHE_CONTROL};
+use salvo_core::http::{Response, StatusCode};
+
+/// Cache policy for static files.
+///
+/// View [module level documentation](index.html) for more details.
+#[derive(Clone, Debug, Default)]
+pub struct CachePolicy {
+    rules: Vec<(String, HeaderValue)>,
+    fingerprinted: Option<HeaderValue>,
+    default: Option<HeaderValue>,
+}
+
+impl CachePolicy {
+    /// Create a new empty `CachePolicy`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule.
+    ///
+    /// Patterns without `/` are matched against the file name, others against the path relative to the static
+    /// root. `*` matches any characters except `/`, `**` matches any characters and `?` matches one character.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cache_control` is not a valid header value.
+    #[inline]
+    pub fn rule(mut self, pattern: impl Into<String>, cache_control: &str) -> Self {
+        self.rules.push((
+            pattern.into(),
+            HeaderValue::from_str(cache_control).expect("invalid cache control value"),
+        ));
+        self
+    }
+
+    /// Set `Cache-Control` of fingerprinted files, it takes priority over rules.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cache_control` is not a valid header value.
+    #[inline]
+    pub fn fingerprinted(mut self, cache_control: &str) -> Self {
+        self.fingerprinted = Some(HeaderValue::from_str(cache_control).expect("invalid cache control value"));
+        self
+    }
+
+    /// Set `Cache-Control` of files not matched by any rule.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cache_control` is not a valid header value.
+    #[inline]
+    pub fn default_value(mut self, cache_control: &str) -> Self {
+        self.default = Some(HeaderValue::from_str(cache_control).expect("invalid cache control value"));
+        self
+    }
+
+    /// Get `Cache-Control` for the file at `path`, relative to the static root.
+    pub fn cache_control(&self, path: &str) -> Option<&HeaderValue> {
+        let path = path.trim_start_matches('/');
+        let file_name = path.rsplit('/').next().unwrap_or_default();
+        if let Some(value) = &self.fingerprinted {
+            if is_fingerprinted(file_name) {
+                return Some(value);
+            }
+        }
+        self.rules
+            .iter()
+            .find(|(pattern, _)| {
+                if pattern.contains('/') {
+                    glob_match(pattern.trim_start_matches('/').as_bytes(), path.as_bytes())
+                } else {
+                    glob_match(pattern.as_bytes(), file_name.as_bytes())
+                }
+            })
+            .map(|(_, value)| value)
+            .or(self.default.as_ref())
+    }
+
+    /// Insert `Cache-Control` into a successful response if not set yet.
+    pub(crate) fn apply(&self, path: &str, res: &mut Response) {
+        let status = res.status_code.unwrap_or(StatusCode::OK);
+        if !(status.is_success() || status == StatusCode::NOT_MODIFIED) || res.headers().contains_key(CACHE_CONTROL) {
+            return;
+        }
+        if let Some(value) = self.cache_control(path) {
+            res.headers_mut().insert(CACHE_CONTROL, value.clone());
+        }
+    }
+}
+
+/// Returns `true` if the file name contains a content hash, such as `app.3f2a9c1b.js` or `index-BxT3d9Qa.js`.
+///
+/// A hash is a dot or dash separated part of the name, other than the first one, which is at least 8 characters
+/// long, contains only ASCII alphanumerics or `_`, and contains at least one digit.
+pub fn is_fingerprinted(file_name: &str) -> bool {
+    let stem = match file_name.rsplit_once('.') {
+        Some((stem, _)) => stem,
+        None => file_name,
+    };
+    stem.split(['.', '-']).skip(1).any(|part| {
+        part.len() >= 8
+            && part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+            && part.bytes().any(|b| b.is_ascii_digit())
+    })
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => {
+            if let Some(rest) = rest.strip_prefix(b"*") {
+                let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+                (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+            } else {
+                (0..=text.len())
+                    .take_while(|i| *i == 0 || text[i - 1] != b'/')
+                    .any(|i| glob_match(rest, &text[i..]))
+            }
+        }
+        Some((b'?', rest)) => matches!(text.split_first(), Some((c, text)) if *c != b'/' && glob_match(rest, text)),
+        Some((c, rest)) => matches!(text.split_first(), Some((t, text)) if t == c && glob_match(rest, text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprinted() {
+        assert!(is_fingerprinted("app.3f2a9c1b.js"));
+        assert!(is_fingerprinted("index-BxT3d9Qa.js"));
+        assert!(!is_fingerprinted("app.js"));
+        assert!(!is_fingerprinted("jquery-min.js"));
+        assert!(!is_fingerprinted("bootstrap-extended.css"));
+        assert!(!is_fingerprinted("20240101.log"));
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let policy = CachePolicy::new()
+            .fingerprinted("immutable")
+            .rule("*.html", "no-cache")
+            .rule("images/**", "images")
+            .default_value("default");
+        assert_eq!(policy.cache_control("app.3f2a9c1b.js").unwrap(), "immutable");
+        assert_eq!(policy.cache_control("docs/index.html").unwrap(), "no-cache");
+        assert_eq!(policy.cache_control("images/a/b.png").unwrap(), "images");
+        assert_eq!(policy.cache_control("/images/b.png").unwrap(), "images");
+        assert_eq!(policy.cache_control("css/images/b.png").unwrap(), "default");
+        assert_eq!(policy.cache_control("app.js").unwrap(), "default");
+        assert!(CachePolicy::new().cache_control("app.js").is_none());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*.js", b"app.js"));
+        assert!(!glob_match(b"*.js", b"app.json"));
+        assert!(glob_match(b"assets/*.js", b"assets/app.js"));
+        assert!(!glob_match(b"assets/*.js", b"assets/lib/app.js"));
+        assert!(glob_match(b"assets/**/*.js", b"assets/lib/app.js"));
+        assert!(glob_match(b"assets/**/*.js", b"assets/app.js"));
+        assert!(glob_match(b"file?.txt", b"file1.txt"));
+    }
+}
@@ -1,23 +1,27 @@
 //! serve static dir
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt::{self, Display, Write};
-use std::fs::Metadata;
-use std::path::{Path, PathBuf};
+use std::io::ErrorKind;
+use std::path::PathBuf;
 use std::str::FromStr;
-use std::time::SystemTime;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use salvo_core::fs::NamedFile;
-use salvo_core::http::header::ACCEPT_ENCODING;
-use salvo_core::http::{self, HeaderValue, Request, Response, StatusCode, StatusError};
-use salvo_core::writing::Text;
+use salvo_core::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, VARY};
+use salvo_core::http::headers::ETag;
+use salvo_core::http::{self, HeaderValue, Method, Request, Response, StatusCode, StatusError};
+use salvo_core::writing::{ReadSeeker, Text};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, IntoVecString};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use time::{macros::format_description, OffsetDateTime};
+use tokio::io::AsyncReadExt;
 
-use super::{decode_url_path_safely, encode_url_path, format_url_path_safely, join_path, redirect_to_dir_url};
+use super::{decode_url_path_safely, encode_url_path, format_url_path_safely, redirect_to_dir_url};
+use crate::access::Authorizer;
+use crate::cache::CachePolicy;
+use crate::vfs::{LocalFs, Vfs, VfsMetadata};
 
 /// CompressionAlgo
 #[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
@@ -120,10 +124,15 @@ where
 }
 
 /// Handler that serves a directory.
+///
+/// Files are read from a [`Vfs`], [`StaticDir::new`] serves local directories and [`StaticDir::with_vfs`] serves
+/// any other backend, see the [`vfs`](crate::vfs) module.
+///
+/// The public `roots` field is replaced by the [`StaticDir::roots`] accessor of local directories, the roots are
+/// kept by the [`LocalFs`].
 #[non_exhaustive]
-pub struct StaticDir {
-    /// Static roots.
-    pub roots: Vec<PathBuf>,
+pub struct StaticDir<V = LocalFs> {
+    vfs: V,
     /// During the file chunk read, the maximum read size at one time will affect the
     /// access experience and the demand for server memory.
     ///
@@ -146,11 +155,46 @@ pub struct StaticDir {
     pub defaults: Vec<String>,
     /// Fallback file name. This is used when the requested file is not found.
     pub fallback: Option<String>,
+    /// Single page application mode, the fallback is only served for client side routes.
+    pub spa: bool,
+    /// Request path prefixes which are never served with the fallback in single page application mode.
+    pub spa_excluded_prefixes: Vec<String>,
+    /// Output format of the directory listing.
+    pub list_format: ListFormat,
+    /// Sort order of the directory listing.
+    pub list_sort: ListSort,
+    /// Sort the directory listing in descending order.
+    pub list_sort_desc: bool,
+    /// File names rendered below the directory listing if found in the listed directory, such as `README.md`.
+    pub list_readmes: Vec<String>,
+    #[allow(clippy::type_complexity)]
+    list_hidden_filters: Vec<Box<dyn Fn(&str) -> bool + Send + Sync>>,
+    #[allow(clippy::type_complexity)]
+    list_renderer: Option<Box<dyn Fn(&Request, &CurrentInfo, &mut Response) + Send + Sync>>,
+    /// Cache policy of served files.
+    pub cache_policy: Option<CachePolicy>,
+    authorizer: Option<Box<dyn Authorizer>>,
 }
 impl StaticDir {
-    /// Create new `StaticDir`.
+    /// Create new `StaticDir` serving local directories.
     #[inline]
     pub fn new<T: StaticRoots + Sized>(roots: T) -> Self {
+        Self::with_vfs(LocalFs::with_roots(roots))
+    }
+
+    /// Get the static roots.
+    #[inline]
+    pub fn roots(&self) -> &[PathBuf] {
+        self.vfs.roots()
+    }
+}
+impl<V> StaticDir<V>
+where
+    V: Vfs,
+{
+    /// Create new `StaticDir` serving files from a [`Vfs`].
+    #[inline]
+    pub fn with_vfs(vfs: V) -> Self {
         let mut compressed_variations = HashMap::new();
         compressed_variations.insert(CompressionAlgo::Brotli, vec!["br".to_owned()]);
         compressed_variations.insert(CompressionAlgo::Zstd, vec!["zst".to_owned()]);
@@ -158,7 +202,7 @@ impl StaticDir {
         compressed_variations.insert(CompressionAlgo::Deflate, vec!["deflate".to_owned()]);
 
         StaticDir {
-            roots: roots.collect(),
+            vfs,
             chunk_size: None,
             include_dot_files: false,
             exclude_filters: vec![],
@@ -166,9 +210,25 @@ impl StaticDir {
             compressed_variations,
             defaults: vec![],
             fallback: None,
+            spa: false,
+            spa_excluded_prefixes: vec![],
+            list_format: ListFormat::default(),
+            list_sort: ListSort::default(),
+            list_sort_desc: false,
+            list_readmes: vec![],
+            list_hidden_filters: vec![],
+            list_renderer: None,
+            cache_policy: None,
+            authorizer: None,
         }
     }
 
+    /// Get the filesystem.
+    #[inline]
+    pub fn vfs(&self) -> &V {
+        &self.vfs
+    }
+
     /// Sets include_dot_files and returns a new `StaticDirOptions`.
     #[inline]
     pub fn include_dot_files(mut self, include_dot_files: bool) -> Self {
@@ -179,6 +239,10 @@ impl StaticDir {
     /// Exclude files.
     ///
     /// The filter function returns true to exclude the file.
+    ///
+    /// **Note:** the filter receives the path relative to the root, such as `docs/index.html`, not the path joined
+    /// with the local root as in the previous versions, so the same filter works for every [`Vfs`]. Filters
+    /// matching absolute paths need to be updated.
     #[inline]
     pub fn exclude<F>(mut self, filter: F) -> Self
     where
@@ -195,6 +259,66 @@ impl StaticDir {
         self
     }
 
+    /// Sets the output format of the directory listing.
+    #[inline]
+    pub fn list_format(mut self, format: ListFormat) -> Self {
+        self.list_format = format;
+        self
+    }
+
+    /// Sets the sort order of the directory listing, directories are always listed before files.
+    #[inline]
+    pub fn list_sort(mut self, sort: ListSort, desc: bool) -> Self {
+        self.list_sort = sort;
+        self.list_sort_desc = desc;
+        self
+    }
+
+    /// Sets file names rendered below the directory listing, the first one found in the listed directory is used.
+    #[inline]
+    pub fn list_readmes(mut self, names: impl IntoVecString) -> Self {
+        self.list_readmes = names.into_vec_string();
+        self
+    }
+
+    /// Hide entries from the directory listing, hidden files are still served.
+    ///
+    /// The filter function receives the entry name and returns true to hide the entry.
+    #[inline]
+    pub fn list_hidden<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.list_hidden_filters.push(Box::new(filter));
+        self
+    }
+
+    /// Render the directory listing with a custom function instead of the built-in templates.
+    #[inline]
+    pub fn list_renderer<F>(mut self, renderer: F) -> Self
+    where
+        F: Fn(&Request, &CurrentInfo, &mut Response) + Send + Sync + 'static,
+    {
+        self.list_renderer = Some(Box::new(renderer));
+        self
+    }
+
+    /// Sets cache policy of served files.
+    #[inline]
+    pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.cache_policy = Some(policy);
+        self
+    }
+
+    /// Sets the authorizer called with the path of every requested file or directory before it is served.
+    ///
+    /// View [`access`](crate::access) module documentation for more details.
+    #[inline]
+    pub fn authorizer(mut self, authorizer: impl Authorizer) -> Self {
+        self.authorizer = Some(Box::new(authorizer));
+        self
+    }
+
     /// Sets compressed_variations and returns a new `StaticDirOptions`.
     #[inline]
     pub fn compressed_variation<A>(mut self, algo: A, exts: &str) -> Self
@@ -219,6 +343,25 @@ impl StaticDir {
         self
     }
 
+    /// Serves a single page application using history API routing.
+    ///
+    /// The `index` file is served without redirect for `GET` and `HEAD` requests whose path is not found and does
+    /// not look like a file, so client side routes like `/users/1` render the application while missing assets like
+    /// `/app.js` still respond `404 Not Found`.
+    #[inline]
+    pub fn spa(mut self, index: impl Into<String>) -> Self {
+        self.fallback = Some(index.into());
+        self.spa = true;
+        self
+    }
+
+    /// Excludes request paths starting with `prefix` from the single page application fallback, such as `/api`.
+    #[inline]
+    pub fn spa_exclude(mut self, prefix: impl Into<String>) -> Self {
+        self.spa_excluded_prefixes.push(prefix.into());
+        self
+    }
+
     /// During the file chunk read, the maximum read size at one time will affect the
     /// access experience and the demand for server memory.
     ///
@@ -231,6 +374,23 @@ impl StaticDir {
         self
     }
 
+    fn is_spa_route(&self, req: &Request, rel_path: &str) -> bool {
+        if ![Method::GET, Method::HEAD].contains(req.method()) {
+            return false;
+        }
+        let req_path = req.uri().path();
+        if self.spa_excluded_prefixes.iter().any(|prefix| {
+            req_path
+                .strip_prefix(prefix.trim_end_matches('/'))
+                .map(|rest| rest.is_empty() || rest.starts_with('/'))
+                .unwrap_or(false)
+        }) {
+            return false;
+        }
+        let file_name = rel_path.rsplit('/').next().unwrap_or_default();
+        !file_name.contains('.')
+    }
+
     #[inline]
     fn is_compressed_ext(&self, ext: &str) -> bool {
         for exts in self.compressed_variations.values() {
@@ -240,234 +400,336 @@ impl StaticDir {
         }
         false
     }
+
+    #[inline]
+    fn is_excluded(&self, path: &str) -> bool {
+        self.exclude_filters.iter().any(|filter| filter(path))
+    }
+
+    async fn is_file(&self, path: &str) -> bool {
+        !self.is_excluded(path) && self.vfs.metadata(path).await.is_ok_and(|metadata| !metadata.is_dir)
+    }
+
+    /// Find a compressed variation of `path` accepted by the client.
+    async fn compressed_variation_of(&self, req: &Request, path: &str) -> Option<(String, CompressionAlgo)> {
+        let ext = path.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+        if self.compressed_variations.is_empty() || ext.is_some_and(|ext| self.is_compressed_ext(&ext)) {
+            return None;
+        }
+        let header = req
+            .headers()
+            .get(ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        for (algo, q) in http::parse_accept_encoding(header) {
+            let Ok(algo) = algo.parse::<CompressionAlgo>() else {
+                continue;
+            };
+            if q == 0 {
+                continue;
+            }
+            for ext in self.compressed_variations.get(&algo).into_iter().flatten() {
+                let variation = format!("{path}.{ext}");
+                if self.is_file(&variation).await {
+                    return Some((variation, algo));
+                }
+            }
+        }
+        None
+    }
+
+    async fn serve_file(&self, req: &Request, res: &mut Response, path: &str) {
+        let (served, content_encoding) = match self.compressed_variation_of(req, path).await {
+            Some((variation, algo)) => (variation, Some(algo)),
+            None => (path.to_owned(), None),
+        };
+        let (metadata, file) = match (self.vfs.metadata(&served).await, self.vfs.open(&served).await) {
+            (Ok(metadata), Ok(file)) => (metadata, file),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::error!(error = ?e, path = %served, "open static file failed");
+                res.render(StatusError::internal_server_error().brief("Read file failed."));
+                return;
+            }
+        };
+        let mime = mime_infer::from_path(path).first_or_octet_stream();
+        if let Ok(value) = HeaderValue::from_str(mime.as_ref()) {
+            res.headers_mut().insert(CONTENT_TYPE, value);
+        }
+        if let Some(algo) = content_encoding {
+            res.headers_mut().insert(CONTENT_ENCODING, algo.into());
+            res.headers_mut()
+                .insert(VARY, HeaderValue::from_static("accept-encoding"));
+        }
+        let mut seeker = ReadSeeker::new(file, metadata.len);
+        if let Some(size) = self.chunk_size {
+            seeker = seeker.buffer_size(size as usize);
+        }
+        if let Some(modified) = metadata.modified {
+            seeker = seeker.last_modified(modified);
+            if let Some(etag) = etag(&metadata, modified) {
+                seeker = seeker.etag(etag);
+            }
+        }
+        seeker.send(req.headers(), res).await;
+        if let Some(policy) = &self.cache_policy {
+            policy.apply(path, res);
+        }
+    }
+
+    async fn list_dir(&self, req: &Request, res: &mut Response, path: &str) {
+        let entries = match self.vfs.read_dir(path).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::error!(error = ?e, path = %path, "read static dir failed");
+                res.render(StatusError::internal_server_error());
+                return;
+            }
+        };
+        let mut files = vec![];
+        let mut dirs = vec![];
+        for (name, metadata) in entries {
+            if !self.include_dot_files && name.starts_with('.') {
+                continue;
+            }
+            if self.is_excluded(&join_rel_path(path, &name)) {
+                continue;
+            }
+            if self.list_hidden_filters.iter().any(|filter| filter(&name)) {
+                continue;
+            }
+            if metadata.is_dir {
+                dirs.push(DirInfo::new(name, &metadata));
+            } else {
+                files.push(FileInfo::new(name, &metadata));
+            }
+        }
+        match self.list_sort {
+            ListSort::Name => files.sort_by(|a, b| a.name.cmp(&b.name)),
+            ListSort::Modified => files.sort_by(|a, b| a.modified.cmp(&b.modified).then(a.name.cmp(&b.name))),
+            ListSort::Size => files.sort_by(|a, b| a.size.cmp(&b.size).then(a.name.cmp(&b.name))),
+        }
+        match self.list_sort {
+            ListSort::Modified => dirs.sort_by(|a, b| a.modified.cmp(&b.modified).then(a.name.cmp(&b.name))),
+            _ => dirs.sort_by(|a, b| a.name.cmp(&b.name)),
+        }
+        if self.list_sort_desc {
+            files.reverse();
+            dirs.reverse();
+        }
+        let mut current = CurrentInfo::new(decode_url_path_safely(req.uri().path()), files, dirs);
+        for name in &self.list_readmes {
+            if current.files.iter().any(|file| file.name == *name) {
+                if let Some(readme) = self.read_to_string(&join_rel_path(path, name)).await {
+                    current.readme = Some(readme);
+                    break;
+                }
+            }
+        }
+        res.status_code(StatusCode::OK);
+        if let Some(renderer) = &self.list_renderer {
+            renderer(req, &current, res);
+            return;
+        }
+        render_list(req, self.list_format, &current, res);
+    }
+
+    async fn read_to_string(&self, path: &str) -> Option<String> {
+        let mut file = self.vfs.open(path).await.ok()?;
+        let mut content = String::new();
+        file.read_to_string(&mut content).await.ok()?;
+        Some(content)
+    }
+}
+
+/// Join a name to a `/` separated path relative to the root.
+fn join_rel_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{path}/{name}")
+    }
+}
+
+/// Build an `ETag` from the length and last modified time.
+fn etag(metadata: &VfsMetadata, modified: SystemTime) -> Option<ETag> {
+    let dur = modified.duration_since(UNIX_EPOCH).ok()?;
+    format!("\"{:x}-{:x}-{:x}\"", metadata.len, dur.as_secs(), dur.subsec_nanos())
+        .parse()
+        .ok()
+}
+/// Output format of the directory listing.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub enum ListFormat {
+    /// Negotiated with the `Accept` request header.
+    #[default]
+    Auto,
+    /// HTML page.
+    Html,
+    /// JSON document.
+    Json,
+    /// XML document.
+    Xml,
+    /// Plain text.
+    Text,
 }
+
+/// Sort order of the directory listing.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub enum ListSort {
+    /// Sort by name.
+    #[default]
+    Name,
+    /// Sort by last modified time.
+    Modified,
+    /// Sort by size, directories are sorted by name.
+    Size,
+}
+
+/// Information of the listed directory.
 #[derive(Serialize, Deserialize, Debug)]
-struct CurrentInfo {
-    path: String,
-    files: Vec<FileInfo>,
-    dirs: Vec<DirInfo>,
+#[non_exhaustive]
+pub struct CurrentInfo {
+    /// Request path of the directory.
+    pub path: String,
+    /// Files in the directory.
+    pub files: Vec<FileInfo>,
+    /// Sub directories in the directory.
+    pub dirs: Vec<DirInfo>,
+    /// Content of the readme file found in the directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme: Option<String>,
 }
 impl CurrentInfo {
     #[inline]
-    fn new(path: String, files: Vec<FileInfo>, dirs: Vec<DirInfo>) -> CurrentInfo {
-        CurrentInfo { path, files, dirs }
+    pub(crate) fn new(path: String, files: Vec<FileInfo>, dirs: Vec<DirInfo>) -> CurrentInfo {
+        CurrentInfo {
+            path,
+            files,
+            dirs,
+            readme: None,
+        }
     }
 }
+/// Information of a listed file.
 #[derive(Serialize, Deserialize, Debug)]
-struct FileInfo {
-    name: String,
-    size: u64,
-    modified: OffsetDateTime,
+#[non_exhaustive]
+pub struct FileInfo {
+    /// File name.
+    pub name: String,
+    /// File size in bytes.
+    pub size: u64,
+    /// Last modified time.
+    pub modified: OffsetDateTime,
 }
 impl FileInfo {
     #[inline]
-    fn new(name: String, metadata: Metadata) -> FileInfo {
+    pub(crate) fn new(name: String, metadata: &VfsMetadata) -> FileInfo {
         FileInfo {
             name,
-            size: metadata.len(),
-            modified: metadata.modified().unwrap_or_else(|_| SystemTime::now()).into(),
+            size: metadata.len,
+            modified: metadata.modified.unwrap_or_else(SystemTime::now).into(),
         }
     }
 }
+/// Information of a listed sub directory.
 #[derive(Serialize, Deserialize, Debug)]
-struct DirInfo {
-    name: String,
-    modified: OffsetDateTime,
+#[non_exhaustive]
+pub struct DirInfo {
+    /// Directory name.
+    pub name: String,
+    /// Last modified time.
+    pub modified: OffsetDateTime,
 }
 impl DirInfo {
     #[inline]
-    fn new(name: String, metadata: Metadata) -> DirInfo {
+    pub(crate) fn new(name: String, metadata: &VfsMetadata) -> DirInfo {
         DirInfo {
             name,
-            modified: metadata.modified().unwrap_or_else(|_| SystemTime::now()).into(),
+            modified: metadata.modified.unwrap_or_else(SystemTime::now).into(),
         }
     }
 }
 
 #[async_trait]
-impl Handler for StaticDir {
-    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+impl<V> Handler for StaticDir<V>
+where
+    V: Vfs,
+{
+    async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
         let param = req.params().iter().find(|(key, _)| key.starts_with('*'));
-        let req_path = req.uri().path();
         let rel_path = if let Some((_, value)) = param {
             value.clone()
         } else {
-            decode_url_path_safely(req_path)
+            decode_url_path_safely(req.uri().path())
         };
-        let rel_path = format_url_path_safely(&rel_path);
-        let mut files: HashMap<String, Metadata> = HashMap::new();
-        let mut dirs: HashMap<String, Metadata> = HashMap::new();
-        let is_dot_file = Path::new(&rel_path)
-            .file_name()
-            .and_then(|s| s.to_str())
-            .map(|s| s.starts_with('.'))
-            .unwrap_or(false);
-        let mut abs_path = None;
-        if self.include_dot_files || !is_dot_file {
-            for root in &self.roots {
-                let raw_path = join_path!(root, &rel_path);
-                for filter in &self.exclude_filters {
-                    if filter(&raw_path) {
-                        continue;
-                    }
-                }
-                let path = Path::new(&raw_path);
-                if path.is_dir() {
+        let rel_path = format_url_path_safely(&rel_path).trim_end_matches('/').to_owned();
+        if let Some(authorizer) = &self.authorizer {
+            if let Err(e) = authorizer.authorize(&rel_path, req, depot).await {
+                res.render(e);
+                return;
+            }
+        }
+        let is_dot_file = rel_path.rsplit('/').next().is_some_and(|name| name.starts_with('.'));
+        if (self.include_dot_files || !is_dot_file) && !self.is_excluded(&rel_path) {
+            match self.vfs.metadata(&rel_path).await {
+                Ok(metadata) if metadata.is_dir => {
+                    let req_path = req.uri().path();
                     if !req_path.ends_with('/') && !req_path.is_empty() {
                         redirect_to_dir_url(req.uri(), res);
                         return;
                     }
-
-                    for ifile in &self.defaults {
-                        let ipath = path.join(ifile);
-                        if ipath.is_file() {
-                            abs_path = Some(ipath);
-                            break;
+                    for default in &self.defaults {
+                        let path = join_rel_path(&rel_path, default);
+                        if self.is_file(&path).await {
+                            self.serve_file(req, res, &path).await;
+                            return;
                         }
                     }
-
-                    if self.auto_list && abs_path.is_none() {
-                        abs_path = Some(path.to_path_buf());
-                    }
-                    if abs_path.is_some() {
-                        break;
+                    if self.auto_list {
+                        self.list_dir(req, res, &rel_path).await;
+                        return;
                     }
-                } else if path.is_file() {
-                    abs_path = Some(path.to_path_buf());
                 }
-            }
-        }
-        let fallback = self.fallback.as_deref().unwrap_or_default();
-        if abs_path.is_none() && !fallback.is_empty() {
-            for root in &self.roots {
-                let raw_path = join_path!(root, fallback);
-                for filter in &self.exclude_filters {
-                    if filter(&raw_path) {
-                        continue;
-                    }
+                Ok(_) => {
+                    self.serve_file(req, res, &rel_path).await;
+                    return;
                 }
-                let path = Path::new(&raw_path);
-                if path.is_file() {
-                    abs_path = Some(path.to_path_buf());
-                    break;
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    tracing::error!(error = ?e, path = %rel_path, "read static file metadata failed");
+                    res.render(StatusError::internal_server_error());
+                    return;
                 }
+                Err(_) => {}
             }
         }
-
-        let abs_path = match abs_path {
-            Some(path) => path,
-            None => {
-                res.render(StatusError::not_found());
+        if let Some(fallback) = &self.fallback {
+            if (!self.spa || self.is_spa_route(req, &rel_path)) && self.is_file(fallback).await {
+                self.serve_file(req, res, fallback).await;
                 return;
             }
-        };
-
-        if abs_path.is_file() {
-            let ext = abs_path.extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase());
-            let is_compressed_ext = ext.as_deref().map(|ext| self.is_compressed_ext(ext)).unwrap_or(false);
-            let mut content_encoding = None;
-            let named_path = if !is_compressed_ext {
-                if !self.compressed_variations.is_empty() {
-                    let mut new_abs_path = None;
-                    let header = req
-                        .headers()
-                        .get(ACCEPT_ENCODING)
-                        .and_then(|v| v.to_str().ok())
-                        .unwrap_or_default();
-                    let accept_algos = http::parse_accept_encoding(header)
-                        .into_iter()
-                        .filter_map(|(algo, _level)| {
-                            if let Ok(algo) = algo.parse::<CompressionAlgo>() {
-                                Some(algo)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<HashSet<_>>();
-                    for (algo, exts) in &self.compressed_variations {
-                        if accept_algos.contains(algo) {
-                            for zip_ext in exts {
-                                let mut path = abs_path.clone();
-                                path.as_mut_os_string().push(&*format!(".{}", zip_ext));
-                                if path.is_file() {
-                                    new_abs_path = Some(path);
-                                    content_encoding = Some(algo.to_string());
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                    new_abs_path.unwrap_or(abs_path)
-                } else {
-                    abs_path
-                }
-            } else {
-                abs_path
-            };
-
-            let builder = {
-                let mut builder = NamedFile::builder(named_path)
-                    .content_type(mime_infer::from_ext(ext.as_deref().unwrap_or_default()).first_or_octet_stream());
-                if let Some(content_encoding) = content_encoding {
-                    builder = builder.content_encoding(content_encoding);
-                }
-                if let Some(size) = self.chunk_size {
-                    builder = builder.buffer_size(size);
-                }
-                builder
-            };
-            if let Ok(named_file) = builder.build().await {
-                let headers = req.headers();
-                named_file.send(headers, res).await;
-            } else {
-                res.render(StatusError::internal_server_error().brief("Read file failed."));
-            }
-        } else if abs_path.is_dir() {
-            // list the dir
-            if let Ok(mut entries) = tokio::fs::read_dir(&abs_path).await {
-                while let Ok(Some(entry)) = entries.next_entry().await {
-                    let file_name = entry.file_name().to_string_lossy().to_string();
-                    if self.include_dot_files || !file_name.starts_with('.') {
-                        let raw_path = join_path!(&abs_path, &file_name);
-                        for filter in &self.exclude_filters {
-                            if filter(&raw_path) {
-                                continue;
-                            }
-                        }
-                        if let Ok(metadata) = entry.metadata().await {
-                            if metadata.is_dir() {
-                                dirs.entry(file_name).or_insert(metadata);
-                            } else {
-                                files.entry(file_name).or_insert(metadata);
-                            }
-                        }
-                    }
-                }
-            }
-
-            let format = req.first_accept().unwrap_or(mime::TEXT_HTML);
-            let mut files: Vec<FileInfo> = files
-                .into_iter()
-                .map(|(name, metadata)| FileInfo::new(name, metadata))
-                .collect();
-            files.sort_by(|a, b| a.name.cmp(&b.name));
-            let mut dirs: Vec<DirInfo> = dirs
-                .into_iter()
-                .map(|(name, metadata)| DirInfo::new(name, metadata))
-                .collect();
-            dirs.sort_by(|a, b| a.name.cmp(&b.name));
-            let root = CurrentInfo::new(decode_url_path_safely(req_path), files, dirs);
-            res.status_code(StatusCode::OK);
-            match format.subtype().as_ref() {
-                "plain" => res.render(Text::Plain(list_text(&root))),
-                "json" => res.render(Text::Json(list_json(&root))),
-                "xml" => res.render(Text::Xml(list_xml(&root))),
-                _ => res.render(Text::Html(list_html(&root))),
-            };
         }
+        res.render(StatusError::not_found());
     }
 }
 
+pub(crate) fn render_list(req: &Request, format: ListFormat, current: &CurrentInfo, res: &mut Response) {
+    let format = match format {
+        ListFormat::Auto => req.first_accept().unwrap_or(mime::TEXT_HTML),
+        ListFormat::Html => mime::TEXT_HTML,
+        ListFormat::Json => mime::APPLICATION_JSON,
+        ListFormat::Xml => mime::TEXT_XML,
+        ListFormat::Text => mime::TEXT_PLAIN,
+    };
+    match format.subtype().as_ref() {
+        "plain" => res.render(Text::Plain(list_text(current))),
+        "json" => res.render(Text::Json(list_json(current))),
+        "xml" => res.render(Text::Xml(list_xml(current))),
+        _ => res.render(Text::Html(list_html(current))),
+    };
+}
+
 #[inline]
 fn list_json(current: &CurrentInfo) -> String {
     json!(current).to_string()
@@ -586,6 +848,9 @@ fn list_html(current: &CurrentInfo) -> String {
         }
         write!(ftxt, "</table>").ok();
     }
+    if let Some(readme) = &current.readme {
+        write!(ftxt, "<hr/><article><pre>{}</pre></article>", escape_html(readme)).ok();
+    }
     write!(
         ftxt,
         r#"<hr/><footer><a href="https://salvo.rs" target="_blank">salvo</a></footer></body>"#
@@ -597,6 +862,20 @@ fn list_html(current: &CurrentInfo) -> String {
 fn list_text(current: &CurrentInfo) -> String {
     json!(current).to_string()
 }
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
 
 const HTML_STYLE: &str = r#"
     :root {
@@ -1,31 +1,57 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
 use rust_embed::{EmbeddedFile, Metadata, RustEmbed};
-use salvo_core::http::header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH};
-use salvo_core::http::{HeaderValue, Mime, Request, Response, StatusCode};
+use salvo_core::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, ETAG, IF_NONE_MATCH, VARY};
+use salvo_core::http::{self, HeaderValue, Method, Mime, Request, Response, StatusCode};
 use salvo_core::{async_trait, Depot, FlowCtrl, Handler, IntoVecString};
 
 use super::{decode_url_path_safely, format_url_path_safely, join_path, redirect_to_dir_url};
+use crate::cache::CachePolicy;
+use crate::dir::CompressionAlgo;
 
 /// Handler that serves embed file.
+///
+/// Files are served with an `ETag` computed from the content hash, and conditional requests with `If-None-Match`
+/// are answered with `304 Not Modified`. If a precompressed variation of the requested file is embedded, such as
+/// `app.js.br` for `app.js`, and the client accepts its encoding, the variation is served instead.
 #[non_exhaustive]
-#[derive(Default)]
 pub struct StaticEmbed<T> {
     _assets: PhantomData<T>,
     /// Default file names list.
     pub defaults: Vec<String>,
     /// Fallback file name. This is used when the requested file is not found.
     pub fallback: Option<String>,
+    /// Compressed variations.
+    ///
+    /// The key is the compression algorithm, and the value is the file extension.
+    /// If the compression file exists, it will serve the compressed file instead of the original file.
+    pub compressed_variations: HashMap<CompressionAlgo, Vec<String>>,
+    /// Cache policy of served files.
+    pub cache_policy: Option<CachePolicy>,
 }
 
 /// Create a new `StaticEmbed` middleware.
 #[inline]
 pub fn static_embed<T: RustEmbed>() -> StaticEmbed<T> {
-    StaticEmbed {
-        _assets: PhantomData,
-        defaults: vec![],
-        fallback: None,
+    StaticEmbed::default()
+}
+
+impl<T> Default for StaticEmbed<T> {
+    fn default() -> Self {
+        let mut compressed_variations = HashMap::new();
+        compressed_variations.insert(CompressionAlgo::Brotli, vec!["br".to_owned()]);
+        compressed_variations.insert(CompressionAlgo::Zstd, vec!["zst".to_owned()]);
+        compressed_variations.insert(CompressionAlgo::Gzip, vec!["gz".to_owned()]);
+        compressed_variations.insert(CompressionAlgo::Deflate, vec!["deflate".to_owned()]);
+        Self {
+            _assets: PhantomData,
+            defaults: vec![],
+            fallback: None,
+            compressed_variations,
+            cache_policy: None,
+        }
     }
 }
 
@@ -33,7 +59,7 @@ pub fn static_embed<T: RustEmbed>() -> StaticEmbed<T> {
 #[inline]
 pub fn render_embedded_file(file: EmbeddedFile, req: &Request, res: &mut Response, mime: Option<Mime>) {
     let EmbeddedFile { data, metadata, .. } = file;
-    render_embedded_data(data, &metadata, req, res, mime);
+    render_embedded_data(data, &metadata, req, res, mime, None, false);
 }
 
 fn render_embedded_data(
@@ -42,21 +68,39 @@ fn render_embedded_data(
     req: &Request,
     res: &mut Response,
     mime: Option<Mime>,
+    content_encoding: Option<CompressionAlgo>,
+    negotiated: bool,
 ) {
     let mime = mime.unwrap_or_else(|| mime_infer::from_path(req.uri().path()).first_or_octet_stream());
+    let mime = if (mime.type_() == mime::TEXT || mime.subtype() == mime::JSON || mime.subtype() == mime::JAVASCRIPT)
+        && mime.get_param(mime::CHARSET).is_none()
+    {
+        format!("{mime}; charset=utf-8").parse::<Mime>().unwrap_or(mime)
+    } else {
+        mime
+    };
     res.headers_mut().insert(
         CONTENT_TYPE,
         mime.as_ref()
             .parse()
             .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
     );
+    if let Some(algo) = content_encoding {
+        res.headers_mut().insert(CONTENT_ENCODING, algo.into());
+    }
+    // The identity representation varies by `Accept-Encoding` too, when compressed variations exist.
+    if negotiated || content_encoding.is_some() {
+        res.headers_mut()
+            .append(VARY, HeaderValue::from_static("accept-encoding"));
+    }
 
     let hash = hex::encode(metadata.sha256_hash());
     // if etag is matched, return 304
     if req
         .headers()
         .get(IF_NONE_MATCH)
-        .map(|etag| etag.to_str().unwrap_or("000000").eq(&hash))
+        .and_then(|v| v.to_str().ok())
+        .map(|v| etag_matches(v, &hash))
         .unwrap_or(false)
     {
         res.status_code(StatusCode::NOT_MODIFIED);
@@ -64,12 +108,15 @@ fn render_embedded_data(
     }
 
     // otherwise, return 200 with etag hash
-    if let Ok(hash) = hash.parse() {
-        res.headers_mut().insert(ETAG, hash);
+    if let Ok(etag) = format!("\"{hash}\"").parse() {
+        res.headers_mut().insert(ETAG, etag);
     } else {
         tracing::error!("Failed to parse etag hash: {}", hash);
     }
 
+    if req.method() == Method::HEAD {
+        return;
+    }
     match data {
         Cow::Borrowed(data) => {
             res.write_body(data).ok();
@@ -80,6 +127,17 @@ fn render_embedded_data(
     }
 }
 
+/// Returns `true` if the `If-None-Match` header value matches the content hash.
+///
+/// Both quoted and bare hashes are accepted, so clients which cached the unquoted `ETag` of earlier versions still
+/// get `304 Not Modified`.
+fn etag_matches(if_none_match: &str, hash: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/").trim_matches('"') == hash)
+}
+
 impl<T> StaticEmbed<T>
 where
     T: RustEmbed + Send + Sync + 'static,
@@ -87,11 +145,7 @@ where
     /// Create a new `StaticEmbed`.
     #[inline]
     pub fn new() -> Self {
-        Self {
-            _assets: PhantomData,
-            defaults: vec![],
-            fallback: None,
-        }
+        Self::default()
     }
 
     /// Create a new `StaticEmbed` with defaults.
@@ -107,6 +161,58 @@ where
         self.fallback = Some(fallback.into());
         self
     }
+
+    /// Sets cache policy of served files.
+    #[inline]
+    pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.cache_policy = Some(policy);
+        self
+    }
+
+    /// Sets compressed_variations and returns a new `StaticEmbed`.
+    #[inline]
+    pub fn compressed_variation<A>(mut self, algo: A, exts: &str) -> Self
+    where
+        A: Into<CompressionAlgo>,
+    {
+        self.compressed_variations
+            .insert(algo.into(), exts.split(',').map(|s| s.trim().to_string()).collect());
+        self
+    }
+
+    /// Returns `true` if any compressed variation of `key_path` is embedded.
+    fn has_compressed_variation(&self, key_path: &str) -> bool {
+        self.compressed_variations
+            .values()
+            .flatten()
+            .any(|ext| T::get(&format!("{key_path}.{ext}")).is_some())
+    }
+
+    /// Find an embedded compressed variation of `key_path` accepted by the client.
+    fn compressed_variation_of(&self, req: &Request, key_path: &str) -> Option<(EmbeddedFile, CompressionAlgo)> {
+        if self.compressed_variations.is_empty() {
+            return None;
+        }
+        let header = req
+            .headers()
+            .get(ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        for (algo, q) in http::parse_accept_encoding(header) {
+            let Ok(algo) = algo.parse::<CompressionAlgo>() else {
+                continue;
+            };
+            if q == 0 {
+                continue;
+            }
+            for ext in self.compressed_variations.get(&algo).into_iter().flatten() {
+                if let Some(file) = T::get(&format!("{key_path}.{ext}")) {
+                    return Some((file, algo));
+                }
+            }
+        }
+        None
+    }
 }
 #[async_trait]
 impl<T> Handler for StaticEmbed<T>
@@ -150,7 +256,15 @@ where
         match embedded_file {
             Some(file) => {
                 let mime = mime_infer::from_path(&*key_path).first_or_octet_stream();
-                render_embedded_file(file, req, res, Some(mime));
+                let (file, content_encoding, negotiated) = match self.compressed_variation_of(req, &key_path) {
+                    Some((file, algo)) => (file, Some(algo), true),
+                    None => (file, None, self.has_compressed_variation(&key_path)),
+                };
+                let EmbeddedFile { data, metadata, .. } = file;
+                render_embedded_data(data, &metadata, req, res, Some(mime), content_encoding, negotiated);
+                if let Some(policy) = &self.cache_policy {
+                    policy.apply(&key_path, res);
+                }
             }
             None => {
                 res.status_code(StatusCode::NOT_FOUND);
@@ -166,7 +280,7 @@ pub struct EmbeddedFileHandler(pub EmbeddedFile);
 impl Handler for EmbeddedFileHandler {
     #[inline]
     async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
-        render_embedded_data(self.0.data.clone(), &self.0.metadata, req, res, None);
+        render_embedded_data(self.0.data.clone(), &self.0.metadata, req, res, None, None, false);
     }
 }
 
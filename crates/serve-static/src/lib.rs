@@ -5,8 +5,11 @@
 #![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+pub mod access;
+pub mod cache;
 pub mod dir;
 mod file;
+pub mod vfs;
 
 use percent_encoding::{utf8_percent_encode, CONTROLS};
 use salvo_core::http::uri::{Parts as UriParts, Uri};
@@ -38,6 +41,14 @@ cfg_feature! {
     mod embed;
     pub use embed::{render_embedded_file, static_embed, EmbeddedFileExt, StaticEmbed};
 }
+cfg_feature! {
+    #![feature = "webdav"]
+    pub mod webdav;
+}
+cfg_feature! {
+    #![feature = "image"]
+    pub mod transform;
+}
 
 #[inline]
 pub(crate) fn encode_url_path(path: &str) -> String {
@@ -104,12 +115,12 @@ mod tests {
 
     #[tokio::test]
     async fn test_serve_static_dir() {
-        let router = Router::with_path("<*path>").get(
-            StaticDir::new(vec!["test/static"])
-                .include_dot_files(false)
-                .auto_list(true)
-                .defaults("index.html"),
-        );
+        let dir = StaticDir::new(vec!["test/static"])
+            .include_dot_files(false)
+            .auto_list(true)
+            .defaults("index.html");
+        assert_eq!(dir.roots(), [std::path::PathBuf::from("test/static")]);
+        let router = Router::with_path("<*path>").get(dir);
         let service = Service::new(router);
 
         async fn access(service: &Service, accept: &str, url: &str) -> String {
@@ -164,6 +175,96 @@ mod tests {
         assert!(content == "copy3");
     }
 
+    #[tokio::test]
+    async fn test_serve_static_dir_listing() {
+        let router = Router::with_path("<*path>").get(
+            StaticDir::new(vec!["test/static"])
+                .auto_list(true)
+                .list_format(dir::ListFormat::Json)
+                .list_readmes("test3.txt")
+                .list_hidden(|name| name == "dir2"),
+        );
+        let service = Service::new(router);
+        let content = TestClient::get("http://127.0.0.1:5801/dir1/")
+            .add_header("accept", "text/html", true)
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        let info: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(info["readme"], "copy3");
+        assert_eq!(info["files"][0]["name"], "test3.txt");
+        assert!(info["dirs"].as_array().unwrap().is_empty());
+
+        let router =
+            Router::with_path("<*path>").get(StaticDir::new(vec!["test/static"]).auto_list(true).list_renderer(
+                |_req, info, res| {
+                    res.render(format!("{} entries", info.files.len() + info.dirs.len()));
+                },
+            ));
+        let service = Service::new(router);
+        let content = TestClient::get("http://127.0.0.1:5801/dir1/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "2 entries");
+    }
+
+    #[tokio::test]
+    async fn test_serve_static_dir_cache_policy() {
+        let router = Router::with_path("<*path>").get(
+            StaticDir::new(vec!["test/static"]).defaults("index.html").cache_policy(
+                cache::CachePolicy::new()
+                    .rule("*.html", "no-cache")
+                    .rule("dir1/**", "max-age=60"),
+            ),
+        );
+        let service = Service::new(router);
+
+        let response = TestClient::get("http://127.0.0.1:5801/").send(&service).await;
+        assert_eq!(response.headers().get("cache-control").unwrap(), "no-cache");
+        let response = TestClient::get("http://127.0.0.1:5801/dir1/dir2/test3.txt")
+            .send(&service)
+            .await;
+        assert_eq!(response.headers().get("cache-control").unwrap(), "max-age=60");
+        let response = TestClient::get("http://127.0.0.1:5801/test1.txt").send(&service).await;
+        assert!(response.headers().get("cache-control").is_none());
+        let response = TestClient::get("http://127.0.0.1:5801/notexist.html")
+            .send(&service)
+            .await;
+        assert!(response.headers().get("cache-control").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_serve_static_dir_spa() {
+        let router = Router::with_path("<*path>").get(
+            StaticDir::new(vec!["test/static"])
+                .defaults("index.html")
+                .spa("index.html")
+                .spa_exclude("/api"),
+        );
+        let service = Service::new(router);
+
+        let mut response = TestClient::get("http://127.0.0.1:5801/users/1").send(&service).await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::OK);
+        assert!(response.take_string().await.unwrap().contains("Index page"));
+
+        let mut response = TestClient::get("http://127.0.0.1:5801/test1.txt").send(&service).await;
+        assert_eq!(response.take_string().await.unwrap(), "copy1");
+
+        let response = TestClient::get("http://127.0.0.1:5801/app.js").send(&service).await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::NOT_FOUND);
+
+        let response = TestClient::get("http://127.0.0.1:5801/api/users").send(&service).await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::NOT_FOUND);
+
+        let mut response = TestClient::get("http://127.0.0.1:5801/apis").send(&service).await;
+        assert!(response.take_string().await.unwrap().contains("Index page"));
+    }
+
     #[tokio::test]
     async fn test_serve_static_file() {
         let router = Router::new()
@@ -242,5 +343,38 @@ mod tests {
             .send(&service)
             .await;
         assert_eq!(response.status_code.unwrap(), StatusCode::NOT_FOUND);
+
+        let response = TestClient::get("http://127.0.0.1:5801/dir/test2.txt")
+            .add_header("accept-encoding", "br, gzip", true)
+            .send(&service)
+            .await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::OK);
+        assert_eq!(response.headers().get("content-encoding").unwrap(), "gzip");
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(response.headers().get("vary").unwrap(), "accept-encoding");
+
+        let response = TestClient::get("http://127.0.0.1:5801/dir/test2.txt")
+            .send(&service)
+            .await;
+        assert!(response.headers().get("content-encoding").is_none());
+        assert_eq!(response.headers().get("vary").unwrap(), "accept-encoding");
+
+        let response = TestClient::get("http://127.0.0.1:5801/dir/test1.txt")
+            .add_header("accept-encoding", "gzip", true)
+            .send(&service)
+            .await;
+        assert!(response.headers().get("content-encoding").is_none());
+        assert!(response.headers().get("vary").is_none());
+        let etag = response.headers().get("etag").unwrap().to_str().unwrap().to_owned();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+        let response = TestClient::get("http://127.0.0.1:5801/dir/test1.txt")
+            .add_header("if-none-match", etag, true)
+            .send(&service)
+            .await;
+        assert_eq!(response.status_code.unwrap(), StatusCode::NOT_MODIFIED);
     }
 }
@@ -0,0 +1,578 @@
+//! On-the-fly image transformation.
+//!
+//! [`ImageTransform`] reads images from a [`Vfs`] backend and resizes, crops or converts them according to the query
+//! parameters of the request:
+//!
+//! | Parameter | Description                                                      |
+//! |-----------|------------------------------------------------------------------|
+//! | `w`       | Target width in pixels.                                          |
+//! | `h`       | Target height in pixels.                                         |
+//! | `fit`     | `contain` (default), `cover` (crop to fill) or `fill` (stretch). |
+//! | `fmt`     | `png`, `jpeg`, `webp` or `gif`, the source format by default.    |
+//! | `q`       | JPEG quality, from 1 to 100.                                     |
+//! | `sig`     | Signature created by [`UrlSigner`], required if a signer is set. |
+//!
+//! Transformed images are cached in memory, so only the first request for a variation pays the decoding cost.
+//! Set a [`UrlSigner`] to reject variations which are not created by the application, otherwise anyone can make the
+//! server resize images to arbitrary sizes.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_serve_static::transform::{ImageTransform, TransformParams, UrlSigner};
+//! use salvo_serve_static::vfs::LocalFs;
+//!
+//! let signer = UrlSigner::new("secret key");
+//! // Links to `/media/avatar.png?{query}` are rendered by the application.
+//! let query = TransformParams::new().width(128).height(128).signed_query("avatar.png", &signer);
+//! println!("{query}");
+//!
+//! let router = Router::with_path("media/<**path>").get(ImageTransform::new(LocalFs::new("uploads")).signer(signer));
+//! ```
+use std::fmt::{self, Display, Formatter};
+use std::io::Cursor;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use moka::future::Cache;
+use salvo_core::http::header::{HeaderValue, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use salvo_core::http::{Method, Request, Response, StatusCode, StatusError};
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use super::{decode_url_path_safely, format_url_path_safely};
+use crate::cache::CachePolicy;
+use crate::vfs::Vfs;
+
+pub use salvo_extra::signed_url::UrlSigner;
+
+/// How the image is fitted into the target size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fit {
+    /// Scale to fit inside the target size, keeping the aspect ratio.
+    #[default]
+    Contain,
+    /// Scale and crop to cover the target size, keeping the aspect ratio.
+    Cover,
+    /// Stretch to the target size.
+    Fill,
+}
+impl FromStr for Fit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "contain" => Ok(Self::Contain),
+            "cover" => Ok(Self::Cover),
+            "fill" => Ok(Self::Fill),
+            _ => Err(format!("unknown fit: {s}")),
+        }
+    }
+}
+impl Display for Fit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Contain => write!(f, "contain"),
+            Self::Cover => write!(f, "cover"),
+            Self::Fill => write!(f, "fill"),
+        }
+    }
+}
+
+/// Output format of the transformed image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// PNG
+    Png,
+    /// JPEG
+    Jpeg,
+    /// WebP, lossless.
+    Webp,
+    /// GIF
+    Gif,
+}
+impl OutputFormat {
+    fn image_format(self) -> ImageFormat {
+        match self {
+            Self::Png => ImageFormat::Png,
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Webp => ImageFormat::WebP,
+            Self::Gif => ImageFormat::Gif,
+        }
+    }
+    fn from_image_format(format: ImageFormat) -> Option<Self> {
+        match format {
+            ImageFormat::Png => Some(Self::Png),
+            ImageFormat::Jpeg => Some(Self::Jpeg),
+            ImageFormat::WebP => Some(Self::Webp),
+            ImageFormat::Gif => Some(Self::Gif),
+            _ => None,
+        }
+    }
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Webp => "image/webp",
+            Self::Gif => "image/gif",
+        }
+    }
+}
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(Self::Png),
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            "webp" => Ok(Self::Webp),
+            "gif" => Ok(Self::Gif),
+            _ => Err(format!("unknown image format: {s}")),
+        }
+    }
+}
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Png => write!(f, "png"),
+            Self::Jpeg => write!(f, "jpeg"),
+            Self::Webp => write!(f, "webp"),
+            Self::Gif => write!(f, "gif"),
+        }
+    }
+}
+
+/// Parameters of an image transformation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TransformParams {
+    /// Target width in pixels.
+    pub width: Option<u32>,
+    /// Target height in pixels.
+    pub height: Option<u32>,
+    /// How the image is fitted into the target size.
+    pub fit: Fit,
+    /// Output format, the source format if not set.
+    pub format: Option<OutputFormat>,
+    /// JPEG quality.
+    pub quality: Option<u8>,
+}
+impl TransformParams {
+    /// Create new empty `TransformParams`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the target width.
+    #[inline]
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets the target height.
+    #[inline]
+    pub fn height(mut self, height: u32) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    /// Sets how the image is fitted into the target size.
+    #[inline]
+    pub fn fit(mut self, fit: Fit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Sets the output format.
+    #[inline]
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Sets the JPEG quality.
+    #[inline]
+    pub fn quality(mut self, quality: u8) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    /// Parse parameters from the query of the request.
+    pub fn from_request(req: &Request) -> Result<Self, StatusError> {
+        fn parse<T: FromStr>(req: &Request, key: &str) -> Result<Option<T>, StatusError> {
+            match req.queries().get(key) {
+                Some(value) => value
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| StatusError::bad_request().brief(format!("Invalid query parameter `{key}`."))),
+                None => Ok(None),
+            }
+        }
+        let params = Self {
+            width: parse(req, "w")?,
+            height: parse(req, "h")?,
+            fit: parse(req, "fit")?.unwrap_or_default(),
+            format: parse(req, "fmt")?,
+            quality: parse(req, "q")?,
+        };
+        if params.width == Some(0) || params.height == Some(0) {
+            return Err(StatusError::bad_request().brief("Image size must not be zero."));
+        }
+        if matches!(params.quality, Some(q) if !(1..=100).contains(&q)) {
+            return Err(StatusError::bad_request().brief("Image quality must be between 1 and 100."));
+        }
+        Ok(params)
+    }
+
+    /// Returns the query string of the transformation of the image at `path`, including the signature of `signer`.
+    ///
+    /// `path` is relative to the root of the [`Vfs`], leading slashes are ignored.
+    pub fn signed_query(&self, path: &str, signer: &UrlSigner) -> String {
+        let query = self.to_query();
+        let sig = signer.signature(path.trim_start_matches('/'), &query);
+        if query.is_empty() {
+            format!("sig={sig}")
+        } else {
+            format!("{query}&sig={sig}")
+        }
+    }
+
+    /// Encode parameters as a query string, in a stable order.
+    pub fn to_query(&self) -> String {
+        let mut pairs = vec![];
+        if let Some(width) = self.width {
+            pairs.push(format!("w={width}"));
+        }
+        if let Some(height) = self.height {
+            pairs.push(format!("h={height}"));
+        }
+        if self.fit != Fit::Contain {
+            pairs.push(format!("fit={}", self.fit));
+        }
+        if let Some(format) = self.format {
+            pairs.push(format!("fmt={format}"));
+        }
+        if let Some(quality) = self.quality {
+            pairs.push(format!("q={quality}"));
+        }
+        pairs.join("&")
+    }
+
+    fn apply(&self, image: DynamicImage) -> DynamicImage {
+        let (width, height) = match (self.width, self.height) {
+            (None, None) => return image,
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (width, u32::MAX),
+            (None, Some(height)) => (u32::MAX, height),
+        };
+        match self.fit {
+            Fit::Cover if self.width.is_some() && self.height.is_some() => {
+                image.resize_to_fill(width, height, FilterType::Lanczos3)
+            }
+            Fit::Fill if self.width.is_some() && self.height.is_some() => {
+                image.resize_exact(width, height, FilterType::Lanczos3)
+            }
+            _ => image.resize(width, height, FilterType::Lanczos3),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Transformed {
+    data: Vec<u8>,
+    content_type: &'static str,
+    etag: String,
+}
+
+/// Handler that transforms images read from a [`Vfs`].
+///
+/// View [module level documentation](index.html) for more details.
+pub struct ImageTransform<V> {
+    vfs: V,
+    signer: Option<UrlSigner>,
+    max_width: u32,
+    max_height: u32,
+    max_source_size: u64,
+    cache: Cache<String, Arc<Transformed>>,
+    cache_policy: Option<CachePolicy>,
+}
+impl<V> ImageTransform<V>
+where
+    V: Vfs,
+{
+    /// Create a new `ImageTransform`.
+    ///
+    /// Output sizes are limited to 4096x4096, source files to 20 MiB and the cache to 64 MiB by default.
+    #[inline]
+    pub fn new(vfs: V) -> Self {
+        Self {
+            vfs,
+            signer: None,
+            max_width: 4096,
+            max_height: 4096,
+            max_source_size: 20 * 1024 * 1024,
+            cache: Self::build_cache(64 * 1024 * 1024),
+            cache_policy: None,
+        }
+    }
+
+    /// Require signed URLs, requests without a valid signature are rejected with `403 Forbidden`.
+    #[inline]
+    pub fn signer(mut self, signer: UrlSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Sets the maximum output size.
+    #[inline]
+    pub fn max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_width = width;
+        self.max_height = height;
+        self
+    }
+
+    /// Sets the maximum size of source files in bytes.
+    #[inline]
+    pub fn max_source_size(mut self, size: u64) -> Self {
+        self.max_source_size = size;
+        self
+    }
+
+    /// Sets the maximum total size of cached images in bytes.
+    #[inline]
+    pub fn cache_capacity(mut self, capacity: u64) -> Self {
+        self.cache = Self::build_cache(capacity);
+        self
+    }
+
+    /// Sets cache policy of transformed images.
+    #[inline]
+    pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.cache_policy = Some(policy);
+        self
+    }
+
+    fn build_cache(capacity: u64) -> Cache<String, Arc<Transformed>> {
+        Cache::builder()
+            .max_capacity(capacity)
+            .weigher(|key: &String, value: &Arc<Transformed>| {
+                (key.len() + value.data.len()).try_into().unwrap_or(u32::MAX)
+            })
+            .build()
+    }
+
+    async fn transform(&self, path: &str, params: &TransformParams) -> Result<Arc<Transformed>, StatusError> {
+        let metadata = match self.vfs.metadata(path).await {
+            Ok(metadata) if !metadata.is_dir => metadata,
+            _ => return Err(StatusError::not_found()),
+        };
+        if metadata.len > self.max_source_size {
+            return Err(StatusError::payload_too_large());
+        }
+        let modified = metadata
+            .modified
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let key = format!(
+            "{path}?{}#{:x}-{:x}-{:x}",
+            params.to_query(),
+            metadata.len,
+            modified.as_secs(),
+            modified.subsec_nanos()
+        );
+        if let Some(transformed) = self.cache.get(&key).await {
+            return Ok(transformed);
+        }
+
+        let mut source = Vec::with_capacity(metadata.len as usize);
+        let mut file = self.vfs.open(path).await.map_err(|_| StatusError::not_found())?;
+        file.read_to_end(&mut source)
+            .await
+            .map_err(|_| StatusError::internal_server_error().brief("Read file failed."))?;
+        let params = params.clone();
+        let etag = format!("\"{}\"", hex::encode(&Sha256::digest(key.as_bytes())[..16]));
+        let transformed = tokio::task::spawn_blocking(move || encode(&source, &params, etag))
+            .await
+            .map_err(|_| StatusError::internal_server_error())??;
+        let transformed = Arc::new(transformed);
+        self.cache.insert(key, transformed.clone()).await;
+        Ok(transformed)
+    }
+}
+
+fn encode(source: &[u8], params: &TransformParams, etag: String) -> Result<Transformed, StatusError> {
+    let source_format = image::guess_format(source)
+        .map_err(|_| StatusError::unsupported_media_type().brief("Unsupported image format."))?;
+    let image = image::load_from_memory_with_format(source, source_format)
+        .map_err(|_| StatusError::unsupported_media_type().brief("Decode image failed."))?;
+    let image = params.apply(image);
+    let format = params
+        .format
+        .or_else(|| OutputFormat::from_image_format(source_format))
+        .unwrap_or(OutputFormat::Png);
+    let mut data = Cursor::new(Vec::new());
+    let result = match format {
+        OutputFormat::Jpeg => {
+            let encoder = JpegEncoder::new_with_quality(&mut data, params.quality.unwrap_or(80));
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)
+        }
+        OutputFormat::Webp => DynamicImage::ImageRgba8(image.to_rgba8()).write_to(&mut data, format.image_format()),
+        _ => image.write_to(&mut data, format.image_format()),
+    };
+    if let Err(e) = result {
+        tracing::error!(error = ?e, "encode image failed");
+        return Err(StatusError::internal_server_error().brief("Encode image failed."));
+    }
+    Ok(Transformed {
+        data: data.into_inner(),
+        content_type: format.content_type(),
+        etag,
+    })
+}
+
+#[async_trait]
+impl<V> Handler for ImageTransform<V>
+where
+    V: Vfs,
+{
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        if req.method() != Method::GET && req.method() != Method::HEAD {
+            res.status_code(StatusCode::METHOD_NOT_ALLOWED);
+            return;
+        }
+        let param = req.params().iter().find(|(key, _)| key.starts_with('*'));
+        let path = if let Some((_, value)) = param {
+            value.clone()
+        } else {
+            decode_url_path_safely(req.uri().path())
+        };
+        let path = format_url_path_safely(&path);
+        if path.is_empty() || path.ends_with('/') || path.split('/').any(|seg| seg.starts_with('.')) {
+            res.render(StatusError::not_found());
+            return;
+        }
+        let params = match TransformParams::from_request(req) {
+            Ok(params) => params,
+            Err(e) => {
+                res.render(e);
+                return;
+            }
+        };
+        if let Some(signer) = &self.signer {
+            let sig = req.queries().get("sig").map(String::as_str).unwrap_or_default();
+            if !signer.verify_signature(path.trim_start_matches('/'), &params.to_query(), sig) {
+                res.render(StatusError::forbidden().brief("Invalid signature."));
+                return;
+            }
+        }
+        if params.width.unwrap_or_default() > self.max_width || params.height.unwrap_or_default() > self.max_height {
+            res.render(StatusError::bad_request().brief("Image size is too large."));
+            return;
+        }
+
+        let transformed = match self.transform(&path, &params).await {
+            Ok(transformed) => transformed,
+            Err(e) => {
+                res.render(e);
+                return;
+            }
+        };
+        if let Ok(etag) = HeaderValue::from_str(&transformed.etag) {
+            res.headers_mut().insert(ETAG, etag);
+        }
+        let not_modified = req
+            .headers()
+            .get(IF_NONE_MATCH)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| {
+                v.split(',')
+                    .any(|tag| tag.trim() == "*" || tag.trim() == transformed.etag)
+            })
+            .unwrap_or(false);
+        if not_modified {
+            res.status_code(StatusCode::NOT_MODIFIED);
+        } else {
+            res.headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static(transformed.content_type));
+            res.status_code(StatusCode::OK);
+            if req.method() != Method::HEAD {
+                res.write_body(transformed.data.clone()).ok();
+            }
+        }
+        if let Some(policy) = &self.cache_policy {
+            policy.apply(&path, res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, Rgba, RgbaImage};
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+    use crate::vfs::MemoryFs;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 255]));
+        let mut data = Cursor::new(Vec::new());
+        image.write_to(&mut data, ImageFormat::Png).unwrap();
+        data.into_inner()
+    }
+
+    async fn fetch(service: &Service, url: &str) -> (StatusCode, Vec<u8>) {
+        let mut res = TestClient::get(url).send(service).await;
+        let status = res.status_code.unwrap_or(StatusCode::OK);
+        (status, res.take_bytes(None).await.unwrap().to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_image_transform() {
+        let fs = MemoryFs::new().file("photo.png", png(40, 20)).file("note.txt", "text");
+        let service = Service::new(Router::with_path("<**path>").get(ImageTransform::new(fs).max_size(100, 100)));
+
+        let (status, data) = fetch(&service, "http://127.0.0.1:5801/photo.png?w=10").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(image::load_from_memory(&data).unwrap().dimensions(), (10, 5));
+
+        let (_, data) = fetch(&service, "http://127.0.0.1:5801/photo.png?w=10&h=10&fit=cover&fmt=jpeg").await;
+        assert_eq!(image::guess_format(&data).unwrap(), ImageFormat::Jpeg);
+        assert_eq!(image::load_from_memory(&data).unwrap().dimensions(), (10, 10));
+
+        let (status, _) = fetch(&service, "http://127.0.0.1:5801/photo.png?w=1000").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = fetch(&service, "http://127.0.0.1:5801/photo.png?fit=zoom").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = fetch(&service, "http://127.0.0.1:5801/note.txt?w=10").await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let (status, _) = fetch(&service, "http://127.0.0.1:5801/missing.png").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_image_transform_signed() {
+        let signer = UrlSigner::new("secret");
+        let query = TransformParams::new().width(8).signed_query("photo.png", &signer);
+        let fs = MemoryFs::new().file("photo.png", png(16, 16));
+        let service = Service::new(Router::with_path("<**path>").get(ImageTransform::new(fs).signer(signer)));
+
+        let (status, _) = fetch(&service, &format!("http://127.0.0.1:5801/photo.png?{query}")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = fetch(&service, "http://127.0.0.1:5801/photo.png?w=8").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let tampered = query.replace("w=8", "w=9");
+        let (status, _) = fetch(&service, &format!("http://127.0.0.1:5801/photo.png?{tampered}")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,525 @@
+//! Virtual filesystem for static serving backends.
+//!
+//! [`StaticDir`](crate::StaticDir) serves files from any [`Vfs`] backend with the same range, `ETag` and conditional
+//! request handling, use [`StaticDir::with_vfs`](crate::StaticDir::with_vfs) to serve a backend other than the local
+//! disk. Three backends are provided:
+//!
+//! - [`LocalFs`] serves local directories, it is the backend of [`StaticDir::new`](crate::StaticDir::new).
+//! - [`MemoryFs`] serves files kept in memory, such as the entries of a zip archive loaded at startup.
+//! - [`OverlayFs`] layers two backends, files of the upper one shadow files of the lower one. Nest it to overlay
+//!   more roots.
+//!
+//! Object stores such as S3 are supported by implementing [`Vfs`] for a client of the store, the `File` type only
+//! needs to implement [`AsyncRead`] and [`AsyncSeek`], so it can download ranges lazily.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_serve_static::vfs::{LocalFs, MemoryFs, OverlayFs};
+//! use salvo_serve_static::StaticDir;
+//!
+//! let builtin = MemoryFs::new().file("index.html", "<h1>Hello</h1>");
+//! let fs = OverlayFs::new(LocalFs::new("static"), builtin);
+//! let router = Router::with_path("<**path>").get(StaticDir::with_vfs(fs).defaults("index.html"));
+//! ```
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::{Cursor, ErrorKind, Result as IoResult, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+use super::format_url_path_safely;
+use crate::dir::StaticRoots;
+
+/// Metadata of a file or directory in a [`Vfs`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct VfsMetadata {
+    /// Is a directory.
+    pub is_dir: bool,
+    /// Content length.
+    pub len: u64,
+    /// Last modified time.
+    pub modified: Option<SystemTime>,
+    /// Creation time.
+    pub created: Option<SystemTime>,
+}
+impl VfsMetadata {
+    /// Create a new `VfsMetadata`.
+    #[inline]
+    pub fn new(is_dir: bool, len: u64, modified: Option<SystemTime>, created: Option<SystemTime>) -> Self {
+        Self {
+            is_dir,
+            len,
+            modified,
+            created,
+        }
+    }
+}
+impl From<std::fs::Metadata> for VfsMetadata {
+    #[inline]
+    fn from(metadata: std::fs::Metadata) -> Self {
+        Self::new(
+            metadata.is_dir(),
+            metadata.len(),
+            metadata.modified().ok(),
+            metadata.created().ok(),
+        )
+    }
+}
+
+/// Read only virtual filesystem.
+///
+/// Paths are `/` separated, relative to the root of the filesystem and never contain `.` or `..` segments, the root
+/// itself is the empty path. Missing files are reported with [`ErrorKind::NotFound`].
+pub trait Vfs: Send + Sync + 'static {
+    /// File opened for reading.
+    type File: AsyncRead + AsyncSeek + Unpin + Send + 'static;
+
+    /// Get metadata of a file or directory.
+    fn metadata(&self, path: &str) -> impl Future<Output = IoResult<VfsMetadata>> + Send;
+    /// List a directory.
+    fn read_dir(&self, path: &str) -> impl Future<Output = IoResult<Vec<(String, VfsMetadata)>>> + Send;
+    /// Open a file for reading.
+    fn open(&self, path: &str) -> impl Future<Output = IoResult<Self::File>> + Send;
+}
+
+/// [`Vfs`] backend reading files from local directories.
+///
+/// With several roots, a file is read from the first root which contains it and the listings of a directory are
+/// merged.
+#[derive(Clone, Debug)]
+pub struct LocalFs {
+    roots: Vec<PathBuf>,
+}
+impl LocalFs {
+    /// Create a new `LocalFs`.
+    #[inline]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            roots: vec![root.into()],
+        }
+    }
+
+    /// Create a new `LocalFs` with several roots.
+    #[inline]
+    pub fn with_roots<T: StaticRoots>(roots: T) -> Self {
+        Self { roots: roots.collect() }
+    }
+
+    /// Get the roots.
+    #[inline]
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Path of a file in the first root, where [`WebDav`](crate::webdav::WebDav) writes files.
+    #[cfg(feature = "webdav")]
+    pub(crate) fn path(&self, path: &str) -> PathBuf {
+        self.roots
+            .first()
+            .map(|root| root.join(path))
+            .unwrap_or_else(|| path.into())
+    }
+
+    async fn read_root_dir(root: &Path, path: &str) -> IoResult<Vec<(String, VfsMetadata)>> {
+        let mut entries = tokio::fs::read_dir(root.join(path)).await?;
+        let mut list = vec![];
+        while let Some(entry) = entries.next_entry().await? {
+            list.push((
+                entry.file_name().to_string_lossy().into_owned(),
+                entry.metadata().await?.into(),
+            ));
+        }
+        Ok(list)
+    }
+}
+impl Vfs for LocalFs {
+    type File = tokio::fs::File;
+
+    async fn metadata(&self, path: &str) -> IoResult<VfsMetadata> {
+        let mut error = None;
+        for root in &self.roots {
+            match tokio::fs::metadata(root.join(path)).await {
+                Ok(metadata) => return Ok(metadata.into()),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| ErrorKind::NotFound.into()))
+    }
+    async fn read_dir(&self, path: &str) -> IoResult<Vec<(String, VfsMetadata)>> {
+        let mut list: Option<Vec<(String, VfsMetadata)>> = None;
+        let mut error = None;
+        for root in &self.roots {
+            match Self::read_root_dir(root, path).await {
+                Ok(entries) => match &mut list {
+                    Some(list) => {
+                        for (name, metadata) in entries {
+                            if !list.iter().any(|(n, _)| *n == name) {
+                                list.push((name, metadata));
+                            }
+                        }
+                    }
+                    None => list = Some(entries),
+                },
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        list.ok_or_else(|| error.unwrap_or_else(|| ErrorKind::NotFound.into()))
+    }
+    async fn open(&self, path: &str) -> IoResult<Self::File> {
+        let mut error = None;
+        for root in &self.roots {
+            match tokio::fs::File::open(root.join(path)).await {
+                Ok(file) => return Ok(file),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| ErrorKind::NotFound.into()))
+    }
+}
+
+/// [`Vfs`] backend serving files kept in memory.
+///
+/// Directories are implied by the paths of the files.
+#[derive(Clone, Debug)]
+pub struct MemoryFs {
+    files: BTreeMap<String, Arc<[u8]>>,
+    modified: SystemTime,
+}
+impl Default for MemoryFs {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            files: BTreeMap::new(),
+            modified: SystemTime::now(),
+        }
+    }
+}
+impl MemoryFs {
+    /// Create a new empty `MemoryFs`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file.
+    #[inline]
+    pub fn file(mut self, path: impl AsRef<str>, data: impl Into<Vec<u8>>) -> Self {
+        self.insert(path, data);
+        self
+    }
+
+    /// Sets the last modified time reported for all files, the creation time of the `MemoryFs` by default.
+    #[inline]
+    pub fn modified(mut self, modified: SystemTime) -> Self {
+        self.modified = modified;
+        self
+    }
+
+    /// Insert a file, replacing the existing one.
+    pub fn insert(&mut self, path: impl AsRef<str>, data: impl Into<Vec<u8>>) {
+        let path = format_url_path_safely(path.as_ref()).trim_end_matches('/').to_owned();
+        self.files.insert(path, data.into().into());
+    }
+
+    fn dir_prefix(path: &str) -> String {
+        if path.is_empty() {
+            String::new()
+        } else {
+            format!("{path}/")
+        }
+    }
+}
+impl Vfs for MemoryFs {
+    type File = Cursor<Arc<[u8]>>;
+
+    async fn metadata(&self, path: &str) -> IoResult<VfsMetadata> {
+        if let Some(data) = self.files.get(path) {
+            return Ok(VfsMetadata::new(false, data.len() as u64, Some(self.modified), None));
+        }
+        let prefix = Self::dir_prefix(path);
+        if path.is_empty()
+            || self
+                .files
+                .range(prefix.clone()..)
+                .next()
+                .is_some_and(|(key, _)| key.starts_with(&prefix))
+        {
+            Ok(VfsMetadata::new(true, 0, Some(self.modified), None))
+        } else {
+            Err(ErrorKind::NotFound.into())
+        }
+    }
+    async fn read_dir(&self, path: &str) -> IoResult<Vec<(String, VfsMetadata)>> {
+        let prefix = Self::dir_prefix(path);
+        let mut entries = BTreeMap::new();
+        for (key, data) in self.files.range(prefix.clone()..) {
+            let Some(rest) = key.strip_prefix(&prefix) else {
+                break;
+            };
+            match rest.split_once('/') {
+                Some((dir, _)) => entries
+                    .entry(dir.to_owned())
+                    .or_insert_with(|| VfsMetadata::new(true, 0, Some(self.modified), None)),
+                None => entries
+                    .entry(rest.to_owned())
+                    .or_insert_with(|| VfsMetadata::new(false, data.len() as u64, Some(self.modified), None)),
+            };
+        }
+        if entries.is_empty() && !path.is_empty() {
+            return Err(ErrorKind::NotFound.into());
+        }
+        Ok(entries.into_iter().collect())
+    }
+    async fn open(&self, path: &str) -> IoResult<Self::File> {
+        self.files
+            .get(path)
+            .map(|data| Cursor::new(data.clone()))
+            .ok_or_else(|| ErrorKind::NotFound.into())
+    }
+}
+
+/// [`Vfs`] backend layering two backends, files of `upper` shadow files of `lower` with the same path.
+#[derive(Clone, Debug)]
+pub struct OverlayFs<U, L> {
+    upper: U,
+    lower: L,
+}
+impl<U, L> OverlayFs<U, L>
+where
+    U: Vfs,
+    L: Vfs,
+{
+    /// Create a new `OverlayFs`.
+    #[inline]
+    pub fn new(upper: U, lower: L) -> Self {
+        Self { upper, lower }
+    }
+}
+impl<U, L> Vfs for OverlayFs<U, L>
+where
+    U: Vfs,
+    L: Vfs,
+{
+    type File = OverlayFile<U::File, L::File>;
+
+    async fn metadata(&self, path: &str) -> IoResult<VfsMetadata> {
+        match self.upper.metadata(path).await {
+            Err(e) if e.kind() == ErrorKind::NotFound => self.lower.metadata(path).await,
+            result => result,
+        }
+    }
+    async fn read_dir(&self, path: &str) -> IoResult<Vec<(String, VfsMetadata)>> {
+        let upper = self.upper.read_dir(path).await;
+        let lower = self.lower.read_dir(path).await;
+        let (mut entries, lower) = match (upper, lower) {
+            (Ok(upper), Ok(lower)) => (upper, lower),
+            (Ok(upper), Err(_)) => return Ok(upper),
+            (Err(_), Ok(lower)) => return Ok(lower),
+            (Err(e), Err(_)) => return Err(e),
+        };
+        for (name, metadata) in lower {
+            if !entries.iter().any(|(n, _)| *n == name) {
+                entries.push((name, metadata));
+            }
+        }
+        Ok(entries)
+    }
+    async fn open(&self, path: &str) -> IoResult<Self::File> {
+        match self.upper.open(path).await {
+            Ok(file) => Ok(OverlayFile::Upper(file)),
+            Err(e) if e.kind() == ErrorKind::NotFound => self.lower.open(path).await.map(OverlayFile::Lower),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// File opened from an [`OverlayFs`].
+#[derive(Debug)]
+pub enum OverlayFile<U, L> {
+    /// File opened from the upper layer.
+    Upper(U),
+    /// File opened from the lower layer.
+    Lower(L),
+}
+impl<U, L> AsyncRead for OverlayFile<U, L>
+where
+    U: AsyncRead + Unpin,
+    L: AsyncRead + Unpin,
+{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<IoResult<()>> {
+        match self.get_mut() {
+            Self::Upper(file) => Pin::new(file).poll_read(cx, buf),
+            Self::Lower(file) => Pin::new(file).poll_read(cx, buf),
+        }
+    }
+}
+impl<U, L> AsyncSeek for OverlayFile<U, L>
+where
+    U: AsyncSeek + Unpin,
+    L: AsyncSeek + Unpin,
+{
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> IoResult<()> {
+        match self.get_mut() {
+            Self::Upper(file) => Pin::new(file).start_seek(position),
+            Self::Lower(file) => Pin::new(file).start_seek(position),
+        }
+    }
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<u64>> {
+        match self.get_mut() {
+            Self::Upper(file) => Pin::new(file).poll_complete(cx),
+            Self::Lower(file) => Pin::new(file).poll_complete(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, ETAG, IF_NONE_MATCH, RANGE};
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+
+    use super::*;
+    use crate::dir::ListFormat;
+    use crate::StaticDir;
+
+    fn service() -> Service {
+        let upper = MemoryFs::new()
+            .file("index.html", "upper index")
+            .file("docs/guide.txt", "guide");
+        let lower = MemoryFs::new()
+            .file("index.html", "lower index")
+            .file("docs/api.txt", "api")
+            .file("app.js", "console.log(1)")
+            .file("app.js.gz", "gzipped")
+            .file(".env", "secret");
+        let handler = StaticDir::with_vfs(OverlayFs::new(upper, lower))
+            .defaults("index.html")
+            .auto_list(true)
+            .list_format(ListFormat::Json);
+        Service::new(Router::with_path("<**path>").get(handler))
+    }
+
+    #[tokio::test]
+    async fn test_memory_fs() {
+        let fs = MemoryFs::new().file("a/b/c.txt", "c").file("a/d.txt", "d");
+        assert!(fs.metadata("a").await.unwrap().is_dir);
+        assert!(fs.metadata("a/b").await.unwrap().is_dir);
+        assert_eq!(fs.metadata("a/d.txt").await.unwrap().len, 1);
+        assert!(fs.metadata("a/x").await.is_err());
+        let names = fs
+            .read_dir("a")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(name, metadata)| (name, metadata.is_dir))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![("b".to_owned(), true), ("d.txt".to_owned(), false)]);
+    }
+
+    #[tokio::test]
+    async fn test_static_vfs() {
+        let service = service();
+
+        let content = TestClient::get("http://127.0.0.1:5801/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert_eq!(content, "upper index");
+
+        let content = TestClient::get("http://127.0.0.1:5801/docs/")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(content.contains("guide.txt") && content.contains("api.txt"));
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/app.js")
+            .add_header(ACCEPT_ENCODING, "gzip", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(res.take_bytes(None).await.unwrap(), "gzipped");
+
+        let res = TestClient::get("http://127.0.0.1:5801/.env").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        let res = TestClient::get("http://127.0.0.1:5801/missing.txt")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_static_vfs_conditional() {
+        let service = service();
+
+        let mut res = TestClient::get("http://127.0.0.1:5801/docs/api.txt")
+            .add_header(RANGE, "bytes=1-", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(res.take_string().await.unwrap(), "pi");
+
+        let etag = res.headers().get(ETAG).unwrap().clone();
+        let res = TestClient::get("http://127.0.0.1:5801/docs/api.txt")
+            .add_header(IF_NONE_MATCH, etag, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_MODIFIED));
+    }
+
+    #[tokio::test]
+    async fn test_static_vfs_authorizer() {
+        #[handler]
+        async fn identify(req: &mut Request, depot: &mut Depot) {
+            if let Some(user) = req.header::<String>("x-user") {
+                depot.insert("user", user);
+            }
+        }
+        let fs = MemoryFs::new()
+            .file("public.txt", "public")
+            .file("private/alice.txt", "alice");
+        let handler = StaticDir::with_vfs(fs).authorizer(|path: &str, _req: &Request, depot: &Depot| {
+            let user = depot.get::<String>("user").map(String::as_str).unwrap_or_default();
+            if path.starts_with("private/") && path != format!("private/{user}.txt") {
+                Err(StatusError::not_found())
+            } else {
+                Ok(())
+            }
+        });
+        let service = Service::new(Router::with_path("<**path>").hoop(identify).get(handler));
+
+        let res = TestClient::get("http://127.0.0.1:5801/public.txt").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        let res = TestClient::get("http://127.0.0.1:5801/private/alice.txt")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        let res = TestClient::get("http://127.0.0.1:5801/private/alice.txt")
+            .add_header("x-user", "bob", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        let mut res = TestClient::get("http://127.0.0.1:5801/private/alice.txt")
+            .add_header("x-user", "alice", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.take_string().await.unwrap(), "alice");
+    }
+}
@@ -0,0 +1,792 @@
+//! WebDAV handler.
+//!
+//! [`WebDav`] implements the subset of [RFC 4918](https://www.rfc-editor.org/rfc/rfc4918) used by the WebDAV
+//! clients built into common operating systems: `OPTIONS`, `GET`, `HEAD`, `PUT`, `DELETE`, `MKCOL`, `COPY`, `MOVE`,
+//! `PROPFIND`, `LOCK` and `UNLOCK`. Dead properties and `PROPPATCH` are not supported, locks are kept in memory.
+//!
+//! Files are stored by a [`DavFs`] backend, [`LocalFs`] stores them in a local directory. Authentication is left to
+//! the usual middlewares, such as `BasicAuth`, added as hoops of the router.
+//!
+//! # Example
+//!
+//! ```
+//! use salvo_core::prelude::*;
+//! use salvo_serve_static::webdav::{LocalFs, WebDav};
+//!
+//! let router = Router::with_path("dav/<**path>").goal(WebDav::new(LocalFs::new("/srv/files")));
+//! ```
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::io::{ErrorKind, Result as IoResult};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::StreamExt;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use salvo_core::http::header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+use salvo_core::http::{Request, Response, StatusCode};
+use salvo_core::writing::ReadSeeker;
+use salvo_core::{async_trait, Depot, FlowCtrl, Handler};
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::OffsetDateTime;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+use super::{decode_url_path_safely, format_url_path_safely};
+pub use crate::vfs::{LocalFs, Vfs, VfsMetadata};
+
+const HREF_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b']')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+const DAV_METHODS: &str = "OPTIONS, GET, HEAD, PUT, DELETE, MKCOL, COPY, MOVE, PROPFIND, LOCK, UNLOCK";
+
+/// Storage backend of [`WebDav`], a [`Vfs`] which can also be modified.
+pub trait DavFs: Vfs {
+    /// File opened for writing.
+    type Writer: AsyncWrite + Unpin + Send + 'static;
+
+    /// Create or truncate a file for writing.
+    fn create(&self, path: &str) -> impl Future<Output = IoResult<Self::Writer>> + Send;
+    /// Create a collection, its parent must exist.
+    fn create_dir(&self, path: &str) -> impl Future<Output = IoResult<()>> + Send;
+    /// Remove a file.
+    fn remove_file(&self, path: &str) -> impl Future<Output = IoResult<()>> + Send;
+    /// Remove a collection and all its members.
+    fn remove_dir(&self, path: &str) -> impl Future<Output = IoResult<()>> + Send;
+    /// Move a resource.
+    fn rename(&self, from: &str, to: &str) -> impl Future<Output = IoResult<()>> + Send;
+    /// Copy a file.
+    fn copy_file(&self, from: &str, to: &str) -> impl Future<Output = IoResult<()>> + Send;
+}
+
+impl DavFs for LocalFs {
+    type Writer = tokio::fs::File;
+
+    async fn create(&self, path: &str) -> IoResult<Self::Writer> {
+        tokio::fs::File::create(self.path(path)).await
+    }
+    async fn create_dir(&self, path: &str) -> IoResult<()> {
+        tokio::fs::create_dir(self.path(path)).await
+    }
+    async fn remove_file(&self, path: &str) -> IoResult<()> {
+        tokio::fs::remove_file(self.path(path)).await
+    }
+    async fn remove_dir(&self, path: &str) -> IoResult<()> {
+        tokio::fs::remove_dir_all(self.path(path)).await
+    }
+    async fn rename(&self, from: &str, to: &str) -> IoResult<()> {
+        tokio::fs::rename(self.path(from), self.path(to)).await
+    }
+    async fn copy_file(&self, from: &str, to: &str) -> IoResult<()> {
+        tokio::fs::copy(self.path(from), self.path(to)).await.map(|_| ())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct DavLock {
+    token: String,
+    path: String,
+    infinite: bool,
+    timeout: Duration,
+    expires: SystemTime,
+}
+impl DavLock {
+    fn covers(&self, path: &str) -> bool {
+        self.path == path || (self.infinite && is_descendant(path, &self.path))
+    }
+}
+
+/// WebDAV handler.
+///
+/// View [module level documentation](index.html) for more details.
+pub struct WebDav<F> {
+    fs: F,
+    read_only: bool,
+    lock_timeout: Duration,
+    locks: Mutex<HashMap<String, DavLock>>,
+}
+
+impl<F> WebDav<F>
+where
+    F: DavFs,
+{
+    /// Create a new `WebDav`.
+    #[inline]
+    pub fn new(fs: F) -> Self {
+        Self {
+            fs,
+            read_only: false,
+            lock_timeout: Duration::from_secs(3600),
+            locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reject all methods which modify resources with `403 Forbidden`.
+    #[inline]
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Sets the maximum lock timeout, the default is one hour.
+    #[inline]
+    pub fn lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// Get the storage backend.
+    #[inline]
+    pub fn fs(&self) -> &F {
+        &self.fs
+    }
+
+    /// Returns `true` if `path` is locked by a lock whose token is not submitted in the `If` header.
+    async fn is_locked(&self, req: &Request, path: &str, with_members: bool) -> bool {
+        let now = SystemTime::now();
+        let mut locks = self.locks.lock().await;
+        locks.retain(|_, lock| lock.expires > now);
+        let submitted = req
+            .headers()
+            .get("if")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        locks.values().any(|lock| {
+            (lock.covers(path) || (with_members && is_descendant(&lock.path, path))) && !submitted.contains(&lock.token)
+        })
+    }
+
+    async fn remove_locks(&self, path: &str) {
+        self.locks
+            .lock()
+            .await
+            .retain(|_, lock| lock.path != path && !is_descendant(&lock.path, path));
+    }
+
+    async fn handle_get(&self, req: &Request, res: &mut Response, path: &str) {
+        let metadata = match self.fs.metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(e) => return render_io_error(res, e.kind()),
+        };
+        if metadata.is_dir {
+            res.status_code(StatusCode::METHOD_NOT_ALLOWED);
+            return;
+        }
+        let file = match self.fs.open(path).await {
+            Ok(file) => file,
+            Err(e) => return render_io_error(res, e.kind()),
+        };
+        let mime = mime_infer::from_path(path).first_or_octet_stream();
+        if let Ok(value) = HeaderValue::from_str(mime.as_ref()) {
+            res.headers_mut().insert(CONTENT_TYPE, value);
+        }
+        let mut seeker = ReadSeeker::new(file, metadata.len);
+        if let Some(modified) = metadata.modified {
+            seeker = seeker.last_modified(modified);
+        }
+        seeker.send(req.headers(), res).await;
+    }
+
+    async fn handle_put(&self, req: &mut Request, res: &mut Response, path: &str) {
+        if path.is_empty() {
+            res.status_code(StatusCode::METHOD_NOT_ALLOWED);
+            return;
+        }
+        let existed = match self.fs.metadata(path).await {
+            Ok(metadata) if metadata.is_dir => {
+                res.status_code(StatusCode::METHOD_NOT_ALLOWED);
+                return;
+            }
+            Ok(_) => true,
+            Err(_) => false,
+        };
+        if !self.parent_exists(path).await {
+            res.status_code(StatusCode::CONFLICT);
+            return;
+        }
+        let mut writer = match self.fs.create(path).await {
+            Ok(writer) => writer,
+            Err(e) => return render_io_error(res, e.kind()),
+        };
+        let mut body = req.take_body();
+        while let Some(frame) = body.next().await {
+            let result = match frame {
+                Ok(frame) => match frame.into_data() {
+                    Ok(data) => writer.write_all(&data).await,
+                    Err(_) => Ok(()),
+                },
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::error!(error = ?e, path, "write webdav file failed");
+                res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+                return;
+            }
+        }
+        if let Err(e) = writer.shutdown().await {
+            tracing::error!(error = ?e, path, "close webdav file failed");
+            res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+            return;
+        }
+        res.status_code(if existed {
+            StatusCode::NO_CONTENT
+        } else {
+            StatusCode::CREATED
+        });
+    }
+
+    async fn handle_delete(&self, res: &mut Response, path: &str) {
+        if path.is_empty() {
+            res.status_code(StatusCode::FORBIDDEN);
+            return;
+        }
+        let result = match self.fs.metadata(path).await {
+            Ok(metadata) if metadata.is_dir => self.fs.remove_dir(path).await,
+            Ok(_) => self.fs.remove_file(path).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => {
+                self.remove_locks(path).await;
+                res.status_code(StatusCode::NO_CONTENT);
+            }
+            Err(e) => render_io_error(res, e.kind()),
+        }
+    }
+
+    async fn handle_mkcol(&self, req: &Request, res: &mut Response, path: &str) {
+        let has_body = req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v != "0")
+            .unwrap_or(false);
+        if has_body {
+            res.status_code(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            return;
+        }
+        if path.is_empty() || self.fs.metadata(path).await.is_ok() {
+            res.status_code(StatusCode::METHOD_NOT_ALLOWED);
+            return;
+        }
+        if !self.parent_exists(path).await {
+            res.status_code(StatusCode::CONFLICT);
+            return;
+        }
+        match self.fs.create_dir(path).await {
+            Ok(()) => {
+                res.status_code(StatusCode::CREATED);
+            }
+            Err(e) => render_io_error(res, e.kind()),
+        }
+    }
+
+    async fn handle_copy_move(&self, req: &Request, res: &mut Response, path: &str, prefix: &str, is_move: bool) {
+        let Some(destination) = destination_path(req, prefix) else {
+            res.status_code(StatusCode::BAD_REQUEST);
+            return;
+        };
+        if path.is_empty() || destination.is_empty() || destination == path || is_descendant(&destination, path) {
+            res.status_code(StatusCode::FORBIDDEN);
+            return;
+        }
+        if self.is_locked(req, &destination, true).await {
+            res.status_code(StatusCode::LOCKED);
+            return;
+        }
+        let source = match self.fs.metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(e) => return render_io_error(res, e.kind()),
+        };
+        let overwrite = req
+            .headers()
+            .get("overwrite")
+            .map(|v| !v.as_bytes().eq_ignore_ascii_case(b"F"))
+            .unwrap_or(true);
+        let existed = match self.fs.metadata(&destination).await {
+            Ok(_) if !overwrite => {
+                res.status_code(StatusCode::PRECONDITION_FAILED);
+                return;
+            }
+            Ok(metadata) => {
+                let result = if metadata.is_dir {
+                    self.fs.remove_dir(&destination).await
+                } else {
+                    self.fs.remove_file(&destination).await
+                };
+                if let Err(e) = result {
+                    return render_io_error(res, e.kind());
+                }
+                true
+            }
+            Err(_) => false,
+        };
+        if !self.parent_exists(&destination).await {
+            res.status_code(StatusCode::CONFLICT);
+            return;
+        }
+        let result = if is_move {
+            let result = self.fs.rename(path, &destination).await;
+            if result.is_ok() {
+                self.remove_locks(path).await;
+            }
+            result
+        } else if source.is_dir {
+            let shallow = req.headers().get("depth").map(|v| v == "0").unwrap_or(false);
+            self.copy_dir(path, &destination, shallow).await
+        } else {
+            self.fs.copy_file(path, &destination).await
+        };
+        match result {
+            Ok(()) => {
+                res.status_code(if existed {
+                    StatusCode::NO_CONTENT
+                } else {
+                    StatusCode::CREATED
+                });
+            }
+            Err(e) => render_io_error(res, e.kind()),
+        }
+    }
+
+    async fn copy_dir(&self, from: &str, to: &str, shallow: bool) -> IoResult<()> {
+        let mut pending = vec![(from.to_owned(), to.to_owned())];
+        while let Some((from, to)) = pending.pop() {
+            self.fs.create_dir(&to).await?;
+            if shallow {
+                break;
+            }
+            for (name, metadata) in self.fs.read_dir(&from).await? {
+                let (child_from, child_to) = (format!("{from}/{name}"), format!("{to}/{name}"));
+                if metadata.is_dir {
+                    pending.push((child_from, child_to));
+                } else {
+                    self.fs.copy_file(&child_from, &child_to).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_propfind(&self, req: &Request, res: &mut Response, path: &str) {
+        let metadata = match self.fs.metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(e) => return render_io_error(res, e.kind()),
+        };
+        let depth0 = req.headers().get("depth").map(|v| v == "0").unwrap_or(false);
+        let mut href = req.uri().path().to_owned();
+        if metadata.is_dir && !href.ends_with('/') {
+            href.push('/');
+        }
+        let name = path.rsplit('/').next().unwrap_or_default();
+        let mut xml = r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#.to_owned();
+        write_response(&mut xml, &href, name, &metadata);
+        if metadata.is_dir && !depth0 {
+            let mut entries = match self.fs.read_dir(path).await {
+                Ok(entries) => entries,
+                Err(e) => return render_io_error(res, e.kind()),
+            };
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            for (name, metadata) in entries {
+                let mut child_href = format!("{href}{}", utf8_percent_encode(&name, HREF_ENCODE_SET));
+                if metadata.is_dir {
+                    child_href.push('/');
+                }
+                write_response(&mut xml, &child_href, &name, &metadata);
+            }
+        }
+        xml.push_str("</D:multistatus>");
+        render_xml(res, StatusCode::MULTI_STATUS, xml);
+    }
+
+    async fn handle_lock(&self, req: &mut Request, res: &mut Response, path: &str) {
+        let timeout = req
+            .headers()
+            .get("timeout")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| {
+                v.split(',')
+                    .find_map(|t| t.trim().strip_prefix("Second-")?.parse::<u64>().ok())
+            })
+            .map(|secs| Duration::from_secs(secs).min(self.lock_timeout))
+            .unwrap_or(self.lock_timeout);
+        let body = req.payload().await.map(|b| b.to_vec()).unwrap_or_default();
+        let now = SystemTime::now();
+        let mut locks = self.locks.lock().await;
+        locks.retain(|_, lock| lock.expires > now);
+
+        if body.is_empty() {
+            // refresh an existing lock
+            let submitted = req
+                .headers()
+                .get("if")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            let Some(lock) = locks
+                .values_mut()
+                .find(|lock| lock.covers(path) && submitted.contains(&lock.token))
+            else {
+                res.status_code(StatusCode::PRECONDITION_FAILED);
+                return;
+            };
+            lock.timeout = timeout;
+            lock.expires = now + timeout;
+            let xml = lock_discovery(lock, req.uri().path());
+            render_xml(res, StatusCode::OK, xml);
+            return;
+        }
+
+        let infinite = req.headers().get("depth").map(|v| v != "0").unwrap_or(true);
+        if locks
+            .values()
+            .any(|lock| lock.covers(path) || (infinite && is_descendant(&lock.path, path)))
+        {
+            res.status_code(StatusCode::LOCKED);
+            return;
+        }
+        let mut status = StatusCode::OK;
+        if self.fs.metadata(path).await.is_err() {
+            if path.is_empty() || !self.parent_exists(path).await {
+                res.status_code(StatusCode::CONFLICT);
+                return;
+            }
+            let created = match self.fs.create(path).await {
+                Ok(mut writer) => writer.shutdown().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = created {
+                return render_io_error(res, e.kind());
+            }
+            status = StatusCode::CREATED;
+        }
+        let lock = DavLock {
+            token: format!("opaquelocktoken:{}", new_token()),
+            path: path.to_owned(),
+            infinite,
+            timeout,
+            expires: now + timeout,
+        };
+        if let Ok(value) = HeaderValue::from_str(&format!("<{}>", lock.token)) {
+            res.headers_mut().insert("lock-token", value);
+        }
+        let xml = lock_discovery(&lock, req.uri().path());
+        locks.insert(lock.token.clone(), lock);
+        render_xml(res, status, xml);
+    }
+
+    async fn handle_unlock(&self, req: &Request, res: &mut Response, path: &str) {
+        let token = req
+            .headers()
+            .get("lock-token")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().trim_start_matches('<').trim_end_matches('>').to_owned());
+        let mut locks = self.locks.lock().await;
+        match token {
+            Some(token) if locks.get(&token).map(|lock| lock.covers(path)).unwrap_or(false) => {
+                locks.remove(&token);
+                res.status_code(StatusCode::NO_CONTENT);
+            }
+            _ => {
+                res.status_code(StatusCode::CONFLICT);
+            }
+        }
+    }
+
+    async fn parent_exists(&self, path: &str) -> bool {
+        match path.rsplit_once('/') {
+            Some((parent, _)) => self.fs.metadata(parent).await.map(|m| m.is_dir).unwrap_or(false),
+            None => true,
+        }
+    }
+}
+
+#[async_trait]
+impl<F> Handler for WebDav<F>
+where
+    F: DavFs,
+{
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+        let param = req
+            .params()
+            .iter()
+            .find(|(key, _)| key.starts_with('*'))
+            .map(|(_, value)| value.clone());
+        let req_path = decode_url_path_safely(req.uri().path());
+        let (rel_path, prefix) = match param {
+            Some(value) => {
+                let prefix = req_path
+                    .trim_end_matches('/')
+                    .strip_suffix(value.trim_end_matches('/'))
+                    .unwrap_or_default();
+                let prefix = format!("{}/", prefix.trim_end_matches('/'));
+                (value, prefix)
+            }
+            None => (req_path, "/".to_owned()),
+        };
+        let path = format_url_path_safely(&rel_path);
+        let path = path.trim_end_matches('/');
+        ctrl.skip_rest();
+
+        let method = req.method().as_str().to_owned();
+        let is_write = !matches!(method.as_str(), "OPTIONS" | "GET" | "HEAD" | "PROPFIND");
+        if is_write && self.read_only {
+            res.status_code(StatusCode::FORBIDDEN);
+            return;
+        }
+        let with_members = matches!(method.as_str(), "DELETE" | "MOVE");
+        if is_write
+            && !matches!(method.as_str(), "LOCK" | "UNLOCK" | "COPY")
+            && self.is_locked(req, path, with_members).await
+        {
+            res.status_code(StatusCode::LOCKED);
+            return;
+        }
+        match method.as_str() {
+            "OPTIONS" => {
+                res.headers_mut().insert("dav", HeaderValue::from_static("1, 2"));
+                res.headers_mut().insert("allow", HeaderValue::from_static(DAV_METHODS));
+                res.headers_mut()
+                    .insert("ms-author-via", HeaderValue::from_static("DAV"));
+                res.status_code(StatusCode::OK);
+            }
+            "GET" | "HEAD" => self.handle_get(req, res, path).await,
+            "PUT" => self.handle_put(req, res, path).await,
+            "DELETE" => self.handle_delete(res, path).await,
+            "MKCOL" => self.handle_mkcol(req, res, path).await,
+            "COPY" => self.handle_copy_move(req, res, path, &prefix, false).await,
+            "MOVE" => self.handle_copy_move(req, res, path, &prefix, true).await,
+            "PROPFIND" => self.handle_propfind(req, res, path).await,
+            "LOCK" => self.handle_lock(req, res, path).await,
+            "UNLOCK" => self.handle_unlock(req, res, path).await,
+            _ => {
+                res.headers_mut().insert("allow", HeaderValue::from_static(DAV_METHODS));
+                res.status_code(StatusCode::METHOD_NOT_ALLOWED);
+            }
+        }
+    }
+}
+
+/// Returns `true` if `path` is a member of the collection `parent`, at any depth.
+fn is_descendant(path: &str, parent: &str) -> bool {
+    if parent.is_empty() {
+        return !path.is_empty();
+    }
+    path.strip_prefix(parent)
+        .map(|rest| rest.starts_with('/'))
+        .unwrap_or(false)
+}
+
+/// Get the relative path of the `Destination` header, `None` if it is missing or outside the handler's prefix.
+///
+/// `prefix` is the request path of the handler's root, ending with `/`.
+fn destination_path(req: &Request, prefix: &str) -> Option<String> {
+    let destination = req.headers().get("destination")?.to_str().ok()?;
+    let uri = destination.parse::<salvo_core::http::uri::Uri>().ok()?;
+    let dest_path = decode_url_path_safely(uri.path());
+    if dest_path == prefix.trim_end_matches('/') {
+        return Some(String::new());
+    }
+    let rel_path = dest_path.strip_prefix(prefix)?;
+    Some(format_url_path_safely(rel_path).trim_end_matches('/').to_owned())
+}
+
+fn render_io_error(res: &mut Response, kind: ErrorKind) {
+    res.status_code(match kind {
+        ErrorKind::NotFound => StatusCode::NOT_FOUND,
+        ErrorKind::PermissionDenied => StatusCode::FORBIDDEN,
+        ErrorKind::AlreadyExists => StatusCode::METHOD_NOT_ALLOWED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    });
+}
+
+fn render_xml(res: &mut Response, status: StatusCode, xml: String) {
+    res.status_code(status);
+    res.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/xml; charset=utf-8"));
+    res.write_body(xml).ok();
+}
+
+fn write_response(xml: &mut String, href: &str, name: &str, metadata: &VfsMetadata) {
+    write!(
+        xml,
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop><D:displayname>{}</D:displayname>",
+        escape_xml(href),
+        escape_xml(name)
+    )
+    .ok();
+    if metadata.is_dir {
+        xml.push_str("<D:resourcetype><D:collection/></D:resourcetype>");
+    } else {
+        let mime = mime_infer::from_path(name).first_or_octet_stream();
+        write!(
+            xml,
+            "<D:resourcetype/><D:getcontentlength>{}</D:getcontentlength><D:getcontenttype>{}</D:getcontenttype>",
+            metadata.len,
+            escape_xml(mime.as_ref())
+        )
+        .ok();
+    }
+    if let Some(modified) = metadata.modified {
+        let format =
+            format_description!("[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT");
+        if let Ok(modified) = OffsetDateTime::from(modified).format(&format) {
+            write!(xml, "<D:getlastmodified>{modified}</D:getlastmodified>").ok();
+        }
+    }
+    if let Some(created) = metadata.created {
+        if let Ok(created) = OffsetDateTime::from(created).format(&Rfc3339) {
+            write!(xml, "<D:creationdate>{created}</D:creationdate>").ok();
+        }
+    }
+    let modified_secs = metadata
+        .modified
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    write!(xml, "<D:getetag>\"{:x}-{:x}\"</D:getetag>", metadata.len, modified_secs).ok();
+    xml.push_str(
+        "<D:supportedlock><D:lockentry><D:lockscope><D:exclusive/></D:lockscope><D:locktype><D:write/></D:locktype>\
+         </D:lockentry></D:supportedlock>",
+    );
+    xml.push_str("</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>");
+}
+
+fn lock_discovery(lock: &DavLock, href: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><D:prop xmlns:D="DAV:"><D:lockdiscovery><D:activelock><D:locktype><D:write/></D:locktype><D:lockscope><D:exclusive/></D:lockscope><D:depth>{}</D:depth><D:timeout>Second-{}</D:timeout><D:locktoken><D:href>{}</D:href></D:locktoken><D:lockroot><D:href>{}</D:href></D:lockroot></D:activelock></D:lockdiscovery></D:prop>"#,
+        if lock.infinite { "infinity" } else { "0" },
+        lock.timeout.as_secs(),
+        escape_xml(&lock.token),
+        escape_xml(href)
+    )
+}
+
+fn new_token() -> String {
+    let bytes = (0..4).map(|_| fastrand::u32(..)).collect::<Vec<_>>();
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:04x}{:08x}",
+        bytes[0],
+        bytes[1] >> 16,
+        bytes[1] & 0xffff,
+        bytes[2] >> 16,
+        bytes[2] & 0xffff,
+        bytes[3]
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use salvo_core::http::Method;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{RequestBuilder, ResponseExt};
+
+    use super::*;
+
+    fn request(method: &str, path: &str) -> RequestBuilder {
+        RequestBuilder::new(
+            format!("http://127.0.0.1:5801/dav/{path}"),
+            Method::from_bytes(method.as_bytes()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_webdav() {
+        let root = std::env::temp_dir().join(format!("salvo-webdav-{}", fastrand::u64(..)));
+        std::fs::create_dir_all(&root).unwrap();
+        let router = Router::with_path("dav/<**path>").goal(WebDav::new(LocalFs::new(&root)));
+        let service = Service::new(router);
+
+        let res = request("MKCOL", "docs").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        let res = request("PUT", "docs/a.txt").text("hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        let res = request("PUT", "missing/a.txt").text("hello").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::CONFLICT));
+
+        let mut res = request("PROPFIND", "docs")
+            .add_header("depth", "1", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::MULTI_STATUS));
+        let xml = res.take_string().await.unwrap();
+        assert!(xml.contains("<D:href>/dav/docs/</D:href>"));
+        assert!(xml.contains("<D:href>/dav/docs/a.txt</D:href>"));
+        assert!(xml.contains("<D:getcontentlength>5</D:getcontentlength>"));
+
+        let res = request("COPY", "docs")
+            .add_header("destination", "http://127.0.0.1:5801/dav/backup", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        let res = request("MOVE", "docs/a.txt")
+            .add_header("destination", "http://127.0.0.1:5801/dav/backup/a.txt", true)
+            .add_header("overwrite", "F", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::PRECONDITION_FAILED));
+        let res = request("MOVE", "docs/a.txt")
+            .add_header("destination", "http://127.0.0.1:5801/dav/b.txt", true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::CREATED));
+        let mut res = request("GET", "b.txt").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+        let mut res = request("GET", "backup/a.txt").send(&service).await;
+        assert_eq!(res.take_string().await.unwrap(), "hello");
+
+        let res = request("LOCK", "b.txt")
+            .text(r#"<?xml version="1.0"?><D:lockinfo xmlns:D="DAV:"><D:lockscope><D:exclusive/></D:lockscope><D:locktype><D:write/></D:locktype></D:lockinfo>"#)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::OK));
+        let token = res.headers().get("lock-token").unwrap().to_str().unwrap().to_owned();
+        let res = request("PUT", "b.txt").text("world").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::LOCKED));
+        let res = request("DELETE", "").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::LOCKED));
+        let res = request("PUT", "b.txt")
+            .add_header("if", format!("({token})"), true)
+            .text("world")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        let res = request("UNLOCK", "b.txt")
+            .add_header("lock-token", token, true)
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+
+        let res = request("DELETE", "backup").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NO_CONTENT));
+        let res = request("PROPFIND", "backup").send(&service).await;
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
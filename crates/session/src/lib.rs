@@ -49,6 +49,11 @@ adversary were able to tamper with the expiry of a cookie, salvo
 sessions would still check the expiry on the contained session before
 using it
 
+### Header transport
+
+Mobile and API clients which do not keep cookies can send the signed session ID in a header instead, see
+[`IdTransport`]. The header is signed and verified the same way as the cookie.
+
 ### If anything goes wrong with the above process
 
 If there are any failures in the above session retrieval process, a
@@ -78,13 +83,40 @@ use async_session::base64;
 use async_session::hmac::{Hmac, Mac, NewMac};
 use async_session::sha2::Sha256;
 use cookie::{Cookie, Key, SameSite};
+use salvo_core::http::header::{HeaderName, HeaderValue};
 use salvo_core::http::uri::Scheme;
+use salvo_core::secret::SecretProvider;
 use salvo_core::{async_trait, Depot, Error, FlowCtrl, Handler, Request, Response};
 
 /// Key for store data in depot.
 pub const SESSION_KEY: &str = "::salvo::session";
 const BASE64_DIGEST_LEN: usize = 44;
 
+/// How the session ID is transported between clients and the server.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum IdTransport {
+    /// In a cookie, for web browsers.
+    #[default]
+    Cookie,
+    /// In a header, for mobile and API clients.
+    Header,
+    /// In both, the cookie takes precedence when a request sends both.
+    CookieThenHeader,
+    /// In both, the header takes precedence when a request sends both.
+    HeaderThenCookie,
+}
+impl IdTransport {
+    #[inline]
+    fn uses_cookie(self) -> bool {
+        self != Self::Header
+    }
+    #[inline]
+    fn uses_header(self) -> bool {
+        self != Self::Cookie
+    }
+}
+
 /// Trait for `Depot` to get and set session.
 pub trait SessionDepotExt {
     /// Sets session
@@ -123,6 +155,8 @@ pub struct HandlerBuilder<S> {
     cookie_path: String,
     cookie_name: String,
     cookie_domain: Option<String>,
+    header_name: String,
+    id_transport: IdTransport,
     session_ttl: Option<Duration>,
     save_unchanged: bool,
     same_site_policy: SameSite,
@@ -136,6 +170,8 @@ impl<S: SessionStore> fmt::Debug for HandlerBuilder<S> {
             .field("cookie_path", &self.cookie_path)
             .field("cookie_name", &self.cookie_name)
             .field("cookie_domain", &self.cookie_domain)
+            .field("header_name", &self.header_name)
+            .field("id_transport", &self.id_transport)
             .field("session_ttl", &self.session_ttl)
             .field("same_site_policy", &self.same_site_policy)
             .field("key", &"..")
@@ -158,6 +194,8 @@ where
             cookie_path: "/".into(),
             cookie_name: "salvo.session.id".into(),
             cookie_domain: None,
+            header_name: "x-session-id".into(),
+            id_transport: IdTransport::Cookie,
             same_site_policy: SameSite::Lax,
             session_ttl: Some(Duration::from_secs(24 * 60 * 60)),
             key: Key::from(secret),
@@ -165,6 +203,23 @@ where
         }
     }
 
+    /// Create new `HandlerBuilder` with the key loaded from a secret provider, the secret must be at least 64 bytes.
+    ///
+    /// `fallbacks` are the names of keys which have been rotated out. Sessions signed by them are still accepted, so
+    /// rotating the key does not drop the sessions of users.
+    pub async fn from_secrets<P>(store: S, provider: &P, name: &str, fallbacks: &[&str]) -> Result<Self, Error>
+    where
+        P: SecretProvider + ?Sized,
+    {
+        let secret = provider.load(name).await?;
+        secret.cookie_key()?;
+        let mut builder = Self::new(store, secret.expose());
+        for fallback in fallbacks {
+            builder = builder.add_fallback_key(provider.load(fallback).await?.cookie_key()?);
+        }
+        Ok(builder)
+    }
+
     /// Sets a cookie path for this session middleware.
     ///
     /// The default for this value is "/".
@@ -227,6 +282,27 @@ where
         self.cookie_domain = Some(cookie_domain.as_ref().to_owned());
         self
     }
+
+    /// Sets the name of the header that the session ID is sent in, when the [`IdTransport`] uses a header.
+    ///
+    /// The default value is "x-session-id". The server sends the header when the session ID changes, such as for
+    /// a new session or after [`Session::regenerate`], clients should send it back with the following requests.
+    #[inline]
+    pub fn header_name(mut self, header_name: impl Into<String>) -> Self {
+        self.header_name = header_name.into();
+        self
+    }
+
+    /// Sets how the session ID is transported, the default is [`IdTransport::Cookie`].
+    ///
+    /// When both a cookie and a header are used, a new session ID is sent in both, and the precedence decides which
+    /// one is used when a request sends both.
+    #[inline]
+    pub fn id_transport(mut self, id_transport: IdTransport) -> Self {
+        self.id_transport = id_transport;
+        self
+    }
+
     /// Sets fallbacks.
     #[inline]
     pub fn fallback_keys(mut self, keys: Vec<impl Into<Key>>) -> Self {
@@ -249,11 +325,15 @@ where
             cookie_path,
             cookie_name,
             cookie_domain,
+            header_name,
+            id_transport,
             session_ttl,
             same_site_policy,
             key,
             fallback_keys,
         } = self;
+        let header_name =
+            HeaderName::from_bytes(header_name.as_bytes()).map_err(|_| Error::Other("invalid header name".into()))?;
         let hmac =
             Hmac::<Sha256>::new_from_slice(key.signing()).map_err(|_| Error::Other("invalid key length".into()))?;
         let fallback_hmacs = fallback_keys
@@ -267,6 +347,8 @@ where
             cookie_path,
             cookie_name,
             cookie_domain,
+            header_name,
+            id_transport,
             session_ttl,
             same_site_policy,
             hmac,
@@ -281,6 +363,8 @@ pub struct SessionHandler<S> {
     cookie_path: String,
     cookie_name: String,
     cookie_domain: Option<String>,
+    header_name: HeaderName,
+    id_transport: IdTransport,
     session_ttl: Option<Duration>,
     save_unchanged: bool,
     same_site_policy: SameSite,
@@ -295,6 +379,8 @@ impl<S: SessionStore> fmt::Debug for SessionHandler<S> {
             .field("cookie_path", &self.cookie_path)
             .field("cookie_name", &self.cookie_name)
             .field("cookie_domain", &self.cookie_domain)
+            .field("header_name", &self.header_name)
+            .field("id_transport", &self.id_transport)
             .field("session_ttl", &self.session_ttl)
             .field("same_site_policy", &self.same_site_policy)
             .field("key", &"..")
@@ -309,10 +395,27 @@ where
     S: SessionStore,
 {
     async fn handle(&self, req: &mut Request, depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
-        let cookie = req.cookies().get(&self.cookie_name);
-        let cookie_value = cookie.and_then(|cookie| self.verify_signature(cookie.value()).ok());
+        let cookie_value = || {
+            req.cookies()
+                .get(&self.cookie_name)
+                .filter(|_| self.id_transport.uses_cookie())
+                .and_then(|cookie| self.verify_signature(cookie.value()).ok())
+        };
+        let header_value = || {
+            req.headers()
+                .get(&self.header_name)
+                .filter(|_| self.id_transport.uses_header())
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| self.verify_signature(value).ok())
+        };
+        let id_value = match self.id_transport {
+            IdTransport::HeaderThenCookie => header_value().or_else(cookie_value),
+            _ => cookie_value().or_else(header_value),
+        };
 
-        let mut session = self.load_or_create(cookie_value).await;
+        // Kept to destroy the stored session if the handlers rotate its ID.
+        let loaded = self.load(id_value).await;
+        let mut session = loaded.clone().unwrap_or_default();
 
         if let Some(ttl) = self.session_ttl {
             session.expire_in(ttl);
@@ -330,18 +433,38 @@ where
             if let Err(e) = self.store.destroy_session(session).await {
                 tracing::error!(error = ?e, "unable to destroy session");
             }
-            res.remove_cookie(&self.cookie_name);
-        } else if self.save_unchanged || session.data_changed() {
-            match self.store.store_session(session).await {
-                Ok(cookie_value) => {
-                    if let Some(cookie_value) = cookie_value {
-                        let secure_cookie = req.uri().scheme() == Some(&Scheme::HTTPS);
-                        let cookie = self.build_cookie(secure_cookie, cookie_value);
-                        res.add_cookie(cookie);
-                    }
+            if self.id_transport.uses_cookie() {
+                res.remove_cookie(&self.cookie_name);
+            }
+        } else {
+            let rotated = loaded.filter(|loaded| loaded.id() != session.id());
+            let is_rotated = rotated.is_some();
+            if let Some(rotated) = rotated {
+                if let Err(e) = self.store.destroy_session(rotated).await {
+                    tracing::error!(error = ?e, "unable to destroy rotated session");
                 }
-                Err(e) => {
-                    tracing::error!(error = ?e, "store session error");
+            }
+            if self.save_unchanged || is_rotated || session.data_changed() {
+                match self.store.store_session(session).await {
+                    Ok(Some(id_value)) => {
+                        if self.id_transport.uses_header() {
+                            match HeaderValue::from_str(&self.sign(&id_value)) {
+                                Ok(value) => {
+                                    res.headers_mut().insert(self.header_name.clone(), value);
+                                }
+                                Err(e) => tracing::error!(error = ?e, "invalid session header value"),
+                            }
+                        }
+                        if self.id_transport.uses_cookie() {
+                            let secure_cookie = req.uri().scheme() == Some(&Scheme::HTTPS);
+                            let cookie = self.build_cookie(secure_cookie, id_value);
+                            res.add_cookie(cookie);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::error!(error = ?e, "store session error");
+                    }
                 }
             }
         }
@@ -357,13 +480,13 @@ where
         HandlerBuilder::new(store, secret)
     }
     #[inline]
-    async fn load_or_create(&self, cookie_value: Option<String>) -> Session {
-        let session = match cookie_value {
-            Some(cookie_value) => self.store.load_session(cookie_value).await.ok().flatten(),
+    async fn load(&self, id_value: Option<String>) -> Option<Session> {
+        let session = match id_value {
+            Some(id_value) => self.store.load_session(id_value).await.ok().flatten(),
             None => None,
         };
 
-        session.and_then(|session| session.validate()).unwrap_or_default()
+        session.and_then(|session| session.validate())
     }
     // the following is reused verbatim from
     // https://github.com/SergioBenitez/cookie-rs/blob/master/src/secure/signed.rs#L51-L66
@@ -418,14 +541,18 @@ where
     // https://github.com/SergioBenitez/cookie-rs/blob/master/src/secure/signed.rs#L37-46
     /// signs the cookie's value providing integrity and authenticity.
     fn sign_cookie(&self, cookie: &mut Cookie<'_>) {
-        // Compute HMAC-SHA256 of the cookie's value.
+        let new_value = self.sign(cookie.value());
+        cookie.set_value(new_value);
+    }
+    /// Signs a value, the signed value is [MAC | original-value].
+    fn sign(&self, value: &str) -> String {
+        // Compute HMAC-SHA256 of the value.
         let mut mac = self.hmac.clone();
-        mac.update(cookie.value().as_bytes());
+        mac.update(value.as_bytes());
 
-        // Cookie's new value is [MAC | original-value].
         let mut new_value = base64::encode(mac.finalize().into_bytes());
-        new_value.push_str(cookie.value());
-        cookie.set_value(new_value);
+        new_value.push_str(value);
+        new_value
     }
 }
 
@@ -460,6 +587,28 @@ mod tests {
         assert_eq!(handler.session_ttl, Some(Duration::from_secs(30)));
     }
 
+    #[tokio::test]
+    async fn test_session_from_secrets() {
+        use salvo_core::secret::EnvSecrets;
+
+        let provider = EnvSecrets::with_vars([
+            ("SALVO_SESSION_TEST_KEY", "secretab".repeat(8)),
+            ("SALVO_SESSION_TEST_OLD_KEY", "oldkeyab".repeat(8)),
+            ("SALVO_SESSION_TEST_SHORT_KEY", "short".to_owned()),
+        ])
+        .prefix("SALVO_SESSION_TEST_");
+        let builder = HandlerBuilder::from_secrets(MemoryStore::new(), &provider, "key", &["old-key"])
+            .await
+            .unwrap();
+        assert_eq!(builder.fallback_keys.len(), 1);
+        assert!(builder.build().is_ok());
+        assert!(
+            HandlerBuilder::from_secrets(MemoryStore::new(), &provider, "short-key", &[])
+                .await
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn test_session_login() {
         #[handler]
@@ -527,4 +676,52 @@ mod tests {
         let mut respone = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
         assert_eq!(respone.take_string().await.unwrap(), "home");
     }
+
+    #[tokio::test]
+    async fn test_session_header_transport() {
+        #[handler]
+        pub async fn counter(req: &mut Request, depot: &mut Depot) -> String {
+            let session = depot.session_mut().unwrap();
+            let count = session.get::<u32>("count").unwrap_or_default() + 1;
+            session.insert("count", count).unwrap();
+            if req.query::<bool>("rotate").unwrap_or_default() {
+                session.regenerate();
+            }
+            count.to_string()
+        }
+
+        let session_handler = SessionHandler::builder(
+            MemoryStore::new(),
+            b"secretabsecretabsecretabsecretabsecretabsecretabsecretabsecretab",
+        )
+        .id_transport(IdTransport::HeaderThenCookie)
+        .header_name("x-token")
+        .build()
+        .unwrap();
+        let service = Service::new(Router::new().hoop(session_handler).get(counter));
+
+        let mut respone = TestClient::get("http://127.0.0.1:5800/").send(&service).await;
+        assert_eq!(respone.take_string().await.unwrap(), "1");
+        assert!(respone.headers().get(SET_COOKIE).is_some());
+        let token = respone.headers().get("x-token").unwrap().clone();
+
+        let mut respone = TestClient::get("http://127.0.0.1:5800/?rotate=true")
+            .add_header("x-token", token.clone(), true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "2");
+        let rotated = respone.headers().get("x-token").unwrap().clone();
+        assert_ne!(rotated, token);
+
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header("x-token", token, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "1");
+        let mut respone = TestClient::get("http://127.0.0.1:5800/")
+            .add_header("x-token", rotated, true)
+            .send(&service)
+            .await;
+        assert_eq!(respone.take_string().await.unwrap(), "3");
+    }
 }
@@ -0,0 +1,594 @@
+//! Outbound webhooks for Savlo web framework.
+//!
+//! [`Webhooks`] keeps registered [`Endpoint`]s in a [`WebhookStore`]. Each event enqueued with
+//! [`Webhooks::enqueue`] creates a [`Delivery`] for every endpoint subscribed to it, which is posted to the
+//! endpoint by the dispatcher task spawned with [`Webhooks::spawn`].
+//!
+//! Requests are signed following the [Standard Webhooks](https://www.standardwebhooks.com) specification, with the
+//! `webhook-id`, `webhook-timestamp` and `webhook-signature` headers. Receivers can check them with [`verify`].
+//! Secrets in the format of the specification, `whsec_` followed by the base64 encoded key, are decoded, other
+//! secrets are used as they are.
+//!
+//! A delivery succeeds with a `2xx` response. Otherwise it is retried with exponential backoff, and marked as
+//! failed after the max attempts. [`Webhooks::inspector`] creates a handler listing the latest deliveries.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use salvo_core::prelude::*;
+//! use salvo_proxy::client::HttpClient;
+//! use salvo_webhook::{Endpoint, MemoryStore, Webhooks};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
+//!     let server = Server::new(acceptor);
+//!     let webhooks = Webhooks::new(MemoryStore::new(), HttpClient::default());
+//!     webhooks.spawn(server.tasks());
+//!
+//!     webhooks
+//!         .register(Endpoint::new("crm", "https://crm.example.com/hooks", "secret").event("user.created"))
+//!         .await
+//!         .unwrap();
+//!     webhooks
+//!         .enqueue("user.created", &serde_json::json!({"id": 1}))
+//!         .await
+//!         .unwrap();
+//!
+//!     let router = Router::with_path("webhooks/deliveries").get(webhooks.inspector());
+//!     server.serve(router).await;
+//! }
+//! ```
+//!
+//! Read more: <https://salvo.rs>
+#![doc(html_favicon_url = "https://salvo.rs/favicon-32x32.png")]
+#![doc(html_logo_url = "https://salvo.rs/images/logo.svg")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures_util::future::join_all;
+use hmac::{Hmac, Mac};
+use salvo_core::http::header::{CONTENT_TYPE, USER_AGENT};
+use salvo_core::http::{Method, ReqBody, StatusCode, StatusError};
+use salvo_core::tasks::Tasks;
+use salvo_core::writing::Json;
+use salvo_core::{async_trait, hyper, Depot, Error, FlowCtrl, Handler, Request, Response};
+use salvo_proxy::client::HttpClient;
+use serde::Serialize;
+use sha2::Sha256;
+use ulid::Ulid;
+
+pub mod store;
+pub use store::{Delivery, DeliveryStatus, Endpoint, MemoryStore, WebhookStore};
+
+/// Header of the delivery id.
+pub const WEBHOOK_ID: &str = "webhook-id";
+/// Header of the unix timestamp in seconds when the request is sent.
+pub const WEBHOOK_TIMESTAMP: &str = "webhook-timestamp";
+/// Header of the signature.
+pub const WEBHOOK_SIGNATURE: &str = "webhook-signature";
+/// Tolerance of the timestamp of received requests recommended by the specification.
+pub const DEFAULT_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+/// Prefix of the secrets in the format of the specification.
+pub const SECRET_PREFIX: &str = "whsec_";
+
+/// Sign a request, returns the value of the `webhook-signature` header.
+///
+/// A secret prefixed with [`SECRET_PREFIX`] is decoded from base64, otherwise its bytes are used as the key.
+pub fn sign(secret: &str, id: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(&secret_key(secret)).expect("HMAC can take key of any size");
+    mac.update(id.as_bytes());
+    mac.update(b".");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("v1,{}", STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+/// Verify the `webhook-signature` header of a received request.
+///
+/// The header may contain several space separated signatures, it is valid if any of them matches. Requests whose
+/// timestamp differs from the current time by more than `tolerance` are rejected, so a captured request can not be
+/// replayed later, [`DEFAULT_TOLERANCE`] is a good default.
+pub fn verify(secret: &str, id: &str, timestamp: u64, body: &[u8], signature: &str, tolerance: Duration) -> bool {
+    if (now_millis() / 1000).abs_diff(timestamp) > tolerance.as_secs() {
+        return false;
+    }
+    let expected = sign(secret, id, timestamp, body);
+    signature.split_whitespace().any(|sig| {
+        sig.len() == expected.len() && sig.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+    })
+}
+
+/// Outbound webhooks.
+///
+/// Cloning is cheap, all clones share the same store.
+pub struct Webhooks<S> {
+    store: Arc<S>,
+    client: HttpClient,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    batch_size: usize,
+    poll_interval: Duration,
+}
+impl<S> Clone for Webhooks<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            client: self.client.clone(),
+            max_attempts: self.max_attempts,
+            initial_backoff: self.initial_backoff,
+            max_backoff: self.max_backoff,
+            batch_size: self.batch_size,
+            poll_interval: self.poll_interval,
+        }
+    }
+}
+impl<S> Debug for Webhooks<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Webhooks")
+            .field("client", &self.client)
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("batch_size", &self.batch_size)
+            .field("poll_interval", &self.poll_interval)
+            .finish()
+    }
+}
+impl<S: WebhookStore> Webhooks<S> {
+    /// Create new `Webhooks`.
+    ///
+    /// Timeouts of the requests are set with [`HttpClient::timeout`].
+    #[inline]
+    pub fn new(store: S, client: HttpClient) -> Self {
+        Self {
+            store: Arc::new(store),
+            client,
+            max_attempts: 8,
+            initial_backoff: Duration::from_secs(10),
+            max_backoff: Duration::from_secs(3600),
+            batch_size: 32,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Set the max attempts of a delivery, default is 8.
+    #[inline]
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry and the max delay, default are 10 seconds and 1 hour.
+    ///
+    /// The delay doubles after each failed attempt.
+    #[inline]
+    pub fn retry_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Set how many deliveries are attempted concurrently by a dispatch, default is 32.
+    #[inline]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set how often the dispatcher task looks for due deliveries, default is 1 second.
+    #[inline]
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Get the store.
+    #[inline]
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Register an endpoint, an endpoint with the same id is replaced.
+    #[inline]
+    pub async fn register(&self, endpoint: Endpoint) -> Result<(), Error> {
+        self.store.save_endpoint(endpoint).await
+    }
+
+    /// Unregister an endpoint, its pending deliveries fail on their next attempt.
+    #[inline]
+    pub async fn unregister(&self, id: &str) -> Result<bool, Error> {
+        self.store.remove_endpoint(id).await
+    }
+
+    /// Enqueue an event for every endpoint subscribed to it, returns the ids of the deliveries.
+    pub async fn enqueue(&self, event: &str, payload: &(impl Serialize + Sync)) -> Result<Vec<String>, Error> {
+        let payload = serde_json::to_string(payload).map_err(Error::other)?;
+        let now = now_millis();
+        let mut ids = Vec::new();
+        for endpoint in self.store.endpoints().await? {
+            if !endpoint.accepts(event) {
+                continue;
+            }
+            let delivery = Delivery {
+                id: format!("msg_{}", Ulid::new()),
+                endpoint_id: endpoint.id,
+                event: event.to_owned(),
+                payload: payload.clone(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                created_at: now,
+                next_attempt_at: now,
+                last_status_code: None,
+                last_error: None,
+            };
+            ids.push(delivery.id.clone());
+            self.store.save_delivery(delivery).await?;
+        }
+        Ok(ids)
+    }
+
+    /// Attempt due deliveries once, returns how many were attempted.
+    pub async fn dispatch(&self) -> Result<usize, Error> {
+        let deliveries = self.store.due_deliveries(now_millis(), self.batch_size).await?;
+        if deliveries.is_empty() {
+            return Ok(0);
+        }
+        let endpoints = self
+            .store
+            .endpoints()
+            .await?
+            .into_iter()
+            .map(|endpoint| (endpoint.id.clone(), endpoint))
+            .collect::<HashMap<_, _>>();
+        let count = deliveries.len();
+        let results = join_all(deliveries.into_iter().map(|delivery| {
+            let endpoint = endpoints.get(&delivery.endpoint_id);
+            self.attempt(endpoint, delivery)
+        }))
+        .await;
+        for result in results {
+            if let Err(e) = result {
+                tracing::error!(error = ?e, "save webhook delivery failed");
+            }
+        }
+        Ok(count)
+    }
+
+    async fn attempt(&self, endpoint: Option<&Endpoint>, mut delivery: Delivery) -> Result<(), Error> {
+        delivery.attempts += 1;
+        let result = match endpoint {
+            Some(endpoint) => self.send(endpoint, &delivery).await,
+            None => Err(Error::other("endpoint is unregistered")),
+        };
+        match result {
+            Ok(status) => {
+                delivery.last_status_code = Some(status.as_u16());
+                delivery.last_error = None;
+            }
+            Err(e) => {
+                delivery.last_status_code = None;
+                delivery.last_error = Some(e.to_string());
+            }
+        }
+        let succeeded = delivery
+            .last_status_code
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .map(|status| status.is_success())
+            .unwrap_or(false);
+        if succeeded {
+            delivery.status = DeliveryStatus::Succeeded;
+        } else if endpoint.is_none() || delivery.attempts >= self.max_attempts {
+            tracing::warn!(delivery = %delivery.id, attempts = delivery.attempts, "webhook delivery failed");
+            delivery.status = DeliveryStatus::Failed;
+        } else {
+            let backoff = self
+                .initial_backoff
+                .saturating_mul(2u32.saturating_pow(delivery.attempts - 1))
+                .min(self.max_backoff);
+            delivery.next_attempt_at = now_millis() + backoff.as_millis() as u64;
+        }
+        self.store.save_delivery(delivery).await
+    }
+
+    async fn send(&self, endpoint: &Endpoint, delivery: &Delivery) -> Result<StatusCode, Error> {
+        let timestamp = now_millis() / 1000;
+        let signature = sign(&endpoint.secret, &delivery.id, timestamp, delivery.payload.as_bytes());
+        let req = hyper::Request::builder()
+            .method(Method::POST)
+            .uri(&endpoint.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(USER_AGENT, "salvo-webhook")
+            .header(WEBHOOK_ID, &delivery.id)
+            .header(WEBHOOK_TIMESTAMP, timestamp)
+            .header(WEBHOOK_SIGNATURE, signature)
+            .body(ReqBody::from(delivery.payload.clone()))
+            .map_err(Error::other)?;
+        Ok(self.client.send(req).await?.status())
+    }
+
+    /// Spawn the dispatcher as a periodic background task named `webhooks`.
+    pub fn spawn(&self, tasks: &Tasks) {
+        let webhooks = self.clone();
+        tasks.spawn_periodic("webhooks", self.poll_interval, move || {
+            let webhooks = webhooks.clone();
+            async move {
+                // Keep dispatching while there is a full batch of due deliveries.
+                loop {
+                    match webhooks.dispatch().await {
+                        Ok(count) if count >= webhooks.batch_size => continue,
+                        Ok(_) => break,
+                        Err(e) => {
+                            tracing::error!(error = ?e, "dispatch webhooks failed");
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Create a handler which lists the latest deliveries as JSON.
+    ///
+    /// The `status` query parameter filters deliveries by status, and `limit` sets how many are listed, default is
+    /// 100.
+    #[inline]
+    pub fn inspector(&self) -> WebhookInspector<S> {
+        WebhookInspector { webhooks: self.clone() }
+    }
+}
+
+fn secret_key(secret: &str) -> Cow<'_, [u8]> {
+    match secret.strip_prefix(SECRET_PREFIX).map(|key| STANDARD.decode(key)) {
+        Some(Ok(key)) => Cow::Owned(key),
+        Some(Err(e)) => {
+            tracing::warn!(error = ?e, "webhook secret is not valid base64, it is used as it is");
+            Cow::Borrowed(secret.as_bytes())
+        }
+        None => Cow::Borrowed(secret.as_bytes()),
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Handler which lists the latest deliveries of [`Webhooks`] as JSON.
+#[derive(Clone, Debug)]
+pub struct WebhookInspector<S> {
+    webhooks: Webhooks<S>,
+}
+#[async_trait]
+impl<S: WebhookStore> Handler for WebhookInspector<S> {
+    async fn handle(&self, req: &mut Request, _depot: &mut Depot, res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let status = match req.query::<&str>("status") {
+            None => None,
+            Some("pending") => Some(DeliveryStatus::Pending),
+            Some("succeeded") => Some(DeliveryStatus::Succeeded),
+            Some("failed") => Some(DeliveryStatus::Failed),
+            Some(_) => {
+                res.render(StatusError::bad_request().brief("Invalid delivery status."));
+                return;
+            }
+        };
+        let limit = req.query::<usize>("limit").unwrap_or(100);
+        match self.webhooks.store.deliveries(status, limit).await {
+            Ok(deliveries) => res.render(Json(deliveries)),
+            Err(e) => {
+                tracing::error!(error = ?e, "list webhook deliveries failed");
+                res.render(StatusError::internal_server_error());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parking_lot::Mutex;
+    use salvo_core::http::ResBody;
+    use salvo_core::prelude::*;
+    use salvo_core::test::{ResponseExt, TestClient};
+    use salvo_proxy::client::MockClient;
+
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let now = now_millis() / 1000;
+        let signature = sign("secret", "msg_1", now, b"{}");
+        assert!(signature.starts_with("v1,"));
+        assert!(verify("secret", "msg_1", now, b"{}", &signature, DEFAULT_TOLERANCE));
+        assert!(verify(
+            "secret",
+            "msg_1",
+            now,
+            b"{}",
+            &format!("v1,old {signature}"),
+            DEFAULT_TOLERANCE
+        ));
+        assert!(!verify("other", "msg_1", now, b"{}", &signature, DEFAULT_TOLERANCE));
+        assert!(!verify(
+            "secret",
+            "msg_1",
+            now + 1,
+            b"{}",
+            &signature,
+            DEFAULT_TOLERANCE
+        ));
+
+        let stale = now - 600;
+        let signature = sign("secret", "msg_1", stale, b"{}");
+        assert!(!verify("secret", "msg_1", stale, b"{}", &signature, DEFAULT_TOLERANCE));
+        assert!(verify(
+            "secret",
+            "msg_1",
+            stale,
+            b"{}",
+            &signature,
+            Duration::from_secs(3600)
+        ));
+    }
+
+    #[test]
+    fn test_sign_standard_secret() {
+        // Example of the Standard Webhooks specification.
+        let signature = sign(
+            "whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw",
+            "msg_p5jXN8AQM9LWM0D4loKWxJek",
+            1614265330,
+            br#"{"test": 2432232314}"#,
+        );
+        assert_eq!(signature, "v1,g0hM9SsE+OTPJTGt/tmIKtSyZlE3uFJELVlNIOLJ1OE=");
+        assert_ne!(
+            sign("MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw", "msg_1", 0, b"{}"),
+            sign("whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw", "msg_1", 0, b"{}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deliver_with_retry() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received2 = received.clone();
+        let client = MockClient::new(move |req: hyper::Request<ReqBody>| {
+            let mut received = received2.lock();
+            let header = |name| req.headers().get(name).unwrap().to_str().unwrap().to_owned();
+            received.push((header(WEBHOOK_ID), header(WEBHOOK_TIMESTAMP), header(WEBHOOK_SIGNATURE)));
+            let status = if received.len() == 1 {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::NO_CONTENT
+            };
+            hyper::Response::builder().status(status).body(ResBody::None).unwrap()
+        });
+        let webhooks =
+            Webhooks::new(MemoryStore::new(), HttpClient::new(client)).retry_backoff(Duration::ZERO, Duration::ZERO);
+        webhooks
+            .register(Endpoint::new("a", "http://a.example.com/hooks", "secret").event("user.created"))
+            .await
+            .unwrap();
+        webhooks
+            .register(Endpoint::new("b", "http://b.example.com/hooks", "secret").event("user.deleted"))
+            .await
+            .unwrap();
+
+        let ids = webhooks.enqueue("user.created", &[1, 2]).await.unwrap();
+        assert_eq!(ids.len(), 1);
+
+        assert_eq!(webhooks.dispatch().await.unwrap(), 1);
+        let pending = webhooks
+            .store()
+            .deliveries(Some(DeliveryStatus::Pending), 10)
+            .await
+            .unwrap();
+        assert_eq!(pending[0].attempts, 1);
+        assert_eq!(pending[0].last_status_code, Some(500));
+
+        assert_eq!(webhooks.dispatch().await.unwrap(), 1);
+        assert_eq!(webhooks.dispatch().await.unwrap(), 0);
+        let succeeded = webhooks
+            .store()
+            .deliveries(Some(DeliveryStatus::Succeeded), 10)
+            .await
+            .unwrap();
+        assert_eq!(succeeded[0].attempts, 2);
+
+        let (id, timestamp, signature) = received.lock()[1].clone();
+        assert_eq!(id, ids[0]);
+        assert!(verify(
+            "secret",
+            &id,
+            timestamp.parse().unwrap(),
+            b"[1,2]",
+            &signature,
+            DEFAULT_TOLERANCE
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_retention() {
+        let store = MemoryStore::new().retention(Duration::from_secs(60));
+        let delivery = |id: &str, status, created_at| Delivery {
+            id: id.to_owned(),
+            endpoint_id: "a".to_owned(),
+            event: "user.created".to_owned(),
+            payload: "{}".to_owned(),
+            status,
+            attempts: 1,
+            created_at,
+            next_attempt_at: u64::MAX,
+            last_status_code: None,
+            last_error: None,
+        };
+        store
+            .save_delivery(delivery("1", DeliveryStatus::Succeeded, 0))
+            .await
+            .unwrap();
+        store
+            .save_delivery(delivery("2", DeliveryStatus::Failed, 0))
+            .await
+            .unwrap();
+        store
+            .save_delivery(delivery("3", DeliveryStatus::Pending, 0))
+            .await
+            .unwrap();
+        store
+            .save_delivery(delivery("4", DeliveryStatus::Succeeded, 30_000))
+            .await
+            .unwrap();
+
+        assert!(store.due_deliveries(80_000, 10).await.unwrap().is_empty());
+        let ids = store
+            .deliveries(None, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|delivery| delivery.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["4", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_inspector() {
+        let client = MockClient::new(|_| {
+            hyper::Response::builder()
+                .status(StatusCode::BAD_GATEWAY)
+                .body(ResBody::None)
+                .unwrap()
+        });
+        let webhooks = Webhooks::new(MemoryStore::new(), HttpClient::new(client)).max_attempts(1);
+        webhooks
+            .register(Endpoint::new("a", "http://a.example.com/hooks", "secret"))
+            .await
+            .unwrap();
+        webhooks.enqueue("user.created", &"hello").await.unwrap();
+        webhooks.dispatch().await.unwrap();
+
+        let service = Service::new(Router::new().get(webhooks.inspector()));
+        let content = TestClient::get("http://127.0.0.1:5800/?status=failed")
+            .send(&service)
+            .await
+            .take_string()
+            .await
+            .unwrap();
+        assert!(content.contains(r#""status":"failed","attempts":1"#));
+        assert!(content.contains(r#""last_status_code":502"#));
+
+        let res = TestClient::get("http://127.0.0.1:5800/?status=unknown")
+            .send(&service)
+            .await;
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+    }
+}
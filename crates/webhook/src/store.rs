@@ -0,0 +1,186 @@
+//! Persistence of webhook endpoints and deliveries.
+use std::future::Future;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use salvo_core::Error;
+use serde::{Deserialize, Serialize};
+
+/// A registered webhook endpoint.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Endpoint {
+    /// Id of the endpoint.
+    pub id: String,
+    /// URL the events are posted to.
+    pub url: String,
+    /// Secret to sign the requests with.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Kinds of events the endpoint subscribes to, all events if it is empty.
+    pub events: Vec<String>,
+}
+impl Endpoint {
+    /// Create new `Endpoint` subscribed to all events.
+    #[inline]
+    pub fn new(id: impl Into<String>, url: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            url: url.into(),
+            secret: secret.into(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Subscribe to a kind of event.
+    #[inline]
+    pub fn event(mut self, kind: impl Into<String>) -> Self {
+        self.events.push(kind.into());
+        self
+    }
+
+    /// Whether the endpoint subscribes to the kind of event.
+    #[inline]
+    pub fn accepts(&self, kind: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|event| event == kind)
+    }
+}
+
+/// Status of a delivery.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Waiting for the next attempt.
+    Pending,
+    /// Delivered with a `2xx` response.
+    Succeeded,
+    /// All attempts failed.
+    Failed,
+}
+
+/// Delivery of an event to an endpoint.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Delivery {
+    /// Id of the delivery, sent as the `webhook-id` header.
+    pub id: String,
+    /// Id of the endpoint.
+    pub endpoint_id: String,
+    /// Kind of the event.
+    pub event: String,
+    /// JSON payload of the event.
+    pub payload: String,
+    /// Status of the delivery.
+    pub status: DeliveryStatus,
+    /// How many attempts were made.
+    pub attempts: u32,
+    /// When the event was enqueued, in milliseconds since unix epoch.
+    pub created_at: u64,
+    /// When the next attempt is due, in milliseconds since unix epoch.
+    pub next_attempt_at: u64,
+    /// Response status code of the last attempt.
+    pub last_status_code: Option<u16>,
+    /// Error of the last attempt.
+    pub last_error: Option<String>,
+}
+
+/// Storage of webhook endpoints and deliveries.
+pub trait WebhookStore: Send + Sync + 'static {
+    /// Insert or update an endpoint.
+    fn save_endpoint(&self, endpoint: Endpoint) -> impl Future<Output = Result<(), Error>> + Send;
+
+    /// Remove an endpoint, returns `false` if it does not exist.
+    fn remove_endpoint(&self, id: &str) -> impl Future<Output = Result<bool, Error>> + Send;
+
+    /// Get all endpoints.
+    fn endpoints(&self) -> impl Future<Output = Result<Vec<Endpoint>, Error>> + Send;
+
+    /// Insert or update a delivery.
+    fn save_delivery(&self, delivery: Delivery) -> impl Future<Output = Result<(), Error>> + Send;
+
+    /// Get pending deliveries whose next attempt is due at `now`, oldest first.
+    fn due_deliveries(&self, now: u64, limit: usize) -> impl Future<Output = Result<Vec<Delivery>, Error>> + Send;
+
+    /// Get the latest deliveries, optionally with a status, newest first.
+    fn deliveries(
+        &self,
+        status: Option<DeliveryStatus>,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<Delivery>, Error>> + Send;
+}
+
+/// In-memory [`WebhookStore`], deliveries are lost when the process exits.
+///
+/// Succeeded and failed deliveries are removed after the [retention](MemoryStore::retention), when the dispatcher
+/// looks for due deliveries. Pending deliveries are always kept.
+#[derive(Debug)]
+pub struct MemoryStore {
+    endpoints: Mutex<IndexMap<String, Endpoint>>,
+    deliveries: Mutex<IndexMap<String, Delivery>>,
+    retention: Duration,
+}
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self {
+            endpoints: Mutex::default(),
+            deliveries: Mutex::default(),
+            retention: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+impl MemoryStore {
+    /// Create new `MemoryStore`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long finished deliveries are kept after they are enqueued, default is 24 hours.
+    #[inline]
+    pub fn retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+}
+impl WebhookStore for MemoryStore {
+    async fn save_endpoint(&self, endpoint: Endpoint) -> Result<(), Error> {
+        self.endpoints.lock().insert(endpoint.id.clone(), endpoint);
+        Ok(())
+    }
+
+    async fn remove_endpoint(&self, id: &str) -> Result<bool, Error> {
+        Ok(self.endpoints.lock().shift_remove(id).is_some())
+    }
+
+    async fn endpoints(&self) -> Result<Vec<Endpoint>, Error> {
+        Ok(self.endpoints.lock().values().cloned().collect())
+    }
+
+    async fn save_delivery(&self, delivery: Delivery) -> Result<(), Error> {
+        self.deliveries.lock().insert(delivery.id.clone(), delivery);
+        Ok(())
+    }
+
+    async fn due_deliveries(&self, now: u64, limit: usize) -> Result<Vec<Delivery>, Error> {
+        let mut deliveries = self.deliveries.lock();
+        let expired_at = now.saturating_sub(self.retention.as_millis() as u64);
+        deliveries.retain(|_, delivery| delivery.status == DeliveryStatus::Pending || delivery.created_at > expired_at);
+        Ok(deliveries
+            .values()
+            .filter(|delivery| delivery.status == DeliveryStatus::Pending && delivery.next_attempt_at <= now)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn deliveries(&self, status: Option<DeliveryStatus>, limit: usize) -> Result<Vec<Delivery>, Error> {
+        Ok(self
+            .deliveries
+            .lock()
+            .values()
+            .rev()
+            .filter(|delivery| status.is_none() || status == Some(delivery.status))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
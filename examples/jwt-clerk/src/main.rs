@@ -25,7 +25,10 @@ async fn main() {
     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
     let router = Router::new()
         .push(Router::with_hoop(auth_handler).path("welcome").get(welcome))
-        .push(Router::with_path("<**rest>").goal(Proxy::new(vec!["http://localhost:5801"], HyperClient::default())));
+        .push(Router::with_path("<**rest>").goal(Proxy::new(
+            vec!["http://localhost:5801"],
+            HyperClient::builder().allow_http(true).build().unwrap(),
+        )));
     Server::new(acceptor).serve(router).await;
 }
 #[handler]
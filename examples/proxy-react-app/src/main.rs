@@ -5,7 +5,10 @@ use salvo::proxy::HyperClient;
 async fn main() {
     tracing_subscriber::fmt().init();
 
-    let router = Router::with_path("<**rest>").goal(Proxy::new(vec!["http://localhost:3000"], HyperClient::default()));
+    let router = Router::with_path("<**rest>").goal(Proxy::new(
+        vec!["http://localhost:3000"],
+        HyperClient::builder().allow_http(true).build().unwrap(),
+    ));
     println!("{:?}", router);
 
     let acceptor = TcpListener::new("0.0.0.0:5800").bind().await;
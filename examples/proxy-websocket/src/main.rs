@@ -5,7 +5,10 @@ use salvo::proxy::HyperClient;
 async fn main() {
     tracing_subscriber::fmt().init();
 
-    let router = Router::with_path("<**rest>").goal(Proxy::new(vec!["http://localhost:5800"], HyperClient::default()));
+    let router = Router::with_path("<**rest>").goal(Proxy::new(
+        vec!["http://localhost:5800"],
+        HyperClient::builder().allow_http(true).build().unwrap(),
+    ));
     println!("{:?}", router);
     tracing::info!("Run `cargo run --bin example-websocket-chat` to start websocket chat server");
     let acceptor = TcpListener::new("0.0.0.0:8888").bind().await;